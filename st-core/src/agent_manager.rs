@@ -1,12 +1,18 @@
 use crate::agent::run_agent;
+use crate::behavior_tree::behavior_definitions::ShipBehaviorStore;
+use crate::competitor_tracker::CompetitorTracker;
 use crate::configuration::AgentConfiguration;
+use crate::data_retention::DataRetention;
+use crate::galaxy_crawler::GalaxyCrawler;
 use crate::reqwest_helpers::{create_client, ResetSignal};
 use crate::st_client::{StClient, StClientTrait};
+use crate::strategy::StrategyStore;
 use crate::transfer_cargo_manager::TransferCargoManager;
 use crate::universe_server::universe_server::{InMemoryUniverse, InMemoryUniverseClient};
 use anyhow::Result;
+use itertools::Itertools;
 use sqlx::{Pool, Postgres};
-use st_domain::{FactionSymbol, RegistrationRequest};
+use st_domain::{AgentSymbol, FactionSymbol, RegistrationRequest};
 use st_store::bmc::jump_gate_bmc::InMemoryJumpGateBmc;
 use st_store::bmc::ship_bmc::{InMemoryShips, InMemoryShipsBmc};
 use st_store::bmc::{Bmc, DbBmc, InMemoryBmc};
@@ -17,31 +23,67 @@ use st_store::{
     db, DbModelManager, InMemoryAgentBmc, InMemoryConstructionBmc, InMemoryFleetBmc, InMemoryMarketBmc, InMemoryStatusBmc, InMemorySupplyChainBmc,
     InMemorySystemsBmc,
 };
+use std::num::NonZeroU32;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::watch::Receiver;
 use tokio::sync::{mpsc, watch};
 use tokio::task::JoinHandle;
+use tokio_cron_scheduler::JobScheduler;
 use tracing::{event, Level};
 
 pub struct AgentManager {
     reset_rx: mpsc::Receiver<ResetSignal>,
-    cfg: AgentConfiguration,
-    current_agent_handle: Option<JoinHandle<()>>,
-    bmc: Option<Arc<dyn Bmc>>,
+    /// the first configuration is the main agent that also runs the jobs maintaining the shared universe data
+    cfgs: Vec<AgentConfiguration>,
+    running_agents: Vec<RunningAgent>,
+}
+
+struct RunningAgent {
+    agent_symbol: String,
+    handle: JoinHandle<()>,
+    galaxy_crawler_scheduler: Option<JobScheduler>,
+    data_retention_scheduler: Option<JobScheduler>,
+    competitor_tracker_scheduler: Option<JobScheduler>,
+}
+
+impl RunningAgent {
+    async fn shutdown(mut self) {
+        if let Some(mut scheduler) = self.galaxy_crawler_scheduler.take() {
+            if let Err(e) = scheduler.shutdown().await {
+                event!(Level::WARN, "Failed to shut down galaxy crawler: {}", e);
+            }
+        }
+
+        if let Some(mut scheduler) = self.data_retention_scheduler.take() {
+            if let Err(e) = scheduler.shutdown().await {
+                event!(Level::WARN, "Failed to shut down data retention: {}", e);
+            }
+        }
+
+        if let Some(mut scheduler) = self.competitor_tracker_scheduler.take() {
+            if let Err(e) = scheduler.shutdown().await {
+                event!(Level::WARN, "Failed to shut down competitor tracker: {}", e);
+            }
+        }
+
+        // If it's still running, abort it
+        self.handle.abort();
+        event!(Level::INFO, "Agent {} stopped", self.agent_symbol);
+    }
 }
 
 impl AgentManager {
-    pub fn new(cfg: AgentConfiguration) -> (Self, mpsc::Sender<ResetSignal>) {
+    pub fn new(cfgs: Vec<AgentConfiguration>) -> (Self, mpsc::Sender<ResetSignal>) {
+        assert!(!cfgs.is_empty(), "AgentManager needs at least one agent configuration");
         let (reset_tx, reset_rx) = mpsc::channel::<ResetSignal>(8);
 
         (
             Self {
                 reset_rx,
-                cfg,
-                current_agent_handle: None,
-                bmc: None,
+                cfgs,
+                running_agents: Vec::new(),
             },
             reset_tx,
         )
@@ -49,24 +91,18 @@ impl AgentManager {
 
     pub async fn run(&mut self) -> Result<()> {
         loop {
-            // Create a shutdown channel for this agent instance
+            // Create a shutdown channel for this generation of agents
             let (shutdown_tx, shutdown_rx) = watch::channel(false);
 
-            let either_handle = if self.cfg.use_in_memory_agent {
-                self.initialize_and_start_in_memory_agent(shutdown_rx.clone())
-                    .await
-            } else {
-                self.initialize_and_start_db_agent(shutdown_rx.clone())
-                    .await
-            };
-            // Initialize the environment and start a new agent
-            match either_handle {
-                Ok(agent_handle) => {
-                    self.current_agent_handle = Some(agent_handle);
-                    event!(Level::INFO, "Agent started successfully");
+            // Initialize the environment and start the agents
+            match self.start_agents(shutdown_rx).await {
+                Ok(()) => {
+                    event!(Level::INFO, "{} agent(s) started successfully", self.running_agents.len());
                 }
                 Err(e) => {
-                    event!(Level::ERROR, "Failed to start agent: {}", e);
+                    event!(Level::ERROR, "Failed to start agents: {}", e);
+                    let _ = shutdown_tx.send(true);
+                    self.shutdown_agents().await;
                     // Wait a bit before trying again
                     tokio::time::sleep(Duration::from_secs(5)).await;
                     continue;
@@ -75,21 +111,13 @@ impl AgentManager {
 
             // Wait for a reset signal
             if let Some(signal) = self.reset_rx.recv().await {
-                event!(Level::INFO, "Reset signal received: {:?}, restarting agent", signal);
+                event!(Level::INFO, "Reset signal received: {:?}, restarting agents", signal);
 
-                // Signal the current agent to shut down
+                // Signal the current agents to shut down
                 let _ = shutdown_tx.send(true);
+                self.shutdown_agents().await;
 
-                // If we have a handle to the current agent, wait for it to shut down
-                if let Some(handle) = self.current_agent_handle.take() {
-                    // Give the agent a chance to shut down gracefully
-                    tokio::time::sleep(Duration::from_secs(1)).await;
-
-                    // If it's still running, abort it
-                    handle.abort();
-                }
-
-                // Wait a bit before starting a new agent
+                // Wait a bit before starting new agents
                 tokio::time::sleep(Duration::from_secs(2)).await;
             } else {
                 // Channel closed, exit the loop
@@ -100,7 +128,42 @@ impl AgentManager {
         Ok(())
     }
 
-    async fn initialize_and_start_in_memory_agent(&mut self, shutdown_rx: watch::Receiver<bool>) -> Result<JoinHandle<()>> {
+    /// Starts the agents one after another, so only the first one prepares (and possibly archives) the database schema of a new reset.
+    async fn start_agents(&mut self, shutdown_rx: watch::Receiver<bool>) -> Result<()> {
+        let configured_agent_symbols = self
+            .cfgs
+            .iter()
+            .map(|cfg| AgentSymbol(cfg.spacetraders_agent_symbol.clone()))
+            .collect_vec();
+
+        for (idx, cfg) in self.cfgs.clone().iter().enumerate() {
+            let is_main_agent = idx == 0;
+            let running_agent = if cfg.use_in_memory_agent {
+                Self::initialize_and_start_in_memory_agent(cfg, shutdown_rx.clone()).await?
+            } else {
+                Self::initialize_and_start_db_agent(cfg, is_main_agent, &configured_agent_symbols, shutdown_rx.clone()).await?
+            };
+            event!(Level::INFO, "Agent {} started", running_agent.agent_symbol);
+            self.running_agents.push(running_agent);
+        }
+
+        Ok(())
+    }
+
+    async fn shutdown_agents(&mut self) {
+        if self.running_agents.is_empty() {
+            return;
+        }
+
+        // Give the agents a chance to shut down gracefully
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        for running_agent in self.running_agents.drain(..) {
+            running_agent.shutdown().await;
+        }
+    }
+
+    async fn initialize_and_start_in_memory_agent(cfg: &AgentConfiguration, shutdown_rx: watch::Receiver<bool>) -> Result<RunningAgent> {
         let manifest_dir = env!("CARGO_MANIFEST_DIR");
 
         let json_path = std::path::Path::new(manifest_dir)
@@ -115,6 +178,7 @@ impl AgentManager {
         let client = Arc::new(in_memory_client) as Arc<dyn StClientTrait>;
 
         let agent = client.get_agent().await?.data;
+        let agent_symbol = agent.symbol.0.clone();
 
         let ship_bmc = InMemoryShipsBmc::new(InMemoryShips::new());
         let agent_bmc = InMemoryAgentBmc::new(agent);
@@ -150,17 +214,24 @@ impl AgentManager {
             in_mem_status_bmc: Arc::new(status_bmc),
             in_mem_ledger_bmc: Arc::new(Default::default()),
             in_mem_contract_bmc: Arc::new(Default::default()),
+            in_mem_cargo_flow_bmc: Arc::new(Default::default()),
         };
 
         let bmc = Arc::new(bmc) as Arc<dyn Bmc>;
-        let transfer_cargo_manager = Arc::new(TransferCargoManager::new());
-
-        self.bmc = Some(bmc.clone());
+        let strategy_store = StrategyStore::new(cfg.strategy_file.clone())?;
+        let transfer_cargo_manager = Arc::new(TransferCargoManager::new(strategy_store.clone()));
+        let ship_behavior_store = ShipBehaviorStore::new(cfg.behavior_definition_dir.clone())?;
 
         // Spawn the agent task
-        let handle = Self::spawn_and_get_handle(shutdown_rx, client, bmc, transfer_cargo_manager);
-
-        Ok(handle)
+        let handle = Self::spawn_and_get_handle(shutdown_rx, client, bmc, transfer_cargo_manager, ship_behavior_store, strategy_store);
+
+        Ok(RunningAgent {
+            agent_symbol,
+            handle,
+            galaxy_crawler_scheduler: None,
+            data_retention_scheduler: None,
+            competitor_tracker_scheduler: None,
+        })
     }
 
     fn spawn_and_get_handle(
@@ -168,11 +239,13 @@ impl AgentManager {
         client: Arc<dyn StClientTrait>,
         bmc: Arc<dyn Bmc>,
         transfer_cargo_manager: Arc<TransferCargoManager>,
+        ship_behavior_store: ShipBehaviorStore,
+        strategy_store: StrategyStore,
     ) -> JoinHandle<()> {
         tokio::spawn(async move {
             // Run agent with the authenticated client
             let agent_task = async {
-                match run_agent(client, bmc, transfer_cargo_manager).await {
+                match run_agent(client, bmc, transfer_cargo_manager, ship_behavior_store, strategy_store).await {
                     Ok(()) => event!(Level::INFO, "Agent completed successfully"),
                     Err(e) => event!(Level::ERROR, "Agent error: {}", e),
                 }
@@ -205,41 +278,79 @@ impl AgentManager {
         })
     }
 
-    async fn initialize_and_start_db_agent(&mut self, shutdown_rx: watch::Receiver<bool>) -> Result<JoinHandle<()>> {
+    /// The universe data is shared between all agents in the db, so only the main agent crawls the galaxy, runs the retention job and tracks the competitors.
+    /// The ships of agents that aren't among the `configured_agent_symbols` anymore get pruned by the retention job.
+    async fn initialize_and_start_db_agent(
+        cfg: &AgentConfiguration,
+        is_main_agent: bool,
+        configured_agent_symbols: &[AgentSymbol],
+        shutdown_rx: watch::Receiver<bool>,
+    ) -> Result<RunningAgent> {
         // Create a reset channel for this specific agent instance
         let (agent_reset_tx, _) = mpsc::channel::<ResetSignal>(8);
 
         // Create the initial client (without token) with reset detection
-        let client_with_account_token = create_client(Some(self.cfg.spacetraders_account_token.clone()), Some(agent_reset_tx.clone()));
-        let client_with_account_token = StClient::try_with_base_url(client_with_account_token, &self.cfg.spacetraders_base_url)?;
+        let client_with_account_token = create_client(Some(cfg.spacetraders_account_token.clone()), Some(agent_reset_tx.clone()));
+        let client_with_account_token = StClient::try_with_base_url(client_with_account_token, &cfg.spacetraders_base_url)?;
 
         // Get the status (this will verify the API is responding)
         let status = client_with_account_token.get_status().await?;
 
         // Initialize database pool
-        let pool = db::prepare_database_schema(&status, self.cfg.pg_connection_string()).await?;
+        let pool = db::prepare_database_schema(&status, cfg.pg_connection_string()).await?;
 
         // Get the authenticated client
-        let authenticated_client = get_authenticated_client(&self.cfg, pool.clone(), client_with_account_token).await?;
+        let authenticated_client = get_authenticated_client(cfg, pool.clone(), client_with_account_token).await?;
         let client = Arc::new(authenticated_client) as Arc<dyn StClientTrait>;
 
-        let db_mm = DbModelManager::new(pool.clone());
+        let agent_symbol = AgentSymbol(cfg.spacetraders_agent_symbol.clone());
+        let db_mm = DbModelManager::for_agent(pool.clone(), agent_symbol.clone());
         let db_bmc = DbBmc::new(db_mm);
         let bmc = Arc::new(db_bmc) as Arc<dyn Bmc>;
 
-        self.bmc = Some(bmc.clone());
-        let transfer_cargo_manager = Arc::new(TransferCargoManager::new());
+        let strategy_store = StrategyStore::new(cfg.strategy_file.clone())?;
+        let transfer_cargo_manager = Arc::new(TransferCargoManager::new(strategy_store.clone()));
+        let ship_behavior_store = ShipBehaviorStore::new(cfg.behavior_definition_dir.clone())?;
+
+        let mut galaxy_crawler_scheduler = None;
+        let mut data_retention_scheduler = None;
+        let mut competitor_tracker_scheduler = None;
 
-        let handle = Self::spawn_and_get_handle(shutdown_rx, client, bmc, transfer_cargo_manager);
+        if is_main_agent {
+            if let Some(requests_per_minute) = NonZeroU32::new(cfg.galaxy_crawler_requests_per_minute) {
+                let home_system = client.get_agent().await?.data.headquarters.system_symbol();
+                let crawler = GalaxyCrawler::new(Arc::clone(&client), Arc::clone(&bmc), pool.clone(), home_system, requests_per_minute);
+                galaxy_crawler_scheduler = Some(GalaxyCrawler::schedule(Arc::new(crawler)).await?);
+            }
+
+            if let Some(retention_hours) = NonZeroU32::new(cfg.data_retention_hours) {
+                let retention = DataRetention::new(Arc::clone(&bmc), retention_hours, configured_agent_symbols.to_vec());
+                data_retention_scheduler = Some(DataRetention::schedule(Arc::new(retention)).await?);
+            }
 
-        Ok(handle)
+            if let Some(requests_per_minute) = NonZeroU32::new(cfg.competitor_tracking_requests_per_minute) {
+                let tracker = CompetitorTracker::new(Arc::clone(&client), Arc::clone(&bmc), requests_per_minute);
+                competitor_tracker_scheduler = Some(CompetitorTracker::schedule(Arc::new(tracker)).await?);
+            }
+        }
+
+        let handle = Self::spawn_and_get_handle(shutdown_rx, client, bmc, transfer_cargo_manager, ship_behavior_store, strategy_store);
+
+        Ok(RunningAgent {
+            agent_symbol: agent_symbol.0,
+            handle,
+            galaxy_crawler_scheduler,
+            data_retention_scheduler,
+            competitor_tracker_scheduler,
+        })
     }
 }
 
 pub async fn get_authenticated_client(cfg: &AgentConfiguration, pool: Pool<Postgres>, client_with_account_token: StClient) -> Result<StClient> {
     event!(Level::INFO, "Trying to load registration from database");
 
-    let maybe_existing_registration = db::load_registration(&pool).await?;
+    let agent_symbol = AgentSymbol(cfg.spacetraders_agent_symbol.clone());
+    let maybe_existing_registration = db::load_registration(&pool, &agent_symbol).await?;
 
     match maybe_existing_registration {
         Some(db_entry) => {
@@ -269,7 +380,7 @@ pub async fn get_authenticated_client(cfg: &AgentConfiguration, pool: Pool<Postg
 
             event!(Level::INFO, "Registration complete: {:?}", registration_response);
 
-            let _ = db::save_registration(&pool, registration_response.clone()).await;
+            let _ = db::save_registration(&pool, &agent_symbol, registration_response.clone()).await;
 
             // Create a reset channel for this specific client
             let (agent_reset_tx, _) = mpsc::channel::<ResetSignal>(8);
@@ -0,0 +1,41 @@
+use anyhow::Result;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio_cron_scheduler::{Job, JobScheduler};
+use tracing::{event, Level};
+
+/// Starts a scheduler that runs `job` every `interval`.
+///
+/// A run that is still busy when the next one is due makes the next one a no-op.
+/// Errors are logged and the job is retried with the next run.
+pub async fn schedule_repeated<F, Fut>(interval: Duration, description: &'static str, job: F) -> Result<JobScheduler>
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<()>> + Send + 'static,
+{
+    let scheduler = JobScheduler::new().await?;
+    let job = Arc::new(job);
+    let is_running = Arc::new(Mutex::new(()));
+
+    let repeated_job = Job::new_repeated_async(interval, move |_uuid, _scheduler| {
+        let job = Arc::clone(&job);
+        let is_running = Arc::clone(&is_running);
+        Box::pin(async move {
+            let Ok(_guard) = is_running.try_lock() else {
+                event!(Level::DEBUG, "{} is still running - skipping this run", description);
+                return;
+            };
+
+            if let Err(e) = job().await {
+                event!(Level::WARN, "{} failed - retrying with the next run: {}", description, e);
+            }
+        })
+    })?;
+
+    scheduler.add(repeated_job).await?;
+    scheduler.start().await?;
+
+    Ok(scheduler)
+}
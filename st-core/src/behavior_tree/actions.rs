@@ -2,6 +2,11 @@ use crate::behavior_tree::behavior_args::BehaviorArgs;
 use crate::behavior_tree::behavior_tree::Response::Success;
 use crate::behavior_tree::behavior_tree::{ActionEvent, Actionable, Response};
 use crate::behavior_tree::ship_behaviors::ShipAction;
+use crate::contract_manager::{units_covered_by_active_tickets, ContractTicketScope};
+use crate::materialized_supply_chain_manager;
+use crate::mining_hauler_dispatcher::{
+    decide_drone_cargo_action, estimate_delivery_round_trip_secs, is_hauler_filled_enough, DroneCargoAction, DroneCargoStatus,
+};
 use crate::ship::ShipOperations;
 use crate::st_client::StClientTrait;
 use anyhow::Result;
@@ -13,13 +18,14 @@ use itertools::Itertools;
 use st_domain::budgeting::credits::Credits;
 use st_domain::budgeting::treasury_redesign::FinanceTicketDetails::{PurchaseTradeGoods, RefuelShip, SellTradeGoods};
 use st_domain::budgeting::treasury_redesign::{FinanceTicket, FinanceTicketDetails, Income};
+use st_domain::cargo_flow::{CargoFlowEntry, CargoFlowKind};
 use st_domain::cargo_transfer::{InternalTransferCargoRequest, InternalTransferCargoResponse, InternalTransferCargoToHaulerResult, TransferCargoError};
 use st_domain::TransactionActionEvent::{PurchasedShip, PurchasedTradeGoods, SoldTradeGoods, SuppliedConstructionSite};
 use st_domain::{
-    get_exploration_tasks_for_waypoint, Cargo, Contract, ExplorationTask, NavStatus, OperationExpenseEvent, RefuelShipResponse, RefuelShipResponseBody,
-    ShipSymbol, Survey, TradeGoodSymbol, TravelAction, WaypointModifierSymbol,
+    get_exploration_tasks_for_waypoint, Cargo, Contract, ExplorationTask, MiningOpsConfig, NavStatus, OperationExpenseEvent, RawDeliveryRoute,
+    RefuelShipResponse, RefuelShipResponseBody, ShipRole, ShipSymbol, Survey, TradeGoodSymbol, TravelAction, WaypointModifierSymbol,
 };
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::ops::{Add, Not};
 use std::sync::Arc;
 use tokio::sync::mpsc::Sender;
@@ -448,6 +454,8 @@ impl Actionable for ShipAction {
                         ExplorationTask::GetMarket => {
                             let market = state.perform_get_market().await?;
                             args.insert_market(market).await?;
+                            args.market_observation_scheduler
+                                .record_observation(&state.nav.waypoint_symbol, Utc::now())?;
                         }
                         ExplorationTask::GetJumpGate => {
                             let jump_gate = state.perform_get_jump_gate().await?;
@@ -484,19 +492,64 @@ impl Actionable for ShipAction {
             },
             ShipAction::SetNextObservationTime => {
                 let now = Utc::now();
-                state.set_next_observation_time(now.add(TimeDelta::minutes(10)));
+                let scheduler = &args.market_observation_scheduler;
+                let history_hours = scheduler.market_observation_strategy().history_hours;
+                let history = args
+                    .get_market_history_of_waypoint(&state.nav.waypoint_symbol, now - TimeDelta::hours(history_hours))
+                    .await?;
+                let active_tickets = args
+                    .treasurer
+                    .get_active_tickets()
+                    .await?
+                    .into_values()
+                    .collect_vec();
+                let interval = scheduler.update_interval(&state.nav.waypoint_symbol, &history, &active_tickets)?;
+
+                // waypoints without a market don't get their observations recorded
+                let next_observation_time = scheduler
+                    .next_observation_at(&state.nav.waypoint_symbol)?
+                    .unwrap_or(now.add(interval));
+                state.set_next_observation_time(next_observation_time);
                 Ok(Success)
             }
-            ShipAction::IsLateEnoughForWaypointObservation => match state.maybe_next_observation_time {
-                None => Ok(Success),
-                Some(next_time) => {
-                    if next_time < Utc::now() {
-                        Ok(Success)
-                    } else {
-                        Err(anyhow!("Not enough time has passed"))
-                    }
+            ShipAction::IsLateEnoughForWaypointObservation => {
+                let now = Utc::now();
+                let scheduler = &args.market_observation_scheduler;
+                match state.maybe_next_observation_time {
+                    Some(next_time) if next_time >= now => Err(anyhow!("Not enough time has passed")),
+                    _ => match scheduler.next_observation_at(&state.nav.waypoint_symbol)? {
+                        // a docked ship has observed the market in the meantime
+                        Some(next_time) if next_time > now => {
+                            state.set_next_observation_time(next_time);
+                            Err(anyhow!("Market has been observed recently"))
+                        }
+                        _ => match scheduler.try_acquire_request(now)? {
+                            Ok(()) => Ok(Success),
+                            Err(next_possible_time) => {
+                                state.set_next_observation_time(next_possible_time);
+                                Err(anyhow!("Market observation budget is used up"))
+                            }
+                        },
+                    },
                 }
-            },
+            }
+            ShipAction::RefreshMarketIfDue => {
+                let now = Utc::now();
+                let waypoint_symbol = state.nav.waypoint_symbol.clone();
+                let scheduler = &args.market_observation_scheduler;
+                let is_marketplace = args
+                    .get_exploration_tasks_waypoint(&waypoint_symbol)
+                    .await?
+                    .contains(&ExplorationTask::GetMarket);
+
+                if is_marketplace && scheduler.is_due(&waypoint_symbol, now)? && scheduler.try_acquire_request(now)?.is_ok() {
+                    let market = state.perform_get_market().await?;
+                    args.insert_market(market).await?;
+                    scheduler.record_observation(&waypoint_symbol, now)?;
+                }
+                // refreshing is optional for ships that are only docked here for something else
+                Ok(Success)
+            }
             ShipAction::SetNextTradeStopAsDestination => match args
                 .treasurer
                 .get_active_tickets_for_ship(&state.symbol)
@@ -603,6 +656,20 @@ impl Actionable for ShipAction {
                                 state.cargo = response.data.cargo.clone();
                                 args.upsert_ship(&state.ship).await?;
 
+                                // a drone sells what it extracted at its site - haulers sell what was already logged as transferred to them
+                                if let Some(mining_site) = state.get_mining_site().filter(|_| state.is_mining_drone()) {
+                                    args.blackboard
+                                        .log_cargo_flows(vec![CargoFlowEntry {
+                                            site: mining_site,
+                                            ship_symbol: state.symbol.clone(),
+                                            kind: CargoFlowKind::SoldByDrone,
+                                            trade_good_symbol: details.trade_good.clone(),
+                                            units: response.data.transaction.units as u32,
+                                            created_at: Utc::now(),
+                                        }])
+                                        .await?;
+                                }
+
                                 args.mark_sell_as_completed(finance_ticket.clone(), &response)
                                     .await?;
 
@@ -679,6 +746,19 @@ impl Actionable for ShipAction {
 
                                 state.cargo = response.data.cargo;
                                 args.upsert_ship(&state.ship).await?;
+
+                                // helpers deliver as well - whoever brings the last units fulfils the contract
+                                let mut contract = response.data.contract;
+                                if is_contract_delivery_complete(&contract) && !contract.fulfilled {
+                                    contract = fulfil_contract_and_report_income(state, args, &contract).await?;
+                                }
+                                if state
+                                    .maybe_contract
+                                    .as_ref()
+                                    .is_some_and(|c| c.id == contract.id)
+                                {
+                                    state.set_contract(contract);
+                                }
                             }
                         }
                         completed_tickets.insert(finance_ticket.clone());
@@ -739,6 +819,19 @@ impl Actionable for ShipAction {
             ShipAction::SiphonResources => {
                 state.perform_siphon_resources().await?;
                 args.upsert_ship(&state.ship).await?;
+                args.transfer_cargo_manager
+                    .dispatcher()
+                    .report_drone_cargo(
+                        state.symbol.clone(),
+                        DroneCargoStatus {
+                            site: state.nav.waypoint_symbol.clone(),
+                            cargo_units: state.cargo.units as u32,
+                            cargo_capacity: state.cargo.capacity as u32,
+                            yield_size: state.get_yield_size_for_siphoning(),
+                            cooldown_secs: state.cooldown.total_seconds.max(0) as u32,
+                            updated_at: Utc::now(),
+                        },
+                    )?;
 
                 Ok(Success)
             }
@@ -755,6 +848,21 @@ impl Actionable for ShipAction {
 
                 Ok(Success)
             }
+            ShipAction::IsSiphoningDemanded => match time_until_siphoning_is_due(args, state) {
+                None => Err(anyhow!("The supply of all siphoned goods is high enough")),
+                Some(remaining) if remaining > TimeDelta::zero() => Err(anyhow!("Siphoning is throttled to the demanded rate for another {remaining}")),
+                Some(_) => Ok(Success),
+            },
+            ShipAction::SleepUntilSiphoningIsDemanded => {
+                // same rhythm as the critical limit check - 60s with the default sleep duration
+                let max_sleep_duration = sleep_duration * 12;
+                let sleep_duration = time_until_siphoning_is_due(args, state)
+                    .and_then(|remaining| remaining.to_std().ok())
+                    .map(|remaining| remaining.min(max_sleep_duration))
+                    .unwrap_or(max_sleep_duration);
+                tokio::time::sleep(sleep_duration).await;
+                Ok(Success)
+            }
             ShipAction::IsAtSiphoningSite => {
                 if let Some(siphoning_waypoint) = state.maybe_siphoning_waypoint.clone() {
                     if state.current_location() == siphoning_waypoint && state.nav.status == NavStatus::InOrbit {
@@ -871,23 +979,53 @@ impl Actionable for ShipAction {
                 Ok(Success)
             }
             ShipAction::ExtractResources => {
-                if let Some((cfg, msc)) = args
+                if let Some(cfg) = args
                     .materialized_supply_chain_manager
                     .get_mining_ops_config_for_system(state.nav.system_symbol.clone())
-                    .zip(
-                        args.materialized_supply_chain_manager
-                            .get_materialized_supply_chain_for_system(state.nav.system_symbol.clone()),
-                    )
                 {
+                    // the ship might mine at a different asteroid than the default mining site of the system
+                    let cfg = MiningOpsConfig {
+                        mining_waypoint: state.nav.waypoint_symbol.clone(),
+                        ..cfg
+                    };
                     loop {
-                        let maybe_survey: Option<Survey> = args
+                        let ranked_surveys = args
                             .blackboard
-                            .get_best_survey_for_current_demand(&cfg, &msc)
+                            .get_ranked_surveys_for_current_demand(&cfg, args.mining_site_manager.haul_cost_per_unit_and_distance())
                             .await?;
+                        let maybe_survey: Option<Survey> = args
+                            .mining_site_manager
+                            .claim_best_survey(&state.symbol, &ranked_surveys)?;
 
                         match state.perform_extract_resources(maybe_survey.clone()).await {
                             Ok(response) => {
                                 args.upsert_ship(&state.ship).await?;
+                                args.mining_site_manager
+                                    .record_extraction(&state.nav.waypoint_symbol, &response.data.extraction)?;
+                                args.transfer_cargo_manager
+                                    .dispatcher()
+                                    .report_drone_cargo(
+                                        state.symbol.clone(),
+                                        DroneCargoStatus {
+                                            site: state.nav.waypoint_symbol.clone(),
+                                            cargo_units: state.cargo.units as u32,
+                                            cargo_capacity: state.cargo.capacity as u32,
+                                            yield_size: state.get_yield_size_for_mining(),
+                                            cooldown_secs: state.cooldown.total_seconds.max(0) as u32,
+                                            updated_at: Utc::now(),
+                                        },
+                                    )?;
+                                let extraction_yield = &response.data.extraction.extraction_yield;
+                                args.blackboard
+                                    .log_cargo_flows(vec![CargoFlowEntry {
+                                        site: state.nav.waypoint_symbol.clone(),
+                                        ship_symbol: state.symbol.clone(),
+                                        kind: CargoFlowKind::Extracted,
+                                        trade_good_symbol: extraction_yield.symbol.clone(),
+                                        units: extraction_yield.units,
+                                        created_at: Utc::now(),
+                                    }])
+                                    .await?;
 
                                 if let Some(survey) = maybe_survey.clone() {
                                     args.blackboard
@@ -918,11 +1056,13 @@ impl Actionable for ShipAction {
                                     // has been exhausted
                                     if let Some(survey) = maybe_survey {
                                         args.blackboard.mark_survey_as_exhausted(&survey).await?;
+                                        args.mining_site_manager.release_survey(&survey.signature)?;
                                     }
                                 } else if e.to_string().contains("4221") {
                                     // Ship survey failed. Target signature is no longer in range or valid
                                     if let Some(survey) = maybe_survey {
                                         args.blackboard.mark_survey_as_exhausted(&survey).await?;
+                                        args.mining_site_manager.release_survey(&survey.signature)?;
                                     }
                                 } else {
                                     break Err(e);
@@ -993,7 +1133,7 @@ impl Actionable for ShipAction {
 
                 match cargo_transfer_result {
                     Ok(res) => match res {
-                        InternalTransferCargoToHaulerResult::NoMatchingShipFound => {}
+                        InternalTransferCargoToHaulerResult::NoMatchingShipFound => Err(anyhow!("No hauler with free cargo space at this site")),
                         InternalTransferCargoToHaulerResult::Success {
                             updated_miner_cargo,
                             transfer_tasks,
@@ -1007,12 +1147,46 @@ impl Actionable for ShipAction {
                                 transfer_tasks.len(),
                                 transfer_tasks
                             );
+
+                            let flows = transfer_tasks
+                                .iter()
+                                .map(|task| CargoFlowEntry {
+                                    site: state.nav.waypoint_symbol.clone(),
+                                    ship_symbol: state.symbol.clone(),
+                                    kind: CargoFlowKind::TransferredToHauler,
+                                    trade_good_symbol: task.trade_good_symbol.clone(),
+                                    units: task.units,
+                                    created_at: Utc::now(),
+                                })
+                                .collect_vec();
+                            args.blackboard.log_cargo_flows(flows).await?;
+
+                            Ok(Success)
                         }
                     },
-                    Err(err) => {
-                        eprintln!("err: {err:?}");
-                    }
+                    Err(err) => Err(anyhow!("Cargo transfer failed: {err:?}")),
                 }
+            }
+            ShipAction::ShouldWaitForHauler => match decide_cargo_action_of_full_drone(args, state).await? {
+                DroneCargoAction::WaitForHauler => Ok(Success),
+                other => Err(anyhow!("Drone decided to {other:?} instead of waiting for a hauler")),
+            },
+            ShipAction::WaitForHauler => {
+                tokio::time::sleep(sleep_duration).await;
+                Ok(Success)
+            }
+            ShipAction::ShouldSellCargoAtDeliveryMarket => match decide_cargo_action_of_full_drone(args, state).await? {
+                // the sale itself is logged as cargo flow by the regular trading behavior
+                DroneCargoAction::SellAtDeliveryMarket => Ok(Success),
+                other => Err(anyhow!("Drone decided to {other:?} instead of selling its cargo")),
+            },
+            ShipAction::JettisonCargoForMining => {
+                let flows = cargo_flows_of_current_cargo(state, CargoFlowKind::Jettisoned);
+                let _responses = state
+                    .perform_jettison_everything_not_on_list(HashSet::new())
+                    .await?;
+                args.upsert_ship(&state.ship).await?;
+                args.blackboard.log_cargo_flows(flows).await?;
 
                 Ok(Success)
             }
@@ -1044,9 +1218,33 @@ impl Actionable for ShipAction {
                     }
                 });
 
+                let delivery_round_trip_secs = match delivery_locations_of_raw_material_site(args, state) {
+                    Some(delivery_locations) => {
+                        let waypoints = args
+                            .blackboard
+                            .get_waypoints_of_system(&state.nav.system_symbol)
+                            .await?;
+                        let delivered_goods = delivery_locations.keys().cloned().collect_vec();
+                        estimate_delivery_round_trip_secs(
+                            &waypoints,
+                            &state.nav.waypoint_symbol,
+                            &delivery_locations,
+                            &delivered_goods,
+                            state.engine.speed.max(0) as u32,
+                        )
+                    }
+                    None => 0,
+                };
+
                 let hauler_wait_result = args
                     .transfer_cargo_manager
-                    .register_hauler_for_pickup_and_wait_until_full(state.nav.waypoint_symbol.clone(), state.symbol.clone(), state.cargo.clone(), tx.clone())
+                    .register_hauler_for_pickup_and_wait_until_full(
+                        state.nav.waypoint_symbol.clone(),
+                        state.symbol.clone(),
+                        state.cargo.clone(),
+                        tx.clone(),
+                        delivery_round_trip_secs,
+                    )
                     .await;
 
                 match hauler_wait_result {
@@ -1067,15 +1265,16 @@ impl Actionable for ShipAction {
                 }
             }
             ShipAction::IsHaulerFilledEnoughForDelivery => {
-                let fill_ratio = state.cargo.units as f64 / state.cargo.capacity as f64;
-                if fill_ratio > 0.8 {
+                let fill_threshold = args.transfer_cargo_manager.hauler_fill_threshold();
+                if is_hauler_filled_enough(state.cargo.units as u32, state.cargo.capacity as u32, fill_threshold) {
                     Ok(Success)
                 } else {
                     Err(anyhow!(
-                        "Ship cargo is {} out of {} --> {}% <= 80%",
+                        "Ship cargo is {} out of {} --> {}% <= {}%",
                         state.cargo.units,
                         state.cargo.capacity,
-                        (fill_ratio * 100.0).round() as u32
+                        (state.cargo.units as f64 / state.cargo.capacity.max(1) as f64 * 100.0).round() as u32,
+                        (fill_threshold * 100.0).round() as u32
                     ))
                 }
             }
@@ -1099,6 +1298,60 @@ impl Actionable for ShipAction {
                     Err(anyhow!("Mining site not found"))
                 }
             }
+            ShipAction::MoveToAlternativeMiningSite => {
+                let role = if state.is_mining_drone() {
+                    ShipRole::Miner
+                } else if state.is_surveyor() {
+                    ShipRole::MiningSurveyor
+                } else {
+                    ShipRole::MiningHauler
+                };
+                let system_symbol = state.nav.system_symbol.clone();
+                let maybe_current_site = state.get_mining_site();
+
+                let delivery_locations = args
+                    .materialized_supply_chain_manager
+                    .get_mining_ops_config_for_system(system_symbol.clone())
+                    .map(|cfg| cfg.delivery_locations)
+                    .unwrap_or_default();
+                let waypoints = args
+                    .blackboard
+                    .get_waypoints_of_system(&system_symbol)
+                    .await?;
+                let ranked_sites = args
+                    .mining_site_manager
+                    .rank_mining_sites(&waypoints, &delivery_locations);
+
+                match args
+                    .mining_site_manager
+                    .relocate_ship(&state.symbol, role.clone(), maybe_current_site.clone(), &ranked_sites)?
+                {
+                    Some(new_mining_site) => {
+                        info!(
+                            message = "Moving to alternative mining site.",
+                            from = maybe_current_site.as_ref().map(|wps| wps.0.clone()),
+                            to = &new_mining_site.0
+                        );
+                        state.set_mining_waypoint(new_mining_site);
+
+                        // haulers would wait forever for drones that don't come back
+                        if let Some(current_site) = maybe_current_site {
+                            if role == ShipRole::Miner
+                                && args
+                                    .mining_site_manager
+                                    .has_drones_at_site(&current_site)
+                                    .not()
+                            {
+                                args.transfer_cargo_manager
+                                    .release_waiting_haulers(&current_site)
+                                    .await;
+                            }
+                        }
+                        Ok(Success)
+                    }
+                    None => Err(anyhow!("No alternative mining site available")),
+                }
+            }
             ShipAction::SleepForNextWaypointCriticalLimitCheck => {
                 // default sleep duration is 5s
                 // let's wait 60s but not hardcode it here
@@ -1159,6 +1412,41 @@ impl Actionable for ShipAction {
                     Err(anyhow!("No contract found"))
                 }
             }
+            ShipAction::IsContractProfitable => {
+                if let Some(contract) = state.maybe_contract.clone() {
+                    let profitability = args
+                        .evaluate_contract_profitability(&state.cargo, &state.nav.waypoint_symbol, state.engine.speed.max(0) as u32, &contract)
+                        .await?;
+                    if profitability.is_worth_accepting(args.contract_manager.contract_strategy().min_profit) {
+                        Ok(Success)
+                    } else {
+                        Err(anyhow!("Contract is not profitable: {profitability:?}"))
+                    }
+                } else {
+                    Err(anyhow!("No contract found"))
+                }
+            }
+            ShipAction::DeclineContract => {
+                // the API has no endpoint for declining a contract - we remember the decline and let its offer expire
+                if let Some(contract) = state.maybe_contract.take() {
+                    info!("Declining unprofitable contract {} of {}", contract.id.0, contract.faction_symbol);
+                    args.blackboard
+                        .mark_contract_as_declined(&contract.id)
+                        .await?;
+                }
+                Ok(Success)
+            }
+            ShipAction::HasOpenDeclinedContractOffer => {
+                let maybe_contract_entry = args
+                    .blackboard
+                    .get_youngest_contract_entry(&state.nav.system_symbol)
+                    .await?;
+                if maybe_contract_entry.is_some_and(|entry| entry.is_open_declined_offer(Utc::now())) {
+                    Ok(Success)
+                } else {
+                    Err(anyhow!("No open declined contract offer"))
+                }
+            }
             ShipAction::CanAffordContract => {
                 if let Some(contract) = state.maybe_contract.clone() {
                     match args
@@ -1202,47 +1490,16 @@ impl Actionable for ShipAction {
             }
             ShipAction::FulfilContract => {
                 if let Some(contract) = state.maybe_contract.clone() {
-                    match state.perform_fulfill_contract(&contract.id).await {
-                        Err(e) => Err(anyhow!("Error fulfilling contract: {}", e)),
-                        Ok(fulfil_contract_response) => {
-                            let contract: &Contract = &fulfil_contract_response.data.contract;
-                            state.set_contract(contract.clone());
-                            args.treasurer
-                                .report_income(
-                                    &state.my_fleet,
-                                    Income::ContractFulfilled {
-                                        contract_id: contract.id.clone(),
-                                        fulfilled_reward: contract.terms.payment.on_fulfilled.into(),
-                                    },
-                                )
-                                .await?;
-
-                            match args
-                                .blackboard
-                                .upsert_contract(&state.nav.system_symbol, &fulfil_contract_response.data.contract)
-                                .await
-                            {
-                                Ok(_) => Ok(Success),
-                                Err(e) => Err(anyhow!(
-                                    "Error upserting contract: {}. Contract: {}",
-                                    e,
-                                    serde_json::to_string_pretty(&contract)?
-                                )),
-                            }
-                        }
-                    }
+                    let contract = fulfil_contract_and_report_income(state, args, &contract).await?;
+                    state.set_contract(contract);
+                    Ok(Success)
                 } else {
                     Err(anyhow!("No contract found"))
                 }
             }
             ShipAction::IsContractDeliveryComplete => {
                 if let Some(contract) = state.maybe_contract.clone() {
-                    let is_complete = contract.fulfilled
-                        || contract
-                            .terms
-                            .deliver
-                            .iter()
-                            .all(|delivery| delivery.units_required == delivery.units_fulfilled);
+                    let is_complete = contract.fulfilled || is_contract_delivery_complete(&contract);
                     if is_complete {
                         Ok(Success)
                     } else {
@@ -1262,12 +1519,35 @@ impl Actionable for ShipAction {
                         // no need to create new tickets
                         Ok(Success)
                     } else {
+                        // helper haulers might already deliver parts of the contract - we take care of one cargo load at a time
+                        let tickets_of_other_ships = args
+                            .treasurer
+                            .get_active_tickets()
+                            .await?
+                            .into_values()
+                            .filter(|t| t.ship_symbol != state.symbol)
+                            .collect_vec();
+                        let scope = ContractTicketScope {
+                            units_covered_by_other_ships: units_covered_by_active_tickets(&contract.id, &tickets_of_other_ships),
+                            max_units_to_purchase: Some(state.cargo.capacity.max(0) as u32),
+                        };
+
                         match args
-                            .create_contract_tickets(&state.symbol, &state.cargo, &state.nav.waypoint_symbol, &contract, &state.my_fleet)
+                            .create_contract_tickets(&state.symbol, &state.cargo, &state.nav.waypoint_symbol, &contract, &state.my_fleet, &scope)
                             .await
                         {
                             Ok(is_affordable) => {
                                 if is_affordable {
+                                    let has_new_tickets = args
+                                        .treasurer
+                                        .get_active_tickets_for_ship(&state.symbol)
+                                        .await?
+                                        .is_empty()
+                                        .not();
+                                    if !has_new_tickets {
+                                        // all open units are being delivered by helpers - wait for them
+                                        tokio::time::sleep(sleep_duration * 12).await;
+                                    }
                                     Ok(Success)
                                 } else {
                                     Err(anyhow!("can't afford contract right now"))
@@ -1303,6 +1583,45 @@ impl Actionable for ShipAction {
     }
 }
 
+fn is_contract_delivery_complete(contract: &Contract) -> bool {
+    contract
+        .terms
+        .deliver
+        .iter()
+        .all(|delivery| delivery.units_required == delivery.units_fulfilled)
+}
+
+async fn fulfil_contract_and_report_income(state: &mut ShipOperations, args: &BehaviorArgs, contract: &Contract) -> Result<Contract> {
+    let fulfil_contract_response = state
+        .perform_fulfill_contract(&contract.id)
+        .await
+        .map_err(|e| anyhow!("Error fulfilling contract: {}", e))?;
+    let contract: Contract = fulfil_contract_response.data.contract;
+
+    args.treasurer
+        .report_income(
+            &state.my_fleet,
+            Income::ContractFulfilled {
+                contract_id: contract.id.clone(),
+                fulfilled_reward: contract.terms.payment.on_fulfilled.into(),
+            },
+        )
+        .await?;
+
+    args.blackboard
+        .upsert_contract(&state.nav.system_symbol, &contract)
+        .await
+        .map_err(|e| {
+            anyhow!(
+                "Error upserting contract: {}. Contract: {}",
+                e,
+                serde_json::to_string_pretty(&contract).unwrap_or_default()
+            )
+        })?;
+
+    Ok(contract)
+}
+
 fn find_completable_tickets_based_on_ship_state(ship_state: &ShipOperations, finance_tickets: &[FinanceTicket]) -> Vec<FinanceTicket> {
     finance_tickets
         .iter()
@@ -1331,6 +1650,86 @@ fn find_completable_tickets_based_on_ship_state(ship_state: &ShipOperations, fin
         .collect_vec()
 }
 
+/// Siphoning drones and the haulers of a gas giant deliver to the siphoning routes, everybody else to the mining routes.
+fn delivery_locations_of_raw_material_site(args: &BehaviorArgs, state: &ShipOperations) -> Option<HashMap<TradeGoodSymbol, RawDeliveryRoute>> {
+    let system_symbol = state.nav.system_symbol.clone();
+    if state.is_siphoner() || state.maybe_siphoning_waypoint.is_some() {
+        args.materialized_supply_chain_manager
+            .get_siphoning_ops_config_for_system(system_symbol)
+            .map(|cfg| cfg.delivery_locations)
+    } else {
+        args.materialized_supply_chain_manager
+            .get_mining_ops_config_for_system(system_symbol)
+            .map(|cfg| cfg.delivery_locations)
+    }
+}
+
+fn time_until_siphoning_is_due(args: &BehaviorArgs, state: &ShipOperations) -> Option<TimeDelta> {
+    let cfg = args
+        .materialized_supply_chain_manager
+        .get_siphoning_ops_config_for_system(state.nav.system_symbol.clone())?;
+    materialized_supply_chain_manager::time_until_siphoning_is_due(cfg.demanded_rate, &state.cooldown, Utc::now())
+}
+
+fn extraction_yield_size(state: &ShipOperations) -> u32 {
+    if state.is_siphoner() {
+        state.get_yield_size_for_siphoning()
+    } else {
+        state.get_yield_size_for_mining()
+    }
+}
+
+/// What a drone does with its full cargo if no hauler could take it right now.
+async fn decide_cargo_action_of_full_drone(args: &BehaviorArgs, state: &ShipOperations) -> Result<DroneCargoAction> {
+    let delivery_locations = delivery_locations_of_raw_material_site(args, state).ok_or_else(|| anyhow!("No raw material delivery config found"))?;
+
+    let waypoints = args
+        .blackboard
+        .get_waypoints_of_system(&state.nav.system_symbol)
+        .await?;
+    let cargo_goods = state
+        .cargo
+        .inventory
+        .iter()
+        .map(|inv| inv.symbol.clone())
+        .collect_vec();
+    let delivery_round_trip_secs = estimate_delivery_round_trip_secs(
+        &waypoints,
+        &state.nav.waypoint_symbol,
+        &delivery_locations,
+        &cargo_goods,
+        state.engine.speed.max(0) as u32,
+    );
+    let maybe_secs_until_hauler_arrives = args
+        .transfer_cargo_manager
+        .secs_until_hauler_is_available(&state.nav.waypoint_symbol)
+        .await;
+
+    Ok(decide_drone_cargo_action(
+        state.cargo.units as u32,
+        extraction_yield_size(state),
+        state.cooldown.total_seconds.max(0) as u32,
+        maybe_secs_until_hauler_arrives,
+        delivery_round_trip_secs,
+    ))
+}
+
+fn cargo_flows_of_current_cargo(state: &ShipOperations, kind: CargoFlowKind) -> Vec<CargoFlowEntry> {
+    state
+        .cargo
+        .inventory
+        .iter()
+        .map(|inv| CargoFlowEntry {
+            site: state.nav.waypoint_symbol.clone(),
+            ship_symbol: state.symbol.clone(),
+            kind: kind.clone(),
+            trade_good_symbol: inv.symbol.clone(),
+            units: inv.units,
+            created_at: Utc::now(),
+        })
+        .collect_vec()
+}
+
 async fn wrap_transfer_cargo_request(
     client: Arc<dyn StClientTrait>,
     internal_args: InternalTransferCargoRequest,
@@ -1367,25 +1766,34 @@ mod tests {
     use std::collections::HashMap;
 
     use st_domain::{
-        DockShipResponse, FleetId, FlightMode, GetMarketResponse, NavAndFuelResponse, NavStatus, NavigateShipResponse, SetFlightModeResponse, ShipSymbol,
-        TravelAction, WaypointSymbol, WaypointTraitSymbol,
+        Agent, AgentSymbol, Cargo, DockShipResponse, FactionSymbol, FleetId, FlightMode, GetMarketResponse, Inventory, NavAndFuelResponse, NavStatus,
+        NavigateShipResponse, SellTradeGoodResponse, SellTradeGoodResponseBody, SetFlightModeResponse, ShipSymbol, TradeGoodSymbol, Transaction,
+        TransactionType, TravelAction, WaypointSymbol, WaypointTraitSymbol,
     };
 
     use crate::behavior_tree::behavior_tree::Response::Success;
     use crate::fleet::ship_runner::ship_behavior_runner;
     use crate::st_client::MockStClientTrait;
     use anyhow::anyhow;
+    use chrono::Utc;
     use itertools::Itertools;
+    use st_domain::budgeting::credits::Credits;
     use std::sync::Arc;
     use tokio::sync::mpsc::{Receiver, Sender};
 
+    use crate::behavior_tree::behavior_definitions::ShipBehaviorStore;
     use crate::calc_batches_based_on_volume_constraint;
+    use crate::contract_manager::ContractManager;
+    use crate::market_observation_scheduler::MarketObservationScheduler;
     use crate::materialized_supply_chain_manager::MaterializedSupplyChainManager;
+    use crate::mining_site_manager::MiningSiteManager;
+    use crate::strategy::StrategyStore;
     use crate::test_objects::TestObjects;
     use crate::transfer_cargo_manager::TransferCargoManager;
     use st_domain::blackboard_ops::MockBlackboardOps;
     use st_domain::budgeting::test_sync_ledger::create_test_ledger_setup;
     use st_domain::budgeting::treasury_redesign::ThreadSafeTreasurer;
+    use st_domain::strategy::StrategyConfig;
     use test_log::test;
 
     async fn test_run_ship_behavior(
@@ -1483,8 +1891,12 @@ mod tests {
         let args = BehaviorArgs {
             blackboard: Arc::new(MockBlackboardOps::new()),
             treasurer: ThreadSafeTreasurer::new(0.into(), task_sender.clone()).await,
-            transfer_cargo_manager: Arc::new(TransferCargoManager::new()),
+            transfer_cargo_manager: Arc::new(TransferCargoManager::default()),
             materialized_supply_chain_manager: MaterializedSupplyChainManager::new(),
+            mining_site_manager: MiningSiteManager::default(),
+            contract_manager: ContractManager::default(),
+            market_observation_scheduler: MarketObservationScheduler::default(),
+            ship_behavior_store: ShipBehaviorStore::default(),
         };
 
         let mocked_client = mock_client
@@ -1527,8 +1939,12 @@ mod tests {
         let args = BehaviorArgs {
             blackboard: Arc::new(MockBlackboardOps::new()),
             treasurer: ThreadSafeTreasurer::new(0.into(), task_sender.clone()).await,
-            transfer_cargo_manager: Arc::new(TransferCargoManager::new()),
+            transfer_cargo_manager: Arc::new(TransferCargoManager::default()),
             materialized_supply_chain_manager: MaterializedSupplyChainManager::new(),
+            mining_site_manager: MiningSiteManager::default(),
+            contract_manager: ContractManager::default(),
+            market_observation_scheduler: MarketObservationScheduler::default(),
+            ship_behavior_store: ShipBehaviorStore::default(),
         };
 
         let mocked_client = mock_client
@@ -1710,8 +2126,12 @@ mod tests {
         let args = BehaviorArgs {
             blackboard: Arc::new(mock_test_blackboard),
             treasurer: ThreadSafeTreasurer::new(0.into(), task_sender.clone()).await,
-            transfer_cargo_manager: Arc::new(TransferCargoManager::new()),
+            transfer_cargo_manager: Arc::new(TransferCargoManager::default()),
             materialized_supply_chain_manager: MaterializedSupplyChainManager::new(),
+            mining_site_manager: MiningSiteManager::default(),
+            contract_manager: ContractManager::default(),
+            market_observation_scheduler: MarketObservationScheduler::default(),
+            ship_behavior_store: ShipBehaviorStore::default(),
         };
 
         let explorer_waypoint_symbols = explorer_waypoints
@@ -1731,6 +2151,144 @@ mod tests {
         assert_eq!(ship_ops.explore_location_queue.len(), 0);
     }
 
+    #[test(tokio::test)]
+    async fn sales_of_mining_haulers_are_not_logged_as_sold_by_drone() {
+        let (test_archiver, task_sender) = create_test_ledger_setup().await;
+        let treasurer = ThreadSafeTreasurer::new(100_000.into(), task_sender.clone()).await;
+        let fleet_id = FleetId(42);
+        treasurer
+            .create_fleet(&fleet_id, Credits::new(75_000))
+            .await
+            .unwrap();
+
+        // a hauler of a mining site - it has a mining waypoint, but no mining laser
+        let mut hauler = TestObjects::test_ship(500);
+        hauler.nav.status = NavStatus::Docked;
+        hauler.cargo = Cargo {
+            capacity: 80,
+            units: 60,
+            inventory: vec![Inventory::new(TradeGoodSymbol::IRON_ORE, 60)],
+        };
+        assert!(!hauler.is_mining_drone());
+
+        treasurer
+            .create_sell_trade_goods_ticket(
+                &fleet_id,
+                TradeGoodSymbol::IRON_ORE,
+                hauler.nav.waypoint_symbol.clone(),
+                hauler.symbol.clone(),
+                60,
+                Credits::new(50),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let sell_response = SellTradeGoodResponse {
+            data: SellTradeGoodResponseBody {
+                agent: Agent {
+                    account_id: None,
+                    symbol: AgentSymbol("FLWI".to_string()),
+                    headquarters: hauler.nav.waypoint_symbol.clone(),
+                    credits: 103_000,
+                    starting_faction: FactionSymbol::GALACTIC,
+                    ship_count: 1,
+                },
+                cargo: Cargo {
+                    capacity: 80,
+                    units: 0,
+                    inventory: vec![],
+                },
+                transaction: Transaction {
+                    waypoint_symbol: hauler.nav.waypoint_symbol.clone(),
+                    ship_symbol: hauler.symbol.clone(),
+                    trade_symbol: TradeGoodSymbol::IRON_ORE,
+                    transaction_type: TransactionType::Sell,
+                    units: 60,
+                    price_per_unit: 50,
+                    total_price: 3_000,
+                    timestamp: Utc::now(),
+                },
+            },
+        };
+        let mut mock_client = MockStClientTrait::new();
+        mock_client
+            .expect_sell_trade_good()
+            .return_once(move |_, _, _| Ok(sell_response));
+
+        let mut blackboard = MockBlackboardOps::new();
+        blackboard.expect_upsert_ship().returning(|_| Ok(()));
+        // the units of a hauler were logged as TransferredToHauler at the site already
+        blackboard.expect_log_cargo_flows().never();
+
+        let args = BehaviorArgs {
+            blackboard: Arc::new(blackboard),
+            treasurer,
+            transfer_cargo_manager: Arc::new(TransferCargoManager::default()),
+            materialized_supply_chain_manager: MaterializedSupplyChainManager::new(),
+            mining_site_manager: MiningSiteManager::default(),
+            contract_manager: ContractManager::default(),
+            market_observation_scheduler: MarketObservationScheduler::default(),
+            ship_behavior_store: ShipBehaviorStore::default(),
+        };
+
+        let mut ship_ops = ShipOperations::new(hauler, Arc::new(mock_client), fleet_id);
+        ship_ops.set_mining_waypoint(WaypointSymbol("X1-FOO-ASTEROID".to_string()));
+
+        let (result, _, _) = test_run_ship_behavior(
+            &mut ship_ops,
+            Duration::from_millis(1),
+            args,
+            Behavior::new_action(ShipAction::PerformTradeActionAndMarkAsCompleted),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result, Success);
+        assert_eq!(ship_ops.cargo.units, 0);
+    }
+
+    #[test(tokio::test)]
+    async fn hauler_departs_once_filled_above_the_strategy_threshold() {
+        let (test_archiver, task_sender) = create_test_ledger_setup().await;
+
+        let mut strategy_config = StrategyConfig::default();
+        strategy_config.cargo_transfer.hauler_fill_threshold = 0.5;
+
+        let create_args = |transfer_cargo_manager: TransferCargoManager, treasurer: ThreadSafeTreasurer| BehaviorArgs {
+            blackboard: Arc::new(MockBlackboardOps::new()),
+            treasurer,
+            transfer_cargo_manager: Arc::new(transfer_cargo_manager),
+            materialized_supply_chain_manager: MaterializedSupplyChainManager::new(),
+            mining_site_manager: MiningSiteManager::default(),
+            contract_manager: ContractManager::default(),
+            market_observation_scheduler: MarketObservationScheduler::default(),
+            ship_behavior_store: ShipBehaviorStore::default(),
+        };
+
+        let mut ship = TestObjects::test_ship(500);
+        ship.cargo.capacity = 80;
+        ship.cargo.units = 50;
+        let mut ship_ops = ShipOperations::new(ship, Arc::new(MockStClientTrait::new()), FleetId(42));
+        let is_filled_enough = Behavior::new_action(ShipAction::IsHaulerFilledEnoughForDelivery);
+
+        // 63% are not enough for the default threshold of 80%
+        let default_args = create_args(TransferCargoManager::default(), ThreadSafeTreasurer::new(0.into(), task_sender.clone()).await);
+        let err = test_run_ship_behavior(&mut ship_ops, Duration::from_millis(1), default_args, is_filled_enough.clone())
+            .await
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("63% <= 80%"), "{err:#}");
+
+        let args = create_args(
+            TransferCargoManager::new(StrategyStore::from_strategy_config(strategy_config)),
+            ThreadSafeTreasurer::new(0.into(), task_sender.clone()).await,
+        );
+        let (result, _, _) = test_run_ship_behavior(&mut ship_ops, Duration::from_millis(1), args, is_filled_enough)
+            .await
+            .unwrap();
+        assert_eq!(result, Success);
+    }
+
     /*
 
     #[tokio::test]
@@ -1,14 +1,18 @@
 use anyhow::Result;
+use chrono::Utc;
 use st_domain::blackboard_ops::BlackboardOps;
 
+use crate::behavior_tree::behavior_definitions::ShipBehaviorStore;
 use crate::contract_manager;
-use crate::contract_manager::calculate_necessary_tickets_for_contract;
+use crate::contract_manager::{calculate_necessary_tickets_for_contract_share, ContractManager, ContractTicketScope};
+use crate::market_observation_scheduler::MarketObservationScheduler;
 use crate::materialized_supply_chain_manager::MaterializedSupplyChainManager;
+use crate::mining_site_manager::MiningSiteManager;
 use crate::transfer_cargo_manager::TransferCargoManager;
 use st_domain::budgeting::treasury_redesign::{FinanceTicket, FinanceTicketDetails, ThreadSafeTreasurer};
 use st_domain::{
-    Cargo, Contract, DeliverCargoToContractResponse, FleetId, MarketEntry, PurchaseShipResponse, PurchaseTradeGoodResponse, SellTradeGoodResponse, ShipSymbol,
-    SupplyConstructionSiteResponse, WaypointSymbol,
+    Cargo, Contract, ContractProfitability, DeliverCargoToContractResponse, FleetId, MarketEntry, PurchaseShipResponse, PurchaseTradeGoodResponse,
+    SellTradeGoodResponse, ShipSymbol, SupplyConstructionSiteResponse, WaypointSymbol,
 };
 use std::sync::Arc;
 
@@ -18,6 +22,10 @@ pub struct BehaviorArgs {
     pub treasurer: ThreadSafeTreasurer,
     pub transfer_cargo_manager: Arc<TransferCargoManager>,
     pub materialized_supply_chain_manager: MaterializedSupplyChainManager,
+    pub mining_site_manager: MiningSiteManager,
+    pub contract_manager: ContractManager,
+    pub market_observation_scheduler: MarketObservationScheduler,
+    pub ship_behavior_store: ShipBehaviorStore,
 }
 
 impl BehaviorArgs {
@@ -93,6 +101,30 @@ impl BehaviorArgs {
         }
     }
 
+    pub(crate) async fn evaluate_contract_profitability(
+        &self,
+        cargo: &Cargo,
+        ship_location: &WaypointSymbol,
+        engine_speed: u32,
+        contract: &Contract,
+    ) -> Result<ContractProfitability> {
+        let latest_market_entries: Vec<MarketEntry> = self
+            .blackboard
+            .get_latest_market_entries(&ship_location.system_symbol())
+            .await?;
+
+        let waypoints_of_system = self
+            .blackboard
+            .get_waypoints_of_system(&ship_location.system_symbol())
+            .await?;
+
+        let result = contract_manager::calculate_necessary_tickets_for_contract(cargo, ship_location, contract, &latest_market_entries, &waypoints_of_system)?;
+
+        Ok(self
+            .contract_manager
+            .evaluate_profitability(&result, ship_location, &waypoints_of_system, engine_speed, Utc::now()))
+    }
+
     pub(crate) async fn create_contract_tickets(
         &self,
         ship_symbol: &ShipSymbol,
@@ -100,6 +132,7 @@ impl BehaviorArgs {
         ship_location: &WaypointSymbol,
         contract: &Contract,
         fleet_id: &FleetId,
+        scope: &ContractTicketScope,
     ) -> Result<bool> {
         let latest_market_entries: Vec<MarketEntry> = self
             .blackboard
@@ -111,7 +144,7 @@ impl BehaviorArgs {
             .get_waypoints_of_system(&ship_location.system_symbol())
             .await?;
 
-        let result = calculate_necessary_tickets_for_contract(ship_cargo, ship_location, contract, &latest_market_entries, &waypoints_of_system)?;
+        let result = calculate_necessary_tickets_for_contract_share(ship_cargo, ship_location, contract, &latest_market_entries, &waypoints_of_system, scope)?;
 
         let required_capital = result.required_capital();
 
@@ -0,0 +1,481 @@
+use crate::behavior_tree::behavior_tree::{Behavior, ParallelPolicy};
+use crate::behavior_tree::ship_behaviors::{ship_behaviors, ship_behaviors_with_sub_behavior_overrides, Behaviors, ShipAction};
+use crate::hot_reload::HotReloadable;
+use anyhow::{anyhow, bail, Context, Result};
+use itertools::Itertools;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::hash::Hash;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// File representation of a behavior tree.
+///
+/// Mirrors `Behavior<A>` without the indices and adds `SubBehavior` for referencing other trees by name.
+/// Names are either the file stem of another definition file or one of the labels of `Behaviors::to_labelled_sub_behaviors`.
+///
+/// json: `{"Sequence": [{"SubBehavior": "orbit_if_necessary"}, {"Action": "Refuel"}]}`
+/// ron:  `Sequence([SubBehavior("orbit_if_necessary"), Action(Refuel)])`
+/// yaml: `!Sequence [!SubBehavior orbit_if_necessary, !Action Refuel]`
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub enum BehaviorDefinition<A> {
+    Action(A),
+    Invert(Box<BehaviorDefinition<A>>),
+    Select(Vec<BehaviorDefinition<A>>),
+    Sequence(Vec<BehaviorDefinition<A>>),
+    While {
+        // optional here, so that a missing condition is reported by the validation and not as a cryptic parse error
+        condition: Option<Box<BehaviorDefinition<A>>>,
+        action: Box<BehaviorDefinition<A>>,
+    },
+    Parallel {
+        children: Vec<BehaviorDefinition<A>>,
+        success_policy: ParallelPolicy,
+        failure_policy: ParallelPolicy,
+    },
+    Retry {
+        child: Box<BehaviorDefinition<A>>,
+        max_attempts: u32,
+        backoff_ms: u64,
+    },
+    Timeout {
+        child: Box<BehaviorDefinition<A>>,
+        duration_ms: u64,
+    },
+    Cooldown {
+        child: Box<BehaviorDefinition<A>>,
+        duration_ms: u64,
+    },
+    AlwaysSucceed(Box<BehaviorDefinition<A>>),
+    SubBehavior(String),
+}
+
+impl<A: Clone + Display + Hash> BehaviorDefinition<A> {
+    fn collect_validation_errors(&self, path: &str, known_names: &[String], errors: &mut Vec<String>) {
+        match self {
+            BehaviorDefinition::Action(_) => {}
+            BehaviorDefinition::Invert(child) => child.collect_validation_errors(&format!("{path}/Invert"), known_names, errors),
+            BehaviorDefinition::Select(children) | BehaviorDefinition::Sequence(children) => {
+                let node_name = if matches!(self, BehaviorDefinition::Select(_)) {
+                    "Select"
+                } else {
+                    "Sequence"
+                };
+                if children.is_empty() {
+                    errors.push(format!("{path}: {node_name} without children"));
+                }
+                for (idx, child) in children.iter().enumerate() {
+                    child.collect_validation_errors(&format!("{path}/{node_name}[{idx}]"), known_names, errors);
+                }
+            }
+            BehaviorDefinition::While { condition, action } => {
+                match condition {
+                    None => errors.push(format!("{path}: While without condition")),
+                    Some(condition) => condition.collect_validation_errors(&format!("{path}/While.condition"), known_names, errors),
+                }
+                action.collect_validation_errors(&format!("{path}/While.action"), known_names, errors);
+            }
+            BehaviorDefinition::Parallel { children, .. } => {
+                if children.is_empty() {
+                    errors.push(format!("{path}: Parallel without children"));
+                }
+                for (idx, child) in children.iter().enumerate() {
+                    child.collect_validation_errors(&format!("{path}/Parallel[{idx}]"), known_names, errors);
+                }
+            }
+            BehaviorDefinition::Retry { child, max_attempts, .. } => {
+                if *max_attempts == 0 {
+                    errors.push(format!("{path}: Retry with max_attempts 0"));
+                }
+                child.collect_validation_errors(&format!("{path}/Retry"), known_names, errors);
+            }
+            BehaviorDefinition::Timeout { child, duration_ms } => {
+                if *duration_ms == 0 {
+                    errors.push(format!("{path}: Timeout with duration_ms 0"));
+                }
+                child.collect_validation_errors(&format!("{path}/Timeout"), known_names, errors);
+            }
+            BehaviorDefinition::Cooldown { child, .. } => child.collect_validation_errors(&format!("{path}/Cooldown"), known_names, errors),
+            BehaviorDefinition::AlwaysSucceed(child) => child.collect_validation_errors(&format!("{path}/AlwaysSucceed"), known_names, errors),
+            BehaviorDefinition::SubBehavior(name) => {
+                if !known_names.contains(name) {
+                    errors.push(format!("{path}: unknown SubBehavior '{name}'"));
+                }
+            }
+        }
+    }
+
+    fn resolve(
+        &self,
+        definitions: &HashMap<String, BehaviorDefinition<A>>,
+        builtin_sub_behaviors: &HashMap<String, Behavior<A>>,
+        resolution_stack: &mut Vec<String>,
+    ) -> Result<Behavior<A>> {
+        let resolve_all = |children: &[BehaviorDefinition<A>], resolution_stack: &mut Vec<String>| {
+            children
+                .iter()
+                .map(|child| child.resolve(definitions, builtin_sub_behaviors, resolution_stack))
+                .collect::<Result<Vec<_>>>()
+        };
+
+        match self {
+            BehaviorDefinition::Action(a) => Ok(Behavior::new_action(a.clone())),
+            BehaviorDefinition::Invert(child) => Ok(Behavior::new_invert(child.resolve(definitions, builtin_sub_behaviors, resolution_stack)?)),
+            BehaviorDefinition::Select(children) => Ok(Behavior::new_select(resolve_all(children, resolution_stack)?)),
+            BehaviorDefinition::Sequence(children) => Ok(Behavior::new_sequence(resolve_all(children, resolution_stack)?)),
+            BehaviorDefinition::While { condition, action } => {
+                let condition = condition
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("While without condition"))?;
+                Ok(Behavior::new_while(
+                    condition.resolve(definitions, builtin_sub_behaviors, resolution_stack)?,
+                    action.resolve(definitions, builtin_sub_behaviors, resolution_stack)?,
+                ))
+            }
+            BehaviorDefinition::Parallel {
+                children,
+                success_policy,
+                failure_policy,
+            } => Ok(Behavior::new_parallel(
+                resolve_all(children, resolution_stack)?,
+                *success_policy,
+                *failure_policy,
+            )),
+            BehaviorDefinition::Retry {
+                child,
+                max_attempts,
+                backoff_ms,
+            } => Ok(Behavior::new_retry(
+                child.resolve(definitions, builtin_sub_behaviors, resolution_stack)?,
+                *max_attempts,
+                Duration::from_millis(*backoff_ms),
+            )),
+            BehaviorDefinition::Timeout { child, duration_ms } => Ok(Behavior::new_timeout(
+                child.resolve(definitions, builtin_sub_behaviors, resolution_stack)?,
+                Duration::from_millis(*duration_ms),
+            )),
+            BehaviorDefinition::Cooldown { child, duration_ms } => Ok(Behavior::new_cooldown(
+                child.resolve(definitions, builtin_sub_behaviors, resolution_stack)?,
+                Duration::from_millis(*duration_ms),
+            )),
+            BehaviorDefinition::AlwaysSucceed(child) => Ok(Behavior::new_always_succeed(child.resolve(
+                definitions,
+                builtin_sub_behaviors,
+                resolution_stack,
+            )?)),
+            BehaviorDefinition::SubBehavior(name) => resolve_named_behavior(name, definitions, builtin_sub_behaviors, resolution_stack),
+        }
+    }
+}
+
+fn resolve_named_behavior<A: Clone + Display + Hash>(
+    name: &str,
+    definitions: &HashMap<String, BehaviorDefinition<A>>,
+    builtin_sub_behaviors: &HashMap<String, Behavior<A>>,
+    resolution_stack: &mut Vec<String>,
+) -> Result<Behavior<A>> {
+    if resolution_stack.iter().any(|n| n == name) {
+        bail!("cyclic SubBehavior reference: {} -> {}", resolution_stack.join(" -> "), name);
+    }
+
+    // definitions from files take precedence over the builtin sub-behaviors
+    match (definitions.get(name), builtin_sub_behaviors.get(name)) {
+        (Some(definition), _) => {
+            resolution_stack.push(name.to_string());
+            let result = definition.resolve(definitions, builtin_sub_behaviors, resolution_stack);
+            resolution_stack.pop();
+            result
+        }
+        (None, Some(builtin)) => Ok(builtin.clone()),
+        (None, None) => Err(anyhow!("unknown SubBehavior '{name}'")),
+    }
+}
+
+/// Validates all definitions and resolves their `SubBehavior` references.
+/// All validation errors are collected, so that a broken definition directory can be fixed in one go.
+pub fn resolve_behavior_definitions<A: Clone + Display + Hash>(
+    definitions: &HashMap<String, BehaviorDefinition<A>>,
+    builtin_sub_behaviors: &HashMap<String, Behavior<A>>,
+) -> Result<HashMap<String, Behavior<A>>> {
+    let known_names = definitions
+        .keys()
+        .chain(builtin_sub_behaviors.keys())
+        .cloned()
+        .unique()
+        .collect_vec();
+
+    let mut errors = vec![];
+    for (name, definition) in definitions
+        .iter()
+        .sorted_by_key(|(name, _)| name.to_string())
+    {
+        definition.collect_validation_errors(name, &known_names, &mut errors);
+    }
+
+    if errors.is_empty() {
+        // only check for cycles if the definitions are structurally valid
+        for name in definitions.keys().sorted() {
+            if let Err(err) = resolve_named_behavior(name, definitions, builtin_sub_behaviors, &mut vec![]) {
+                errors.push(format!("{name}: {err}"));
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        bail!("Invalid behavior definitions:\n{}", errors.join("\n"));
+    }
+
+    definitions
+        .keys()
+        .map(|name| {
+            let mut behavior = resolve_named_behavior(name, definitions, builtin_sub_behaviors, &mut vec![])?;
+            behavior.update_indices();
+            Ok((name.clone(), behavior))
+        })
+        .collect()
+}
+
+pub fn parse_behavior_definition<A: DeserializeOwned>(path: &Path, content: &str) -> Result<BehaviorDefinition<A>> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => serde_json::from_str(content).map_err(|e| anyhow!(e)),
+        Some("ron") => ron::from_str(content).map_err(|e| anyhow!(e)),
+        Some("yaml") | Some("yml") => serde_yaml::from_str(content).map_err(|e| anyhow!(e)),
+        _ => Err(anyhow!("unsupported file extension")),
+    }
+    .with_context(|| format!("Unable to parse behavior definition {}", path.display()))
+}
+
+fn is_behavior_definition_file(path: &Path) -> bool {
+    matches!(path.extension().and_then(|ext| ext.to_str()), Some("json" | "ron" | "yaml" | "yml"))
+}
+
+/// Loads all definition files of the directory. The file stem is the name of the behavior.
+/// Behaviors with the name of a `Behaviors` field replace the builtin tree, all others can be used as sub-behaviors.
+/// A replaced sub-behavior also replaces its copies in the builtin trees that embed it.
+pub fn load_ship_behaviors_from_dir(definition_dir: &Path) -> Result<Behaviors> {
+    let mut definitions: HashMap<String, BehaviorDefinition<ShipAction>> = HashMap::new();
+    for path in list_definition_files(definition_dir)? {
+        let name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| anyhow!("Invalid file name {}", path.display()))?
+            .to_string();
+
+        if definitions.contains_key(&name) {
+            bail!("Behavior '{name}' is defined more than once in {}", definition_dir.display());
+        }
+
+        let content = std::fs::read_to_string(&path).with_context(|| format!("Unable to read {}", path.display()))?;
+        definitions.insert(name, parse_behavior_definition(&path, &content)?);
+    }
+
+    let overridden_sub_behaviors = resolve_behavior_definitions(&definitions, &ship_behaviors().to_labelled_sub_behaviors())?;
+    let mut behaviors = ship_behaviors_with_sub_behavior_overrides(&overridden_sub_behaviors);
+
+    // resolve again, so that definitions referencing a builtin composite (e.g. navigate_to_destination) get the one with the overrides
+    let resolved = resolve_behavior_definitions(&definitions, &behaviors.to_labelled_sub_behaviors())?;
+
+    for (name, behavior) in resolved {
+        if let Some(target) = behaviors.get_behavior_mut(&name) {
+            *target = behavior;
+        }
+    }
+
+    Ok(behaviors)
+}
+
+fn list_definition_files(definition_dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = std::fs::read_dir(definition_dir).with_context(|| format!("Unable to read behavior definition dir {}", definition_dir.display()))?;
+
+    let mut files = vec![];
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_file() && is_behavior_definition_file(&path) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Hands out the behavior trees for ships.
+///
+/// Without a definition dir the builtin trees from `ship_behaviors()` are used.
+/// With a definition dir, changes to the files are picked up the next time a ship starts a tree.
+/// If a changed definition is invalid, the error is logged and the previously loaded trees stay active.
+#[derive(Clone)]
+pub struct ShipBehaviorStore {
+    behaviors: HotReloadable<Behaviors>,
+}
+
+impl Default for ShipBehaviorStore {
+    fn default() -> Self {
+        Self {
+            behaviors: HotReloadable::fixed(ship_behaviors()),
+        }
+    }
+}
+
+impl ShipBehaviorStore {
+    /// Fails if the initial definitions are invalid - we don't want to start the agent with broken trees.
+    pub fn new(maybe_definition_dir: Option<PathBuf>) -> Result<Self> {
+        match maybe_definition_dir {
+            None => Ok(Self::default()),
+            Some(definition_dir) => Ok(Self {
+                behaviors: HotReloadable::load(definition_dir, "behavior definitions", list_definition_files, load_ship_behaviors_from_dir)?,
+            }),
+        }
+    }
+
+    pub fn get_behaviors(&self) -> Behaviors {
+        self.behaviors.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::behavior_tree::ship_behaviors::ShipAction::*;
+
+    fn builtin_sub_behaviors() -> HashMap<String, Behavior<ShipAction>> {
+        ship_behaviors().to_labelled_sub_behaviors()
+    }
+
+    #[test]
+    fn parses_all_supported_formats() {
+        let json = r#"{"Sequence": [{"SubBehavior": "orbit_if_necessary"}, {"Action": "Refuel"}]}"#;
+        let ron = r#"Sequence([SubBehavior("orbit_if_necessary"), Action(Refuel)])"#;
+        let yaml = "!Sequence\n- !SubBehavior orbit_if_necessary\n- !Action Refuel\n";
+
+        let expected = BehaviorDefinition::Sequence(vec![
+            BehaviorDefinition::SubBehavior("orbit_if_necessary".to_string()),
+            BehaviorDefinition::Action(Refuel),
+        ]);
+
+        let from_json: BehaviorDefinition<ShipAction> = parse_behavior_definition(Path::new("a.json"), json).unwrap();
+        let from_ron: BehaviorDefinition<ShipAction> = parse_behavior_definition(Path::new("a.ron"), ron).unwrap();
+        let from_yaml: BehaviorDefinition<ShipAction> = parse_behavior_definition(Path::new("a.yaml"), yaml).unwrap();
+
+        assert_eq!(from_json, expected);
+        assert_eq!(from_ron, expected);
+        assert_eq!(from_yaml, expected);
+    }
+
+    #[test]
+    fn resolves_sub_behaviors_from_builtins_and_other_definitions() {
+        let definitions = HashMap::from([
+            (
+                "refuel_and_wait".to_string(),
+                BehaviorDefinition::Sequence(vec![
+                    BehaviorDefinition::SubBehavior("wait_for_arrival_bt".to_string()),
+                    BehaviorDefinition::Action(Refuel),
+                ]),
+            ),
+            (
+                "miner_behavior".to_string(),
+                BehaviorDefinition::While {
+                    condition: Some(Box::new(BehaviorDefinition::Action(HasCargoSpaceForMining))),
+                    action: Box::new(BehaviorDefinition::SubBehavior("refuel_and_wait".to_string())),
+                },
+            ),
+        ]);
+
+        let builtin = builtin_sub_behaviors();
+        let resolved = resolve_behavior_definitions(&definitions, &builtin).unwrap();
+
+        let mut expected = Behavior::new_while(
+            Behavior::new_action(HasCargoSpaceForMining),
+            Behavior::new_sequence(vec![
+                builtin.get("wait_for_arrival_bt").unwrap().clone(),
+                Behavior::new_action(Refuel),
+            ]),
+        );
+        expected.update_indices();
+
+        assert_eq!(resolved.get("miner_behavior"), Some(&expected));
+    }
+
+    #[test]
+    fn collects_all_validation_errors() {
+        let definitions = HashMap::from([
+            (
+                "miner_behavior".to_string(),
+                BehaviorDefinition::Sequence(vec![
+                    BehaviorDefinition::While {
+                        condition: None,
+                        action: Box::new(BehaviorDefinition::Action(ExtractResources)),
+                    },
+                    BehaviorDefinition::SubBehavior("does_not_exist".to_string()),
+                ]),
+            ),
+            ("trading_behavior".to_string(), BehaviorDefinition::Select(vec![])),
+        ]);
+
+        let err = resolve_behavior_definitions(&definitions, &builtin_sub_behaviors())
+            .unwrap_err()
+            .to_string();
+
+        assert!(err.contains("miner_behavior/Sequence[0]: While without condition"), "{err}");
+        assert!(err.contains("miner_behavior/Sequence[1]: unknown SubBehavior 'does_not_exist'"), "{err}");
+        assert!(err.contains("trading_behavior: Select without children"), "{err}");
+    }
+
+    #[test]
+    fn resolves_decorators() {
+        let json = r#"{"Retry": {"child": {"Timeout": {"child": {"Action": "WaitForArrival"}, "duration_ms": 60000}}, "max_attempts": 3, "backoff_ms": 500}}"#;
+        let definition: BehaviorDefinition<ShipAction> = parse_behavior_definition(Path::new("a.json"), json).unwrap();
+
+        let resolved = resolve_behavior_definitions(&HashMap::from([("a".to_string(), definition)]), &HashMap::new()).unwrap();
+
+        let mut expected = Behavior::new_retry(
+            Behavior::new_timeout(Behavior::new_action(WaitForArrival), Duration::from_secs(60)),
+            3,
+            Duration::from_millis(500),
+        );
+        expected.update_indices();
+
+        assert_eq!(resolved.get("a"), Some(&expected));
+    }
+
+    #[test]
+    fn overridden_sub_behaviors_reach_the_builtin_trees_that_embed_them() {
+        let dir = std::env::temp_dir().join(format!("behavior_definitions_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("orbit_if_necessary.ron"),
+            "Select([Action(IsInOrbit), Sequence([Action(PrintExploreLocations), Action(Orbit)])])",
+        )
+        .unwrap();
+
+        let behaviors = load_ship_behaviors_from_dir(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let builtin = ship_behaviors();
+        assert!(!format!("{:?}", builtin.miner_behavior).contains("PrintExploreLocations"));
+
+        // miner_behavior embeds orbit_if_necessary directly, navigate_to_destination via the refuel actions
+        assert!(format!("{:?}", behaviors.miner_behavior).contains("PrintExploreLocations"));
+        assert!(format!("{:?}", behaviors.navigate_to_destination).contains("PrintExploreLocations"));
+        assert!(format!("{:?}", behaviors.refuel_behavior).contains("PrintExploreLocations"));
+        assert_eq!(behaviors.dock_if_necessary, builtin.dock_if_necessary);
+    }
+
+    #[test]
+    fn detects_cyclic_references() {
+        let definitions = HashMap::from([
+            ("a".to_string(), BehaviorDefinition::SubBehavior("b".to_string())),
+            (
+                "b".to_string(),
+                BehaviorDefinition::Invert(Box::new(BehaviorDefinition::SubBehavior("a".to_string()))),
+            ),
+        ]);
+
+        let err = resolve_behavior_definitions::<ShipAction>(&definitions, &HashMap::new())
+            .unwrap_err()
+            .to_string();
+
+        assert!(err.contains("cyclic SubBehavior reference"), "{err}");
+    }
+}
@@ -2,6 +2,8 @@ use crate::behavior_tree::ship_behaviors::ShipAction;
 use crate::ship::ShipOperations;
 use anyhow::anyhow;
 use async_trait::async_trait;
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use st_domain::budgeting::treasury_redesign::FinanceTicket;
@@ -14,11 +16,11 @@ use std::sync::Arc;
 use std::time::Duration;
 use strum::Display;
 use tokio::sync::mpsc::Sender;
-use tokio::time::sleep;
+use tokio::time::{sleep, Instant};
 use tracing::{event, Level};
 // inspired by @chamlis design from spacetraders discord
 
-#[derive(Debug, Clone, Serialize, Eq, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub enum Behavior<A: Display> {
     Action(A, Option<usize>),
     Invert(Box<Behavior<A>>, Option<usize>),
@@ -32,6 +34,71 @@ pub enum Behavior<A: Display> {
         action: Box<Behavior<A>>,
         index: Option<usize>,
     },
+
+    // Runs all children concurrently. Each child works on its own copy of the state.
+    // The node finishes as soon as one of the policies is met and keeps the state of the child that decided the outcome.
+    Parallel {
+        children: Vec<Behavior<A>>,
+        success_policy: ParallelPolicy,
+        failure_policy: ParallelPolicy,
+        index: Option<usize>,
+    },
+
+    // Runs the child up to max_attempts times until it succeeds. Sleeps for backoff between the attempts.
+    Retry {
+        child: Box<Behavior<A>>,
+        max_attempts: u32,
+        backoff: Duration,
+        index: Option<usize>,
+    },
+
+    // Fails if the child doesn't finish within the duration.
+    Timeout {
+        child: Box<Behavior<A>>,
+        duration: Duration,
+        index: Option<usize>,
+    },
+
+    // Fails without running the child if the child succeeded less than duration ago.
+    // The time of the last success is kept in the state (see CooldownState), so the cooldown outlasts the run of the tree.
+    Cooldown {
+        child: Box<Behavior<A>>,
+        duration: Duration,
+        index: Option<usize>,
+    },
+
+    // Runs the child and succeeds regardless of its result.
+    AlwaysSucceed(Box<Behavior<A>>, Option<usize>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Display)]
+pub enum ParallelPolicy {
+    RequireOne,
+    RequireAll,
+}
+
+impl ParallelPolicy {
+    fn is_met(&self, num_matching_children: usize, num_children: usize) -> bool {
+        match self {
+            ParallelPolicy::RequireOne => num_matching_children >= 1,
+            ParallelPolicy::RequireAll => num_matching_children == num_children,
+        }
+    }
+}
+
+/// Remembers when the children of `Cooldown` nodes succeeded the last time.
+/// The trees are cloned for every run, so this lives in the state of whoever runs the tree (e.g. the ship) - not in the tree.
+/// The nodes are identified by their hash, so identical `Cooldown` nodes share their cooldown.
+pub trait CooldownState {
+    fn last_success(&self, cooldown_hash: u64) -> Option<Instant>;
+    fn mark_success(&mut self, cooldown_hash: u64, at: Instant);
+
+    fn remaining_cooldown(&self, cooldown_hash: u64, duration: Duration) -> Option<Duration> {
+        let last_success = self.last_success(cooldown_hash)?;
+        duration
+            .checked_sub(last_success.elapsed())
+            .filter(|d| !d.is_zero())
+    }
 }
 
 impl<A: Display + Hash> Hash for Behavior<A> {
@@ -57,6 +124,32 @@ impl<A: Display + Hash> Hash for Behavior<A> {
                 condition.hash(state);
                 action.hash(state);
             }
+            Behavior::Parallel {
+                children,
+                success_policy,
+                failure_policy,
+                ..
+            } => {
+                for child in children {
+                    child.hash(state);
+                }
+                success_policy.hash(state);
+                failure_policy.hash(state);
+            }
+            Behavior::Retry {
+                child, max_attempts, backoff, ..
+            } => {
+                child.hash(state);
+                max_attempts.hash(state);
+                backoff.hash(state);
+            }
+            Behavior::Timeout { child, duration, .. } | Behavior::Cooldown { child, duration, .. } => {
+                child.hash(state);
+                duration.hash(state);
+            }
+            Behavior::AlwaysSucceed(child, _) => {
+                child.hash(state);
+            }
         }
     }
 }
@@ -86,6 +179,44 @@ impl<A: Display + Hash> Behavior<A> {
         }
     }
 
+    pub fn new_parallel(children: Vec<Behavior<A>>, success_policy: ParallelPolicy, failure_policy: ParallelPolicy) -> Self {
+        Behavior::Parallel {
+            children,
+            success_policy,
+            failure_policy,
+            index: None,
+        }
+    }
+
+    pub fn new_retry(child: Behavior<A>, max_attempts: u32, backoff: Duration) -> Self {
+        Behavior::Retry {
+            child: Box::new(child),
+            max_attempts,
+            backoff,
+            index: None,
+        }
+    }
+
+    pub fn new_timeout(child: Behavior<A>, duration: Duration) -> Self {
+        Behavior::Timeout {
+            child: Box::new(child),
+            duration,
+            index: None,
+        }
+    }
+
+    pub fn new_cooldown(child: Behavior<A>, duration: Duration) -> Self {
+        Behavior::Cooldown {
+            child: Box::new(child),
+            duration,
+            index: None,
+        }
+    }
+
+    pub fn new_always_succeed(child: Behavior<A>) -> Self {
+        Behavior::AlwaysSucceed(Box::new(child), None)
+    }
+
     fn calculate_hash(&self) -> u64 {
         let mut hasher = DefaultHasher::new();
         self.hash(&mut hasher);
@@ -99,6 +230,11 @@ impl<A: Display + Hash> Behavior<A> {
             Behavior::Select(_, index) => *index,
             Behavior::Sequence(_, index) => *index,
             Behavior::While { index, .. } => *index,
+            Behavior::Parallel { index, .. } => *index,
+            Behavior::Retry { index, .. } => *index,
+            Behavior::Timeout { index, .. } => *index,
+            Behavior::Cooldown { index, .. } => *index,
+            Behavior::AlwaysSucceed(_, index) => *index,
         }
     }
 
@@ -114,11 +250,15 @@ impl<A: Display + Hash> Behavior<A> {
 
         match self {
             Behavior::Action(_, index) => *index = Some(current_index),
-            Behavior::Invert(child, index) => {
+            Behavior::Invert(child, index)
+            | Behavior::AlwaysSucceed(child, index)
+            | Behavior::Retry { child, index, .. }
+            | Behavior::Timeout { child, index, .. }
+            | Behavior::Cooldown { child, index, .. } => {
                 *index = Some(current_index);
                 child.update_indices_recursive(next_index);
             }
-            Behavior::Select(children, index) | Behavior::Sequence(children, index) => {
+            Behavior::Select(children, index) | Behavior::Sequence(children, index) | Behavior::Parallel { children, index, .. } => {
                 *index = Some(current_index);
                 for child in children {
                     child.update_indices_recursive(next_index);
@@ -198,8 +338,12 @@ impl<A: Display + Hash> Behavior<A> {
 
         match self {
             Behavior::Action(_, _) => {}
-            Behavior::Invert(child, _) => child.build_mermaid(output, Some(current_index), labelled_sub_graphs),
-            Behavior::Select(children, _) | Behavior::Sequence(children, _) => {
+            Behavior::Invert(child, _)
+            | Behavior::AlwaysSucceed(child, _)
+            | Behavior::Retry { child, .. }
+            | Behavior::Timeout { child, .. }
+            | Behavior::Cooldown { child, .. } => child.build_mermaid(output, Some(current_index), labelled_sub_graphs),
+            Behavior::Select(children, _) | Behavior::Sequence(children, _) | Behavior::Parallel { children, .. } => {
                 for child in children {
                     child.build_mermaid(output, Some(current_index), labelled_sub_graphs);
                 }
@@ -220,6 +364,15 @@ impl<A: Display> Display for Behavior<A> {
             Behavior::Select(..) => write!(f, "Select"),
             Behavior::Sequence(..) => write!(f, "Sequence"),
             Behavior::While { .. } => write!(f, "While"),
+            Behavior::Parallel {
+                success_policy,
+                failure_policy,
+                ..
+            } => write!(f, "Parallel(success: {}, failure: {})", success_policy, failure_policy),
+            Behavior::Retry { max_attempts, backoff, .. } => write!(f, "Retry({}x, backoff: {:?})", max_attempts, backoff),
+            Behavior::Timeout { duration, .. } => write!(f, "Timeout({:?})", duration),
+            Behavior::Cooldown { duration, .. } => write!(f, "Cooldown({:?})", duration),
+            Behavior::AlwaysSucceed(..) => write!(f, "AlwaysSucceed"),
         }
     }
 }
@@ -270,6 +423,7 @@ pub trait Actionable: Serialize + Clone + Send + Sync {
 impl<A> Actionable for Behavior<A>
 where
     A: Actionable + Serialize + Display + Hash + PartialEq + 'static,
+    <A as Actionable>::ActionState: CooldownState,
 {
     type ActionError = <A as Actionable>::ActionError;
     type ActionArgs = <A as Actionable>::ActionArgs;
@@ -364,6 +518,124 @@ where
                     }
                 }
             },
+            Behavior::Parallel {
+                children,
+                success_policy,
+                failure_policy,
+                index,
+            } => {
+                let num_children = children.len();
+                let mut running_children: FuturesUnordered<_> = children
+                    .iter()
+                    .map(|child| {
+                        let mut child_state = state.clone();
+                        let state_changed_tx = state_changed_tx.clone();
+                        let action_completed_tx = action_completed_tx.clone();
+                        async move {
+                            let result = child
+                                .run(args, &mut child_state, sleep_duration, state_changed_tx, action_completed_tx)
+                                .await;
+                            (result, child_state)
+                        }
+                    })
+                    .collect();
+
+                let mut num_succeeded = 0;
+                let mut errors = vec![];
+                let mut maybe_outcome = None;
+
+                while let Some((result, child_state)) = running_children.next().await {
+                    match result {
+                        Ok(_) => num_succeeded += 1,
+                        Err(err) => errors.push(err.to_string()),
+                    }
+
+                    if success_policy.is_met(num_succeeded, num_children) {
+                        *state = child_state;
+                        maybe_outcome = Some(Ok(Response::Success));
+                        break;
+                    } else if failure_policy.is_met(errors.len(), num_children) {
+                        *state = child_state;
+                        maybe_outcome = Some(Err(Self::ActionError::from(anyhow!(
+                            "Parallel failed. Idx: {index:?}. Errors were: {}",
+                            errors.join("\n")
+                        ))));
+                        break;
+                    }
+                }
+
+                // dropping the futures cancels the children that are still running
+                drop(running_children);
+
+                maybe_outcome.unwrap_or_else(|| {
+                    Err(Self::ActionError::from(anyhow!(
+                        "Parallel finished without meeting a policy. Idx: {index:?}. Errors were: {}",
+                        errors.join("\n")
+                    )))
+                })
+            }
+            Behavior::Retry {
+                child, max_attempts, backoff, ..
+            } => {
+                let mut attempt = 1;
+                loop {
+                    let result = child
+                        .run(args, state, sleep_duration, state_changed_tx.clone(), action_completed_tx.clone())
+                        .await;
+                    match result {
+                        Ok(r) => break Ok(r),
+                        Err(err) if attempt >= *max_attempts => {
+                            let maybe_idx = child.index();
+                            break Err(Self::ActionError::from(anyhow!(
+                                "giving up after {attempt} attempts. Idx: {maybe_idx:?}. Error: {err}"
+                            )));
+                        }
+                        Err(err) => {
+                            event!(
+                                Level::DEBUG,
+                                message = "Retrying failed behavior",
+                                index = child.index(),
+                                attempt,
+                                max_attempts,
+                                error = %err,
+                            );
+                            attempt += 1;
+                            sleep(*backoff).await;
+                        }
+                    }
+                }
+            }
+            Behavior::Timeout { child, duration, .. } => {
+                let run_child = child.run(args, state, sleep_duration, state_changed_tx.clone(), action_completed_tx.clone());
+                match tokio::time::timeout(*duration, run_child).await {
+                    Ok(result) => result,
+                    Err(_) => {
+                        let maybe_idx = child.index();
+                        Err(Self::ActionError::from(anyhow!("timed out after {duration:?}. Idx: {maybe_idx:?}")))
+                    }
+                }
+            }
+            Behavior::Cooldown { child, duration, .. } => match state.remaining_cooldown(hash, *duration) {
+                Some(remaining) => Err(Self::ActionError::from(anyhow!("cooling down for another {remaining:?}"))),
+                None => {
+                    let result = child
+                        .run(args, state, sleep_duration, state_changed_tx.clone(), action_completed_tx.clone())
+                        .await;
+                    if result.is_ok() {
+                        state.mark_success(hash, Instant::now());
+                    }
+                    result
+                }
+            },
+            Behavior::AlwaysSucceed(child, _) => {
+                if let Err(err) = child
+                    .run(args, state, sleep_duration, state_changed_tx.clone(), action_completed_tx.clone())
+                    .await
+                {
+                    event!(Level::DEBUG, message = "Ignoring failed behavior", index = child.index(), error = %err);
+                }
+                Ok(Response::Success)
+            }
         };
         match &result {
             Ok(o) => {
@@ -410,20 +682,23 @@ pub fn compute_sub_behavior_hashes<A: Display + Hash>(labelled_sub_behaviors: &H
 
 #[cfg(test)]
 mod tests {
-    use super::{ActionEvent, Actionable, Behavior, Response};
+    use super::{ActionEvent, Actionable, Behavior, CooldownState, ParallelPolicy, Response};
     use anyhow::anyhow;
     use async_trait::async_trait;
     use core::time::Duration;
     use serde::Serialize;
+    use std::collections::HashMap;
     use strum::Display;
     use tokio::sync::mpsc;
     use tokio::sync::mpsc::Sender;
+    use tokio::time::Instant;
 
     #[derive(Clone, Debug, Serialize, PartialEq, Display, Hash)]
     enum MyAction {
         Increase,
         Decrease,
         IsLowerThan2,
+        WaitLong,
     }
 
     #[async_trait]
@@ -456,12 +731,32 @@ mod tests {
                         Err(anyhow!(">= 2"))
                     }
                 }
+                MyAction::WaitLong => {
+                    tokio::time::sleep(Duration::from_secs(10)).await;
+                    Ok(Response::Success)
+                }
             }
         }
     }
 
     #[derive(Debug, Eq, PartialEq, Clone)]
-    struct MyState(i32);
+    struct MyState(i32, HashMap<u64, Instant>);
+
+    impl MyState {
+        fn new(value: i32) -> Self {
+            Self(value, HashMap::new())
+        }
+    }
+
+    impl CooldownState for MyState {
+        fn last_success(&self, cooldown_hash: u64) -> Option<Instant> {
+            self.1.get(&cooldown_hash).copied()
+        }
+
+        fn mark_success(&mut self, cooldown_hash: u64, at: Instant) {
+            self.1.insert(cooldown_hash, at);
+        }
+    }
 
     #[tokio::test]
     async fn test_select() {
@@ -470,7 +765,7 @@ mod tests {
             Behavior::new_action(MyAction::Decrease),
         ]);
 
-        let mut my_state = MyState(0);
+        let mut my_state = MyState::new(0);
         let (tx, rx) = mpsc::channel(32);
         let (tx2, rx2) = mpsc::channel(32);
 
@@ -478,7 +773,7 @@ mod tests {
             .await
             .unwrap();
         println!("{:?}", my_state);
-        assert_eq!(my_state, MyState(1));
+        assert_eq!(my_state.0, 1);
     }
 
     #[tokio::test]
@@ -488,7 +783,7 @@ mod tests {
             Behavior::new_action(MyAction::Decrease),
         ]);
 
-        let mut my_state = MyState(0);
+        let mut my_state = MyState::new(0);
         let (tx, rx) = mpsc::channel(32);
         let (tx2, rx2) = mpsc::channel(32);
 
@@ -496,14 +791,14 @@ mod tests {
             .await
             .unwrap();
         println!("{:?}", my_state);
-        assert_eq!(my_state, MyState(0));
+        assert_eq!(my_state.0, 0);
     }
 
     #[tokio::test]
     async fn test_while() {
         let bt: Behavior<MyAction> = Behavior::new_while(Behavior::new_action(MyAction::IsLowerThan2), Behavior::new_action(MyAction::Increase));
 
-        let mut my_state = MyState(0);
+        let mut my_state = MyState::new(0);
         let (tx, rx) = mpsc::channel(32);
         let (tx2, rx2) = mpsc::channel(32);
 
@@ -512,14 +807,14 @@ mod tests {
             .unwrap();
 
         println!("{:?}", my_state);
-        assert_eq!(my_state, MyState(2));
+        assert_eq!(my_state.0, 2);
     }
 
     #[tokio::test]
     async fn test_while_terminating_immediately() {
         let bt: Behavior<MyAction> = Behavior::new_while(Behavior::new_action(MyAction::IsLowerThan2), Behavior::new_action(MyAction::Increase));
 
-        let mut my_state = MyState(0);
+        let mut my_state = MyState::new(0);
         let (tx, rx) = mpsc::channel(32);
         let (tx2, rx2) = mpsc::channel(32);
 
@@ -527,7 +822,7 @@ mod tests {
             .run(&(), &mut my_state, Duration::from_millis(1), tx, tx2)
             .await;
         println!("{:?}", my_state);
-        assert_eq!(my_state, MyState(2));
+        assert_eq!(my_state.0, 2);
         result.is_ok();
     }
 
@@ -552,4 +847,170 @@ mod tests {
         let mermaid_string = bt.to_mermaid();
         println!("mermaid graph\n{}", mermaid_string)
     }
+
+    #[tokio::test]
+    async fn test_retry() {
+        // fails on the first attempt (state 1) and succeeds on the second one (state 2)
+        let increase_until_2 = Behavior::new_sequence(vec![
+            Behavior::new_action(MyAction::Increase),
+            Behavior::new_invert(Behavior::new_action(MyAction::IsLowerThan2)),
+        ]);
+
+        let mut my_state = MyState::new(0);
+        let (tx, rx) = mpsc::channel(32);
+        let (tx2, rx2) = mpsc::channel(32);
+
+        let result = Behavior::new_retry(increase_until_2.clone(), 1, Duration::from_millis(1))
+            .run(&(), &mut my_state, Duration::from_millis(1), tx.clone(), tx2.clone())
+            .await;
+        assert!(result.is_err());
+        assert_eq!(my_state.0, 1);
+
+        let mut my_state = MyState::new(0);
+        let result = Behavior::new_retry(increase_until_2, 3, Duration::from_millis(1))
+            .run(&(), &mut my_state, Duration::from_millis(1), tx, tx2)
+            .await;
+        assert!(result.is_ok());
+        assert_eq!(my_state.0, 2);
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        let mut my_state = MyState::new(0);
+        let (tx, rx) = mpsc::channel(32);
+        let (tx2, rx2) = mpsc::channel(32);
+
+        let result = Behavior::new_timeout(Behavior::new_action(MyAction::WaitLong), Duration::from_millis(10))
+            .run(&(), &mut my_state, Duration::from_millis(1), tx.clone(), tx2.clone())
+            .await;
+        assert!(result.is_err());
+
+        let result = Behavior::new_timeout(Behavior::new_action(MyAction::Increase), Duration::from_millis(10))
+            .run(&(), &mut my_state, Duration::from_millis(1), tx, tx2)
+            .await;
+        assert!(result.is_ok());
+        assert_eq!(my_state.0, 1);
+    }
+
+    #[tokio::test]
+    async fn test_parallel() {
+        let (tx, rx) = mpsc::channel(32);
+        let (tx2, rx2) = mpsc::channel(32);
+
+        // the fast child decides the outcome - the slow one gets cancelled
+        let mut my_state = MyState::new(0);
+        let bt: Behavior<MyAction> = Behavior::new_parallel(
+            vec![
+                Behavior::new_action(MyAction::WaitLong),
+                Behavior::new_action(MyAction::Increase),
+            ],
+            ParallelPolicy::RequireOne,
+            ParallelPolicy::RequireAll,
+        );
+        let result = tokio::time::timeout(
+            Duration::from_secs(1),
+            bt.run(&(), &mut my_state, Duration::from_millis(1), tx.clone(), tx2.clone()),
+        )
+        .await
+        .expect("parallel should finish before the slow child");
+        assert!(result.is_ok());
+        assert_eq!(my_state.0, 1);
+
+        let mut my_state = MyState::new(5);
+        let bt: Behavior<MyAction> = Behavior::new_parallel(
+            vec![
+                Behavior::new_action(MyAction::IsLowerThan2),
+                Behavior::new_action(MyAction::Increase),
+            ],
+            ParallelPolicy::RequireAll,
+            ParallelPolicy::RequireOne,
+        );
+        let result = bt
+            .run(&(), &mut my_state, Duration::from_millis(1), tx, tx2)
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cooldown() {
+        let mut bt: Behavior<MyAction> = Behavior::new_cooldown(Behavior::new_action(MyAction::Increase), Duration::from_secs(1));
+        bt.update_indices();
+
+        let mut my_state = MyState::new(0);
+        let (tx, rx) = mpsc::channel(32);
+        let (tx2, rx2) = mpsc::channel(32);
+
+        assert!(bt
+            .run(&(), &mut my_state, Duration::from_millis(1), tx.clone(), tx2.clone())
+            .await
+            .is_ok());
+        assert!(bt
+            .run(&(), &mut my_state, Duration::from_millis(1), tx.clone(), tx2.clone())
+            .await
+            .is_err());
+        assert_eq!(my_state.0, 1);
+
+        // the cooldown is part of the state - a copy of the tree (like the ones the fleet runner starts for every task) keeps cooling down
+        let copy = bt.clone();
+        assert_eq!(copy, bt);
+        assert!(copy
+            .run(&(), &mut my_state, Duration::from_millis(1), tx.clone(), tx2.clone())
+            .await
+            .is_err());
+        assert_eq!(my_state.0, 1);
+
+        // another ship with its own state isn't affected by the cooldown
+        let mut other_state = MyState::new(0);
+        assert!(copy
+            .run(&(), &mut other_state, Duration::from_millis(1), tx.clone(), tx2.clone())
+            .await
+            .is_ok());
+        assert_eq!(other_state.0, 1);
+
+        // the child runs again once the cooldown passed
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        assert!(copy
+            .run(&(), &mut my_state, Duration::from_millis(1), tx, tx2)
+            .await
+            .is_ok());
+        assert_eq!(my_state.0, 2);
+    }
+
+    #[tokio::test]
+    async fn test_always_succeed() {
+        let mut my_state = MyState::new(5);
+        let (tx, rx) = mpsc::channel(32);
+        let (tx2, rx2) = mpsc::channel(32);
+
+        let result = Behavior::new_always_succeed(Behavior::new_action(MyAction::IsLowerThan2))
+            .run(&(), &mut my_state, Duration::from_millis(1), tx, tx2)
+            .await;
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_mermaid_with_decorators() {
+        let mut bt: Behavior<MyAction> = Behavior::new_retry(
+            Behavior::new_timeout(
+                Behavior::new_parallel(
+                    vec![
+                        Behavior::new_action(MyAction::Increase),
+                        Behavior::new_always_succeed(Behavior::new_action(MyAction::Decrease)),
+                    ],
+                    ParallelPolicy::RequireAll,
+                    ParallelPolicy::RequireOne,
+                ),
+                Duration::from_secs(1),
+            ),
+            3,
+            Duration::from_millis(500),
+        );
+        bt.update_indices();
+
+        let mermaid_string = bt.to_mermaid();
+        assert!(mermaid_string.contains("Retry(3x, backoff: 500ms)"));
+        assert!(mermaid_string.contains("Timeout(1s)"));
+        assert!(mermaid_string.contains("Parallel(success: RequireAll, failure: RequireOne)"));
+        assert!(mermaid_string.contains("node4 --> node5"));
+    }
 }
@@ -1,4 +1,5 @@
 pub mod actions;
 pub mod behavior_args;
+pub mod behavior_definitions;
 pub mod behavior_tree;
 pub mod ship_behaviors;
@@ -30,6 +30,7 @@ pub enum ShipAction {
     HasPermanentExploreLocationEntry,
     SetPermanentExploreLocationAsDestination,
     SetNextObservationTime,
+    RefreshMarketIfDue,
     SleepUntilNextObservationTimeOrShipPurchaseTicketHasBeenAssigned,
     IsAtDestination,
     IsAtObservationWaypoint,
@@ -47,6 +48,8 @@ pub enum ShipAction {
     SiphonResources,
     JettisonInvaluableCarboHydrates,
     HasCargoSpaceForSiphoning,
+    IsSiphoningDemanded,
+    SleepUntilSiphoningIsDemanded,
     SetSiphoningSiteAsDestination,
     IsAtSiphoningSite,
     WaitForCooldown,
@@ -60,13 +63,21 @@ pub enum ShipAction {
     SetMiningSiteAsDestination,
     IsAtMiningSite,
     AttemptCargoTransfer,
+    ShouldWaitForHauler,
+    WaitForHauler,
+    ShouldSellCargoAtDeliveryMarket,
+    JettisonCargoForMining,
     AnnounceHaulerReadyForPickup,
     IsHaulerFilledEnoughForDelivery,
     HasAsteroidReachedCriticalLimit,
     SleepForNextWaypointCriticalLimitCheck,
+    MoveToAlternativeMiningSite,
     NegotiateContract,
     AcceptContract,
     CanAffordContract,
+    IsContractProfitable,
+    DeclineContract,
+    HasOpenDeclinedContractOffer,
     HasAcceptedContract,
     HasActiveContract,
     FulfilContract,
@@ -74,6 +85,7 @@ pub enum ShipAction {
     CreateContractTicketsIfNecessary,
 }
 
+#[derive(Clone)]
 pub struct Behaviors {
     pub navigate_to_destination: Behavior<ShipAction>,
     pub adjust_flight_mode_if_necessary: Behavior<ShipAction>,
@@ -85,6 +97,7 @@ pub struct Behaviors {
     pub stationary_probe_behavior: Behavior<ShipAction>,
     pub trading_behavior: Behavior<ShipAction>,
     pub siphoning_behavior: Behavior<ShipAction>,
+    pub siphoning_hauler_behavior: Behavior<ShipAction>,
     pub mining_hauler_behavior: Behavior<ShipAction>,
     pub miner_behavior: Behavior<ShipAction>,
     pub contractor_behavior: Behavior<ShipAction>,
@@ -108,9 +121,38 @@ impl Behaviors {
 
         HashMap::from(all)
     }
+
+    pub fn get_behavior_mut(&mut self, name: &str) -> Option<&mut Behavior<ShipAction>> {
+        match name {
+            "navigate_to_destination" => Some(&mut self.navigate_to_destination),
+            "adjust_flight_mode_if_necessary" => Some(&mut self.adjust_flight_mode_if_necessary),
+            "orbit_if_necessary" => Some(&mut self.orbit_if_necessary),
+            "wait_for_arrival_bt" => Some(&mut self.wait_for_arrival_bt),
+            "dock_if_necessary" => Some(&mut self.dock_if_necessary),
+            "refuel_behavior" => Some(&mut self.refuel_behavior),
+            "explorer_behavior" => Some(&mut self.explorer_behavior),
+            "stationary_probe_behavior" => Some(&mut self.stationary_probe_behavior),
+            "trading_behavior" => Some(&mut self.trading_behavior),
+            "siphoning_behavior" => Some(&mut self.siphoning_behavior),
+            "siphoning_hauler_behavior" => Some(&mut self.siphoning_hauler_behavior),
+            "mining_hauler_behavior" => Some(&mut self.mining_hauler_behavior),
+            "miner_behavior" => Some(&mut self.miner_behavior),
+            "contractor_behavior" => Some(&mut self.contractor_behavior),
+            "surveyor_behavior" => Some(&mut self.surveyor_behavior),
+            _ => None,
+        }
+    }
 }
 
 pub fn ship_behaviors() -> Behaviors {
+    ship_behaviors_with_sub_behavior_overrides(&HashMap::new())
+}
+
+/// Builds the builtin trees with the overrides in place of the builtin sub-behaviors (keyed by the labels of `Behaviors::to_labelled_sub_behaviors`),
+/// so that an overridden sub-behavior also ends up in every tree that embeds it.
+pub fn ship_behaviors_with_sub_behavior_overrides(overrides: &HashMap<String, Behavior<ShipAction>>) -> Behaviors {
+    let sub_behavior = |label: &str, builtin: Behavior<ShipAction>| overrides.get(label).cloned().unwrap_or(builtin);
+
     /*
     /// Runs behaviors one by one until all succeeded.
     ///
@@ -128,29 +170,41 @@ pub fn ship_behaviors() -> Behaviors {
     Select(Vec<Behavior<A>>),
      */
 
-    let mut wait_for_arrival_bt = Behavior::new_sequence(vec![
-        Behavior::new_action(ShipAction::WaitForArrival),
-        Behavior::new_action(ShipAction::FixNavStatusIfNecessary),
-        Behavior::new_action(ShipAction::MarkTravelActionAsCompleteIfPossible),
-        Behavior::new_action(ShipAction::PrintTravelActions),
-    ]);
+    let mut wait_for_arrival_bt = sub_behavior(
+        "wait_for_arrival_bt",
+        Behavior::new_sequence(vec![
+            Behavior::new_action(ShipAction::WaitForArrival),
+            Behavior::new_action(ShipAction::FixNavStatusIfNecessary),
+            Behavior::new_action(ShipAction::MarkTravelActionAsCompleteIfPossible),
+            Behavior::new_action(ShipAction::PrintTravelActions),
+        ]),
+    );
 
     let wait_for_cooldown_bt = Behavior::new_sequence(vec![Behavior::new_action(ShipAction::WaitForCooldown)]);
 
-    let mut orbit_if_necessary = Behavior::new_select(vec![
-        Behavior::new_action(ShipAction::IsInOrbit),
-        Behavior::new_action(ShipAction::Orbit),
-    ]);
+    let mut orbit_if_necessary = sub_behavior(
+        "orbit_if_necessary",
+        Behavior::new_select(vec![
+            Behavior::new_action(ShipAction::IsInOrbit),
+            Behavior::new_action(ShipAction::Orbit),
+        ]),
+    );
 
-    let mut dock_if_necessary = Behavior::new_select(vec![
-        Behavior::new_action(ShipAction::IsDocked),
-        Behavior::new_action(ShipAction::Dock),
-    ]);
+    let mut dock_if_necessary = sub_behavior(
+        "dock_if_necessary",
+        Behavior::new_select(vec![
+            Behavior::new_action(ShipAction::IsDocked),
+            Behavior::new_action(ShipAction::Dock),
+        ]),
+    );
 
-    let mut adjust_flight_mode_if_necessary = Behavior::new_select(vec![
-        Behavior::new_action(ShipAction::IsCorrectFlightMode),
-        Behavior::new_action(ShipAction::SetFlightMode),
-    ]);
+    let mut adjust_flight_mode_if_necessary = sub_behavior(
+        "adjust_flight_mode_if_necessary",
+        Behavior::new_select(vec![
+            Behavior::new_action(ShipAction::IsCorrectFlightMode),
+            Behavior::new_action(ShipAction::SetFlightMode),
+        ]),
+    );
 
     let execute_navigate_travel_action = Behavior::new_sequence(vec![
         Behavior::new_action(ShipAction::IsNavigationAction),
@@ -161,18 +215,22 @@ pub fn ship_behaviors() -> Behaviors {
         Behavior::new_action(ShipAction::WaitForArrival),
     ]);
 
-    let mut execute_refuel_travel_action = Behavior::new_sequence(vec![
-        Behavior::new_action(ShipAction::IsRefuelAction),
-        wait_for_arrival_bt.clone(),
-        Behavior::new_select(vec![
-            Behavior::new_action(ShipAction::CanSkipRefueling),
-            Behavior::new_sequence(vec![
-                dock_if_necessary.clone(),
-                Behavior::new_action(ShipAction::Refuel),
-                orbit_if_necessary.clone(),
+    let mut execute_refuel_travel_action = sub_behavior(
+        "refuel_behavior",
+        Behavior::new_sequence(vec![
+            Behavior::new_action(ShipAction::IsRefuelAction),
+            wait_for_arrival_bt.clone(),
+            Behavior::new_select(vec![
+                Behavior::new_action(ShipAction::CanSkipRefueling),
+                Behavior::new_sequence(vec![
+                    dock_if_necessary.clone(),
+                    Behavior::new_action(ShipAction::Refuel),
+                    Behavior::new_action(ShipAction::RefreshMarketIfDue),
+                    orbit_if_necessary.clone(),
+                ]),
             ]),
         ]),
-    ]);
+    );
 
     let travel_action_behavior = Behavior::new_select(vec![execute_navigate_travel_action, execute_refuel_travel_action.clone()]);
 
@@ -189,22 +247,25 @@ pub fn ship_behaviors() -> Behaviors {
         ]),
     );
 
-    let mut navigate_to_destination = Behavior::new_select(vec![
-        Behavior::new_action(ShipAction::IsAtDestination),
-        Behavior::new_sequence(vec![
-            Behavior::new_action(ShipAction::FixNavStatusIfNecessary),
-            Behavior::new_action(ShipAction::HasDestination),
-            wait_for_arrival_bt.clone(),
-            Behavior::new_select(vec![
-                Behavior::new_action(ShipAction::HasRouteToDestination),
-                Behavior::new_action(ShipAction::ComputePathToDestination),
-                Behavior::new_action(ShipAction::PrintTravelActions),
+    let mut navigate_to_destination = sub_behavior(
+        "navigate_to_destination",
+        Behavior::new_select(vec![
+            Behavior::new_action(ShipAction::IsAtDestination),
+            Behavior::new_sequence(vec![
+                Behavior::new_action(ShipAction::FixNavStatusIfNecessary),
+                Behavior::new_action(ShipAction::HasDestination),
+                wait_for_arrival_bt.clone(),
+                Behavior::new_select(vec![
+                    Behavior::new_action(ShipAction::HasRouteToDestination),
+                    Behavior::new_action(ShipAction::ComputePathToDestination),
+                    Behavior::new_action(ShipAction::PrintTravelActions),
+                ]),
+                follow_travel_actions.clone(),
+                Behavior::new_action(ShipAction::FixNavStatusIfNecessary),
+                Behavior::new_action(ShipAction::RemoveDestination),
             ]),
-            follow_travel_actions.clone(),
-            Behavior::new_action(ShipAction::FixNavStatusIfNecessary),
-            Behavior::new_action(ShipAction::RemoveDestination),
         ]),
-    ]);
+    );
 
     let purchase_ship_if_has_ticket = Behavior::new_select(vec![
         Behavior::new_invert(Behavior::new_action(ShipAction::HasShipPurchaseTicketForWaypoint)),
@@ -302,11 +363,21 @@ pub fn ship_behaviors() -> Behaviors {
      acceptContractIfNecessaryNode,
     */
 
+    // the last delivery of a helper hauler might have fulfilled the contract already
     let fulfill_contract_if_possible = Behavior::new_select(vec![
         Behavior::new_invert(Behavior::new_action(ShipAction::IsContractDeliveryComplete)),
+        Behavior::new_invert(Behavior::new_action(ShipAction::HasActiveContract)),
         Behavior::new_action(ShipAction::FulfilContract),
     ]);
 
+    // offers that aren't accepted yet get checked against the contract strategy
+    let decline_unprofitable_contract = Behavior::new_select(vec![
+        Behavior::new_invert(Behavior::new_action(ShipAction::HasActiveContract)),
+        Behavior::new_action(ShipAction::HasAcceptedContract),
+        Behavior::new_action(ShipAction::IsContractProfitable),
+        Behavior::new_action(ShipAction::DeclineContract),
+    ]);
+
     let accept_contract_if_necessary_and_within_budget = Behavior::new_sequence(vec![
         Behavior::new_action(ShipAction::HasActiveContract),
         Behavior::new_select(vec![
@@ -320,8 +391,10 @@ pub fn ship_behaviors() -> Behaviors {
     ]);
 
     // if we have no contract, stop at any waypoint and negotiate a new one
+    // the API rejects negotiations as long as a declined offer hasn't expired
     let negotiate_contract_if_necessary = Behavior::new_select(vec![
         Behavior::new_action(ShipAction::HasActiveContract),
+        Behavior::new_action(ShipAction::HasOpenDeclinedContractOffer),
         Behavior::new_sequence(vec![wait_for_arrival_bt.clone(), Behavior::new_action(ShipAction::NegotiateContract)]),
     ]);
 
@@ -341,7 +414,9 @@ pub fn ship_behaviors() -> Behaviors {
     let mut contractor_behavior = Behavior::new_sequence(vec![
         Behavior::new_action(ShipAction::FixNavStatusIfNecessary),
         fulfill_contract_if_possible,
+        decline_unprofitable_contract.clone(),
         negotiate_contract_if_necessary,
+        decline_unprofitable_contract,
         accept_contract_if_necessary_and_within_budget,
         Behavior::new_action(ShipAction::CreateContractTicketsIfNecessary),
         purchase_and_deliver_contract_materials,
@@ -376,6 +451,11 @@ pub fn ship_behaviors() -> Behaviors {
         Behavior::new_while(
             Behavior::new_action(ShipAction::HasCargoSpaceForSiphoning),
             Behavior::new_sequence(vec![
+                // we only siphon as much as the supply chain consumes
+                Behavior::new_while(
+                    Behavior::new_invert(Behavior::new_action(ShipAction::IsSiphoningDemanded)),
+                    Behavior::new_action(ShipAction::SleepUntilSiphoningIsDemanded),
+                ),
                 wait_for_cooldown_bt.clone(),
                 orbit_if_necessary.clone(),
                 Behavior::new_action(ShipAction::SiphonResources),
@@ -389,7 +469,32 @@ pub fn ship_behaviors() -> Behaviors {
         trading_behavior.clone(),
     ]);
 
-    let mut siphoning_behavior = Behavior::new_sequence(vec![siphon_until_full_behavior, deliver_all_goods_behavior.clone()]);
+    // hand the cargo to a hauler - if none will be around soon, we deliver it ourselves
+    let mut siphoning_behavior = Behavior::new_sequence(vec![
+        siphon_until_full_behavior,
+        Behavior::new_select(vec![
+            Behavior::new_action(ShipAction::AttemptCargoTransfer),
+            Behavior::new_sequence(vec![
+                Behavior::new_action(ShipAction::ShouldWaitForHauler),
+                Behavior::new_action(ShipAction::WaitForHauler),
+            ]),
+            deliver_all_goods_behavior.clone(),
+        ]),
+    ]);
+
+    let mut siphoning_hauler_behavior = Behavior::new_select(vec![
+        Behavior::new_sequence(vec![
+            Behavior::new_action(ShipAction::IsHaulerFilledEnoughForDelivery),
+            deliver_all_goods_behavior.clone(),
+        ]),
+        Behavior::new_sequence(vec![
+            go_to_siphoning_site_if_necessary.clone(),
+            wait_for_arrival_bt.clone(),
+            orbit_if_necessary.clone(),
+            Behavior::new_action(ShipAction::AnnounceHaulerReadyForPickup),
+            deliver_all_goods_behavior.clone(),
+        ]),
+    ]);
 
     let survey_if_necessary = Behavior::new_select(vec![
         Behavior::new_invert(Behavior::new_action(ShipAction::IsSurveyNecessary)),
@@ -411,12 +516,19 @@ pub fn ship_behaviors() -> Behaviors {
 
     let mine_if_necessary = Behavior::new_sequence(vec![survey_if_necessary, extract_resources]);
 
+    // stays at the current site if there is no alternative - the asteroid recovers over time
+    let relocate_if_mining_site_is_unstable = Behavior::new_select(vec![
+        Behavior::new_invert(Behavior::new_action(ShipAction::HasAsteroidReachedCriticalLimit)),
+        Behavior::new_always_succeed(Behavior::new_action(ShipAction::MoveToAlternativeMiningSite)),
+    ]);
+
     let mut mining_hauler_behavior = Behavior::new_select(vec![
         Behavior::new_sequence(vec![
             Behavior::new_action(ShipAction::IsHaulerFilledEnoughForDelivery),
             deliver_all_goods_behavior.clone(),
         ]),
         Behavior::new_sequence(vec![
+            relocate_if_mining_site_is_unstable.clone(),
             go_to_mining_site_if_necessary.clone(),
             wait_for_arrival_bt.clone(),
             orbit_if_necessary.clone(),
@@ -429,26 +541,54 @@ pub fn ship_behaviors() -> Behaviors {
         go_to_mining_site_if_necessary.clone(),
         wait_for_arrival_bt.clone(),
         orbit_if_necessary.clone(),
+        // hand the cargo to a hauler - if none is around, we wait, sell it ourselves or jettison it (whatever costs the fewest units)
         Behavior::new_while(
             Behavior::new_invert(Behavior::new_action(ShipAction::HasCargoSpaceForMining)),
-            Behavior::new_action(ShipAction::AttemptCargoTransfer),
+            Behavior::new_select(vec![
+                Behavior::new_action(ShipAction::AttemptCargoTransfer),
+                Behavior::new_sequence(vec![
+                    Behavior::new_action(ShipAction::ShouldWaitForHauler),
+                    Behavior::new_action(ShipAction::WaitForHauler),
+                ]),
+                Behavior::new_sequence(vec![
+                    Behavior::new_action(ShipAction::ShouldSellCargoAtDeliveryMarket),
+                    deliver_all_goods_behavior.clone(),
+                    go_to_mining_site_if_necessary.clone(),
+                    wait_for_arrival_bt.clone(),
+                    orbit_if_necessary.clone(),
+                ]),
+                Behavior::new_action(ShipAction::JettisonCargoForMining),
+            ]),
         ),
         Behavior::new_while(
             Behavior::new_action(ShipAction::HasAsteroidReachedCriticalLimit),
-            Behavior::new_sequence(vec![
-                Behavior::new_action(ShipAction::SleepForNextWaypointCriticalLimitCheck),
-                Behavior::new_action(ShipAction::CollectWaypointInfos),
+            Behavior::new_select(vec![
+                Behavior::new_sequence(vec![
+                    Behavior::new_action(ShipAction::MoveToAlternativeMiningSite),
+                    go_to_mining_site_if_necessary.clone(),
+                    wait_for_arrival_bt.clone(),
+                    orbit_if_necessary.clone(),
+                ]),
+                Behavior::new_sequence(vec![
+                    Behavior::new_action(ShipAction::SleepForNextWaypointCriticalLimitCheck),
+                    Behavior::new_action(ShipAction::CollectWaypointInfos),
+                ]),
             ]),
         ),
         mine_if_necessary.clone(),
     ]);
 
     let mut surveyor_behavior = Behavior::new_sequence(vec![
+        relocate_if_mining_site_is_unstable,
         go_to_mining_site_if_necessary.clone(),
         wait_for_arrival_bt.clone(),
         orbit_if_necessary.clone(),
         Behavior::new_while(
-            Behavior::new_action(ShipAction::IsAtMiningSite), // intentional endless loop
+            // endless loop until the asteroid becomes unstable
+            Behavior::new_sequence(vec![
+                Behavior::new_action(ShipAction::IsAtMiningSite),
+                Behavior::new_invert(Behavior::new_action(ShipAction::HasAsteroidReachedCriticalLimit)),
+            ]),
             Behavior::new_sequence(vec![wait_for_cooldown_bt, Behavior::new_action(ShipAction::Survey)]),
         ),
     ]);
@@ -464,6 +604,7 @@ pub fn ship_behaviors() -> Behaviors {
         stationary_probe_behavior: stationary_probe_behavior.update_indices().clone(),
         trading_behavior: trading_behavior.update_indices().clone(),
         siphoning_behavior: siphoning_behavior.update_indices().clone(),
+        siphoning_hauler_behavior: siphoning_hauler_behavior.update_indices().clone(),
         mining_hauler_behavior: mining_hauler_behavior.update_indices().clone(),
         miner_behavior: miner_behavior.update_indices().clone(),
         contractor_behavior: contractor_behavior.update_indices().clone(),
@@ -2,11 +2,13 @@ use crate::pathfinder::pathfinder;
 use crate::survey_manager;
 use anyhow::anyhow;
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, TimeDelta, Utc};
 use itertools::Itertools;
 use st_domain::blackboard_ops::BlackboardOps;
+use st_domain::cargo_flow::CargoFlowEntry;
+use st_domain::extraction_analytics::{ExtractionYieldReport, YIELD_LOOKBACK_HOURS};
 use st_domain::{
-    Construction, Contract, CreateSurveyResponse, Extraction, JumpGate, LabelledCoordinate, MarketData, MarketEntry, MaterializedSupplyChain,
+    Construction, Contract, ContractEntry, ContractId, CreateSurveyResponse, Extraction, JumpGate, LabelledCoordinate, MarketData, MarketEntry,
     MiningOpsConfig, Ship, Shipyard, Survey, SystemSymbol, TravelAction, Waypoint, WaypointModifier, WaypointSymbol,
 };
 use st_store::bmc::Bmc;
@@ -165,20 +167,38 @@ impl BlackboardOps for BmcBlackboard {
             .await?)
     }
 
-    async fn get_best_survey_for_current_demand(
+    async fn get_ranked_surveys_for_current_demand(
         &self,
         mining_config: &MiningOpsConfig,
-        materialized_supply_chain: &MaterializedSupplyChain,
-    ) -> anyhow::Result<Option<Survey>> {
+        haul_cost_per_unit_and_distance: f64,
+    ) -> anyhow::Result<Vec<(Survey, f64)>> {
         let available_surveys = self
             .bmc
             .survey_bmc()
             .get_all_valid_surveys_for_waypoint(&Ctx::Anonymous, &mining_config.mining_waypoint)
             .await?;
 
-        let maybe_best_survey_with_score = survey_manager::pick_best_survey(available_surveys, materialized_supply_chain);
+        let recent_survey_usages = self
+            .bmc
+            .survey_bmc()
+            .get_survey_usages_since(&Ctx::Anonymous, Utc::now() - TimeDelta::hours(YIELD_LOOKBACK_HOURS))
+            .await?;
+        let yield_report = ExtractionYieldReport::from_survey_usages(&recent_survey_usages);
+
+        let waypoints_of_system = self
+            .bmc
+            .system_bmc()
+            .get_waypoints_of_system(&Ctx::Anonymous, &mining_config.mining_waypoint.system_symbol())
+            .await?;
 
-        Ok(maybe_best_survey_with_score.map(|(survey, _score)| survey.clone()))
+        Ok(survey_manager::rank_surveys_for_current_demand(
+            available_surveys,
+            mining_config,
+            &waypoints_of_system,
+            &yield_report,
+            haul_cost_per_unit_and_distance,
+            Utc::now(),
+        ))
     }
 
     async fn mark_survey_as_exhausted(&self, survey: &Survey) -> anyhow::Result<()> {
@@ -222,6 +242,13 @@ impl BlackboardOps for BmcBlackboard {
         Ok(())
     }
 
+    async fn log_cargo_flows(&self, entries: Vec<CargoFlowEntry>) -> anyhow::Result<()> {
+        self.bmc
+            .cargo_flow_bmc()
+            .log_cargo_flows(&Ctx::Anonymous, &entries)
+            .await
+    }
+
     async fn mark_asteroid_has_reached_critical_limit(&self, mining_waypoint: &WaypointSymbol, waypoint_modifier: &WaypointModifier) -> anyhow::Result<()> {
         if let Ok(waypoint) = self
             .bmc
@@ -261,10 +288,32 @@ impl BlackboardOps for BmcBlackboard {
             .await
     }
 
+    async fn get_market_history_of_waypoint(&self, waypoint_symbol: &WaypointSymbol, since: DateTime<Utc>) -> anyhow::Result<Vec<MarketEntry>> {
+        self.bmc
+            .market_bmc()
+            .get_market_history_of_waypoint(&Ctx::Anonymous, waypoint_symbol, since)
+            .await
+    }
+
     async fn get_youngest_contract(&self, system_symbol: &SystemSymbol) -> anyhow::Result<Option<Contract>> {
+        let maybe_entry = self.get_youngest_contract_entry(system_symbol).await?;
+
+        Ok(maybe_entry
+            .filter(|entry| entry.declined_at.is_none())
+            .map(|entry| entry.contract))
+    }
+
+    async fn get_youngest_contract_entry(&self, system_symbol: &SystemSymbol) -> anyhow::Result<Option<ContractEntry>> {
         self.bmc
             .contract_bmc()
             .get_youngest_contract(&Ctx::Anonymous, system_symbol)
             .await
     }
+
+    async fn mark_contract_as_declined(&self, contract_id: &ContractId) -> anyhow::Result<()> {
+        self.bmc
+            .contract_bmc()
+            .mark_contract_as_declined(&Ctx::Anonymous, contract_id, Utc::now())
+            .await
+    }
 }
@@ -0,0 +1,505 @@
+use crate::pagination::{fetch_all_pages, PaginationInput};
+use crate::st_client::StClientTrait;
+use anyhow::{anyhow, Context};
+use axum::http::Extensions;
+use chrono::Utc;
+use reqwest::header::{HeaderValue, CONTENT_TYPE};
+use reqwest::{Client, Request, Response, StatusCode};
+use reqwest_middleware::{ClientBuilder, ClientWithMiddleware, Middleware, Next};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use st_domain::{distance_to, get_exploration_tasks_for_waypoint, AgentSymbol, ExplorationTask, FlightMode, ShipRegistrationRole};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const REDACTED: &str = "<REDACTED>";
+
+/// Recorded request/response pairs of the SpaceTraders api, so `StClient` and the `st_model` deserializers can be tested offline against payloads in the shape of the live api.
+///
+/// Headers aren't recorded (they carry the bearer token) and the values of all json fields with `token` in their name are redacted.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RecordedRequest {
+    pub method: String,
+    /// path and query without the host, so a cassette can be replayed against any base url
+    pub path: String,
+    pub body: Option<Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RecordedResponse {
+    pub status: u16,
+    /// bodies that aren't json are stored as json string
+    pub body: Value,
+}
+
+impl Cassette {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let json = std::fs::read_to_string(path).with_context(|| format!("Failed to read cassette {}", path.display()))?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)? + "\n").with_context(|| format!("Failed to write cassette {}", path.display()))
+    }
+}
+
+enum CassetteMode {
+    /// forwards all requests and writes the cassette to the path after every interaction
+    Record { path: PathBuf },
+    /// answers every request with the first recorded interaction that matches and hasn't been played yet - never hits the network
+    Replay { played: Vec<bool> },
+}
+
+/// Has to be the innermost middleware, so it records the requests as they go over the wire and replays them behind retries and rate limiting.
+pub struct CassetteMiddleware {
+    state: Mutex<(Cassette, CassetteMode)>,
+}
+
+impl CassetteMiddleware {
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self {
+            state: Mutex::new((Cassette::default(), CassetteMode::Record { path: path.into() })),
+        }
+    }
+
+    pub fn replay(cassette: Cassette) -> Self {
+        let played = vec![false; cassette.interactions.len()];
+        Self {
+            state: Mutex::new((cassette, CassetteMode::Replay { played })),
+        }
+    }
+
+    fn store(&self, interaction: Interaction) -> anyhow::Result<()> {
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|_| anyhow!("Cassette lock poisoned"))?;
+        let (cassette, mode) = &mut *state;
+        if let CassetteMode::Record { path } = mode {
+            cassette.interactions.push(interaction);
+            cassette.save(path)?;
+        }
+        Ok(())
+    }
+
+    fn find_recorded_response(&self, request: &RecordedRequest) -> anyhow::Result<Option<RecordedResponse>> {
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|_| anyhow!("Cassette lock poisoned"))?;
+        let (cassette, mode) = &mut *state;
+        match mode {
+            CassetteMode::Record { .. } => Ok(None),
+            CassetteMode::Replay { played } => {
+                let idx = cassette
+                    .interactions
+                    .iter()
+                    .enumerate()
+                    .position(|(idx, interaction)| !played[idx] && &interaction.request == request)
+                    .ok_or_else(|| anyhow!("No recorded interaction left for {} {}", request.method, request.path))?;
+                played[idx] = true;
+                Ok(Some(cassette.interactions[idx].response.clone()))
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Middleware for CassetteMiddleware {
+    async fn handle(&self, req: Request, extensions: &mut Extensions, next: Next<'_>) -> reqwest_middleware::Result<Response> {
+        let recorded_request = to_recorded_request(&req);
+
+        if let Some(recorded_response) = self
+            .find_recorded_response(&recorded_request)
+            .map_err(reqwest_middleware::Error::Middleware)?
+        {
+            return to_response(&recorded_response);
+        }
+
+        let response = next.run(req, extensions).await?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let bytes = response.bytes().await?;
+
+        self.store(Interaction {
+            request: recorded_request,
+            response: RecordedResponse {
+                status: status.as_u16(),
+                body: to_redacted_json(&bytes),
+            },
+        })
+        .map_err(reqwest_middleware::Error::Middleware)?;
+
+        // the body has been consumed - the caller gets an identical copy
+        let mut http_response = axum::http::Response::new(bytes);
+        *http_response.status_mut() = status;
+        *http_response.headers_mut() = headers;
+        Ok(Response::from(http_response))
+    }
+}
+
+fn to_recorded_request(req: &Request) -> RecordedRequest {
+    let url = req.url();
+    let path = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+
+    RecordedRequest {
+        method: req.method().to_string(),
+        path,
+        body: req
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(to_redacted_json),
+    }
+}
+
+fn to_response(recorded: &RecordedResponse) -> reqwest_middleware::Result<Response> {
+    let (body, content_type) = match &recorded.body {
+        Value::String(text) => (text.clone(), "text/plain"),
+        json => (json.to_string(), "application/json"),
+    };
+
+    let mut http_response = axum::http::Response::new(body);
+    *http_response.status_mut() =
+        StatusCode::from_u16(recorded.status).map_err(|err| reqwest_middleware::Error::Middleware(anyhow!("Invalid recorded status: {}", err)))?;
+    http_response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+    Ok(Response::from(http_response))
+}
+
+fn to_redacted_json(bytes: &[u8]) -> Value {
+    match serde_json::from_slice::<Value>(bytes) {
+        Ok(mut json) => {
+            redact_tokens(&mut json);
+            json
+        }
+        Err(_) => Value::String(String::from_utf8_lossy(bytes).to_string()),
+    }
+}
+
+fn redact_tokens(json: &mut Value) {
+    match json {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if key.to_lowercase().contains("token") && value.is_string() {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact_tokens(value);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(redact_tokens),
+        _ => {}
+    }
+}
+
+/// A client that answers all requests from the cassette.
+pub fn create_replay_client(cassette: Cassette) -> ClientWithMiddleware {
+    ClientBuilder::new(Client::new())
+        .with(CassetteMiddleware::replay(cassette))
+        .build()
+}
+
+/// Calls the endpoints that only read data, starting with the public info of the agent. Safe to run against the live api at any time.
+pub async fn record_read_only_endpoints(client: &dyn StClientTrait, agent_symbol: &AgentSymbol) -> anyhow::Result<()> {
+    client.get_status().await?;
+    client
+        .list_agents_page(PaginationInput { page: 1, limit: 20 })
+        .await?;
+    let agent = client.get_public_agent(agent_symbol).await?.data;
+    let system_symbol = agent.headquarters.system_symbol();
+
+    client.get_system(&system_symbol).await?;
+    // all pages, so the cassette covers every kind of waypoint detail (market, shipyard, jump gate and construction site)
+    let waypoints = fetch_all_pages(|pagination_input| client.list_waypoints_of_system_page(&system_symbol, pagination_input)).await?;
+    for waypoint in waypoints.iter() {
+        for task in get_exploration_tasks_for_waypoint(waypoint) {
+            match task {
+                ExplorationTask::GetMarket => {
+                    client.get_marketplace(waypoint.symbol.clone()).await?;
+                }
+                ExplorationTask::GetShipyard => {
+                    client.get_shipyard(waypoint.symbol.clone()).await?;
+                }
+                ExplorationTask::GetJumpGate => {
+                    client.get_jump_gate(waypoint.symbol.clone()).await?;
+                }
+                // charting changes the state of the waypoint
+                ExplorationTask::CreateChart => {}
+            }
+        }
+        if waypoint.is_under_construction {
+            client.get_construction_site(&waypoint.symbol).await?;
+        }
+    }
+    client.get_supply_chain().await?;
+
+    client.get_agent().await?;
+    let ships = client
+        .list_ships(PaginationInput { page: 1, limit: 20 })
+        .await?
+        .data;
+    if let Some(ship) = ships.first() {
+        client.get_ship(ship.symbol.clone()).await?;
+    }
+
+    Ok(())
+}
+
+/// Walks the command ship through the endpoints that change the state of a ship and buys the cheapest ship at the shipyard of the probe.
+/// Spends credits and moves the command ship, so only run it with a freshly registered agent (command ship at the headquarters, probe at a shipyard).
+pub async fn record_ship_endpoints(client: &dyn StClientTrait) -> anyhow::Result<()> {
+    let ships = client
+        .list_ships(PaginationInput { page: 1, limit: 20 })
+        .await?
+        .data;
+    let command_ship = ships
+        .iter()
+        .find(|ship| ship.registration.role == ShipRegistrationRole::Command)
+        .context("No command ship")?;
+    let probe = ships
+        .iter()
+        .find(|ship| ship.registration.role == ShipRegistrationRole::Satellite)
+        .context("No probe")?;
+    let ship_symbol = command_ship.symbol.clone();
+    let headquarters = command_ship.nav.waypoint_symbol.clone();
+
+    let cheapest_trade_good = client
+        .get_marketplace(headquarters.clone())
+        .await?
+        .data
+        .trade_goods
+        .unwrap_or_default()
+        .into_iter()
+        .min_by_key(|trade_good| trade_good.purchase_price)
+        .context("No trade goods at the headquarters")?
+        .symbol;
+    client.dock_ship(ship_symbol.clone()).await?;
+    client
+        .purchase_trade_good(ship_symbol.clone(), 2, cheapest_trade_good.clone())
+        .await?;
+    client
+        .sell_trade_good(ship_symbol.clone(), 1, cheapest_trade_good.clone())
+        .await?;
+    client.orbit_ship(ship_symbol.clone()).await?;
+    client
+        .jettison_cargo(ship_symbol.clone(), cheapest_trade_good, 1)
+        .await?;
+
+    // a round trip to the closest waypoint, so the ship can refuel at the marketplace of the headquarters
+    let system = client.get_system(&headquarters.system_symbol()).await?.data;
+    let hq = system
+        .waypoints
+        .iter()
+        .find(|waypoint| waypoint.symbol == headquarters)
+        .context("Headquarters not in its system")?;
+    let closest_waypoint = system
+        .waypoints
+        .iter()
+        .map(|waypoint| (waypoint, distance_to(hq.x, hq.y, waypoint.x, waypoint.y)))
+        .filter(|(_, distance)| *distance > 0)
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(waypoint, _)| waypoint.symbol.clone())
+        .context("No other waypoint in the system")?;
+
+    client
+        .set_flight_mode(ship_symbol.clone(), &FlightMode::Cruise)
+        .await?;
+    let mut fuel = command_ship.fuel.clone();
+    for destination in [closest_waypoint, headquarters] {
+        let response = client
+            .navigate(ship_symbol.clone(), &destination)
+            .await?
+            .data;
+        if let Ok(travel_time) = (response.nav.route.arrival - Utc::now()).to_std() {
+            tokio::time::sleep(travel_time).await;
+        }
+        fuel = response.fuel;
+    }
+    client.dock_ship(ship_symbol.clone()).await?;
+    client
+        .refuel(ship_symbol, (fuel.capacity - fuel.current) as u32, false)
+        .await?;
+
+    let cheapest_ship_type = client
+        .get_shipyard(probe.nav.waypoint_symbol.clone())
+        .await?
+        .data
+        .ships
+        .unwrap_or_default()
+        .into_iter()
+        .min_by_key(|ship| ship.purchase_price)
+        .context("No ships at the shipyard of the probe")?
+        .r#type;
+    client
+        .purchase_ship(cheapest_ship_type, probe.nav.waypoint_symbol.clone())
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::st_client::StClient;
+    use st_domain::{FactionSymbol, NavStatus, ShipSymbol, ShipType, SystemSymbol, TradeGoodSymbol, WaypointSymbol};
+
+    /// The fixtures follow the response schemas of the api docs - they should be re-recorded against the live api with `st-cli record-cassette` (`--ship-endpoints` for ship_endpoints.json).
+    fn replay_client(fixture: &str) -> StClient {
+        let cassette = Cassette::load(
+            &Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("fixtures/cassettes")
+                .join(fixture),
+        )
+        .unwrap();
+        StClient::try_with_base_url(create_replay_client(cassette), "https://api.spacetraders.io/v2").unwrap()
+    }
+
+    fn wps(symbol: &str) -> WaypointSymbol {
+        WaypointSymbol(symbol.to_string())
+    }
+
+    #[tokio::test]
+    async fn replays_read_only_payloads_through_st_client() -> anyhow::Result<()> {
+        let client = replay_client("read_only_endpoints.json");
+
+        let status = client.get_status().await?;
+        assert_eq!(status.reset_date, "2025-04-06");
+
+        let agents = client
+            .list_agents_page(PaginationInput { page: 1, limit: 20 })
+            .await?;
+        assert_eq!(agents.data[0].starting_faction, FactionSymbol::LORDS);
+        assert_eq!(agents.data[0].account_id, None);
+
+        let agent = client
+            .get_public_agent(&AgentSymbol("FLWI_TEST".to_string()))
+            .await?
+            .data;
+        assert_eq!(agent.headquarters, wps("X1-AD75-A1"));
+
+        let system_symbol = SystemSymbol("X1-AD75".to_string());
+        let system = client.get_system(&system_symbol).await?.data;
+        let waypoints = fetch_all_pages(|pagination_input| client.list_waypoints_of_system_page(&system_symbol, pagination_input)).await?;
+        assert_eq!(waypoints.len(), system.waypoints.len());
+
+        // the details are only complete where one of our ships is - the command ship is docked at A1, the probe at H52
+        let shipyard = client.get_shipyard(wps("X1-AD75-A2")).await?.data;
+        assert!(shipyard.ships.is_none());
+        let shipyard = client.get_shipyard(wps("X1-AD75-H52")).await?.data;
+        assert_eq!(shipyard.ships.map(|ships| ships.len()), Some(2));
+
+        let market = client.get_marketplace(wps("X1-AD75-A2")).await?.data;
+        assert!(market.trade_goods.is_none());
+        let market = client.get_marketplace(wps("X1-AD75-A1")).await?.data;
+        assert_eq!(market.trade_goods.map(|trade_goods| trade_goods.len()), Some(7));
+
+        let jump_gate = client.get_jump_gate(wps("X1-AD75-I55")).await?.data;
+        assert_eq!(jump_gate.connections.len(), 5);
+
+        let construction_site = client
+            .get_construction_site(&wps("X1-AD75-I55"))
+            .await?
+            .data;
+        assert!(!construction_site.is_complete);
+        assert_eq!(construction_site.materials[0].trade_symbol, TradeGoodSymbol::FAB_MATS);
+
+        let supply_chain = client.get_supply_chain().await?.data;
+        assert_eq!(
+            supply_chain.export_to_import_map[&TradeGoodSymbol::LIQUID_HYDROGEN],
+            vec![TradeGoodSymbol::MACHINERY]
+        );
+
+        let agent = client.get_agent().await?.data;
+        assert!(agent.account_id.is_some());
+
+        let ships = client
+            .list_ships(PaginationInput { page: 1, limit: 20 })
+            .await?;
+        assert_eq!(ships.meta.total, 2);
+        let ship = client
+            .get_ship(ShipSymbol("FLWI_TEST-1".to_string()))
+            .await?
+            .data;
+        assert_eq!(ship.nav.status, NavStatus::Docked);
+
+        // every interaction gets played once
+        assert!(client.get_status().await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn replays_ship_payloads_through_st_client() -> anyhow::Result<()> {
+        let client = replay_client("ship_endpoints.json");
+        let ship_symbol = ShipSymbol("FLWI_TEST-1".to_string());
+
+        let purchase = client
+            .purchase_trade_good(ship_symbol.clone(), 2, TradeGoodSymbol::FUEL)
+            .await?
+            .data;
+        assert_eq!(purchase.cargo.units, 2);
+        assert_eq!(purchase.agent.credits, 175_000 - purchase.transaction.total_price as i64);
+
+        let navigation = client
+            .navigate(ship_symbol.clone(), &wps("X1-AD75-XD5A"))
+            .await?
+            .data;
+        assert_eq!(navigation.nav.status, NavStatus::InTransit);
+        assert_eq!(navigation.fuel.consumed.amount, 23);
+
+        let refuel = client.refuel(ship_symbol, 46, false).await?.data;
+        assert_eq!(refuel.fuel.current, refuel.fuel.capacity);
+
+        let purchased_ship = client
+            .purchase_ship(ShipType::SHIP_SURVEYOR, wps("X1-AD75-H52"))
+            .await?
+            .data;
+        assert_eq!(purchased_ship.ship.registration.role, ShipRegistrationRole::Surveyor);
+        assert_eq!(purchased_ship.agent.ship_count, 3);
+
+        Ok(())
+    }
+
+    /// The recorded arrivals lie in the past, so the replay doesn't wait for the ship.
+    #[tokio::test]
+    async fn recording_the_ship_endpoints_matches_the_fixture() -> anyhow::Result<()> {
+        let client = replay_client("ship_endpoints.json");
+
+        record_ship_endpoints(&client).await?;
+
+        // every interaction gets played once
+        assert!(client
+            .dock_ship(ShipSymbol("FLWI_TEST-1".to_string()))
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn tokens_are_redacted_in_nested_bodies() {
+        let json = to_redacted_json(br#"{"data": {"token": "secret", "agent": {"symbol": "FLWI"}, "ships": [{"accountToken": "secret"}]}}"#);
+
+        assert_eq!(json["data"]["token"], REDACTED);
+        assert_eq!(json["data"]["ships"][0]["accountToken"], REDACTED);
+        assert_eq!(json["data"]["agent"]["symbol"], "FLWI");
+    }
+}
@@ -0,0 +1,82 @@
+use crate::background_jobs::schedule_repeated;
+use crate::pagination::PaginationInput;
+use crate::st_client::StClientTrait;
+use anyhow::Result;
+use chrono::Utc;
+use governor::{DefaultDirectRateLimiter, Quota, RateLimiter};
+use itertools::Itertools;
+use st_domain::competitor_intelligence::AgentSnapshot;
+use st_store::bmc::Bmc;
+use st_store::Ctx;
+use std::num::NonZeroU32;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_cron_scheduler::JobScheduler;
+use tracing::{event, Level};
+
+/// How often the tracker downloads the agents list.
+const TRACKING_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const PAGE_LIMIT: u32 = 20;
+
+/// Stores the credits and ship counts of all agents of the reset over time, so we can see who is growing and how fast.
+///
+/// Like the galaxy crawler, the tracker has its own rate limiter and only takes a share of the requests of the client.
+pub struct CompetitorTracker {
+    client: Arc<dyn StClientTrait>,
+    bmc: Arc<dyn Bmc>,
+    limiter: DefaultDirectRateLimiter,
+}
+
+impl CompetitorTracker {
+    pub fn new(client: Arc<dyn StClientTrait>, bmc: Arc<dyn Bmc>, requests_per_minute: NonZeroU32) -> Self {
+        Self {
+            client,
+            bmc,
+            limiter: RateLimiter::direct(Quota::per_minute(requests_per_minute)),
+        }
+    }
+
+    pub async fn schedule(tracker: Arc<CompetitorTracker>) -> Result<JobScheduler> {
+        schedule_repeated(TRACKING_INTERVAL, "Collecting agent snapshots", move || {
+            let tracker = Arc::clone(&tracker);
+            async move { tracker.collect_agent_snapshots().await.map(|_| ()) }
+        })
+        .await
+    }
+
+    /// All pages of one run get the same timestamp, so that a run can be compared with an earlier one.
+    pub async fn collect_agent_snapshots(&self) -> Result<usize> {
+        let observed_at = Utc::now();
+        let mut num_snapshots = 0;
+        let mut page = 1;
+
+        loop {
+            self.limiter.until_ready().await;
+            let response = self
+                .client
+                .list_agents_page(PaginationInput { page, limit: PAGE_LIMIT })
+                .await?;
+            let total_number_of_pages = response.meta.total.div_ceil(response.meta.limit.max(1));
+
+            let snapshots = response
+                .data
+                .iter()
+                .map(|agent| AgentSnapshot::from_agent(agent, observed_at))
+                .collect_vec();
+            self.bmc
+                .agent_bmc()
+                .save_agent_snapshots(&Ctx::Anonymous, &snapshots)
+                .await?;
+            num_snapshots += snapshots.len();
+
+            if page >= total_number_of_pages {
+                break;
+            }
+            page += 1;
+        }
+
+        event!(Level::INFO, "Collected snapshots of {} agents", num_snapshots);
+
+        Ok(num_snapshots)
+    }
+}
@@ -1,4 +1,5 @@
 use st_store::PgConnectionString;
+use std::path::PathBuf;
 
 #[derive(Clone, Debug)]
 pub struct AgentConfiguration {
@@ -10,10 +11,108 @@ pub struct AgentConfiguration {
     pub spacetraders_base_url: String,
     pub use_in_memory_agent: bool,
     pub no_agent: bool,
+    /// optional directory with behavior tree definitions (json, ron or yaml) that replace the builtin trees
+    pub behavior_definition_dir: Option<PathBuf>,
+    /// optional toml file with the economic parameters of the fleets (see `resources/strategy.toml`)
+    pub strategy_file: Option<PathBuf>,
+    /// share of the rate limit the background galaxy crawler may use - 0 disables the crawler
+    pub galaxy_crawler_requests_per_minute: u32,
+    /// market observations, ships and surveys older than this get downsampled or pruned - 0 disables the retention job
+    pub data_retention_hours: u32,
+    /// share of the rate limit the job that downloads the public agents list may use - 0 disables competitor tracking
+    pub competitor_tracking_requests_per_minute: u32,
 }
 
 impl AgentConfiguration {
     pub fn pg_connection_string(&self) -> PgConnectionString {
         PgConnectionString(self.database_url.clone())
     }
+
+    /// Configurations for the main agent and the agents that run next to it in the same database, e.g. to compare strategies or factions.
+    ///
+    /// `additional_agents` is a comma separated list of `AGENT_SYMBOL:FACTION[:STRATEGY_FILE]`.
+    /// The additional agents share the account token, the api and the behavior trees of the main agent
+    /// and use the strategy file of the main agent if they don't bring their own.
+    pub fn with_additional_agents(self, additional_agents: &str) -> anyhow::Result<Vec<AgentConfiguration>> {
+        let mut cfgs = vec![self.clone()];
+
+        for spec in additional_agents
+            .split(',')
+            .map(str::trim)
+            .filter(|spec| !spec.is_empty())
+        {
+            let mut parts = spec.splitn(3, ':').map(str::trim);
+            let (Some(agent_symbol), Some(faction)) = (parts.next(), parts.next()) else {
+                anyhow::bail!("Invalid additional agent '{}' - expected AGENT_SYMBOL:FACTION[:STRATEGY_FILE]", spec);
+            };
+            if agent_symbol.is_empty() || faction.is_empty() {
+                anyhow::bail!("Invalid additional agent '{}' - expected AGENT_SYMBOL:FACTION[:STRATEGY_FILE]", spec);
+            }
+            if cfgs
+                .iter()
+                .any(|cfg| cfg.spacetraders_agent_symbol == agent_symbol)
+            {
+                anyhow::bail!("Agent {} is configured more than once", agent_symbol);
+            }
+
+            cfgs.push(AgentConfiguration {
+                spacetraders_agent_symbol: agent_symbol.to_string(),
+                spacetraders_agent_faction: faction.to_string(),
+                strategy_file: parts
+                    .next()
+                    .map(PathBuf::from)
+                    .or(self.strategy_file.clone()),
+                ..self.clone()
+            });
+        }
+
+        Ok(cfgs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn main_cfg() -> AgentConfiguration {
+        AgentConfiguration {
+            database_url: "postgres://localhost/spacetraders".to_string(),
+            spacetraders_agent_faction: "GALACTIC".to_string(),
+            spacetraders_agent_symbol: "MAIN".to_string(),
+            spacetraders_registration_email: "".to_string(),
+            spacetraders_account_token: "token".to_string(),
+            spacetraders_base_url: "https://api.spacetraders.io/v2".to_string(),
+            use_in_memory_agent: false,
+            no_agent: false,
+            behavior_definition_dir: None,
+            strategy_file: Some(PathBuf::from("strategy.toml")),
+            galaxy_crawler_requests_per_minute: 30,
+            data_retention_hours: 48,
+            competitor_tracking_requests_per_minute: 10,
+        }
+    }
+
+    #[test]
+    fn additional_agents_inherit_everything_but_symbol_faction_and_strategy() {
+        let cfgs = main_cfg()
+            .with_additional_agents("RIVAL:COSMIC, MINER:VOID:miner.toml,")
+            .unwrap();
+
+        assert_eq!(
+            cfgs.iter()
+                .map(|cfg| (cfg.spacetraders_agent_symbol.as_str(), cfg.spacetraders_agent_faction.as_str()))
+                .collect::<Vec<_>>(),
+            vec![("MAIN", "GALACTIC"), ("RIVAL", "COSMIC"), ("MINER", "VOID")]
+        );
+        assert_eq!(cfgs[1].strategy_file, Some(PathBuf::from("strategy.toml")));
+        assert_eq!(cfgs[2].strategy_file, Some(PathBuf::from("miner.toml")));
+        assert_eq!(cfgs[2].spacetraders_account_token, "token");
+    }
+
+    #[test]
+    fn invalid_or_duplicate_additional_agents_are_rejected() {
+        assert_eq!(main_cfg().with_additional_agents("").unwrap().len(), 1);
+        assert!(main_cfg().with_additional_agents("RIVAL").is_err());
+        assert!(main_cfg().with_additional_agents("MAIN:COSMIC").is_err());
+    }
 }
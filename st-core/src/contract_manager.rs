@@ -1,37 +1,70 @@
-use crate::{calc_batches_based_on_volume_constraint, get_closest_waypoint};
+use crate::strategy::StrategyStore;
+use crate::{calc_batches_based_on_volume_constraint, calculate_time, get_closest_waypoint};
 use anyhow::{anyhow, Result};
+use chrono::{DateTime, TimeDelta, Utc};
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use st_domain::budgeting::credits::Credits;
 use st_domain::budgeting::treasury_redesign::{
-    DeliverCargoContractTicketDetails, PurchaseCargoReason, PurchaseTradeGoodsTicketDetails, SellTradeGoodsTicketDetails,
+    DeliverCargoContractTicketDetails, FinanceTicket, FinanceTicketDetails, PurchaseCargoReason, PurchaseTradeGoodsTicketDetails, SellTradeGoodsTicketDetails,
 };
+use st_domain::strategy::ContractStrategy;
 use st_domain::trading::group_markets_by_type;
 use st_domain::{
-    combine_maps, trading, Cargo, Contract, ContractEvaluationResult, MarketEntry, MarketTradeGood, TradeGoodSymbol, TradeGoodType, Waypoint, WaypointSymbol,
+    combine_maps, trading, Cargo, Contract, ContractEvaluationResult, ContractId, ContractProfitability, FlightMode, LabelledCoordinate, MarketEntry,
+    MarketTradeGood, TradeGoodSymbol, TradeGoodType, Waypoint, WaypointSymbol,
 };
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
 
+/// Decides which contracts are worth accepting, based on the contract settings of the strategy.
 #[derive(Clone, Debug)]
 pub struct ContractManager {
-    #[allow(dead_code)]
-    contract: Arc<Mutex<Option<Contract>>>,
+    strategy_store: StrategyStore,
 }
 
 impl Default for ContractManager {
     fn default() -> Self {
-        Self::new()
+        Self::new(StrategyStore::default())
     }
 }
 
 impl ContractManager {
-    pub fn new() -> Self {
-        Self {
-            contract: Arc::new(Mutex::new(None)),
-        }
+    pub fn new(strategy_store: StrategyStore) -> Self {
+        Self { strategy_store }
+    }
+
+    pub fn contract_strategy(&self) -> ContractStrategy {
+        self.strategy_store.get_strategy().contracts
+    }
+
+    pub fn evaluate_profitability(
+        &self,
+        evaluation_result: &ContractEvaluationResult,
+        ship_location: &WaypointSymbol,
+        waypoints_of_system: &[Waypoint],
+        engine_speed: u32,
+        now: DateTime<Utc>,
+    ) -> ContractProfitability {
+        evaluate_contract_profitability(
+            evaluation_result,
+            ship_location,
+            waypoints_of_system,
+            engine_speed,
+            &self.contract_strategy(),
+            now,
+        )
     }
 }
 
+/// Limits the tickets of a single ship to its share of the contract.
+#[derive(Clone, Debug, Default)]
+pub struct ContractTicketScope {
+    /// units that are already being delivered by other ships
+    pub units_covered_by_other_ships: HashMap<TradeGoodSymbol, u32>,
+    /// the ship only purchases up to this many units in total
+    pub max_units_to_purchase: Option<u32>,
+}
+
 #[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
 struct SplitCargoForContractUsageResult {
     excess: HashMap<TradeGoodSymbol, u32>,
@@ -104,6 +137,25 @@ pub fn calculate_necessary_tickets_for_contract(
     contract: &Contract,
     latest_market_entries: &[MarketEntry],
     waypoints_of_system: &[Waypoint],
+) -> Result<ContractEvaluationResult> {
+    calculate_necessary_tickets_for_contract_share(
+        ship_cargo,
+        ship_location,
+        contract,
+        latest_market_entries,
+        waypoints_of_system,
+        &ContractTicketScope::default(),
+    )
+}
+
+/// Like `calculate_necessary_tickets_for_contract`, but only purchases the units that aren't covered by other ships (up to the max of the scope).
+pub fn calculate_necessary_tickets_for_contract_share(
+    ship_cargo: &Cargo,
+    ship_location: &WaypointSymbol,
+    contract: &Contract,
+    latest_market_entries: &[MarketEntry],
+    waypoints_of_system: &[Waypoint],
+    scope: &ContractTicketScope,
 ) -> Result<ContractEvaluationResult> {
     let ship_cargo_size = ship_cargo.capacity as u32;
 
@@ -125,7 +177,25 @@ pub fn calculate_necessary_tickets_for_contract(
     let mut purchase_tickets: Vec<PurchaseTradeGoodsTicketDetails> = vec![];
     let mut delivery_tickets: Vec<DeliverCargoContractTicketDetails> = vec![];
 
-    for (trade_symbol, (quantity, destination_wps)) in still_open_contract_entries.iter() {
+    let mut remaining_units_to_purchase = scope.max_units_to_purchase.unwrap_or(u32::MAX);
+
+    for (trade_symbol, (open_quantity, destination_wps)) in still_open_contract_entries
+        .iter()
+        .sorted_by_key(|(tg, _)| *tg)
+    {
+        let covered_by_other_ships = scope
+            .units_covered_by_other_ships
+            .get(trade_symbol)
+            .copied()
+            .unwrap_or_default();
+        let quantity = &open_quantity
+            .saturating_sub(covered_by_other_ships)
+            .min(remaining_units_to_purchase);
+        if *quantity == 0 {
+            continue;
+        }
+        remaining_units_to_purchase -= quantity;
+
         let (best_purchase_location, market_entry) = all_supply_markets
             .get(trade_symbol)
             .cloned()
@@ -180,6 +250,67 @@ pub fn calculate_necessary_tickets_for_contract(
     })
 }
 
+/// Sums up the contract deliveries of the given tickets per trade good.
+pub fn units_covered_by_active_tickets(contract_id: &ContractId, active_tickets: &[FinanceTicket]) -> HashMap<TradeGoodSymbol, u32> {
+    let mut result: HashMap<TradeGoodSymbol, u32> = HashMap::new();
+    for ticket in active_tickets {
+        if let FinanceTicketDetails::DeliverContractCargo(details) = &ticket.details {
+            if &details.contract_id == contract_id {
+                *result.entry(details.trade_good.clone()).or_default() += details.quantity;
+            }
+        }
+    }
+    result
+}
+
+/// Compares the payment of a contract with the purchase costs and what a ship could have earned with regular trades during the travel time.
+/// Each purchase batch is one round trip: from the current location to the market and then to the delivery destination.
+pub fn evaluate_contract_profitability(
+    evaluation_result: &ContractEvaluationResult,
+    ship_location: &WaypointSymbol,
+    waypoints_of_system: &[Waypoint],
+    engine_speed: u32,
+    strategy: &ContractStrategy,
+    now: DateTime<Utc>,
+) -> ContractProfitability {
+    let contract = &evaluation_result.contract;
+    let waypoint_map: HashMap<&WaypointSymbol, &Waypoint> = waypoints_of_system
+        .iter()
+        .map(|wp| (&wp.symbol, wp))
+        .collect();
+    let travel_secs_between = |from: &WaypointSymbol, to: &WaypointSymbol| match (waypoint_map.get(from), waypoint_map.get(to)) {
+        (Some(from_wp), Some(to_wp)) => calculate_time(&FlightMode::Cruise, from_wp.distance_to(*to_wp), engine_speed),
+        _ => 0,
+    };
+
+    let mut current_location = ship_location.clone();
+    let mut travel_secs = 0;
+    for (purchase_ticket, delivery_ticket) in evaluation_result
+        .purchase_tickets
+        .iter()
+        .zip(evaluation_result.delivery_tickets.iter())
+    {
+        travel_secs += travel_secs_between(&current_location, &purchase_ticket.waypoint_symbol);
+        travel_secs += travel_secs_between(&purchase_ticket.waypoint_symbol, &delivery_ticket.waypoint_symbol);
+        current_location = delivery_ticket.waypoint_symbol.clone();
+    }
+
+    let payment: Credits = (contract.terms.payment.on_accepted + contract.terms.payment.on_fulfilled).into();
+    let purchase_costs = evaluation_result.estimated_purchase_costs();
+    let opportunity_costs: Credits = ((strategy.opportunity_cost_per_hour.0 as f64 * travel_secs as f64 / 3600.0).round() as i64).into();
+    let expected_profit = payment - purchase_costs - opportunity_costs;
+    let is_completable_before_deadline = now + TimeDelta::seconds(travel_secs as i64) <= contract.terms.deadline;
+
+    ContractProfitability {
+        payment,
+        purchase_costs,
+        travel_secs,
+        opportunity_costs,
+        expected_profit,
+        is_completable_before_deadline,
+    }
+}
+
 pub(crate) fn create_sell_tickets_for_cargo_items(
     inventory_entries_to_sell: &HashMap<TradeGoodSymbol, u32>,
     ship_location: &WaypointSymbol,
@@ -228,7 +359,6 @@ pub(crate) fn create_sell_tickets_for_cargo_items(
 mod tests {
     use super::*;
     use crate::universe_server::universe_server::InMemoryUniverse;
-    use itertools::Itertools;
     use st_domain::{ContractId, ContractTerms, Delivery, Inventory, Payment, WaypointType};
 
     #[test]
@@ -453,6 +583,111 @@ mod tests {
         assert_eq!(actual_required_capital, 9_795.into());
     }
 
+    #[test]
+    fn test_calculate_necessary_tickets_for_contract_share_skips_units_of_other_ships() {
+        let test_universe = get_in_memory_universe();
+        let test_market_entries = get_test_market_entries(&test_universe);
+        let test_waypoints = get_test_waypoints(&test_universe);
+
+        let test_contract = create_test_contract();
+        let empty_test_cargo = create_test_cargo(vec![], 80);
+        let ship_location = test_waypoints
+            .iter()
+            .find(|wp| wp.r#type == WaypointType::ENGINEERED_ASTEROID)
+            .unwrap();
+
+        let scope = ContractTicketScope {
+            units_covered_by_other_ships: HashMap::from([(TradeGoodSymbol::IRON, 95)]),
+            max_units_to_purchase: None,
+        };
+        let result = calculate_necessary_tickets_for_contract_share(
+            &empty_test_cargo,
+            &ship_location.symbol,
+            &test_contract,
+            &test_market_entries,
+            &test_waypoints,
+            &scope,
+        )
+        .unwrap();
+
+        let actual_purchase_ticket_quantities = result
+            .purchase_tickets
+            .iter()
+            .map(|t| (t.trade_good.clone(), t.quantity))
+            .collect_vec();
+        assert_eq!(actual_purchase_ticket_quantities, vec![(TradeGoodSymbol::COPPER, 35)]);
+
+        let capped_scope = ContractTicketScope {
+            units_covered_by_other_ships: Default::default(),
+            max_units_to_purchase: Some(50),
+        };
+        let capped_result = calculate_necessary_tickets_for_contract_share(
+            &empty_test_cargo,
+            &ship_location.symbol,
+            &test_contract,
+            &test_market_entries,
+            &test_waypoints,
+            &capped_scope,
+        )
+        .unwrap();
+
+        let total_purchased_units = capped_result
+            .purchase_tickets
+            .iter()
+            .map(|t| t.quantity)
+            .sum::<u32>();
+        assert_eq!(total_purchased_units, 50);
+    }
+
+    #[test]
+    fn test_evaluate_contract_profitability() {
+        let test_universe = get_in_memory_universe();
+        let test_market_entries = get_test_market_entries(&test_universe);
+        let test_waypoints = get_test_waypoints(&test_universe);
+
+        let now = Utc::now();
+        let mut test_contract = create_test_contract();
+        test_contract.terms.deadline = now + TimeDelta::days(7);
+
+        let empty_test_cargo = create_test_cargo(vec![], 80);
+        let ship_location = test_waypoints
+            .iter()
+            .find(|wp| wp.r#type == WaypointType::ENGINEERED_ASTEROID)
+            .unwrap();
+
+        let evaluation_result =
+            calculate_necessary_tickets_for_contract(&empty_test_cargo, &ship_location.symbol, &test_contract, &test_market_entries, &test_waypoints).unwrap();
+
+        let free_strategy = ContractStrategy {
+            opportunity_cost_per_hour: 0.into(),
+            ..ContractStrategy::default()
+        };
+        let profitability = evaluate_contract_profitability(&evaluation_result, &ship_location.symbol, &test_waypoints, 30, &free_strategy, now);
+
+        assert_eq!(profitability.payment, 20_000.into());
+        assert_eq!(profitability.purchase_costs, 14_795.into());
+        assert_eq!(profitability.expected_profit, 5_205.into());
+        assert!(profitability.travel_secs > 0);
+        assert!(profitability.is_worth_accepting(1_000.into()));
+
+        let expensive_strategy = ContractStrategy {
+            opportunity_cost_per_hour: 1_000_000.into(),
+            ..ContractStrategy::default()
+        };
+        let profitability = evaluate_contract_profitability(&evaluation_result, &ship_location.symbol, &test_waypoints, 30, &expensive_strategy, now);
+        assert_eq!(
+            profitability.expected_profit,
+            profitability.payment - profitability.purchase_costs - profitability.opportunity_costs
+        );
+        assert!(!profitability.is_worth_accepting(1_000.into()));
+
+        test_contract.terms.deadline = now;
+        let expired_evaluation_result =
+            calculate_necessary_tickets_for_contract(&empty_test_cargo, &ship_location.symbol, &test_contract, &test_market_entries, &test_waypoints).unwrap();
+        let profitability = evaluate_contract_profitability(&expired_evaluation_result, &ship_location.symbol, &test_waypoints, 30, &free_strategy, now);
+        assert!(!profitability.is_completable_before_deadline);
+    }
+
     fn get_in_memory_universe() -> InMemoryUniverse {
         let manifest_dir = env!("CARGO_MANIFEST_DIR");
 
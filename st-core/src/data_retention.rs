@@ -0,0 +1,143 @@
+use crate::background_jobs::schedule_repeated;
+use anyhow::Result;
+use chrono::{DateTime, TimeDelta, Utc};
+use st_domain::AgentSymbol;
+use st_store::bmc::Bmc;
+use st_store::Ctx;
+use std::num::NonZeroU32;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_cron_scheduler::JobScheduler;
+use tracing::{event, Level};
+
+/// How often the retention job cleans up the db.
+const RETENTION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DataRetentionReport {
+    pub downsampled_market_observations: u64,
+    pub pruned_ships: u64,
+    pub pruned_surveys: u64,
+}
+
+/// Keeps the tables that grow with every observation from growing without bound over a reset.
+///
+/// Market observations older than the retention period are downsampled into hourly aggregates, left-behind ships of agents that aren't configured anymore
+/// and exhausted surveys nobody uses anymore are deleted.
+pub struct DataRetention {
+    bmc: Arc<dyn Bmc>,
+    retention: TimeDelta,
+    active_agent_symbols: Vec<AgentSymbol>,
+}
+
+impl DataRetention {
+    pub fn new(bmc: Arc<dyn Bmc>, retention_hours: NonZeroU32, active_agent_symbols: Vec<AgentSymbol>) -> Self {
+        Self {
+            bmc,
+            retention: TimeDelta::hours(retention_hours.get() as i64),
+            active_agent_symbols,
+        }
+    }
+
+    pub async fn schedule(retention: Arc<DataRetention>) -> Result<JobScheduler> {
+        schedule_repeated(RETENTION_INTERVAL, "Data retention", move || {
+            let retention = Arc::clone(&retention);
+            async move { retention.run(Utc::now()).await.map(|_| ()) }
+        })
+        .await
+    }
+
+    pub async fn run(&self, now: DateTime<Utc>) -> Result<DataRetentionReport> {
+        let older_than = now - self.retention;
+
+        let mut report = DataRetentionReport {
+            downsampled_market_observations: self
+                .bmc
+                .market_bmc()
+                .downsample_market_history(&Ctx::Anonymous, older_than)
+                .await?,
+            pruned_ships: self
+                .bmc
+                .ship_bmc()
+                .prune_superseded_ships(&self.active_agent_symbols, older_than)
+                .await?,
+            ..DataRetentionReport::default()
+        };
+
+        // the universe data is shared, but every agent in the db leaves its own surveys behind
+        for agent_symbol in self
+            .bmc
+            .agent_bmc()
+            .list_agent_symbols(&Ctx::Anonymous)
+            .await?
+        {
+            let ctx = Ctx::Agent(agent_symbol);
+            report.pruned_surveys += self
+                .bmc
+                .survey_bmc()
+                .prune_exhausted_surveys(&ctx, older_than)
+                .await?;
+        }
+
+        event!(
+            Level::INFO,
+            "Data retention: downsampled {} market observations, pruned {} ships and {} surveys older than {}",
+            report.downsampled_market_observations,
+            report.pruned_ships,
+            report.pruned_surveys,
+            older_than
+        );
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::in_memory_universe::in_memory_test_universe::get_test_universe;
+    use crate::test_objects::TestObjects;
+    use st_domain::ShipSymbol;
+
+    #[tokio::test]
+    async fn prunes_the_ships_of_agents_that_are_not_configured_anymore() {
+        let (bmc, _client) = get_test_universe().await;
+        let now = Utc::now();
+        let last_week = now - TimeDelta::days(7);
+
+        let ship = |symbol: &str| {
+            let mut ship = TestObjects::test_ship(100);
+            ship.symbol = ShipSymbol(symbol.to_string());
+            ship
+        };
+        let superseded = AgentSymbol("FLWI_OLD".to_string());
+        let active = AgentSymbol("FLWI_NEW".to_string());
+
+        bmc.ship_bmc()
+            .upsert_ships(&Ctx::Agent(superseded.clone()), &[ship("FLWI_OLD-1"), ship("FLWI_OLD-2")], last_week)
+            .await
+            .unwrap();
+        // idle ships of an active agent stay
+        bmc.ship_bmc()
+            .upsert_ships(&Ctx::Agent(active.clone()), &[ship("FLWI_NEW-1")], last_week)
+            .await
+            .unwrap();
+
+        let retention = DataRetention::new(Arc::clone(&bmc), NonZeroU32::new(48).unwrap(), vec![active]);
+        let report = retention.run(now).await.unwrap();
+        assert_eq!(report.pruned_ships, 2);
+
+        let remaining = bmc
+            .ship_bmc()
+            .get_ships(&Ctx::Anonymous, None)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|ship| ship.symbol.0)
+            .collect::<Vec<_>>();
+        assert_eq!(remaining, vec!["FLWI_NEW-1".to_string()]);
+
+        // nothing is left to prune with the next run
+        assert_eq!(retention.run(now).await.unwrap().pruned_ships, 0);
+    }
+}
@@ -0,0 +1,249 @@
+use crate::fleet::fleet::FleetAdmiral;
+use anyhow::Result;
+use chrono::Utc;
+use itertools::Itertools;
+use st_domain::budgeting::capital_allocation::{
+    compute_fleet_returns, estimate_remaining_construction_cost, CapitalAllocationDecision, CapitalAllocationInput, CapitalAllocationPolicy,
+    FleetCapitalDemand, ShipPurchaseCandidate,
+};
+use st_domain::budgeting::credits::Credits;
+use st_domain::budgeting::treasury_redesign::ThreadSafeTreasurer;
+use st_domain::FleetConfig::{ConstructJumpGateCfg, TradingCfg};
+use st_domain::{FleetPhaseName, SystemSymbol};
+use st_store::bmc::Bmc;
+use st_store::Ctx;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::{event, Level};
+
+/// Rebalances the fleet budgets with a pluggable `CapitalAllocationPolicy`.
+/// Runs on a fixed schedule and additionally whenever the fleet phase or the ship purchase demand changes.
+pub struct CapitalAllocator {
+    bmc: Arc<dyn Bmc>,
+    treasurer: ThreadSafeTreasurer,
+    policy: Arc<dyn CapitalAllocationPolicy>,
+    /// realized profits older than this don't count towards the ROI of a fleet
+    roi_window: chrono::Duration,
+}
+
+/// the parts of the admiral's state that trigger a rebalancing when they change
+#[derive(Clone, Debug, PartialEq)]
+struct AllocationTrigger {
+    fleet_phase_name: FleetPhaseName,
+    num_fleets: usize,
+    num_ship_purchase_demands: usize,
+}
+
+impl CapitalAllocator {
+    pub fn new(bmc: Arc<dyn Bmc>, treasurer: ThreadSafeTreasurer, policy: Arc<dyn CapitalAllocationPolicy>, roi_window: chrono::Duration) -> Self {
+        Self {
+            bmc,
+            treasurer,
+            policy,
+            roi_window,
+        }
+    }
+
+    pub async fn run_periodically(self, fleet_admiral: Arc<Mutex<FleetAdmiral>>, interval: Duration, check_interval: Duration) -> Result<()> {
+        let mut maybe_last_run: Option<(Instant, AllocationTrigger)> = None;
+
+        loop {
+            let trigger = {
+                let admiral = fleet_admiral.lock().await;
+                AllocationTrigger {
+                    fleet_phase_name: admiral.fleet_phase.name.clone(),
+                    num_fleets: admiral.fleets.len(),
+                    num_ship_purchase_demands: admiral.ship_purchase_demand.len(),
+                }
+            };
+
+            let is_due = match &maybe_last_run {
+                None => true,
+                Some((last_run, last_trigger)) => last_run.elapsed() >= interval || last_trigger != &trigger,
+            };
+
+            if is_due {
+                // a failed rebalancing leaves the budgets as they are - no reason to stop the fleets
+                if let Err(err) = self.rebalance(&fleet_admiral).await {
+                    event!(Level::ERROR, message = "Capital allocation failed", error = %err);
+                }
+                maybe_last_run = Some((Instant::now(), trigger));
+            }
+
+            tokio::time::sleep(check_interval).await;
+        }
+    }
+
+    pub async fn rebalance(&self, fleet_admiral: &Arc<Mutex<FleetAdmiral>>) -> Result<Vec<CapitalAllocationDecision>> {
+        let (fleet_phase_name, system_symbol, demands_without_construction, maybe_next_ship_purchase, ship_price_guesses) = {
+            let admiral = fleet_admiral.lock().await;
+            let strategy = admiral.strategy();
+
+            let demands = admiral
+                .fleets
+                .values()
+                .sorted_by_key(|fleet| fleet.id.clone())
+                .map(|fleet| {
+                    let ships_of_fleet = admiral.get_ships_of_fleet_id(&fleet.id);
+                    let (total_capital, operating_reserve) = FleetAdmiral::calc_required_operating_capital_for_fleet(fleet, &ships_of_fleet, &strategy.budgets);
+                    FleetCapitalDemand {
+                        fleet_id: fleet.id.clone(),
+                        num_ships: ships_of_fleet.len(),
+                        // the budget includes the operating reserve - otherwise it would be transferred back to the treasury
+                        base_budget: total_capital.max(operating_reserve),
+                        base_operating_reserve: operating_reserve,
+                        can_deploy_additional_capital: matches!(fleet.cfg, TradingCfg(_) | ConstructJumpGateCfg(_)),
+                        construction_need: Credits::default(),
+                    }
+                })
+                .collect_vec();
+
+            let maybe_next_ship_purchase = admiral
+                .get_next_ship_purchase()
+                .and_then(|(ship_type, fleet_task)| Some((ship_type, admiral.get_fleet_executing_fleet_task(&fleet_task)?)));
+
+            let system_symbol = admiral
+                .fleets
+                .values()
+                .find_map(|fleet| match &fleet.cfg {
+                    ConstructJumpGateCfg(cfg) => Some(cfg.system_symbol.clone()),
+                    _ => None,
+                })
+                .or_else(|| {
+                    admiral
+                        .all_ships
+                        .values()
+                        .next()
+                        .map(|s| s.nav.system_symbol.clone())
+                });
+
+            (
+                admiral.fleet_phase.name.clone(),
+                system_symbol,
+                demands,
+                maybe_next_ship_purchase,
+                strategy.ship_price_guesses,
+            )
+        };
+
+        // the spawning fleet has a fixed bootstrap budget until we know the system
+        if fleet_phase_name == FleetPhaseName::InitialExploration {
+            return Ok(vec![]);
+        }
+
+        let Some(system_symbol) = system_symbol else {
+            return Ok(vec![]);
+        };
+
+        let demands = self
+            .add_construction_needs(demands_without_construction, fleet_admiral, &system_symbol)
+            .await?;
+
+        let maybe_next_ship_purchase = match maybe_next_ship_purchase {
+            None => None,
+            Some((ship_type, beneficiary_fleet_id)) => {
+                let ship_prices = self
+                    .bmc
+                    .shipyard_bmc()
+                    .get_latest_ship_prices(&Ctx::Anonymous, &system_symbol)
+                    .await?
+                    .with_price_guesses(ship_price_guesses);
+                ship_prices
+                    .get_best_purchase_location(&ship_type)
+                    .map(|(_, (_, price))| price)
+                    .or_else(|| ship_prices.guess_price_for_ship(&ship_type))
+                    .map(|price| ShipPurchaseCandidate {
+                        ship_type,
+                        beneficiary_fleet_id,
+                        price: (price as i64).into(),
+                    })
+            }
+        };
+
+        let ledger_entries = self
+            .bmc
+            .ledger_bmc()
+            .get_timestamped_ledger_entries_in_order(&Ctx::Anonymous)
+            .await?;
+        let treasurer = self.treasurer.get_instance().await?;
+        let current_budgets = treasurer.get_fleet_budgets()?;
+        let fleet_returns = compute_fleet_returns(&ledger_entries, &current_budgets, Utc::now() - self.roi_window);
+
+        let decisions = self.policy.allocate(&CapitalAllocationInput {
+            agent_credits: treasurer.current_agent_credits(),
+            current_budgets: &current_budgets,
+            demands: &demands,
+            fleet_returns: &fleet_returns,
+            maybe_next_ship_purchase,
+        });
+
+        for decision in decisions.iter() {
+            event!(
+                Level::INFO,
+                message = "Applying capital allocation decision",
+                policy = self.policy.name(),
+                fleet_id = decision.fleet_id.0,
+                new_total_capital = decision.new_total_capital.0,
+                new_operating_reserve = decision.new_operating_reserve.0,
+                reason = decision.reason,
+            );
+            self.treasurer
+                .apply_capital_allocation_decision(decision)
+                .await?;
+        }
+
+        Ok(decisions)
+    }
+
+    async fn add_construction_needs(
+        &self,
+        demands: Vec<FleetCapitalDemand>,
+        fleet_admiral: &Arc<Mutex<FleetAdmiral>>,
+        system_symbol: &SystemSymbol,
+    ) -> Result<Vec<FleetCapitalDemand>> {
+        let construction_fleet_ids = {
+            let admiral = fleet_admiral.lock().await;
+            admiral
+                .fleets
+                .values()
+                .filter(|fleet| matches!(fleet.cfg, ConstructJumpGateCfg(_)))
+                .map(|fleet| fleet.id.clone())
+                .collect_vec()
+        };
+
+        if construction_fleet_ids.is_empty() {
+            return Ok(demands);
+        }
+
+        let maybe_construction_site = self
+            .bmc
+            .construction_bmc()
+            .get_construction_site_for_system(&Ctx::Anonymous, system_symbol.clone())
+            .await?;
+
+        let remaining_construction_cost = match maybe_construction_site {
+            Some(construction_site) if !construction_site.is_complete => {
+                let market_entries = self
+                    .bmc
+                    .market_bmc()
+                    .get_latest_market_data_for_system(&Ctx::Anonymous, system_symbol)
+                    .await?;
+                estimate_remaining_construction_cost(&construction_site, &market_entries)
+            }
+            _ => Credits::default(),
+        };
+
+        Ok(demands
+            .into_iter()
+            .map(|demand| FleetCapitalDemand {
+                construction_need: if construction_fleet_ids.contains(&demand.fleet_id) {
+                    remaining_construction_cost
+                } else {
+                    Credits::default()
+                },
+                ..demand
+            })
+            .collect_vec())
+    }
+}
@@ -11,6 +11,7 @@ use st_domain::budgeting::treasury_redesign::{
     ActiveTradeRoute, DeliverConstructionMaterialsTicketDetails, FinanceTicketDetails, FleetBudget, LedgerEntry, PurchaseCargoReason,
     PurchaseTradeGoodsTicketDetails, SellTradeGoodsTicketDetails,
 };
+use st_domain::competitor_intelligence::discount_contested_trading_opportunities;
 use st_domain::{
     calc_scored_supply_chain_routes, trading, ActivityLevel, ConstructJumpGateFleetConfig, Construction, EvaluatedTradingOpportunity, Fleet, FleetId,
     FleetPhase, FleetTask, FleetTaskCompletion, Inventory, LabelledCoordinate, MarketEntry, MarketTradeGood, MaterializedSupplyChain,
@@ -69,6 +70,7 @@ impl ConstructJumpGateFleet {
         active_trade_routes: &HashSet<ActiveTradeRoute>,
         fleet_budget: &FleetBudget,
         blocked_budget_for_contracts: Credits,
+        market_contestation_scores: &HashMap<WaypointSymbol, f64>,
     ) -> Result<NewTasksResultForConstructionFleet> {
         let fleet_ships: Vec<&Ship> = admiral.get_ships_of_fleet(fleet);
 
@@ -139,6 +141,11 @@ impl ConstructJumpGateFleet {
 
         let market_data: Vec<(WaypointSymbol, Vec<MarketTradeGood>)> = trading::to_trade_goods_with_locations(latest_market_entries);
         let trading_opportunities = trading::find_trading_opportunities_sorted_by_profit_per_distance_unit(&market_data, &waypoint_map, &no_go_trades);
+        let trading_opportunities = discount_contested_trading_opportunities(
+            trading_opportunities,
+            market_contestation_scores,
+            admiral.strategy().competitors.max_route_discount,
+        );
 
         let available_capital = fleet_budget.available_capital() - blocked_budget_for_contracts;
 
@@ -267,7 +274,7 @@ impl ConstructJumpGateFleet {
     }
 }
 
-fn create_tasks_for_ships_with_cargo(
+pub(crate) fn create_tasks_for_ships_with_cargo(
     ships_with_cargo: &Vec<&Ship>,
     latest_market_entries: &Vec<MarketEntry>,
     waypoint_map: &HashMap<WaypointSymbol, &Waypoint>,
@@ -373,8 +380,8 @@ async fn determine_construction_fleet_actions(
         .iter()
         .filter(|s| s.cargo.capacity > 0)
         .count() as u32;
-    let budget_per_trader: Credits = 75_000.into();
-    let budget_required_for_trading = budget_per_trader * num_of_traders;
+    let strategy = admiral.strategy();
+    let budget_required_for_trading = strategy.budgets.budget_per_trader * num_of_traders;
 
     let is_low_on_cash = fleet_budget.available_capital() < budget_required_for_trading;
 
@@ -384,11 +391,8 @@ async fn determine_construction_fleet_actions(
             .map(|cs| cs.missing_construction_materials())
             .unwrap_or_default();
 
-        let priority_map_of_construction_materials = HashMap::from([(TradeGoodSymbol::FAB_MATS, 1), (TradeGoodSymbol::ADVANCED_CIRCUITRY, 2)]);
-        let budget_limits_for_construction_materials = HashMap::from([
-            (TradeGoodSymbol::FAB_MATS, 125_000),
-            (TradeGoodSymbol::ADVANCED_CIRCUITRY, 750_000),
-        ]);
+        let priority_map_of_construction_materials = &strategy.construction.material_priorities;
+        let budget_limits_for_construction_materials = &strategy.construction.material_budget_limits;
 
         let goods_of_interest = materialized_supply_chain.goods_of_interest.clone();
 
@@ -432,10 +436,9 @@ async fn determine_construction_fleet_actions(
                     ActivityLevel::Strong => mtg.supply >= SupplyLevel::High,
                     _ => mtg.supply >= SupplyLevel::Moderate,
                 };
-                let budget_limit_for_construction_material: Credits = (*budget_limits_for_construction_materials
+                let budget_limit_for_construction_material: Credits = *budget_limits_for_construction_materials
                     .get(&mtg.symbol)
-                    .unwrap_or(&100_000_000))
-                .into();
+                    .unwrap_or(&strategy.construction.default_material_budget_limit);
                 let has_met_budget_requirement = available_capital > budget_limit_for_construction_material;
                 let no_ongoing_delivery = active_trade_routes
                     .iter()
@@ -459,34 +462,12 @@ async fn determine_construction_fleet_actions(
             })
             .collect_vec();
 
-        let boosted_trade_routes = scored_supply_chain_routes
-            .into_iter()
-            .filter(|r| r.score > 0)
-            .filter(|r| {
-                active_trade_routes
-                    .iter()
-                    .any(|atr| {
-                        atr.from == r.tgr.source_location
-                            && atr.to == r.tgr.delivery_location
-                            && atr.trade_good == r.tgr.trade_good
-                            && r.num_allowed_parallel_pickups <= atr.number_ongoing_trades as u32
-                    })
-                    .not()
-            })
-            .take(unassigned_ships_of_fleet.len())
-            .map(|r| BoostSupplyChain {
-                trade_good_symbol: r.tgr.trade_good.clone(),
-                from: r.tgr.source_location.clone(),
-                to: r.tgr.delivery_location.clone(),
-                scored_supply_chain_support_route: r.clone(),
-                units: r
-                    .tgr
-                    .source_market_entry
-                    .trade_volume
-                    .min(r.tgr.delivery_market_entry.trade_volume) as u32,
-                estimated_costs: Credits::from(r.purchase_price) * r.tgr.source_market_entry.trade_volume,
-            })
-            .collect_vec();
+        // the dedicated supply chain boosting fleet takes care of feeding the producers - we don't compete with it for the same routes
+        let boosted_trade_routes = if admiral.has_supply_chain_boosting_ships() {
+            vec![]
+        } else {
+            create_boost_supply_chain_actions(&scored_supply_chain_routes, active_trade_routes, unassigned_ships_of_fleet.len())
+        };
 
         if is_low_on_cash {
             profitable_trading_actions
@@ -514,7 +495,43 @@ async fn determine_construction_fleet_actions(
     Ok(prioritized_actions)
 }
 
-fn find_best_combination(
+/// Picks the best scored routes that aren't already served by enough ongoing trades.
+pub(crate) fn create_boost_supply_chain_actions(
+    scored_supply_chain_routes: &[ScoredSupplyChainSupportRoute],
+    active_trade_routes: &HashSet<ActiveTradeRoute>,
+    max_num_actions: usize,
+) -> Vec<ConstructionFleetAction> {
+    scored_supply_chain_routes
+        .iter()
+        .filter(|r| r.score > 0)
+        .filter(|r| {
+            active_trade_routes
+                .iter()
+                .any(|atr| {
+                    atr.from == r.tgr.source_location
+                        && atr.to == r.tgr.delivery_location
+                        && atr.trade_good == r.tgr.trade_good
+                        && r.num_allowed_parallel_pickups <= atr.number_ongoing_trades as u32
+                })
+                .not()
+        })
+        .take(max_num_actions)
+        .map(|r| BoostSupplyChain {
+            trade_good_symbol: r.tgr.trade_good.clone(),
+            from: r.tgr.source_location.clone(),
+            to: r.tgr.delivery_location.clone(),
+            scored_supply_chain_support_route: r.clone(),
+            units: r
+                .tgr
+                .source_market_entry
+                .trade_volume
+                .min(r.tgr.delivery_market_entry.trade_volume) as u32,
+            estimated_costs: Credits::from(r.purchase_price) * r.tgr.source_market_entry.trade_volume,
+        })
+        .collect_vec()
+}
+
+pub(crate) fn find_best_combination(
     ships: &[&Ship],
     actions: &[ConstructionFleetAction],
     waypoint_map: &HashMap<WaypointSymbol, &Waypoint>,
@@ -966,6 +983,8 @@ fn find_best_selling_location_for_inventory_entry(
 mod tests {
     use super::*;
     use crate::materialized_supply_chain_manager::MaterializedSupplyChainManager;
+    use crate::mining_site_manager::MiningSiteManager;
+    use crate::strategy::StrategyStore;
     use st_domain::budgeting::test_sync_ledger::create_test_ledger_setup;
     use st_domain::budgeting::treasury_redesign::{ImprovedTreasurer, ThreadSafeTreasurer};
     use tokio::test;
@@ -1079,6 +1098,8 @@ available_capital_after {available_capital_after} < 4_000c.
             treasurer: treasurer.clone(),
             materialized_supply_chain_manager,
             ship_purchase_demand: input.admiral_ship_purchase_demand.clone(),
+            mining_site_manager: MiningSiteManager::default(),
+            strategy_store: StrategyStore::default(),
         };
 
         let actual_tasks = ConstructJumpGateFleet::compute_ship_tasks(
@@ -1093,6 +1114,7 @@ available_capital_after {available_capital_after} < 4_000c.
             &input.active_trade_routes,
             &input.fleet_budget,
             0.into(),
+            &HashMap::new(),
         )
         .await?;
         Ok(actual_tasks)
@@ -1,35 +1,50 @@
+use crate::behavior_tree::behavior_definitions::ShipBehaviorStore;
 use crate::behavior_tree::ship_behaviors::ShipAction;
 use crate::contract_manager;
+use crate::contract_manager::ContractTicketScope;
 use crate::fleet::construction_fleet::{CargoDeliveryAction, ConstructJumpGateFleet, NewTasksResultForConstructionFleet};
 use crate::fleet::fleet_runner::FleetRunner;
 use crate::fleet::initial_data_collector::load_and_store_initial_data_in_bmcs;
 use crate::fleet::market_observation_fleet::MarketObservationFleet;
 use crate::fleet::mining_fleet::MiningFleet;
 use crate::fleet::siphoning_fleet::SiphoningFleet;
+use crate::fleet::supply_chain_boosting_fleet::SupplyChainBoostingFleet;
 use crate::fleet::supply_chain_test::format_number;
 use crate::fleet::system_spawning_fleet::SystemSpawningFleet;
 use crate::marketplaces::marketplaces::{find_marketplaces_for_exploration, find_shipyards_for_exploration};
 use crate::materialized_supply_chain_manager::MaterializedSupplyChainManager;
+use crate::mining_site_manager::{with_mining_site, MiningSiteManager};
 use crate::pagination::fetch_all_pages;
+use crate::ship_purchase_planner::{plan_ship_purchase_batch, PurchaserCandidate};
 use crate::st_client::StClientTrait;
+use crate::strategy::StrategyStore;
 use crate::transfer_cargo_manager::TransferCargoManager;
 use anyhow::{anyhow, Result};
-use chrono::Utc;
+use chrono::{TimeDelta, Utc};
 use comfy_table::presets::UTF8_FULL;
 use comfy_table::{ContentArrangement, Table};
 use itertools::Itertools;
 use pathfinding::num_traits::Zero;
 use serde::{Deserialize, Serialize};
 use st_domain::budgeting::credits::Credits;
-use st_domain::budgeting::treasury_redesign::{ActiveTradeRoute, FinanceTicket, FinanceTicketDetails, FleetBudget, LedgerArchiveTask, ThreadSafeTreasurer};
+use st_domain::budgeting::ledger_audit::{decide_drift_resolution, CreditDriftResolution, LedgerAuditConfig};
+use st_domain::budgeting::treasury_redesign::{
+    ActiveTradeRoute, FinanceTicket, FinanceTicketDetails, FleetBudget, ImprovedTreasurer, LedgerArchiveTask, LedgerEntry, ThreadSafeTreasurer,
+};
+use st_domain::competitor_intelligence::measure_market_contestations;
+use st_domain::extraction_analytics::{ExtractionYieldReport, YIELD_LOOKBACK_HOURS};
+use st_domain::strategy::{BudgetStrategy, ContractStrategy, ShoppingListStrategy, StrategyConfig};
+use st_domain::supply_chain_boost::SupplyChainBoostEvaluation;
 use st_domain::FleetConfig::SystemSpawningCfg;
-use st_domain::FleetTask::{ConstructJumpGate, InitialExploration, MineOres, ObserveAllWaypointsOfSystemWithStationaryProbes, SiphonGases, TradeProfitably};
+use st_domain::FleetTask::{
+    BoostSupplyChains, ConstructJumpGate, InitialExploration, MineOres, ObserveAllWaypointsOfSystemWithStationaryProbes, SiphonGases, TradeProfitably,
+};
 use st_domain::{
-    trading, ConstructJumpGateFleetConfig, Contract, ContractEvaluationResult, Fleet, FleetConfig, FleetDecisionFacts, FleetId, FleetPhase, FleetPhaseName,
-    FleetTask, FleetTaskCompletion, MarketEntry, MarketObservationFleetConfig, MarketTradeGood, MaterializedSupplyChain, MiningFleetConfig,
+    trading, ConstructJumpGateFleetConfig, Contract, ContractEntry, ContractEvaluationResult, Fleet, FleetConfig, FleetDecisionFacts, FleetId, FleetPhase,
+    FleetPhaseName, FleetTask, FleetTaskCompletion, MarketEntry, MarketObservationFleetConfig, MarketTradeGood, MaterializedSupplyChain, MiningFleetConfig,
     OperationExpenseEvent, Ship, ShipFrameSymbol, ShipPriceInfo, ShipRegistrationRole, ShipSymbol, ShipTask, ShipTaskCompletionAnalysis, ShipType,
-    SiphoningFleetConfig, StationaryProbeLocation, SystemSpawningFleetConfig, SystemSymbol, TicketId, TradingFleetConfig, TransactionActionEvent, Waypoint,
-    WaypointSymbol, WaypointType,
+    SiphoningFleetConfig, StationaryProbeLocation, SupplyChainBoostingFleetConfig, SystemSpawningFleetConfig, SystemSymbol, TicketId, TradingFleetConfig,
+    TransactionActionEvent, Waypoint, WaypointSymbol, WaypointType,
 };
 use st_store::bmc::Bmc;
 use st_store::{load_fleet_overview, upsert_fleets_data, Ctx};
@@ -42,7 +57,7 @@ use strum::Display;
 use tokio::sync::Mutex;
 use tokio::task::JoinHandle;
 use tracing::{error, event, Level};
-use FleetConfig::{ConstructJumpGateCfg, MarketObservationCfg, MiningCfg, SiphoningCfg, TradingCfg};
+use FleetConfig::{ConstructJumpGateCfg, MarketObservationCfg, MiningCfg, SiphoningCfg, SupplyChainBoostingCfg, TradingCfg};
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub enum ShipStatusReport {
@@ -76,10 +91,23 @@ pub struct FleetAdmiral {
     pub stationary_probe_locations: Vec<StationaryProbeLocation>,
     pub treasurer: ThreadSafeTreasurer,
     pub materialized_supply_chain_manager: MaterializedSupplyChainManager,
+    pub mining_site_manager: MiningSiteManager,
     pub ship_purchase_demand: VecDeque<(ShipType, FleetTask)>,
+    pub strategy_store: StrategyStore,
+}
+
+/// What the fleet runner takes over from the treasurer initialization.
+pub struct TreasurerStartup {
+    pub archiver_join_handle: JoinHandle<()>,
+    /// drift between agent credits and replayed ledger - the ledger auditor corrects it once it confirms the drift
+    pub pending_credit_drift: Option<Credits>,
 }
 
 impl FleetAdmiral {
+    pub fn strategy(&self) -> StrategyConfig {
+        self.strategy_store.get_strategy()
+    }
+
     pub async fn redistribute_distribute_fleet_budgets(&self, _ship_price_info: &ShipPriceInfo, system_symbol: &SystemSymbol) -> Result<()> {
         if self.treasurer.get_active_tickets().await?.is_empty().not() {
             event!(Level::WARN, message = "called redistribute_fleet_budgets with active_tickets");
@@ -94,6 +122,8 @@ impl FleetAdmiral {
             treasury_credits = treasury_credits_before_rebalancing.0
         );
 
+        let budgets = self.strategy().budgets;
+
         //FIXME: make sure this works with multiple systems
         self.treasurer.remove_all_fleets().await?;
 
@@ -110,7 +140,7 @@ impl FleetAdmiral {
                     })
                     .unwrap();
                 self.treasurer
-                    .create_fleet(&spawning_fleet, 25_000.into())
+                    .create_fleet(&spawning_fleet, budgets.spawning_fleet_budget)
                     .await?;
                 self.treasurer
                     .transfer_funds_to_fleet_to_top_up_available_capital(&spawning_fleet)
@@ -143,12 +173,19 @@ impl FleetAdmiral {
                     })
                     .unwrap();
 
+                let supply_chain_boosting_fleet = self
+                    .get_fleet_executing_fleet_task(&BoostSupplyChains {
+                        system_symbol: system_symbol.clone(),
+                    })
+                    .unwrap();
+
                 let number_of_traders = self.get_ships_of_fleet_id(&construction_fleet).len() as u32;
-                let budget_per_trader: Credits = 75_000.into();
-                let reserve_per_trader: Credits = 1_000.into();
+                let number_of_boosting_haulers = self
+                    .get_ships_of_fleet_id(&supply_chain_boosting_fleet)
+                    .len() as u32;
 
                 self.treasurer
-                    .create_fleet(&construction_fleet, budget_per_trader * number_of_traders)
+                    .create_fleet(&construction_fleet, budgets.budget_per_trader * number_of_traders)
                     .await?;
 
                 self.treasurer
@@ -156,7 +193,7 @@ impl FleetAdmiral {
                     .await?;
 
                 self.treasurer
-                    .set_new_operating_reserve(&construction_fleet, reserve_per_trader * number_of_traders)
+                    .set_new_operating_reserve(&construction_fleet, budgets.fuel_reserve_per_ship * number_of_traders)
                     .await?;
 
                 self.treasurer
@@ -164,24 +201,34 @@ impl FleetAdmiral {
                     .await?;
 
                 self.treasurer
-                    .create_fleet(&siphoning_fleet, 5_000.into())
+                    .create_fleet(&siphoning_fleet, budgets.siphoning_fleet_budget)
                     .await?;
                 self.treasurer
-                    .set_new_operating_reserve(&siphoning_fleet, 5_000.into())
+                    .set_new_operating_reserve(&siphoning_fleet, budgets.siphoning_fleet_budget)
                     .await?;
                 self.treasurer
                     .transfer_funds_to_fleet_to_top_up_available_capital(&siphoning_fleet)
                     .await?;
 
                 self.treasurer
-                    .create_fleet(&mining_fleet, 5_000.into())
+                    .create_fleet(&mining_fleet, budgets.mining_fleet_budget)
                     .await?;
                 self.treasurer
-                    .set_new_operating_reserve(&mining_fleet, 5_000.into())
+                    .set_new_operating_reserve(&mining_fleet, budgets.mining_fleet_budget)
                     .await?;
                 self.treasurer
                     .transfer_funds_to_fleet_to_top_up_available_capital(&mining_fleet)
                     .await?;
+
+                self.treasurer
+                    .create_fleet(&supply_chain_boosting_fleet, budgets.budget_per_trader * number_of_boosting_haulers)
+                    .await?;
+                self.treasurer
+                    .set_new_operating_reserve(&supply_chain_boosting_fleet, budgets.fuel_reserve_per_ship * number_of_boosting_haulers)
+                    .await?;
+                self.treasurer
+                    .transfer_funds_to_fleet_to_top_up_available_capital(&supply_chain_boosting_fleet)
+                    .await?;
             }
             FleetPhaseName::TradeProfitably => {}
         }
@@ -234,8 +281,10 @@ Fleet Budgets after rebalancing
         let all_ships_purchased = admiral.ship_purchase_demand.is_empty();
         let ships_of_fleet = admiral.get_ships_of_fleet(fleet);
 
-        let (new_total_capital, new_operating_reserve) = Self::calc_required_operating_capital_for_fleet(fleet, &ships_of_fleet);
-        let construction_budget = Credits::new(1_000_000);
+        let strategy = admiral.strategy();
+
+        let (new_total_capital, new_operating_reserve) = Self::calc_required_operating_capital_for_fleet(fleet, &ships_of_fleet, &strategy.budgets);
+        let construction_budget = strategy.construction.budget_after_all_ship_purchases;
 
         let new_total_capital = match fleet.cfg {
             SystemSpawningCfg(_) => 0.into(),
@@ -243,6 +292,7 @@ Fleet Budgets after rebalancing
             SiphoningCfg(_) => 0.into(),
             MiningCfg(_) => 0.into(),
             TradingCfg(_) => new_total_capital,
+            SupplyChainBoostingCfg(_) => new_total_capital,
             ConstructJumpGateCfg(_) => {
                 if all_ships_purchased {
                     new_total_capital + construction_budget
@@ -282,16 +332,17 @@ Fleet Budgets after rebalancing
         Ok(())
     }
 
-    pub(crate) fn calc_required_operating_capital_for_fleet(fleet: &Fleet, ships: &[&Ship]) -> (Credits, Credits) {
+    pub(crate) fn calc_required_operating_capital_for_fleet(fleet: &Fleet, ships: &[&Ship], budgets: &BudgetStrategy) -> (Credits, Credits) {
         let num_fuel_consuming_ships = ships.iter().filter(|s| s.fuel.capacity > 0).count() as u32;
         let num_trading_ships = ships.iter().filter(|s| s.cargo.capacity > 0).count() as u32;
 
-        let required_fuel_budget = Credits::new(1_000) * num_fuel_consuming_ships;
-        let required_trading_budget = Credits::new(75_000) * num_trading_ships;
+        let required_fuel_budget = budgets.fuel_reserve_per_ship * num_fuel_consuming_ships;
+        let required_trading_budget = budgets.budget_per_trader * num_trading_ships;
 
         let (total_capital, operating_reserve) = match fleet.cfg {
             TradingCfg(_) => (required_trading_budget, required_fuel_budget),
             ConstructJumpGateCfg(_) => (required_trading_budget, required_fuel_budget),
+            SupplyChainBoostingCfg(_) => (required_trading_budget, required_fuel_budget),
             MiningCfg(_) => (Credits::new(0), required_fuel_budget),
             SiphoningCfg(_) => (Credits::new(0), required_fuel_budget),
             MarketObservationCfg(_) => (Credits::new(0), required_fuel_budget),
@@ -506,7 +557,8 @@ Fleet Budgets after rebalancing
         client: Arc<dyn StClientTrait>,
         bmc: Arc<dyn Bmc>,
         transfer_cargo_manager: Arc<TransferCargoManager>,
-        treasurer_archiver_join_handle: JoinHandle<()>,
+        ship_behavior_store: ShipBehaviorStore,
+        treasurer_startup: TreasurerStartup,
     ) -> Result<()> {
         event!(Level::INFO, "Running fleets");
 
@@ -515,8 +567,9 @@ Fleet Budgets after rebalancing
             Arc::clone(&client),
             bmc,
             Arc::clone(&transfer_cargo_manager),
+            ship_behavior_store,
             Duration::from_secs(5),
-            treasurer_archiver_join_handle,
+            treasurer_startup,
         )
         .await?;
 
@@ -526,6 +579,11 @@ Fleet Budgets after rebalancing
     pub async fn report_ship_action_completed(&mut self, ship_status_report: &ShipStatusReport, bmc: Arc<dyn Bmc>, _messages_in_queue: usize) -> Result<()> {
         match ship_status_report {
             ShipStatusReport::ShipActionCompleted(ship, ship_action) => {
+                if ship_action == &ShipAction::MoveToAlternativeMiningSite {
+                    self.persist_relocated_mining_site(&ship.symbol, Arc::clone(&bmc))
+                        .await?;
+                }
+
                 let maybe_fleet = self.get_fleet_of_ship(&ship.symbol);
 
                 let fleet_tasks: Vec<FleetTask> = maybe_fleet
@@ -607,6 +665,30 @@ Fleet Budgets after rebalancing
         }
     }
 
+    /// The ship has left its unstable mining site - we keep its task in sync, so that it doesn't return after a restart.
+    async fn persist_relocated_mining_site(&mut self, ship_symbol: &ShipSymbol, bmc: Arc<dyn Bmc>) -> Result<()> {
+        let maybe_updated_task = self
+            .mining_site_manager
+            .get_assigned_site(ship_symbol)
+            .zip(self.ship_tasks.get(ship_symbol))
+            .and_then(|(mining_waypoint, ship_task)| with_mining_site(ship_task, mining_waypoint));
+
+        if let Some(updated_task) = maybe_updated_task {
+            event!(
+                Level::INFO,
+                message = "Ship moved to another mining site",
+                ship = ship_symbol.0,
+                task = ?updated_task,
+            );
+            self.ship_tasks.insert(ship_symbol.clone(), updated_task);
+            bmc.ship_bmc()
+                .save_ship_tasks(&Ctx::Anonymous, &self.ship_tasks)
+                .await?;
+        }
+
+        Ok(())
+    }
+
     pub fn get_fleet_of_ship(&self, ship_symbol: &ShipSymbol) -> Option<&Fleet> {
         self.ship_fleet_assignment
             .get(ship_symbol)
@@ -621,19 +703,24 @@ Fleet Budgets after rebalancing
         self.fleet_tasks.get(fleet_id).cloned().unwrap_or_default()
     }
 
-    pub async fn load_or_create(bmc: Arc<dyn Bmc>, system_symbol: SystemSymbol, client: Arc<dyn StClientTrait>) -> Result<(Self, JoinHandle<()>)> {
+    pub async fn load_or_create(
+        bmc: Arc<dyn Bmc>,
+        system_symbol: SystemSymbol,
+        client: Arc<dyn StClientTrait>,
+        strategy_store: StrategyStore,
+    ) -> Result<(Self, TreasurerStartup)> {
         //make sure we have up-to-date agent info
         let agent = client.get_agent().await?;
         bmc.agent_bmc()
             .store_agent(&Ctx::Anonymous, &agent.data)
             .await?;
 
-        match Self::load_admiral(Arc::clone(&bmc)).await? {
+        match Self::load_admiral(Arc::clone(&bmc), strategy_store.clone()).await? {
             None => {
                 event!(Level::INFO, "loading admiral failed - creating a new one");
                 load_and_store_initial_data_in_bmcs(Arc::clone(&client), Arc::clone(&bmc)).await?;
 
-                let (admiral, treasurer_join_handle) = Self::create(Arc::clone(&bmc), system_symbol, Arc::clone(&client)).await?;
+                let (admiral, treasurer_startup) = Self::create(Arc::clone(&bmc), system_symbol, Arc::clone(&client), strategy_store).await?;
                 upsert_fleets_data(
                     Arc::clone(&bmc),
                     &Ctx::Anonymous,
@@ -643,13 +730,13 @@ Fleet Budgets after rebalancing
                     &admiral.ship_tasks,
                 )
                 .await?;
-                Ok((admiral, treasurer_join_handle))
+                Ok((admiral, treasurer_startup))
             }
-            Some((admiral, treasurer_archiver_join_handle)) => Ok((admiral, treasurer_archiver_join_handle)),
+            Some((admiral, treasurer_startup)) => Ok((admiral, treasurer_startup)),
         }
     }
 
-    pub async fn initialize_treasurer(bmc: Arc<dyn Bmc>) -> Result<(ThreadSafeTreasurer, JoinHandle<()>)> {
+    pub async fn initialize_treasurer(bmc: Arc<dyn Bmc>) -> Result<(ThreadSafeTreasurer, TreasurerStartup)> {
         let agent_info = bmc.agent_bmc().load_agent(&Ctx::Anonymous).await?;
 
         let replay_log = bmc
@@ -676,14 +763,14 @@ Fleet Budgets after rebalancing
             }
         });
 
-        let treasurer = if replay_log.is_empty() {
+        let (treasurer, pending_credit_drift) = if replay_log.is_empty() {
             let treasurer = ThreadSafeTreasurer::new(agent_info.credits.into(), archive_task_sender).await;
             event!(
                 Level::INFO,
                 message = "Created new Treasurer from agent_info",
                 agent_credits = agent_info.credits
             );
-            treasurer
+            (treasurer, None)
         } else {
             let num_ledger_entries = replay_log.len();
             let treasurer = ThreadSafeTreasurer::from_replayed_ledger_log(replay_log, archive_task_sender);
@@ -696,25 +783,46 @@ Fleet Budgets after rebalancing
                 agent_credits = agent_info.credits
             );
 
-            if treasurer_agent_credits.0 != agent_info.credits {
-                event!(
-                    Level::WARN,
-                    message = "Created new Treasurer from replay_log had difference in agent credits. Resetting treasurer",
-                    treasurer_agent_credits = treasurer_agent_credits.0,
-                    agent_credits = agent_info.credits
-                );
-                treasurer
-                    .reset_treasurer_due_to_agent_credit_diff(agent_info.credits.into())
-                    .await?;
-            }
+            let credit_drift = Credits::from(agent_info.credits) - treasurer_agent_credits;
+            // startup counts as the first audit - small drifts are only corrected after the ledger auditor has seen them a second time
+            let pending_credit_drift = match decide_drift_resolution(credit_drift, None, &LedgerAuditConfig::default()) {
+                CreditDriftResolution::NoDrift => None,
+                CreditDriftResolution::AwaitingConfirmation | CreditDriftResolution::Corrected { .. } => {
+                    event!(
+                        Level::WARN,
+                        message = "Created new Treasurer from replay_log had small difference in agent credits. Waiting for the ledger audit to confirm it",
+                        treasurer_agent_credits = treasurer_agent_credits.0,
+                        agent_credits = agent_info.credits
+                    );
+                    Some(credit_drift)
+                }
+                CreditDriftResolution::ExceedsThreshold { .. } => {
+                    event!(
+                        Level::WARN,
+                        message = "Created new Treasurer from replay_log had difference in agent credits. Resetting treasurer",
+                        treasurer_agent_credits = treasurer_agent_credits.0,
+                        agent_credits = agent_info.credits
+                    );
+                    treasurer
+                        .reset_treasurer_due_to_agent_credit_diff(agent_info.credits.into())
+                        .await?;
+                    None
+                }
+            };
 
-            treasurer
+            (treasurer, pending_credit_drift)
         };
 
-        Ok((treasurer, archiver_handle))
+        Ok((
+            treasurer,
+            TreasurerStartup {
+                archiver_join_handle: archiver_handle,
+                pending_credit_drift,
+            },
+        ))
     }
 
-    async fn load_admiral(bmc: Arc<dyn Bmc>) -> Result<Option<(Self, JoinHandle<()>)>> {
+    async fn load_admiral(bmc: Arc<dyn Bmc>, strategy_store: StrategyStore) -> Result<Option<(Self, TreasurerStartup)>> {
         let overview = load_fleet_overview(Arc::clone(&bmc), &Ctx::Anonymous).await?;
 
         if overview.fleets.is_empty() || overview.all_ships.is_empty() {
@@ -731,7 +839,8 @@ Fleet Budgets after rebalancing
 
             // recompute ship-tasks and persist them. Might have been outdated since last agent restart
             let facts = collect_fleet_decision_facts(Arc::clone(&bmc), &system_symbol).await?;
-            let fleet_phase = compute_fleet_phase_with_tasks(system_symbol.clone(), &facts, &overview.completed_fleet_tasks);
+            let strategy = strategy_store.get_strategy();
+            let fleet_phase = compute_fleet_phase_with_tasks(system_symbol.clone(), &facts, &overview.completed_fleet_tasks, &strategy.shopping_lists);
             let (fleets, fleet_tasks) = compute_fleets_with_tasks(&facts, &overview.fleets, &overview.fleet_task_assignments, &fleet_phase);
             let fleet_map: HashMap<FleetId, Fleet> = fleets.iter().map(|f| (f.id.clone(), f.clone())).collect();
             let fleet_task_map: HashMap<FleetId, Vec<FleetTask>> = fleet_tasks
@@ -789,12 +898,19 @@ Fleet Budgets after rebalancing
                 materialized_supply_chain_manager.register_materialized_supply_chain(system_symbol.clone(), msc)?;
             }
 
-            let (treasurer, treasurer_archiver_join_handle) = Self::initialize_treasurer(bmc.clone()).await?;
+            let (treasurer, treasurer_startup) = Self::initialize_treasurer(bmc.clone()).await?;
 
             treasurer.remove_tickets_with_0_units().await?;
 
             let current_ship_demands = get_all_next_ship_purchases(&ship_map, &fleet_phase);
 
+            let mining_site_manager = MiningSiteManager::new(strategy_store.clone());
+            let recent_survey_usages = bmc
+                .survey_bmc()
+                .get_survey_usages_since(&Ctx::Anonymous, Utc::now() - TimeDelta::hours(YIELD_LOOKBACK_HOURS))
+                .await?;
+            mining_site_manager.seed_observed_yields(&ExtractionYieldReport::from_survey_usages(&recent_survey_usages))?;
+
             let admiral = Self {
                 completed_fleet_tasks: overview.completed_fleet_tasks.clone(),
                 fleets: fleet_map,
@@ -808,13 +924,16 @@ Fleet Budgets after rebalancing
                 stationary_probe_locations: overview.stationary_probe_locations,
                 treasurer: treasurer.clone(),
                 materialized_supply_chain_manager,
+                mining_site_manager,
                 ship_purchase_demand: VecDeque::from(current_ship_demands),
+                strategy_store,
             };
 
             let ship_prices = bmc
                 .shipyard_bmc()
                 .get_latest_ship_prices(&Ctx::Anonymous, &system_symbol)
-                .await?;
+                .await?
+                .with_price_guesses(strategy.ship_price_guesses);
 
             admiral
                 .redistribute_distribute_fleet_budgets(&ship_prices, &system_symbol)
@@ -833,7 +952,7 @@ Fleet Budgets after rebalancing
             )
             .await?;
 
-            Ok(Some((admiral, treasurer_archiver_join_handle)))
+            Ok(Some((admiral, treasurer_startup)))
         }
     }
 
@@ -857,7 +976,12 @@ Fleet Budgets after rebalancing
         Ok(())
     }
 
-    pub async fn create(bmc: Arc<dyn Bmc>, system_symbol: SystemSymbol, client: Arc<dyn StClientTrait>) -> Result<(Self, JoinHandle<()>)> {
+    pub async fn create(
+        bmc: Arc<dyn Bmc>,
+        system_symbol: SystemSymbol,
+        client: Arc<dyn StClientTrait>,
+        strategy_store: StrategyStore,
+    ) -> Result<(Self, TreasurerStartup)> {
         let ships = bmc.ship_bmc().get_ships(&Ctx::Anonymous, None).await?;
         let stationary_probe_locations = bmc
             .ship_bmc()
@@ -897,7 +1021,8 @@ Fleet Budgets after rebalancing
             .load_completed_fleet_tasks(&Ctx::Anonymous)
             .await?;
 
-        let fleet_phase = compute_fleet_phase_with_tasks(system_symbol.clone(), &facts, &completed_tasks);
+        let strategy = strategy_store.get_strategy();
+        let fleet_phase = compute_fleet_phase_with_tasks(system_symbol.clone(), &facts, &completed_tasks, &strategy.shopping_lists);
 
         let (fleets, fleet_tasks) = compute_fleets_with_tasks(&facts, &HashMap::new(), &HashMap::new(), &fleet_phase);
 
@@ -923,14 +1048,15 @@ Fleet Budgets after rebalancing
             materialized_supply_chain_manager.register_materialized_supply_chain(system_symbol.clone(), msc)?;
         }
 
-        let (treasurer, treasurer_archiver_join_handle) = Self::initialize_treasurer(bmc.clone()).await?;
+        let (treasurer, treasurer_startup) = Self::initialize_treasurer(bmc.clone()).await?;
 
         let current_ship_demands = get_all_next_ship_purchases(&ship_map, &fleet_phase);
 
         let ship_prices = bmc
             .shipyard_bmc()
             .get_latest_ship_prices(&Ctx::Anonymous, &system_symbol)
-            .await?;
+            .await?
+            .with_price_guesses(strategy.ship_price_guesses);
 
         let admiral = Self {
             completed_fleet_tasks: completed_tasks,
@@ -944,7 +1070,9 @@ Fleet Budgets after rebalancing
             stationary_probe_locations,
             treasurer,
             materialized_supply_chain_manager,
+            mining_site_manager: MiningSiteManager::new(strategy_store.clone()),
             ship_purchase_demand: VecDeque::from(current_ship_demands),
+            strategy_store,
         };
 
         admiral
@@ -961,7 +1089,7 @@ Fleet Budgets after rebalancing
         )
         .await?;
 
-        Ok((admiral, treasurer_archiver_join_handle))
+        Ok((admiral, treasurer_startup))
     }
 
     pub(crate) async fn pure_compute_ship_tasks(
@@ -973,7 +1101,9 @@ Fleet Budgets after rebalancing
         active_tickets: &[FinanceTicket],
         fleet_budgets: &HashMap<FleetId, FleetBudget>,
         active_trade_routes: &HashSet<ActiveTradeRoute>,
-        maybe_current_contract: &Option<Contract>,
+        maybe_current_contract: &Option<ContractEntry>,
+        supply_chain_boost_evaluations: &[SupplyChainBoostEvaluation],
+        market_contestation_scores: &HashMap<WaypointSymbol, f64>,
     ) -> Result<Vec<(ShipSymbol, ShipTask)>> {
         let mut new_ship_tasks: HashMap<ShipSymbol, ShipTask> = HashMap::new();
 
@@ -1030,6 +1160,7 @@ Fleet Budgets after rebalancing
                     let mut new_construction_fleet_tasks: HashMap<ShipSymbol, ShipTask> = HashMap::new();
 
                     let is_it_time_for_contracting = ships_of_fleet.len() > 1;
+                    let contract_strategy = admiral.strategy().contracts;
 
                     // only do contracts, if we have a hauler available
                     // could be improved by integrating the profitability of trades into the trading calculations, but that's already too complex imo
@@ -1041,6 +1172,7 @@ Fleet Budgets after rebalancing
                         &fleet_budget,
                         &latest_market_data,
                         &waypoints,
+                        &contract_strategy,
                     )? {
                         new_construction_fleet_tasks.insert(command_ship.clone(), ShipTask::ExecuteContracts);
 
@@ -1054,6 +1186,32 @@ Fleet Budgets after rebalancing
                         (unassigned_ships_of_fleet, 0.into())
                     };
 
+                    // contracts that don't fit into the cargo hold of the command ship get delivered by idle haulers as well
+                    let helper_haulers = match maybe_current_contract.as_ref().map(|entry| &entry.contract) {
+                        Some(contract) if is_it_time_for_contracting && contract.accepted && !contract.fulfilled => {
+                            assign_contract_deliveries_to_helper_haulers(
+                                admiral,
+                                fleet_id,
+                                contract,
+                                &unassigned_ships_of_fleet,
+                                active_tickets,
+                                &fleet_budget,
+                                &latest_market_data,
+                                &waypoints,
+                                &contract_strategy,
+                            )
+                            .await?
+                        }
+                        _ => vec![],
+                    };
+                    for helper_hauler in helper_haulers.iter() {
+                        new_construction_fleet_tasks.insert(helper_hauler.clone(), ShipTask::Trade);
+                    }
+                    let unassigned_ships_of_fleet = unassigned_ships_of_fleet
+                        .into_iter()
+                        .filter(|s| !helper_haulers.contains(&s.symbol))
+                        .collect_vec();
+
                     let either_compute_task_result = ConstructJumpGateFleet::compute_ship_tasks(
                         admiral,
                         cfg,
@@ -1066,143 +1224,60 @@ Fleet Budgets after rebalancing
                         active_trade_routes,
                         &fleet_budget,
                         blocked_budget,
+                        market_contestation_scores,
                     )
                     .await;
 
                     match either_compute_task_result {
                         Err(err) => Err(err),
-                        Ok(NewTasksResultForConstructionFleet {
-                            new_potential_construction_tasks,
-                            unassigned_ships_with_existing_tickets,
-                            deliver_tasks_from_existing_cargo,
-                        }) => {
-                            // local mutability, because you can't run async code inside iterator chains.
-                            // TODO: make this function pure again, by removing the treasurer... calls
-
-                            for (ship_symbol, deliver_from_cargo_tasks) in deliver_tasks_from_existing_cargo.clone() {
-                                let mut new_finance_tickets = Vec::new();
-                                for task in deliver_from_cargo_tasks {
-                                    let maybe_new_delivery_ticket = match task {
-                                        CargoDeliveryAction::SellOffCargoInventory {
-                                            trade_good_symbol,
-                                            units,
-                                            to,
-                                            delivery_market_entry,
-                                        } => admiral
-                                            .treasurer
-                                            .create_sell_trade_goods_ticket(
-                                                fleet_id,
-                                                trade_good_symbol,
-                                                to,
-                                                ship_symbol.clone(),
-                                                units,
-                                                delivery_market_entry.sell_price.into(),
-                                                None,
-                                            )
-                                            .await
-                                            .ok(),
-                                        CargoDeliveryAction::DeliverConstructionMaterialsFromCargo { trade_good_symbol, units, to } => admiral
-                                            .treasurer
-                                            .create_delivery_construction_material_ticket(fleet_id, trade_good_symbol, to, ship_symbol.clone(), units, None)
-                                            .await
-                                            .ok(),
-                                    };
-                                    if let Some(new_delivery_ticket) = maybe_new_delivery_ticket {
-                                        new_finance_tickets.push(new_delivery_ticket);
-                                    } else {
-                                        error!("Unable to create delivery ticket for deliver_tasks_from_existing_cargo for ship")
-                                    }
-                                }
-                            }
-
-                            for potential_construction_task in new_potential_construction_tasks.iter() {
-                                let purchase_details = potential_construction_task.create_purchase_ticket_details();
-
-                                if let Some(ship) = admiral
-                                    .all_ships
-                                    .get(&potential_construction_task.ship_symbol)
-                                {
-                                    if ship.cargo.capacity - ship.cargo.units < purchase_details.quantity as i32 {
-                                        println!("cargo doesn't fit");
-                                    }
-                                }
-
-                                let maybe_purchase_ticket = admiral
-                                    .treasurer
-                                    .create_purchase_trade_goods_ticket(
-                                        fleet_id,
-                                        purchase_details.trade_good,
-                                        purchase_details.waypoint_symbol,
-                                        potential_construction_task.ship_symbol.clone(),
-                                        purchase_details.quantity,
-                                        purchase_details.expected_price_per_unit,
-                                        purchase_details.purchase_cargo_reason,
-                                    )
-                                    .await
-                                    .ok()
-                                    .filter(|pt| pt.details.get_units() > 0);
-
-                                let maybe_sell_ticket = if let Some(purchase_ticket) = &maybe_purchase_ticket {
-                                    // we might not have been able to afford purchasing _all_ units
-                                    let affordable_units = purchase_ticket.details.get_units();
-
-                                    let sell_or_delivery_details = potential_construction_task.create_sell_or_deliver_ticket_details();
-
-                                    match sell_or_delivery_details {
-                                        FinanceTicketDetails::RefuelShip(_) => None,
-                                        FinanceTicketDetails::PurchaseShip(_) => None,
-                                        FinanceTicketDetails::PurchaseTradeGoods(_) => None,
-                                        FinanceTicketDetails::SellTradeGoods(d) => admiral
-                                            .treasurer
-                                            .create_sell_trade_goods_ticket(
-                                                fleet_id,
-                                                d.trade_good,
-                                                d.waypoint_symbol,
-                                                potential_construction_task.ship_symbol.clone(),
-                                                affordable_units,
-                                                d.expected_price_per_unit,
-                                                Some(purchase_ticket.ticket_id),
-                                            )
-                                            .await
-                                            .ok(),
-                                        FinanceTicketDetails::SupplyConstructionSite(d) => admiral
-                                            .treasurer
-                                            .create_delivery_construction_material_ticket(
-                                                fleet_id,
-                                                d.trade_good,
-                                                d.waypoint_symbol,
-                                                potential_construction_task.ship_symbol.clone(),
-                                                affordable_units,
-                                                Some(purchase_ticket.ticket_id),
-                                            )
-                                            .await
-                                            .ok(),
-                                        FinanceTicketDetails::DeliverContractCargo(_) => None,
-                                    }
-                                } else {
-                                    None
-                                };
-
-                                if maybe_purchase_ticket.zip(maybe_sell_ticket).is_some() {
-                                    new_construction_fleet_tasks.insert(potential_construction_task.ship_symbol.clone(), ShipTask::Trade);
-                                }
-                            }
-
-                            for ss in unassigned_ships_with_existing_tickets.iter() {
-                                new_construction_fleet_tasks.insert(ss.clone(), ShipTask::Trade);
-                            }
-
-                            for ss in deliver_tasks_from_existing_cargo.keys() {
-                                new_construction_fleet_tasks.insert(ss.clone(), ShipTask::Trade);
-                            }
-
+                        Ok(new_tasks_result) => {
+                            let new_trading_tasks = Self::create_tickets_for_new_trading_tasks(admiral, fleet_id, new_tasks_result).await;
+                            new_construction_fleet_tasks.extend(new_trading_tasks);
                             Ok(new_construction_fleet_tasks)
                         }
                     }
                 }
                 TradingCfg(_cfg) => Err(anyhow!("pure_compute_ship_tasks for TradingCfg not implemented yet")),
-                MiningCfg(cfg) => MiningFleet::compute_ship_tasks(cfg, &unassigned_ships_of_fleet),
+                MiningCfg(cfg) => {
+                    let delivery_locations = admiral
+                        .materialized_supply_chain_manager
+                        .get_mining_ops_config_for_system(cfg.system_symbol.clone())
+                        .map(|mining_ops_config| mining_ops_config.delivery_locations)
+                        .unwrap_or_default();
+                    let ranked_sites = admiral
+                        .mining_site_manager
+                        .rank_mining_sites(&waypoints, &delivery_locations)
+                        .into_iter()
+                        .map(|site| site.waypoint_symbol)
+                        .collect_vec();
+
+                    MiningFleet::compute_ship_tasks(
+                        cfg,
+                        &unassigned_ships_of_fleet,
+                        &ranked_sites,
+                        &admiral.ship_tasks,
+                        admiral.mining_site_manager.max_drones_per_site(),
+                    )
+                }
                 SiphoningCfg(cfg) => SiphoningFleet::compute_ship_tasks(cfg, &unassigned_ships_of_fleet),
+                SupplyChainBoostingCfg(cfg) => {
+                    let either_compute_task_result = SupplyChainBoostingFleet::compute_ship_tasks(
+                        admiral,
+                        cfg,
+                        &latest_market_data,
+                        &waypoints,
+                        &unassigned_ships_of_fleet,
+                        active_trade_routes,
+                        &fleet_budget,
+                        supply_chain_boost_evaluations,
+                    )
+                    .await;
+
+                    match either_compute_task_result {
+                        Err(err) => Err(err),
+                        Ok(new_tasks_result) => Ok(Self::create_tickets_for_new_trading_tasks(admiral, fleet_id, new_tasks_result).await),
+                    }
+                }
             };
 
             match either_computed_new_tasks {
@@ -1260,6 +1335,144 @@ Fleet Budgets after rebalancing
         Ok(new_ship_tasks.into_iter().collect_vec())
     }
 
+    /// Creates the purchase and sell/delivery tickets for the trading tasks computed by the construction or supply chain boosting fleet.
+    /// Ships that already have tickets or still carry cargo are put to trading as well.
+    async fn create_tickets_for_new_trading_tasks(
+        admiral: &FleetAdmiral,
+        fleet_id: &FleetId,
+        new_tasks_result: NewTasksResultForConstructionFleet,
+    ) -> HashMap<ShipSymbol, ShipTask> {
+        let NewTasksResultForConstructionFleet {
+            new_potential_construction_tasks,
+            unassigned_ships_with_existing_tickets,
+            deliver_tasks_from_existing_cargo,
+        } = new_tasks_result;
+
+        let mut new_tasks: HashMap<ShipSymbol, ShipTask> = HashMap::new();
+
+        // local mutability, because you can't run async code inside iterator chains.
+        // TODO: make this function pure again, by removing the treasurer... calls
+
+        for (ship_symbol, deliver_from_cargo_tasks) in deliver_tasks_from_existing_cargo.clone() {
+            let mut new_finance_tickets = Vec::new();
+            for task in deliver_from_cargo_tasks {
+                let maybe_new_delivery_ticket = match task {
+                    CargoDeliveryAction::SellOffCargoInventory {
+                        trade_good_symbol,
+                        units,
+                        to,
+                        delivery_market_entry,
+                    } => admiral
+                        .treasurer
+                        .create_sell_trade_goods_ticket(
+                            fleet_id,
+                            trade_good_symbol,
+                            to,
+                            ship_symbol.clone(),
+                            units,
+                            delivery_market_entry.sell_price.into(),
+                            None,
+                        )
+                        .await
+                        .ok(),
+                    CargoDeliveryAction::DeliverConstructionMaterialsFromCargo { trade_good_symbol, units, to } => admiral
+                        .treasurer
+                        .create_delivery_construction_material_ticket(fleet_id, trade_good_symbol, to, ship_symbol.clone(), units, None)
+                        .await
+                        .ok(),
+                };
+                if let Some(new_delivery_ticket) = maybe_new_delivery_ticket {
+                    new_finance_tickets.push(new_delivery_ticket);
+                } else {
+                    error!("Unable to create delivery ticket for deliver_tasks_from_existing_cargo for ship")
+                }
+            }
+        }
+
+        for potential_construction_task in new_potential_construction_tasks.iter() {
+            let purchase_details = potential_construction_task.create_purchase_ticket_details();
+
+            if let Some(ship) = admiral
+                .all_ships
+                .get(&potential_construction_task.ship_symbol)
+            {
+                if ship.cargo.capacity - ship.cargo.units < purchase_details.quantity as i32 {
+                    println!("cargo doesn't fit");
+                }
+            }
+
+            let maybe_purchase_ticket = admiral
+                .treasurer
+                .create_purchase_trade_goods_ticket(
+                    fleet_id,
+                    purchase_details.trade_good,
+                    purchase_details.waypoint_symbol,
+                    potential_construction_task.ship_symbol.clone(),
+                    purchase_details.quantity,
+                    purchase_details.expected_price_per_unit,
+                    purchase_details.purchase_cargo_reason,
+                )
+                .await
+                .ok()
+                .filter(|pt| pt.details.get_units() > 0);
+
+            let maybe_sell_ticket = if let Some(purchase_ticket) = &maybe_purchase_ticket {
+                // we might not have been able to afford purchasing _all_ units
+                let affordable_units = purchase_ticket.details.get_units();
+
+                let sell_or_delivery_details = potential_construction_task.create_sell_or_deliver_ticket_details();
+
+                match sell_or_delivery_details {
+                    FinanceTicketDetails::RefuelShip(_) => None,
+                    FinanceTicketDetails::PurchaseShip(_) => None,
+                    FinanceTicketDetails::PurchaseTradeGoods(_) => None,
+                    FinanceTicketDetails::SellTradeGoods(d) => admiral
+                        .treasurer
+                        .create_sell_trade_goods_ticket(
+                            fleet_id,
+                            d.trade_good,
+                            d.waypoint_symbol,
+                            potential_construction_task.ship_symbol.clone(),
+                            affordable_units,
+                            d.expected_price_per_unit,
+                            Some(purchase_ticket.ticket_id),
+                        )
+                        .await
+                        .ok(),
+                    FinanceTicketDetails::SupplyConstructionSite(d) => admiral
+                        .treasurer
+                        .create_delivery_construction_material_ticket(
+                            fleet_id,
+                            d.trade_good,
+                            d.waypoint_symbol,
+                            potential_construction_task.ship_symbol.clone(),
+                            affordable_units,
+                            Some(purchase_ticket.ticket_id),
+                        )
+                        .await
+                        .ok(),
+                    FinanceTicketDetails::DeliverContractCargo(_) => None,
+                }
+            } else {
+                None
+            };
+
+            if maybe_purchase_ticket.zip(maybe_sell_ticket).is_some() {
+                new_tasks.insert(potential_construction_task.ship_symbol.clone(), ShipTask::Trade);
+            }
+        }
+
+        for ss in unassigned_ships_with_existing_tickets.iter() {
+            new_tasks.insert(ss.clone(), ShipTask::Trade);
+        }
+
+        for ss in deliver_tasks_from_existing_cargo.keys() {
+            new_tasks.insert(ss.clone(), ShipTask::Trade);
+        }
+
+        new_tasks
+    }
+
     pub(crate) async fn compute_ship_tasks(admiral: &mut FleetAdmiral, facts: &FleetDecisionFacts, bmc: Arc<dyn Bmc>) -> Result<Vec<(ShipSymbol, ShipTask)>> {
         let system_symbol = facts.agent_info.headquarters.system_symbol();
 
@@ -1271,7 +1484,8 @@ Fleet Budgets after rebalancing
         let ship_prices = bmc
             .shipyard_bmc()
             .get_latest_ship_prices(&Ctx::Anonymous, &system_symbol)
-            .await?;
+            .await?
+            .with_price_guesses(admiral.strategy().ship_price_guesses);
 
         let latest_market_data = bmc
             .market_bmc()
@@ -1283,36 +1497,15 @@ Fleet Budgets after rebalancing
             .get_youngest_contract(&Ctx::Anonymous, &system_symbol)
             .await?;
 
-        // increase construction budget to 20M after all ships have been purchased
-        if admiral.ship_purchase_demand.is_empty() {
-            if admiral.fleet_phase.name == FleetPhaseName::ConstructJumpGate {
-                if let Some((construction_fleet_id, _)) = admiral
-                    .fleets
-                    .iter()
-                    .find(|(_id, fleet)| matches!(fleet.cfg, ConstructJumpGateCfg(_)))
-                {
-                    if let Ok(construction_fleet_budget) = admiral
-                        .treasurer
-                        .get_fleet_budget(construction_fleet_id)
-                        .await
-                    {
-                        let budget_during_construction_phase_after_ship_purchases = 20_000_000.into();
-                        if construction_fleet_budget.current_capital != budget_during_construction_phase_after_ship_purchases {
-                            admiral
-                                .treasurer
-                                .set_fleet_budget(construction_fleet_id, budget_during_construction_phase_after_ship_purchases)
-                                .await?;
-
-                            admiral
-                                .treasurer
-                                .transfer_funds_to_fleet_to_top_up_available_capital(construction_fleet_id)
-                                .await?;
-                        }
-                    }
-                }
-            }
-        } else {
-            admiral.try_create_ship_purchase_ticket(&ship_prices).await;
+        let supply_chain_boost_evaluations = Self::evaluate_supply_chain_boosts(admiral, bmc.clone(), &system_symbol).await?;
+
+        let market_contestation_scores = Self::evaluate_market_contestation(admiral, bmc.clone(), &system_symbol).await?;
+
+        // fleet budgets (including the construction budget) are rebalanced by the CapitalAllocator
+        if admiral.ship_purchase_demand.is_empty().not() {
+            admiral
+                .try_create_ship_purchase_ticket(&ship_prices, &waypoints)
+                .await;
         }
 
         let fleet_budgets = admiral.get_fleet_budgets().await;
@@ -1338,6 +1531,8 @@ Fleet Budgets after rebalancing
                 &fleet_budgets,
                 &HashSet::from_iter(active_trade_routes.iter().cloned()),
                 &maybe_youngest_contract,
+                &supply_chain_boost_evaluations,
+                &market_contestation_scores,
             )
             .await?
         };
@@ -1349,6 +1544,48 @@ Fleet Budgets after rebalancing
         Ok(new_tasks)
     }
 
+    /// Only evaluated if we have a supply chain boosting fleet with ships - loading the market history of the producers isn't free.
+    async fn evaluate_supply_chain_boosts(admiral: &FleetAdmiral, bmc: Arc<dyn Bmc>, system_symbol: &SystemSymbol) -> Result<Vec<SupplyChainBoostEvaluation>> {
+        let maybe_materialized_supply_chain = admiral
+            .materialized_supply_chain_manager
+            .get_materialized_supply_chain_for_system(system_symbol.clone());
+
+        match maybe_materialized_supply_chain {
+            Some(materialized_supply_chain) if admiral.has_supply_chain_boosting_ships() => {
+                let strategy = admiral.strategy().supply_chain_boosting;
+                let candidate_routes = SupplyChainBoostingFleet::candidate_routes(&strategy, &materialized_supply_chain);
+                SupplyChainBoostingFleet::evaluate_boosted_routes(bmc, &candidate_routes, &strategy, Utc::now()).await
+            }
+            _ => Ok(vec![]),
+        }
+    }
+
+    /// Scores how likely other agents trade at the markets of the system, so the trade planning can prefer the quiet routes.
+    /// Skipped if the strategy doesn't discount contested routes - loading the market history of the whole system isn't free.
+    async fn evaluate_market_contestation(admiral: &FleetAdmiral, bmc: Arc<dyn Bmc>, system_symbol: &SystemSymbol) -> Result<HashMap<WaypointSymbol, f64>> {
+        let strategy = admiral.strategy().competitors;
+        if strategy.max_route_discount <= 0.0 {
+            return Ok(HashMap::new());
+        }
+
+        let since = Utc::now() - TimeDelta::hours(strategy.history_hours);
+        let history = bmc
+            .market_bmc()
+            .get_market_history_of_system(&Ctx::Anonymous, system_symbol, since)
+            .await?;
+        let our_agent_symbols = bmc
+            .agent_bmc()
+            .list_agent_symbols(&Ctx::Anonymous)
+            .await?
+            .into_iter()
+            .collect();
+
+        Ok(measure_market_contestations(&history, &our_agent_symbols)
+            .into_iter()
+            .map(|contestation| (contestation.waypoint_symbol, contestation.score))
+            .collect())
+    }
+
     pub(crate) fn assign_ship_tasks(admiral: &mut FleetAdmiral, ship_tasks: Vec<(ShipSymbol, ShipTask)>) {
         for (ship_symbol, ship_task) in ship_tasks {
             admiral.ship_tasks.insert(ship_symbol, ship_task);
@@ -1453,6 +1690,13 @@ Json entries of all ledger entries after dismantling the fleets:\n{}
         self.get_ships_of_fleet_id(&fleet.id)
     }
 
+    /// the construction fleet leaves feeding the producers to the dedicated fleet - as soon as it has ships
+    pub(crate) fn has_supply_chain_boosting_ships(&self) -> bool {
+        self.fleets
+            .values()
+            .any(|fleet| matches!(fleet.cfg, SupplyChainBoostingCfg(_)) && self.get_ships_of_fleet_id(&fleet.id).is_empty().not())
+    }
+
     pub(crate) fn get_ships_of_fleet_id(&self, fleet_id: &FleetId) -> Vec<&Ship> {
         self.ship_fleet_assignment
             .iter()
@@ -1495,18 +1739,26 @@ Json entries of all ledger entries after dismantling the fleets:\n{}
         }
     }
 
-    async fn try_create_ship_purchase_ticket(&mut self, ship_prices: &ShipPriceInfo) {
+    async fn try_create_ship_purchase_ticket(&mut self, ship_prices: &ShipPriceInfo, waypoints: &[Waypoint]) {
         //println!("Overview before creation of ship purchase ticket:\n{}", self.generate_state_overview().await);
-        if let Ok(_) = self.create_ship_purchase_ticket(ship_prices).await {}
+        if let Ok(_) = self
+            .create_ship_purchase_ticket(ship_prices, waypoints)
+            .await
+        {}
         //println!("Overview after creation of ship purchase ticket:\n{}", self.generate_state_overview().await);
     }
 
-    async fn create_ship_purchase_ticket(&mut self, ship_prices: &ShipPriceInfo) -> Result<()> {
+    /// Creates the tickets for the next ship purchase demands - the ship_purchase_planner picks the shipyard and the purchasing ship
+    /// and batches the following demands that can be bought in the same visit.
+    /// Demands are only removed from the queue once their ticket has been created.
+    async fn create_ship_purchase_ticket(&mut self, ship_prices: &ShipPriceInfo, waypoints: &[Waypoint]) -> Result<()> {
         let treasurer = self.treasurer.clone();
+        let strategy = self.strategy().ship_purchases;
 
         let (ship_type, fleet_task) = self
             .ship_purchase_demand
-            .pop_front()
+            .front()
+            .cloned()
             .ok_or(anyhow!("No ship purchase demands available"))?;
 
         let maybe_existing_ship_purchase_ticket = treasurer
@@ -1519,10 +1771,6 @@ Json entries of all ledger entries after dismantling the fleets:\n{}
             });
 
         if let Some(ship_purchase_ticket) = maybe_existing_ship_purchase_ticket {
-            // put ticket back - we are already purchasing a ship
-            self.ship_purchase_demand
-                .push_front((ship_type, fleet_task));
-
             event!(
                 Level::INFO,
                 message = "There's already an ongoing ship purchase for this ship_type. No Op",
@@ -1534,65 +1782,102 @@ Json entries of all ledger entries after dismantling the fleets:\n{}
             return Ok(());
         }
 
-        let (_, (shipyard_wps, price)) = ship_prices
-            .get_best_purchase_location(&ship_type)
-            .ok_or(anyhow!("No shipyard found selling {ship_type}"))?;
-
-        let ship_price = ((price as f64 * 1.02) as i64).into();
-
-        let beneficiary_fleet = self
-            .get_fleet_executing_fleet_task(&fleet_task)
-            .ok_or(anyhow!("No fleet found executing task {fleet_task:?}"))?;
+        let coordinates: HashMap<WaypointSymbol, (i64, i64)> = waypoints
+            .iter()
+            .map(|wp| (wp.symbol.clone(), (wp.x, wp.y)))
+            .collect();
 
-        let purchasing_ship = self
-            .get_ship_purchaser(&fleet_task, &shipyard_wps)
-            .ok_or(anyhow!("No suitable purchasing ship found for {ship_type}"))?;
+        let demands = self
+            .ship_purchase_demand
+            .iter()
+            .take(strategy.max_batch_size as usize)
+            .map(|(ship_type, fleet_task)| {
+                let maybe_fleet_location = self
+                    .get_fleet_executing_fleet_task(fleet_task)
+                    .and_then(|fleet_id| self.get_location_of_fleet(&fleet_id));
+                (*ship_type, maybe_fleet_location)
+            })
+            .collect_vec();
 
-        let executing_fleet = self
-            .get_fleet_of_ship(&purchasing_ship)
-            .map(|f| f.id.clone())
-            .ok_or(anyhow!("Ship {} not assigned to any fleet", purchasing_ship))?;
+        let purchasers = self.get_ship_purchaser_candidates(&fleet_task);
+        let plans = plan_ship_purchase_batch(&demands, ship_prices, &purchasers, &coordinates, &strategy);
+        if plans.is_empty() {
+            event!(
+                Level::WARN,
+                "unable to find shipyard and ship_purchaser for {ship_type}. This should not happen."
+            );
+            return Err(anyhow!("No shipyard and suitable purchasing ship found for {ship_type}"));
+        }
 
-        match treasurer
-            .create_ship_purchase_ticket_financed_from_global_treasury(&beneficiary_fleet, ship_type, ship_price, shipyard_wps.clone(), purchasing_ship.clone())
-            .await
-        {
-            Ok(ticket) => {
-                event!(
-                    Level::INFO,
-                    message = "Created ship purchase ticket",
-                    ship_type = ship_type.to_string(),
-                    purchasing_ship = purchasing_ship.0,
-                    beneficiary_fleet = beneficiary_fleet.0,
-                    executing_fleet = executing_fleet.0,
-                    shipyard_wps = shipyard_wps.0,
-                    price = ship_price.0,
-                    ticket_id = ticket.ticket_id.to_string(),
-                );
-                Ok(())
-            }
+        let batch_size = plans.len();
+        for plan in plans {
+            let (_, fleet_task) = self
+                .ship_purchase_demand
+                .front()
+                .cloned()
+                .ok_or(anyhow!("No ship purchase demands available"))?;
+
+            let beneficiary_fleet = self
+                .get_fleet_executing_fleet_task(&fleet_task)
+                .ok_or(anyhow!("No fleet found executing task {fleet_task:?}"))?;
+
+            let executing_fleet = self
+                .get_fleet_of_ship(&plan.purchaser)
+                .map(|f| f.id.clone())
+                .ok_or(anyhow!("Ship {} not assigned to any fleet", plan.purchaser))?;
+
+            match treasurer
+                .create_ship_purchase_ticket_financed_from_global_treasury(
+                    &beneficiary_fleet,
+                    plan.ship_type,
+                    plan.price,
+                    plan.shipyard.clone(),
+                    plan.purchaser.clone(),
+                )
+                .await
+            {
+                Ok(ticket) => {
+                    self.ship_purchase_demand.pop_front();
+                    event!(
+                        Level::INFO,
+                        message = "Created ship purchase ticket",
+                        ship_type = plan.ship_type.to_string(),
+                        purchasing_ship = plan.purchaser.0,
+                        beneficiary_fleet = beneficiary_fleet.0,
+                        executing_fleet = executing_fleet.0,
+                        shipyard_wps = plan.shipyard.0,
+                        price = plan.price.0,
+                        total_costs = plan.total_costs().0,
+                        secs_until_working = plan.secs_until_working(),
+                        batch_size,
+                        ticket_id = ticket.ticket_id.to_string(),
+                    );
+                }
 
-            Err(err) => {
-                self.ship_purchase_demand
-                    .push_front((ship_type, fleet_task));
-                event!(
-                    Level::DEBUG,
-                    message = "Unable to create ship purchase ticket - this can be switched to DEBUG once everything's working",
-                    error = err.to_string(),
-                    ship_type = ship_type.to_string(),
-                    purchasing_ship = purchasing_ship.0,
-                    beneficiary_fleet = beneficiary_fleet.0,
-                    executing_fleet = executing_fleet.0,
-                    shipyard_wps = shipyard_wps.0,
-                    price = ship_price.0,
-                );
+                Err(err) => {
+                    event!(
+                        Level::DEBUG,
+                        message = "Unable to create ship purchase ticket - this can be switched to DEBUG once everything's working",
+                        error = err.to_string(),
+                        ship_type = plan.ship_type.to_string(),
+                        purchasing_ship = plan.purchaser.0,
+                        beneficiary_fleet = beneficiary_fleet.0,
+                        executing_fleet = executing_fleet.0,
+                        shipyard_wps = plan.shipyard.0,
+                        price = plan.price.0,
+                    );
 
-                Err(anyhow!(err))
+                    return Err(anyhow!(err));
+                }
             }
         }
+
+        Ok(())
     }
 
-    fn get_ship_purchaser(&self, for_fleet_task: &FleetTask, shipyard_wps: &WaypointSymbol) -> Option<ShipSymbol> {
+    /// Stationary probes only purchase at the shipyard they observe. The spawning ship and the command frigate can travel to any shipyard,
+    /// but the frigate's detour costs us its trades.
+    fn get_ship_purchaser_candidates(&self, for_fleet_task: &FleetTask) -> Vec<PurchaserCandidate> {
         let system_symbol = match for_fleet_task {
             InitialExploration { system_symbol } => system_symbol,
             ObserveAllWaypointsOfSystemWithStationaryProbes { system_symbol } => system_symbol,
@@ -1600,34 +1885,60 @@ Json entries of all ledger entries after dismantling the fleets:\n{}
             TradeProfitably { system_symbol } => system_symbol,
             MineOres { system_symbol } => system_symbol,
             SiphonGases { system_symbol } => system_symbol,
+            BoostSupplyChains { system_symbol } => system_symbol,
         };
+        let frigate_cost_per_hour = self.strategy().ship_purchases.frigate_cost_per_hour;
+
+        let stationary_probes = self.stationary_probe_locations.iter().filter_map(|spl| {
+            self.all_ships
+                .get(&spl.probe_ship_symbol)
+                .map(|ship| PurchaserCandidate {
+                    ship_symbol: ship.symbol.clone(),
+                    location: spl.waypoint_symbol.clone(),
+                    engine_speed: ship.engine.speed.max(1) as u32,
+                    is_stationary: true,
+                    cost_per_hour: 0.into(),
+                })
+        });
 
-        let purchase_candidates = self
-            .stationary_probe_locations
-            .iter()
-            .find(|spl| spl.waypoint_symbol == shipyard_wps.clone())
-            .map(|spl| spl.probe_ship_symbol.clone())
-            .or_else(|| {
-                self.find_spawning_ship_for_system(system_symbol)
-                    .or_else(|| {
-                        self.all_ships
-                            .values()
-                            .find(|s| s.frame.symbol == ShipFrameSymbol::FRAME_FRIGATE)
-                            .map(|s| s.symbol.clone())
-                    })
-            })
-            .into_iter()
-            .collect_vec();
+        let spawning_ship = self
+            .find_spawning_ship_for_system(system_symbol)
+            .and_then(|ss| self.all_ships.get(&ss))
+            .map(|ship| PurchaserCandidate {
+                ship_symbol: ship.symbol.clone(),
+                location: ship.nav.waypoint_symbol.clone(),
+                engine_speed: ship.engine.speed.max(1) as u32,
+                is_stationary: false,
+                cost_per_hour: 0.into(),
+            });
 
-        let maybe_result = purchase_candidates.first().cloned();
+        let frigates = self
+            .all_ships
+            .values()
+            .filter(|s| s.frame.symbol == ShipFrameSymbol::FRAME_FRIGATE)
+            .map(|ship| PurchaserCandidate {
+                ship_symbol: ship.symbol.clone(),
+                location: ship.nav.waypoint_symbol.clone(),
+                engine_speed: ship.engine.speed.max(1) as u32,
+                is_stationary: false,
+                cost_per_hour: frigate_cost_per_hour,
+            });
 
-        match maybe_result {
-            None => {
-                event!(Level::WARN, "unable to find ship_purchaser. This should not happen.");
-                None
-            }
-            Some(result) => Some(result),
-        }
+        stationary_probes
+            .chain(spawning_ship)
+            .chain(frigates)
+            .collect_vec()
+    }
+
+    /// The waypoint where most ships of the fleet are - a fleet without ships has no location yet.
+    fn get_location_of_fleet(&self, fleet_id: &FleetId) -> Option<WaypointSymbol> {
+        self.get_ships_of_fleet_id(fleet_id)
+            .iter()
+            .map(|ship| ship.nav.waypoint_symbol.clone())
+            .counts()
+            .into_iter()
+            .max_by_key(|(wps, count)| (*count, wps.clone()))
+            .map(|(wps, _)| wps)
     }
 
     fn find_spawning_ship_for_system(&self, spawning_system_symbol: &SystemSymbol) -> Option<ShipSymbol> {
@@ -1646,7 +1957,7 @@ Json entries of all ledger entries after dismantling the fleets:\n{}
         }
     }
 
-    fn get_fleet_executing_fleet_task(&self, fleet_task: &FleetTask) -> Option<FleetId> {
+    pub(crate) fn get_fleet_executing_fleet_task(&self, fleet_task: &FleetTask) -> Option<FleetId> {
         self.fleet_tasks
             .iter()
             .find_map(|(id, tasks)| tasks.contains(fleet_task).then_some(id.clone()))
@@ -1693,9 +2004,17 @@ pub async fn recompute_tasks_after_ship_finishing_behavior_tree(
             let ship_prices = bmc
                 .shipyard_bmc()
                 .get_latest_ship_prices(&Ctx::Anonymous, &ship.nav.system_symbol)
+                .await?
+                .with_price_guesses(admiral.strategy().ship_price_guesses);
+
+            let waypoints = bmc
+                .system_bmc()
+                .get_waypoints_of_system(&Ctx::Anonymous, &ship.nav.system_symbol)
                 .await?;
 
-            admiral.try_create_ship_purchase_ticket(&ship_prices).await;
+            admiral
+                .try_create_ship_purchase_ticket(&ship_prices, &waypoints)
+                .await;
 
             let new_tasks = FleetAdmiral::compute_ship_tasks(admiral, &facts, Arc::clone(&bmc)).await?;
             if let Some((ss, new_task_for_ship)) = new_tasks.iter().find(|(ss, _task)| ss == &ship.symbol) {
@@ -1760,6 +2079,11 @@ pub async fn recompute_tasks_after_ship_finishing_behavior_tree(
             ship_symbol: ship.symbol.clone(),
             task: finished_task.clone(),
         }),
+
+        ShipTask::HaulSiphonedGoods { .. } => Ok(NewTaskResult::AssignNewTaskToShip {
+            ship_symbol: ship.symbol.clone(),
+            task: finished_task.clone(),
+        }),
     }
 }
 
@@ -1802,6 +2126,9 @@ pub fn compute_fleet_configs(
                     system_symbol: system_symbol.clone(),
                     siphoning_waypoint: fleet_decision_facts.gas_giant.clone(),
                 })),
+                BoostSupplyChains { system_symbol } => Some(SupplyChainBoostingCfg(SupplyChainBoostingFleetConfig {
+                    system_symbol: system_symbol.clone(),
+                })),
             };
             maybe_cfg.map(|cfg| (cfg, t.clone()))
         })
@@ -1812,6 +2139,7 @@ pub fn compute_fleet_phase_with_tasks(
     system_symbol: SystemSymbol,
     fleet_decision_facts: &FleetDecisionFacts,
     completed_tasks: &[FleetTaskCompletion],
+    shopping_lists: &ShoppingListStrategy,
 ) -> FleetPhase {
     use FleetTask::*;
 
@@ -1880,15 +2208,15 @@ pub fn compute_fleet_phase_with_tasks(
     if !has_collected_all_waypoint_details_once {
         create_initial_exploration_fleet_phase(&system_symbol, num_shipyards_of_interest)
     } else if !is_jump_gate_done {
-        create_construction_fleet_phase(&system_symbol, num_shipyards_of_interest, num_marketplaces_ex_shipyards)
+        create_construction_fleet_phase(&system_symbol, num_shipyards_of_interest, num_marketplaces_ex_shipyards, shopping_lists)
     } else if is_jump_gate_done {
-        create_trade_profitably_fleet_phase(system_symbol, num_waypoints_of_interest)
+        create_trade_profitably_fleet_phase(system_symbol, num_waypoints_of_interest, shopping_lists)
     } else {
         unimplemented!("this shouldn't happen - think harder")
     }
 }
 
-pub fn create_trade_profitably_fleet_phase(system_symbol: SystemSymbol, num_waypoints_of_interest: usize) -> FleetPhase {
+pub fn create_trade_profitably_fleet_phase(system_symbol: SystemSymbol, num_waypoints_of_interest: usize, shopping_lists: &ShoppingListStrategy) -> FleetPhase {
     let tasks = [
         TradeProfitably {
             system_symbol: system_symbol.clone(),
@@ -1900,7 +2228,7 @@ pub fn create_trade_profitably_fleet_phase(system_symbol: SystemSymbol, num_wayp
 
     let probe_observation_task = tasks[1].clone();
 
-    let trading_fleet = [ShipType::SHIP_LIGHT_HAULER].repeat(4);
+    let trading_fleet = shopping_lists.trading_fleet.clone();
 
     let probe_observation_fleet = [ShipType::SHIP_PROBE].repeat(num_waypoints_of_interest);
 
@@ -1952,7 +2280,12 @@ pub fn create_initial_exploration_fleet_phase(system_symbol: &SystemSymbol, num_
     }
 }
 
-pub fn create_construction_fleet_phase(system_symbol: &SystemSymbol, num_shipyards_of_interest: usize, num_marketplaces_ex_shipyards: usize) -> FleetPhase {
+pub fn create_construction_fleet_phase(
+    system_symbol: &SystemSymbol,
+    num_shipyards_of_interest: usize,
+    num_marketplaces_ex_shipyards: usize,
+    shopping_lists: &ShoppingListStrategy,
+) -> FleetPhase {
     let tasks = [
         ConstructJumpGate {
             system_symbol: system_symbol.clone(),
@@ -1966,23 +2299,26 @@ pub fn create_construction_fleet_phase(system_symbol: &SystemSymbol, num_shipyar
         SiphonGases {
             system_symbol: system_symbol.clone(),
         },
+        BoostSupplyChains {
+            system_symbol: system_symbol.clone(),
+        },
     ];
 
     let shipyard_probes = [ShipType::SHIP_PROBE].repeat(num_shipyards_of_interest);
-    let construction_fleet = [vec![ShipType::SHIP_COMMAND_FRIGATE], [ShipType::SHIP_LIGHT_HAULER].repeat(3)].concat();
+    let construction_fleet = shopping_lists.construction_fleet.clone();
 
-    let mining_squad = [
-        vec![ShipType::SHIP_MINING_DRONE],
-        vec![
-            ShipType::SHIP_LIGHT_HAULER,
-            ShipType::SHIP_SURVEYOR,
-            ShipType::SHIP_MINING_DRONE,
-            ShipType::SHIP_MINING_DRONE,
-        ],
-    ]
-    .concat();
+    // the first mining squad is bought before the siphoning fleet, the remaining ones after it
+    let first_mining_squad = if shopping_lists.num_mining_squads > 0 {
+        shopping_lists.mining_squad.clone()
+    } else {
+        vec![]
+    };
+    let remaining_mining_squads = shopping_lists
+        .mining_squad
+        .repeat(shopping_lists.num_mining_squads.saturating_sub(1) as usize);
 
-    let siphoning_fleet = [ShipType::SHIP_SIPHON_DRONE].repeat(3);
+    let siphoning_fleet = shopping_lists.siphoning_fleet.clone();
+    let supply_chain_boosting_fleet = shopping_lists.supply_chain_boosting_fleet.clone();
 
     let other_probes = [ShipType::SHIP_PROBE].repeat(num_marketplaces_ex_shipyards);
 
@@ -1991,6 +2327,7 @@ pub fn create_construction_fleet_phase(system_symbol: &SystemSymbol, num_shipyar
     let probe_observation_task = tasks[1].clone();
     let mining_task = tasks[2].clone();
     let siphoning_task = tasks[3].clone();
+    let supply_chain_boosting_task = tasks[4].clone();
 
     let shopping_list_in_order = shipyard_probes
         .iter()
@@ -2009,7 +2346,7 @@ pub fn create_construction_fleet_phase(system_symbol: &SystemSymbol, num_shipyar
                 .map(|ship_type| (ship_type, construct_jump_gate_task.clone())),
         )
         .chain(
-            mining_squad
+            first_mining_squad
                 .iter()
                 .cloned()
                 .map(|ship_type| (ship_type, mining_task.clone())),
@@ -2021,7 +2358,13 @@ pub fn create_construction_fleet_phase(system_symbol: &SystemSymbol, num_shipyar
                 .map(|ship_type| (ship_type, siphoning_task.clone())),
         )
         .chain(
-            mining_squad
+            supply_chain_boosting_fleet
+                .iter()
+                .cloned()
+                .map(|ship_type| (ship_type, supply_chain_boosting_task.clone())),
+        )
+        .chain(
+            remaining_mining_squads
                 .iter()
                 .cloned()
                 .map(|ship_type| (ship_type, mining_task.clone())),
@@ -2219,6 +2562,19 @@ pub async fn collect_fleet_decision_facts(bmc: Arc<dyn Bmc>, system_symbol: &Sys
         engineered_asteroid,
     })
 }
+
+/// Replays the ledger of an agent into a fresh treasurer for processes without access to the running one (e.g. the ui).
+/// Unlike the startup of the running treasurer, entries it rejects don't abort the replay - they are logged and skipped.
+pub fn replay_ledger(ledger_entries: impl IntoIterator<Item = LedgerEntry>) -> ImprovedTreasurer {
+    let mut treasurer = ImprovedTreasurer::new();
+    for entry in ledger_entries {
+        if let Err(err) = treasurer.process_ledger_entry(entry.clone()) {
+            event!(Level::WARN, message = "Skipping rejected ledger entry", entry = ?entry, error = %err);
+        }
+    }
+    treasurer
+}
+
 pub fn diff_waypoint_symbols(waypoints_of_interest: &[WaypointSymbol], already_explored: &[WaypointSymbol]) -> Vec<WaypointSymbol> {
     let set2: HashSet<_> = already_explored.iter().collect();
 
@@ -2346,6 +2702,8 @@ pub fn get_all_next_ship_purchases(ship_map: &HashMap<ShipSymbol, Ship>, fleet_p
 enum ContractorEvaluationResult {
     CanAffordContract { evaluation_result: ContractEvaluationResult },
     CannotAffordContractCurrently,
+    ContractNotProfitable,
+    ContractDeclined,
     ContractAlreadyAccepted,
     ContractAlreadyFulfilled,
     NoActiveContractFound,
@@ -2354,16 +2712,18 @@ enum ContractorEvaluationResult {
 
 fn should_command_ship_do_contracts(
     unassigned_command_ship: &Ship,
-    maybe_current_contract: &Option<Contract>,
+    maybe_current_contract: &Option<ContractEntry>,
     fleet_budget: &FleetBudget,
     latest_market_entries: &[MarketEntry],
     waypoints_of_system: &[Waypoint],
+    contract_strategy: &ContractStrategy,
 ) -> Result<ContractorEvaluationResult> {
     use ContractorEvaluationResult::*;
 
     let ship_has_cargo = unassigned_command_ship.cargo.units > 0;
 
-    if let Some(contract) = maybe_current_contract {
+    if let Some(contract_entry) = maybe_current_contract {
+        let contract = &contract_entry.contract;
         let ship_has_non_contract_related_cargo = unassigned_command_ship
             .cargo
             .inventory
@@ -2378,6 +2738,13 @@ fn should_command_ship_do_contracts(
             Ok(ContractAlreadyAccepted)
         } else if contract.fulfilled {
             Ok(ContractAlreadyFulfilled)
+        } else if contract_entry.declined_at.is_some() {
+            // we can only negotiate a new contract once the declined offer has expired
+            if contract_entry.is_open_declined_offer(Utc::now()) {
+                Ok(ContractDeclined)
+            } else {
+                Ok(NoActiveContractFound)
+            }
         } else if ship_has_non_contract_related_cargo {
             Ok(ShipHasNonContractRelatedCargo)
         } else {
@@ -2389,9 +2756,20 @@ fn should_command_ship_do_contracts(
                 waypoints_of_system,
             )?;
 
+            let profitability = contract_manager::evaluate_contract_profitability(
+                &evaluation_result,
+                &unassigned_command_ship.nav.waypoint_symbol,
+                waypoints_of_system,
+                unassigned_command_ship.engine.speed as u32,
+                contract_strategy,
+                Utc::now(),
+            );
+
             let required_capital = evaluation_result.required_capital();
             let available_fleet_capital = fleet_budget.available_capital();
-            if required_capital > available_fleet_capital {
+            if !profitability.is_worth_accepting(contract_strategy.min_profit) {
+                Ok(ContractNotProfitable)
+            } else if required_capital > available_fleet_capital {
                 Ok(CannotAffordContractCurrently)
             } else {
                 Ok(CanAffordContract { evaluation_result })
@@ -2407,29 +2785,38 @@ fn should_command_ship_do_contracts(
 
 fn potentially_assign_contracting_to_command_ship(
     unassigned_ships_of_fleet: &[&Ship],
-    maybe_current_contract: &Option<Contract>,
+    maybe_current_contract: &Option<ContractEntry>,
     fleet_budget: &FleetBudget,
     latest_market_entries: &[MarketEntry],
     waypoints_of_system: &[Waypoint],
+    contract_strategy: &ContractStrategy,
 ) -> Result<Option<(ShipSymbol, Credits)>> {
     let result: Option<(ShipSymbol, Credits)> = if let Some(command_ship) = unassigned_ships_of_fleet
         .iter()
         .find(|ship| ship.is_command_ship())
     {
-        let command_ship_evaluation_result =
-            should_command_ship_do_contracts(command_ship, maybe_current_contract, fleet_budget, latest_market_entries, waypoints_of_system)?;
+        let command_ship_evaluation_result = should_command_ship_do_contracts(
+            command_ship,
+            maybe_current_contract,
+            fleet_budget,
+            latest_market_entries,
+            waypoints_of_system,
+            contract_strategy,
+        )?;
 
         use ContractorEvaluationResult::*;
         match command_ship_evaluation_result {
-            NoActiveContractFound | ContractAlreadyAccepted | ContractAlreadyFulfilled => {
+            NoActiveContractFound | ContractAlreadyAccepted | ContractAlreadyFulfilled | ContractNotProfitable => {
                 // contract is already ongoing or
                 // no active contract found - so we don't know if we can afford to execute it.
                 // Let's try
+                // the command ship declines unprofitable contracts - they are skipped afterwards
 
                 Some((command_ship.symbol.clone(), 0.into()))
             }
             CanAffordContract { evaluation_result } => Some((command_ship.symbol.clone(), evaluation_result.required_capital())),
             CannotAffordContractCurrently => None,
+            ContractDeclined => None,
             ShipHasNonContractRelatedCargo => None,
         }
     } else {
@@ -2438,3 +2825,229 @@ fn potentially_assign_contracting_to_command_ship(
 
     Ok(result)
 }
+
+/// Gives idle, empty haulers of the fleet the purchase and delivery tickets for contract units that no ship is delivering yet - one cargo load per hauler.
+#[allow(clippy::too_many_arguments)]
+async fn assign_contract_deliveries_to_helper_haulers(
+    admiral: &FleetAdmiral,
+    fleet_id: &FleetId,
+    contract: &Contract,
+    unassigned_ships_of_fleet: &[&Ship],
+    active_tickets: &[FinanceTicket],
+    fleet_budget: &FleetBudget,
+    latest_market_entries: &[MarketEntry],
+    waypoints_of_system: &[Waypoint],
+    contract_strategy: &ContractStrategy,
+) -> Result<Vec<ShipSymbol>> {
+    let num_active_helpers = active_tickets
+        .iter()
+        .filter(|t| matches!(&t.details, FinanceTicketDetails::DeliverContractCargo(d) if d.contract_id == contract.id))
+        .filter(|t| {
+            admiral
+                .all_ships
+                .get(&t.ship_symbol)
+                .is_some_and(|s| !s.is_command_ship())
+        })
+        .map(|t| t.ship_symbol.clone())
+        .unique()
+        .count();
+
+    let idle_haulers = unassigned_ships_of_fleet
+        .iter()
+        .filter(|s| s.is_hauler() && !s.is_command_ship() && s.cargo.units == 0)
+        .take((contract_strategy.max_helper_haulers as usize).saturating_sub(num_active_helpers))
+        .collect_vec();
+
+    let mut units_covered_by_other_ships = contract_manager::units_covered_by_active_tickets(&contract.id, active_tickets);
+    let mut available_capital = fleet_budget.available_capital();
+    let mut helper_haulers = vec![];
+
+    for hauler in idle_haulers {
+        let scope = ContractTicketScope {
+            units_covered_by_other_ships: units_covered_by_other_ships.clone(),
+            max_units_to_purchase: Some(hauler.cargo.capacity as u32),
+        };
+        let evaluation_result = contract_manager::calculate_necessary_tickets_for_contract_share(
+            &hauler.cargo,
+            &hauler.nav.waypoint_symbol,
+            contract,
+            latest_market_entries,
+            waypoints_of_system,
+            &scope,
+        )?;
+
+        if evaluation_result.delivery_tickets.is_empty() {
+            break;
+        }
+
+        let required_capital = evaluation_result.estimated_purchase_costs();
+        if required_capital > available_capital {
+            break;
+        }
+
+        let mut ticket_details = vec![];
+        for purchase_ticket in evaluation_result.purchase_tickets {
+            ticket_details.push(FinanceTicketDetails::PurchaseTradeGoods(purchase_ticket));
+        }
+        for delivery_ticket in evaluation_result.delivery_tickets {
+            *units_covered_by_other_ships
+                .entry(delivery_ticket.trade_good.clone())
+                .or_default() += delivery_ticket.quantity;
+            ticket_details.push(FinanceTicketDetails::DeliverContractCargo(delivery_ticket));
+        }
+
+        admiral
+            .treasurer
+            .create_multiple_tickets(&hauler.symbol, fleet_id, ticket_details)
+            .await?;
+        available_capital -= required_capital;
+        helper_haulers.push(hauler.symbol.clone());
+    }
+
+    Ok(helper_haulers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::behavior_tree::behavior_args::BehaviorArgs;
+    use crate::behavior_tree::ship_behaviors::ship_behaviors;
+    use crate::contract_manager::ContractManager;
+    use crate::fleet::ship_runner::ship_behavior_runner;
+    use crate::market_observation_scheduler::MarketObservationScheduler;
+    use crate::ship::ShipOperations;
+    use crate::st_client::MockStClientTrait;
+    use crate::test_objects::TestObjects;
+    use chrono::DateTime;
+    use st_domain::blackboard_ops::MockBlackboardOps;
+    use st_domain::budgeting::test_sync_ledger::create_test_ledger_setup;
+    use st_domain::{ContractId, ContractTerms, Delivery, NavStatus, Payment, ShipFrameSymbol, TradeGoodSymbol};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use test_log::test;
+
+    fn declined_contract_entry(deadline_to_accept: DateTime<Utc>) -> ContractEntry {
+        let now = Utc::now();
+        ContractEntry {
+            system_symbol: TestObjects::system_symbol(),
+            contract: Contract {
+                id: ContractId("c-1".to_string()),
+                faction_symbol: "COSMIC".to_string(),
+                contract_type: "PROCUREMENT".to_string(),
+                terms: ContractTerms {
+                    deadline: now + TimeDelta::days(7),
+                    payment: Payment {
+                        on_accepted: 1_000,
+                        on_fulfilled: 2_000,
+                    },
+                    deliver: vec![Delivery {
+                        trade_symbol: TradeGoodSymbol::IRON,
+                        destination_symbol: TestObjects::waypoint_symbol(),
+                        units_required: 100,
+                        units_fulfilled: 0,
+                    }],
+                },
+                accepted: false,
+                fulfilled: false,
+                deadline_to_accept,
+            },
+            created_at: now - TimeDelta::hours(1),
+            updated_at: now,
+            declined_at: Some(now),
+        }
+    }
+
+    /// runs the contractor behavior of a ship without contract and reports whether it tried to negotiate a new one
+    async fn contractor_behavior_negotiates(youngest_contract_entry: ContractEntry) -> Result<bool> {
+        let (_test_archiver, task_sender) = create_test_ledger_setup().await;
+        let treasurer = ThreadSafeTreasurer::new(100_000.into(), task_sender).await;
+
+        let mut ship = TestObjects::test_ship(400);
+        ship.nav.status = NavStatus::InOrbit;
+
+        let negotiation_attempts = Arc::new(AtomicUsize::new(0));
+        let mut mock_client = MockStClientTrait::new();
+        let attempts = negotiation_attempts.clone();
+        mock_client.expect_negotiate_contract().returning(move |_| {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(anyhow!("400 - ship already has an open contract offer"))
+        });
+
+        let mut blackboard = MockBlackboardOps::new();
+        blackboard
+            .expect_get_youngest_contract_entry()
+            .returning(move |_| Ok(Some(youngest_contract_entry.clone())));
+
+        let args = BehaviorArgs {
+            blackboard: Arc::new(blackboard),
+            treasurer,
+            transfer_cargo_manager: Arc::new(TransferCargoManager::default()),
+            materialized_supply_chain_manager: MaterializedSupplyChainManager::new(),
+            mining_site_manager: MiningSiteManager::default(),
+            contract_manager: ContractManager::default(),
+            market_observation_scheduler: MarketObservationScheduler::default(),
+            ship_behavior_store: ShipBehaviorStore::default(),
+        };
+
+        let (ship_updated_tx, _ship_updated_rx) = tokio::sync::mpsc::channel(32);
+        let (ship_action_completed_tx, _ship_action_completed_rx) = tokio::sync::mpsc::channel(32);
+        let mut contractor_behavior = ship_behaviors().contractor_behavior;
+        contractor_behavior.update_indices();
+
+        let mut ship_ops = ShipOperations::new(ship, Arc::new(mock_client), FleetId(42));
+        // without a contract to work on the tree fails either way
+        let result = ship_behavior_runner(
+            &mut ship_ops,
+            Duration::from_millis(1),
+            &args,
+            contractor_behavior,
+            ship_updated_tx,
+            ship_action_completed_tx,
+        )
+        .await;
+        assert!(result.is_err());
+
+        Ok(negotiation_attempts.load(Ordering::SeqCst) > 0)
+    }
+
+    #[test]
+    fn replaying_the_ledger_skips_rejected_entries() {
+        let fleet_created = |total_capital: i64| LedgerEntry::FleetCreated {
+            fleet_id: FleetId(1),
+            total_capital: total_capital.into(),
+        };
+
+        let treasurer = replay_ledger(vec![
+            LedgerEntry::TreasuryCreated { credits: 175_000.into() },
+            fleet_created(50_000),
+            // the fleet exists already
+            fleet_created(75_000),
+        ]);
+
+        assert_eq!(treasurer.current_agent_credits(), 175_000.into());
+        let fleet_budgets = treasurer.get_fleet_budgets().unwrap();
+        assert_eq!(fleet_budgets.len(), 1);
+        assert_eq!(fleet_budgets[&FleetId(1)].budget, 50_000.into());
+    }
+
+    #[test(tokio::test)]
+    async fn command_ship_skips_declined_contracts_until_their_offer_expired() -> Result<()> {
+        let mut command_ship = TestObjects::test_ship(400);
+        command_ship.frame.symbol = ShipFrameSymbol::FRAME_FRIGATE;
+        let ships = vec![&command_ship];
+        let assign = |entry: ContractEntry| {
+            potentially_assign_contracting_to_command_ship(&ships, &Some(entry), &FleetBudget::default(), &[], &[], &ContractStrategy::default())
+        };
+
+        let open_offer = declined_contract_entry(Utc::now() + TimeDelta::hours(1));
+        assert_eq!(assign(open_offer.clone())?, None);
+        // a ship that already runs the contractor behavior doesn't negotiate either
+        assert!(!contractor_behavior_negotiates(open_offer).await?);
+
+        // a new contract can be negotiated
+        let expired_offer = declined_contract_entry(Utc::now() - TimeDelta::hours(1));
+        assert_eq!(assign(expired_offer.clone())?, Some((command_ship.symbol.clone(), 0.into())));
+        assert!(contractor_behavior_negotiates(expired_offer).await?);
+
+        Ok(())
+    }
+}
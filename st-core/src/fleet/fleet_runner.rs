@@ -1,11 +1,17 @@
 use crate::behavior_tree::behavior_args::BehaviorArgs;
+use crate::behavior_tree::behavior_definitions::ShipBehaviorStore;
 use crate::behavior_tree::behavior_tree::ActionEvent;
 use crate::behavior_tree::ship_behaviors::ShipAction;
+use crate::contract_manager::ContractManager;
+use crate::fleet::capital_allocator::CapitalAllocator;
 use crate::fleet::fleet::{
     collect_fleet_decision_facts, compute_fleet_phase_with_tasks, compute_fleets_with_tasks, get_all_next_ship_purchases,
-    recompute_tasks_after_ship_finishing_behavior_tree, FleetAdmiral, NewTaskResult, ShipStatusReport,
+    recompute_tasks_after_ship_finishing_behavior_tree, FleetAdmiral, NewTaskResult, ShipStatusReport, TreasurerStartup,
 };
+use crate::fleet::ledger_auditor::LedgerAuditor;
 use crate::fleet::ship_runner::ship_behavior_runner;
+use crate::market_observation_scheduler::MarketObservationScheduler;
+use crate::mining_site_manager::mining_role_and_site;
 use crate::ship::ShipOperations;
 use crate::st_client::StClientTrait;
 use anyhow::{anyhow, Result};
@@ -15,6 +21,9 @@ use crate::bmc_blackboard::BmcBlackboard;
 use crate::transfer_cargo_manager::TransferCargoManager;
 use itertools::Itertools;
 use st_domain::blackboard_ops::BlackboardOps;
+use st_domain::budgeting::capital_allocation::RoiCapitalAllocationPolicy;
+use st_domain::budgeting::credits::Credits;
+use st_domain::budgeting::ledger_audit::LedgerAuditConfig;
 use st_domain::budgeting::treasury_redesign::ThreadSafeTreasurer;
 use st_domain::{
     get_exploration_tasks_for_waypoint, FleetId, OperationExpenseEvent, Ship, ShipFrameSymbol, ShipSymbol, ShipTask, StationaryProbeLocation,
@@ -53,11 +62,17 @@ impl FleetRunner {
         client: Arc<dyn StClientTrait>,
         bmc: Arc<dyn Bmc>,
         transfer_cargo_manager: Arc<TransferCargoManager>,
+        ship_behavior_store: ShipBehaviorStore,
         sleep_duration: Duration,
-        treasurer_archiver_join_handle: JoinHandle<()>,
+        treasurer_startup: TreasurerStartup,
     ) -> Result<()> {
         event!(Level::INFO, "Running fleets");
 
+        let TreasurerStartup {
+            archiver_join_handle,
+            pending_credit_drift,
+        } = treasurer_startup;
+
         let (ship_updated_tx, ship_updated_rx): (Sender<ShipOperations>, Receiver<ShipOperations>) = tokio::sync::mpsc::channel(32);
         let (ship_action_completed_tx, ship_action_completed_rx): (Sender<ActionEvent>, Receiver<ActionEvent>) = tokio::sync::mpsc::channel(32);
         let (ship_status_report_tx, ship_status_report_rx): (Sender<ShipStatusReport>, Receiver<ShipStatusReport>) = tokio::sync::mpsc::channel(32);
@@ -72,12 +87,19 @@ impl FleetRunner {
             .await
             .materialized_supply_chain_manager
             .clone();
+        let mining_site_manager = fleet_admiral.lock().await.mining_site_manager.clone();
+        let contract_manager = ContractManager::new(fleet_admiral.lock().await.strategy_store.clone());
+        let market_observation_scheduler = MarketObservationScheduler::new(fleet_admiral.lock().await.strategy_store.clone());
 
         let args: BehaviorArgs = BehaviorArgs {
             blackboard: Arc::clone(&blackboard),
             treasurer: thread_safe_treasurer.clone(),
             transfer_cargo_manager: Arc::clone(&transfer_cargo_manager),
             materialized_supply_chain_manager,
+            mining_site_manager,
+            contract_manager,
+            market_observation_scheduler,
+            ship_behavior_store,
         };
 
         let ship_fibers: HashMap<ShipSymbol, JoinHandle<Result<()>>> = HashMap::new();
@@ -148,6 +170,7 @@ impl FleetRunner {
             ship_action_completed_rx,
             ship_status_report_rx,
             sleep_duration,
+            pending_credit_drift,
         ));
 
         for (ss, ship) in all_ships_map {
@@ -162,7 +185,7 @@ impl FleetRunner {
             .await?;
         }
 
-        let _ = tokio::join!(msg_listeners_join_handle, treasurer_archiver_join_handle);
+        let _ = tokio::join!(msg_listeners_join_handle, archiver_join_handle);
 
         Ok(())
     }
@@ -378,15 +401,19 @@ impl FleetRunner {
         fleet_id: FleetId,
     ) -> Result<Option<(Ship, ShipTask)>> {
         use crate::behavior_tree::behavior_tree::Response;
-        use crate::behavior_tree::ship_behaviors::ship_behaviors;
         use anyhow::Error;
 
         use tracing::{span, Level};
-        let behaviors = ship_behaviors();
+        // picks up changed behavior definitions at the start of each tree
+        let behaviors = args.ship_behavior_store.get_behaviors();
 
         let mut ship = ship_op.lock().await;
 
         ship.my_fleet = fleet_id;
+        if let Some((role, mining_waypoint)) = mining_role_and_site(&ship_task) {
+            args.mining_site_manager
+                .register_assignment(ship.symbol.clone(), role, mining_waypoint)?;
+        }
         let ship_updated_tx_clone = ship_updated_tx.clone();
         let ship_action_completed_tx_clone = ship_action_completed_tx.clone();
 
@@ -428,6 +455,10 @@ impl FleetRunner {
                 ship.set_siphoning_waypoint(siphoning_waypoint);
                 Some((behaviors.siphoning_behavior, "siphoning_behavior"))
             }
+            ShipTask::HaulSiphonedGoods { siphoning_waypoint } => {
+                ship.set_siphoning_waypoint(siphoning_waypoint);
+                Some((behaviors.siphoning_hauler_behavior, "siphoning_hauler_behavior"))
+            }
             ShipTask::SurveyMiningSite { mining_waypoint } => {
                 ship.set_mining_waypoint(mining_waypoint.clone());
                 Some((behaviors.surveyor_behavior, "surveyor_behavior"))
@@ -643,7 +674,12 @@ impl FleetRunner {
                         let facts = collect_fleet_decision_facts(Arc::clone(&bmc), &system_symbol).await?;
                         admiral_guard.update_materialized_supply_chain(&facts.materialized_supply_chain)?;
 
-                        let fleet_phase = compute_fleet_phase_with_tasks(system_symbol.clone(), &facts, &admiral_guard.completed_fleet_tasks);
+                        let fleet_phase = compute_fleet_phase_with_tasks(
+                            system_symbol.clone(),
+                            &facts,
+                            &admiral_guard.completed_fleet_tasks,
+                            &admiral_guard.strategy().shopping_lists,
+                        );
                         let (fleets, fleet_tasks) = compute_fleets_with_tasks(&facts, &admiral_guard.fleets, &admiral_guard.fleet_tasks, &fleet_phase);
                         // println!("Computed new fleets after dismantling the fleets: {:?}", fleets_to_dismantle);
                         // dbg!(&fleets);
@@ -662,7 +698,8 @@ impl FleetRunner {
                         let ship_price_info = bmc
                             .shipyard_bmc()
                             .get_latest_ship_prices(&Ctx::Anonymous, &system_symbol)
-                            .await?;
+                            .await?
+                            .with_price_guesses(admiral_guard.strategy().ship_price_guesses);
 
                         //FIXME: assuming one fleet task per fleet
                         let fleet_task_list = admiral_guard
@@ -983,11 +1020,18 @@ impl FleetRunner {
         ship_action_completed_rx: Receiver<ActionEvent>,
         ship_status_report_rx: Receiver<ShipStatusReport>,
         sleep_duration: Duration,
+        pending_credit_drift: Option<Credits>,
     ) {
         // Extract all needed data with a single lock acquisition
-        let (bmc, fleet_admiral, ship_status_report_tx) = {
+        let (bmc, fleet_admiral, ship_status_report_tx, client, treasurer) = {
             let guard = runner.lock().await;
-            (Arc::clone(&guard.bmc), Arc::clone(&guard.fleet_admiral), guard.ship_status_report_tx.clone())
+            (
+                Arc::clone(&guard.bmc),
+                Arc::clone(&guard.fleet_admiral),
+                guard.ship_status_report_tx.clone(),
+                Arc::clone(&guard.client),
+                guard.treasurer.clone(),
+            )
         };
 
         // Create a cancellation token for coordinated shutdown
@@ -1093,12 +1137,26 @@ impl FleetRunner {
             res
         });
 
+        let capital_allocator = CapitalAllocator::new(
+            Arc::clone(&bmc),
+            treasurer.clone(),
+            Arc::new(RoiCapitalAllocationPolicy::default()),
+            chrono::Duration::hours(1),
+        );
+        let capital_allocation_join_handle =
+            tokio::spawn(capital_allocator.run_periodically(Arc::clone(&fleet_admiral), Duration::from_secs(5 * 60), Duration::from_secs(10)));
+
+        let ledger_auditor = LedgerAuditor::new(Arc::clone(&bmc), client, treasurer, LedgerAuditConfig::default(), pending_credit_drift);
+        let ledger_audit_join_handle = tokio::spawn(ledger_auditor.run_periodically(Arc::clone(&fleet_admiral), Duration::from_secs(10 * 60)));
+
         // Wait for all tasks and handle errors
-        let (updated_result, action_result, status_result, restart_idle_ships_result) = tokio::join!(
+        let (updated_result, action_result, status_result, restart_idle_ships_result, ledger_audit_result, capital_allocation_result) = tokio::join!(
             ship_updated_listener_join_handle,
             ship_action_update_listener_join_handle,
             ship_status_report_listener_join_handle,
-            restart_idle_ships_join_handle
+            restart_idle_ships_join_handle,
+            ledger_audit_join_handle,
+            capital_allocation_join_handle
         );
 
         // Log any join errors
@@ -1114,6 +1172,12 @@ impl FleetRunner {
         if let Err(e) = restart_idle_ships_result {
             event!(Level::ERROR, "restart_idle_ships_result join error: {}", e);
         }
+        if let Err(e) = ledger_audit_result {
+            event!(Level::ERROR, "ledger_audit_result join error: {}", e);
+        }
+        if let Err(e) = capital_allocation_result {
+            event!(Level::ERROR, "capital_allocation_result join error: {}", e);
+        }
 
         event!(Level::WARN, "All listeners have exited, fleet runner will no longer process messages");
     }
@@ -1121,12 +1185,14 @@ impl FleetRunner {
 
 #[cfg(test)]
 mod tests {
+    use crate::behavior_tree::behavior_definitions::ShipBehaviorStore;
     use crate::bmc_blackboard::BmcBlackboard;
     use crate::fleet::fleet::FleetAdmiral;
     use crate::fleet::fleet_runner::FleetRunner;
     use crate::fleet::initial_data_collector::load_and_store_initial_data_in_bmcs;
     use crate::format_and_sort_collection;
     use crate::st_client::StClientTrait;
+    use crate::strategy::StrategyStore;
     use crate::transfer_cargo_manager::TransferCargoManager;
     use crate::universe_server::universe_server::{InMemoryUniverse, InMemoryUniverseClient};
     use chrono::Utc;
@@ -1223,12 +1289,14 @@ mod tests {
             in_mem_status_bmc: Arc::new(status_bmc),
             in_mem_ledger_bmc: Arc::new(ledger_bmc),
             in_mem_contract_bmc: Arc::new(contract_bmc),
+            in_mem_cargo_flow_bmc: Arc::new(Default::default()),
         };
 
         let client = Arc::new(in_memory_client) as Arc<dyn StClientTrait>;
         let bmc = Arc::new(bmc) as Arc<dyn Bmc>;
         let blackboard = BmcBlackboard::new(Arc::clone(&bmc));
-        let transfer_cargo_manager = Arc::new(TransferCargoManager::new());
+        let strategy_store = StrategyStore::default();
+        let transfer_cargo_manager = Arc::new(TransferCargoManager::new(strategy_store.clone()));
 
         load_and_store_initial_data_in_bmcs(Arc::clone(&client), Arc::clone(&bmc))
             .await
@@ -1236,7 +1304,7 @@ mod tests {
 
         println!("Creating fleet admiral");
 
-        let (fleet_admiral, treasurer_archiver_join_handle) = FleetAdmiral::load_or_create(Arc::clone(&bmc), hq_system_symbol, Arc::clone(&client))
+        let (fleet_admiral, treasurer_startup) = FleetAdmiral::load_or_create(Arc::clone(&bmc), hq_system_symbol, Arc::clone(&client), strategy_store)
             .await
             .expect("FleetAdmiral::load_or_create");
 
@@ -1273,8 +1341,9 @@ mod tests {
                 Arc::clone(&client),
                 Arc::clone(&bmc),
                 Arc::clone(&transfer_cargo_manager),
+                ShipBehaviorStore::default(),
                 Duration::from_millis(1),
-                treasurer_archiver_join_handle,
+                treasurer_startup,
             )
             .await
             .unwrap();
@@ -1486,7 +1555,7 @@ evaluation_result: {evaluation_result}
 
         assert_eq!(1, completed_tasks.len());
         assert_eq!(FleetPhaseName::ConstructJumpGate, admiral_mutex.lock().await.fleet_phase.name);
-        assert_eq!(4, fleets.len());
+        assert_eq!(5, fleets.len());
 
         let siphoning_fleet = fleets
             .iter()
@@ -1512,6 +1581,11 @@ evaluation_result: {evaluation_result}
             })
             .expect("One MarketObservation Fleet");
 
+        fleets
+            .iter()
+            .find(|f| matches!(f.cfg, FleetConfig::SupplyChainBoostingCfg(_)))
+            .expect("One SupplyChainBoosting Fleet");
+
         let construct_jump_gate_fleet = fleets
             .iter()
             .find_map(|f| match &f.cfg {
@@ -0,0 +1,202 @@
+use crate::fleet::fleet::FleetAdmiral;
+use crate::st_client::StClientTrait;
+use anyhow::Result;
+use chrono::Utc;
+use itertools::Itertools;
+use st_domain::budgeting::credits::Credits;
+use st_domain::budgeting::ledger_audit::{audit_ledger, CreditDriftResolution, LedgerAuditConfig, LedgerAuditInput, LedgerAuditReport};
+use st_domain::budgeting::treasury_redesign::ThreadSafeTreasurer;
+use st_domain::{ShipSymbol, SystemSymbol, Transaction};
+use st_store::bmc::Bmc;
+use st_store::Ctx;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::{event, Level};
+
+/// Regularly reconciles the treasurer with the agent credits reported by the api and the market transactions of our ships.
+/// Small drifts are corrected with explicit `TreasuryAdjusted` ledger entries, everything else only ends up in the report.
+pub struct LedgerAuditor {
+    bmc: Arc<dyn Bmc>,
+    client: Arc<dyn StClientTrait>,
+    treasurer: ThreadSafeTreasurer,
+    config: LedgerAuditConfig,
+    previous_credit_drift: Option<Credits>,
+}
+
+impl LedgerAuditor {
+    /// `pending_credit_drift` is the drift found at startup - the first audit confirms (and corrects) it, if it's still the same.
+    pub fn new(
+        bmc: Arc<dyn Bmc>,
+        client: Arc<dyn StClientTrait>,
+        treasurer: ThreadSafeTreasurer,
+        config: LedgerAuditConfig,
+        pending_credit_drift: Option<Credits>,
+    ) -> Self {
+        Self {
+            bmc,
+            client,
+            treasurer,
+            config,
+            previous_credit_drift: pending_credit_drift,
+        }
+    }
+
+    pub async fn run_periodically(mut self, fleet_admiral: Arc<Mutex<FleetAdmiral>>, interval: Duration) -> Result<()> {
+        loop {
+            tokio::time::sleep(interval).await;
+
+            let (known_ships, systems) = {
+                let admiral = fleet_admiral.lock().await;
+                let known_ships = admiral.all_ships.keys().cloned().collect::<HashSet<_>>();
+                let systems = admiral
+                    .all_ships
+                    .values()
+                    .map(|s| s.nav.system_symbol.clone())
+                    .unique()
+                    .collect_vec();
+                (known_ships, systems)
+            };
+
+            // an audit failing is no reason to stop the fleets
+            if let Err(err) = self.run_audit(&known_ships, &systems).await {
+                event!(Level::ERROR, message = "Ledger audit failed", error = %err);
+            }
+        }
+    }
+
+    pub async fn run_audit(&mut self, known_ships: &HashSet<ShipSymbol>, systems: &[SystemSymbol]) -> Result<LedgerAuditReport> {
+        let agent = self.client.get_agent().await?.data;
+        self.bmc
+            .agent_bmc()
+            .store_agent(&Ctx::Anonymous, &agent)
+            .await?;
+
+        let ledger_entries = self
+            .bmc
+            .ledger_bmc()
+            .get_timestamped_ledger_entries_in_order(&Ctx::Anonymous)
+            .await?;
+        let treasurer = self.treasurer.get_instance().await?;
+
+        let mut market_transactions: Vec<Transaction> = Vec::new();
+        for system_symbol in systems {
+            let market_entries = self
+                .bmc
+                .market_bmc()
+                .get_latest_market_data_for_system(&Ctx::Anonymous, system_symbol)
+                .await?;
+            market_transactions.extend(
+                market_entries
+                    .into_iter()
+                    .flat_map(|me| me.market_data.transactions.unwrap_or_default()),
+            );
+        }
+
+        let report = audit_ledger(
+            LedgerAuditInput {
+                ledger_entries: &ledger_entries,
+                treasurer: &treasurer,
+                agent_credits: agent.credits.into(),
+                previous_credit_drift: self.previous_credit_drift,
+                market_transactions: &market_transactions,
+                known_ships,
+                now: Utc::now(),
+            },
+            &self.config,
+        );
+
+        self.previous_credit_drift = match &report.drift_resolution {
+            CreditDriftResolution::Corrected { credits } => {
+                self.treasurer
+                    .adjust_treasury_fund(
+                        *credits,
+                        format!(
+                            "Ledger audit: agent has {} but treasurer has {}",
+                            report.agent_credits, report.treasurer_credits
+                        ),
+                    )
+                    .await?;
+                None
+            }
+            CreditDriftResolution::NoDrift => None,
+            CreditDriftResolution::AwaitingConfirmation | CreditDriftResolution::ExceedsThreshold { .. } => Some(report.credit_drift),
+        };
+
+        self.bmc
+            .ledger_bmc()
+            .save_ledger_audit_report(&Ctx::Anonymous, &report)
+            .await?;
+
+        if report.has_discrepancies() {
+            event!(
+                Level::WARN,
+                message = "Ledger audit found discrepancies",
+                credit_drift = report.credit_drift.0,
+                drift_resolution = ?report.drift_resolution,
+                num_audited_ticket_completions = report.num_audited_ticket_completions,
+                num_unmatched_ticket_completions = report.unmatched_ticket_completions.len(),
+                num_unmatched_transactions = report.unmatched_transactions.len(),
+                num_duplicate_ticket_creations = report.duplicate_ticket_creations.len(),
+                num_duplicate_ticket_completions = report.duplicate_ticket_completions.len(),
+                num_orphaned_tickets = report.orphaned_tickets.len(),
+            );
+        } else {
+            event!(
+                Level::INFO,
+                message = "Ledger audit found no discrepancies",
+                num_audited_ticket_completions = report.num_audited_ticket_completions,
+            );
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::in_memory_universe::in_memory_test_universe::get_test_universe;
+    use st_domain::budgeting::treasury_redesign::LedgerEntry;
+
+    #[tokio::test]
+    async fn corrects_a_credit_drift_found_at_startup_only_after_the_audit_confirmed_it() -> Result<()> {
+        let (bmc, client) = get_test_universe().await;
+        let agent_credits = Credits::from(client.get_agent().await?.data.credits);
+        let drift = Credits::new(1_000);
+        bmc.ledger_bmc()
+            .archive_ledger_entry(
+                &Ctx::Anonymous,
+                &LedgerEntry::TreasuryCreated {
+                    credits: agent_credits - drift,
+                },
+            )
+            .await?;
+
+        // startup only notices the drift
+        let (treasurer, treasurer_startup) = FleetAdmiral::initialize_treasurer(Arc::clone(&bmc)).await?;
+        assert_eq!(treasurer_startup.pending_credit_drift, Some(drift));
+        assert_eq!(treasurer.get_current_agent_credits().await?, agent_credits - drift);
+
+        // without the drift of the startup, the first audit would only wait for the confirmation
+        let mut unconfirmed_auditor = LedgerAuditor::new(Arc::clone(&bmc), Arc::clone(&client), treasurer.clone(), LedgerAuditConfig::default(), None);
+        let report = unconfirmed_auditor.run_audit(&HashSet::new(), &[]).await?;
+        assert_eq!(report.drift_resolution, CreditDriftResolution::AwaitingConfirmation);
+        assert_eq!(treasurer.get_current_agent_credits().await?, agent_credits - drift);
+
+        // the first audit confirms the drift of the startup and corrects it
+        let mut auditor = LedgerAuditor::new(
+            Arc::clone(&bmc),
+            client,
+            treasurer.clone(),
+            LedgerAuditConfig::default(),
+            treasurer_startup.pending_credit_drift,
+        );
+        let report = auditor.run_audit(&HashSet::new(), &[]).await?;
+        assert_eq!(report.drift_resolution, CreditDriftResolution::Corrected { credits: drift });
+        assert_eq!(treasurer.get_current_agent_credits().await?, agent_credits);
+
+        Ok(())
+    }
+}
@@ -1,7 +1,5 @@
-use st_domain::{
-    MiningFleetConfig, Ship, ShipSymbol,
-    ShipTask,
-};
+use crate::mining_site_manager::{mining_role_and_site, pick_mining_site};
+use st_domain::{MiningFleetConfig, Ship, ShipRole, ShipSymbol, ShipTask, WaypointSymbol};
 use std::collections::HashMap;
 use tracing::event;
 use tracing_core::Level;
@@ -9,41 +7,87 @@ use tracing_core::Level;
 pub struct MiningFleet;
 
 impl MiningFleet {
-    pub fn compute_ship_tasks(cfg: &MiningFleetConfig, ships: &[&Ship]) -> anyhow::Result<HashMap<ShipSymbol, ShipTask>> {
+    /// Spreads the new ships across the ranked mining sites, taking the sites of the already assigned ships into account.
+    /// Without ranked sites (e.g. before the supply chain has been materialized) everybody goes to the configured mining waypoint.
+    pub fn compute_ship_tasks(
+        cfg: &MiningFleetConfig,
+        ships: &[&Ship],
+        ranked_sites: &[WaypointSymbol],
+        existing_ship_tasks: &HashMap<ShipSymbol, ShipTask>,
+        max_drones_per_site: usize,
+    ) -> anyhow::Result<HashMap<ShipSymbol, ShipTask>> {
         if ships.is_empty() {
             return Ok(HashMap::new());
         }
 
-        let new_tasks: HashMap<ShipSymbol, ShipTask> = ships
+        let sites = if ranked_sites.is_empty() {
+            vec![cfg.mining_waypoint.clone()]
+        } else {
+            ranked_sites.to_vec()
+        };
+
+        let mut assignments: HashMap<ShipSymbol, (ShipRole, WaypointSymbol)> = existing_ship_tasks
+            .iter()
+            .filter_map(|(ss, task)| mining_role_and_site(task).map(|role_and_site| (ss.clone(), role_and_site)))
+            .collect();
+
+        // drones first, so that surveyors and haulers can follow them
+        let mut ships_by_role = ships
             .iter()
-            .filter_map(|s| match Self::calc_ship_task(s, cfg) {
-                Ok(task) => Some((s.symbol.clone(), task)),
+            .filter_map(|s| match Self::calc_ship_role(s) {
+                Ok(role) => Some((s, role)),
                 Err(e) => {
                     event!(Level::ERROR, "Failed to compute ship task: {:?}", e);
                     None
                 }
             })
-            .collect();
+            .collect::<Vec<_>>();
+        ships_by_role.sort_by_key(|(s, role)| (Self::role_order(role), s.symbol.clone()));
+
+        let mut new_tasks: HashMap<ShipSymbol, ShipTask> = HashMap::new();
+
+        for (s, role) in ships_by_role {
+            assignments.remove(&s.symbol);
+            let mining_waypoint = pick_mining_site(&role, &sites, &assignments, max_drones_per_site).unwrap_or_else(|| sites[0].clone());
+
+            let task = match role {
+                ShipRole::Miner => ShipTask::MineMaterialsAtWaypoint {
+                    mining_waypoint: mining_waypoint.clone(),
+                },
+                ShipRole::MiningSurveyor => ShipTask::SurveyMiningSite {
+                    mining_waypoint: mining_waypoint.clone(),
+                },
+                _ => ShipTask::HaulMiningGoods {
+                    mining_waypoint: mining_waypoint.clone(),
+                },
+            };
+
+            assignments.insert(s.symbol.clone(), (role, mining_waypoint));
+            new_tasks.insert(s.symbol.clone(), task);
+        }
 
         Ok(new_tasks)
     }
-    fn calc_ship_task(s: &Ship, cfg: &MiningFleetConfig) -> anyhow::Result<ShipTask> {
-        let task = if s.is_mining_drone() {
-            ShipTask::MineMaterialsAtWaypoint {
-                mining_waypoint: cfg.mining_waypoint.clone(),
-            }
+
+    fn calc_ship_role(s: &Ship) -> anyhow::Result<ShipRole> {
+        let role = if s.is_mining_drone() {
+            ShipRole::Miner
         } else if s.is_surveyor() {
-            ShipTask::SurveyMiningSite {
-                mining_waypoint: cfg.mining_waypoint.clone(),
-            }
+            ShipRole::MiningSurveyor
         } else if s.is_hauler() {
-            ShipTask::HaulMiningGoods {
-                mining_waypoint: cfg.mining_waypoint.clone(),
-            }
+            ShipRole::MiningHauler
         } else {
             anyhow::bail!("This should not happen. The type of the ship doesn't match our expectation in the mining-fleet. We're quite picky here... {s:?}")
         };
 
-        Ok(task)
+        Ok(role)
+    }
+
+    fn role_order(role: &ShipRole) -> u8 {
+        match role {
+            ShipRole::Miner => 0,
+            ShipRole::MiningSurveyor => 1,
+            _ => 2,
+        }
     }
 }
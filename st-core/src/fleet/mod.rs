@@ -1,10 +1,13 @@
+pub mod capital_allocator;
 mod construction_fleet;
 pub mod fleet;
 pub mod fleet_runner;
 mod initial_data_collector;
+pub mod ledger_auditor;
 mod market_observation_fleet;
 mod mining_fleet;
 pub mod ship_runner;
 mod siphoning_fleet;
+mod supply_chain_boosting_fleet;
 mod supply_chain_test;
 mod system_spawning_fleet;
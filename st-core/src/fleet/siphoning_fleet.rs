@@ -1,9 +1,12 @@
-use st_domain::{Ship, ShipSymbol, ShipTask, SiphoningFleetConfig};
+use st_domain::{Ship, ShipRole, ShipSymbol, ShipTask, SiphoningFleetConfig};
 use std::collections::HashMap;
+use tracing::event;
+use tracing_core::Level;
 
 pub struct SiphoningFleet;
 
 impl SiphoningFleet {
+    /// Siphon drones extract gas at the siphoning waypoint, haulers collect their cargo there and deliver it to the siphoning delivery routes.
     pub fn compute_ship_tasks(cfg: &SiphoningFleetConfig, ships: &[&Ship]) -> anyhow::Result<HashMap<ShipSymbol, ShipTask>> {
         if ships.is_empty() {
             return Ok(HashMap::new());
@@ -11,16 +14,95 @@ impl SiphoningFleet {
 
         let new_tasks: HashMap<ShipSymbol, ShipTask> = ships
             .iter()
-            .map(|s| {
-                (
-                    s.symbol.clone(),
-                    ShipTask::SiphonCarboHydratesAtWaypoint {
-                        siphoning_waypoint: cfg.siphoning_waypoint.clone(),
-                    },
-                )
+            .filter_map(|s| match Self::calc_ship_role(s) {
+                Ok(role) => Some((s, role)),
+                Err(e) => {
+                    event!(Level::ERROR, "Failed to compute ship task: {:?}", e);
+                    None
+                }
+            })
+            .map(|(s, role)| {
+                let siphoning_waypoint = cfg.siphoning_waypoint.clone();
+                let task = match role {
+                    ShipRole::SiphoningHauler => ShipTask::HaulSiphonedGoods { siphoning_waypoint },
+                    _ => ShipTask::SiphonCarboHydratesAtWaypoint { siphoning_waypoint },
+                };
+                (s.symbol.clone(), task)
             })
             .collect();
 
         Ok(new_tasks)
     }
+
+    fn calc_ship_role(s: &Ship) -> anyhow::Result<ShipRole> {
+        let role = if s.is_siphoner() {
+            ShipRole::Siphoner
+        } else if s.is_hauler() {
+            ShipRole::SiphoningHauler
+        } else {
+            anyhow::bail!("The type of the ship doesn't match our expectation in the siphoning-fleet. {s:?}")
+        };
+
+        Ok(role)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_objects::TestObjects;
+    use st_domain::{Mount, Requirements, ShipFrameSymbol, ShipMountSymbol, SystemSymbol, WaypointSymbol};
+
+    fn ship(symbol: &str, frame: ShipFrameSymbol, mounts: Vec<ShipMountSymbol>) -> Ship {
+        let mut ship = TestObjects::test_ship(100);
+        ship.symbol = ShipSymbol(symbol.to_string());
+        ship.frame.symbol = frame;
+        ship.mounts = mounts
+            .into_iter()
+            .map(|symbol| Mount {
+                symbol,
+                name: "".to_string(),
+                description: None,
+                strength: Some(10),
+                deposits: None,
+                requirements: Requirements {
+                    power: None,
+                    crew: None,
+                    slots: None,
+                },
+            })
+            .collect();
+        ship
+    }
+
+    #[test]
+    fn haulers_of_the_siphoning_fleet_collect_the_gas_of_the_drones() {
+        let siphoning_waypoint = WaypointSymbol("X1-FOO-B2".to_string());
+        let cfg = SiphoningFleetConfig {
+            system_symbol: SystemSymbol("X1-FOO".to_string()),
+            siphoning_waypoint: siphoning_waypoint.clone(),
+        };
+        let drone_1 = ship("FLWI-1", ShipFrameSymbol::FRAME_DRONE, vec![ShipMountSymbol::MOUNT_GAS_SIPHON_I]);
+        let hauler = ship("FLWI-2", ShipFrameSymbol::FRAME_LIGHT_FREIGHTER, vec![]);
+        let drone_2 = ship("FLWI-3", ShipFrameSymbol::FRAME_DRONE, vec![ShipMountSymbol::MOUNT_GAS_SIPHON_I]);
+        let probe = ship("FLWI-4", ShipFrameSymbol::FRAME_PROBE, vec![]);
+
+        assert_eq!(SiphoningFleet::calc_ship_role(&drone_1).unwrap(), ShipRole::Siphoner);
+        assert_eq!(SiphoningFleet::calc_ship_role(&hauler).unwrap(), ShipRole::SiphoningHauler);
+        assert!(SiphoningFleet::calc_ship_role(&probe).is_err());
+
+        let tasks = SiphoningFleet::compute_ship_tasks(&cfg, &[&drone_1, &hauler, &drone_2, &probe]).unwrap();
+
+        let siphon = ShipTask::SiphonCarboHydratesAtWaypoint {
+            siphoning_waypoint: siphoning_waypoint.clone(),
+        };
+        assert_eq!(
+            tasks,
+            HashMap::from([
+                (drone_1.symbol.clone(), siphon.clone()),
+                (hauler.symbol.clone(), ShipTask::HaulSiphonedGoods { siphoning_waypoint }),
+                (drone_2.symbol.clone(), siphon),
+            ])
+        );
+    }
 }
@@ -0,0 +1,169 @@
+use crate::fleet::construction_fleet::{
+    create_boost_supply_chain_actions, create_tasks_for_ships_with_cargo, find_best_combination, NewTasksResultForConstructionFleet, PotentialConstructionTask,
+};
+use crate::fleet::fleet::FleetAdmiral;
+use anyhow::*;
+use chrono::{DateTime, TimeDelta, Utc};
+use itertools::Itertools;
+use st_domain::budgeting::treasury_redesign::{ActiveTradeRoute, FleetBudget};
+use st_domain::strategy::SupplyChainBoostingStrategy;
+use st_domain::supply_chain_boost::{boost_deliveries_from_ledger, evaluate_supply_chain_boost, export_observations, BoostedRoute, SupplyChainBoostEvaluation};
+use st_domain::{
+    calc_scored_supply_chain_routes, MarketEntry, MaterializedSupplyChain, ScoredSupplyChainSupportRoute, Ship, ShipSymbol, SupplyChainBoostingFleetConfig,
+    Waypoint, WaypointSymbol,
+};
+use st_store::bmc::Bmc;
+use st_store::Ctx;
+use std::collections::{HashMap, HashSet};
+use std::ops::Not;
+use std::sync::Arc;
+use tracing::event;
+use tracing_core::Level;
+
+/// How far we look back before the first delivery to find the market observation we compare against.
+const MARKET_HISTORY_LOOKBACK_HOURS: i64 = 24;
+
+pub struct SupplyChainBoostingFleet;
+
+impl SupplyChainBoostingFleet {
+    /// The routes that feed the producers of the goods of the strategy (directly or further down their supply chains), best scored first.
+    pub fn candidate_routes(strategy: &SupplyChainBoostingStrategy, materialized_supply_chain: &MaterializedSupplyChain) -> Vec<ScoredSupplyChainSupportRoute> {
+        calc_scored_supply_chain_routes(materialized_supply_chain, strategy.producing_trade_goods.clone())
+            .into_iter()
+            .filter(|r| r.score > 0)
+            .collect_vec()
+    }
+
+    /// Compares the export of every producer we've been feeding before and after our first delivery.
+    pub async fn evaluate_boosted_routes(
+        bmc: Arc<dyn Bmc>,
+        candidate_routes: &[ScoredSupplyChainSupportRoute],
+        strategy: &SupplyChainBoostingStrategy,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<SupplyChainBoostEvaluation>> {
+        let ledger_entries = bmc
+            .ledger_bmc()
+            .get_timestamped_ledger_entries_in_order(&Ctx::Anonymous)
+            .await?;
+        let deliveries = boost_deliveries_from_ledger(&ledger_entries);
+
+        let mut evaluations = Vec::new();
+        for route in candidate_routes
+            .iter()
+            .map(|r| BoostedRoute::from(&r.tgr))
+            .unique()
+        {
+            let maybe_deliveries = deliveries.get(&(route.producer.clone(), route.input_good.clone()));
+
+            let export_history = match maybe_deliveries {
+                None => vec![],
+                Some(boost_deliveries) => {
+                    let since = boost_deliveries.first_delivery_at - TimeDelta::hours(MARKET_HISTORY_LOOKBACK_HOURS);
+                    // the data retention downsamples the older part of the history into hourly aggregates
+                    let hourly_aggregates = bmc
+                        .market_bmc()
+                        .get_hourly_market_aggregates_of_waypoint(&Ctx::Anonymous, &route.producer, since)
+                        .await?;
+                    let market_history = bmc
+                        .market_bmc()
+                        .get_market_history_of_waypoint(&Ctx::Anonymous, &route.producer, since)
+                        .await?;
+                    export_observations(&hourly_aggregates, &market_history, &route.producer, &route.export_good)
+                }
+            };
+
+            evaluations.push(evaluate_supply_chain_boost(&route, maybe_deliveries, &export_history, strategy, now));
+        }
+
+        Ok(evaluations)
+    }
+
+    /// Keeps feeding the inputs of the producers - routes whose evaluation shows that the export didn't react get stopped.
+    /// Ships that still carry cargo sell it off first.
+    pub async fn compute_ship_tasks(
+        admiral: &FleetAdmiral,
+        cfg: &SupplyChainBoostingFleetConfig,
+        latest_market_entries: &Vec<MarketEntry>,
+        waypoints: &[Waypoint],
+        unassigned_ships_of_fleet: &[&Ship],
+        active_trade_routes: &HashSet<ActiveTradeRoute>,
+        fleet_budget: &FleetBudget,
+        boost_evaluations: &[SupplyChainBoostEvaluation],
+    ) -> Result<NewTasksResultForConstructionFleet> {
+        let mut unassigned_ships_with_existing_tickets: HashSet<ShipSymbol> = HashSet::new();
+        for s in unassigned_ships_of_fleet.iter() {
+            let existing_tickets = admiral
+                .treasurer
+                .get_active_tickets_for_ship(&s.symbol)
+                .await?;
+            if existing_tickets.is_empty().not() {
+                unassigned_ships_with_existing_tickets.insert(s.symbol.clone());
+            }
+        }
+
+        let (ships_with_cargo, idle_ships): (Vec<&Ship>, Vec<&Ship>) = unassigned_ships_of_fleet
+            .iter()
+            .filter(|s| {
+                unassigned_ships_with_existing_tickets
+                    .contains(&s.symbol)
+                    .not()
+            })
+            .cloned()
+            .partition(|s| s.cargo.units > 0);
+
+        let waypoint_map: HashMap<WaypointSymbol, &Waypoint> = waypoints
+            .iter()
+            .map(|wp| (wp.symbol.clone(), wp))
+            .collect::<HashMap<_, _>>();
+
+        let deliver_tasks_from_existing_cargo = create_tasks_for_ships_with_cargo(&ships_with_cargo, latest_market_entries, &waypoint_map, &None);
+
+        let stopped_routes: HashSet<BoostedRoute> = boost_evaluations
+            .iter()
+            .filter(|evaluation| evaluation.verdict.keep_feeding().not())
+            .map(|evaluation| evaluation.route.clone())
+            .collect();
+
+        for evaluation in boost_evaluations
+            .iter()
+            .filter(|evaluation| evaluation.verdict.keep_feeding().not())
+        {
+            event!(
+                Level::DEBUG,
+                message = "Not feeding producer anymore - its export didn't react",
+                producer = evaluation.route.producer.0,
+                input_good = evaluation.route.input_good.to_string(),
+                export_good = evaluation.route.export_good.to_string(),
+                volume_change_ratio = evaluation.volume_change_ratio(),
+                price_change_ratio = evaluation.price_change_ratio(),
+            );
+        }
+
+        let maybe_materialized_supply_chain = admiral
+            .materialized_supply_chain_manager
+            .get_materialized_supply_chain_for_system(cfg.system_symbol.clone());
+
+        let new_potential_construction_tasks = match maybe_materialized_supply_chain {
+            Some(materialized_supply_chain) if idle_ships.is_empty().not() => {
+                let routes_to_feed = Self::candidate_routes(&admiral.strategy().supply_chain_boosting, &materialized_supply_chain)
+                    .into_iter()
+                    .filter(|r| stopped_routes.contains(&BoostedRoute::from(&r.tgr)).not())
+                    .collect_vec();
+
+                let actions = create_boost_supply_chain_actions(&routes_to_feed, active_trade_routes, idle_ships.len());
+
+                find_best_combination(&idle_ships, &actions, &waypoint_map, fleet_budget)
+                    .into_iter()
+                    .map(|(ship_symbol, task)| PotentialConstructionTask { ship_symbol, task })
+                    .collect_vec()
+            }
+            _ => vec![],
+        };
+
+        Ok(NewTasksResultForConstructionFleet {
+            new_potential_construction_tasks,
+            unassigned_ships_with_existing_tickets,
+            deliver_tasks_from_existing_cargo,
+        })
+    }
+}
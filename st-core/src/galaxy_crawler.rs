@@ -0,0 +1,229 @@
+use crate::background_jobs::schedule_repeated;
+use crate::format_time_delta_hh_mm_ss;
+use crate::pagination::{fetch_all_pages_into_queue, PaginationInput};
+use crate::st_client::StClientTrait;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use governor::{DefaultDirectRateLimiter, Quota, RateLimiter};
+use itertools::Itertools;
+use sqlx::{Pool, Postgres};
+use st_domain::galaxy_crawl::GalaxyCrawlProgress;
+use st_domain::SystemSymbol;
+use st_store::bmc::Bmc;
+use st_store::{db, upsert_waypoints_from_receiver, Ctx};
+use std::num::NonZeroU32;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_cron_scheduler::JobScheduler;
+use tracing::{event, Level};
+
+/// How often the crawler job checks whether there's something left to download.
+const CRAWL_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const PAGE_LIMIT: u32 = 20;
+
+/// Downloads the systems and waypoints of the whole galaxy in the background after a reset.
+///
+/// The crawler has its own rate limiter in addition to the one of the client, so it only takes a share of the requests and the fleets don't starve.
+/// It can be interrupted at any time - the systems pages are resumed from the stored `GalaxyCrawlProgress` and the waypoints
+/// are only downloaded for systems whose waypoints aren't complete in the db yet.
+pub struct GalaxyCrawler {
+    client: Arc<dyn StClientTrait>,
+    bmc: Arc<dyn Bmc>,
+    pool: Pool<Postgres>,
+    home_system: SystemSymbol,
+    limiter: DefaultDirectRateLimiter,
+}
+
+impl GalaxyCrawler {
+    pub fn new(client: Arc<dyn StClientTrait>, bmc: Arc<dyn Bmc>, pool: Pool<Postgres>, home_system: SystemSymbol, requests_per_minute: NonZeroU32) -> Self {
+        Self {
+            client,
+            bmc,
+            pool,
+            home_system,
+            limiter: RateLimiter::direct(Quota::per_minute(requests_per_minute)),
+        }
+    }
+
+    pub async fn schedule(crawler: Arc<GalaxyCrawler>) -> Result<JobScheduler> {
+        schedule_repeated(CRAWL_INTERVAL, "Galaxy crawl", move || {
+            let crawler = Arc::clone(&crawler);
+            async move { crawler.crawl().await }
+        })
+        .await
+    }
+
+    pub async fn crawl(&self) -> Result<()> {
+        let maybe_progress = self
+            .bmc
+            .status_bmc()
+            .get_galaxy_crawl_progress(&Ctx::Anonymous)
+            .await?;
+
+        let mut progress = match maybe_progress {
+            Some(progress) if progress.is_complete() => return Ok(()),
+            Some(progress) => progress,
+            None => {
+                let status = self
+                    .bmc
+                    .status_bmc()
+                    .get_status(&Ctx::Anonymous)
+                    .await?
+                    .context("status of the current reset should have been stored")?;
+                GalaxyCrawlProgress::new(status.stats.systems as u32, Utc::now())
+            }
+        };
+
+        self.collect_missing_systems(&mut progress).await?;
+        self.collect_missing_waypoints(&mut progress).await?;
+
+        if progress.is_complete() {
+            event!(
+                Level::INFO,
+                "Galaxy crawl complete - all {} systems and their waypoints are in the db",
+                progress.systems_in_db
+            );
+        }
+
+        Ok(())
+    }
+
+    async fn save_progress(&self, progress: &GalaxyCrawlProgress) -> Result<()> {
+        self.bmc
+            .status_bmc()
+            .save_galaxy_crawl_progress(&Ctx::Anonymous, progress)
+            .await
+    }
+
+    async fn update_progress(&self, progress: &mut GalaxyCrawlProgress, systems_with_missing_waypoints: u32) -> Result<()> {
+        let systems_in_db = db::select_count_of_systems(&self.pool).await? as u32;
+        progress.update_counts(systems_in_db, systems_with_missing_waypoints, Utc::now());
+        self.save_progress(progress).await
+    }
+
+    async fn collect_missing_systems(&self, progress: &mut GalaxyCrawlProgress) -> Result<()> {
+        let systems_in_db = db::select_count_of_systems(&self.pool).await? as u32;
+        if systems_in_db >= progress.total_systems {
+            return Ok(());
+        }
+
+        event!(
+            Level::INFO,
+            "Not all {} systems are in database. Currently stored: {}. Resuming with page {}",
+            progress.total_systems,
+            systems_in_db,
+            progress.completed_systems_pages + 1
+        );
+
+        loop {
+            let page = progress.completed_systems_pages + 1;
+            self.limiter.until_ready().await;
+            let response = self
+                .client
+                .list_systems_page(PaginationInput { page, limit: PAGE_LIMIT })
+                .await?;
+            let total_number_of_pages = response.meta.total.div_ceil(response.meta.limit);
+
+            db::upsert_systems_page(&self.pool, response.data, Utc::now()).await?;
+
+            progress.completed_systems_pages = page;
+            progress.systems_in_db = db::select_count_of_systems(&self.pool).await? as u32;
+            progress.updated_at = Utc::now();
+            self.save_progress(progress).await?;
+
+            if page >= total_number_of_pages {
+                break;
+            }
+        }
+
+        if !progress.has_all_systems() {
+            // the pages have shifted since we started - start over with the next run
+            event!(
+                Level::WARN,
+                "Downloaded all systems pages, but only {} of {} systems are in the database",
+                progress.systems_in_db,
+                progress.total_systems
+            );
+            progress.completed_systems_pages = 0;
+            self.save_progress(progress).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn collect_missing_waypoints(&self, progress: &mut GalaxyCrawlProgress) -> Result<()> {
+        let systems = db::select_systems_with_waypoint_details_to_be_loaded(&self.pool).await?;
+        let maybe_home_system = db::select_system_with_coordinate(&self.pool, &self.home_system).await?;
+
+        // the systems closest to home first
+        let sorted = match &maybe_home_system {
+            Some(home_system) => systems
+                .iter()
+                .sorted_by_key(|s| home_system.distance_to(s))
+                .collect_vec(),
+            None => systems.iter().collect_vec(),
+        };
+
+        self.update_progress(progress, sorted.len() as u32).await?;
+        if sorted.is_empty() {
+            return Ok(());
+        }
+
+        event!(
+            Level::INFO,
+            "Collecting missing waypoints for {} systems in order of distance from home-system {}",
+            sorted.len(),
+            self.home_system
+        );
+
+        let start_timestamp = Utc::now();
+
+        for (idx, system) in sorted.iter().enumerate() {
+            self.collect_waypoints_of_system(SystemSymbol(system.system_symbol.clone()))
+                .await?;
+
+            let number_elements_left = sorted.len() - idx - 1;
+            self.update_progress(progress, number_elements_left as u32)
+                .await?;
+
+            let duration = Utc::now() - start_timestamp;
+            let download_speed = (idx + 1) as f32 / duration.num_seconds().max(1) as f32; // systems per second
+            let estimated_rest_duration = chrono::Duration::seconds((number_elements_left as f32 / download_speed) as i64);
+
+            event!(
+                Level::DEBUG,
+                "Downloaded waypoints for system {} ({} of {} systems) avg {:.2} systems/s; estimated duration: {}",
+                system.system_symbol,
+                idx + 1,
+                sorted.len(),
+                download_speed,
+                format_time_delta_hh_mm_ss(estimated_rest_duration),
+            );
+        }
+
+        Ok(())
+    }
+
+    async fn collect_waypoints_of_system(&self, system_symbol: SystemSymbol) -> Result<()> {
+        let (tx, rx) = mpsc::channel(100); // Buffer up to 100 pages
+
+        let client = &self.client;
+        let limiter = &self.limiter;
+        let system_symbol = &system_symbol;
+
+        let producer = fetch_all_pages_into_queue(
+            |page| async move {
+                limiter.until_ready().await;
+                client
+                    .list_waypoints_of_system_page(system_symbol, page)
+                    .await
+            },
+            PaginationInput { page: 1, limit: PAGE_LIMIT },
+            tx,
+        );
+
+        tokio::try_join!(producer, upsert_waypoints_from_receiver(&self.pool, rx))?;
+        Ok(())
+    }
+}
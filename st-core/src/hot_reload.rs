@@ -0,0 +1,141 @@
+use anyhow::Result;
+use itertools::Itertools;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tracing::{event, Level};
+
+type Fingerprint = Vec<(PathBuf, Option<SystemTime>)>;
+
+#[derive(Clone)]
+struct Source<T> {
+    path: PathBuf,
+    /// what is loaded - only used for logging
+    description: &'static str,
+    /// the files whose modification times are watched - the file itself or the files of a directory
+    list_files: fn(&Path) -> Result<Vec<PathBuf>>,
+    load: fn(&Path) -> Result<T>,
+}
+
+struct Loaded<T> {
+    fingerprint: Fingerprint,
+    value: T,
+}
+
+/// A value loaded from a file (or directory) that is reloaded when the modification times of its files change.
+///
+/// Changes are picked up the next time the value is asked for.
+/// If a changed file is invalid, the error is logged and the previously loaded value stays active.
+#[derive(Clone)]
+pub struct HotReloadable<T> {
+    maybe_source: Option<Source<T>>,
+    loaded: Arc<Mutex<Loaded<T>>>,
+}
+
+impl<T: Clone> HotReloadable<T> {
+    /// A value without a backing file - it never changes.
+    pub fn fixed(value: T) -> Self {
+        Self {
+            maybe_source: None,
+            loaded: Arc::new(Mutex::new(Loaded { fingerprint: vec![], value })),
+        }
+    }
+
+    /// Fails if the initial load fails - we don't want to start the agent with a broken configuration.
+    pub fn load(path: PathBuf, description: &'static str, list_files: fn(&Path) -> Result<Vec<PathBuf>>, load: fn(&Path) -> Result<T>) -> Result<Self> {
+        let source = Source {
+            path,
+            description,
+            list_files,
+            load,
+        };
+        let fingerprint = source.compute_fingerprint()?;
+        let value = (source.load)(&source.path)?;
+
+        event!(
+            Level::INFO,
+            message = "Loaded hot-reloadable file",
+            description,
+            path = source.path.display().to_string(),
+            num_files = fingerprint.len(),
+        );
+
+        Ok(Self {
+            maybe_source: Some(source),
+            loaded: Arc::new(Mutex::new(Loaded { fingerprint, value })),
+        })
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.maybe_source
+            .as_ref()
+            .map(|source| source.path.as_path())
+    }
+
+    pub fn get(&self) -> T {
+        let mut loaded = match self.loaded.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        if let Some(source) = &self.maybe_source {
+            match source.compute_fingerprint() {
+                Ok(fingerprint) if fingerprint != loaded.fingerprint => {
+                    // remember the fingerprint in any case, so that a broken file is only reported once
+                    loaded.fingerprint = fingerprint;
+                    match (source.load)(&source.path) {
+                        Ok(value) => {
+                            loaded.value = value;
+                            event!(
+                                Level::INFO,
+                                message = "Reloaded hot-reloadable file",
+                                description = source.description,
+                                path = source.path.display().to_string(),
+                            );
+                        }
+                        Err(err) => {
+                            event!(
+                                Level::ERROR,
+                                message = "Unable to reload hot-reloadable file - keeping the previous value",
+                                description = source.description,
+                                path = source.path.display().to_string(),
+                                error = format!("{err:#}"),
+                            );
+                        }
+                    }
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    event!(
+                        Level::WARN,
+                        message = "Unable to check hot-reloadable file for changes",
+                        description = source.description,
+                        path = source.path.display().to_string(),
+                        error = format!("{err:#}"),
+                    );
+                }
+            }
+        }
+
+        loaded.value.clone()
+    }
+}
+
+impl<T> Source<T> {
+    fn compute_fingerprint(&self) -> Result<Fingerprint> {
+        Ok((self.list_files)(&self.path)?
+            .into_iter()
+            .map(|path| {
+                let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+                (path, modified)
+            })
+            .collect_vec())
+    }
+}
+
+/// `list_files` for a value that is loaded from a single file.
+pub fn single_file(path: &Path) -> Result<Vec<PathBuf>> {
+    // fail early, if the file doesn't exist - a missing file is reported as error and doesn't count as change
+    std::fs::metadata(path)?;
+    Ok(vec![path.to_path_buf()])
+}
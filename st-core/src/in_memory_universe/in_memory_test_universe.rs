@@ -64,6 +64,7 @@ pub async fn get_test_universe() -> (Arc<dyn Bmc>, Arc<dyn StClientTrait>) {
         in_mem_status_bmc: Arc::new(status_bmc),
         in_mem_ledger_bmc: Arc::new(ledger_bmc),
         in_mem_contract_bmc: Arc::new(contract_bmc),
+        in_mem_cargo_flow_bmc: Arc::new(Default::default()),
     };
 
     let client = Arc::new(in_memory_client) as Arc<dyn StClientTrait>;
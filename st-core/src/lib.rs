@@ -1,3 +1,4 @@
+pub mod cassette;
 pub mod configuration;
 pub mod fleet;
 pub mod pagination;
@@ -14,16 +15,27 @@ use std::fmt::Display;
 pub mod agent;
 pub mod agent_manager;
 pub mod app_state;
+pub mod background_jobs;
 pub mod behavior_tree;
 mod bmc_blackboard;
+pub mod competitor_tracker;
+pub mod data_retention;
 pub mod exploration;
+pub mod galaxy_crawler;
+pub mod hot_reload;
 pub mod in_memory_universe;
 pub mod marketplaces;
 pub mod pathfinder;
+pub mod strategy;
 pub mod universe_server;
 
 pub mod contract_manager;
+pub mod market_observation_scheduler;
 pub mod materialized_supply_chain_manager;
+pub mod mining_hauler_dispatcher;
+pub mod mining_site_manager;
+pub mod ship_purchase_planner;
+pub mod ship_type_evaluator;
 pub mod survey_manager;
 
 #[cfg(test)]
@@ -0,0 +1,163 @@
+use crate::strategy::StrategyStore;
+use anyhow::anyhow;
+use chrono::{DateTime, TimeDelta, Utc};
+use st_domain::budgeting::treasury_redesign::{FinanceTicket, FinanceTicketDetails};
+use st_domain::market_observation::{apply_request_budget, measure_market_volatility, observation_interval};
+use st_domain::strategy::MarketObservationStrategy;
+use st_domain::{MarketEntry, WaypointSymbol};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+/// Decides when a market needs to be observed again - shared by the stationary probes and all ships that happen to be docked at a market.
+///
+/// Every market gets its own interval based on its volatility and our trading there. All observations that go through the scheduler
+/// share a global request budget.
+#[derive(Clone, Debug)]
+pub struct MarketObservationScheduler {
+    strategy_store: StrategyStore,
+    state: Arc<Mutex<MarketObservationState>>,
+}
+
+#[derive(Debug, Default)]
+struct MarketObservationState {
+    intervals: HashMap<WaypointSymbol, TimeDelta>,
+    last_observations: HashMap<WaypointSymbol, DateTime<Utc>>,
+    recent_requests: VecDeque<DateTime<Utc>>,
+}
+
+impl Default for MarketObservationScheduler {
+    fn default() -> Self {
+        Self::new(StrategyStore::default())
+    }
+}
+
+impl MarketObservationScheduler {
+    pub fn new(strategy_store: StrategyStore) -> Self {
+        Self {
+            strategy_store,
+            state: Arc::new(Mutex::new(MarketObservationState::default())),
+        }
+    }
+
+    pub fn market_observation_strategy(&self) -> MarketObservationStrategy {
+        self.strategy_store.get_strategy().market_observation
+    }
+
+    /// Recomputes the interval of a market from its recent history and the active trade tickets at the market.
+    pub fn update_interval(&self, waypoint_symbol: &WaypointSymbol, history: &[MarketEntry], active_tickets: &[FinanceTicket]) -> anyhow::Result<TimeDelta> {
+        let strategy = self.market_observation_strategy();
+        let num_active_trades = active_tickets
+            .iter()
+            .filter(|ticket| {
+                matches!(
+                    ticket.details,
+                    FinanceTicketDetails::PurchaseTradeGoods(_) | FinanceTicketDetails::SellTradeGoods(_)
+                )
+            })
+            .filter(|ticket| &ticket.details.get_waypoint() == waypoint_symbol)
+            .count();
+        let interval = observation_interval(measure_market_volatility(history).as_ref(), num_active_trades, &strategy);
+
+        self.state
+            .lock()
+            .map_err(|_| anyhow!("Lock poisoned"))?
+            .intervals
+            .insert(waypoint_symbol.clone(), interval);
+
+        Ok(interval)
+    }
+
+    /// Every fresh observation of a market pushes its next scheduled observation back - no matter who observed it.
+    pub fn record_observation(&self, waypoint_symbol: &WaypointSymbol, observed_at: DateTime<Utc>) -> anyhow::Result<()> {
+        self.state
+            .lock()
+            .map_err(|_| anyhow!("Lock poisoned"))?
+            .last_observations
+            .insert(waypoint_symbol.clone(), observed_at);
+
+        Ok(())
+    }
+
+    /// None for markets we haven't observed yet - they are due right away.
+    pub fn next_observation_at(&self, waypoint_symbol: &WaypointSymbol) -> anyhow::Result<Option<DateTime<Utc>>> {
+        let strategy = self.market_observation_strategy();
+        let state = self.state.lock().map_err(|_| anyhow!("Lock poisoned"))?;
+
+        let Some(last_observation) = state.last_observations.get(waypoint_symbol) else {
+            return Ok(None);
+        };
+        let interval = apply_request_budget(&state.intervals, strategy.max_requests_per_minute)
+            .get(waypoint_symbol)
+            .cloned()
+            .unwrap_or(TimeDelta::minutes(strategy.default_interval_minutes));
+
+        Ok(Some(*last_observation + interval))
+    }
+
+    pub fn is_due(&self, waypoint_symbol: &WaypointSymbol, now: DateTime<Utc>) -> anyhow::Result<bool> {
+        Ok(self
+            .next_observation_at(waypoint_symbol)?
+            .map(|next_observation_at| next_observation_at <= now)
+            .unwrap_or(true))
+    }
+
+    /// Takes one request from the global budget. If the budget of the last minute is used up, we get the time the next request fits in.
+    pub fn try_acquire_request(&self, now: DateTime<Utc>) -> anyhow::Result<Result<(), DateTime<Utc>>> {
+        let max_requests_per_minute = self.market_observation_strategy().max_requests_per_minute as usize;
+        let mut state = self.state.lock().map_err(|_| anyhow!("Lock poisoned"))?;
+
+        let window_start = now - TimeDelta::minutes(1);
+        while state
+            .recent_requests
+            .front()
+            .is_some_and(|requested_at| *requested_at <= window_start)
+        {
+            state.recent_requests.pop_front();
+        }
+
+        if state.recent_requests.len() < max_requests_per_minute.max(1) {
+            state.recent_requests.push_back(now);
+            Ok(Ok(()))
+        } else {
+            let oldest_request = state.recent_requests.front().cloned().unwrap_or(now);
+            Ok(Err(oldest_request + TimeDelta::minutes(1)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn observations_of_docked_ships_postpone_the_next_observation_within_the_request_budget() -> anyhow::Result<()> {
+        let scheduler = MarketObservationScheduler::default();
+        let strategy = scheduler.market_observation_strategy();
+        let market = WaypointSymbol("X1-FOO-A1".to_string());
+        let now = DateTime::<Utc>::default();
+
+        assert!(scheduler.is_due(&market, now)?);
+
+        // without history we fall back to the default interval
+        let interval = scheduler.update_interval(&market, &[], &[])?;
+        assert_eq!(interval, TimeDelta::minutes(strategy.default_interval_minutes));
+
+        scheduler.record_observation(&market, now)?;
+        assert_eq!(scheduler.next_observation_at(&market)?, Some(now + interval));
+        assert!(!scheduler.is_due(&market, now + TimeDelta::minutes(5))?);
+
+        // a trader docked at the market and observed it after five minutes
+        scheduler.record_observation(&market, now + TimeDelta::minutes(5))?;
+        assert!(!scheduler.is_due(&market, now + interval)?);
+        assert!(scheduler.is_due(&market, now + TimeDelta::minutes(5) + interval)?);
+
+        for _ in 0..strategy.max_requests_per_minute {
+            assert_eq!(scheduler.try_acquire_request(now)?, Ok(()));
+        }
+        let later = now + TimeDelta::seconds(30);
+        assert_eq!(scheduler.try_acquire_request(later)?, Err(now + TimeDelta::minutes(1)));
+        assert_eq!(scheduler.try_acquire_request(now + TimeDelta::minutes(1))?, Ok(()));
+
+        Ok(())
+    }
+}
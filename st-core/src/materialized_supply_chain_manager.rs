@@ -1,6 +1,7 @@
 use anyhow::anyhow;
+use chrono::{DateTime, TimeDelta, Utc};
 use st_domain::{
-    MaterializedSupplyChain, MiningOpsConfig, RawDeliveryRoute, RawMaterialSource, RawMaterialSourceType, SiphoningOpsConfig, SupplyLevel,
+    Cooldown, MaterializedSupplyChain, MiningOpsConfig, RawDeliveryRoute, RawMaterialSource, RawMaterialSourceType, SiphoningOpsConfig, SupplyLevel,
     SystemSymbol, TradeGoodSymbol, WaypointSymbol,
 };
 use std::collections::{HashMap, HashSet};
@@ -75,10 +76,12 @@ impl MaterializedSupplyChainManager {
         let raw_material_source_type = RawMaterialSourceType::Siphoning;
 
         let (siphoning_waypoint, delivery_locations, demanded_goods) = get_locations_and_demand_for_raw_material(raw_material_source_type, msc);
+        let demanded_rate = demanded_siphoning_rate(&delivery_locations);
 
         Some(SiphoningOpsConfig {
             siphoning_waypoint,
             demanded_goods,
+            demanded_rate,
             delivery_locations,
         })
     }
@@ -138,3 +141,120 @@ fn get_locations_and_demand_for_raw_material(
         .unwrap();
     (waypoint.symbol.clone(), delivery_locations, demanded_goods)
 }
+
+/// The lower the supply at the delivery markets, the more of the siphoned gas they absorb.
+/// A siphon yields any of the gases, so the most demanded one sets the rate.
+fn demanded_siphoning_rate(delivery_locations: &HashMap<TradeGoodSymbol, RawDeliveryRoute>) -> f64 {
+    delivery_locations
+        .values()
+        .map(|route| match route.delivery_market_entry.supply {
+            SupplyLevel::Scarce => 1.0,
+            SupplyLevel::Limited => 0.75,
+            SupplyLevel::Moderate => 0.5,
+            SupplyLevel::High | SupplyLevel::Abundant => 0.0,
+        })
+        .fold(0.0, f64::max)
+}
+
+/// How long a siphon drone pauses before siphoning again, so that the fleet siphons at the demanded rate.
+/// With the full rate the drone siphons as soon as its cooldown expires, with half the rate it waits twice the cooldown.
+/// Returns `None` if nothing is demanded.
+pub fn time_until_siphoning_is_due(demanded_rate: f64, cooldown: &Cooldown, now: DateTime<Utc>) -> Option<TimeDelta> {
+    if demanded_rate <= 0.0 {
+        return None;
+    }
+
+    let remaining = match cooldown.expiration {
+        Some(expiration) => {
+            let last_siphoning_at = expiration - TimeDelta::seconds(cooldown.total_seconds as i64);
+            let throttled_cooldown_secs = cooldown.total_seconds as f64 / demanded_rate.min(1.0);
+            last_siphoning_at + TimeDelta::milliseconds((throttled_cooldown_secs * 1000.0) as i64) - now
+        }
+        None => TimeDelta::zero(),
+    };
+
+    Some(remaining.max(TimeDelta::zero()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use st_domain::{MarketTradeGood, ShipSymbol, TradeGoodType};
+
+    fn delivery_route(trade_good: TradeGoodSymbol, supply: SupplyLevel) -> RawDeliveryRoute {
+        let market_entry = MarketTradeGood {
+            symbol: trade_good.clone(),
+            trade_good_type: TradeGoodType::Import,
+            trade_volume: 60,
+            supply,
+            activity: None,
+            purchase_price: 100,
+            sell_price: 90,
+        };
+        RawDeliveryRoute {
+            source: RawMaterialSource {
+                trade_good,
+                source_type: RawMaterialSourceType::Siphoning,
+                source_waypoint: WaypointSymbol("X1-FOO-B2".to_string()),
+            },
+            delivery_location: WaypointSymbol("X1-FOO-A1".to_string()),
+            distance: 50,
+            delivery_market_entry: market_entry.clone(),
+            export_entry: market_entry,
+        }
+    }
+
+    #[test]
+    fn the_most_demanded_gas_sets_the_siphoning_rate() {
+        let rate = |supplies: Vec<(TradeGoodSymbol, SupplyLevel)>| {
+            let delivery_locations = supplies
+                .into_iter()
+                .map(|(tg, supply)| (tg.clone(), delivery_route(tg, supply)))
+                .collect();
+            demanded_siphoning_rate(&delivery_locations)
+        };
+
+        assert_eq!(rate(vec![]), 0.0);
+        assert_eq!(
+            rate(vec![
+                (TradeGoodSymbol::LIQUID_HYDROGEN, SupplyLevel::High),
+                (TradeGoodSymbol::HYDROCARBON, SupplyLevel::Abundant)
+            ]),
+            0.0
+        );
+        assert_eq!(
+            rate(vec![
+                (TradeGoodSymbol::LIQUID_HYDROGEN, SupplyLevel::Moderate),
+                (TradeGoodSymbol::HYDROCARBON, SupplyLevel::High)
+            ]),
+            0.5
+        );
+        assert_eq!(
+            rate(vec![
+                (TradeGoodSymbol::LIQUID_HYDROGEN, SupplyLevel::Moderate),
+                (TradeGoodSymbol::HYDROCARBON, SupplyLevel::Scarce)
+            ]),
+            1.0
+        );
+    }
+
+    #[test]
+    fn siphoning_is_throttled_to_the_demanded_rate() {
+        let now = Utc::now();
+        // the last siphoning was 60s ago with a cooldown of 50s
+        let cooldown = Cooldown {
+            ship_symbol: ShipSymbol("FLWI-1".to_string()),
+            total_seconds: 50,
+            remaining_seconds: 0,
+            expiration: Some(now - TimeDelta::seconds(10)),
+        };
+
+        assert_eq!(time_until_siphoning_is_due(0.0, &cooldown, now), None);
+        assert_eq!(time_until_siphoning_is_due(1.0, &cooldown, now), Some(TimeDelta::zero()));
+        assert_eq!(time_until_siphoning_is_due(0.75, &cooldown, now), Some(TimeDelta::milliseconds(6_666)));
+        assert_eq!(time_until_siphoning_is_due(0.5, &cooldown, now), Some(TimeDelta::seconds(40)));
+
+        let never_siphoned = Cooldown { expiration: None, ..cooldown };
+        assert_eq!(time_until_siphoning_is_due(0.5, &never_siphoned, now), Some(TimeDelta::zero()));
+    }
+}
@@ -0,0 +1,265 @@
+use crate::calculate_time;
+use anyhow::anyhow;
+use chrono::{DateTime, TimeDelta, Utc};
+use st_domain::{FlightMode, LabelledCoordinate, RawDeliveryRoute, ShipSymbol, TradeGoodSymbol, Waypoint, WaypointSymbol};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Docking, selling and undocking at the delivery markets of a round trip.
+const SECS_AT_DELIVERY_MARKETS: u32 = 30;
+
+/// Drones that haven't reported their cargo for this long have left the site (or the game) and don't count towards its fill rate.
+const DRONE_STATUS_TTL_SECS: i64 = 600;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DroneCargoStatus {
+    pub site: WaypointSymbol,
+    pub cargo_units: u32,
+    pub cargo_capacity: u32,
+    pub yield_size: u32,
+    pub cooldown_secs: u32,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl DroneCargoStatus {
+    pub fn units_per_sec(&self) -> f64 {
+        self.yield_size as f64 / self.cooldown_secs.max(1) as f64
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HaulerTrip {
+    pub site: WaypointSymbol,
+    pub expected_back_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DroneCargoAction {
+    WaitForHauler,
+    SellAtDeliveryMarket,
+    Jettison,
+}
+
+/// Estimates how long a ship needs from the mining site to the delivery markets of the goods and back.
+/// We fly to the farthest market - the others are usually on the way.
+pub fn estimate_delivery_round_trip_secs(
+    waypoints: &[Waypoint],
+    site: &WaypointSymbol,
+    delivery_locations: &HashMap<TradeGoodSymbol, RawDeliveryRoute>,
+    trade_goods: &[TradeGoodSymbol],
+    engine_speed: u32,
+) -> u32 {
+    let maybe_site_wp = waypoints.iter().find(|wp| &wp.symbol == site);
+
+    let max_distance = trade_goods
+        .iter()
+        .filter_map(|tg| delivery_locations.get(tg))
+        .filter_map(|route| {
+            let delivery_wp = waypoints
+                .iter()
+                .find(|wp| wp.symbol == route.delivery_location)?;
+            maybe_site_wp.map(|site_wp| site_wp.distance_to(delivery_wp))
+        })
+        .max()
+        .unwrap_or_default();
+
+    2 * calculate_time(&FlightMode::Cruise, max_distance, engine_speed.max(1)) + SECS_AT_DELIVERY_MARKETS
+}
+
+/// Decides what a drone with a full cargo hold does if no hauler could take its cargo right now.
+///
+/// Each option costs units: waiting for the next hauler and selling the cargo ourselves cost the units we'd have extracted in the meantime,
+/// jettisoning costs the cargo itself. We pick the cheapest one - on a tie we'd rather wait than sell and rather sell than jettison.
+pub fn decide_drone_cargo_action(
+    cargo_units: u32,
+    yield_size: u32,
+    cooldown_secs: u32,
+    maybe_secs_until_hauler_arrives: Option<i64>,
+    delivery_round_trip_secs: u32,
+) -> DroneCargoAction {
+    let units_per_sec = yield_size as f64 / cooldown_secs.max(1) as f64;
+
+    let units_lost_waiting = maybe_secs_until_hauler_arrives.map(|secs| secs.max(0) as f64 * units_per_sec);
+    let units_lost_selling = delivery_round_trip_secs as f64 * units_per_sec;
+    let units_lost_jettisoning = cargo_units as f64;
+
+    match units_lost_waiting {
+        Some(waiting) if waiting <= units_lost_selling && waiting <= units_lost_jettisoning => DroneCargoAction::WaitForHauler,
+        _ if units_lost_selling <= units_lost_jettisoning => DroneCargoAction::SellAtDeliveryMarket,
+        _ => DroneCargoAction::Jettison,
+    }
+}
+
+/// A hauler is ready for the delivery once it's filled above the threshold.
+pub fn is_hauler_filled_enough(cargo_units: u32, cargo_capacity: u32, fill_threshold: f64) -> bool {
+    cargo_units as f64 / cargo_capacity.max(1) as f64 > fill_threshold
+}
+
+/// A hauler leaves the site once it's filled above the threshold.
+///
+/// It leaves early if another hauler is waiting to take over and the drones would need longer than a whole round trip to fill it up.
+/// Delivering what we have keeps the rotation going instead of having two haulers idle at the same site.
+pub fn should_hauler_depart(
+    cargo_units: u32,
+    cargo_capacity: u32,
+    fill_threshold: f64,
+    site_units_per_sec: f64,
+    delivery_round_trip_secs: u32,
+    num_other_waiting_haulers: usize,
+) -> bool {
+    if is_hauler_filled_enough(cargo_units, cargo_capacity, fill_threshold) {
+        return true;
+    }
+
+    if cargo_units == 0 || num_other_waiting_haulers == 0 || site_units_per_sec <= 0.0 {
+        return false;
+    }
+
+    let units_until_threshold = fill_threshold * cargo_capacity as f64 - cargo_units as f64;
+    let secs_until_threshold = units_until_threshold / site_units_per_sec;
+
+    secs_until_threshold > delivery_round_trip_secs as f64
+}
+
+#[derive(Debug, Default)]
+struct DispatcherState {
+    drones: HashMap<ShipSymbol, DroneCargoStatus>,
+    hauler_trips: HashMap<ShipSymbol, HaulerTrip>,
+}
+
+/// Keeps track of the cargo of the drones and the trips of the haulers at each mining site.
+#[derive(Clone, Debug, Default)]
+pub struct MiningHaulerDispatcher {
+    state: Arc<Mutex<DispatcherState>>,
+}
+
+impl MiningHaulerDispatcher {
+    pub fn report_drone_cargo(&self, drone: ShipSymbol, status: DroneCargoStatus) -> anyhow::Result<()> {
+        self.state
+            .lock()
+            .map_err(|_| anyhow!("Lock poisoned"))?
+            .drones
+            .insert(drone, status);
+
+        Ok(())
+    }
+
+    /// The combined extraction rate of the drones at this site in units per second.
+    pub fn site_units_per_sec(&self, site: &WaypointSymbol, now: DateTime<Utc>) -> f64 {
+        self.state
+            .lock()
+            .map(|state| {
+                state
+                    .drones
+                    .values()
+                    .filter(|status| &status.site == site && now - status.updated_at < TimeDelta::seconds(DRONE_STATUS_TTL_SECS))
+                    .map(|status| status.units_per_sec())
+                    .sum()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn hauler_arrived(&self, hauler: &ShipSymbol) -> anyhow::Result<()> {
+        self.state
+            .lock()
+            .map_err(|_| anyhow!("Lock poisoned"))?
+            .hauler_trips
+            .remove(hauler);
+
+        Ok(())
+    }
+
+    pub fn hauler_departed(&self, hauler: ShipSymbol, site: WaypointSymbol, expected_back_at: DateTime<Utc>) -> anyhow::Result<()> {
+        self.state
+            .lock()
+            .map_err(|_| anyhow!("Lock poisoned"))?
+            .hauler_trips
+            .insert(hauler, HaulerTrip { site, expected_back_at });
+
+        Ok(())
+    }
+
+    /// None if none of the haulers of this site is on its way.
+    pub fn secs_until_next_hauler_returns(&self, site: &WaypointSymbol, now: DateTime<Utc>) -> Option<i64> {
+        self.state.lock().ok().and_then(|state| {
+            state
+                .hauler_trips
+                .values()
+                .filter(|trip| &trip.site == site)
+                .map(|trip| (trip.expected_back_at - now).num_seconds().max(0))
+                .min()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::ops::Not;
+
+    #[test]
+    fn drones_pick_the_cheapest_option_for_their_full_cargo() {
+        // 10 units per 70s extraction, 30 units of cargo
+        let decide = |maybe_secs_until_hauler_arrives, round_trip_secs| decide_drone_cargo_action(30, 10, 70, maybe_secs_until_hauler_arrives, round_trip_secs);
+
+        assert_eq!(decide(Some(60), 300), DroneCargoAction::WaitForHauler);
+        // the hauler takes longer than selling the cargo ourselves
+        assert_eq!(decide(Some(400), 180), DroneCargoAction::SellAtDeliveryMarket);
+        // during a 700s trip we'd extract 100 units - more than our cargo is worth
+        assert_eq!(decide(None, 700), DroneCargoAction::Jettison);
+        assert_eq!(decide(Some(3600), 700), DroneCargoAction::Jettison);
+    }
+
+    #[test]
+    fn haulers_leave_early_if_another_one_takes_over() {
+        // 40 of 80 units, threshold 64 units
+        let depart = |site_units_per_sec, num_other_waiting_haulers| should_hauler_depart(40, 80, 0.8, site_units_per_sec, 300, num_other_waiting_haulers);
+
+        // nobody takes over
+        assert!(depart(0.01, 0).not());
+        // 24 units at 0.01 units/s takes 2400s - longer than a round trip
+        assert!(depart(0.01, 1));
+        // 24 units at 0.5 units/s take 48s
+        assert!(depart(0.5, 1).not());
+        // without any drone reports we only go by the fill threshold
+        assert!(depart(0.0, 1).not());
+        assert!(should_hauler_depart(70, 80, 0.8, 0.0, 300, 0));
+    }
+
+    #[test]
+    fn tracks_hauler_trips_and_drone_extraction_rates() {
+        let now = Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap();
+        let site = WaypointSymbol("X1-FOO-A".to_string());
+        let dispatcher = MiningHaulerDispatcher::default();
+
+        let status = |updated_at| DroneCargoStatus {
+            site: site.clone(),
+            cargo_units: 10,
+            cargo_capacity: 15,
+            yield_size: 10,
+            cooldown_secs: 50,
+            updated_at,
+        };
+        dispatcher
+            .report_drone_cargo(ShipSymbol("MINER-1".to_string()), status(now))
+            .unwrap();
+        dispatcher
+            .report_drone_cargo(ShipSymbol("MINER-2".to_string()), status(now - TimeDelta::seconds(DRONE_STATUS_TTL_SECS)))
+            .unwrap();
+        assert_eq!(dispatcher.site_units_per_sec(&site, now), 0.2);
+
+        assert_eq!(dispatcher.secs_until_next_hauler_returns(&site, now), None);
+        for (hauler, secs) in [("HAULER-1", 300), ("HAULER-2", 120)] {
+            dispatcher
+                .hauler_departed(ShipSymbol(hauler.to_string()), site.clone(), now + TimeDelta::seconds(secs))
+                .unwrap();
+        }
+        assert_eq!(dispatcher.secs_until_next_hauler_returns(&site, now), Some(120));
+
+        dispatcher
+            .hauler_arrived(&ShipSymbol("HAULER-2".to_string()))
+            .unwrap();
+        assert_eq!(dispatcher.secs_until_next_hauler_returns(&site, now), Some(300));
+    }
+}
@@ -0,0 +1,581 @@
+use crate::strategy::StrategyStore;
+use crate::universe_server::universe_server::get_possible_extraction_materials_by_waypoint_traits;
+use anyhow::anyhow;
+use itertools::Itertools;
+use st_domain::extraction_analytics::{delivery_prices, ExtractionYieldReport, YieldStats, DEFAULT_UNITS_PER_EXTRACTION};
+use st_domain::{
+    Extraction, LabelledCoordinate, RawDeliveryRoute, ShipRole, ShipSymbol, ShipTask, Survey, SurveySignature, TradeGoodSymbol, Waypoint, WaypointSymbol,
+    WaypointTraitSymbol, WaypointType,
+};
+use std::collections::{HashMap, HashSet};
+use std::ops::Not;
+use std::sync::{Arc, Mutex};
+use tracing::debug;
+
+/// A site that is 100 units further away from its delivery markets needs twice the value to be ranked equally.
+const DISTANCE_HALVING_THE_SCORE: f64 = 100.0;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RankedMiningSite {
+    pub waypoint_symbol: WaypointSymbol,
+    pub deliverable_goods: Vec<TradeGoodSymbol>,
+    pub expected_value_per_extraction: f64,
+    pub avg_distance_to_delivery_locations: f64,
+    pub score: f64,
+}
+
+/// Ranks the asteroids of a system by the expected credit value per extraction and the distance to their delivery markets.
+///
+/// The value is based on our observed extractions at the site and the current prices at the delivery markets. Sites without
+/// observations are estimated by the average price of their possible deposits - goods we can't deliver are worth nothing.
+/// Asteroids that have reached their critical limit are skipped.
+pub fn rank_mining_sites(
+    waypoints: &[Waypoint],
+    delivery_locations: &HashMap<TradeGoodSymbol, RawDeliveryRoute>,
+    observed_yields: &HashMap<WaypointSymbol, YieldStats>,
+) -> Vec<RankedMiningSite> {
+    let waypoint_map: HashMap<&WaypointSymbol, &Waypoint> = waypoints.iter().map(|wp| (&wp.symbol, wp)).collect();
+    let delivered_goods: HashSet<TradeGoodSymbol> = delivery_locations.keys().cloned().collect();
+    let prices = delivery_prices(delivery_locations, &delivered_goods);
+
+    waypoints
+        .iter()
+        .filter(|wp| wp.r#type == WaypointType::ASTEROID || wp.r#type == WaypointType::ENGINEERED_ASTEROID)
+        .filter(|wp| wp.has_reached_critical_limit().not())
+        .filter_map(|wp| {
+            let trait_symbols: Vec<WaypointTraitSymbol> = wp.traits.iter().map(|t| t.symbol.clone()).collect();
+            let possible_goods = get_possible_extraction_materials_by_waypoint_traits(&trait_symbols);
+            let deliverable_goods: HashSet<TradeGoodSymbol> = possible_goods
+                .intersection(&delivered_goods)
+                .cloned()
+                .collect();
+
+            if deliverable_goods.is_empty() {
+                return None;
+            }
+
+            let expected_value_per_extraction = match observed_yields.get(&wp.symbol) {
+                Some(observed) if observed.num_extractions > 0 => observed.avg_value_per_extraction(&prices),
+                _ => {
+                    let total_price: i64 = deliverable_goods
+                        .iter()
+                        .filter_map(|tg| prices.get(tg))
+                        .map(|price| price.0)
+                        .sum();
+                    DEFAULT_UNITS_PER_EXTRACTION * total_price as f64 / possible_goods.len() as f64
+                }
+            };
+
+            let distances = deliverable_goods
+                .iter()
+                .filter_map(|tg| delivery_locations.get(tg))
+                .filter_map(|route| waypoint_map.get(&route.delivery_location))
+                .map(|delivery_wp| wp.distance_to(delivery_wp) as f64)
+                .collect_vec();
+            let avg_distance_to_delivery_locations = if distances.is_empty() {
+                0.0
+            } else {
+                distances.iter().sum::<f64>() / distances.len() as f64
+            };
+
+            let score = expected_value_per_extraction / (1.0 + avg_distance_to_delivery_locations / DISTANCE_HALVING_THE_SCORE);
+
+            Some(RankedMiningSite {
+                waypoint_symbol: wp.symbol.clone(),
+                deliverable_goods: deliverable_goods.into_iter().sorted().collect(),
+                expected_value_per_extraction,
+                avg_distance_to_delivery_locations,
+                score,
+            })
+        })
+        .sorted_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.waypoint_symbol.cmp(&b.waypoint_symbol))
+        })
+        .collect()
+}
+
+pub fn mining_role_and_site(ship_task: &ShipTask) -> Option<(ShipRole, WaypointSymbol)> {
+    match ship_task {
+        ShipTask::MineMaterialsAtWaypoint { mining_waypoint } => Some((ShipRole::Miner, mining_waypoint.clone())),
+        ShipTask::SurveyMiningSite { mining_waypoint } => Some((ShipRole::MiningSurveyor, mining_waypoint.clone())),
+        ShipTask::HaulMiningGoods { mining_waypoint } => Some((ShipRole::MiningHauler, mining_waypoint.clone())),
+        _ => None,
+    }
+}
+
+/// Returns the same mining task at a different site. None for non-mining tasks.
+pub fn with_mining_site(ship_task: &ShipTask, mining_waypoint: WaypointSymbol) -> Option<ShipTask> {
+    match ship_task {
+        ShipTask::MineMaterialsAtWaypoint { .. } => Some(ShipTask::MineMaterialsAtWaypoint { mining_waypoint }),
+        ShipTask::SurveyMiningSite { .. } => Some(ShipTask::SurveyMiningSite { mining_waypoint }),
+        ShipTask::HaulMiningGoods { .. } => Some(ShipTask::HaulMiningGoods { mining_waypoint }),
+        _ => None,
+    }
+}
+
+/// Picks the site for a ship of the mining fleet.
+///
+/// Drones fill up the sites in order of their rank. Once all sites are full, they join the site with the fewest drones.
+/// Surveyors and haulers follow the drones - they join the site with drones that has the fewest ships of their own role.
+/// Returns None for surveyors and haulers if none of the sites has drones.
+pub fn pick_mining_site(
+    role: &ShipRole,
+    ranked_sites: &[WaypointSymbol],
+    assignments: &HashMap<ShipSymbol, (ShipRole, WaypointSymbol)>,
+    max_drones_per_site: usize,
+) -> Option<WaypointSymbol> {
+    let count = |site: &WaypointSymbol, role: &ShipRole| {
+        assignments
+            .values()
+            .filter(|(r, s)| r == role && s == site)
+            .count()
+    };
+
+    match role {
+        ShipRole::Miner => ranked_sites
+            .iter()
+            .find(|site| count(site, role) < max_drones_per_site)
+            .or_else(|| ranked_sites.iter().min_by_key(|site| count(site, role)))
+            .cloned(),
+        _ => ranked_sites
+            .iter()
+            .filter(|site| count(site, &ShipRole::Miner) > 0)
+            .min_by_key(|site| count(site, role))
+            .cloned(),
+    }
+}
+
+/// Returns the survey with the highest value after discounting it by the number of drones that are already using it.
+/// Ties go to the higher ranked survey.
+pub fn pick_shared_survey(ranked_surveys: &[(Survey, f64)], survey_claims: &HashMap<ShipSymbol, SurveySignature>) -> Option<Survey> {
+    let num_claims = |survey: &Survey| {
+        survey_claims
+            .values()
+            .filter(|signature| **signature == survey.signature)
+            .count()
+    };
+
+    ranked_surveys
+        .iter()
+        .map(|(survey, value)| (survey, value / (1 + num_claims(survey)) as f64))
+        .fold(None, |maybe_best: Option<(&Survey, f64)>, (survey, shared_value)| match maybe_best {
+            Some((_, best_value)) if best_value >= shared_value => maybe_best,
+            _ => Some((survey, shared_value)),
+        })
+        .map(|(survey, _)| survey.clone())
+}
+
+#[derive(Debug, Default)]
+struct MiningSiteState {
+    observed_yields: HashMap<WaypointSymbol, YieldStats>,
+    assignments: HashMap<ShipSymbol, (ShipRole, WaypointSymbol)>,
+    survey_claims: HashMap<ShipSymbol, SurveySignature>,
+}
+
+/// Keeps track of the mining sites of the ships, the yields we observed at each site and the surveys the drones are using.
+#[derive(Clone, Debug)]
+pub struct MiningSiteManager {
+    strategy_store: StrategyStore,
+    state: Arc<Mutex<MiningSiteState>>,
+}
+
+impl Default for MiningSiteManager {
+    fn default() -> Self {
+        Self::new(StrategyStore::default())
+    }
+}
+
+impl MiningSiteManager {
+    pub fn new(strategy_store: StrategyStore) -> Self {
+        Self {
+            strategy_store,
+            state: Arc::new(Mutex::new(MiningSiteState::default())),
+        }
+    }
+
+    pub fn max_drones_per_site(&self) -> usize {
+        self.strategy_store
+            .get_strategy()
+            .mining
+            .max_drones_per_site as usize
+    }
+
+    pub fn haul_cost_per_unit_and_distance(&self) -> f64 {
+        self.strategy_store
+            .get_strategy()
+            .mining
+            .haul_cost_per_unit_and_distance
+    }
+
+    pub fn register_assignment(&self, ship_symbol: ShipSymbol, role: ShipRole, mining_waypoint: WaypointSymbol) -> anyhow::Result<()> {
+        self.state
+            .lock()
+            .map_err(|_| anyhow!("Lock poisoned"))?
+            .assignments
+            .insert(ship_symbol, (role, mining_waypoint));
+
+        Ok(())
+    }
+
+    pub fn get_assigned_site(&self, ship_symbol: &ShipSymbol) -> Option<WaypointSymbol> {
+        self.state
+            .lock()
+            .ok()?
+            .assignments
+            .get(ship_symbol)
+            .map(|(_, site)| site.clone())
+    }
+
+    pub fn has_drones_at_site(&self, mining_waypoint: &WaypointSymbol) -> bool {
+        self.state
+            .lock()
+            .map(|state| {
+                state
+                    .assignments
+                    .values()
+                    .any(|(role, site)| role == &ShipRole::Miner && site == mining_waypoint)
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn record_extraction(&self, mining_waypoint: &WaypointSymbol, extraction: &Extraction) -> anyhow::Result<()> {
+        self.state
+            .lock()
+            .map_err(|_| anyhow!("Lock poisoned"))?
+            .observed_yields
+            .entry(mining_waypoint.clone())
+            .or_default()
+            .add_extraction(extraction);
+
+        Ok(())
+    }
+
+    /// Picks the survey for the next extraction of a drone from the ranked surveys of its site.
+    ///
+    /// The drones of a site share the best surveys: each drone that is already using a survey halves its value for the others,
+    /// since they deplete its deposits together. This spreads the drones across the top surveys instead of exhausting one after another.
+    pub fn claim_best_survey(&self, ship_symbol: &ShipSymbol, ranked_surveys: &[(Survey, f64)]) -> anyhow::Result<Option<Survey>> {
+        let mut state = self.state.lock().map_err(|_| anyhow!("Lock poisoned"))?;
+        state.survey_claims.remove(ship_symbol);
+
+        let maybe_best_survey = pick_shared_survey(ranked_surveys, &state.survey_claims);
+
+        if let Some(survey) = maybe_best_survey.as_ref() {
+            state
+                .survey_claims
+                .insert(ship_symbol.clone(), survey.signature.clone());
+        }
+
+        Ok(maybe_best_survey)
+    }
+
+    /// Called once a survey has been exhausted or expired - the drones pick a new one for their next extraction.
+    pub fn release_survey(&self, survey_signature: &SurveySignature) -> anyhow::Result<()> {
+        self.state
+            .lock()
+            .map_err(|_| anyhow!("Lock poisoned"))?
+            .survey_claims
+            .retain(|_, signature| signature != survey_signature);
+
+        Ok(())
+    }
+
+    /// Replaces the observed yields with the ones from the survey_usage_log, e.g. after a restart.
+    pub fn seed_observed_yields(&self, yield_report: &ExtractionYieldReport) -> anyhow::Result<()> {
+        self.state
+            .lock()
+            .map_err(|_| anyhow!("Lock poisoned"))?
+            .observed_yields = yield_report
+            .by_asteroid
+            .iter()
+            .map(|(wp, stats)| (wp.clone(), stats.clone()))
+            .collect();
+
+        Ok(())
+    }
+
+    pub fn rank_mining_sites(&self, waypoints: &[Waypoint], delivery_locations: &HashMap<TradeGoodSymbol, RawDeliveryRoute>) -> Vec<RankedMiningSite> {
+        let observed_yields = self
+            .state
+            .lock()
+            .map(|state| state.observed_yields.clone())
+            .unwrap_or_default();
+
+        rank_mining_sites(waypoints, delivery_locations, &observed_yields)
+    }
+
+    /// Moves the ship away from its current site, e.g. because the asteroid has reached its critical limit.
+    /// Returns None (and keeps the current assignment) if there is no other site to go to.
+    pub fn relocate_ship(
+        &self,
+        ship_symbol: &ShipSymbol,
+        role: ShipRole,
+        maybe_current_site: Option<WaypointSymbol>,
+        ranked_sites: &[RankedMiningSite],
+    ) -> anyhow::Result<Option<WaypointSymbol>> {
+        let max_drones_per_site = self.max_drones_per_site();
+        let mut state = self.state.lock().map_err(|_| anyhow!("Lock poisoned"))?;
+
+        let candidates = ranked_sites
+            .iter()
+            .map(|site| site.waypoint_symbol.clone())
+            .filter(|site| Some(site) != maybe_current_site.as_ref())
+            .collect_vec();
+
+        let mut other_assignments = state.assignments.clone();
+        other_assignments.remove(ship_symbol);
+
+        let maybe_new_site = pick_mining_site(&role, &candidates, &other_assignments, max_drones_per_site);
+
+        if let Some(new_site) = maybe_new_site.clone() {
+            debug!("Relocating {} {} from {:?} to {}", role_label(&role), ship_symbol, maybe_current_site, new_site);
+            state
+                .assignments
+                .insert(ship_symbol.clone(), (role, new_site));
+            state.survey_claims.remove(ship_symbol);
+        }
+
+        Ok(maybe_new_site)
+    }
+}
+
+fn role_label(role: &ShipRole) -> &'static str {
+    match role {
+        ShipRole::Miner => "miner",
+        ShipRole::MiningSurveyor => "surveyor",
+        ShipRole::MiningHauler => "hauler",
+        _ => "ship",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use st_domain::{
+        ExtractionYield, MarketTradeGood, RawMaterialSource, RawMaterialSourceType, SupplyLevel, SurveySize, SystemSymbol, TradeGoodType, WaypointModifier,
+        WaypointModifierSymbol, WaypointTrait,
+    };
+
+    fn waypoint(symbol: &str, r#type: WaypointType, x: i64, y: i64, traits: &[WaypointTraitSymbol]) -> Waypoint {
+        Waypoint {
+            symbol: WaypointSymbol(symbol.to_string()),
+            r#type,
+            system_symbol: SystemSymbol("X1-FOO".to_string()),
+            x,
+            y,
+            orbitals: vec![],
+            orbits: None,
+            faction: None,
+            traits: traits
+                .iter()
+                .map(|t| WaypointTrait {
+                    symbol: t.clone(),
+                    name: t.to_string(),
+                    description: t.to_string(),
+                })
+                .collect(),
+            modifiers: vec![],
+            chart: None,
+            is_under_construction: false,
+        }
+    }
+
+    fn delivery_route(trade_good: TradeGoodSymbol, delivery_location: &str) -> (TradeGoodSymbol, RawDeliveryRoute) {
+        let market_trade_good = MarketTradeGood {
+            symbol: trade_good.clone(),
+            trade_good_type: TradeGoodType::Import,
+            trade_volume: 60,
+            supply: SupplyLevel::Scarce,
+            activity: None,
+            purchase_price: 100,
+            sell_price: 90,
+        };
+
+        (
+            trade_good.clone(),
+            RawDeliveryRoute {
+                source: RawMaterialSource {
+                    trade_good,
+                    source_type: RawMaterialSourceType::Mining,
+                    source_waypoint: WaypointSymbol("X1-FOO-EA".to_string()),
+                },
+                delivery_location: WaypointSymbol(delivery_location.to_string()),
+                distance: 0,
+                delivery_market_entry: market_trade_good.clone(),
+                export_entry: market_trade_good,
+            },
+        )
+    }
+
+    fn test_system() -> (Vec<Waypoint>, HashMap<TradeGoodSymbol, RawDeliveryRoute>) {
+        let waypoints = vec![
+            waypoint("X1-FOO-MARKET", WaypointType::PLANET, 0, 0, &[WaypointTraitSymbol::MARKETPLACE]),
+            waypoint(
+                "X1-FOO-EA",
+                WaypointType::ENGINEERED_ASTEROID,
+                20,
+                0,
+                &[WaypointTraitSymbol::COMMON_METAL_DEPOSITS],
+            ),
+            waypoint("X1-FOO-NEAR", WaypointType::ASTEROID, 100, 0, &[WaypointTraitSymbol::COMMON_METAL_DEPOSITS]),
+            waypoint("X1-FOO-FAR", WaypointType::ASTEROID, 300, 0, &[WaypointTraitSymbol::COMMON_METAL_DEPOSITS]),
+            waypoint("X1-FOO-RARE", WaypointType::ASTEROID, 10, 0, &[WaypointTraitSymbol::RARE_METAL_DEPOSITS]),
+        ];
+
+        let delivery_locations = HashMap::from([
+            delivery_route(TradeGoodSymbol::IRON_ORE, "X1-FOO-MARKET"),
+            delivery_route(TradeGoodSymbol::COPPER_ORE, "X1-FOO-MARKET"),
+        ]);
+
+        (waypoints, delivery_locations)
+    }
+
+    fn symbols(ranked: &[RankedMiningSite]) -> Vec<String> {
+        ranked.iter().map(|s| s.waypoint_symbol.0.clone()).collect()
+    }
+
+    #[test]
+    fn ranks_sites_by_distance_and_observed_yield() {
+        let (mut waypoints, delivery_locations) = test_system();
+
+        // sites without deposits we can deliver (X1-FOO-RARE) are not considered
+        let ranked = rank_mining_sites(&waypoints, &delivery_locations, &HashMap::new());
+        assert_eq!(symbols(&ranked), vec!["X1-FOO-EA", "X1-FOO-NEAR", "X1-FOO-FAR"]);
+        assert_eq!(ranked[0].deliverable_goods, vec![TradeGoodSymbol::IRON_ORE, TradeGoodSymbol::COPPER_ORE]);
+
+        // a high observed yield makes up for the longer distance - 10 units of iron ore at 90c each
+        let mut observed_yield = YieldStats::default();
+        for _ in 0..4 {
+            observed_yield.add_extraction(&Extraction {
+                ship_symbol: ShipSymbol("MINER-1".to_string()),
+                extraction_yield: ExtractionYield {
+                    symbol: TradeGoodSymbol::IRON_ORE,
+                    units: 10,
+                },
+            });
+        }
+        let observed_yields = HashMap::from([(WaypointSymbol("X1-FOO-FAR".to_string()), observed_yield)]);
+
+        let ranked = rank_mining_sites(&waypoints, &delivery_locations, &observed_yields);
+        assert_eq!(symbols(&ranked), vec!["X1-FOO-FAR", "X1-FOO-EA", "X1-FOO-NEAR"]);
+        assert_eq!(ranked[0].expected_value_per_extraction, 900.0);
+
+        // unstable sites are skipped
+        waypoints[1].modifiers.push(WaypointModifier {
+            symbol: WaypointModifierSymbol::CRITICAL_LIMIT,
+            name: "Critical Limit".to_string(),
+            description: "".to_string(),
+        });
+        let ranked = rank_mining_sites(&waypoints, &delivery_locations, &observed_yields);
+        assert_eq!(symbols(&ranked), vec!["X1-FOO-FAR", "X1-FOO-NEAR"]);
+    }
+
+    #[test]
+    fn spreads_drones_across_sites_and_support_ships_follow_them() {
+        let sites = vec![WaypointSymbol("X1-FOO-A".to_string()), WaypointSymbol("X1-FOO-B".to_string())];
+        let mut assignments = HashMap::new();
+
+        let mut assign = |ship: &str, role: ShipRole| {
+            let site = pick_mining_site(&role, &sites, &assignments, 2).unwrap();
+            assignments.insert(ShipSymbol(ship.to_string()), (role, site.clone()));
+            site.0
+        };
+
+        assert_eq!(assign("MINER-1", ShipRole::Miner), "X1-FOO-A");
+        assert_eq!(assign("SURVEYOR-1", ShipRole::MiningSurveyor), "X1-FOO-A");
+        assert_eq!(assign("MINER-2", ShipRole::Miner), "X1-FOO-A");
+        assert_eq!(assign("MINER-3", ShipRole::Miner), "X1-FOO-B");
+        assert_eq!(assign("SURVEYOR-2", ShipRole::MiningSurveyor), "X1-FOO-B");
+        assert_eq!(assign("HAULER-1", ShipRole::MiningHauler), "X1-FOO-A");
+        assert_eq!(assign("HAULER-2", ShipRole::MiningHauler), "X1-FOO-B");
+        assert_eq!(assign("MINER-4", ShipRole::Miner), "X1-FOO-B");
+        // all sites are full - the next drone joins the site with the fewest drones
+        assert_eq!(assign("MINER-5", ShipRole::Miner), "X1-FOO-A");
+    }
+
+    #[test]
+    fn drones_share_the_best_surveys() {
+        let survey = |signature: &str| Survey {
+            signature: SurveySignature(signature.to_string()),
+            waypoint_symbol: WaypointSymbol("X1-FOO-EA".to_string()),
+            deposits: vec![],
+            expiration: Default::default(),
+            size: SurveySize::LARGE,
+        };
+        let ranked = vec![(survey("BEST"), 600.0), (survey("GOOD"), 400.0), (survey("MEH"), 100.0)];
+
+        let manager = MiningSiteManager::default();
+        let claim = |ship: &str| {
+            manager
+                .claim_best_survey(&ShipSymbol(ship.to_string()), &ranked)
+                .unwrap()
+                .unwrap()
+                .signature
+                .0
+        };
+
+        assert_eq!(claim("MINER-1"), "BEST");
+        assert_eq!(claim("MINER-2"), "GOOD");
+        // 600 / 2 is still better than 400 / 2 and 100
+        assert_eq!(claim("MINER-3"), "BEST");
+        // re-claiming doesn't count the own claim
+        assert_eq!(claim("MINER-1"), "BEST");
+
+        manager
+            .release_survey(&SurveySignature("BEST".to_string()))
+            .unwrap();
+        assert_eq!(claim("MINER-3"), "BEST");
+        assert_eq!(claim("MINER-1"), "BEST");
+    }
+
+    #[test]
+    fn relocates_ships_away_from_unstable_sites() {
+        let (mut waypoints, delivery_locations) = test_system();
+        let manager = MiningSiteManager::default();
+        let ea = WaypointSymbol("X1-FOO-EA".to_string());
+
+        for (ship, role) in [
+            ("MINER-1", ShipRole::Miner),
+            ("MINER-2", ShipRole::Miner),
+            ("SURVEYOR-1", ShipRole::MiningSurveyor),
+        ] {
+            manager
+                .register_assignment(ShipSymbol(ship.to_string()), role, ea.clone())
+                .unwrap();
+        }
+
+        waypoints[1].modifiers.push(WaypointModifier {
+            symbol: WaypointModifierSymbol::CRITICAL_LIMIT,
+            name: "Critical Limit".to_string(),
+            description: "".to_string(),
+        });
+        let ranked = manager.rank_mining_sites(&waypoints, &delivery_locations);
+
+        // the surveyor stays until drones have moved to another site
+        let surveyor = ShipSymbol("SURVEYOR-1".to_string());
+        assert_eq!(
+            manager
+                .relocate_ship(&surveyor, ShipRole::MiningSurveyor, Some(ea.clone()), &ranked)
+                .unwrap(),
+            None
+        );
+
+        for miner in ["MINER-1", "MINER-2"] {
+            let new_site = manager
+                .relocate_ship(&ShipSymbol(miner.to_string()), ShipRole::Miner, Some(ea.clone()), &ranked)
+                .unwrap();
+            assert_eq!(new_site, Some(WaypointSymbol("X1-FOO-NEAR".to_string())));
+        }
+        assert!(manager.has_drones_at_site(&ea).not());
+
+        assert_eq!(
+            manager
+                .relocate_ship(&surveyor, ShipRole::MiningSurveyor, Some(ea.clone()), &ranked)
+                .unwrap(),
+            Some(WaypointSymbol("X1-FOO-NEAR".to_string()))
+        );
+        assert_eq!(manager.get_assigned_site(&surveyor), Some(WaypointSymbol("X1-FOO-NEAR".to_string())));
+    }
+}
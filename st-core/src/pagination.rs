@@ -4,7 +4,7 @@ use std::future::Future;
 use anyhow::Result;
 use chrono::{DateTime, Local, Utc};
 use futures::future;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use st_domain::Meta;
 use tracing::log::trace;
 use tracing::{event, trace_span, Instrument, Level};
@@ -15,7 +15,7 @@ pub struct PaginationInput {
     pub limit: u32,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct PaginatedResponse<T> {
     pub data: Vec<T>,
     pub meta: Meta,
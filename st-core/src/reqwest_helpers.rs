@@ -1,3 +1,4 @@
+use crate::cassette::CassetteMiddleware;
 use axum::http::Extensions;
 use governor::{DefaultDirectRateLimiter, Quota, RateLimiter};
 use log::{debug, error};
@@ -10,6 +11,17 @@ use std::time::{Duration, Instant};
 use tokio::sync::mpsc::Sender;
 
 pub fn create_client(maybe_bearer_token: Option<String>, reset_tx: Option<Sender<ResetSignal>>) -> ClientWithMiddleware {
+    create_client_builder(maybe_bearer_token, reset_tx).build()
+}
+
+/// The production client that additionally records all requests and responses into a cassette (see `cassette.rs`).
+pub fn create_recording_client(maybe_bearer_token: Option<String>, recorder: CassetteMiddleware) -> ClientWithMiddleware {
+    create_client_builder(maybe_bearer_token, None)
+        .with(recorder)
+        .build()
+}
+
+fn create_client_builder(maybe_bearer_token: Option<String>, reset_tx: Option<Sender<ResetSignal>>) -> ClientBuilder {
     let reqwest_client = Client::builder().build().unwrap();
 
     let limiter = RateLimiter::direct(Quota::per_second(std::num::NonZeroU32::new(2u32).unwrap()));
@@ -33,10 +45,8 @@ pub fn create_client(maybe_bearer_token: Option<String>, reset_tx: Option<Sender
     }
 
     match maybe_bearer_token {
-        None => client_builder.build(),
-        Some(token) => client_builder
-            .with(AuthenticatedHeaderMiddleware::new(token))
-            .build(),
+        None => client_builder,
+        Some(token) => client_builder.with(AuthenticatedHeaderMiddleware::new(token)),
     }
 }
 #[derive(Debug, Clone)]
@@ -1,3 +1,4 @@
+use crate::behavior_tree::behavior_tree::CooldownState;
 use crate::st_client::StClientTrait;
 use anyhow::*;
 use chrono::{DateTime, Utc};
@@ -9,9 +10,10 @@ use st_domain::{
     PurchaseTradeGoodResponse, RefuelShipResponse, SellTradeGoodResponse, Ship, ShipType, Shipyard, SiphonResourcesResponse, SupplyConstructionSiteResponse,
     Survey, TradeGoodSymbol, TravelAction, WaypointSymbol,
 };
-use std::collections::{HashSet, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::ops::{Deref, DerefMut, Not};
 use std::sync::Arc;
+use tokio::time::Instant;
 use tracing::debug;
 
 #[derive(Clone, Debug, Serialize)]
@@ -28,6 +30,9 @@ pub struct ShipOperations {
     pub maybe_mining_waypoint: Option<WaypointSymbol>,
     pub maybe_siphoning_waypoint: Option<WaypointSymbol>,
     pub maybe_contract: Option<Contract>,
+    /// last successes of the `Cooldown` nodes, by node hash - kept across the runs of the behavior trees
+    #[serde(skip_serializing)]
+    pub cooldowns: HashMap<u64, Instant>,
 }
 
 impl PartialEq for ShipOperations {
@@ -74,6 +79,7 @@ impl ShipOperations {
             maybe_mining_waypoint: None,
             maybe_siphoning_waypoint: None,
             maybe_contract: None,
+            cooldowns: HashMap::new(),
         }
     }
 
@@ -376,6 +382,16 @@ impl ShipOperations {
     }
 }
 
+impl CooldownState for ShipOperations {
+    fn last_success(&self, cooldown_hash: u64) -> Option<Instant> {
+        self.cooldowns.get(&cooldown_hash).copied()
+    }
+
+    fn mark_success(&mut self, cooldown_hash: u64, at: Instant) {
+        self.cooldowns.insert(cooldown_hash, at);
+    }
+}
+
 impl Deref for ShipOperations {
     type Target = Ship;
 
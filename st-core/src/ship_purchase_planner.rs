@@ -0,0 +1,381 @@
+use crate::{calculate_fuel_consumption, calculate_time};
+use itertools::Itertools;
+use st_domain::budgeting::credits::Credits;
+use st_domain::strategy::ShipPurchaseStrategy;
+use st_domain::{FlightMode, ShipPriceInfo, ShipSymbol, ShipType, WaypointSymbol};
+use std::collections::HashMap;
+
+/// The engine speed we assume for ships whose shipyard listing we haven't seen yet.
+const DEFAULT_ENGINE_SPEED: u32 = 10;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShipyardOffer {
+    pub shipyard: WaypointSymbol,
+    pub price: u32,
+    /// engine speed of the ship we'd buy - it has to travel to its fleet after the purchase
+    pub engine_speed: u32,
+    pub is_price_guessed: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PurchaserCandidate {
+    pub ship_symbol: ShipSymbol,
+    pub location: WaypointSymbol,
+    pub engine_speed: u32,
+    /// stationary probes keep observing their waypoint - they only purchase at the shipyard they are parked at
+    pub is_stationary: bool,
+    /// what we lose per hour while this ship travels to the shipyard instead of doing its job
+    pub cost_per_hour: Credits,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShipPurchasePlan {
+    pub ship_type: ShipType,
+    pub shipyard: WaypointSymbol,
+    pub purchaser: ShipSymbol,
+    /// the price of the ticket, including the markup
+    pub price: Credits,
+    pub purchaser_travel_secs: u32,
+    pub delivery_travel_secs: u32,
+    /// fuel and lost time of the purchaser on its way to the shipyard - shared by all purchases of a batch
+    pub purchaser_costs: Credits,
+    /// fuel of the new ship on its way to the fleet
+    pub delivery_costs: Credits,
+    /// what the new ship could have earned until it starts working for its fleet
+    pub waiting_costs: Credits,
+}
+
+impl ShipPurchasePlan {
+    pub fn secs_until_working(&self) -> u32 {
+        self.purchaser_travel_secs + self.delivery_travel_secs
+    }
+
+    pub fn total_costs(&self) -> Credits {
+        self.price + self.purchaser_costs + self.delivery_costs + self.waiting_costs
+    }
+}
+
+/// All shipyards of the system selling the ship type - with the latest known price or the guessed price if we haven't seen one yet.
+pub fn collect_shipyard_offers(ship_prices: &ShipPriceInfo, ship_type: &ShipType) -> Vec<ShipyardOffer> {
+    let known_offers = ship_prices
+        .price_infos
+        .iter()
+        .flat_map(|(wps, shipyard_ships)| {
+            shipyard_ships
+                .iter()
+                .filter(|shipyard_ship| &shipyard_ship.r#type == ship_type)
+                .map(|shipyard_ship| ShipyardOffer {
+                    shipyard: wps.clone(),
+                    price: shipyard_ship.purchase_price,
+                    engine_speed: shipyard_ship.engine.speed.max(1) as u32,
+                    is_price_guessed: false,
+                })
+        })
+        .collect_vec();
+
+    let guessed_offers = ship_prices
+        .compute_ship_type_purchase_location_map()
+        .get(ship_type)
+        .cloned()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|wps| known_offers.iter().all(|offer| &offer.shipyard != wps))
+        .filter_map(|wps| {
+            ship_prices
+                .guess_price_for_ship(ship_type)
+                .map(|price| ShipyardOffer {
+                    shipyard: wps,
+                    price,
+                    engine_speed: DEFAULT_ENGINE_SPEED,
+                    is_price_guessed: true,
+                })
+        })
+        .collect_vec();
+
+    known_offers
+        .into_iter()
+        .chain(guessed_offers)
+        .unique_by(|offer| offer.shipyard.clone())
+        .collect_vec()
+}
+
+fn travel(coordinates: &HashMap<WaypointSymbol, (i64, i64)>, from: &WaypointSymbol, to: &WaypointSymbol, engine_speed: u32) -> (u32, u32) {
+    if from == to {
+        return (0, 0);
+    }
+    match (coordinates.get(from), coordinates.get(to)) {
+        (Some((from_x, from_y)), Some((to_x, to_y))) => {
+            let distance = st_domain::distance_to(*from_x, *from_y, *to_x, *to_y);
+            (
+                calculate_time(&FlightMode::Cruise, distance, engine_speed.max(1)),
+                calculate_fuel_consumption(&FlightMode::Cruise, distance),
+            )
+        }
+        _ => (0, 0),
+    }
+}
+
+fn credits(amount: f64) -> Credits {
+    (amount.round() as i64).into()
+}
+
+fn plan(
+    ship_type: ShipType,
+    offer: &ShipyardOffer,
+    purchaser: &PurchaserCandidate,
+    maybe_fleet_location: Option<&WaypointSymbol>,
+    coordinates: &HashMap<WaypointSymbol, (i64, i64)>,
+    strategy: &ShipPurchaseStrategy,
+) -> Option<ShipPurchasePlan> {
+    if purchaser.is_stationary && purchaser.location != offer.shipyard {
+        return None;
+    }
+
+    let (purchaser_travel_secs, purchaser_fuel) = travel(coordinates, &purchaser.location, &offer.shipyard, purchaser.engine_speed);
+    let (delivery_travel_secs, delivery_fuel) = maybe_fleet_location
+        .map(|fleet_location| travel(coordinates, &offer.shipyard, fleet_location, offer.engine_speed))
+        .unwrap_or_default();
+
+    let hours = |secs: u32| secs as f64 / 3600.0;
+
+    Some(ShipPurchasePlan {
+        ship_type,
+        shipyard: offer.shipyard.clone(),
+        purchaser: purchaser.ship_symbol.clone(),
+        price: credits(offer.price as f64 * (1.0 + strategy.price_markup)),
+        purchaser_travel_secs,
+        delivery_travel_secs,
+        purchaser_costs: credits(purchaser_fuel as f64 * strategy.fuel_cost_per_unit + hours(purchaser_travel_secs) * purchaser.cost_per_hour.0 as f64),
+        delivery_costs: credits(delivery_fuel as f64 * strategy.fuel_cost_per_unit),
+        waiting_costs: credits(hours(purchaser_travel_secs + delivery_travel_secs) * strategy.waiting_cost_per_hour.0 as f64),
+    })
+}
+
+/// Scores every (shipyard, purchaser) pair by the price, the travel costs of both ships and the time until the new ship works for its fleet.
+/// On a tie, the faster plan wins.
+pub fn plan_ship_purchase(
+    ship_type: ShipType,
+    offers: &[ShipyardOffer],
+    purchasers: &[PurchaserCandidate],
+    maybe_fleet_location: Option<&WaypointSymbol>,
+    coordinates: &HashMap<WaypointSymbol, (i64, i64)>,
+    strategy: &ShipPurchaseStrategy,
+) -> Option<ShipPurchasePlan> {
+    offers
+        .iter()
+        .cartesian_product(purchasers.iter())
+        .filter_map(|(offer, purchaser)| plan(ship_type, offer, purchaser, maybe_fleet_location, coordinates, strategy))
+        .min_by_key(|plan| (plan.total_costs(), plan.secs_until_working(), plan.shipyard.clone(), plan.purchaser.clone()))
+}
+
+/// Plans the first demand and adds the following ones to the same visit of the purchaser at the shipyard,
+/// as long as that's not more expensive than purchasing them on their own - the trip of the purchaser is already paid for.
+///
+/// `demands` are the ship types in order of priority and the location of the fleet they are meant for.
+/// We stop at the first demand that doesn't fit, so that the order of the demands is kept.
+pub fn plan_ship_purchase_batch(
+    demands: &[(ShipType, Option<WaypointSymbol>)],
+    ship_prices: &ShipPriceInfo,
+    purchasers: &[PurchaserCandidate],
+    coordinates: &HashMap<WaypointSymbol, (i64, i64)>,
+    strategy: &ShipPurchaseStrategy,
+) -> Vec<ShipPurchasePlan> {
+    let Some((first_ship_type, maybe_first_fleet_location)) = demands.first() else {
+        return vec![];
+    };
+    let Some(first_plan) = plan_ship_purchase(
+        *first_ship_type,
+        &collect_shipyard_offers(ship_prices, first_ship_type),
+        purchasers,
+        maybe_first_fleet_location.as_ref(),
+        coordinates,
+        strategy,
+    ) else {
+        return vec![];
+    };
+
+    let Some(batch_purchaser) = purchasers
+        .iter()
+        .find(|p| p.ship_symbol == first_plan.purchaser)
+    else {
+        return vec![first_plan];
+    };
+
+    let mut batch = vec![first_plan.clone()];
+    for (ship_type, maybe_fleet_location) in demands
+        .iter()
+        .skip(1)
+        .take(strategy.max_batch_size.saturating_sub(1) as usize)
+    {
+        let offers = collect_shipyard_offers(ship_prices, ship_type);
+        let Some(best_plan) = plan_ship_purchase(*ship_type, &offers, purchasers, maybe_fleet_location.as_ref(), coordinates, strategy) else {
+            break;
+        };
+        let Some(batched_plan) = offers
+            .iter()
+            .find(|offer| offer.shipyard == first_plan.shipyard)
+            .and_then(|offer| plan(*ship_type, offer, batch_purchaser, maybe_fleet_location.as_ref(), coordinates, strategy))
+            .map(|plan| ShipPurchasePlan {
+                purchaser_costs: 0.into(),
+                ..plan
+            })
+        else {
+            break;
+        };
+
+        if batched_plan.total_costs() > best_plan.total_costs() {
+            break;
+        }
+        batch.push(batched_plan);
+    }
+
+    batch
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::universe_server::universe_server::InMemoryUniverse;
+
+    #[test]
+    fn prefers_the_shipyard_close_to_the_fleet_and_batches_purchases_there() {
+        let wps = |name: &str| WaypointSymbol(format!("X1-FOO-{name}"));
+        let coordinates = HashMap::from([
+            (wps("CHEAP"), (-5000, 0)),
+            (wps("NEAR"), (10, 0)),
+            (wps("FLEET"), (0, 0)),
+            (wps("HQ"), (0, 10)),
+        ]);
+        let offers = vec![
+            ShipyardOffer {
+                shipyard: wps("CHEAP"),
+                price: 270_000,
+                engine_speed: 10,
+                is_price_guessed: false,
+            },
+            ShipyardOffer {
+                shipyard: wps("NEAR"),
+                price: 277_000,
+                engine_speed: 10,
+                is_price_guessed: false,
+            },
+        ];
+        let frigate = PurchaserCandidate {
+            ship_symbol: ShipSymbol("FLWI-1".to_string()),
+            location: wps("HQ"),
+            engine_speed: 30,
+            is_stationary: false,
+            cost_per_hour: 10_000.into(),
+        };
+        let probe_at_cheap_shipyard = PurchaserCandidate {
+            ship_symbol: ShipSymbol("FLWI-2".to_string()),
+            location: wps("CHEAP"),
+            engine_speed: 3,
+            is_stationary: true,
+            cost_per_hour: 0.into(),
+        };
+        let strategy = ShipPurchaseStrategy::default();
+
+        // the cheap shipyard is far away from the fleet - the new ship would be traveling for hours
+        let plan = plan_ship_purchase(
+            ShipType::SHIP_LIGHT_HAULER,
+            &offers,
+            &[frigate.clone(), probe_at_cheap_shipyard.clone()],
+            Some(&wps("FLEET")),
+            &coordinates,
+            &strategy,
+        )
+        .unwrap();
+        assert_eq!(plan.shipyard, wps("NEAR"));
+        assert_eq!(plan.purchaser, frigate.ship_symbol);
+        assert_eq!(plan.price, 282_540.into());
+        assert!(plan.purchaser_travel_secs > 0);
+
+        // without a fleet location the new ship doesn't need to travel and the probe buys without leaving its post
+        let plan = plan_ship_purchase(
+            ShipType::SHIP_LIGHT_HAULER,
+            &offers,
+            &[frigate.clone(), probe_at_cheap_shipyard.clone()],
+            None,
+            &coordinates,
+            &strategy,
+        )
+        .unwrap();
+        assert_eq!(plan.shipyard, wps("CHEAP"));
+        assert_eq!(plan.purchaser, probe_at_cheap_shipyard.ship_symbol);
+        assert_eq!(plan.secs_until_working(), 0);
+        assert_eq!(plan.total_costs(), 275_400.into());
+
+        // a stationary probe doesn't leave its post
+        assert_eq!(
+            plan_ship_purchase(
+                ShipType::SHIP_LIGHT_HAULER,
+                &offers[1..],
+                &[probe_at_cheap_shipyard],
+                None,
+                &coordinates,
+                &strategy
+            ),
+            None
+        );
+
+        let json_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .join("resources")
+            .join("universe_snapshot.json");
+        let universe = InMemoryUniverse::from_snapshot(json_path).expect("InMemoryUniverse::from_snapshot");
+        let template = universe
+            .shipyards
+            .values()
+            .flat_map(|shipyard| shipyard.ships.clone().unwrap_or_default())
+            .next()
+            .unwrap();
+        let shipyard_ship = |ship_type: ShipType, price: u32| {
+            let mut shipyard_ship = template.clone();
+            shipyard_ship.engine.speed = 10;
+            shipyard_ship.r#type = ship_type;
+            shipyard_ship.purchase_price = price;
+            shipyard_ship
+        };
+        let ship_prices = ShipPriceInfo {
+            price_infos: vec![
+                (
+                    wps("NEAR"),
+                    vec![
+                        shipyard_ship(ShipType::SHIP_LIGHT_HAULER, 277_000),
+                        shipyard_ship(ShipType::SHIP_MINING_DRONE, 42_000),
+                    ],
+                ),
+                (wps("CHEAP"), vec![shipyard_ship(ShipType::SHIP_PROBE, 20_000)]),
+            ],
+            latest_shipyard_infos: vec![],
+            ship_price_guesses: Default::default(),
+        };
+
+        let batch = plan_ship_purchase_batch(
+            &[
+                (ShipType::SHIP_LIGHT_HAULER, Some(wps("FLEET"))),
+                (ShipType::SHIP_MINING_DRONE, Some(wps("FLEET"))),
+                // only sold far away - that's a separate trip
+                (ShipType::SHIP_PROBE, Some(wps("FLEET"))),
+                (ShipType::SHIP_LIGHT_HAULER, Some(wps("FLEET"))),
+            ],
+            &ship_prices,
+            &[frigate.clone()],
+            &coordinates,
+            &strategy,
+        );
+        assert_eq!(
+            batch
+                .iter()
+                .map(|plan| (plan.ship_type, plan.shipyard.clone(), plan.purchaser.clone()))
+                .collect_vec(),
+            vec![
+                (ShipType::SHIP_LIGHT_HAULER, wps("NEAR"), frigate.ship_symbol.clone()),
+                (ShipType::SHIP_MINING_DRONE, wps("NEAR"), frigate.ship_symbol.clone()),
+            ]
+        );
+        assert_eq!(batch[1].purchaser_costs, 0.into());
+    }
+}
@@ -0,0 +1,229 @@
+use crate::{calculate_fuel_consumption, calculate_time};
+use itertools::Itertools;
+use st_domain::budgeting::credits::Credits;
+use st_domain::ship_type_evaluation::{ShipRole, ShipTypeEvaluation};
+use st_domain::trading::{find_trading_opportunities_sorted_by_profit_per_distance_unit, to_trade_goods_with_locations};
+use st_domain::{FlightMode, MarketEntry, ShipMountSymbol, ShipPriceInfo, ShipyardShip, TradeGoodSymbol, TradingOpportunity, Waypoint, WaypointSymbol};
+use std::collections::{HashMap, HashSet};
+
+/// Docking, trading and undocking at both ends of a trade route.
+const SECS_AT_MARKETS: u32 = 60;
+
+/// Buying more than a few times the trade volume of a market in one go drives up the price too much.
+const MAX_TRADE_VOLUMES_PER_TRIP: u32 = 3;
+
+/// The haulers of a fleet spread over the best routes - a ship type is judged by its average over these.
+const NUM_TRADE_ROUTES: usize = 5;
+
+/// Typical cooldown after an extraction or a siphoning. Together with units per extraction matching the strength of the mounts,
+/// this is a rough model - it's only meant to rank the ship types of a role against each other.
+const EXTRACTION_COOLDOWN_SECS: f64 = 80.0;
+
+const MINED_GOODS: [TradeGoodSymbol; 8] = [
+    TradeGoodSymbol::IRON_ORE,
+    TradeGoodSymbol::COPPER_ORE,
+    TradeGoodSymbol::ALUMINUM_ORE,
+    TradeGoodSymbol::QUARTZ_SAND,
+    TradeGoodSymbol::SILICON_CRYSTALS,
+    TradeGoodSymbol::ICE_WATER,
+    TradeGoodSymbol::AMMONIA_ICE,
+    TradeGoodSymbol::PRECIOUS_STONES,
+];
+
+const SIPHONED_GOODS: [TradeGoodSymbol; 3] = [
+    TradeGoodSymbol::HYDROCARBON,
+    TradeGoodSymbol::LIQUID_HYDROGEN,
+    TradeGoodSymbol::LIQUID_NITROGEN,
+];
+
+/// Simulates what each ship type of the known shipyards earns per hour in its role:
+/// haulers run round trips on the current trade opportunities of the system, extractors sell what their mounts extract.
+///
+/// The result is sorted by role and the best return per credit first.
+pub fn evaluate_ship_types(
+    ship_prices: &ShipPriceInfo,
+    latest_market_entries: &[MarketEntry],
+    waypoints: &[Waypoint],
+    fuel_cost_per_unit: f64,
+) -> Vec<ShipTypeEvaluation> {
+    let waypoint_map: HashMap<WaypointSymbol, &Waypoint> = waypoints.iter().map(|wp| (wp.symbol.clone(), wp)).collect();
+
+    // the search expects every market to be a known waypoint
+    let market_data = to_trade_goods_with_locations(latest_market_entries)
+        .into_iter()
+        .filter(|(wps, _)| waypoint_map.contains_key(wps))
+        .collect_vec();
+    let trading_opportunities = find_trading_opportunities_sorted_by_profit_per_distance_unit(&market_data, &waypoint_map, &HashSet::new());
+
+    let best_sell_prices: HashMap<TradeGoodSymbol, i32> = market_data
+        .iter()
+        .flat_map(|(_, trade_goods)| trade_goods.iter())
+        .into_grouping_map_by(|mtg| mtg.symbol.clone())
+        .fold(0, |best, _, mtg| best.max(mtg.sell_price));
+
+    let cheapest_offers = ship_prices
+        .price_infos
+        .iter()
+        .flat_map(|(wps, shipyard_ships)| {
+            shipyard_ships
+                .iter()
+                .map(move |shipyard_ship| (wps, shipyard_ship))
+        })
+        .into_group_map_by(|(_, shipyard_ship)| shipyard_ship.r#type)
+        .into_values()
+        .filter_map(|offers| {
+            offers
+                .into_iter()
+                .min_by_key(|(_, shipyard_ship)| shipyard_ship.purchase_price)
+        })
+        .collect_vec();
+
+    cheapest_offers
+        .into_iter()
+        .filter_map(|(wps, shipyard_ship)| {
+            let role = ShipRole::of_ship_type(&shipyard_ship.r#type)?;
+            let profit_per_hour = match role {
+                ShipRole::Hauling => hauling_profit_per_hour(shipyard_ship, &trading_opportunities, fuel_cost_per_unit),
+                ShipRole::Mining => extraction_profit_per_hour(shipyard_ship.mount_strength(ShipMountSymbol::is_mining_laser), &MINED_GOODS, &best_sell_prices),
+                ShipRole::Siphoning => {
+                    extraction_profit_per_hour(shipyard_ship.mount_strength(ShipMountSymbol::is_gas_siphon), &SIPHONED_GOODS, &best_sell_prices)
+                }
+            };
+
+            Some(ShipTypeEvaluation::new(
+                shipyard_ship.r#type,
+                role,
+                wps.clone(),
+                (shipyard_ship.purchase_price as i64).into(),
+                (
+                    shipyard_ship.cargo_capacity().max(0) as u32,
+                    shipyard_ship.engine.speed.max(0) as u32,
+                    shipyard_ship.frame.fuel_capacity.max(0) as u32,
+                ),
+                profit_per_hour,
+            ))
+        })
+        .sorted_by(|a, b| {
+            a.role
+                .cmp(&b.role)
+                .then(b.return_per_credit.total_cmp(&a.return_per_credit))
+                .then(a.ship_type.cmp(&b.ship_type))
+        })
+        .collect_vec()
+}
+
+/// Round trips between the purchase and the sell market. Routes longer than the fuel capacity would need refueling stops or drifting, so we skip them.
+fn hauling_profit_per_hour(shipyard_ship: &ShipyardShip, trading_opportunities: &[TradingOpportunity], fuel_cost_per_unit: f64) -> Credits {
+    let cargo_capacity = shipyard_ship.cargo_capacity().max(0) as u32;
+    let engine_speed = shipyard_ship.engine.speed.max(1) as u32;
+    let fuel_capacity = shipyard_ship.frame.fuel_capacity.max(0) as u32;
+
+    let best_profits_per_hour = trading_opportunities
+        .iter()
+        .filter(|opp| fuel_capacity == 0 || opp.direct_distance <= fuel_capacity)
+        .map(|opp| {
+            let units = cargo_capacity.min(opp.purchase_market_trade_good_entry.trade_volume.max(0) as u32 * MAX_TRADE_VOLUMES_PER_TRIP);
+            let trip_secs = 2 * calculate_time(&FlightMode::Cruise, opp.direct_distance, engine_speed) + SECS_AT_MARKETS;
+            let fuel = 2 * calculate_fuel_consumption(&FlightMode::Cruise, opp.direct_distance);
+            let profit = units as f64 * opp.profit_per_unit as f64 - fuel as f64 * fuel_cost_per_unit;
+            profit * 3600.0 / trip_secs as f64
+        })
+        .sorted_by(|a, b| b.total_cmp(a))
+        .take(NUM_TRADE_ROUTES)
+        .collect_vec();
+
+    if best_profits_per_hour.is_empty() {
+        return 0.into();
+    }
+    ((best_profits_per_hour.iter().sum::<f64>() / best_profits_per_hour.len() as f64).round() as i64).into()
+}
+
+/// The extracted goods are valued at the average of their best sell prices in the system - which goods we get depends on the site and luck.
+fn extraction_profit_per_hour(mount_strength: i32, extracted_goods: &[TradeGoodSymbol], best_sell_prices: &HashMap<TradeGoodSymbol, i32>) -> Credits {
+    let sell_prices = extracted_goods
+        .iter()
+        .filter_map(|tg| best_sell_prices.get(tg))
+        .collect_vec();
+    if sell_prices.is_empty() {
+        return 0.into();
+    }
+
+    let value_per_unit = sell_prices.iter().map(|&&price| price as f64).sum::<f64>() / sell_prices.len() as f64;
+    let units_per_hour = mount_strength.max(0) as f64 * 3600.0 / EXTRACTION_COOLDOWN_SECS;
+    ((units_per_hour * value_per_unit).round() as i64).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::universe_server::universe_server::InMemoryUniverse;
+    use st_domain::ShipType;
+
+    #[test]
+    fn evaluates_the_ship_types_of_the_test_universe() {
+        let json_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .join("resources")
+            .join("universe_snapshot.json");
+        let universe = InMemoryUniverse::from_snapshot(json_path).expect("InMemoryUniverse::from_snapshot");
+
+        let ship_prices = ShipPriceInfo {
+            price_infos: universe
+                .shipyards
+                .values()
+                .filter_map(|shipyard| {
+                    shipyard
+                        .ships
+                        .clone()
+                        .map(|ships| (shipyard.symbol.clone(), ships))
+                })
+                .collect_vec(),
+            latest_shipyard_infos: vec![],
+            ship_price_guesses: Default::default(),
+        };
+        let market_entries = universe
+            .marketplaces
+            .iter()
+            .map(|(wps, market_data)| MarketEntry {
+                waypoint_symbol: wps.clone(),
+                market_data: market_data.clone(),
+                created_at: Default::default(),
+            })
+            .collect_vec();
+        let waypoints = universe.waypoints.values().cloned().collect_vec();
+
+        let evaluations = evaluate_ship_types(&ship_prices, &market_entries, &waypoints, 0.75);
+
+        // probes and surveyors don't have a role we could compare
+        let evaluated_ship_types = evaluations
+            .iter()
+            .map(|evaluation| (evaluation.role, evaluation.ship_type))
+            .sorted()
+            .collect_vec();
+        assert_eq!(
+            evaluated_ship_types,
+            vec![
+                (ShipRole::Hauling, ShipType::SHIP_LIGHT_HAULER),
+                (ShipRole::Hauling, ShipType::SHIP_LIGHT_SHUTTLE),
+                (ShipRole::Mining, ShipType::SHIP_MINING_DRONE),
+                (ShipRole::Siphoning, ShipType::SHIP_SIPHON_DRONE),
+            ]
+        );
+
+        let light_hauler = evaluations
+            .iter()
+            .find(|evaluation| evaluation.ship_type == ShipType::SHIP_LIGHT_HAULER)
+            .unwrap();
+        assert_eq!(light_hauler.price, 272_775.into());
+        assert!(light_hauler.cargo_capacity > 0);
+        assert!(light_hauler.profit_per_hour.0 > 0);
+
+        // the hauling ship types come with the best return per credit first
+        let hauling = evaluations
+            .iter()
+            .filter(|evaluation| evaluation.role == ShipRole::Hauling)
+            .collect_vec();
+        assert!(hauling[0].return_per_credit >= hauling[1].return_per_credit);
+    }
+}
@@ -28,7 +28,7 @@ pub struct StClient {
 
 impl StClient {
     /// creates a new StClient with a base_url. base_url needs to include everything including "/v2/".
-    /// Inserts a trailing '/' if necessary
+    /// Inserts a trailing '/' if necessary. The paths of the endpoints are joined without a leading '/' - it would drop the "/v2/".
     pub fn try_with_base_url(client: ClientWithMiddleware, base_url: &str) -> Result<Self> {
         let with_trailing_slash = if base_url.ends_with('/') {
             base_url.to_string()
@@ -96,7 +96,7 @@ impl StClientTrait for StClient {
         let resp = self
             .client
             .get(self.base_url.join(&format!(
-                "/systems/{}/waypoints/{}/construction",
+                "systems/{}/waypoints/{}/construction",
                 extract_system_symbol(waypoint_symbol).0,
                 waypoint_symbol.0
             ))?)
@@ -212,7 +212,7 @@ impl StClientTrait for StClient {
         Self::make_api_call(
             self.client
                 .post(self.base_url.join(&format!(
-                    "/systems/{}/waypoints/{}/construction/supply",
+                    "systems/{}/waypoints/{}/construction/supply",
                     waypoint_symbol.system_symbol().0,
                     waypoint_symbol.0
                 ))?)
@@ -342,11 +342,10 @@ impl StClientTrait for StClient {
     }
 
     async fn get_marketplace(&self, waypoint_symbol: WaypointSymbol) -> Result<GetMarketResponse> {
-        let request = self.client.get(self.base_url.join(&format!(
-            "/systems/{}/waypoints/{}/market",
-            waypoint_symbol.system_symbol().0,
-            waypoint_symbol.0
-        ))?);
+        let request = self.client.get(
+            self.base_url
+                .join(&format!("systems/{}/waypoints/{}/market", waypoint_symbol.system_symbol().0, waypoint_symbol.0))?,
+        );
 
         Self::make_api_call(request).await
     }
@@ -362,7 +361,7 @@ impl StClientTrait for StClient {
             .client
             .post(
                 self.base_url
-                    .join(&format!("/my/ships/{}/transfer", from_ship_symbol.0,))?,
+                    .join(&format!("my/ships/{}/transfer", from_ship_symbol.0))?,
             )
             .json(&TransferCargoRequest {
                 ship_symbol: to_ship_id,
@@ -375,7 +374,7 @@ impl StClientTrait for StClient {
 
     async fn get_jump_gate(&self, waypoint_symbol: WaypointSymbol) -> Result<GetJumpGateResponse> {
         let request = self.client.get(self.base_url.join(&format!(
-            "/systems/{}/waypoints/{}/jump-gate",
+            "systems/{}/waypoints/{}/jump-gate",
             waypoint_symbol.system_symbol().0,
             waypoint_symbol.0
         ))?);
@@ -385,7 +384,7 @@ impl StClientTrait for StClient {
 
     async fn get_shipyard(&self, waypoint_symbol: WaypointSymbol) -> Result<GetShipyardResponse> {
         let request = self.client.get(self.base_url.join(&format!(
-            "/systems/{}/waypoints/{}/shipyard",
+            "systems/{}/waypoints/{}/shipyard",
             waypoint_symbol.system_symbol().0,
             waypoint_symbol.0
         ))?);
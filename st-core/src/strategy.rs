@@ -0,0 +1,172 @@
+use crate::hot_reload::{single_file, HotReloadable};
+use anyhow::{Context, Result};
+use st_domain::strategy::StrategyConfig;
+use std::fmt::{Debug, Formatter};
+use std::path::{Path, PathBuf};
+
+pub fn parse_strategy_config(content: &str) -> Result<StrategyConfig> {
+    let strategy_config: StrategyConfig = toml::from_str(content)?;
+    strategy_config.validate()?;
+    Ok(strategy_config)
+}
+
+pub fn load_strategy_config_from_file(strategy_file: &Path) -> Result<StrategyConfig> {
+    let content = std::fs::read_to_string(strategy_file).with_context(|| format!("reading strategy file {}", strategy_file.display()))?;
+    parse_strategy_config(&content).with_context(|| format!("parsing strategy file {}", strategy_file.display()))
+}
+
+/// Hands out the economic parameters of the fleets.
+///
+/// Without a strategy file the defaults of `StrategyConfig` are used.
+/// With a strategy file, changes are picked up the next time a decision asks for the strategy.
+/// If a changed file is invalid, the error is logged and the previously loaded strategy stays active.
+#[derive(Clone)]
+pub struct StrategyStore {
+    strategy_config: HotReloadable<StrategyConfig>,
+}
+
+impl Debug for StrategyStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StrategyStore")
+            .field("maybe_strategy_file", &self.strategy_config.path())
+            .finish()
+    }
+}
+
+impl Default for StrategyStore {
+    fn default() -> Self {
+        Self::from_strategy_config(StrategyConfig::default())
+    }
+}
+
+impl StrategyStore {
+    /// Fails if the initial strategy is invalid - we don't want to start the agent with a broken strategy.
+    pub fn new(maybe_strategy_file: Option<PathBuf>) -> Result<Self> {
+        match maybe_strategy_file {
+            None => Ok(Self::default()),
+            Some(strategy_file) => Ok(Self {
+                strategy_config: HotReloadable::load(strategy_file, "strategy config", single_file, load_strategy_config_from_file)?,
+            }),
+        }
+    }
+
+    /// A fixed strategy without a backing file - e.g. for running the simulation with different parameters.
+    pub fn from_strategy_config(strategy_config: StrategyConfig) -> Self {
+        Self {
+            strategy_config: HotReloadable::fixed(strategy_config),
+        }
+    }
+
+    pub fn get_strategy(&self) -> StrategyConfig {
+        self.strategy_config.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use st_domain::budgeting::credits::Credits;
+    use st_domain::{ShipType, TradeGoodSymbol};
+    use std::time::SystemTime;
+
+    #[test]
+    fn sample_strategy_file_matches_the_defaults() {
+        let content = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/../resources/strategy.toml")).unwrap();
+        let parsed = parse_strategy_config(&content).unwrap();
+
+        assert_eq!(parsed, StrategyConfig::default());
+    }
+
+    #[test]
+    fn missing_sections_fall_back_to_the_defaults() {
+        let content = r#"
+[budgets]
+budget_per_trader = 100000
+
+[construction.material_budget_limits]
+FAB_MATS = 250000
+
+[ship_price_guesses]
+SHIP_LIGHT_HAULER = 300000
+"#;
+
+        let parsed = parse_strategy_config(content).unwrap();
+
+        assert_eq!(parsed.budgets.budget_per_trader, Credits::new(100_000));
+        assert_eq!(parsed.budgets.fuel_reserve_per_ship, Credits::new(1_000));
+        assert_eq!(
+            parsed
+                .construction
+                .material_budget_limits
+                .get(&TradeGoodSymbol::FAB_MATS),
+            Some(&Credits::new(250_000))
+        );
+        assert_eq!(
+            parsed.construction.material_priorities,
+            StrategyConfig::default().construction.material_priorities
+        );
+        assert_eq!(parsed.ship_price_guesses.get(&ShipType::SHIP_LIGHT_HAULER), Some(&300_000));
+        assert_eq!(parsed.ship_price_guesses.get(&ShipType::SHIP_PROBE), None);
+        assert_eq!(parsed.shopping_lists, StrategyConfig::default().shopping_lists);
+    }
+
+    #[test]
+    fn rejects_unknown_fields_and_invalid_values() {
+        let unknown_field = "[budgets]\nbudget_per_traders = 100000\n";
+        assert!(parse_strategy_config(unknown_field).is_err());
+
+        let invalid_values = r#"
+[budgets]
+budget_per_trader = -1
+
+[shopping_lists]
+construction_fleet = []
+
+[cargo_transfer]
+hauler_fill_threshold = 1.5
+
+[mining]
+max_drones_per_site = 0
+haul_cost_per_unit_and_distance = -0.1
+"#;
+        let err = format!("{:#}", parse_strategy_config(invalid_values).unwrap_err());
+        assert!(err.contains("budgets.budget_per_trader"), "{err}");
+        assert!(err.contains("shopping_lists.construction_fleet"), "{err}");
+        assert!(err.contains("cargo_transfer.hauler_fill_threshold"), "{err}");
+        assert!(err.contains("mining.max_drones_per_site"), "{err}");
+        assert!(err.contains("mining.haul_cost_per_unit_and_distance"), "{err}");
+    }
+
+    #[test]
+    fn keeps_the_previous_strategy_if_the_changed_file_is_invalid() {
+        let dir = std::env::temp_dir().join(format!("strategy_store_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let strategy_file = dir.join("strategy.toml");
+
+        std::fs::write(&strategy_file, "[budgets]\nbudget_per_trader = 100000\n").unwrap();
+        let store = StrategyStore::new(Some(strategy_file.clone())).unwrap();
+        assert_eq!(store.get_strategy().budgets.budget_per_trader, Credits::new(100_000));
+
+        std::fs::write(&strategy_file, "[budgets]\nbudget_per_trader = 200000\n").unwrap();
+        // make sure the mtime changes, even on filesystems with a coarse resolution
+        let later = SystemTime::now() + std::time::Duration::from_secs(10);
+        std::fs::File::options()
+            .write(true)
+            .open(&strategy_file)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert_eq!(store.get_strategy().budgets.budget_per_trader, Credits::new(200_000));
+
+        std::fs::write(&strategy_file, "[budgets]\nbudget_per_trader = \"lots\"\n").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&strategy_file)
+            .unwrap()
+            .set_modified(later + std::time::Duration::from_secs(10))
+            .unwrap();
+        assert_eq!(store.get_strategy().budgets.budget_per_trader, Credits::new(200_000));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
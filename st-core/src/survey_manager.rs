@@ -1,31 +1,43 @@
-use itertools::Itertools;
-use st_domain::{MaterializedSupplyChain, Survey, TradeGoodSymbol};
+use chrono::{DateTime, Utc};
+use st_domain::extraction_analytics::{delivery_prices, haul_adjusted_prices, rank_surveys, ExtractionYieldReport};
+use st_domain::{LabelledCoordinate, MiningOpsConfig, Survey, TradeGoodSymbol, Waypoint};
 use std::collections::HashMap;
 
-pub(crate) fn pick_best_survey(all_surveys: Vec<Survey>, materialized_supply_chain: &MaterializedSupplyChain) -> Option<(Survey, i32)> {
-    let demand_for_raw_materials = materialized_supply_chain.calc_demand_for_raw_materials();
+/// Ranks the surveys by their expected credit value per extraction.
+/// The value is based on the current delivery prices of the demanded goods minus the costs of hauling them from the mining site
+/// to their delivery markets and the yields we've observed for the different survey sizes.
+pub(crate) fn rank_surveys_for_current_demand(
+    all_surveys: Vec<Survey>,
+    mining_config: &MiningOpsConfig,
+    waypoints_of_system: &[Waypoint],
+    yield_report: &ExtractionYieldReport,
+    haul_cost_per_unit_and_distance: f64,
+    now: DateTime<Utc>,
+) -> Vec<(Survey, f64)> {
+    let prices = delivery_prices(&mining_config.delivery_locations, &mining_config.demanded_goods);
 
-    let scored_raw_products: HashMap<TradeGoodSymbol, i32> = demand_for_raw_materials
+    let maybe_mining_site = waypoints_of_system
         .iter()
-        .map(|foo| (foo.trade_good_symbol.clone(), foo.score))
-        .collect();
+        .find(|wp| wp.symbol == mining_config.mining_waypoint);
 
-    let scored_surveys: Vec<(Survey, i32)> = all_surveys
+    let haul_distances: HashMap<TradeGoodSymbol, u32> = mining_config
+        .delivery_locations
         .iter()
-        .map(|s| {
-            let item_counts: HashMap<TradeGoodSymbol, usize> = s.deposits.iter().map(|d| d.symbol.clone()).counts();
-
-            let survey_score = item_counts
-                .iter()
-                .map(|(tgs, num_occurrences_in_survey)| scored_raw_products.get(tgs).cloned().unwrap_or(0) * (*num_occurrences_in_survey as i32))
-                .sum::<i32>();
-
-            (s.clone(), survey_score)
+        .map(|(tg, route)| {
+            // the route might start at a different asteroid than the one we're mining at
+            let distance = maybe_mining_site
+                .zip(
+                    waypoints_of_system
+                        .iter()
+                        .find(|wp| wp.symbol == route.delivery_location),
+                )
+                .map(|(mining_site, delivery_wp)| mining_site.distance_to(delivery_wp))
+                .unwrap_or(route.distance);
+            (tg.clone(), distance)
         })
-        .sorted_by_key(|(_, score)| -*score)
-        .collect_vec();
+        .collect();
 
-    let maybe_best: Option<(Survey, i32)> = scored_surveys.first().cloned();
+    let net_prices = haul_adjusted_prices(&prices, &haul_distances, haul_cost_per_unit_and_distance);
 
-    maybe_best
+    rank_surveys(&all_surveys, &net_prices, yield_report, now)
 }
@@ -1,6 +1,8 @@
+use crate::mining_hauler_dispatcher::{should_hauler_depart, MiningHaulerDispatcher};
+use crate::strategy::StrategyStore;
 use anyhow::Result;
+use chrono::{TimeDelta, Utc};
 use itertools::Itertools;
-use metrics::IntoF64;
 use st_domain::cargo_transfer::TransferCargoError::{ReceiveShipDoesntExist, SendingUpdateMessageFailed};
 use st_domain::cargo_transfer::{
     HaulerTransferSummary, InternalTransferCargoRequest, InternalTransferCargoResponse, InternalTransferCargoToHaulerResult, TransferCargoError,
@@ -8,6 +10,7 @@ use st_domain::cargo_transfer::{
 use st_domain::{Cargo, Inventory, ShipSymbol, WaypointSymbol};
 use std::collections::{HashMap, HashSet};
 use std::future::Future;
+use std::ops::Not;
 use std::sync::Arc;
 use tokio::sync::mpsc::Sender;
 use tokio::sync::Mutex;
@@ -15,53 +18,94 @@ use tokio::sync::Mutex;
 pub struct TransferCargoManager {
     // Haulers waiting at each location
     waiting_haulers: Arc<Mutex<HashMap<WaypointSymbol, HashMap<ShipSymbol, (HaulerTransferSummary, Sender<(ShipSymbol, Cargo)>)>>>>,
+    strategy_store: StrategyStore,
+    dispatcher: MiningHaulerDispatcher,
 }
 
 impl Default for TransferCargoManager {
     fn default() -> Self {
-        Self::new()
+        Self::new(StrategyStore::default())
     }
 }
 
 impl TransferCargoManager {
-    pub fn new() -> Self {
+    pub fn new(strategy_store: StrategyStore) -> Self {
         Self {
             waiting_haulers: Arc::new(Mutex::new(HashMap::new())),
+            strategy_store,
+            dispatcher: MiningHaulerDispatcher::default(),
         }
     }
 
+    pub fn dispatcher(&self) -> &MiningHaulerDispatcher {
+        &self.dispatcher
+    }
+
+    /// picks up changes of the strategy file
+    pub fn hauler_fill_threshold(&self) -> f64 {
+        self.strategy_store
+            .get_strategy()
+            .cargo_transfer
+            .hauler_fill_threshold
+    }
+
     pub async fn register_hauler_for_pickup_and_wait_until_full(
         &self,
         waypoint_symbol: WaypointSymbol,
         hauler_ship_symbol: ShipSymbol,
         hauler_cargo: Cargo,
         hauler_cargo_updated_channel: Sender<(ShipSymbol, Cargo)>,
+        delivery_round_trip_secs: u32,
     ) -> Result<HaulerTransferSummary> {
-        // we wait and semantically block for transfers until we're full enough (80%)
+        // we wait for transfers until the dispatcher lets us go (usually once we're 80% full)
         // then we yield the updated cargo of the hauler
+        self.dispatcher.hauler_arrived(&hauler_ship_symbol)?;
         {
             let mut guard = self.waiting_haulers.lock().await;
             guard
                 .entry(waypoint_symbol.clone())
                 .or_default()
-                .insert(hauler_ship_symbol.clone(), (hauler_cargo.into(), hauler_cargo_updated_channel.clone()));
+                .insert(hauler_ship_symbol.clone(), (hauler_cargo.clone().into(), hauler_cargo_updated_channel.clone()));
         }
 
+        let fill_threshold = self.hauler_fill_threshold();
+
+        let mut last_summary: HaulerTransferSummary = hauler_cargo.into();
+
         let summary = loop {
             let mut guard = self.waiting_haulers.lock().await;
 
-            if let Some(ships_at_waypoint) = guard.get(&waypoint_symbol).cloned() {
-                if let Some((summary, _)) = ships_at_waypoint.get(&hauler_ship_symbol) {
-                    let cargo = &summary.cargo;
-                    let fill_amount: f64 = cargo.units.into_f64() / cargo.capacity.into_f64();
+            let maybe_summary = guard
+                .get(&waypoint_symbol)
+                .and_then(|ships_at_waypoint| ships_at_waypoint.get(&hauler_ship_symbol))
+                .map(|(summary, _)| summary.clone());
 
-                    if fill_amount > 0.8 {
+            match maybe_summary {
+                // we've been released (e.g. the drones left the site) - leave with what we have
+                None => break last_summary,
+                Some(summary) => {
+                    let cargo = &summary.cargo;
+                    let num_other_waiting_haulers = guard
+                        .get(&waypoint_symbol)
+                        .map(|ships_at_waypoint| ships_at_waypoint.len() - 1)
+                        .unwrap_or_default();
+
+                    if should_hauler_depart(
+                        cargo.units as u32,
+                        cargo.capacity as u32,
+                        fill_threshold,
+                        self.dispatcher
+                            .site_units_per_sec(&waypoint_symbol, Utc::now()),
+                        delivery_round_trip_secs,
+                        num_other_waiting_haulers,
+                    ) {
                         guard
                             .get_mut(&waypoint_symbol)
                             .unwrap()
                             .remove(&hauler_ship_symbol);
-                        break summary.clone();
+                        break summary;
                     }
+                    last_summary = summary;
                 }
             }
 
@@ -72,11 +116,38 @@ impl TransferCargoManager {
             tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
         };
 
-        // poll regularly until cargo is full enough and remove ourselves from the list again
+        self.dispatcher.hauler_departed(
+            hauler_ship_symbol,
+            waypoint_symbol,
+            Utc::now() + TimeDelta::seconds(delivery_round_trip_secs as i64),
+        )?;
 
         Ok(summary)
     }
 
+    /// Some(0) if a hauler is waiting at the site right now, None if no hauler is assigned to this site.
+    pub async fn secs_until_hauler_is_available(&self, waypoint_symbol: &WaypointSymbol) -> Option<i64> {
+        let has_waiting_haulers = self
+            .waiting_haulers
+            .lock()
+            .await
+            .get(waypoint_symbol)
+            .map(|ships_at_waypoint| ships_at_waypoint.is_empty().not())
+            .unwrap_or_default();
+
+        if has_waiting_haulers {
+            Some(0)
+        } else {
+            self.dispatcher
+                .secs_until_next_hauler_returns(waypoint_symbol, Utc::now())
+        }
+    }
+
+    /// Lets the haulers waiting at this waypoint leave with the cargo they have received so far.
+    pub async fn release_waiting_haulers(&self, waypoint_symbol: &WaypointSymbol) {
+        self.waiting_haulers.lock().await.remove(waypoint_symbol);
+    }
+
     pub async fn try_to_transfer_cargo_until_available_space<F, Fut>(
         &self,
         sending_ship: ShipSymbol,
@@ -290,7 +361,7 @@ mod tests {
 
         let miner_cargo = create_test_cargo(&vec![iron_ore_entry_40_units.clone()], 40);
 
-        let transfer_manager = Arc::new(TransferCargoManager::new());
+        let transfer_manager = Arc::new(TransferCargoManager::default());
 
         let waypoint = WaypointSymbol("WP1".to_string());
 
@@ -394,7 +465,7 @@ mod tests {
         cargo_updated_tx: Sender<(ShipSymbol, Cargo)>,
     ) -> anyhow::Result<(ShipSymbol, HaulerTransferSummary)> {
         let updated_summary = transfer_manager
-            .register_hauler_for_pickup_and_wait_until_full(waypoint_symbol.clone(), ship_symbol.clone(), cargo.clone(), cargo_updated_tx.clone())
+            .register_hauler_for_pickup_and_wait_until_full(waypoint_symbol.clone(), ship_symbol.clone(), cargo.clone(), cargo_updated_tx.clone(), 300)
             .await?;
 
         Ok((ship_symbol.clone(), updated_summary))
@@ -0,0 +1,832 @@
+use crate::pagination::{PaginatedResponse, PaginationInput};
+use crate::st_client::StClientTrait;
+use crate::universe_server::universe_server::{InMemoryUniverse, InMemoryUniverseClient};
+use anyhow::Result;
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, patch, post};
+use axum::{Json, Router};
+use chrono::{NaiveDate, TimeDelta, Utc};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use st_domain::{
+    Agent, AgentCredits, AgentResponse, CreateSurveyResponse, Data, DockShipResponse, ExtractResourcesResponse, GetConstructionResponse, GetJumpGateResponse,
+    GetMarketResponse, GetShipyardResponse, GetSupplyChainResponse, GetSystemResponse, JettisonCargoRequest, JettisonCargoResponse, Leaderboards,
+    ListAgentsResponse, Meta, NavigateShipRequest, NavigateShipResponse, OrbitShipResponse, PatchShipNavRequest, PurchaseShipRequest, PurchaseShipResponse,
+    PurchaseTradeGoodRequest, PurchaseTradeGoodResponse, RefuelShipRequest, RefuelShipResponse, SellTradeGoodRequest, SellTradeGoodResponse,
+    SetFlightModeResponse, Ship, ShipSymbol, SiphonResourcesResponse, StStatusResponse, Stats, SupplyConstructionSiteRequest, SupplyConstructionSiteResponse,
+    Survey, SystemSymbol, TransferCargoRequest, TransferCargoResponse, Waypoint, WaypointSymbol,
+};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{event, Level};
+
+/// Faults the mock api injects before a request reaches the universe. All ratios are between 0 and 1.
+#[derive(Clone, Debug)]
+pub struct FaultInjection {
+    /// share of the requests answered with 429 Too Many Requests
+    pub rate_limited_ratio: f64,
+    /// share of the requests answered with `server_error_status`
+    pub server_error_ratio: f64,
+    pub server_error_status: StatusCode,
+    /// share of the requests whose connection gets closed before they reach the api - the client sees a connection error instead of a response
+    pub dropped_connection_ratio: f64,
+    /// delay before every response
+    pub latency: Duration,
+    /// the server resets after this many requests - from then on, it rejects the tokens of before the reset
+    pub reset_after_requests: Option<u64>,
+    /// the faults are random, but reproducible
+    pub seed: u64,
+}
+
+impl Default for FaultInjection {
+    fn default() -> Self {
+        Self {
+            rate_limited_ratio: 0.0,
+            server_error_ratio: 0.0,
+            server_error_status: StatusCode::INTERNAL_SERVER_ERROR,
+            dropped_connection_ratio: 0.0,
+            latency: Duration::ZERO,
+            reset_after_requests: None,
+            seed: 42,
+        }
+    }
+}
+
+struct MockApiState {
+    client: InMemoryUniverseClient,
+    faults: FaultInjection,
+    rng: Mutex<StdRng>,
+    reset_date: NaiveDate,
+    is_reset: AtomicBool,
+    num_requests: AtomicU64,
+    num_injected_faults: AtomicU64,
+    num_dropped_connections: AtomicU64,
+}
+
+impl MockApiState {
+    fn current_reset_date(&self) -> NaiveDate {
+        if self.is_reset.load(Ordering::SeqCst) {
+            self.reset_date + TimeDelta::days(14)
+        } else {
+            self.reset_date
+        }
+    }
+
+    fn roll(&self) -> f64 {
+        self.rng.lock().map(|mut rng| rng.gen()).unwrap_or(1.0)
+    }
+
+    fn should_drop_connection(&self) -> bool {
+        let ratio = self.faults.dropped_connection_ratio;
+        if ratio > 0.0 && self.roll() < ratio {
+            self.num_dropped_connections.fetch_add(1, Ordering::SeqCst);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Serves the SpaceTraders v2 routes on localhost and answers them from an `InMemoryUniverse`,
+/// so `StClient` can be tested together with the middleware stack of `reqwest_helpers` and the pagination.
+///
+/// The game logic is the one of `InMemoryUniverseClient` - the endpoints it doesn't implement (register, contracts, charts, systems list) aren't routed and answer with 404.
+/// The clients connect to a tcp proxy in front of the api, which injects the connection faults.
+pub struct MockApiServer {
+    /// e.g. http://127.0.0.1:12345/v2
+    pub base_url: String,
+    state: Arc<MockApiState>,
+    handle: JoinHandle<()>,
+    proxy_handle: JoinHandle<()>,
+}
+
+impl MockApiServer {
+    /// Listens on a random free port.
+    pub async fn start(universe: InMemoryUniverse, faults: FaultInjection) -> Result<Self> {
+        Self::start_on(SocketAddr::from(([127, 0, 0, 1], 0)), universe, faults).await
+    }
+
+    pub async fn start_on(addr: SocketAddr, universe: InMemoryUniverse, faults: FaultInjection) -> Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        let local_addr = listener.local_addr()?;
+        let api_listener = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0))).await?;
+        let api_addr = api_listener.local_addr()?;
+
+        let state = Arc::new(MockApiState {
+            client: InMemoryUniverseClient::new(universe),
+            rng: Mutex::new(StdRng::seed_from_u64(faults.seed)),
+            faults,
+            reset_date: Utc::now().date_naive(),
+            is_reset: AtomicBool::new(false),
+            num_requests: AtomicU64::new(0),
+            num_injected_faults: AtomicU64::new(0),
+            num_dropped_connections: AtomicU64::new(0),
+        });
+
+        let router = create_router(Arc::clone(&state));
+        let handle = tokio::spawn(async move {
+            if let Err(e) = axum::serve(api_listener, router).await {
+                event!(Level::ERROR, "Mock api server stopped: {}", e);
+            }
+        });
+        let proxy_handle = tokio::spawn(proxy_connections(listener, api_addr, Arc::clone(&state)));
+
+        event!(Level::INFO, "Mock api server listening on {}", local_addr);
+
+        Ok(Self {
+            base_url: format!("http://{}/v2", local_addr),
+            state,
+            handle,
+            proxy_handle,
+        })
+    }
+
+    /// Rejects the tokens of all authenticated requests from now on, like the real api after a reset.
+    pub fn trigger_reset(&self) {
+        self.state.is_reset.store(true, Ordering::SeqCst);
+    }
+
+    pub fn num_requests(&self) -> u64 {
+        self.state.num_requests.load(Ordering::SeqCst)
+    }
+
+    pub fn num_injected_faults(&self) -> u64 {
+        self.state.num_injected_faults.load(Ordering::SeqCst)
+    }
+
+    /// the requests that never reached the api - they aren't part of `num_requests` and `num_injected_faults`
+    pub fn num_dropped_connections(&self) -> u64 {
+        self.state.num_dropped_connections.load(Ordering::SeqCst)
+    }
+
+    pub fn universe(&self) -> Arc<RwLock<InMemoryUniverse>> {
+        self.state.client.clone_universe_handle()
+    }
+}
+
+impl Drop for MockApiServer {
+    fn drop(&mut self) {
+        self.proxy_handle.abort();
+        self.handle.abort();
+    }
+}
+
+async fn proxy_connections(listener: TcpListener, api_addr: SocketAddr, state: Arc<MockApiState>) {
+    loop {
+        match listener.accept().await {
+            Ok((client, _)) => {
+                let state = Arc::clone(&state);
+                tokio::spawn(async move {
+                    if let Err(e) = proxy_connection(client, api_addr, state).await {
+                        event!(Level::DEBUG, "Mock api connection failed: {}", e);
+                    }
+                });
+            }
+            Err(e) => {
+                event!(Level::ERROR, "Mock api proxy stopped: {}", e);
+                return;
+            }
+        }
+    }
+}
+
+/// Forwards the requests of a client connection to the api and the responses back - or closes the connection instead of forwarding a request.
+async fn proxy_connection(client: TcpStream, api_addr: SocketAddr, state: Arc<MockApiState>) -> std::io::Result<()> {
+    let api = TcpStream::connect(api_addr).await?;
+    let (mut client_read, mut client_write) = client.into_split();
+    let (mut api_read, mut api_write) = api.into_split();
+    let responses = tokio::spawn(async move { tokio::io::copy(&mut api_read, &mut client_write).await });
+
+    // the clients don't pipeline their requests, so every read is (the start of) the next request
+    let mut buf = vec![0u8; 16 * 1024];
+    loop {
+        let num_bytes = client_read.read(&mut buf).await?;
+        if num_bytes == 0 {
+            break;
+        }
+        if state.should_drop_connection() {
+            // dropping all halves closes both connections without an answer
+            responses.abort();
+            return Ok(());
+        }
+        api_write.write_all(&buf[..num_bytes]).await?;
+    }
+
+    api_write.shutdown().await?;
+    let _ = responses.await;
+    Ok(())
+}
+
+fn create_router(state: Arc<MockApiState>) -> Router {
+    let api_routes = Router::new()
+        .route("/agents", get(list_agents))
+        .route("/agents/:agent_symbol", get(get_public_agent))
+        .route("/my/agent", get(get_agent))
+        .route("/my/ships", get(list_ships).post(purchase_ship))
+        .route("/my/ships/:ship_symbol", get(get_ship))
+        .route("/my/ships/:ship_symbol/dock", post(dock_ship))
+        .route("/my/ships/:ship_symbol/orbit", post(orbit_ship))
+        .route("/my/ships/:ship_symbol/nav", patch(set_flight_mode))
+        .route("/my/ships/:ship_symbol/navigate", post(navigate))
+        .route("/my/ships/:ship_symbol/refuel", post(refuel))
+        .route("/my/ships/:ship_symbol/purchase", post(purchase_trade_good))
+        .route("/my/ships/:ship_symbol/sell", post(sell_trade_good))
+        .route("/my/ships/:ship_symbol/jettison", post(jettison_cargo))
+        .route("/my/ships/:ship_symbol/transfer", post(transfer_cargo))
+        .route("/my/ships/:ship_symbol/siphon", post(siphon_resources))
+        .route("/my/ships/:ship_symbol/survey", post(survey))
+        .route("/my/ships/:ship_symbol/extract", post(extract_resources))
+        .route("/my/ships/:ship_symbol/extract/survey", post(extract_resources_with_survey))
+        .route("/systems/:system_symbol", get(get_system))
+        .route("/systems/:system_symbol/waypoints", get(list_waypoints_of_system))
+        .route("/systems/:system_symbol/waypoints/:waypoint_symbol/market", get(get_marketplace))
+        .route("/systems/:system_symbol/waypoints/:waypoint_symbol/shipyard", get(get_shipyard))
+        .route("/systems/:system_symbol/waypoints/:waypoint_symbol/jump-gate", get(get_jump_gate))
+        .route("/systems/:system_symbol/waypoints/:waypoint_symbol/construction", get(get_construction_site))
+        .route(
+            "/systems/:system_symbol/waypoints/:waypoint_symbol/construction/supply",
+            post(supply_construction_site),
+        )
+        .route("/market/supply-chain", get(get_supply_chain));
+
+    Router::new()
+        .route("/v2/", get(get_status))
+        .nest("/v2", api_routes)
+        .layer(middleware::from_fn_with_state(Arc::clone(&state), inject_faults))
+        .with_state(state)
+}
+
+async fn inject_faults(State(state): State<Arc<MockApiState>>, request: Request, next: Next) -> Response {
+    let num_request = state.num_requests.fetch_add(1, Ordering::SeqCst) + 1;
+
+    if !state.faults.latency.is_zero() {
+        tokio::time::sleep(state.faults.latency).await;
+    }
+
+    if let Some(reset_after_requests) = state.faults.reset_after_requests {
+        if num_request > reset_after_requests {
+            state.is_reset.store(true, Ordering::SeqCst);
+        }
+    }
+
+    let is_status_request = request.uri().path() == "/v2/";
+    let has_token = request.headers().contains_key(header::AUTHORIZATION);
+    if state.is_reset.load(Ordering::SeqCst) && has_token && !is_status_request {
+        state.num_injected_faults.fetch_add(1, Ordering::SeqCst);
+        let message = "Failed to parse token. Token reset_date does not match the server. Server resets happen on a weekly to bi-weekly frequency during alpha. After a reset, you should re-register your agent.";
+        return ApiError::new(StatusCode::UNAUTHORIZED, message).into_response();
+    }
+
+    let roll = state.roll();
+
+    if roll < state.faults.rate_limited_ratio {
+        state.num_injected_faults.fetch_add(1, Ordering::SeqCst);
+        let mut response = ApiError::new(StatusCode::TOO_MANY_REQUESTS, "You have reached your API limit.").into_response();
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, header::HeaderValue::from_static("1"));
+        response
+    } else if roll < state.faults.rate_limited_ratio + state.faults.server_error_ratio {
+        state.num_injected_faults.fetch_add(1, Ordering::SeqCst);
+        ApiError::new(state.faults.server_error_status, "Injected server error").into_response()
+    } else {
+        next.run(request).await
+    }
+}
+
+/// Error body in the format of the SpaceTraders api.
+struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+/// The universe rejected the request (e.g. a ship in transit or not enough cargo) - unknown symbols are answered with 404 at the lookup sites.
+impl From<anyhow::Error> for ApiError {
+    fn from(err: anyhow::Error) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, err.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = json!({"error": {"message": self.message, "code": self.status.as_u16()}});
+        (self.status, Json(body)).into_response()
+    }
+}
+
+type ApiResult<T> = std::result::Result<Json<T>, ApiError>;
+
+fn respond<T: Serialize>(result: Result<T>) -> ApiResult<T> {
+    Ok(Json(result?))
+}
+
+#[derive(Deserialize)]
+struct PageQuery {
+    page: Option<u32>,
+    limit: Option<u32>,
+}
+
+impl PageQuery {
+    fn to_pagination_input(&self) -> PaginationInput {
+        PaginationInput {
+            page: self.page.unwrap_or(1).max(1),
+            limit: self.limit.unwrap_or(10).clamp(1, 20),
+        }
+    }
+}
+
+async fn get_status(State(state): State<Arc<MockApiState>>) -> Json<StStatusResponse> {
+    let universe = state.client.universe.read().await;
+
+    Json(StStatusResponse {
+        status: "SpaceTraders mock api is online".to_string(),
+        version: "v2".to_string(),
+        reset_date: state.current_reset_date().to_string(),
+        description: "Serves an in-memory universe".to_string(),
+        stats: Stats {
+            agents: 1,
+            ships: universe.ships.len() as i32,
+            systems: universe.systems.len() as i32,
+            waypoints: universe.waypoints.len() as i32,
+        },
+        leaderboards: Leaderboards {
+            most_credits: vec![AgentCredits {
+                agent_symbol: universe.agent.symbol.0.clone(),
+                credits: universe.agent.credits,
+            }],
+            most_submitted_charts: vec![],
+        },
+    })
+}
+
+async fn public_agent(state: &MockApiState) -> Agent {
+    Agent {
+        account_id: None,
+        ..state.client.universe.read().await.agent.clone()
+    }
+}
+
+async fn list_agents(State(state): State<Arc<MockApiState>>, Query(query): Query<PageQuery>) -> Json<ListAgentsResponse> {
+    let pagination_input = query.to_pagination_input();
+    let agents = if pagination_input.page == 1 {
+        vec![public_agent(&state).await]
+    } else {
+        vec![]
+    };
+
+    Json(ListAgentsResponse {
+        data: agents,
+        meta: Meta {
+            total: 1,
+            page: pagination_input.page,
+            limit: pagination_input.limit,
+        },
+    })
+}
+
+async fn get_public_agent(State(state): State<Arc<MockApiState>>, Path(agent_symbol): Path<String>) -> ApiResult<AgentResponse> {
+    let agent = public_agent(&state).await;
+    if agent.symbol.0 == agent_symbol {
+        Ok(Json(AgentResponse { data: agent }))
+    } else {
+        Err(ApiError::new(StatusCode::NOT_FOUND, format!("Agent {} not found", agent_symbol)))
+    }
+}
+
+async fn get_agent(State(state): State<Arc<MockApiState>>) -> ApiResult<AgentResponse> {
+    respond(state.client.get_agent().await)
+}
+
+async fn list_ships(State(state): State<Arc<MockApiState>>, Query(query): Query<PageQuery>) -> ApiResult<PaginatedResponse<Ship>> {
+    respond(state.client.list_ships(query.to_pagination_input()).await)
+}
+
+async fn get_ship(State(state): State<Arc<MockApiState>>, Path(ship_symbol): Path<String>) -> ApiResult<Data<Ship>> {
+    let universe = state.client.universe.read().await;
+    match universe.ships.get(&ShipSymbol(ship_symbol.clone())) {
+        Some(ship) => Ok(Json(Data { data: ship.clone() })),
+        None => Err(ApiError::new(StatusCode::NOT_FOUND, format!("Ship {} not found", ship_symbol))),
+    }
+}
+
+/// The ship actions answer with 404 for ships that don't exist.
+async fn known_ship(state: &MockApiState, ship_symbol: String) -> Result<ShipSymbol, ApiError> {
+    let ship_symbol = ShipSymbol(ship_symbol);
+    if state
+        .client
+        .universe
+        .read()
+        .await
+        .ships
+        .contains_key(&ship_symbol)
+    {
+        Ok(ship_symbol)
+    } else {
+        Err(ApiError::new(StatusCode::NOT_FOUND, format!("Ship {} not found", ship_symbol.0)))
+    }
+}
+
+async fn purchase_ship(State(state): State<Arc<MockApiState>>, Json(request): Json<PurchaseShipRequest>) -> ApiResult<PurchaseShipResponse> {
+    respond(
+        state
+            .client
+            .purchase_ship(request.ship_type, request.waypoint_symbol)
+            .await,
+    )
+}
+
+async fn dock_ship(State(state): State<Arc<MockApiState>>, Path(ship_symbol): Path<String>) -> ApiResult<DockShipResponse> {
+    let ship_symbol = known_ship(&state, ship_symbol).await?;
+    respond(state.client.dock_ship(ship_symbol).await)
+}
+
+async fn orbit_ship(State(state): State<Arc<MockApiState>>, Path(ship_symbol): Path<String>) -> ApiResult<OrbitShipResponse> {
+    let ship_symbol = known_ship(&state, ship_symbol).await?;
+    respond(state.client.orbit_ship(ship_symbol).await)
+}
+
+async fn set_flight_mode(
+    State(state): State<Arc<MockApiState>>,
+    Path(ship_symbol): Path<String>,
+    Json(request): Json<PatchShipNavRequest>,
+) -> ApiResult<SetFlightModeResponse> {
+    let ship_symbol = known_ship(&state, ship_symbol).await?;
+    respond(
+        state
+            .client
+            .set_flight_mode(ship_symbol, &request.flight_mode)
+            .await,
+    )
+}
+
+async fn navigate(
+    State(state): State<Arc<MockApiState>>,
+    Path(ship_symbol): Path<String>,
+    Json(request): Json<NavigateShipRequest>,
+) -> ApiResult<NavigateShipResponse> {
+    let ship_symbol = known_ship(&state, ship_symbol).await?;
+    respond(
+        state
+            .client
+            .navigate(ship_symbol, &request.waypoint_symbol)
+            .await,
+    )
+}
+
+async fn refuel(
+    State(state): State<Arc<MockApiState>>,
+    Path(ship_symbol): Path<String>,
+    Json(request): Json<RefuelShipRequest>,
+) -> ApiResult<RefuelShipResponse> {
+    let ship_symbol = known_ship(&state, ship_symbol).await?;
+    respond(
+        state
+            .client
+            .refuel(ship_symbol, request.amount, request.from_cargo)
+            .await,
+    )
+}
+
+async fn purchase_trade_good(
+    State(state): State<Arc<MockApiState>>,
+    Path(ship_symbol): Path<String>,
+    Json(request): Json<PurchaseTradeGoodRequest>,
+) -> ApiResult<PurchaseTradeGoodResponse> {
+    let ship_symbol = known_ship(&state, ship_symbol).await?;
+    respond(
+        state
+            .client
+            .purchase_trade_good(ship_symbol, request.units, request.symbol)
+            .await,
+    )
+}
+
+async fn sell_trade_good(
+    State(state): State<Arc<MockApiState>>,
+    Path(ship_symbol): Path<String>,
+    Json(request): Json<SellTradeGoodRequest>,
+) -> ApiResult<SellTradeGoodResponse> {
+    let ship_symbol = known_ship(&state, ship_symbol).await?;
+    respond(
+        state
+            .client
+            .sell_trade_good(ship_symbol, request.units, request.symbol)
+            .await,
+    )
+}
+
+async fn jettison_cargo(
+    State(state): State<Arc<MockApiState>>,
+    Path(ship_symbol): Path<String>,
+    Json(request): Json<JettisonCargoRequest>,
+) -> ApiResult<JettisonCargoResponse> {
+    let ship_symbol = known_ship(&state, ship_symbol).await?;
+    respond(
+        state
+            .client
+            .jettison_cargo(ship_symbol, request.symbol, request.units)
+            .await,
+    )
+}
+
+async fn transfer_cargo(
+    State(state): State<Arc<MockApiState>>,
+    Path(ship_symbol): Path<String>,
+    Json(request): Json<TransferCargoRequest>,
+) -> ApiResult<TransferCargoResponse> {
+    let ship_symbol = known_ship(&state, ship_symbol).await?;
+    respond(
+        state
+            .client
+            .transfer_cargo(ship_symbol, request.ship_symbol, request.trade_symbol, request.units)
+            .await,
+    )
+}
+
+async fn siphon_resources(State(state): State<Arc<MockApiState>>, Path(ship_symbol): Path<String>) -> ApiResult<SiphonResourcesResponse> {
+    let ship_symbol = known_ship(&state, ship_symbol).await?;
+    respond(state.client.siphon_resources(ship_symbol).await)
+}
+
+async fn survey(State(state): State<Arc<MockApiState>>, Path(ship_symbol): Path<String>) -> ApiResult<CreateSurveyResponse> {
+    let ship_symbol = known_ship(&state, ship_symbol).await?;
+    respond(state.client.survey(ship_symbol).await)
+}
+
+async fn extract_resources(State(state): State<Arc<MockApiState>>, Path(ship_symbol): Path<String>) -> ApiResult<ExtractResourcesResponse> {
+    let ship_symbol = known_ship(&state, ship_symbol).await?;
+    respond(state.client.extract_resources(ship_symbol).await)
+}
+
+async fn extract_resources_with_survey(
+    State(state): State<Arc<MockApiState>>,
+    Path(ship_symbol): Path<String>,
+    Json(survey): Json<Survey>,
+) -> ApiResult<ExtractResourcesResponse> {
+    let ship_symbol = known_ship(&state, ship_symbol).await?;
+    respond(
+        state
+            .client
+            .extract_resources_with_survey(ship_symbol, survey)
+            .await,
+    )
+}
+
+async fn get_system(State(state): State<Arc<MockApiState>>, Path(system_symbol): Path<String>) -> ApiResult<GetSystemResponse> {
+    let universe = state.client.universe.read().await;
+    match universe.systems.get(&SystemSymbol(system_symbol.clone())) {
+        Some(system) => Ok(Json(GetSystemResponse { data: system.clone() })),
+        None => Err(ApiError::new(StatusCode::NOT_FOUND, format!("System {} not found", system_symbol))),
+    }
+}
+
+async fn list_waypoints_of_system(
+    State(state): State<Arc<MockApiState>>,
+    Path(system_symbol): Path<String>,
+    Query(query): Query<PageQuery>,
+) -> ApiResult<PaginatedResponse<Waypoint>> {
+    respond(
+        state
+            .client
+            .list_waypoints_of_system_page(&SystemSymbol(system_symbol), query.to_pagination_input())
+            .await,
+    )
+}
+
+async fn get_marketplace(State(state): State<Arc<MockApiState>>, Path((_, waypoint_symbol)): Path<(String, String)>) -> ApiResult<GetMarketResponse> {
+    let waypoint_symbol = WaypointSymbol(waypoint_symbol);
+    if !state
+        .client
+        .universe
+        .read()
+        .await
+        .marketplaces
+        .contains_key(&waypoint_symbol)
+    {
+        return Err(ApiError::new(StatusCode::NOT_FOUND, format!("Marketplace {} not found", waypoint_symbol.0)));
+    }
+    respond(state.client.get_marketplace(waypoint_symbol).await)
+}
+
+async fn get_shipyard(State(state): State<Arc<MockApiState>>, Path((_, waypoint_symbol)): Path<(String, String)>) -> ApiResult<GetShipyardResponse> {
+    let waypoint_symbol = WaypointSymbol(waypoint_symbol);
+    if !state
+        .client
+        .universe
+        .read()
+        .await
+        .shipyards
+        .contains_key(&waypoint_symbol)
+    {
+        return Err(ApiError::new(StatusCode::NOT_FOUND, format!("Shipyard {} not found", waypoint_symbol.0)));
+    }
+    respond(state.client.get_shipyard(waypoint_symbol).await)
+}
+
+async fn get_jump_gate(State(state): State<Arc<MockApiState>>, Path((_, waypoint_symbol)): Path<(String, String)>) -> ApiResult<GetJumpGateResponse> {
+    let waypoint_symbol = WaypointSymbol(waypoint_symbol);
+    if !state
+        .client
+        .universe
+        .read()
+        .await
+        .jump_gates
+        .contains_key(&waypoint_symbol)
+    {
+        return Err(ApiError::new(StatusCode::NOT_FOUND, format!("Jump gate {} not found", waypoint_symbol.0)));
+    }
+    respond(state.client.get_jump_gate(waypoint_symbol).await)
+}
+
+async fn get_construction_site(
+    State(state): State<Arc<MockApiState>>,
+    Path((_, waypoint_symbol)): Path<(String, String)>,
+) -> ApiResult<GetConstructionResponse> {
+    let waypoint_symbol = known_construction_site(&state, waypoint_symbol).await?;
+    respond(state.client.get_construction_site(&waypoint_symbol).await)
+}
+
+async fn supply_construction_site(
+    State(state): State<Arc<MockApiState>>,
+    Path((_, waypoint_symbol)): Path<(String, String)>,
+    Json(request): Json<SupplyConstructionSiteRequest>,
+) -> ApiResult<SupplyConstructionSiteResponse> {
+    let waypoint_symbol = known_construction_site(&state, waypoint_symbol).await?;
+    let ship_symbol = known_ship(&state, request.ship_symbol.0).await?;
+    respond(
+        state
+            .client
+            .supply_construction_site(ship_symbol, request.units, request.trade_symbol, waypoint_symbol)
+            .await,
+    )
+}
+
+async fn known_construction_site(state: &MockApiState, waypoint_symbol: String) -> Result<WaypointSymbol, ApiError> {
+    let waypoint_symbol = WaypointSymbol(waypoint_symbol);
+    if state
+        .client
+        .universe
+        .read()
+        .await
+        .construction_sites
+        .contains_key(&waypoint_symbol)
+    {
+        Ok(waypoint_symbol)
+    } else {
+        Err(ApiError::new(
+            StatusCode::NOT_FOUND,
+            format!("Construction site {} not found", waypoint_symbol.0),
+        ))
+    }
+}
+
+async fn get_supply_chain(State(state): State<Arc<MockApiState>>) -> ApiResult<GetSupplyChainResponse> {
+    respond(state.client.get_supply_chain().await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pagination::fetch_all_pages;
+    use crate::reqwest_helpers::{create_client, ResetSignal};
+    use crate::st_client::StClient;
+    use itertools::Itertools;
+
+    fn load_universe() -> InMemoryUniverse {
+        let json_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .join("resources")
+            .join("universe_snapshot.json");
+        InMemoryUniverse::from_snapshot(json_path).expect("InMemoryUniverse::from_snapshot")
+    }
+
+    #[tokio::test]
+    async fn production_client_pages_through_waypoints_despite_rate_limits() -> Result<()> {
+        let faults = FaultInjection {
+            rate_limited_ratio: 0.3,
+            ..FaultInjection::default()
+        };
+        let server = MockApiServer::start(load_universe(), faults).await?;
+        let client = StClient::try_with_base_url(create_client(Some("token".to_string()), None), &server.base_url)?;
+
+        let home_system = client.get_agent().await?.data.headquarters.system_symbol();
+        let waypoints = fetch_all_pages(|pagination_input| client.list_waypoints_of_system_page(&home_system, pagination_input)).await?;
+
+        let universe = server.universe();
+        let expected_waypoints = universe.read().await.systems[&home_system].waypoints.len();
+        assert_eq!(waypoints.len(), expected_waypoints);
+        assert_eq!(waypoints.iter().map(|wp| &wp.symbol).unique().count(), expected_waypoints);
+
+        // every page got through eventually - the retry middleware took care of the injected 429s
+        let num_pages = expected_waypoints.div_ceil(20) as u64;
+        assert_eq!(server.num_requests(), 1 + num_pages + server.num_injected_faults());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn production_client_retries_requests_whose_connection_got_dropped() -> Result<()> {
+        let faults = FaultInjection {
+            dropped_connection_ratio: 0.5,
+            ..FaultInjection::default()
+        };
+        let server = MockApiServer::start(load_universe(), faults).await?;
+        let client = StClient::try_with_base_url(create_client(Some("token".to_string()), None), &server.base_url)?;
+
+        let home_system = client.get_agent().await?.data.headquarters.system_symbol();
+        let waypoints = fetch_all_pages(|pagination_input| client.list_waypoints_of_system_page(&home_system, pagination_input)).await?;
+
+        let universe = server.universe();
+        let expected_waypoints = universe.read().await.systems[&home_system].waypoints.len();
+        assert_eq!(waypoints.len(), expected_waypoints);
+        assert!(server.num_dropped_connections() > 0);
+
+        // the dropped requests never reached the api - every request got through exactly once
+        let num_pages = expected_waypoints.div_ceil(20) as u64;
+        assert_eq!(server.num_requests(), 1 + num_pages);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn unknown_symbols_are_answered_with_not_found() -> Result<()> {
+        let server = MockApiServer::start(load_universe(), FaultInjection::default()).await?;
+        let client = reqwest::Client::new();
+
+        let not_found_paths = [
+            "my/ships/FLWI_TEST-99",
+            "systems/X1-AD75/waypoints/X1-AD75-B10/market",
+            "systems/X1-AD75/waypoints/X1-AD75-A1/shipyard",
+            "systems/X1-AD75/waypoints/X1-AD75-A1/jump-gate",
+            "systems/X1-AD75/waypoints/X1-AD75-A1/construction",
+        ];
+        for path in not_found_paths {
+            let response = client
+                .get(format!("{}/{}", server.base_url, path))
+                .send()
+                .await?;
+            assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND, "{path}");
+        }
+
+        let response = client
+            .post(format!("{}/my/ships/FLWI_TEST-99/dock", server.base_url))
+            .send()
+            .await?;
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+
+        // the ship exists, but the universe rejects the action - FLWI_TEST-2 is docked already
+        let response = client
+            .post(format!("{}/my/ships/FLWI_TEST-2/dock", server.base_url))
+            .send()
+            .await?;
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+
+        // only the /v2 routes are served
+        let response = client
+            .get(server.base_url.replace("/v2", "/my/agent"))
+            .send()
+            .await?;
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn reset_detection_middleware_reports_rejected_tokens_after_a_reset() -> Result<()> {
+        let server = MockApiServer::start(load_universe(), FaultInjection::default()).await?;
+        let (reset_tx, mut reset_rx) = tokio::sync::mpsc::channel::<ResetSignal>(8);
+        let client = StClient::try_with_base_url(create_client(Some("token".to_string()), Some(reset_tx)), &server.base_url)?;
+
+        let reset_date_before = client.get_status().await?.reset_date;
+        assert!(client.get_agent().await.is_ok());
+
+        server.trigger_reset();
+
+        assert!(client.get_agent().await.is_err());
+        assert!(matches!(reset_rx.try_recv(), Ok(ResetSignal::TokenExpired)));
+        assert_ne!(client.get_status().await?.reset_date, reset_date_before);
+
+        Ok(())
+    }
+}
@@ -1,2 +1,3 @@
 pub mod universe_server;
+pub mod mock_api_server;
 mod universe_snapshot;
@@ -14,7 +14,7 @@ use st_domain::{
     CreateSurveyResponse, CreateSurveyResponseBody, Crew, Data, DeliverCargoToContractResponse, DockShipResponse, ExtractResourcesResponse,
     ExtractResourcesResponseBody, Extraction, ExtractionYield, FactionSymbol, FlightMode, Fuel, FuelConsumed, FulfillContractResponse, GetConstructionResponse,
     GetJumpGateResponse, GetMarketResponse, GetShipyardResponse, GetSupplyChainResponse, GetSystemResponse, JettisonCargoResponse, JumpGate,
-    LabelledCoordinate, ListAgentsResponse, MarketData, Meta, ModuleType, Mount, Nav, NavAndFuelResponse, NavOnlyResponse, NavRouteWaypoint, NavStatus,
+    LabelledCoordinate, ListAgentsResponse, MarketData, Meta, Mount, Nav, NavAndFuelResponse, NavOnlyResponse, NavRouteWaypoint, NavStatus,
     NavigateShipResponse, NegotiateContractResponse, NotEnoughItemsInCargoError, OrbitShipResponse, PurchaseShipResponse, PurchaseShipResponseBody,
     PurchaseTradeGoodResponse, PurchaseTradeGoodResponseBody, RefuelShipResponse, RefuelShipResponseBody, Registration, RegistrationRequest,
     RegistrationResponse, Route, SellTradeGoodResponse, SellTradeGoodResponseBody, SetFlightModeResponse, Ship, ShipMountSymbol, ShipPurchaseTransaction,
@@ -1353,8 +1353,7 @@ impl StClientTrait for InMemoryUniverseClient {
         let read_universe = self.universe.read().await;
         //let mut _universe = self.universe.write().await;
 
-        let start_idx = pagination_input.limit * (pagination_input.page - 1);
-        let num_skip = u32::try_from(start_idx as i32 - 1).unwrap_or(0);
+        let num_skip = pagination_input.limit * (pagination_input.page.max(1) - 1);
         let all_ships = read_universe
             .ships
             .values()
@@ -1385,8 +1384,7 @@ impl StClientTrait for InMemoryUniverseClient {
         let guard = self.universe.read().await;
         //let mut _universe = self.universe.write().await;
 
-        let start_idx = pagination_input.limit * (pagination_input.page - 1);
-        let num_skip = u32::try_from(start_idx as i32 - 1).unwrap_or(0);
+        let num_skip = pagination_input.limit * (pagination_input.page.max(1) - 1);
 
         let system_waypoints = guard
             .systems
@@ -1539,16 +1537,7 @@ fn create_ship_from_shipyard_ship(
 ) -> Ship {
     let ship_symbol = ShipSymbol(format!("{}-{:X}", agent_symbol.0, current_number_of_ships + 1));
     let sy_crew = shipyard_ship.crew.clone();
-    let cargo_capacity = shipyard_ship
-        .modules
-        .iter()
-        .map(|module| match module.symbol {
-            ModuleType::MODULE_CARGO_HOLD_I => module.capacity.unwrap_or_default(),
-            ModuleType::MODULE_CARGO_HOLD_II => module.capacity.unwrap_or_default(),
-            ModuleType::MODULE_CARGO_HOLD_III => module.capacity.unwrap_or_default(),
-            _ => 0,
-        })
-        .sum();
+    let cargo_capacity = shipyard_ship.cargo_capacity();
 
     let current_nav_route_waypoint = NavRouteWaypoint {
         symbol: current_waypoint.symbol.clone(),
@@ -1694,7 +1683,7 @@ fn generate_random_surveys_internal(
     surveys
 }
 
-fn get_possible_extraction_materials_by_waypoint_traits(waypoint_trait_symbols: &[WaypointTraitSymbol]) -> HashSet<TradeGoodSymbol> {
+pub(crate) fn get_possible_extraction_materials_by_waypoint_traits(waypoint_trait_symbols: &[WaypointTraitSymbol]) -> HashSet<TradeGoodSymbol> {
     let trade_symbols_by_waypoint_trait_map = trade_symbols_by_waypoint_trait();
 
     waypoint_trait_symbols
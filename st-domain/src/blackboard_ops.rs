@@ -1,9 +1,10 @@
+use crate::cargo_flow::CargoFlowEntry;
 use crate::{
-    get_exploration_tasks_for_waypoint, Construction, Contract, CreateSurveyResponse, ExplorationTask, Extraction, JumpGate,
-    MarketData, MarketEntry, MaterializedSupplyChain, MiningOpsConfig, Ship, Shipyard, Survey, SystemSymbol, TravelAction, Waypoint, WaypointModifier,
-    WaypointSymbol,
+    get_exploration_tasks_for_waypoint, Construction, Contract, ContractEntry, ContractId, CreateSurveyResponse, ExplorationTask, Extraction, JumpGate,
+    MarketData, MarketEntry, MiningOpsConfig, Ship, Shipyard, Survey, SystemSymbol, TravelAction, Waypoint, WaypointModifier, WaypointSymbol,
 };
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use mockall::automock;
 
 #[automock]
@@ -22,6 +23,7 @@ pub trait BlackboardOps: Send + Sync {
     async fn insert_market(&self, market_data: MarketData) -> anyhow::Result<()>;
 
     async fn get_latest_market_entries(&self, system_symbol: &SystemSymbol) -> anyhow::Result<Vec<MarketEntry>>;
+    async fn get_market_history_of_waypoint(&self, waypoint_symbol: &WaypointSymbol, since: DateTime<Utc>) -> anyhow::Result<Vec<MarketEntry>>;
 
     async fn insert_jump_gate(&self, jump_gate: JumpGate) -> anyhow::Result<()>;
     async fn insert_shipyard(&self, shipyard: Shipyard) -> anyhow::Result<()>;
@@ -42,17 +44,23 @@ pub trait BlackboardOps: Send + Sync {
 
     async fn update_construction_site(&self, construction: &Construction) -> anyhow::Result<()>;
 
-    async fn get_best_survey_for_current_demand(
+    /// All valid surveys of the mining site with their expected credit value per extraction - the most valuable first.
+    async fn get_ranked_surveys_for_current_demand(
         &self,
         mining_config: &MiningOpsConfig,
-        materialized_supply_chain: &MaterializedSupplyChain,
-    ) -> anyhow::Result<Option<Survey>>;
+        haul_cost_per_unit_and_distance: f64,
+    ) -> anyhow::Result<Vec<(Survey, f64)>>;
 
     async fn mark_survey_as_exhausted(&self, survey: &Survey) -> anyhow::Result<()>;
     async fn save_survey_response(&self, create_survey_response: CreateSurveyResponse) -> anyhow::Result<()>;
     async fn log_survey_usage(&self, survey: Survey, extraction: Extraction) -> anyhow::Result<()>;
+    async fn log_cargo_flows(&self, entries: Vec<CargoFlowEntry>) -> anyhow::Result<()>;
     async fn is_survey_necessary(&self, maybe_mining_waypoint: Option<WaypointSymbol>) -> anyhow::Result<bool>;
     async fn mark_asteroid_has_reached_critical_limit(&self, mining_waypoint: &WaypointSymbol, waypoint_modifier: &WaypointModifier) -> anyhow::Result<()>;
     async fn upsert_contract(&self, system_symbol: &SystemSymbol, contract: &Contract) -> anyhow::Result<()>;
+    /// declined contracts are skipped - we don't work on them anymore
     async fn get_youngest_contract(&self, system_symbol: &SystemSymbol) -> anyhow::Result<Option<Contract>>;
+    /// unlike `get_youngest_contract` this includes declined contracts
+    async fn get_youngest_contract_entry(&self, system_symbol: &SystemSymbol) -> anyhow::Result<Option<ContractEntry>>;
+    async fn mark_contract_as_declined(&self, contract_id: &ContractId) -> anyhow::Result<()>;
 }
@@ -0,0 +1,512 @@
+use crate::budgeting::credits::Credits;
+use crate::budgeting::finance_report::compute_finance_report;
+use crate::budgeting::ledger_audit::TimestampedLedgerEntry;
+use crate::budgeting::treasury_redesign::FleetBudget;
+use crate::{Construction, FleetId, MarketEntry, ShipType};
+use chrono::{DateTime, Duration, Utc};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+/// What a fleet needs to keep its ships busy. Computed by the admiral from the ships of the fleet.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FleetCapitalDemand {
+    pub fleet_id: FleetId,
+    pub num_ships: usize,
+    pub base_budget: Credits,
+    pub base_operating_reserve: Credits,
+    /// only fleets that buy goods (trading, construction) can put more capital to work
+    pub can_deploy_additional_capital: bool,
+    /// estimated cost of the construction materials still missing - 0 for non-construction fleets
+    pub construction_need: Credits,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FleetReturn {
+    pub fleet_id: FleetId,
+    pub realized_profit: Credits,
+    pub deployed_capital: Credits,
+    /// realized profit in the observation window relative to the deployed capital
+    pub roi: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ShipPurchaseCandidate {
+    pub ship_type: ShipType,
+    pub beneficiary_fleet_id: FleetId,
+    pub price: Credits,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CapitalAllocationInput<'a> {
+    pub agent_credits: Credits,
+    pub current_budgets: &'a HashMap<FleetId, FleetBudget>,
+    pub demands: &'a [FleetCapitalDemand],
+    pub fleet_returns: &'a HashMap<FleetId, FleetReturn>,
+    pub maybe_next_ship_purchase: Option<ShipPurchaseCandidate>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CapitalAllocationDecision {
+    pub fleet_id: FleetId,
+    pub new_total_capital: Credits,
+    pub new_operating_reserve: Credits,
+    pub reason: String,
+}
+
+/// Decides how the agent credits are split among the fleets.
+/// The decisions are applied by the treasurer and end up in the ledger (including the reason).
+pub trait CapitalAllocationPolicy: Send + Sync + Debug {
+    fn name(&self) -> String;
+
+    /// only returns decisions for fleets whose budget or operating reserve changes
+    fn allocate(&self, input: &CapitalAllocationInput) -> Vec<CapitalAllocationDecision>;
+}
+
+/// Sums up the realized profit per fleet since `since` (see `compute_finance_report`) and relates it to the fleets current budget.
+pub fn compute_fleet_returns(
+    entries: &[TimestampedLedgerEntry],
+    current_budgets: &HashMap<FleetId, FleetBudget>,
+    since: DateTime<Utc>,
+) -> HashMap<FleetId, FleetReturn> {
+    let report = compute_finance_report(entries, Duration::hours(1));
+    let profit_per_fleet: HashMap<FleetId, Credits> = report
+        .fleet_profits
+        .iter()
+        .filter(|b| b.bucket_start >= since)
+        .into_group_map_by(|b| b.fleet_id.clone())
+        .into_iter()
+        .map(|(fleet_id, buckets)| {
+            let profit = buckets
+                .iter()
+                .fold(Credits::default(), |acc, b| acc + b.realized_profit);
+            (fleet_id, profit)
+        })
+        .collect();
+
+    current_budgets
+        .iter()
+        .map(|(fleet_id, budget)| {
+            let realized_profit = profit_per_fleet.get(fleet_id).cloned().unwrap_or_default();
+            // fleets without a budget (e.g. miners) still tie up the operating reserve
+            let deployed_capital = budget.budget.max(budget.operating_reserve);
+            let roi = if deployed_capital.is_positive() {
+                realized_profit.0 as f64 / deployed_capital.0 as f64
+            } else {
+                0.0
+            };
+            (
+                fleet_id.clone(),
+                FleetReturn {
+                    fleet_id: fleet_id.clone(),
+                    realized_profit,
+                    deployed_capital,
+                    roi,
+                },
+            )
+        })
+        .collect()
+}
+
+/// Estimates what the missing construction materials cost at the cheapest market we know of.
+/// Materials without a known market are ignored.
+pub fn estimate_remaining_construction_cost(construction: &Construction, market_entries: &[MarketEntry]) -> Credits {
+    construction
+        .materials
+        .iter()
+        .filter_map(|material| {
+            let missing_units = material.required.saturating_sub(material.fulfilled);
+            let cheapest_purchase_price = market_entries
+                .iter()
+                .flat_map(|me| me.market_data.trade_goods.clone().unwrap_or_default())
+                .filter(|tg| tg.symbol == material.trade_symbol)
+                .map(|tg| tg.purchase_price)
+                .min()?;
+            Some(Credits::from(cheapest_purchase_price) * missing_units)
+        })
+        .fold(Credits::default(), |acc, cost| acc + cost)
+}
+
+/// Funds the fleets in this order:
+/// 1. every fleet gets what it needs to operate its ships - scaled down if the credits don't cover it, but never below its operating reserve
+/// 2. the next ship purchase is kept in the treasury, unless a comparable ship is expected to return less than the best fleet
+/// 3. construction fleets get the estimated cost of the missing materials
+/// 4. the rest goes to the fleets that can put it to work, weighted by their realized ROI
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RoiCapitalAllocationPolicy {
+    /// caps the additional budget (on top of the base budget) per ship
+    pub max_additional_budget_per_ship: Credits,
+    /// keep a buffer in the treasury for unexpected expenses
+    pub treasury_reserve: Credits,
+}
+
+impl Default for RoiCapitalAllocationPolicy {
+    fn default() -> Self {
+        Self {
+            max_additional_budget_per_ship: 500_000.into(),
+            treasury_reserve: 10_000.into(),
+        }
+    }
+}
+
+impl CapitalAllocationPolicy for RoiCapitalAllocationPolicy {
+    fn name(&self) -> String {
+        "roi".to_string()
+    }
+
+    fn allocate(&self, input: &CapitalAllocationInput) -> Vec<CapitalAllocationDecision> {
+        // the operating reserves come off the top - the part of the base budgets above them shares what's left
+        let reserve_total = input
+            .demands
+            .iter()
+            .fold(Credits::default(), |acc, d| acc + d.base_operating_reserve);
+        let above_reserve = |d: &FleetCapitalDemand| (d.base_budget - d.base_operating_reserve).max(Credits::default());
+        let above_reserve_total = input
+            .demands
+            .iter()
+            .fold(Credits::default(), |acc, d| acc + above_reserve(d));
+        let available_for_base_budgets = (input.agent_credits - reserve_total - self.treasury_reserve).max(Credits::default());
+        let base_budget_ratio = if above_reserve_total > available_for_base_budgets {
+            available_for_base_budgets.0 as f64 / above_reserve_total.0 as f64
+        } else {
+            1.0
+        };
+
+        let mut reasons: HashMap<FleetId, Vec<String>> = HashMap::new();
+        let mut base_budgets: HashMap<FleetId, Credits> = HashMap::new();
+        let mut additional_budgets: HashMap<FleetId, Credits> = HashMap::new();
+
+        for d in input.demands.iter() {
+            let granted = d.base_operating_reserve + Credits::from((above_reserve(d).0 as f64 * base_budget_ratio) as i64);
+            base_budgets.insert(d.fleet_id.clone(), granted);
+            let reason = if granted < d.base_budget {
+                format!(
+                    "base budget {granted} of {} for {} ships (credits don't cover all base budgets)",
+                    d.base_budget, d.num_ships
+                )
+            } else {
+                format!("base budget {} for {} ships", d.base_budget, d.num_ships)
+            };
+            reasons.entry(d.fleet_id.clone()).or_default().push(reason);
+        }
+
+        let granted_base_total = base_budgets
+            .values()
+            .fold(Credits::default(), |acc, granted| acc + *granted);
+        let mut spendable = (input.agent_credits - granted_base_total - self.treasury_reserve).max(Credits::default());
+
+        let roi_of = |fleet_id: &FleetId| {
+            input
+                .fleet_returns
+                .get(fleet_id)
+                .map(|r| r.roi)
+                .unwrap_or_default()
+        };
+        let best_fleet_roi = input
+            .demands
+            .iter()
+            .filter(|d| d.can_deploy_additional_capital)
+            .map(|d| roi_of(&d.fleet_id))
+            .fold(0.0, f64::max);
+
+        if let Some(ship_purchase) = &input.maybe_next_ship_purchase {
+            // a new ship is expected to earn what the ships of its fleet earn on average
+            let maybe_marginal_ship_roi = input.demands.iter().find_map(|d| {
+                let fleet_return = input.fleet_returns.get(&d.fleet_id)?;
+                (d.fleet_id == ship_purchase.beneficiary_fleet_id && d.num_ships > 0 && ship_purchase.price.is_positive())
+                    .then(|| fleet_return.realized_profit.0 as f64 / d.num_ships as f64 / ship_purchase.price.0 as f64)
+            });
+
+            match maybe_marginal_ship_roi {
+                Some(marginal_ship_roi) if marginal_ship_roi < best_fleet_roi => {
+                    add_reason_for_all_fleets(
+                        &mut reasons,
+                        format!(
+                            "not reserving {} for {} (marginal ROI {:.3} < best fleet ROI {:.3})",
+                            ship_purchase.price, ship_purchase.ship_type, marginal_ship_roi, best_fleet_roi
+                        ),
+                    );
+                }
+                _ => {
+                    spendable = (spendable - ship_purchase.price).max(Credits::default());
+                    add_reason_for_all_fleets(
+                        &mut reasons,
+                        format!(
+                            "reserved {} in treasury for {} (marginal ROI {} vs best fleet ROI {:.3})",
+                            ship_purchase.price,
+                            ship_purchase.ship_type,
+                            maybe_marginal_ship_roi
+                                .map(|roi| format!("{roi:.3}"))
+                                .unwrap_or("unknown".to_string()),
+                            best_fleet_roi
+                        ),
+                    );
+                }
+            }
+        }
+
+        for d in input
+            .demands
+            .iter()
+            .filter(|d| d.construction_need.is_positive())
+            .sorted_by_key(|d| d.fleet_id.clone())
+        {
+            let granted = d.construction_need.min(spendable);
+            spendable -= granted;
+            *additional_budgets.entry(d.fleet_id.clone()).or_default() += granted;
+            reasons
+                .entry(d.fleet_id.clone())
+                .or_default()
+                .push(format!("construction budget {granted} of estimated {} needed", d.construction_need));
+        }
+
+        let roi_weighted_fleets = input
+            .demands
+            .iter()
+            .filter(|d| d.can_deploy_additional_capital && d.construction_need.is_zero() && roi_of(&d.fleet_id) > 0.0)
+            .sorted_by_key(|d| d.fleet_id.clone())
+            .collect_vec();
+        let total_roi: f64 = roi_weighted_fleets
+            .iter()
+            .map(|d| roi_of(&d.fleet_id))
+            .sum();
+
+        if total_roi > 0.0 {
+            let distributable = spendable;
+            for d in roi_weighted_fleets {
+                let roi = roi_of(&d.fleet_id);
+                let share = Credits::from((distributable.0 as f64 * roi / total_roi) as i64);
+                let cap = self.max_additional_budget_per_ship * d.num_ships as u32;
+                let granted = share.min(cap).min(spendable);
+                spendable -= granted;
+                *additional_budgets.entry(d.fleet_id.clone()).or_default() += granted;
+                reasons.entry(d.fleet_id.clone()).or_default().push(format!(
+                    "additional budget {granted} for ROI {roi:.3} ({:.0}% of total ROI)",
+                    roi / total_roi * 100.0
+                ));
+            }
+        }
+
+        input
+            .demands
+            .iter()
+            .sorted_by_key(|d| d.fleet_id.clone())
+            .filter_map(|d| {
+                let new_operating_reserve = d.base_operating_reserve;
+                let new_total_capital = (base_budgets.get(&d.fleet_id).cloned().unwrap_or_default()
+                    + additional_budgets
+                        .get(&d.fleet_id)
+                        .cloned()
+                        .unwrap_or_default())
+                .max(new_operating_reserve);
+                let current = input.current_budgets.get(&d.fleet_id)?;
+                let has_changed = current.budget != new_total_capital || current.operating_reserve != new_operating_reserve;
+                has_changed.then(|| CapitalAllocationDecision {
+                    fleet_id: d.fleet_id.clone(),
+                    new_total_capital,
+                    new_operating_reserve,
+                    reason: format!(
+                        "{} policy: {}; budget {} -> {}, operating reserve {} -> {}",
+                        self.name(),
+                        reasons
+                            .get(&d.fleet_id)
+                            .cloned()
+                            .unwrap_or_default()
+                            .join("; "),
+                        current.budget,
+                        new_total_capital,
+                        current.operating_reserve,
+                        new_operating_reserve
+                    ),
+                })
+            })
+            .collect_vec()
+    }
+}
+
+fn add_reason_for_all_fleets(reasons: &mut HashMap<FleetId, Vec<String>>, reason: String) {
+    for fleet_reasons in reasons.values_mut() {
+        fleet_reasons.push(reason.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn demand(id: i32, num_ships: usize, base_budget: i64, can_deploy_additional_capital: bool, construction_need: i64) -> FleetCapitalDemand {
+        FleetCapitalDemand {
+            fleet_id: FleetId(id),
+            num_ships,
+            base_budget: base_budget.into(),
+            base_operating_reserve: (1_000 * num_ships as i64).into(),
+            can_deploy_additional_capital,
+            construction_need: construction_need.into(),
+        }
+    }
+
+    fn fleet_return(id: i32, realized_profit: i64, deployed_capital: i64) -> (FleetId, FleetReturn) {
+        (
+            FleetId(id),
+            FleetReturn {
+                fleet_id: FleetId(id),
+                realized_profit: realized_profit.into(),
+                deployed_capital: deployed_capital.into(),
+                roi: realized_profit as f64 / deployed_capital as f64,
+            },
+        )
+    }
+
+    fn budgets(ids: &[i32]) -> HashMap<FleetId, FleetBudget> {
+        ids.iter()
+            .map(|id| (FleetId(*id), FleetBudget::default()))
+            .collect()
+    }
+
+    #[test]
+    fn distributes_surplus_by_roi_and_funds_construction_first() {
+        let policy = RoiCapitalAllocationPolicy {
+            max_additional_budget_per_ship: 1_000_000.into(),
+            treasury_reserve: 0.into(),
+        };
+        let demands = vec![
+            demand(1, 2, 150_000, true, 0),
+            demand(2, 2, 150_000, true, 0),
+            demand(3, 1, 75_000, true, 100_000),
+            demand(4, 3, 3_000, false, 0),
+        ];
+        let fleet_returns = HashMap::from([
+            fleet_return(1, 30_000, 150_000),
+            fleet_return(2, 10_000, 150_000),
+            fleet_return(4, 50_000, 3_000),
+        ]);
+        let current_budgets = budgets(&[1, 2, 3, 4]);
+
+        let decisions = policy.allocate(&CapitalAllocationInput {
+            agent_credits: 778_000.into(),
+            current_budgets: &current_budgets,
+            demands: &demands,
+            fleet_returns: &fleet_returns,
+            maybe_next_ship_purchase: None,
+        });
+
+        // 778k - 378k base = 400k surplus; 100k for construction; 300k split 3:1 by ROI. The miners can't use more capital.
+        assert_eq!(
+            decisions
+                .iter()
+                .map(|d| (d.fleet_id.0, d.new_total_capital))
+                .collect_vec(),
+            vec![(1, 375_000.into()), (2, 225_000.into()), (3, 175_000.into()), (4, 3_000.into())]
+        );
+        assert!(decisions[2].reason.contains("construction budget"));
+    }
+
+    #[test]
+    fn reserves_credits_for_ship_purchase_only_if_it_pays_off() {
+        let policy = RoiCapitalAllocationPolicy {
+            max_additional_budget_per_ship: 1_000_000.into(),
+            treasury_reserve: 0.into(),
+        };
+        let demands = vec![demand(1, 2, 150_000, true, 0), demand(2, 2, 0, false, 0)];
+        let current_budgets = budgets(&[1, 2]);
+        let next_ship_purchase = Some(ShipPurchaseCandidate {
+            ship_type: ShipType::SHIP_MINING_DRONE,
+            beneficiary_fleet_id: FleetId(2),
+            price: 50_000.into(),
+        });
+
+        // each mining drone earned 40k - way better than the traders
+        let good_miners = HashMap::from([fleet_return(1, 15_000, 150_000), fleet_return(2, 80_000, 2_000)]);
+        let decisions = policy.allocate(&CapitalAllocationInput {
+            agent_credits: 300_000.into(),
+            current_budgets: &current_budgets,
+            demands: &demands,
+            fleet_returns: &good_miners,
+            maybe_next_ship_purchase: next_ship_purchase.clone(),
+        });
+        // 300k - 4k operating reserves - 148k base budget above the reserve - 50k for the drone
+        assert_eq!(decisions[0].new_total_capital, 248_000.into());
+        assert!(decisions[0].reason.contains("reserved 50000c"));
+
+        // mining drones barely earn anything - the traders get the money
+        let bad_miners = HashMap::from([fleet_return(1, 15_000, 150_000), fleet_return(2, 1_000, 2_000)]);
+        let decisions = policy.allocate(&CapitalAllocationInput {
+            agent_credits: 300_000.into(),
+            current_budgets: &current_budgets,
+            demands: &demands,
+            fleet_returns: &bad_miners,
+            maybe_next_ship_purchase: next_ship_purchase,
+        });
+        assert_eq!(decisions[0].new_total_capital, 298_000.into());
+        assert!(decisions[0].reason.contains("not reserving"));
+        // the miners have no base budget, but keep their operating reserve
+        assert_eq!(decisions[1].new_total_capital, 2_000.into());
+    }
+
+    #[test]
+    fn keeps_the_operating_reserves_if_the_base_budgets_exceed_the_credits() {
+        let policy = RoiCapitalAllocationPolicy {
+            max_additional_budget_per_ship: 1_000_000.into(),
+            treasury_reserve: 10_000.into(),
+        };
+        let demands = vec![
+            demand(1, 2, 150_000, true, 0),
+            demand(2, 2, 50_000, true, 0),
+            demand(3, 4, 4_000, false, 0),
+        ];
+        let fleet_returns = HashMap::from([fleet_return(1, 30_000, 150_000), fleet_return(2, 10_000, 50_000)]);
+        let current_budgets = budgets(&[1, 2, 3]);
+
+        let decisions = policy.allocate(&CapitalAllocationInput {
+            agent_credits: 102_000.into(),
+            current_budgets: &current_budgets,
+            demands: &demands,
+            fleet_returns: &fleet_returns,
+            maybe_next_ship_purchase: None,
+        });
+
+        // 102k - 8k operating reserves - 10k treasury reserve = 84k for the 148k + 48k above the reserves
+        assert_eq!(
+            decisions
+                .iter()
+                .map(|d| (d.fleet_id.0, d.new_total_capital, d.new_operating_reserve))
+                .collect_vec(),
+            vec![
+                (1, 65_428.into(), 2_000.into()),
+                (2, 22_571.into(), 2_000.into()),
+                (3, 4_000.into(), 4_000.into())
+            ]
+        );
+        assert!(decisions[0]
+            .reason
+            .contains("credits don't cover all base budgets"));
+        let total = decisions
+            .iter()
+            .fold(Credits::default(), |acc, d| acc + d.new_total_capital);
+        assert!(total <= Credits::from(102_000 - 10_000));
+    }
+
+    #[test]
+    fn no_decisions_if_nothing_changes() {
+        let policy = RoiCapitalAllocationPolicy::default();
+        let demands = vec![demand(1, 2, 150_000, true, 0)];
+        let current_budgets = HashMap::from([(
+            FleetId(1),
+            FleetBudget {
+                budget: 150_000.into(),
+                operating_reserve: 2_000.into(),
+                ..Default::default()
+            },
+        )]);
+
+        let decisions = policy.allocate(&CapitalAllocationInput {
+            agent_credits: 500_000.into(),
+            current_budgets: &current_budgets,
+            demands: &demands,
+            fleet_returns: &HashMap::new(),
+            maybe_next_ship_purchase: None,
+        });
+        assert!(decisions.is_empty());
+    }
+}
@@ -0,0 +1,317 @@
+use crate::budgeting::credits::Credits;
+use crate::budgeting::ledger_audit::TimestampedLedgerEntry;
+use crate::budgeting::treasury_redesign::{FinanceTicketDetails, Income, LedgerEntry, PurchaseCargoReason};
+use crate::{ContractEntry, ContractId, TradeGoodSymbol};
+use chrono::{DateTime, Duration, Utc};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ContractOutcome {
+    pub contract_id: ContractId,
+    pub faction_symbol: String,
+    pub trade_goods: Vec<TradeGoodSymbol>,
+    pub accepted: bool,
+    pub fulfilled: bool,
+    /// we declined the offer because it wasn't profitable
+    pub declined: bool,
+    /// on_accepted + on_fulfilled according to the contract terms
+    pub promised_payment: Credits,
+    /// contract income that has actually been logged
+    pub payout: Credits,
+    /// actual purchase costs of the cargo bought for this contract
+    pub spend: Credits,
+    pub realized_profit: Credits,
+    pub units_delivered: u32,
+    /// when the contract was accepted - or when its first ticket was created, if the acceptance hasn't been logged
+    pub started_at: Option<DateTime<Utc>>,
+    pub last_delivery_at: Option<DateTime<Utc>>,
+    pub fulfilled_at: Option<DateTime<Utc>>,
+}
+
+impl ContractOutcome {
+    /// time from the start of the contract until it was fulfilled
+    pub fn duration(&self) -> Option<Duration> {
+        match (self.started_at, self.fulfilled_at) {
+            (Some(started_at), Some(fulfilled_at)) => Some(fulfilled_at - started_at),
+            _ => None,
+        }
+    }
+
+    /// time from the start of the contract until the last cargo delivery
+    pub fn delivery_duration(&self) -> Option<Duration> {
+        match (self.started_at, self.last_delivery_at) {
+            (Some(started_at), Some(last_delivery_at)) => Some(last_delivery_at - started_at),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct ContractLedgerSummary {
+    payout: Credits,
+    spend: Credits,
+    units_delivered: u32,
+    accepted_at: Option<DateTime<Utc>>,
+    first_ticket_created_at: Option<DateTime<Utc>>,
+    last_delivery_at: Option<DateTime<Utc>>,
+    fulfilled_at: Option<DateTime<Utc>>,
+}
+
+/// Builds the outcome of each contract from the ledger entries tagged with its contract id:
+/// - contract income (accepted and fulfilled rewards)
+/// - completed purchases with `PurchaseCargoReason::Contract`
+/// - completed contract deliveries
+///
+/// Contracts we never accepted show up without spend and payout. The newest contract comes first.
+pub fn compute_contract_outcomes(contracts: &[ContractEntry], entries: &[TimestampedLedgerEntry]) -> Vec<ContractOutcome> {
+    let mut summaries: HashMap<ContractId, ContractLedgerSummary> = HashMap::new();
+
+    for TimestampedLedgerEntry { entry, created_at } in entries {
+        match entry {
+            LedgerEntry::IncomeLogged { income, .. } => match income {
+                Income::ContractAccepted { contract_id, accepted_reward } => {
+                    let summary = summaries.entry(contract_id.clone()).or_default();
+                    summary.payout += *accepted_reward;
+                    summary.accepted_at = Some(*created_at);
+                }
+                Income::ContractFulfilled { contract_id, fulfilled_reward } => {
+                    let summary = summaries.entry(contract_id.clone()).or_default();
+                    summary.payout += *fulfilled_reward;
+                    summary.fulfilled_at = Some(*created_at);
+                }
+            },
+            LedgerEntry::TicketCreated { ticket_details, .. } => {
+                if let Some(contract_id) = contract_id_of_ticket(&ticket_details.details) {
+                    let summary = summaries.entry(contract_id.clone()).or_default();
+                    summary.first_ticket_created_at = summary.first_ticket_created_at.or(Some(*created_at));
+                }
+            }
+            LedgerEntry::TicketCompleted {
+                finance_ticket,
+                actual_units,
+                total,
+                ..
+            } => match &finance_ticket.details {
+                FinanceTicketDetails::PurchaseTradeGoods(d) => {
+                    if let Some(PurchaseCargoReason::Contract(contract_id)) = &d.purchase_cargo_reason {
+                        summaries.entry(contract_id.clone()).or_default().spend += total.abs();
+                    }
+                }
+                FinanceTicketDetails::DeliverContractCargo(d) => {
+                    let summary = summaries.entry(d.contract_id.clone()).or_default();
+                    summary.units_delivered += actual_units;
+                    summary.last_delivery_at = Some(*created_at);
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    contracts
+        .iter()
+        .sorted_by_key(|entry| std::cmp::Reverse(entry.created_at))
+        .map(|entry| {
+            let contract = &entry.contract;
+            let summary = summaries.remove(&contract.id).unwrap_or_default();
+            ContractOutcome {
+                contract_id: contract.id.clone(),
+                faction_symbol: contract.faction_symbol.clone(),
+                trade_goods: contract
+                    .terms
+                    .deliver
+                    .iter()
+                    .map(|delivery| delivery.trade_symbol.clone())
+                    .unique()
+                    .collect_vec(),
+                accepted: contract.accepted,
+                fulfilled: contract.fulfilled,
+                declined: entry.declined_at.is_some(),
+                promised_payment: (contract.terms.payment.on_accepted + contract.terms.payment.on_fulfilled).into(),
+                payout: summary.payout,
+                spend: summary.spend,
+                realized_profit: summary.payout - summary.spend,
+                units_delivered: summary.units_delivered,
+                started_at: summary.accepted_at.or(summary.first_ticket_created_at),
+                last_delivery_at: summary.last_delivery_at,
+                fulfilled_at: summary.fulfilled_at,
+            }
+        })
+        .collect_vec()
+}
+
+fn contract_id_of_ticket(details: &FinanceTicketDetails) -> Option<&ContractId> {
+    match details {
+        FinanceTicketDetails::PurchaseTradeGoods(d) => match &d.purchase_cargo_reason {
+            Some(PurchaseCargoReason::Contract(contract_id)) => Some(contract_id),
+            _ => None,
+        },
+        FinanceTicketDetails::DeliverContractCargo(d) => Some(&d.contract_id),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::budgeting::treasury_redesign::{DeliverCargoContractTicketDetails, FinanceTicket, PurchaseTradeGoodsTicketDetails};
+    use crate::{Contract, ContractTerms, Delivery, FleetId, Payment, ShipSymbol, SystemSymbol, WaypointSymbol};
+    use chrono::TimeZone;
+
+    fn ts(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 6, 1, hour, minute, 0).unwrap()
+    }
+
+    fn contract_entry(id: &str, created_at: DateTime<Utc>, accepted: bool, fulfilled: bool) -> ContractEntry {
+        ContractEntry {
+            system_symbol: SystemSymbol("X1-FOO".to_string()),
+            contract: Contract {
+                id: ContractId(id.to_string()),
+                faction_symbol: "COSMIC".to_string(),
+                contract_type: "PROCUREMENT".to_string(),
+                terms: ContractTerms {
+                    deadline: created_at + Duration::days(7),
+                    payment: Payment {
+                        on_accepted: 2_000,
+                        on_fulfilled: 10_000,
+                    },
+                    deliver: vec![Delivery {
+                        trade_symbol: TradeGoodSymbol::IRON,
+                        destination_symbol: WaypointSymbol("X1-FOO-B1".to_string()),
+                        units_required: 20,
+                        units_fulfilled: if fulfilled { 20 } else { 0 },
+                    }],
+                },
+                accepted,
+                fulfilled,
+                deadline_to_accept: created_at + Duration::days(1),
+            },
+            created_at,
+            updated_at: created_at,
+            declined_at: None,
+        }
+    }
+
+    #[test]
+    fn builds_contract_outcomes_from_ledger_entries() {
+        let fleet_id = FleetId(1);
+        let contract_id = ContractId("c-1".to_string());
+
+        let purchase_ticket = FinanceTicket {
+            ticket_id: Default::default(),
+            fleet_id: fleet_id.clone(),
+            ship_symbol: ShipSymbol("FLWI-1".to_string()),
+            details: FinanceTicketDetails::PurchaseTradeGoods(PurchaseTradeGoodsTicketDetails {
+                waypoint_symbol: WaypointSymbol("X1-FOO-A1".to_string()),
+                trade_good: TradeGoodSymbol::IRON,
+                expected_price_per_unit: 100.into(),
+                quantity: 20,
+                expected_total_purchase_price: 2_000.into(),
+                purchase_cargo_reason: Some(PurchaseCargoReason::Contract(contract_id.clone())),
+            }),
+            allocated_credits: 2_000.into(),
+        };
+        let delivery_ticket = FinanceTicket {
+            ticket_id: Default::default(),
+            fleet_id: fleet_id.clone(),
+            ship_symbol: ShipSymbol("FLWI-1".to_string()),
+            details: FinanceTicketDetails::DeliverContractCargo(DeliverCargoContractTicketDetails {
+                waypoint_symbol: WaypointSymbol("X1-FOO-B1".to_string()),
+                trade_good: TradeGoodSymbol::IRON,
+                quantity: 20,
+                contract_id: contract_id.clone(),
+            }),
+            allocated_credits: 0.into(),
+        };
+
+        let entries = vec![
+            (
+                ts(10, 0),
+                LedgerEntry::IncomeLogged {
+                    fleet_id: fleet_id.clone(),
+                    income: Income::ContractAccepted {
+                        contract_id: contract_id.clone(),
+                        accepted_reward: 2_000.into(),
+                    },
+                },
+            ),
+            (
+                ts(10, 1),
+                LedgerEntry::TicketCreated {
+                    fleet_id: fleet_id.clone(),
+                    ticket_details: purchase_ticket.clone(),
+                },
+            ),
+            (
+                ts(10, 10),
+                LedgerEntry::TicketCompleted {
+                    fleet_id: fleet_id.clone(),
+                    finance_ticket: purchase_ticket,
+                    actual_units: 20,
+                    actual_price_per_unit: 110.into(),
+                    total: (-2_200).into(),
+                },
+            ),
+            (
+                ts(10, 40),
+                LedgerEntry::TicketCompleted {
+                    fleet_id: fleet_id.clone(),
+                    finance_ticket: delivery_ticket,
+                    actual_units: 20,
+                    actual_price_per_unit: 0.into(),
+                    total: 0.into(),
+                },
+            ),
+            (
+                ts(10, 45),
+                LedgerEntry::IncomeLogged {
+                    fleet_id: fleet_id.clone(),
+                    income: Income::ContractFulfilled {
+                        contract_id: contract_id.clone(),
+                        fulfilled_reward: 10_000.into(),
+                    },
+                },
+            ),
+        ]
+        .into_iter()
+        .map(|(created_at, entry)| TimestampedLedgerEntry { entry, created_at })
+        .collect_vec();
+
+        let contracts = vec![
+            contract_entry("c-1", ts(9, 55), true, true),
+            ContractEntry {
+                declined_at: Some(ts(11, 5)),
+                ..contract_entry("c-2", ts(11, 0), false, false)
+            },
+        ];
+
+        let outcomes = compute_contract_outcomes(&contracts, &entries);
+
+        assert_eq!(
+            outcomes
+                .iter()
+                .map(|o| o.contract_id.0.clone())
+                .collect_vec(),
+            vec!["c-2".to_string(), "c-1".to_string()]
+        );
+
+        let declined = &outcomes[0];
+        assert!(declined.declined);
+        assert_eq!(declined.payout, 0.into());
+        assert_eq!(declined.spend, 0.into());
+        assert_eq!(declined.duration(), None);
+
+        let fulfilled = &outcomes[1];
+        assert!(!fulfilled.declined);
+        assert_eq!(fulfilled.promised_payment, 12_000.into());
+        assert_eq!(fulfilled.payout, 12_000.into());
+        assert_eq!(fulfilled.spend, 2_200.into());
+        assert_eq!(fulfilled.realized_profit, 9_800.into());
+        assert_eq!(fulfilled.units_delivered, 20);
+        assert_eq!(fulfilled.trade_goods, vec![TradeGoodSymbol::IRON]);
+        assert_eq!(fulfilled.delivery_duration(), Some(Duration::minutes(40)));
+        assert_eq!(fulfilled.duration(), Some(Duration::minutes(45)));
+    }
+}
@@ -0,0 +1,492 @@
+use crate::budgeting::credits::Credits;
+use crate::budgeting::ledger_audit::TimestampedLedgerEntry;
+use crate::budgeting::treasury_redesign::{FinanceTicketDetails, ImprovedTreasurer, Income, LedgerEntry};
+use crate::{FleetId, TicketId, TradeGoodSymbol, TransactionType, WaypointSymbol};
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FleetProfitBucket {
+    pub fleet_id: FleetId,
+    pub bucket_start: DateTime<Utc>,
+    pub realized_profit: Credits,
+    pub cumulative_realized_profit: Credits,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TradeRouteProfit {
+    pub trade_good: TradeGoodSymbol,
+    pub purchase_waypoint_symbol: WaypointSymbol,
+    pub sell_waypoint_symbol: WaypointSymbol,
+    pub num_trades: usize,
+    pub units: u32,
+    pub purchase_total: Credits,
+    pub sell_total: Credits,
+    pub profit: Credits,
+    pub avg_profit_per_trade: Credits,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TradeGoodProfit {
+    pub trade_good: TradeGoodSymbol,
+    pub num_sells: usize,
+    pub units_sold: u32,
+    pub revenue: Credits,
+    /// purchase costs of the sold goods. Sells without a matching purchase ticket (e.g. mined goods) have no cost.
+    pub cost: Credits,
+    pub profit: Credits,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PriceSlippage {
+    pub trade_good: TradeGoodSymbol,
+    pub waypoint_symbol: WaypointSymbol,
+    pub transaction_type: TransactionType,
+    pub num_tickets: usize,
+    pub units: u32,
+    pub expected_total: Credits,
+    pub actual_total: Credits,
+    /// effect on our profit - negative means we bought more expensive or sold cheaper than expected
+    pub slippage: Credits,
+    pub slippage_percent: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CashFlowBucket {
+    pub bucket_start: DateTime<Utc>,
+    pub income: Credits,
+    pub expenses: Credits,
+    pub net: Credits,
+    /// agent credits according to the treasurer at the end of the bucket
+    pub closing_balance: Credits,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FinanceReport {
+    pub bucket_size_minutes: i64,
+    pub fleet_profits: Vec<FleetProfitBucket>,
+    pub trade_route_profits: Vec<TradeRouteProfit>,
+    pub trade_good_profits: Vec<TradeGoodProfit>,
+    pub price_slippages: Vec<PriceSlippage>,
+    pub cash_flow: Vec<CashFlowBucket>,
+}
+
+struct CompletedTicket {
+    details: FinanceTicketDetails,
+    total: Credits,
+}
+
+/// Aggregates the ledger into realized profits, price slippage and cash flow.
+///
+/// Profit is realized when the goods leave our cargo holds:
+/// - selling goods realizes the sell total minus the cost of the matching purchase (if any)
+/// - delivering construction materials realizes the cost of the matching purchase
+/// - contract income, refueling and other expenses are realized immediately
+///
+/// Ship purchases are investments and don't count towards the realized profit (they do show up in the cash flow).
+pub fn compute_finance_report(entries: &[TimestampedLedgerEntry], bucket_size: Duration) -> FinanceReport {
+    let bucket_of = |ts: &DateTime<Utc>| ts.duration_trunc(bucket_size).unwrap_or(*ts);
+
+    let completed_tickets: HashMap<TicketId, CompletedTicket> = entries
+        .iter()
+        .filter_map(|e| match &e.entry {
+            LedgerEntry::TicketCompleted { finance_ticket, total, .. } => Some((
+                finance_ticket.ticket_id,
+                CompletedTicket {
+                    details: finance_ticket.details.clone(),
+                    total: *total,
+                },
+            )),
+            _ => None,
+        })
+        .collect();
+
+    let mut fleet_profit_per_bucket: BTreeMap<(FleetId, DateTime<Utc>), Credits> = BTreeMap::new();
+    let mut routes: BTreeMap<(TradeGoodSymbol, WaypointSymbol, WaypointSymbol), TradeRouteProfit> = BTreeMap::new();
+    let mut trade_goods: BTreeMap<TradeGoodSymbol, TradeGoodProfit> = BTreeMap::new();
+    let mut slippages: BTreeMap<(TradeGoodSymbol, WaypointSymbol, TransactionType), PriceSlippage> = BTreeMap::new();
+    let mut cash_flow: BTreeMap<DateTime<Utc>, CashFlowBucket> = BTreeMap::new();
+
+    let mut treasurer = ImprovedTreasurer::new();
+
+    for TimestampedLedgerEntry { entry, created_at } in entries {
+        let bucket_start = bucket_of(created_at);
+        let mut add_realized_profit = |fleet_id: &FleetId, profit: Credits| {
+            *fleet_profit_per_bucket
+                .entry((fleet_id.clone(), bucket_start))
+                .or_default() += profit;
+        };
+
+        let cash_flow_delta: Credits = match entry {
+            LedgerEntry::TicketCompleted {
+                fleet_id,
+                finance_ticket,
+                actual_units,
+                actual_price_per_unit,
+                total,
+            } => {
+                let maybe_purchase = |maybe_ticket_id: &Option<TicketId>| {
+                    maybe_ticket_id
+                        .as_ref()
+                        .and_then(|id| completed_tickets.get(id))
+                        .filter(|purchase| matches!(purchase.details, FinanceTicketDetails::PurchaseTradeGoods(_)))
+                };
+
+                match &finance_ticket.details {
+                    FinanceTicketDetails::SellTradeGoods(d) => {
+                        let maybe_purchase = maybe_purchase(&d.maybe_matching_purchase_ticket);
+                        let cost = maybe_purchase.map(|p| p.total.abs()).unwrap_or_default();
+                        let profit = *total - cost;
+                        add_realized_profit(fleet_id, profit);
+
+                        let trade_good_profit = trade_goods
+                            .entry(d.trade_good.clone())
+                            .or_insert_with(|| TradeGoodProfit {
+                                trade_good: d.trade_good.clone(),
+                                num_sells: 0,
+                                units_sold: 0,
+                                revenue: 0.into(),
+                                cost: 0.into(),
+                                profit: 0.into(),
+                            });
+                        trade_good_profit.num_sells += 1;
+                        trade_good_profit.units_sold += actual_units;
+                        trade_good_profit.revenue += *total;
+                        trade_good_profit.cost += cost;
+                        trade_good_profit.profit += profit;
+
+                        if let Some(purchase) = maybe_purchase {
+                            let purchase_waypoint_symbol = purchase.details.get_waypoint();
+                            let route = routes
+                                .entry((d.trade_good.clone(), purchase_waypoint_symbol.clone(), d.waypoint_symbol.clone()))
+                                .or_insert_with(|| TradeRouteProfit {
+                                    trade_good: d.trade_good.clone(),
+                                    purchase_waypoint_symbol,
+                                    sell_waypoint_symbol: d.waypoint_symbol.clone(),
+                                    num_trades: 0,
+                                    units: 0,
+                                    purchase_total: 0.into(),
+                                    sell_total: 0.into(),
+                                    profit: 0.into(),
+                                    avg_profit_per_trade: 0.into(),
+                                });
+                            route.num_trades += 1;
+                            route.units += actual_units;
+                            route.purchase_total += cost;
+                            route.sell_total += *total;
+                            route.profit += profit;
+                        }
+
+                        add_slippage(
+                            &mut slippages,
+                            &d.trade_good,
+                            &d.waypoint_symbol,
+                            TransactionType::Sell,
+                            *actual_units,
+                            d.expected_price_per_unit,
+                            *actual_price_per_unit,
+                        );
+                    }
+                    FinanceTicketDetails::PurchaseTradeGoods(d) => {
+                        // realized when the goods are sold or delivered
+                        add_slippage(
+                            &mut slippages,
+                            &d.trade_good,
+                            &d.waypoint_symbol,
+                            TransactionType::Purchase,
+                            *actual_units,
+                            d.expected_price_per_unit,
+                            *actual_price_per_unit,
+                        );
+                    }
+                    FinanceTicketDetails::SupplyConstructionSite(d) => {
+                        if let Some(purchase) = maybe_purchase(&d.maybe_matching_purchase_ticket) {
+                            add_realized_profit(fleet_id, purchase.total);
+                        }
+                    }
+                    FinanceTicketDetails::RefuelShip(_) => add_realized_profit(fleet_id, *total),
+                    FinanceTicketDetails::PurchaseShip(_) => {}
+                    FinanceTicketDetails::DeliverContractCargo(_) => {}
+                }
+                *total
+            }
+            LedgerEntry::ExpenseLogged { fleet_id, total, .. } => {
+                add_realized_profit(fleet_id, -*total);
+                -*total
+            }
+            LedgerEntry::IncomeLogged { fleet_id, income } => {
+                let amount = match income {
+                    Income::ContractAccepted { accepted_reward, .. } => *accepted_reward,
+                    Income::ContractFulfilled { fulfilled_reward, .. } => *fulfilled_reward,
+                };
+                add_realized_profit(fleet_id, amount);
+                amount
+            }
+            _ => 0.into(),
+        };
+
+        // replaying keeps the balance consistent with the treasurer - broken entries are skipped, like the treasurer would have rejected them
+        let _ = treasurer.process_ledger_entry(entry.clone());
+
+        let bucket = cash_flow
+            .entry(bucket_start)
+            .or_insert_with(|| CashFlowBucket {
+                bucket_start,
+                income: 0.into(),
+                expenses: 0.into(),
+                net: 0.into(),
+                closing_balance: 0.into(),
+            });
+        if cash_flow_delta.is_positive() {
+            bucket.income += cash_flow_delta;
+        } else {
+            bucket.expenses += cash_flow_delta.abs();
+        }
+        bucket.net += cash_flow_delta;
+        bucket.closing_balance = treasurer.current_agent_credits();
+    }
+
+    let mut cumulative_per_fleet: HashMap<FleetId, Credits> = HashMap::new();
+    let fleet_profits = fleet_profit_per_bucket
+        .into_iter()
+        .sorted_by_key(|((fleet_id, bucket_start), _)| (*bucket_start, fleet_id.0))
+        .map(|((fleet_id, bucket_start), realized_profit)| {
+            let cumulative = cumulative_per_fleet.entry(fleet_id.clone()).or_default();
+            *cumulative += realized_profit;
+            FleetProfitBucket {
+                fleet_id,
+                bucket_start,
+                realized_profit,
+                cumulative_realized_profit: *cumulative,
+            }
+        })
+        .collect_vec();
+
+    FinanceReport {
+        bucket_size_minutes: bucket_size.num_minutes(),
+        fleet_profits,
+        trade_route_profits: routes
+            .into_values()
+            .map(|mut route| {
+                route.avg_profit_per_trade = (route.profit.0 / route.num_trades as i64).into();
+                route
+            })
+            .sorted_by_key(|route| -route.profit.0)
+            .collect_vec(),
+        trade_good_profits: trade_goods
+            .into_values()
+            .sorted_by_key(|tg| -tg.profit.0)
+            .collect_vec(),
+        price_slippages: slippages
+            .into_values()
+            .map(|mut slippage| {
+                slippage.slippage_percent = if slippage.expected_total.is_zero() {
+                    0.0
+                } else {
+                    slippage.slippage.0 as f64 / slippage.expected_total.0 as f64 * 100.0
+                };
+                slippage
+            })
+            .sorted_by_key(|slippage| slippage.slippage.0)
+            .collect_vec(),
+        cash_flow: cash_flow.into_values().collect_vec(),
+    }
+}
+
+fn add_slippage(
+    slippages: &mut BTreeMap<(TradeGoodSymbol, WaypointSymbol, TransactionType), PriceSlippage>,
+    trade_good: &TradeGoodSymbol,
+    waypoint_symbol: &WaypointSymbol,
+    transaction_type: TransactionType,
+    units: u32,
+    expected_price_per_unit: Credits,
+    actual_price_per_unit: Credits,
+) {
+    let expected_total = expected_price_per_unit * units;
+    let actual_total = actual_price_per_unit * units;
+    let slippage = match transaction_type {
+        TransactionType::Purchase => expected_total - actual_total,
+        TransactionType::Sell => actual_total - expected_total,
+    };
+
+    let entry = slippages
+        .entry((trade_good.clone(), waypoint_symbol.clone(), transaction_type.clone()))
+        .or_insert_with(|| PriceSlippage {
+            trade_good: trade_good.clone(),
+            waypoint_symbol: waypoint_symbol.clone(),
+            transaction_type,
+            num_tickets: 0,
+            units: 0,
+            expected_total: 0.into(),
+            actual_total: 0.into(),
+            slippage: 0.into(),
+            slippage_percent: 0.0,
+        });
+    entry.num_tickets += 1;
+    entry.units += units;
+    entry.expected_total += expected_total;
+    entry.actual_total += actual_total;
+    entry.slippage += slippage;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::budgeting::treasury_redesign::{FinanceTicket, PurchaseTradeGoodsTicketDetails, SellTradeGoodsTicketDetails};
+    use crate::{ContractId, ShipSymbol};
+    use chrono::TimeZone;
+
+    fn ts(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 6, 1, hour, minute, 0).unwrap()
+    }
+
+    fn trade(fleet_id: &FleetId, expected_purchase_price: i64, actual_purchase_price: i64, actual_sell_price: i64) -> (LedgerEntry, LedgerEntry, LedgerEntry) {
+        let purchase_ticket = FinanceTicket {
+            ticket_id: Default::default(),
+            fleet_id: fleet_id.clone(),
+            ship_symbol: ShipSymbol("FLWI-1".to_string()),
+            details: FinanceTicketDetails::PurchaseTradeGoods(PurchaseTradeGoodsTicketDetails {
+                waypoint_symbol: WaypointSymbol("X1-A1".to_string()),
+                trade_good: TradeGoodSymbol::ADVANCED_CIRCUITRY,
+                expected_price_per_unit: expected_purchase_price.into(),
+                quantity: 10,
+                expected_total_purchase_price: (expected_purchase_price * 10).into(),
+                purchase_cargo_reason: None,
+            }),
+            allocated_credits: (expected_purchase_price * 10).into(),
+        };
+        let sell_ticket = FinanceTicket {
+            ticket_id: Default::default(),
+            fleet_id: fleet_id.clone(),
+            ship_symbol: ShipSymbol("FLWI-1".to_string()),
+            details: FinanceTicketDetails::SellTradeGoods(SellTradeGoodsTicketDetails {
+                waypoint_symbol: WaypointSymbol("X1-B1".to_string()),
+                trade_good: TradeGoodSymbol::ADVANCED_CIRCUITRY,
+                expected_price_per_unit: 2_000.into(),
+                quantity: 10,
+                expected_total_sell_price: 20_000.into(),
+                maybe_matching_purchase_ticket: Some(purchase_ticket.ticket_id),
+            }),
+            allocated_credits: 0.into(),
+        };
+
+        (
+            LedgerEntry::TicketCreated {
+                fleet_id: fleet_id.clone(),
+                ticket_details: purchase_ticket.clone(),
+            },
+            LedgerEntry::TicketCompleted {
+                fleet_id: fleet_id.clone(),
+                finance_ticket: purchase_ticket,
+                actual_units: 10,
+                actual_price_per_unit: actual_purchase_price.into(),
+                total: (-actual_purchase_price * 10).into(),
+            },
+            LedgerEntry::TicketCompleted {
+                fleet_id: fleet_id.clone(),
+                finance_ticket: sell_ticket,
+                actual_units: 10,
+                actual_price_per_unit: actual_sell_price.into(),
+                total: (actual_sell_price * 10).into(),
+            },
+        )
+    }
+
+    #[test]
+    fn aggregates_profit_slippage_and_cash_flow() {
+        let fleet_id = FleetId(1);
+        let (created_1, purchased_1, sold_1) = trade(&fleet_id, 1_000, 1_100, 2_000);
+        let (created_2, purchased_2, sold_2) = trade(&fleet_id, 1_000, 1_000, 1_900);
+
+        let entries = vec![
+            (ts(10, 0), LedgerEntry::TreasuryCreated { credits: 100_000.into() }),
+            (
+                ts(10, 0),
+                LedgerEntry::FleetCreated {
+                    fleet_id: fleet_id.clone(),
+                    total_capital: 50_000.into(),
+                },
+            ),
+            (
+                ts(10, 0),
+                LedgerEntry::TransferredFundsFromTreasuryToFleet {
+                    fleet_id: fleet_id.clone(),
+                    credits: 50_000.into(),
+                },
+            ),
+            (ts(10, 5), created_1),
+            (ts(10, 10), purchased_1),
+            (ts(10, 30), sold_1),
+            (ts(11, 5), created_2),
+            (ts(11, 10), purchased_2),
+            (ts(11, 30), sold_2),
+            (
+                ts(11, 40),
+                LedgerEntry::IncomeLogged {
+                    fleet_id: fleet_id.clone(),
+                    income: Income::ContractFulfilled {
+                        contract_id: ContractId("c-1".to_string()),
+                        fulfilled_reward: 5_000.into(),
+                    },
+                },
+            ),
+        ]
+        .into_iter()
+        .map(|(created_at, entry)| TimestampedLedgerEntry { entry, created_at })
+        .collect_vec();
+
+        let report = compute_finance_report(&entries, Duration::hours(1));
+
+        // 20_000 - 11_000 = 9_000 and 19_000 - 10_000 + 5_000 = 14_000
+        assert_eq!(
+            report
+                .fleet_profits
+                .iter()
+                .map(|b| (b.bucket_start, b.realized_profit, b.cumulative_realized_profit))
+                .collect_vec(),
+            vec![
+                (ts(10, 0), 9_000.into(), 9_000.into()),
+                (ts(11, 0), 14_000.into(), 23_000.into())
+            ]
+        );
+
+        assert_eq!(report.trade_route_profits.len(), 1);
+        let route = &report.trade_route_profits[0];
+        assert_eq!(route.num_trades, 2);
+        assert_eq!(route.units, 20);
+        assert_eq!(route.profit, 18_000.into());
+        assert_eq!(route.avg_profit_per_trade, 9_000.into());
+
+        assert_eq!(report.trade_good_profits[0].revenue, 39_000.into());
+        assert_eq!(report.trade_good_profits[0].cost, 21_000.into());
+
+        let purchase_slippage = report
+            .price_slippages
+            .iter()
+            .find(|s| s.transaction_type == TransactionType::Purchase)
+            .unwrap();
+        assert_eq!(purchase_slippage.slippage, (-1_000).into());
+        assert_eq!(purchase_slippage.slippage_percent, -5.0);
+
+        let sell_slippage = report
+            .price_slippages
+            .iter()
+            .find(|s| s.transaction_type == TransactionType::Sell)
+            .unwrap();
+        assert_eq!(sell_slippage.slippage, (-1_000).into());
+
+        assert_eq!(
+            report
+                .cash_flow
+                .iter()
+                .map(|b| (b.income, b.expenses, b.net, b.closing_balance))
+                .collect_vec(),
+            vec![
+                (20_000.into(), 11_000.into(), 9_000.into(), 109_000.into()),
+                (24_000.into(), 10_000.into(), 14_000.into(), 123_000.into())
+            ]
+        );
+    }
+}
@@ -0,0 +1,624 @@
+use crate::budgeting::credits::Credits;
+use crate::budgeting::treasury_redesign::{FinanceTicket, FinanceTicketDetails, ImprovedTreasurer, LedgerEntry};
+use crate::{FleetId, ShipSymbol, TicketId, TradeGoodSymbol, Transaction, TransactionType, WaypointSymbol};
+use chrono::{DateTime, Duration, Utc};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LedgerAuditConfig {
+    /// drifts up to this amount are corrected with a `TreasuryAdjusted` entry. Bigger drifts are only reported.
+    pub max_auto_correct_drift: Credits,
+    /// max time between the api-transaction and the archived `TicketCompleted` entry
+    pub transaction_match_tolerance: Duration,
+}
+
+impl Default for LedgerAuditConfig {
+    fn default() -> Self {
+        Self {
+            max_auto_correct_drift: 5_000.into(),
+            transaction_match_tolerance: Duration::minutes(2),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TimestampedLedgerEntry {
+    pub entry: LedgerEntry,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum CreditDriftResolution {
+    NoDrift,
+    /// the drift has been seen for the first time - might be a transaction that is still in flight
+    AwaitingConfirmation,
+    Corrected {
+        credits: Credits,
+    },
+    ExceedsThreshold {
+        max_auto_correct_drift: Credits,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UnmatchedTicketCompletion {
+    pub ticket_id: TicketId,
+    pub fleet_id: FleetId,
+    pub ship_symbol: ShipSymbol,
+    pub waypoint_symbol: WaypointSymbol,
+    pub trade_good: TradeGoodSymbol,
+    pub units: u32,
+    pub price_per_unit: Credits,
+    pub completed_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum OrphanedTicketReason {
+    FleetDoesNotExist,
+    ShipDoesNotExist,
+    MatchingPurchaseTicketDoesNotExist(TicketId),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OrphanedTicket {
+    pub ticket: FinanceTicket,
+    pub reason: OrphanedTicketReason,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LedgerAuditReport {
+    pub audited_at: DateTime<Utc>,
+    pub agent_credits: Credits,
+    pub treasurer_credits: Credits,
+    /// agent_credits - treasurer_credits
+    pub credit_drift: Credits,
+    pub drift_resolution: CreditDriftResolution,
+    pub num_audited_ticket_completions: usize,
+    pub unmatched_ticket_completions: Vec<UnmatchedTicketCompletion>,
+    pub unmatched_transactions: Vec<Transaction>,
+    pub duplicate_ticket_creations: Vec<TicketId>,
+    pub duplicate_ticket_completions: Vec<TicketId>,
+    pub orphaned_tickets: Vec<OrphanedTicket>,
+}
+
+impl LedgerAuditReport {
+    pub fn has_discrepancies(&self) -> bool {
+        self.drift_resolution != CreditDriftResolution::NoDrift
+            || !self.unmatched_ticket_completions.is_empty()
+            || !self.unmatched_transactions.is_empty()
+            || !self.duplicate_ticket_creations.is_empty()
+            || !self.duplicate_ticket_completions.is_empty()
+            || !self.orphaned_tickets.is_empty()
+    }
+}
+
+pub struct LedgerAuditInput<'a> {
+    pub ledger_entries: &'a [TimestampedLedgerEntry],
+    pub treasurer: &'a ImprovedTreasurer,
+    pub agent_credits: Credits,
+    /// the drift of the previous audit run - we only correct drifts that have been confirmed by two consecutive audits
+    pub previous_credit_drift: Option<Credits>,
+    pub market_transactions: &'a [Transaction],
+    pub known_ships: &'a HashSet<ShipSymbol>,
+    pub now: DateTime<Utc>,
+}
+
+pub fn audit_ledger(input: LedgerAuditInput, config: &LedgerAuditConfig) -> LedgerAuditReport {
+    // TreasuryReset clears all tickets, so everything before the latest reset is irrelevant
+    let relevant_entries = match input
+        .ledger_entries
+        .iter()
+        .rposition(|e| matches!(e.entry, LedgerEntry::TreasuryReset { .. }))
+    {
+        Some(idx) => &input.ledger_entries[idx + 1..],
+        None => input.ledger_entries,
+    };
+
+    let treasurer_credits = input.treasurer.current_agent_credits();
+    let credit_drift = input.agent_credits - treasurer_credits;
+    let drift_resolution = decide_drift_resolution(credit_drift, input.previous_credit_drift, config);
+
+    let (num_audited_ticket_completions, unmatched_ticket_completions, unmatched_transactions) =
+        match_transactions(relevant_entries, input.market_transactions, input.known_ships, input.now, config);
+
+    LedgerAuditReport {
+        audited_at: input.now,
+        agent_credits: input.agent_credits,
+        treasurer_credits,
+        credit_drift,
+        drift_resolution,
+        num_audited_ticket_completions,
+        unmatched_ticket_completions,
+        unmatched_transactions,
+        duplicate_ticket_creations: duplicate_ticket_ids(relevant_entries, |e| match e {
+            LedgerEntry::TicketCreated { ticket_details, .. } => Some(ticket_details.ticket_id),
+            _ => None,
+        }),
+        duplicate_ticket_completions: duplicate_ticket_ids(relevant_entries, |e| match e {
+            LedgerEntry::TicketCompleted { finance_ticket, .. } => Some(finance_ticket.ticket_id),
+            _ => None,
+        }),
+        orphaned_tickets: find_orphaned_tickets(relevant_entries, input.treasurer, input.known_ships),
+    }
+}
+
+pub fn decide_drift_resolution(credit_drift: Credits, previous_credit_drift: Option<Credits>, config: &LedgerAuditConfig) -> CreditDriftResolution {
+    if credit_drift.is_zero() {
+        CreditDriftResolution::NoDrift
+    } else if credit_drift.abs() > config.max_auto_correct_drift {
+        CreditDriftResolution::ExceedsThreshold {
+            max_auto_correct_drift: config.max_auto_correct_drift,
+        }
+    } else if previous_credit_drift == Some(credit_drift) {
+        CreditDriftResolution::Corrected { credits: credit_drift }
+    } else {
+        CreditDriftResolution::AwaitingConfirmation
+    }
+}
+
+fn duplicate_ticket_ids(entries: &[TimestampedLedgerEntry], extract_id: impl Fn(&LedgerEntry) -> Option<TicketId>) -> Vec<TicketId> {
+    entries
+        .iter()
+        .filter_map(|e| extract_id(&e.entry))
+        .duplicates()
+        .sorted()
+        .collect_vec()
+}
+
+fn find_orphaned_tickets(entries: &[TimestampedLedgerEntry], treasurer: &ImprovedTreasurer, known_ships: &HashSet<ShipSymbol>) -> Vec<OrphanedTicket> {
+    let created_ticket_ids: HashSet<TicketId> = entries
+        .iter()
+        .filter_map(|e| match &e.entry {
+            LedgerEntry::TicketCreated { ticket_details, .. } => Some(ticket_details.ticket_id),
+            _ => None,
+        })
+        .collect();
+
+    let fleet_budgets = treasurer.get_fleet_budgets().unwrap_or_default();
+    let active_tickets = treasurer.get_active_tickets().unwrap_or_default();
+
+    active_tickets
+        .into_values()
+        .sorted_by_key(|t| t.ticket_id)
+        .filter_map(|ticket| {
+            let maybe_matching_purchase_ticket = match &ticket.details {
+                FinanceTicketDetails::SellTradeGoods(d) => d.maybe_matching_purchase_ticket,
+                FinanceTicketDetails::SupplyConstructionSite(d) => d.maybe_matching_purchase_ticket,
+                FinanceTicketDetails::PurchaseTradeGoods(_) => None,
+                FinanceTicketDetails::PurchaseShip(_) => None,
+                FinanceTicketDetails::RefuelShip(_) => None,
+                FinanceTicketDetails::DeliverContractCargo(_) => None,
+            };
+
+            let maybe_reason = if !fleet_budgets.contains_key(&ticket.fleet_id) {
+                Some(OrphanedTicketReason::FleetDoesNotExist)
+            } else if !known_ships.contains(&ticket.ship_symbol) && !matches!(ticket.details, FinanceTicketDetails::PurchaseShip(_)) {
+                Some(OrphanedTicketReason::ShipDoesNotExist)
+            } else {
+                maybe_matching_purchase_ticket
+                    .filter(|id| !created_ticket_ids.contains(id))
+                    .map(OrphanedTicketReason::MatchingPurchaseTicketDoesNotExist)
+            };
+
+            maybe_reason.map(|reason| OrphanedTicket { ticket, reason })
+        })
+        .collect_vec()
+}
+
+struct LedgerTransaction {
+    maybe_completion: Option<UnmatchedTicketCompletion>,
+    maybe_ship_symbol: Option<ShipSymbol>,
+    maybe_waypoint_symbol: Option<WaypointSymbol>,
+    trade_good: TradeGoodSymbol,
+    transaction_type: TransactionType,
+    units: u32,
+    price_per_unit: Credits,
+    created_at: DateTime<Utc>,
+}
+
+impl LedgerTransaction {
+    fn matches(&self, tx: &Transaction, tolerance: Duration) -> bool {
+        let is_same_ship = self
+            .maybe_ship_symbol
+            .as_ref()
+            .map(|ss| ss == &tx.ship_symbol)
+            .unwrap_or(true);
+        let is_same_waypoint = self
+            .maybe_waypoint_symbol
+            .as_ref()
+            .map(|wps| wps == &tx.waypoint_symbol)
+            .unwrap_or(true);
+        let time_diff = self.created_at - tx.timestamp;
+
+        is_same_ship
+            && is_same_waypoint
+            && self.trade_good == tx.trade_symbol
+            && self.transaction_type == tx.transaction_type
+            && self.units as i64 == tx.units as i64
+            && self.price_per_unit == Credits::from(tx.price_per_unit)
+            && time_diff >= -tolerance
+            && time_diff <= tolerance
+    }
+}
+
+fn to_ledger_transaction(entry: &TimestampedLedgerEntry) -> Option<LedgerTransaction> {
+    match &entry.entry {
+        LedgerEntry::TicketCompleted {
+            fleet_id,
+            finance_ticket,
+            actual_units,
+            actual_price_per_unit,
+            ..
+        } => {
+            let (trade_good, transaction_type) = match &finance_ticket.details {
+                FinanceTicketDetails::PurchaseTradeGoods(d) => (d.trade_good.clone(), TransactionType::Purchase),
+                FinanceTicketDetails::SellTradeGoods(d) => (d.trade_good.clone(), TransactionType::Sell),
+                FinanceTicketDetails::RefuelShip(_) => (TradeGoodSymbol::FUEL, TransactionType::Purchase),
+                // not a market transaction
+                FinanceTicketDetails::PurchaseShip(_) => return None,
+                FinanceTicketDetails::SupplyConstructionSite(_) => return None,
+                FinanceTicketDetails::DeliverContractCargo(_) => return None,
+            };
+            let waypoint_symbol = finance_ticket.details.get_waypoint();
+
+            Some(LedgerTransaction {
+                maybe_completion: Some(UnmatchedTicketCompletion {
+                    ticket_id: finance_ticket.ticket_id,
+                    fleet_id: fleet_id.clone(),
+                    ship_symbol: finance_ticket.ship_symbol.clone(),
+                    waypoint_symbol: waypoint_symbol.clone(),
+                    trade_good: trade_good.clone(),
+                    units: *actual_units,
+                    price_per_unit: *actual_price_per_unit,
+                    completed_at: entry.created_at,
+                }),
+                maybe_ship_symbol: Some(finance_ticket.ship_symbol.clone()),
+                maybe_waypoint_symbol: Some(waypoint_symbol),
+                trade_good,
+                transaction_type,
+                units: *actual_units,
+                price_per_unit: *actual_price_per_unit,
+                created_at: entry.created_at,
+            })
+        }
+        // expenses (e.g. refueling without ticket) don't know the ship or the waypoint
+        LedgerEntry::ExpenseLogged {
+            trade_good_symbol,
+            units,
+            price_per_unit,
+            ..
+        } => Some(LedgerTransaction {
+            maybe_completion: None,
+            maybe_ship_symbol: None,
+            maybe_waypoint_symbol: None,
+            trade_good: trade_good_symbol.clone(),
+            transaction_type: TransactionType::Purchase,
+            units: *units,
+            price_per_unit: *price_per_unit,
+            created_at: entry.created_at,
+        }),
+        _ => None,
+    }
+}
+
+/// The api only reports the latest transactions of a market, so we only audit ledger entries within the time window covered by the transactions of that market.
+fn match_transactions(
+    entries: &[TimestampedLedgerEntry],
+    market_transactions: &[Transaction],
+    known_ships: &HashSet<ShipSymbol>,
+    now: DateTime<Utc>,
+    config: &LedgerAuditConfig,
+) -> (usize, Vec<UnmatchedTicketCompletion>, Vec<Transaction>) {
+    let tolerance = config.transaction_match_tolerance;
+
+    let our_transactions = market_transactions
+        .iter()
+        .filter(|tx| known_ships.contains(&tx.ship_symbol))
+        .unique()
+        .sorted_by_key(|tx| tx.timestamp)
+        .collect_vec();
+
+    let observed_window_per_waypoint: HashMap<WaypointSymbol, (DateTime<Utc>, DateTime<Utc>)> = market_transactions
+        .iter()
+        .into_group_map_by(|tx| tx.waypoint_symbol.clone())
+        .into_iter()
+        .filter_map(|(wps, txs)| {
+            let (min, max) = txs.iter().map(|tx| tx.timestamp).minmax().into_option()?;
+            Some((wps, (min, max)))
+        })
+        .collect();
+
+    let ledger_start = entries.first().map(|e| e.created_at);
+
+    let mut ledger_transactions = entries
+        .iter()
+        .filter_map(to_ledger_transaction)
+        .collect_vec();
+    let mut is_claimed = vec![false; ledger_transactions.len()];
+
+    let mut unmatched_transactions = Vec::new();
+    for tx in our_transactions {
+        let maybe_idx = ledger_transactions
+            .iter()
+            .enumerate()
+            .filter(|(idx, lt)| !is_claimed[*idx] && lt.matches(tx, tolerance))
+            // prefer the more specific ticket over a plain expense
+            .min_by_key(|(_, lt)| (lt.maybe_completion.is_none(), (lt.created_at - tx.timestamp).abs()))
+            .map(|(idx, _)| idx);
+
+        match maybe_idx {
+            Some(idx) => is_claimed[idx] = true,
+            None => {
+                let is_covered_by_ledger = ledger_start
+                    .map(|start| tx.timestamp >= start)
+                    .unwrap_or(false);
+                let might_be_in_flight = tx.timestamp > now - tolerance;
+                if is_covered_by_ledger && !might_be_in_flight {
+                    unmatched_transactions.push(tx.clone());
+                }
+            }
+        }
+    }
+
+    let mut num_audited = 0;
+    let mut unmatched_completions = Vec::new();
+    for (idx, lt) in ledger_transactions.drain(..).enumerate() {
+        let Some(completion) = lt.maybe_completion else { continue };
+        let is_observed = observed_window_per_waypoint
+            .get(&completion.waypoint_symbol)
+            .map(|(min, max)| completion.completed_at >= *min && completion.completed_at <= *max + tolerance)
+            .unwrap_or(false);
+
+        if is_observed {
+            num_audited += 1;
+            if !is_claimed[idx] {
+                unmatched_completions.push(completion);
+            }
+        }
+    }
+
+    (num_audited, unmatched_completions, unmatched_transactions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::budgeting::treasury_redesign::{PurchaseTradeGoodsTicketDetails, SellTradeGoodsTicketDetails};
+    use chrono::TimeZone;
+
+    fn ts(minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 6, 1, 12, minute, 0).unwrap()
+    }
+
+    fn purchase_ticket(ship: &ShipSymbol) -> FinanceTicket {
+        FinanceTicket {
+            ticket_id: Default::default(),
+            fleet_id: FleetId(1),
+            ship_symbol: ship.clone(),
+            details: FinanceTicketDetails::PurchaseTradeGoods(PurchaseTradeGoodsTicketDetails {
+                waypoint_symbol: WaypointSymbol("X1-A1".to_string()),
+                trade_good: TradeGoodSymbol::ADVANCED_CIRCUITRY,
+                expected_price_per_unit: 1_000.into(),
+                quantity: 10,
+                expected_total_purchase_price: 10_000.into(),
+                purchase_cargo_reason: None,
+            }),
+            allocated_credits: 10_000.into(),
+        }
+    }
+
+    fn tx(ship: &ShipSymbol, minute: u32, units: i32, price_per_unit: i32) -> Transaction {
+        Transaction {
+            waypoint_symbol: WaypointSymbol("X1-A1".to_string()),
+            ship_symbol: ship.clone(),
+            trade_symbol: TradeGoodSymbol::ADVANCED_CIRCUITRY,
+            transaction_type: TransactionType::Purchase,
+            units,
+            price_per_unit,
+            total_price: units * price_per_unit,
+            timestamp: ts(minute),
+        }
+    }
+
+    fn timestamped(entries: Vec<(u32, LedgerEntry)>) -> Vec<TimestampedLedgerEntry> {
+        entries
+            .into_iter()
+            .map(|(minute, entry)| TimestampedLedgerEntry { entry, created_at: ts(minute) })
+            .collect_vec()
+    }
+
+    fn setup_ledger(ship: &ShipSymbol) -> (FinanceTicket, Vec<(u32, LedgerEntry)>) {
+        let ticket = purchase_ticket(ship);
+        let entries = vec![
+            (0, LedgerEntry::TreasuryCreated { credits: 100_000.into() }),
+            (
+                0,
+                LedgerEntry::FleetCreated {
+                    fleet_id: FleetId(1),
+                    total_capital: 50_000.into(),
+                },
+            ),
+            (
+                0,
+                LedgerEntry::TransferredFundsFromTreasuryToFleet {
+                    fleet_id: FleetId(1),
+                    credits: 50_000.into(),
+                },
+            ),
+            (
+                1,
+                LedgerEntry::TicketCreated {
+                    fleet_id: FleetId(1),
+                    ticket_details: ticket.clone(),
+                },
+            ),
+            (
+                5,
+                LedgerEntry::TicketCompleted {
+                    fleet_id: FleetId(1),
+                    finance_ticket: ticket.clone(),
+                    actual_units: 10,
+                    actual_price_per_unit: 990.into(),
+                    total: (-9_900).into(),
+                },
+            ),
+        ];
+        (ticket, entries)
+    }
+
+    #[test]
+    fn clean_ledger_has_no_discrepancies() {
+        let ship = ShipSymbol("FLWI-1".to_string());
+        let (_, entries) = setup_ledger(&ship);
+        let treasurer = ImprovedTreasurer::from_ledger(entries.iter().map(|(_, e)| e.clone()).collect_vec()).unwrap();
+        let entries = timestamped(entries);
+
+        let report = audit_ledger(
+            LedgerAuditInput {
+                ledger_entries: &entries,
+                treasurer: &treasurer,
+                agent_credits: 90_100.into(),
+                previous_credit_drift: None,
+                market_transactions: &[tx(&ship, 5, 10, 990)],
+                known_ships: &HashSet::from([ship.clone()]),
+                now: ts(30),
+            },
+            &LedgerAuditConfig::default(),
+        );
+
+        assert_eq!(report.credit_drift, 0.into());
+        assert_eq!(report.num_audited_ticket_completions, 1);
+        assert!(!report.has_discrepancies(), "{report:#?}");
+    }
+
+    #[test]
+    fn reports_unmatched_completions_and_transactions() {
+        let ship = ShipSymbol("FLWI-1".to_string());
+        let other_agents_ship = ShipSymbol("OTHER-1".to_string());
+        let (ticket, entries) = setup_ledger(&ship);
+        let treasurer = ImprovedTreasurer::from_ledger(entries.iter().map(|(_, e)| e.clone()).collect_vec()).unwrap();
+        let entries = timestamped(entries);
+
+        // price differs from the completed ticket and we have another purchase without any ticket
+        let transactions = vec![
+            tx(&ship, 5, 10, 1_000),
+            tx(&ship, 8, 3, 1_000),
+            tx(&other_agents_ship, 9, 3, 1_000),
+            tx(&ship, 29, 1, 1_000),
+        ];
+
+        let report = audit_ledger(
+            LedgerAuditInput {
+                ledger_entries: &entries,
+                treasurer: &treasurer,
+                agent_credits: 90_100.into(),
+                previous_credit_drift: None,
+                market_transactions: &transactions,
+                known_ships: &HashSet::from([ship.clone()]),
+                now: ts(30),
+            },
+            &LedgerAuditConfig::default(),
+        );
+
+        assert_eq!(
+            report
+                .unmatched_ticket_completions
+                .iter()
+                .map(|c| c.ticket_id)
+                .collect_vec(),
+            vec![ticket.ticket_id]
+        );
+        // the transaction from minute 29 might still be in flight
+        assert_eq!(report.unmatched_transactions, vec![tx(&ship, 5, 10, 1_000), tx(&ship, 8, 3, 1_000)]);
+    }
+
+    #[test]
+    fn finds_duplicate_and_orphaned_tickets() {
+        let ship = ShipSymbol("FLWI-1".to_string());
+        let (ticket, mut entries) = setup_ledger(&ship);
+        entries.push((
+            6,
+            LedgerEntry::TicketCompleted {
+                fleet_id: FleetId(1),
+                finance_ticket: ticket.clone(),
+                actual_units: 10,
+                actual_price_per_unit: 990.into(),
+                total: (-9_900).into(),
+            },
+        ));
+
+        let orphaned_sell_ticket = FinanceTicket {
+            ticket_id: Default::default(),
+            fleet_id: FleetId(1),
+            ship_symbol: ShipSymbol("FLWI-2".to_string()),
+            details: FinanceTicketDetails::SellTradeGoods(SellTradeGoodsTicketDetails {
+                waypoint_symbol: WaypointSymbol("X1-B1".to_string()),
+                trade_good: TradeGoodSymbol::ADVANCED_CIRCUITRY,
+                expected_price_per_unit: 2_000.into(),
+                quantity: 10,
+                expected_total_sell_price: 20_000.into(),
+                maybe_matching_purchase_ticket: None,
+            }),
+            allocated_credits: 0.into(),
+        };
+        entries.push((
+            7,
+            LedgerEntry::TicketCreated {
+                fleet_id: FleetId(1),
+                ticket_details: orphaned_sell_ticket.clone(),
+            },
+        ));
+
+        let treasurer = ImprovedTreasurer::from_ledger(entries.iter().map(|(_, e)| e.clone()).collect_vec()).unwrap();
+        let entries = timestamped(entries);
+
+        let report = audit_ledger(
+            LedgerAuditInput {
+                ledger_entries: &entries,
+                treasurer: &treasurer,
+                agent_credits: 90_100.into(),
+                previous_credit_drift: None,
+                market_transactions: &[],
+                known_ships: &HashSet::from([ship.clone()]),
+                now: ts(30),
+            },
+            &LedgerAuditConfig::default(),
+        );
+
+        assert_eq!(report.duplicate_ticket_completions, vec![ticket.ticket_id]);
+        assert!(report.duplicate_ticket_creations.is_empty());
+        assert_eq!(
+            report.orphaned_tickets,
+            vec![OrphanedTicket {
+                ticket: orphaned_sell_ticket,
+                reason: OrphanedTicketReason::ShipDoesNotExist
+            }]
+        );
+        // the second completion got booked twice
+        assert_eq!(report.credit_drift, 9_900.into());
+    }
+
+    #[test]
+    fn only_corrects_small_and_confirmed_drift() {
+        let config = LedgerAuditConfig::default();
+
+        assert_eq!(decide_drift_resolution(0.into(), None, &config), CreditDriftResolution::NoDrift);
+        assert_eq!(decide_drift_resolution(100.into(), None, &config), CreditDriftResolution::AwaitingConfirmation);
+        assert_eq!(
+            decide_drift_resolution(100.into(), Some(50.into()), &config),
+            CreditDriftResolution::AwaitingConfirmation
+        );
+        assert_eq!(
+            decide_drift_resolution((-100).into(), Some((-100).into()), &config),
+            CreditDriftResolution::Corrected { credits: (-100).into() }
+        );
+        assert_eq!(
+            decide_drift_resolution(100_000.into(), Some(100_000.into()), &config),
+            CreditDriftResolution::ExceedsThreshold {
+                max_auto_correct_drift: config.max_auto_correct_drift
+            }
+        );
+    }
+}
@@ -1,4 +1,8 @@
+pub mod capital_allocation;
+pub mod contract_outcome;
 pub mod credits;
+pub mod finance_report;
+pub mod ledger_audit;
 pub mod treasury_redesign;
 
 #[cfg(any(test, feature = "test-utils"))]
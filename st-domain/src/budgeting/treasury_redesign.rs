@@ -265,6 +265,18 @@ pub enum LedgerEntry {
         fleet_id: FleetId,
         finance_ticket: FinanceTicket,
     },
+    /// explicit correction of a drift between the treasurer and the agent credits reported by the api (see `ledger_audit`)
+    TreasuryAdjusted {
+        credits: Credits,
+        reason: String,
+    },
+    /// new budget of a fleet decided by a `CapitalAllocationPolicy` (see `capital_allocation`)
+    CapitalAllocationDecided {
+        fleet_id: FleetId,
+        new_total_capital: Credits,
+        new_operating_reserve: Credits,
+        reason: String,
+    },
 }
 
 #[derive(PartialEq, Debug, Default, Clone, Serialize, Deserialize)]
@@ -286,6 +298,7 @@ impl FleetBudget {
     }
 }
 
+use crate::budgeting::capital_allocation::CapitalAllocationDecision;
 use crate::budgeting::credits::Credits;
 use crate::budgeting::treasury_redesign::LedgerEntry::*;
 use crate::{FleetId, ShipSymbol, ShipType, TicketId, TradeGoodSymbol, WaypointSymbol};
@@ -435,6 +448,16 @@ where {
         Ok(())
     }
 
+    pub async fn adjust_treasury_fund(&self, credits: Credits, reason: String) -> Result<()> {
+        self.with_treasurer(|t| t.adjust_treasury_fund(credits, reason))
+            .await
+    }
+
+    pub async fn apply_capital_allocation_decision(&self, decision: &CapitalAllocationDecision) -> Result<()> {
+        self.with_treasurer(|t| t.apply_capital_allocation_decision(decision))
+            .await
+    }
+
     pub async fn get_instance(&self) -> Result<ImprovedTreasurer> {
         self.with_treasurer(|t| Ok(t.clone())).await
     }
@@ -814,7 +837,7 @@ impl ImprovedTreasurer {
             })
     }
 
-    fn get_active_trade_routes(&self) -> Result<Vec<ActiveTradeRoute>> {
+    pub fn get_active_trade_routes(&self) -> Result<Vec<ActiveTradeRoute>> {
         let mut active_routes = HashMap::new();
 
         // we have two types of delivery tickets - SellTradeGoods and SupplyConstructionSite
@@ -1078,6 +1101,27 @@ impl ImprovedTreasurer {
         Ok(ticket)
     }
 
+    pub fn adjust_treasury_fund(&mut self, credits: Credits, reason: String) -> Result<()> {
+        if (self.treasury_fund + credits).is_negative() {
+            anyhow::bail!("Adjusting the treasury fund of {} by {} would make it negative", self.treasury_fund, credits);
+        }
+        self.process_ledger_entry(TreasuryAdjusted { credits, reason })
+    }
+
+    pub fn apply_capital_allocation_decision(&mut self, decision: &CapitalAllocationDecision) -> Result<()> {
+        if !self.fleet_budgets.contains_key(&decision.fleet_id) {
+            return Err(anyhow!("Fleet {} doesn't exist", decision.fleet_id));
+        }
+        self.process_ledger_entry(CapitalAllocationDecided {
+            fleet_id: decision.fleet_id.clone(),
+            new_total_capital: decision.new_total_capital,
+            new_operating_reserve: decision.new_operating_reserve,
+            reason: decision.reason.clone(),
+        })?;
+        self.transfer_excess_funds_from_fleet_to_treasury_if_necessary(&decision.fleet_id)?;
+        self.transfer_funds_to_fleet_to_top_up_available_capital(&decision.fleet_id)
+    }
+
     pub fn report_income(&mut self, fleet_id: &FleetId, income: Income) -> Result<()> {
         self.process_ledger_entry(IncomeLogged {
             fleet_id: fleet_id.clone(),
@@ -1432,6 +1476,24 @@ impl ImprovedTreasurer {
                     return Err(anyhow!("Fleet {} doesn't exist", fleet_id));
                 }
             }
+            TreasuryAdjusted { credits, .. } => {
+                self.treasury_fund += credits;
+                self.ledger_entries.push_back(ledger_entry);
+            }
+            CapitalAllocationDecided {
+                fleet_id,
+                new_total_capital,
+                new_operating_reserve,
+                ..
+            } => {
+                if let Some(budget) = self.fleet_budgets.get_mut(&fleet_id) {
+                    budget.budget = new_total_capital;
+                    budget.operating_reserve = new_operating_reserve;
+                    self.ledger_entries.push_back(ledger_entry);
+                } else {
+                    return Err(anyhow!("Fleet {} doesn't exist", fleet_id));
+                }
+            }
             LedgerEntry::IncomeLogged { fleet_id, income } => {
                 if let Some(budget) = self.fleet_budgets.get_mut(&fleet_id) {
                     match income {
@@ -0,0 +1,117 @@
+use crate::{ShipSymbol, TradeGoodSymbol, WaypointSymbol};
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// What happened to the cargo a drone extracted at a mining site.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum CargoFlowKind {
+    Extracted,
+    TransferredToHauler,
+    SoldByDrone,
+    Jettisoned,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CargoFlowEntry {
+    pub site: WaypointSymbol,
+    pub ship_symbol: ShipSymbol,
+    pub kind: CargoFlowKind,
+    pub trade_good_symbol: TradeGoodSymbol,
+    pub units: u32,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SiteThroughput {
+    pub site: WaypointSymbol,
+    pub units_by_kind: BTreeMap<CargoFlowKind, u32>,
+    pub window_hours: f64,
+}
+
+impl SiteThroughput {
+    pub fn units_per_hour(&self, kind: &CargoFlowKind) -> f64 {
+        self.units_by_kind.get(kind).copied().unwrap_or_default() as f64 / self.window_hours
+    }
+
+    /// Share of the extracted units that made it to a market - either via a hauler or sold by the drone itself.
+    pub fn delivery_ratio(&self) -> Option<f64> {
+        let extracted = self
+            .units_by_kind
+            .get(&CargoFlowKind::Extracted)
+            .copied()
+            .unwrap_or_default();
+        let delivered: u32 = [CargoFlowKind::TransferredToHauler, CargoFlowKind::SoldByDrone]
+            .iter()
+            .filter_map(|kind| self.units_by_kind.get(kind))
+            .sum();
+
+        (extracted > 0).then(|| delivered as f64 / extracted as f64)
+    }
+}
+
+/// Aggregates the cargo flows per mining site over the window from `since` until `now`.
+pub fn summarize_site_throughput(entries: &[CargoFlowEntry], since: DateTime<Utc>, now: DateTime<Utc>) -> Vec<SiteThroughput> {
+    let window_hours = ((now - since).num_seconds() as f64 / 3600.0).max(1.0 / 60.0);
+
+    entries
+        .iter()
+        .filter(|entry| entry.created_at >= since && entry.created_at <= now)
+        .into_group_map_by(|entry| entry.site.clone())
+        .into_iter()
+        .sorted_by_key(|(site, _)| site.clone())
+        .map(|(site, site_entries)| {
+            let mut units_by_kind: BTreeMap<CargoFlowKind, u32> = BTreeMap::new();
+            for entry in site_entries {
+                *units_by_kind.entry(entry.kind.clone()).or_default() += entry.units;
+            }
+
+            SiteThroughput {
+                site,
+                units_by_kind,
+                window_hours,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeDelta, TimeZone};
+
+    #[test]
+    fn summarizes_the_cargo_flows_per_site() {
+        let now = Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap();
+        let entry = |site: &str, kind: CargoFlowKind, units: u32, minutes_ago: i64| CargoFlowEntry {
+            site: WaypointSymbol(site.to_string()),
+            ship_symbol: ShipSymbol("MINER-1".to_string()),
+            kind,
+            trade_good_symbol: TradeGoodSymbol::IRON_ORE,
+            units,
+            created_at: now - TimeDelta::minutes(minutes_ago),
+        };
+
+        let entries = vec![
+            entry("X1-FOO-A", CargoFlowKind::Extracted, 10, 30),
+            entry("X1-FOO-A", CargoFlowKind::Extracted, 10, 20),
+            entry("X1-FOO-A", CargoFlowKind::TransferredToHauler, 12, 10),
+            entry("X1-FOO-A", CargoFlowKind::Jettisoned, 8, 5),
+            entry("X1-FOO-B", CargoFlowKind::Extracted, 5, 10),
+            // outside the window
+            entry("X1-FOO-B", CargoFlowKind::Extracted, 5, 180),
+        ];
+
+        let throughput = summarize_site_throughput(&entries, now - TimeDelta::hours(2), now);
+
+        assert_eq!(throughput.len(), 2);
+        let site_a = &throughput[0];
+        assert_eq!(site_a.site, WaypointSymbol("X1-FOO-A".to_string()));
+        assert_eq!(site_a.units_per_hour(&CargoFlowKind::Extracted), 10.0);
+        assert_eq!(site_a.units_per_hour(&CargoFlowKind::SoldByDrone), 0.0);
+        assert_eq!(site_a.delivery_ratio(), Some(0.6));
+
+        assert_eq!(throughput[1].units_by_kind, BTreeMap::from([(CargoFlowKind::Extracted, 5)]));
+    }
+}
@@ -0,0 +1,271 @@
+use crate::{Agent, AgentSymbol, FactionSymbol, MarketEntry, MarketTradeGood, TradeGoodSymbol, TradeGoodType, TradingOpportunity, Transaction, WaypointSymbol};
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
+use ordered_float::OrderedFloat;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// The public info of an agent at the time we've seen it in the agents list.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AgentSnapshot {
+    pub agent_symbol: AgentSymbol,
+    pub starting_faction: FactionSymbol,
+    pub headquarters: WaypointSymbol,
+    pub credits: i64,
+    pub ship_count: i32,
+    pub observed_at: DateTime<Utc>,
+}
+
+impl AgentSnapshot {
+    pub fn from_agent(agent: &Agent, observed_at: DateTime<Utc>) -> Self {
+        Self {
+            agent_symbol: agent.symbol.clone(),
+            starting_faction: agent.starting_faction.clone(),
+            headquarters: agent.headquarters.clone(),
+            credits: agent.credits,
+            ship_count: agent.ship_count,
+            observed_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LeaderboardEntry {
+    pub rank: usize,
+    pub snapshot: AgentSnapshot,
+    /// None for agents that weren't in the earlier snapshots
+    pub credits_change: Option<i64>,
+    pub ship_count_change: Option<i32>,
+}
+
+/// Ranks the agents by credits and compares them with an earlier snapshot of the agents list.
+pub fn compute_leaderboard(latest: &[AgentSnapshot], earlier: &[AgentSnapshot]) -> Vec<LeaderboardEntry> {
+    let earlier_by_agent: HashMap<&AgentSymbol, &AgentSnapshot> = earlier
+        .iter()
+        .map(|snapshot| (&snapshot.agent_symbol, snapshot))
+        .collect();
+
+    latest
+        .iter()
+        .sorted_by_key(|snapshot| (-snapshot.credits, -snapshot.ship_count, snapshot.agent_symbol.clone()))
+        .enumerate()
+        .map(|(idx, snapshot)| {
+            let maybe_earlier = earlier_by_agent.get(&snapshot.agent_symbol);
+            LeaderboardEntry {
+                rank: idx + 1,
+                snapshot: snapshot.clone(),
+                credits_change: maybe_earlier.map(|earlier| snapshot.credits - earlier.credits),
+                ship_count_change: maybe_earlier.map(|earlier| snapshot.ship_count - earlier.ship_count),
+            }
+        })
+        .collect_vec()
+}
+
+/// How likely it is that other agents trade at a market.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MarketContestation {
+    pub waypoint_symbol: WaypointSymbol,
+    /// number of trade goods we could compare between two consecutive observations
+    pub num_comparisons: u32,
+    /// comparisons with a price or supply change in the direction a trader causes, but without a trade of ours in between
+    pub num_unexplained_changes: u32,
+    /// trades of other agents listed in the transactions of the market
+    pub num_foreign_transactions: u32,
+    /// share of the comparisons that point to a competitor - between 0 and 1
+    pub score: f64,
+}
+
+/// Compares each observation of a market with the previous one and looks for changes we didn't cause.
+///
+/// Buying at a market raises the purchase price and lowers the supply, selling does the opposite. Markets drift back on their own,
+/// so we only count the direction a trader causes: buying at exports and selling at imports - both at exchanges.
+/// A change is explained if one of our ships traded the good at the market in between. The transactions of a market are only
+/// visible while one of our ships is there, so foreign transactions are a strong, but rare signal.
+/// Every agent in our db counts as one of us - their trades don't point to a competitor.
+/// Needs at least two observations with trade goods.
+pub fn measure_market_contestation(history: &[MarketEntry], our_agent_symbols: &HashSet<AgentSymbol>) -> Option<MarketContestation> {
+    let our_ship_prefixes = our_agent_symbols
+        .iter()
+        .map(|agent_symbol| format!("{}-", agent_symbol.0))
+        .collect_vec();
+    let is_ours = |tx: &Transaction| {
+        our_ship_prefixes
+            .iter()
+            .any(|prefix| tx.ship_symbol.0.starts_with(prefix))
+    };
+    let waypoint_symbol = history.first()?.waypoint_symbol.clone();
+
+    let transactions = history
+        .iter()
+        .flat_map(|entry| entry.market_data.transactions.iter().flatten())
+        .unique()
+        .collect_vec();
+
+    let observations = history
+        .iter()
+        .filter_map(|entry| {
+            entry.market_data.trade_goods.as_ref().map(|trade_goods| {
+                let goods: HashMap<&TradeGoodSymbol, &MarketTradeGood> = trade_goods.iter().map(|mtg| (&mtg.symbol, mtg)).collect();
+                (entry.created_at, goods)
+            })
+        })
+        .sorted_by_key(|(created_at, _)| *created_at)
+        .collect_vec();
+
+    let mut num_comparisons = 0;
+    let mut num_unexplained_changes = 0;
+    let mut foreign_transactions_in_comparisons = HashSet::new();
+
+    for ((previous_at, previous_goods), (current_at, current_goods)) in observations.iter().tuple_windows() {
+        for (symbol, current) in current_goods.iter() {
+            let Some(previous) = previous_goods.get(symbol) else {
+                continue;
+            };
+            num_comparisons += 1;
+
+            let transactions_in_between = transactions
+                .iter()
+                .filter(|tx| &&tx.trade_symbol == symbol && tx.timestamp > *previous_at && tx.timestamp <= *current_at)
+                .collect_vec();
+            let (ours, foreign): (Vec<_>, Vec<_>) = transactions_in_between
+                .into_iter()
+                .partition(|tx| is_ours(tx));
+
+            if !foreign.is_empty() {
+                foreign_transactions_in_comparisons.extend(foreign);
+            } else if ours.is_empty() && has_trader_caused_change(previous, current) {
+                num_unexplained_changes += 1;
+            }
+        }
+    }
+
+    let num_foreign_transactions = transactions.iter().filter(|tx| !is_ours(tx)).count() as u32;
+    let num_suspicious = num_unexplained_changes + foreign_transactions_in_comparisons.len() as u32;
+
+    (num_comparisons > 0).then(|| MarketContestation {
+        waypoint_symbol,
+        num_comparisons,
+        num_unexplained_changes,
+        num_foreign_transactions,
+        score: (num_suspicious as f64 / num_comparisons as f64).min(1.0),
+    })
+}
+
+/// Measures each market of a history that covers several markets (e.g. all markets of a system) on its own.
+pub fn measure_market_contestations(history: &[MarketEntry], our_agent_symbols: &HashSet<AgentSymbol>) -> Vec<MarketContestation> {
+    history
+        .iter()
+        .cloned()
+        .into_group_map_by(|entry| entry.waypoint_symbol.clone())
+        .into_values()
+        .filter_map(|history_of_market| measure_market_contestation(&history_of_market, our_agent_symbols))
+        .sorted_by_key(|contestation| contestation.waypoint_symbol.clone())
+        .collect_vec()
+}
+
+fn has_trader_caused_change(previous: &MarketTradeGood, current: &MarketTradeGood) -> bool {
+    let supply_change = current.supply.clone() as i32 - previous.supply.clone() as i32;
+    let bought = current.purchase_price > previous.purchase_price || supply_change < 0;
+    let sold = current.sell_price < previous.sell_price || supply_change > 0;
+
+    match current.trade_good_type {
+        TradeGoodType::Export => bought,
+        TradeGoodType::Import => sold,
+        TradeGoodType::Exchange => bought || sold,
+    }
+}
+
+/// A competitor at either end of a route might empty the export or saturate the import before we get there.
+/// The expected profit of a route gets discounted by the score of its more contested market.
+pub fn discount_contested_trading_opportunities(
+    trading_opportunities: Vec<TradingOpportunity>,
+    contestation_scores: &HashMap<WaypointSymbol, f64>,
+    max_route_discount: f64,
+) -> Vec<TradingOpportunity> {
+    trading_opportunities
+        .into_iter()
+        .map(|opportunity| {
+            let score_of = |wps: &WaypointSymbol| contestation_scores.get(wps).cloned().unwrap_or_default();
+            let score = score_of(&opportunity.purchase_waypoint_symbol).max(score_of(&opportunity.sell_waypoint_symbol));
+            let factor = 1.0 - max_route_discount * score.clamp(0.0, 1.0);
+
+            TradingOpportunity {
+                profit_per_unit: (opportunity.profit_per_unit as f64 * factor).round() as u64,
+                profit_per_unit_per_distance: OrderedFloat(opportunity.profit_per_unit_per_distance.0 * factor),
+                ..opportunity
+            }
+        })
+        .sorted_by_key(|opportunity| opportunity.profit_per_unit_per_distance)
+        .rev()
+        .collect_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MarketData, ShipSymbol, SupplyLevel, TransactionType};
+    use chrono::TimeDelta;
+
+    fn observation(minutes: i64, purchase_price: i32, supply: SupplyLevel, transactions: Vec<Transaction>) -> MarketEntry {
+        let wps = WaypointSymbol("X1-FOO-A1".to_string());
+        MarketEntry {
+            waypoint_symbol: wps.clone(),
+            market_data: MarketData {
+                symbol: wps,
+                exports: vec![],
+                imports: vec![],
+                exchange: vec![],
+                transactions: Some(transactions),
+                trade_goods: Some(vec![MarketTradeGood {
+                    symbol: TradeGoodSymbol::IRON,
+                    trade_good_type: TradeGoodType::Export,
+                    trade_volume: 60,
+                    supply,
+                    activity: None,
+                    purchase_price,
+                    sell_price: purchase_price - 10,
+                }]),
+            },
+            created_at: DateTime::<Utc>::default() + TimeDelta::minutes(minutes),
+        }
+    }
+
+    fn purchase(ship_symbol: &str, minutes: i64) -> Transaction {
+        Transaction {
+            waypoint_symbol: WaypointSymbol("X1-FOO-A1".to_string()),
+            ship_symbol: ShipSymbol(ship_symbol.to_string()),
+            trade_symbol: TradeGoodSymbol::IRON,
+            transaction_type: TransactionType::Purchase,
+            units: 20,
+            price_per_unit: 100,
+            total_price: 2_000,
+            timestamp: DateTime::<Utc>::default() + TimeDelta::minutes(minutes),
+        }
+    }
+
+    #[test]
+    fn only_price_jumps_we_did_not_cause_count_as_contested() {
+        let us = HashSet::from([AgentSymbol("FLWI".to_string()), AgentSymbol("FLWI_2".to_string())]);
+        let history = vec![
+            observation(0, 100, SupplyLevel::High, vec![]),
+            // our own purchase explains the first jump
+            observation(10, 110, SupplyLevel::Moderate, vec![purchase("FLWI-3", 5)]),
+            // the market recovering on its own isn't suspicious
+            observation(20, 104, SupplyLevel::High, vec![]),
+            // nobody of us was there
+            observation(30, 115, SupplyLevel::Moderate, vec![]),
+            observation(40, 125, SupplyLevel::Limited, vec![purchase("RIVAL-1", 35)]),
+            // the purchase of our other agent explains the last jump
+            observation(50, 135, SupplyLevel::Scarce, vec![purchase("FLWI_2-1", 45)]),
+        ];
+
+        let contestation = measure_market_contestation(&history, &us).unwrap();
+
+        assert_eq!(contestation.num_comparisons, 5);
+        assert_eq!(contestation.num_unexplained_changes, 1);
+        assert_eq!(contestation.num_foreign_transactions, 1);
+        assert_eq!(contestation.score, 0.4);
+
+        assert_eq!(measure_market_contestations(&history, &us), vec![contestation]);
+    }
+}
@@ -0,0 +1,486 @@
+use crate::budgeting::credits::Credits;
+use crate::budgeting::ledger_audit::TimestampedLedgerEntry;
+use crate::budgeting::treasury_redesign::{FinanceTicketDetails, LedgerEntry};
+use crate::trading::{group_markets_by_type, to_trade_goods_with_locations};
+use crate::{Construction, MarketEntry, ShipSymbol, SupplyLevel, TradeGoodSymbol, TradeGoodType, WaypointSymbol};
+use chrono::{DateTime, TimeDelta, Utc};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// How far back we look at construction deliveries when estimating delivery rates.
+pub const DELIVERY_LOOKBACK_HOURS: i64 = 24;
+
+/// Deliveries of the last minutes would inflate the rate - we average over at least this time span.
+const MIN_OBSERVATION_HOURS: f64 = 1.0;
+
+/// Rough guess of how often an export market restocks its trade volume per hour without crashing its price.
+pub fn restocks_per_hour(supply: &SupplyLevel) -> f64 {
+    match supply {
+        SupplyLevel::Scarce => 0.5,
+        SupplyLevel::Limited => 1.0,
+        SupplyLevel::Moderate => 2.0,
+        SupplyLevel::High => 3.0,
+        SupplyLevel::Abundant => 4.0,
+    }
+}
+
+fn raise_supply_level(supply: &SupplyLevel, levels: u32) -> SupplyLevel {
+    let raised = (supply.clone() as u32 + levels).min(SupplyLevel::Abundant as u32);
+    match raised {
+        0 => SupplyLevel::Scarce,
+        1 => SupplyLevel::Limited,
+        2 => SupplyLevel::Moderate,
+        3 => SupplyLevel::High,
+        _ => SupplyLevel::Abundant,
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ConstructionDelivery {
+    pub trade_good: TradeGoodSymbol,
+    pub ship_symbol: ShipSymbol,
+    pub units: u32,
+    pub delivered_at: DateTime<Utc>,
+}
+
+/// Collects the completed `SupplyConstructionSite` tickets of the ledger.
+pub fn construction_deliveries_from_ledger(entries: &[TimestampedLedgerEntry]) -> Vec<ConstructionDelivery> {
+    entries
+        .iter()
+        .filter_map(|TimestampedLedgerEntry { entry, created_at }| match entry {
+            LedgerEntry::TicketCompleted {
+                finance_ticket, actual_units, ..
+            } => match &finance_ticket.details {
+                FinanceTicketDetails::SupplyConstructionSite(d) => Some(ConstructionDelivery {
+                    trade_good: d.trade_good.clone(),
+                    ship_symbol: finance_ticket.ship_symbol.clone(),
+                    units: *actual_units,
+                    delivered_at: *created_at,
+                }),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect_vec()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ExportMarketSupply {
+    pub waypoint_symbol: WaypointSymbol,
+    pub supply: SupplyLevel,
+    pub trade_volume: u32,
+    pub purchase_price: Credits,
+}
+
+impl ExportMarketSupply {
+    pub fn units_per_hour(&self, supply_level_boost: u32) -> f64 {
+        self.trade_volume as f64 * restocks_per_hour(&raise_supply_level(&self.supply, supply_level_boost))
+    }
+}
+
+/// Picks the export market with the best supply (and then the lowest price) for each of the trade goods.
+pub fn best_export_markets(latest_market_entries: &[MarketEntry], trade_goods: &HashSet<TradeGoodSymbol>) -> HashMap<TradeGoodSymbol, ExportMarketSupply> {
+    let trading_entries = to_trade_goods_with_locations(latest_market_entries);
+    group_markets_by_type(&trading_entries, TradeGoodType::Export)
+        .into_iter()
+        .filter(|(tg, _)| trade_goods.contains(tg))
+        .filter_map(|(tg, markets)| {
+            markets
+                .into_iter()
+                .max_by_key(|(_, mtg)| (mtg.supply.clone(), -mtg.purchase_price))
+                .map(|(waypoint_symbol, mtg)| {
+                    (
+                        tg,
+                        ExportMarketSupply {
+                            waypoint_symbol,
+                            supply: mtg.supply,
+                            trade_volume: mtg.trade_volume.max(0) as u32,
+                            purchase_price: (mtg.purchase_price as i64).into(),
+                        },
+                    )
+                })
+        })
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum ForecastBottleneck {
+    Completed,
+    /// no hauler delivered this material recently, so we can't tell how fast it's going to be
+    NoHaulerAssigned,
+    HaulerCapacity,
+    MarketSupply,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MaterialForecast {
+    pub trade_good: TradeGoodSymbol,
+    pub required: u32,
+    pub fulfilled: u32,
+    pub remaining: u32,
+    pub num_haulers: u32,
+    pub units_per_hour_per_hauler: Option<f64>,
+    pub market_units_per_hour: Option<f64>,
+    pub projected_units_per_hour: Option<f64>,
+    pub bottleneck: ForecastBottleneck,
+    pub remaining_costs: Credits,
+    pub eta: Option<DateTime<Utc>>,
+}
+
+impl MaterialForecast {
+    fn hours_to_completion(&self) -> Option<f64> {
+        if self.remaining == 0 {
+            Some(0.0)
+        } else {
+            self.projected_units_per_hour
+                .filter(|rate| *rate > 0.0)
+                .map(|rate| self.remaining as f64 / rate)
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct ForecastScenario {
+    pub additional_haulers: u32,
+    pub supply_level_boost: u32,
+}
+
+impl ForecastScenario {
+    pub fn what_if_scenarios() -> Vec<ForecastScenario> {
+        vec![
+            ForecastScenario {
+                additional_haulers: 1,
+                supply_level_boost: 0,
+            },
+            ForecastScenario {
+                additional_haulers: 2,
+                supply_level_boost: 0,
+            },
+            ForecastScenario {
+                additional_haulers: 0,
+                supply_level_boost: 1,
+            },
+            ForecastScenario {
+                additional_haulers: 1,
+                supply_level_boost: 1,
+            },
+        ]
+    }
+
+    pub fn label(&self) -> String {
+        let mut parts = vec![];
+        if self.additional_haulers > 0 {
+            parts.push(format!("+{} hauler(s)", self.additional_haulers));
+        }
+        if self.supply_level_boost > 0 {
+            parts.push(format!("export supply +{} level(s)", self.supply_level_boost));
+        }
+        if parts.is_empty() {
+            "current fleet".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ScenarioForecast {
+    pub label: String,
+    pub eta: Option<DateTime<Utc>>,
+    pub bottleneck_material: Option<TradeGoodSymbol>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ConstructionForecast {
+    pub computed_at: DateTime<Utc>,
+    pub materials: Vec<MaterialForecast>,
+    /// the material that gets finished last - None if the construction is complete
+    pub bottleneck_material: Option<TradeGoodSymbol>,
+    /// None if we can't tell yet, because one of the materials isn't being delivered
+    pub eta: Option<DateTime<Utc>>,
+    pub available_budget: Credits,
+    pub remaining_costs: Credits,
+    pub budget_shortfall: Credits,
+    pub scenarios: Vec<ScenarioForecast>,
+}
+
+/// Projects when each construction material will be complete.
+///
+/// The delivery rate of a material is the minimum of
+/// - what its haulers delivered per hour within the last `DELIVERY_LOOKBACK_HOURS`
+/// - what its best export market can restock per hour at its current supply level
+///
+/// Additional haulers of a what-if scenario are assigned one by one to the material that's finished last.
+/// They deliver at the average rate of all haulers.
+pub fn forecast_construction(
+    construction: &Construction,
+    deliveries: &[ConstructionDelivery],
+    export_markets: &HashMap<TradeGoodSymbol, ExportMarketSupply>,
+    available_budget: Credits,
+    now: DateTime<Utc>,
+) -> ConstructionForecast {
+    let recent_deliveries = deliveries
+        .iter()
+        .filter(|d| d.delivered_at >= now - TimeDelta::hours(DELIVERY_LOOKBACK_HOURS))
+        .collect_vec();
+
+    let materials = forecast_materials(construction, &recent_deliveries, export_markets, &ForecastScenario::default(), now);
+    let (bottleneck_material, eta) = overall_eta(&materials);
+
+    let remaining_costs: Credits = materials
+        .iter()
+        .map(|m| m.remaining_costs.0)
+        .sum::<i64>()
+        .into();
+    let budget_shortfall: Credits = (remaining_costs - available_budget).0.max(0).into();
+
+    let scenarios = ForecastScenario::what_if_scenarios()
+        .into_iter()
+        .map(|scenario| {
+            let scenario_materials = forecast_materials(construction, &recent_deliveries, export_markets, &scenario, now);
+            let (bottleneck_material, eta) = overall_eta(&scenario_materials);
+            ScenarioForecast {
+                label: scenario.label(),
+                eta,
+                bottleneck_material,
+            }
+        })
+        .collect_vec();
+
+    ConstructionForecast {
+        computed_at: now,
+        materials,
+        bottleneck_material,
+        eta,
+        available_budget,
+        remaining_costs,
+        budget_shortfall,
+        scenarios,
+    }
+}
+
+struct ObservedDeliveries {
+    units: u32,
+    haulers: HashSet<ShipSymbol>,
+    first_delivery_at: DateTime<Utc>,
+}
+
+fn forecast_materials(
+    construction: &Construction,
+    recent_deliveries: &[&ConstructionDelivery],
+    export_markets: &HashMap<TradeGoodSymbol, ExportMarketSupply>,
+    scenario: &ForecastScenario,
+    now: DateTime<Utc>,
+) -> Vec<MaterialForecast> {
+    let mut observed: BTreeMap<TradeGoodSymbol, ObservedDeliveries> = BTreeMap::new();
+    for delivery in recent_deliveries {
+        let entry = observed
+            .entry(delivery.trade_good.clone())
+            .or_insert_with(|| ObservedDeliveries {
+                units: 0,
+                haulers: HashSet::new(),
+                first_delivery_at: delivery.delivered_at,
+            });
+        entry.units += delivery.units;
+        entry.haulers.insert(delivery.ship_symbol.clone());
+        entry.first_delivery_at = entry.first_delivery_at.min(delivery.delivered_at);
+    }
+
+    let rate_per_hauler = |obs: &ObservedDeliveries| {
+        let hours = ((now - obs.first_delivery_at).num_seconds() as f64 / 3600.0).max(MIN_OBSERVATION_HOURS);
+        obs.units as f64 / hours / obs.haulers.len().max(1) as f64
+    };
+
+    let average_rate_per_hauler = if observed.is_empty() {
+        None
+    } else {
+        Some(observed.values().map(rate_per_hauler).sum::<f64>() / observed.len() as f64)
+    };
+
+    let mut materials = construction
+        .materials
+        .iter()
+        .map(|cm| {
+            let remaining = cm.required.saturating_sub(cm.fulfilled);
+            let maybe_observed = observed.get(&cm.trade_symbol);
+            let maybe_market = export_markets.get(&cm.trade_symbol);
+            MaterialForecast {
+                trade_good: cm.trade_symbol.clone(),
+                required: cm.required,
+                fulfilled: cm.fulfilled,
+                remaining,
+                num_haulers: maybe_observed
+                    .map(|obs| obs.haulers.len() as u32)
+                    .unwrap_or_default(),
+                units_per_hour_per_hauler: maybe_observed
+                    .map(rate_per_hauler)
+                    .or(average_rate_per_hauler),
+                market_units_per_hour: maybe_market.map(|market| market.units_per_hour(scenario.supply_level_boost)),
+                projected_units_per_hour: None,
+                bottleneck: ForecastBottleneck::NoHaulerAssigned,
+                remaining_costs: maybe_market
+                    .map(|market| (market.purchase_price.0 * remaining as i64).into())
+                    .unwrap_or_default(),
+                eta: None,
+            }
+        })
+        .collect_vec();
+
+    materials.iter_mut().for_each(|m| update_projection(m, now));
+
+    for _ in 0..scenario.additional_haulers {
+        let maybe_slowest = materials
+            .iter_mut()
+            .filter(|m| m.units_per_hour_per_hauler.is_some())
+            .filter(|m| matches!(m.bottleneck, ForecastBottleneck::NoHaulerAssigned | ForecastBottleneck::HaulerCapacity))
+            .max_by(|a, b| {
+                let hours_a = a.hours_to_completion().unwrap_or(f64::INFINITY);
+                let hours_b = b.hours_to_completion().unwrap_or(f64::INFINITY);
+                hours_a.total_cmp(&hours_b)
+            });
+        if let Some(slowest) = maybe_slowest {
+            slowest.num_haulers += 1;
+            update_projection(slowest, now);
+        }
+    }
+
+    materials
+}
+
+fn update_projection(material: &mut MaterialForecast, now: DateTime<Utc>) {
+    if material.remaining == 0 {
+        material.bottleneck = ForecastBottleneck::Completed;
+        material.eta = Some(now);
+        return;
+    }
+
+    let hauler_units_per_hour = material
+        .units_per_hour_per_hauler
+        .filter(|_| material.num_haulers > 0)
+        .map(|rate| rate * material.num_haulers as f64);
+
+    let (projected_units_per_hour, bottleneck) = match (hauler_units_per_hour, material.market_units_per_hour) {
+        (None, _) => (None, ForecastBottleneck::NoHaulerAssigned),
+        (Some(hauler_rate), Some(market_rate)) if market_rate < hauler_rate => (Some(market_rate), ForecastBottleneck::MarketSupply),
+        (Some(hauler_rate), _) => (Some(hauler_rate), ForecastBottleneck::HaulerCapacity),
+    };
+
+    material.projected_units_per_hour = projected_units_per_hour;
+    material.bottleneck = bottleneck;
+    material.eta = material
+        .hours_to_completion()
+        .map(|hours| now + TimeDelta::seconds((hours * 3600.0).round() as i64));
+}
+
+fn overall_eta(materials: &[MaterialForecast]) -> (Option<TradeGoodSymbol>, Option<DateTime<Utc>>) {
+    let maybe_slowest = materials.iter().filter(|m| m.remaining > 0).max_by(|a, b| {
+        let hours_a = a.hours_to_completion().unwrap_or(f64::INFINITY);
+        let hours_b = b.hours_to_completion().unwrap_or(f64::INFINITY);
+        hours_a.total_cmp(&hours_b)
+    });
+
+    match maybe_slowest {
+        None => (None, materials.iter().filter_map(|m| m.eta).max()),
+        Some(slowest) => (Some(slowest.trade_good.clone()), slowest.eta),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ConstructionMaterial;
+    use chrono::TimeZone;
+
+    fn ts(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 6, 1, hour, minute, 0).unwrap()
+    }
+
+    fn delivery(trade_good: TradeGoodSymbol, ship: &str, units: u32, delivered_at: DateTime<Utc>) -> ConstructionDelivery {
+        ConstructionDelivery {
+            trade_good,
+            ship_symbol: ShipSymbol(ship.to_string()),
+            units,
+            delivered_at,
+        }
+    }
+
+    fn market(supply: SupplyLevel, trade_volume: u32, purchase_price: i64) -> ExportMarketSupply {
+        ExportMarketSupply {
+            waypoint_symbol: WaypointSymbol("X1-FOO-A1".to_string()),
+            supply,
+            trade_volume,
+            purchase_price: purchase_price.into(),
+        }
+    }
+
+    #[test]
+    fn projects_completion_per_material_and_finds_the_bottleneck() {
+        let construction = Construction {
+            symbol: WaypointSymbol("X1-FOO-I1".to_string()),
+            materials: vec![
+                ConstructionMaterial {
+                    trade_symbol: TradeGoodSymbol::FAB_MATS,
+                    required: 1_600,
+                    fulfilled: 400,
+                },
+                ConstructionMaterial {
+                    trade_symbol: TradeGoodSymbol::ADVANCED_CIRCUITRY,
+                    required: 400,
+                    fulfilled: 100,
+                },
+                ConstructionMaterial {
+                    trade_symbol: TradeGoodSymbol::QUANTUM_STABILIZERS,
+                    required: 1,
+                    fulfilled: 1,
+                },
+            ],
+            is_complete: false,
+        };
+
+        let now = ts(12, 0);
+        let deliveries = vec![
+            // 240 units in 4 hours by one hauler --> 60 units/h
+            delivery(TradeGoodSymbol::FAB_MATS, "HAULER-1", 120, ts(8, 0)),
+            delivery(TradeGoodSymbol::FAB_MATS, "HAULER-1", 120, ts(10, 0)),
+            // 80 units in 2 hours by two haulers --> 20 units/h per hauler
+            delivery(TradeGoodSymbol::ADVANCED_CIRCUITRY, "HAULER-2", 40, ts(10, 0)),
+            delivery(TradeGoodSymbol::ADVANCED_CIRCUITRY, "HAULER-3", 40, ts(11, 0)),
+            // too old
+            delivery(TradeGoodSymbol::ADVANCED_CIRCUITRY, "HAULER-3", 40, ts(11, 0) - TimeDelta::days(2)),
+        ];
+
+        let export_markets = HashMap::from([
+            (TradeGoodSymbol::FAB_MATS, market(SupplyLevel::Moderate, 60, 2_000)),
+            // restocks only 30 units/h, but the haulers could move 40 units/h
+            (TradeGoodSymbol::ADVANCED_CIRCUITRY, market(SupplyLevel::Limited, 30, 4_000)),
+        ]);
+
+        let forecast = forecast_construction(&construction, &deliveries, &export_markets, 1_000_000.into(), now);
+
+        let fab_mats = &forecast.materials[0];
+        assert_eq!(fab_mats.remaining, 1_200);
+        assert_eq!(fab_mats.num_haulers, 1);
+        assert_eq!(fab_mats.projected_units_per_hour, Some(60.0));
+        assert_eq!(fab_mats.bottleneck, ForecastBottleneck::HaulerCapacity);
+        assert_eq!(fab_mats.eta, Some(now + TimeDelta::hours(20)));
+
+        let advanced_circuitry = &forecast.materials[1];
+        assert_eq!(advanced_circuitry.num_haulers, 2);
+        assert_eq!(advanced_circuitry.projected_units_per_hour, Some(30.0));
+        assert_eq!(advanced_circuitry.bottleneck, ForecastBottleneck::MarketSupply);
+        assert_eq!(advanced_circuitry.eta, Some(now + TimeDelta::hours(10)));
+
+        assert_eq!(forecast.materials[2].bottleneck, ForecastBottleneck::Completed);
+
+        assert_eq!(forecast.bottleneck_material, Some(TradeGoodSymbol::FAB_MATS));
+        assert_eq!(forecast.eta, Some(now + TimeDelta::hours(20)));
+        assert_eq!(forecast.remaining_costs, (1_200 * 2_000 + 300 * 4_000).into());
+        assert_eq!(forecast.budget_shortfall, (3_600_000 - 1_000_000).into());
+
+        // the additional hauler helps with FAB_MATS: 1_200 units at 120 units/h
+        let one_more_hauler = &forecast.scenarios[0];
+        assert_eq!(one_more_hauler.label, "+1 hauler(s)");
+        assert_eq!(one_more_hauler.eta, Some(now + TimeDelta::hours(10)));
+    }
+}
@@ -0,0 +1,299 @@
+use crate::budgeting::credits::Credits;
+use crate::{Extraction, RawDeliveryRoute, Survey, SurveySize, TradeGoodSymbol, WaypointSymbol};
+use chrono::{DateTime, DurationRound, TimeDelta, Utc};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Rough yield of a mining drone. Used as long as we haven't observed any extractions.
+pub const DEFAULT_UNITS_PER_EXTRACTION: f64 = 7.0;
+
+/// How far back we look at the survey_usage_log when estimating yields.
+pub const YIELD_LOOKBACK_HOURS: i64 = 24;
+
+/// Surveys that expire sooner are likely to be rejected by the time the extract-request arrives.
+pub const MIN_SURVEY_TIME_LEFT_SECS: i64 = 15;
+
+/// An entry of the survey_usage_log together with the survey that has been used for the extraction.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SurveyUsage {
+    pub survey: Survey,
+    pub extraction: Extraction,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct YieldStats {
+    pub num_extractions: u32,
+    pub units_by_trade_good: BTreeMap<TradeGoodSymbol, u32>,
+}
+
+impl YieldStats {
+    pub fn add_extraction(&mut self, extraction: &Extraction) {
+        self.num_extractions += 1;
+        *self
+            .units_by_trade_good
+            .entry(extraction.extraction_yield.symbol.clone())
+            .or_default() += extraction.extraction_yield.units;
+    }
+
+    pub fn total_units(&self) -> u32 {
+        self.units_by_trade_good.values().sum()
+    }
+
+    pub fn avg_units_per_extraction(&self) -> f64 {
+        self.total_units() as f64 / self.num_extractions.max(1) as f64
+    }
+
+    /// Goods without a price are worth nothing to us - we jettison them.
+    pub fn avg_value_per_extraction(&self, prices: &HashMap<TradeGoodSymbol, Credits>) -> f64 {
+        let total_value: i64 = self
+            .units_by_trade_good
+            .iter()
+            .map(|(tg, units)| {
+                prices
+                    .get(tg)
+                    .map(|price| price.0 * *units as i64)
+                    .unwrap_or_default()
+            })
+            .sum();
+
+        total_value as f64 / self.num_extractions.max(1) as f64
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ExtractionYieldReport {
+    pub by_asteroid: BTreeMap<WaypointSymbol, YieldStats>,
+    pub by_survey_size: BTreeMap<SurveySize, YieldStats>,
+    /// Keyed by the deposit type we got, not by the deposits the survey promised.
+    pub by_deposit_type: BTreeMap<TradeGoodSymbol, YieldStats>,
+    pub by_hour: BTreeMap<DateTime<Utc>, YieldStats>,
+}
+
+impl ExtractionYieldReport {
+    pub fn from_survey_usages(survey_usages: &[SurveyUsage]) -> Self {
+        let mut report = Self::default();
+
+        for usage in survey_usages {
+            let hour = usage
+                .created_at
+                .duration_trunc(TimeDelta::hours(1))
+                .unwrap_or(usage.created_at);
+
+            for stats in [
+                report
+                    .by_asteroid
+                    .entry(usage.survey.waypoint_symbol.clone())
+                    .or_default(),
+                report
+                    .by_survey_size
+                    .entry(usage.survey.size.clone())
+                    .or_default(),
+                report
+                    .by_deposit_type
+                    .entry(usage.extraction.extraction_yield.symbol.clone())
+                    .or_default(),
+                report.by_hour.entry(hour).or_default(),
+            ] {
+                stats.add_extraction(&usage.extraction);
+            }
+        }
+
+        report
+    }
+
+    pub fn expected_units_per_extraction(&self, survey_size: &SurveySize) -> f64 {
+        match self.by_survey_size.get(survey_size) {
+            Some(stats) if stats.num_extractions > 0 => stats.avg_units_per_extraction(),
+            _ => DEFAULT_UNITS_PER_EXTRACTION,
+        }
+    }
+}
+
+/// The price we currently get for each good at its delivery market. Goods that aren't in demand are left out.
+pub fn delivery_prices(
+    delivery_locations: &HashMap<TradeGoodSymbol, RawDeliveryRoute>,
+    demanded_goods: &HashSet<TradeGoodSymbol>,
+) -> HashMap<TradeGoodSymbol, Credits> {
+    delivery_locations
+        .iter()
+        .filter(|(tg, _)| demanded_goods.contains(tg))
+        .map(|(tg, route)| (tg.clone(), Credits::new(route.delivery_market_entry.sell_price as i64)))
+        .collect()
+}
+
+/// Each extraction with a survey yields one of its deposits - every listed deposit is equally likely.
+pub fn expected_value_per_extraction(survey: &Survey, prices: &HashMap<TradeGoodSymbol, Credits>, expected_units: f64) -> f64 {
+    if survey.deposits.is_empty() {
+        return 0.0;
+    }
+
+    let avg_price = survey
+        .deposits
+        .iter()
+        .map(|deposit| prices.get(&deposit.symbol).map(|p| p.0).unwrap_or_default() as f64)
+        .sum::<f64>()
+        / survey.deposits.len() as f64;
+
+    avg_price * expected_units
+}
+
+/// Deducts the costs of hauling a unit of cargo to its delivery market from the sell price. Goods that aren't worth the trip are dropped.
+pub fn haul_adjusted_prices(
+    prices: &HashMap<TradeGoodSymbol, Credits>,
+    haul_distances: &HashMap<TradeGoodSymbol, u32>,
+    haul_cost_per_unit_and_distance: f64,
+) -> HashMap<TradeGoodSymbol, Credits> {
+    prices
+        .iter()
+        .filter_map(|(tg, price)| {
+            let distance = haul_distances.get(tg).copied().unwrap_or_default();
+            let haul_cost = (distance as f64 * haul_cost_per_unit_and_distance).round() as i64;
+            let net_price = price.0 - haul_cost;
+            (net_price > 0).then(|| (tg.clone(), Credits::new(net_price)))
+        })
+        .collect()
+}
+
+/// Ranks the surveys by their expected credit value per extraction - the most valuable first.
+/// Surveys that only contain goods we don't need or that are about to expire are skipped.
+/// Of equally valuable surveys we use the one that expires first.
+pub fn rank_surveys(
+    surveys: &[Survey],
+    prices: &HashMap<TradeGoodSymbol, Credits>,
+    yield_report: &ExtractionYieldReport,
+    now: DateTime<Utc>,
+) -> Vec<(Survey, f64)> {
+    surveys
+        .iter()
+        .filter(|survey| survey.expiration - now >= TimeDelta::seconds(MIN_SURVEY_TIME_LEFT_SECS))
+        .map(|survey| {
+            let expected_units = yield_report.expected_units_per_extraction(&survey.size);
+            (survey.clone(), expected_value_per_extraction(survey, prices, expected_units))
+        })
+        .filter(|(_, value)| *value > 0.0)
+        .sorted_by(|(a, value_a), (b, value_b)| {
+            value_b
+                .total_cmp(value_a)
+                .then_with(|| a.expiration.cmp(&b.expiration))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ExtractionYield, ShipSymbol, SurveyDeposit, SurveySignature};
+    use chrono::TimeZone;
+
+    fn survey(signature: &str, waypoint: &str, size: SurveySize, deposits: &[TradeGoodSymbol]) -> Survey {
+        Survey {
+            signature: SurveySignature(signature.to_string()),
+            waypoint_symbol: WaypointSymbol(waypoint.to_string()),
+            deposits: deposits
+                .iter()
+                .map(|tg| SurveyDeposit { symbol: tg.clone() })
+                .collect(),
+            expiration: Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap(),
+            size,
+        }
+    }
+
+    fn usage(survey: &Survey, trade_good: TradeGoodSymbol, units: u32, minute: u32) -> SurveyUsage {
+        SurveyUsage {
+            survey: survey.clone(),
+            extraction: Extraction {
+                ship_symbol: ShipSymbol("MINER-1".to_string()),
+                extraction_yield: ExtractionYield { symbol: trade_good, units },
+            },
+            created_at: Utc.with_ymd_and_hms(2025, 6, 1, 10, 0, 0).unwrap() + TimeDelta::minutes(minute as i64),
+        }
+    }
+
+    #[test]
+    fn aggregates_yields_by_asteroid_size_deposit_and_hour() {
+        let large = survey("A-1", "X1-FOO-A", SurveySize::LARGE, &[TradeGoodSymbol::IRON_ORE]);
+        let small = survey("B-1", "X1-FOO-B", SurveySize::SMALL, &[TradeGoodSymbol::COPPER_ORE]);
+
+        let usages = vec![
+            usage(&large, TradeGoodSymbol::IRON_ORE, 10, 0),
+            usage(&large, TradeGoodSymbol::IRON_ORE, 8, 30),
+            usage(&small, TradeGoodSymbol::COPPER_ORE, 3, 70),
+        ];
+
+        let report = ExtractionYieldReport::from_survey_usages(&usages);
+
+        let asteroid_a = &report.by_asteroid[&WaypointSymbol("X1-FOO-A".to_string())];
+        assert_eq!(asteroid_a.num_extractions, 2);
+        assert_eq!(asteroid_a.avg_units_per_extraction(), 9.0);
+
+        assert_eq!(report.expected_units_per_extraction(&SurveySize::LARGE), 9.0);
+        assert_eq!(report.expected_units_per_extraction(&SurveySize::SMALL), 3.0);
+        assert_eq!(report.expected_units_per_extraction(&SurveySize::MODERATE), DEFAULT_UNITS_PER_EXTRACTION);
+
+        assert_eq!(report.by_deposit_type[&TradeGoodSymbol::COPPER_ORE].total_units(), 3);
+        assert_eq!(
+            report
+                .by_hour
+                .values()
+                .map(|stats| stats.num_extractions)
+                .collect_vec(),
+            vec![2, 1]
+        );
+
+        let prices = HashMap::from([(TradeGoodSymbol::IRON_ORE, Credits::new(50))]);
+        assert_eq!(asteroid_a.avg_value_per_extraction(&prices), 450.0);
+        // copper is not in demand
+        assert_eq!(
+            report.by_asteroid[&WaypointSymbol("X1-FOO-B".to_string())].avg_value_per_extraction(&prices),
+            0.0
+        );
+    }
+
+    #[test]
+    fn ranks_surveys_by_expected_value() {
+        let prices = HashMap::from([
+            (TradeGoodSymbol::IRON_ORE, Credits::new(50)),
+            (TradeGoodSymbol::COPPER_ORE, Credits::new(100)),
+        ]);
+
+        let iron_only = survey("IRON", "X1-FOO-A", SurveySize::LARGE, &[TradeGoodSymbol::IRON_ORE, TradeGoodSymbol::IRON_ORE]);
+        let mixed = survey(
+            "MIXED",
+            "X1-FOO-A",
+            SurveySize::SMALL,
+            &[TradeGoodSymbol::COPPER_ORE, TradeGoodSymbol::QUARTZ_SAND],
+        );
+        let worthless = survey("WORTHLESS", "X1-FOO-A", SurveySize::LARGE, &[TradeGoodSymbol::QUARTZ_SAND]);
+
+        let now = Utc.with_ymd_and_hms(2025, 6, 1, 11, 0, 0).unwrap();
+
+        // without observations all sizes yield the same: 50c (iron) vs. (100c + 0c) / 2 (mixed)
+        let no_observations = ExtractionYieldReport::default();
+        let surveys = vec![worthless.clone(), mixed.clone(), iron_only.clone()];
+        let ranked = rank_surveys(&surveys, &prices, &no_observations, now);
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].1, 50.0 * DEFAULT_UNITS_PER_EXTRACTION);
+
+        // large surveys yielded more units in the past
+        let report = ExtractionYieldReport::from_survey_usages(&[
+            usage(&iron_only, TradeGoodSymbol::IRON_ORE, 10, 0),
+            usage(&mixed, TradeGoodSymbol::COPPER_ORE, 4, 5),
+        ]);
+        let ranked = rank_surveys(&surveys, &prices, &report, now);
+        assert_eq!(ranked[0], (iron_only.clone(), 500.0));
+        assert_eq!(ranked[1], (mixed.clone(), 200.0));
+
+        // hauling copper ore is too expensive - iron ore loses 10c per unit
+        let distances = HashMap::from([(TradeGoodSymbol::IRON_ORE, 100), (TradeGoodSymbol::COPPER_ORE, 2000)]);
+        let net_prices = haul_adjusted_prices(&prices, &distances, 0.1);
+        assert_eq!(net_prices, HashMap::from([(TradeGoodSymbol::IRON_ORE, Credits::new(40))]));
+        let ranked = rank_surveys(&surveys, &net_prices, &report, now);
+        assert_eq!(ranked, vec![(iron_only.clone(), 400.0)]);
+
+        // surveys that are about to expire are skipped
+        let almost_expired = iron_only.expiration - TimeDelta::seconds(MIN_SURVEY_TIME_LEFT_SECS - 1);
+        assert!(rank_surveys(&surveys, &prices, &report, almost_expired).is_empty());
+    }
+}
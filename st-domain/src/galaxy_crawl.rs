@@ -0,0 +1,92 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// How far the background crawler got with downloading the systems and waypoints of the whole galaxy of the current reset.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct GalaxyCrawlProgress {
+    /// number of systems of the galaxy according to the status endpoint
+    pub total_systems: u32,
+    /// the systems are downloaded page by page - the crawler resumes with the page after this one
+    pub completed_systems_pages: u32,
+    pub systems_in_db: u32,
+    pub systems_with_missing_waypoints: u32,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl GalaxyCrawlProgress {
+    pub fn new(total_systems: u32, now: DateTime<Utc>) -> Self {
+        Self {
+            total_systems,
+            completed_systems_pages: 0,
+            systems_in_db: 0,
+            // we don't know better until the systems have been downloaded
+            systems_with_missing_waypoints: total_systems,
+            started_at: now,
+            updated_at: now,
+            completed_at: None,
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.completed_at.is_some()
+    }
+
+    pub fn has_all_systems(&self) -> bool {
+        self.systems_in_db >= self.total_systems
+    }
+
+    /// ratio of systems whose waypoints have been downloaded
+    pub fn completion_ratio(&self) -> f64 {
+        if self.total_systems == 0 {
+            return 1.0;
+        }
+        let systems_with_waypoints = self
+            .systems_in_db
+            .saturating_sub(self.systems_with_missing_waypoints);
+        systems_with_waypoints as f64 / self.total_systems as f64
+    }
+
+    pub fn update_counts(&mut self, systems_in_db: u32, systems_with_missing_waypoints: u32, now: DateTime<Utc>) {
+        self.systems_in_db = systems_in_db;
+        self.systems_with_missing_waypoints = systems_with_missing_waypoints;
+        self.updated_at = now;
+        self.completed_at = if self.has_all_systems() && systems_with_missing_waypoints == 0 {
+            self.completed_at.or(Some(now))
+        } else {
+            None
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn completes_once_all_systems_have_their_waypoints() {
+        let ts = |hour| Utc.with_ymd_and_hms(2025, 6, 1, hour, 0, 0).unwrap();
+
+        let mut progress = GalaxyCrawlProgress::new(100, ts(8));
+        assert_eq!(progress.completion_ratio(), 0.0);
+
+        progress.update_counts(60, 60, ts(9));
+        assert!(!progress.has_all_systems());
+        assert!(!progress.is_complete());
+
+        progress.update_counts(100, 25, ts(10));
+        assert!(progress.has_all_systems());
+        assert!(!progress.is_complete());
+        assert_eq!(progress.completion_ratio(), 0.75);
+
+        progress.update_counts(100, 0, ts(11));
+        assert_eq!(progress.completed_at, Some(ts(11)));
+
+        // a later check doesn't move the completion timestamp
+        progress.update_counts(100, 0, ts(12));
+        assert_eq!(progress.completed_at, Some(ts(11)));
+        assert_eq!(progress.updated_at, ts(12));
+    }
+}
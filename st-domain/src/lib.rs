@@ -1,9 +1,22 @@
 pub mod blackboard_ops;
 pub mod budgeting;
+pub mod cargo_flow;
 pub mod cargo_transfer;
+pub mod competitor_intelligence;
+pub mod construction_forecast;
+pub mod extraction_analytics;
+pub mod galaxy_crawl;
+pub mod market_history;
+pub mod market_observation;
 pub mod messages;
+pub mod ship_type_evaluation;
 pub mod st_model;
+pub mod strategy;
 pub mod supply_chain;
+pub mod supply_chain_boost;
+pub mod system_map;
+#[cfg(test)]
+mod test_objects;
 pub mod trading;
 
 pub use messages::*;
@@ -0,0 +1,143 @@
+use crate::{ActivityLevel, MarketEntry, SupplyLevel, TradeGoodSymbol, WaypointSymbol};
+use chrono::{DateTime, DurationRound, TimeDelta, Utc};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+/// All observations of a trade good at a market within one hour, condensed into a single row.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MarketHourlyAggregate {
+    pub waypoint_symbol: WaypointSymbol,
+    pub trade_good_symbol: TradeGoodSymbol,
+    pub hour: DateTime<Utc>,
+    pub num_observations: u32,
+    pub min_purchase_price: i32,
+    pub avg_purchase_price: f64,
+    pub max_purchase_price: i32,
+    pub min_sell_price: i32,
+    pub avg_sell_price: f64,
+    pub max_sell_price: i32,
+    pub avg_trade_volume: f64,
+    pub last_supply: SupplyLevel,
+    pub last_activity: Option<ActivityLevel>,
+}
+
+impl MarketHourlyAggregate {
+    /// Combines two aggregates of the same trade good and hour - `later` holds the more recent observations.
+    pub fn merge(self, later: MarketHourlyAggregate) -> MarketHourlyAggregate {
+        let num_observations = self.num_observations + later.num_observations;
+        let (weight, later_weight) = (self.num_observations as f64, later.num_observations as f64);
+        let weighted_avg = |a: f64, b: f64| (a * weight + b * later_weight) / num_observations.max(1) as f64;
+
+        MarketHourlyAggregate {
+            num_observations,
+            min_purchase_price: self.min_purchase_price.min(later.min_purchase_price),
+            avg_purchase_price: weighted_avg(self.avg_purchase_price, later.avg_purchase_price),
+            max_purchase_price: self.max_purchase_price.max(later.max_purchase_price),
+            min_sell_price: self.min_sell_price.min(later.min_sell_price),
+            avg_sell_price: weighted_avg(self.avg_sell_price, later.avg_sell_price),
+            max_sell_price: self.max_sell_price.max(later.max_sell_price),
+            avg_trade_volume: weighted_avg(self.avg_trade_volume, later.avg_trade_volume),
+            ..later
+        }
+    }
+}
+
+/// Splits the history of a market into the observations we keep and the ones that are old enough to be downsampled.
+/// The latest observation is always kept, because it is still the current state of a market that hasn't been visited for a while.
+pub fn split_off_downsampled_observations(history: Vec<MarketEntry>, older_than: DateTime<Utc>) -> (Vec<MarketEntry>, Vec<MarketEntry>) {
+    let maybe_latest = history.iter().map(|entry| entry.created_at).max();
+
+    history
+        .into_iter()
+        .partition(|entry| entry.created_at >= older_than || Some(entry.created_at) == maybe_latest)
+}
+
+/// Condenses the observations into one aggregate per market, trade good and hour - oldest hour first.
+pub fn aggregate_hourly(observations: &[MarketEntry]) -> Vec<MarketHourlyAggregate> {
+    observations
+        .iter()
+        .sorted_by_key(|entry| entry.created_at)
+        .flat_map(|entry| {
+            let hour = entry
+                .created_at
+                .duration_trunc(TimeDelta::hours(1))
+                .unwrap_or(entry.created_at);
+
+            entry
+                .market_data
+                .trade_goods
+                .iter()
+                .flatten()
+                .map(move |mtg| MarketHourlyAggregate {
+                    waypoint_symbol: entry.waypoint_symbol.clone(),
+                    trade_good_symbol: mtg.symbol.clone(),
+                    hour,
+                    num_observations: 1,
+                    min_purchase_price: mtg.purchase_price,
+                    avg_purchase_price: mtg.purchase_price as f64,
+                    max_purchase_price: mtg.purchase_price,
+                    min_sell_price: mtg.sell_price,
+                    avg_sell_price: mtg.sell_price as f64,
+                    max_sell_price: mtg.sell_price,
+                    avg_trade_volume: mtg.trade_volume as f64,
+                    last_supply: mtg.supply.clone(),
+                    last_activity: mtg.activity.clone(),
+                })
+        })
+        .into_grouping_map_by(|aggregate| (aggregate.waypoint_symbol.clone(), aggregate.trade_good_symbol.clone(), aggregate.hour))
+        .reduce(|acc, _, aggregate| acc.merge(aggregate))
+        .into_values()
+        .sorted_by(|a, b| {
+            a.hour
+                .cmp(&b.hour)
+                .then(a.waypoint_symbol.cmp(&b.waypoint_symbol))
+                .then(a.trade_good_symbol.cmp(&b.trade_good_symbol))
+        })
+        .collect_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_objects::market_entry;
+
+    #[test]
+    fn downsamples_old_observations_but_keeps_the_latest_one() {
+        let start = DateTime::<Utc>::default();
+        let history = vec![
+            market_entry(10, 100, SupplyLevel::Moderate),
+            market_entry(20, 110, SupplyLevel::Moderate),
+            market_entry(50, 120, SupplyLevel::High),
+            market_entry(70, 130, SupplyLevel::High),
+            // the latest observation is older than the cutoff, but the market hasn't been visited since
+            market_entry(110, 140, SupplyLevel::Abundant),
+        ];
+
+        let (kept, downsampled) = split_off_downsampled_observations(history, start + TimeDelta::hours(3));
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].created_at, start + TimeDelta::minutes(110));
+        assert_eq!(downsampled.len(), 4);
+
+        let aggregates = aggregate_hourly(&downsampled);
+        assert_eq!(aggregates.len(), 2);
+
+        let first_hour = &aggregates[0];
+        assert_eq!(first_hour.hour, start);
+        assert_eq!(first_hour.num_observations, 3);
+        assert_eq!(first_hour.min_purchase_price, 100);
+        assert_eq!(first_hour.avg_purchase_price, 110.0);
+        assert_eq!(first_hour.max_sell_price, 110);
+        assert_eq!(first_hour.last_supply, SupplyLevel::High);
+
+        let second_hour = &aggregates[1];
+        assert_eq!(second_hour.hour, start + TimeDelta::hours(1));
+        assert_eq!(second_hour.num_observations, 1);
+
+        // the kept observation gets downsampled later - its hour has been aggregated already
+        let later = aggregate_hourly(&kept).remove(0);
+        let merged = second_hour.clone().merge(later);
+        assert_eq!(merged.num_observations, 2);
+        assert_eq!(merged.avg_purchase_price, 135.0);
+        assert_eq!(merged.last_supply, SupplyLevel::Abundant);
+    }
+}
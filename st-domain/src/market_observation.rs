@@ -0,0 +1,159 @@
+use crate::strategy::MarketObservationStrategy;
+use crate::{MarketEntry, MarketTradeGood, TradeGoodSymbol, WaypointSymbol};
+use chrono::TimeDelta;
+use itertools::Itertools;
+use std::collections::HashMap;
+
+/// How fast the trade goods of a market change between our observations - averaged over its trade goods.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MarketVolatility {
+    /// relative change of the purchase and sell prices per hour
+    pub price_change_per_hour: f64,
+    /// steps of the supply level per hour
+    pub supply_changes_per_hour: f64,
+}
+
+/// Compares each observation of a market with the previous one. Needs at least two observations at different times.
+pub fn measure_market_volatility(history: &[MarketEntry]) -> Option<MarketVolatility> {
+    let observations = history
+        .iter()
+        .filter_map(|entry| {
+            entry.market_data.trade_goods.as_ref().map(|trade_goods| {
+                let goods: HashMap<&TradeGoodSymbol, &MarketTradeGood> = trade_goods.iter().map(|mtg| (&mtg.symbol, mtg)).collect();
+                (entry.created_at, goods)
+            })
+        })
+        .sorted_by_key(|(created_at, _)| *created_at)
+        .collect_vec();
+
+    let mut total_hours = 0.0;
+    let mut total_price_change = 0.0;
+    let mut total_supply_changes = 0.0;
+
+    for ((previous_at, previous_goods), (current_at, current_goods)) in observations.iter().tuple_windows() {
+        let changes = current_goods
+            .iter()
+            .filter_map(|(symbol, current)| {
+                let previous = previous_goods.get(symbol)?;
+                let price_change =
+                    (relative_change(previous.purchase_price, current.purchase_price) + relative_change(previous.sell_price, current.sell_price)) / 2.0;
+                let supply_change = (current.supply.clone() as i32 - previous.supply.clone() as i32).abs() as f64;
+                Some((price_change, supply_change))
+            })
+            .collect_vec();
+
+        if changes.is_empty() {
+            continue;
+        }
+
+        total_hours += (*current_at - *previous_at).num_seconds() as f64 / 3600.0;
+        total_price_change += changes
+            .iter()
+            .map(|(price_change, _)| price_change)
+            .sum::<f64>()
+            / changes.len() as f64;
+        total_supply_changes += changes
+            .iter()
+            .map(|(_, supply_change)| supply_change)
+            .sum::<f64>()
+            / changes.len() as f64;
+    }
+
+    (total_hours > 0.0).then(|| MarketVolatility {
+        price_change_per_hour: total_price_change / total_hours,
+        supply_changes_per_hour: total_supply_changes / total_hours,
+    })
+}
+
+fn relative_change(previous: i32, current: i32) -> f64 {
+    if previous > 0 {
+        (current - previous).abs() as f64 / previous as f64
+    } else {
+        0.0
+    }
+}
+
+/// How long we can wait until the next observation of a market.
+///
+/// We want to see the market again before its prices are expected to have moved more than the strategy allows. Markets without
+/// enough history are observed at the default interval. Our own trades move the prices of a market, so every active trade ticket
+/// there shortens the interval further.
+pub fn observation_interval(maybe_volatility: Option<&MarketVolatility>, num_active_trades: usize, strategy: &MarketObservationStrategy) -> TimeDelta {
+    let minutes = match maybe_volatility {
+        None => strategy.default_interval_minutes as f64,
+        Some(volatility) => {
+            let change_per_hour = volatility.price_change_per_hour + volatility.supply_changes_per_hour * strategy.supply_change_weight;
+            if change_per_hour > 0.0 {
+                strategy.max_price_change / change_per_hour * 60.0
+            } else {
+                strategy.max_interval_minutes as f64
+            }
+        }
+    };
+    let minutes = minutes / (1.0 + num_active_trades as f64 * strategy.trade_weight);
+    let minutes = minutes.clamp(strategy.min_interval_minutes as f64, strategy.max_interval_minutes as f64);
+
+    TimeDelta::seconds((minutes * 60.0).round() as i64)
+}
+
+/// Observing every market at its interval might need more requests than the budget allows. In that case we stretch all intervals
+/// by the same factor, so that the volatile markets still get observed more often than the quiet ones.
+pub fn apply_request_budget(intervals: &HashMap<WaypointSymbol, TimeDelta>, max_requests_per_minute: u32) -> HashMap<WaypointSymbol, TimeDelta> {
+    let requests_per_minute: f64 = intervals
+        .values()
+        .map(|interval| 60.0 / interval.num_seconds().max(1) as f64)
+        .sum();
+    let stretch_factor = (requests_per_minute / max_requests_per_minute.max(1) as f64).max(1.0);
+
+    intervals
+        .iter()
+        .map(|(wps, interval)| (wps.clone(), TimeDelta::seconds((interval.num_seconds() as f64 * stretch_factor).round() as i64)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_objects::market_entry;
+    use crate::SupplyLevel;
+
+    #[test]
+    fn observes_volatile_markets_and_markets_we_trade_at_more_often() {
+        let strategy = MarketObservationStrategy::default();
+
+        let quiet_history = vec![
+            market_entry(0, 110, SupplyLevel::Moderate),
+            market_entry(60, 110, SupplyLevel::Moderate),
+        ];
+        // purchase price moves by 10%, sell price by 11% and supply by one step - within 30 minutes
+        let volatile_history = vec![
+            market_entry(0, 110, SupplyLevel::Moderate),
+            market_entry(30, 121, SupplyLevel::High),
+        ];
+
+        let quiet = measure_market_volatility(&quiet_history).unwrap();
+        let volatile = measure_market_volatility(&volatile_history).unwrap();
+        assert_eq!(quiet.price_change_per_hour, 0.0);
+        assert_eq!(volatile.supply_changes_per_hour, 2.0);
+        assert!(volatile.price_change_per_hour > 0.2);
+        assert_eq!(measure_market_volatility(&quiet_history[..1]), None);
+
+        let max_interval = TimeDelta::minutes(strategy.max_interval_minutes);
+        let min_interval = TimeDelta::minutes(strategy.min_interval_minutes);
+        assert_eq!(observation_interval(Some(&quiet), 0, &strategy), max_interval);
+        let volatile_interval = observation_interval(Some(&volatile), 0, &strategy);
+        assert!(volatile_interval >= min_interval && volatile_interval < TimeDelta::minutes(5));
+        assert_eq!(observation_interval(None, 0, &strategy), TimeDelta::minutes(strategy.default_interval_minutes));
+        assert!(observation_interval(Some(&quiet), 2, &strategy) < max_interval);
+
+        let intervals = HashMap::from([
+            (WaypointSymbol("X1-FOO-A1".to_string()), TimeDelta::minutes(1)),
+            (WaypointSymbol("X1-FOO-A2".to_string()), TimeDelta::minutes(4)),
+        ]);
+        // 1.25 requests per minute don't fit into a budget of 1 - both intervals get stretched by 1.25
+        let stretched = apply_request_budget(&intervals, 1);
+        assert_eq!(stretched[&WaypointSymbol("X1-FOO-A1".to_string())], TimeDelta::seconds(75));
+        assert_eq!(stretched[&WaypointSymbol("X1-FOO-A2".to_string())], TimeDelta::minutes(5));
+        assert_eq!(apply_request_budget(&intervals, 20), intervals);
+    }
+}
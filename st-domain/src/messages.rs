@@ -3,6 +3,7 @@ use crate::budgeting::treasury_redesign::{
     DeliverCargoContractTicketDetails, DeliverConstructionMaterialsTicketDetails, FinanceTicket, PurchaseShipTicketDetails, PurchaseTradeGoodsTicketDetails,
     SellTradeGoodsTicketDetails,
 };
+use crate::strategy::default_ship_price_guesses;
 use crate::{
     Agent, Construction, Contract, FlightMode, JumpGate, MarketData, MaterializedSupplyChain, PurchaseShipResponse, PurchaseTradeGoodResponse,
     RawDeliveryRoute, RefuelShipResponse, SellTradeGoodResponse, Ship, ShipSymbol, ShipType, Shipyard, ShipyardShip, SupplyConstructionSiteResponse,
@@ -126,6 +127,8 @@ pub enum ShipTask {
 
     PrepositionShipForTrade { first_purchase_location: WaypointSymbol },
     SiphonCarboHydratesAtWaypoint { siphoning_waypoint: WaypointSymbol },
+
+    HaulSiphonedGoods { siphoning_waypoint: WaypointSymbol },
     ExecuteContracts,
 }
 
@@ -167,6 +170,11 @@ pub struct SiphoningFleetConfig {
     pub siphoning_waypoint: WaypointSymbol,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct SupplyChainBoostingFleetConfig {
+    pub system_symbol: SystemSymbol,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Display)]
 pub enum FleetConfig {
     SystemSpawningCfg(SystemSpawningFleetConfig),
@@ -175,6 +183,7 @@ pub enum FleetConfig {
     ConstructJumpGateCfg(ConstructJumpGateFleetConfig),
     MiningCfg(MiningFleetConfig),
     SiphoningCfg(SiphoningFleetConfig),
+    SupplyChainBoostingCfg(SupplyChainBoostingFleetConfig),
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
@@ -206,6 +215,7 @@ pub enum FleetTask {
     TradeProfitably { system_symbol: SystemSymbol },
     MineOres { system_symbol: SystemSymbol },
     SiphonGases { system_symbol: SystemSymbol },
+    BoostSupplyChains { system_symbol: SystemSymbol },
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -245,26 +255,20 @@ pub struct FleetPhase {
 pub struct ShipPriceInfo {
     pub price_infos: Vec<(WaypointSymbol, Vec<ShipyardShip>)>,
     pub latest_shipyard_infos: Vec<ShipyardData>,
+    /// fallback for ship types that are sold in the system, but whose price we don't know yet
+    #[serde(default = "default_ship_price_guesses")]
+    pub ship_price_guesses: HashMap<ShipType, u32>,
 }
 
 impl ShipPriceInfo {
-    pub fn guess_price_for_ship(ship_type: &ShipType) -> Option<u32> {
-        match ship_type {
-            ShipType::SHIP_PROBE => Some(25_000),
-            ShipType::SHIP_LIGHT_HAULER => Some(277_000),
-            ShipType::SHIP_LIGHT_SHUTTLE => Some(90_000),
-            ShipType::SHIP_SIPHON_DRONE => Some(40_000),
-            ShipType::SHIP_MINING_DRONE => Some(42_000),
-            ShipType::SHIP_SURVEYOR => Some(30_000),
-            ShipType::SHIP_INTERCEPTOR => None,
-            ShipType::SHIP_COMMAND_FRIGATE => None,
-            ShipType::SHIP_EXPLORER => None,
-            ShipType::SHIP_HEAVY_FREIGHTER => None,
-            ShipType::SHIP_ORE_HOUND => None,
-            ShipType::SHIP_REFINING_FREIGHTER => None,
-            ShipType::SHIP_BULK_FREIGHTER => None,
-        }
+    pub fn with_price_guesses(self, ship_price_guesses: HashMap<ShipType, u32>) -> Self {
+        Self { ship_price_guesses, ..self }
     }
+
+    pub fn guess_price_for_ship(&self, ship_type: &ShipType) -> Option<u32> {
+        self.ship_price_guesses.get(ship_type).cloned()
+    }
+
     pub fn compute_ship_type_purchase_location_map(&self) -> HashMap<ShipType, Vec<WaypointSymbol>> {
         self.latest_shipyard_infos
             .iter()
@@ -296,6 +300,7 @@ impl ShipPriceInfo {
     }
 
     fn get_price_and_location(
+        &self,
         ship_type: &ShipType,
         purchase_price_map: &HashMap<ShipType, Vec<(WaypointSymbol, u32)>>,
         purchase_location_map: &HashMap<ShipType, Vec<WaypointSymbol>>,
@@ -306,9 +311,10 @@ impl ShipPriceInfo {
             .cloned()
             .or_else(|| {
                 purchase_location_map.get(ship_type).and_then(|waypoints| {
-                    waypoints
-                        .first()
-                        .and_then(|wps| Self::guess_price_for_ship(ship_type).map(|guessed_price| (wps.clone(), guessed_price)))
+                    waypoints.first().and_then(|wps| {
+                        self.guess_price_for_ship(ship_type)
+                            .map(|guessed_price| (wps.clone(), guessed_price))
+                    })
                 })
             })
     }
@@ -316,7 +322,7 @@ impl ShipPriceInfo {
     pub fn get_best_purchase_location(&self, ship_type: &ShipType) -> Option<(ShipType, (WaypointSymbol, u32))> {
         let purchase_price_map: HashMap<ShipType, Vec<(WaypointSymbol, u32)>> = self.compute_ship_type_purchase_price_map();
         let purchase_location_map: HashMap<ShipType, Vec<WaypointSymbol>> = self.compute_ship_type_purchase_location_map();
-        Self::get_price_and_location(ship_type, &purchase_price_map, &purchase_location_map)
+        self.get_price_and_location(ship_type, &purchase_price_map, &purchase_location_map)
             .map(|(wps, p)| (*ship_type, wps, p))
             .map(|(st, wps, p)| (st, (wps, p)))
     }
@@ -329,7 +335,7 @@ impl ShipPriceInfo {
             .iter()
             .unique()
             .filter_map(|ship_type| {
-                Self::get_price_and_location(ship_type, &purchase_price_map, &purchase_location_map)
+                self.get_price_and_location(ship_type, &purchase_price_map, &purchase_location_map)
                     .map(|(wps, p)| (*ship_type, wps, p))
                     .map(|(st, wps, p)| (st, (wps, p)))
             })
@@ -451,10 +457,29 @@ pub struct JumpGateEntry {
     pub updated_at: DateTime<Utc>,
 }
 
+#[derive(Serialize, Clone, Debug, Deserialize)]
+pub struct ContractEntry {
+    pub system_symbol: SystemSymbol,
+    pub contract: Contract,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// the offer was declined and is ignored until it expires
+    pub declined_at: Option<DateTime<Utc>>,
+}
+
+impl ContractEntry {
+    /// The API can't decline an offer, so it stays open until its deadline - no new contract can be negotiated meanwhile.
+    pub fn is_open_declined_offer(&self, now: DateTime<Utc>) -> bool {
+        self.declined_at.is_some() && self.contract.deadline_to_accept > now
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct SiphoningOpsConfig {
     pub siphoning_waypoint: WaypointSymbol,
     pub demanded_goods: HashSet<TradeGoodSymbol>,
+    /// share of the full siphoning capacity the delivery markets absorb (0.0 - 1.0)
+    pub demanded_rate: f64,
     pub delivery_locations: HashMap<TradeGoodSymbol, RawDeliveryRoute>,
 }
 
@@ -486,3 +511,20 @@ impl ContractEvaluationResult {
             .into()
     }
 }
+
+/// Expected outcome of a contract, including what our ships could have earned with regular trades in the meantime.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ContractProfitability {
+    pub payment: Credits,
+    pub purchase_costs: Credits,
+    pub travel_secs: u32,
+    pub opportunity_costs: Credits,
+    pub expected_profit: Credits,
+    pub is_completable_before_deadline: bool,
+}
+
+impl ContractProfitability {
+    pub fn is_worth_accepting(&self, min_profit: Credits) -> bool {
+        self.is_completable_before_deadline && self.expected_profit >= min_profit
+    }
+}
@@ -0,0 +1,162 @@
+use crate::budgeting::credits::Credits;
+use crate::strategy::ShoppingListStrategy;
+use crate::{ShipType, WaypointSymbol};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use strum::Display;
+
+/// What we buy a ship for. Hauling covers the trading, construction and supply chain boosting fleets.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Display)]
+pub enum ShipRole {
+    Hauling,
+    Mining,
+    Siphoning,
+}
+
+impl ShipRole {
+    /// The role a ship type has on the shopping lists. Command ships, probes and surveyors can't be replaced by another hull type.
+    pub fn of_ship_type(ship_type: &ShipType) -> Option<ShipRole> {
+        match ship_type {
+            ShipType::SHIP_LIGHT_HAULER | ShipType::SHIP_LIGHT_SHUTTLE | ShipType::SHIP_HEAVY_FREIGHTER | ShipType::SHIP_BULK_FREIGHTER => {
+                Some(ShipRole::Hauling)
+            }
+            ShipType::SHIP_MINING_DRONE | ShipType::SHIP_ORE_HOUND => Some(ShipRole::Mining),
+            ShipType::SHIP_SIPHON_DRONE => Some(ShipRole::Siphoning),
+            _ => None,
+        }
+    }
+}
+
+/// The simulated earnings of a ship type in a role - based on the cheapest offer in the known shipyards.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ShipTypeEvaluation {
+    pub ship_type: ShipType,
+    pub role: ShipRole,
+    pub shipyard: WaypointSymbol,
+    pub price: Credits,
+    pub cargo_capacity: u32,
+    pub engine_speed: u32,
+    pub fuel_capacity: u32,
+    pub profit_per_hour: Credits,
+    /// credits earned per hour for each credit of the purchase price
+    pub return_per_credit: f64,
+    pub payback_hours: Option<f64>,
+}
+
+impl ShipTypeEvaluation {
+    pub fn new(
+        ship_type: ShipType,
+        role: ShipRole,
+        shipyard: WaypointSymbol,
+        price: Credits,
+        (cargo_capacity, engine_speed, fuel_capacity): (u32, u32, u32),
+        profit_per_hour: Credits,
+    ) -> Self {
+        Self {
+            return_per_credit: if price.0 > 0 {
+                profit_per_hour.0 as f64 / price.0 as f64
+            } else {
+                0.0
+            },
+            payback_hours: (profit_per_hour.0 > 0).then(|| price.0 as f64 / profit_per_hour.0 as f64),
+            ship_type,
+            role,
+            shipyard,
+            price,
+            cargo_capacity,
+            engine_speed,
+            fuel_capacity,
+            profit_per_hour,
+        }
+    }
+}
+
+/// The ship type with the best return per credit of each role - roles without a profitable ship type are missing.
+pub fn best_ship_type_per_role(evaluations: &[ShipTypeEvaluation]) -> HashMap<ShipRole, ShipType> {
+    evaluations
+        .iter()
+        .filter(|evaluation| evaluation.profit_per_hour.0 > 0)
+        .into_group_map_by(|evaluation| evaluation.role)
+        .into_iter()
+        .filter_map(|(role, evaluations)| {
+            evaluations
+                .into_iter()
+                .max_by(|a, b| a.return_per_credit.total_cmp(&b.return_per_credit))
+                .map(|best| (role, best.ship_type))
+        })
+        .collect()
+}
+
+/// Replaces every ship of the shopping lists with the best ship type of its role. Ships without a role and roles without a
+/// profitable ship type stay as they are, so the suggestion can be copied into the strategy file as a whole.
+pub fn suggest_shopping_lists(current: &ShoppingListStrategy, evaluations: &[ShipTypeEvaluation]) -> ShoppingListStrategy {
+    let best_per_role = best_ship_type_per_role(evaluations);
+    let replace = |ship_types: &[ShipType]| {
+        ship_types
+            .iter()
+            .map(|ship_type| {
+                ShipRole::of_ship_type(ship_type)
+                    .and_then(|role| best_per_role.get(&role).cloned())
+                    .unwrap_or(*ship_type)
+            })
+            .collect_vec()
+    };
+
+    ShoppingListStrategy {
+        construction_fleet: replace(&current.construction_fleet),
+        mining_squad: replace(&current.mining_squad),
+        num_mining_squads: current.num_mining_squads,
+        siphoning_fleet: replace(&current.siphoning_fleet),
+        supply_chain_boosting_fleet: replace(&current.supply_chain_boosting_fleet),
+        trading_fleet: replace(&current.trading_fleet),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaces_the_ships_of_a_role_with_the_best_return_per_credit() {
+        let evaluation = |ship_type: ShipType, role: ShipRole, price: i64, profit_per_hour: i64| {
+            ShipTypeEvaluation::new(
+                ship_type,
+                role,
+                WaypointSymbol("X1-FOO-A1".to_string()),
+                price.into(),
+                (40, 10, 400),
+                profit_per_hour.into(),
+            )
+        };
+
+        let evaluations = vec![
+            evaluation(ShipType::SHIP_LIGHT_HAULER, ShipRole::Hauling, 270_000, 27_000),
+            evaluation(ShipType::SHIP_LIGHT_SHUTTLE, ShipRole::Hauling, 90_000, 13_500),
+            // the only siphoning ship doesn't earn anything - we keep what we have
+            evaluation(ShipType::SHIP_SIPHON_DRONE, ShipRole::Siphoning, 40_000, 0),
+        ];
+        assert_eq!(evaluations[0].payback_hours, Some(10.0));
+        assert_eq!(evaluations[1].return_per_credit, 0.15);
+        assert_eq!(evaluations[2].payback_hours, None);
+
+        let current = ShoppingListStrategy::default();
+        let suggested = suggest_shopping_lists(&current, &evaluations);
+
+        assert_eq!(
+            suggested.construction_fleet,
+            vec![
+                ShipType::SHIP_COMMAND_FRIGATE,
+                ShipType::SHIP_LIGHT_SHUTTLE,
+                ShipType::SHIP_LIGHT_SHUTTLE,
+                ShipType::SHIP_LIGHT_SHUTTLE
+            ]
+        );
+        assert_eq!(suggested.trading_fleet, [ShipType::SHIP_LIGHT_SHUTTLE].repeat(4));
+        assert_eq!(suggested.siphoning_fleet[0], ShipType::SHIP_SIPHON_DRONE);
+        assert_eq!(suggested.siphoning_fleet[1], ShipType::SHIP_LIGHT_SHUTTLE);
+        // no mining ship has been evaluated
+        assert_eq!(suggested.mining_squad[0], ShipType::SHIP_MINING_DRONE);
+        assert_eq!(suggested.num_mining_squads, current.num_mining_squads);
+    }
+}
@@ -112,6 +112,29 @@ pub struct ShipyardShip {
     pub crew: ShipyardShipCrew,
 }
 
+impl ShipyardShip {
+    pub fn cargo_capacity(&self) -> i32 {
+        self.modules
+            .iter()
+            .map(|module| match module.symbol {
+                ModuleType::MODULE_CARGO_HOLD_I => module.capacity.unwrap_or_default(),
+                ModuleType::MODULE_CARGO_HOLD_II => module.capacity.unwrap_or_default(),
+                ModuleType::MODULE_CARGO_HOLD_III => module.capacity.unwrap_or_default(),
+                _ => 0,
+            })
+            .sum()
+    }
+
+    /// summed up strength of the mounts matching the predicate, e.g. `ShipMountSymbol::is_mining_laser`
+    pub fn mount_strength(&self, predicate: impl Fn(&ShipMountSymbol) -> bool) -> i32 {
+        self.mounts
+            .iter()
+            .filter(|mount| predicate(&mount.symbol))
+            .map(|mount| mount.strength.unwrap_or_default())
+            .sum()
+    }
+}
+
 pub type CreateChartResponse = Data<CreateChartBody>;
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
@@ -1462,7 +1485,6 @@ pub struct Cargo {
 
 impl Cargo {
     pub fn available_cargo_space(&self) -> u32 {
-        
         (self.capacity - self.units) as u32
     }
 
@@ -0,0 +1,458 @@
+use crate::budgeting::credits::Credits;
+use crate::{serialize_as_sorted_map, ShipType, TradeGoodSymbol};
+use anyhow::{bail, Result};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Economic parameters of the fleets that used to be compiled in.
+///
+/// Every section is optional in the strategy file - missing values fall back to the defaults below,
+/// which reproduce the previously hardcoded behavior.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct StrategyConfig {
+    pub budgets: BudgetStrategy,
+    pub construction: ConstructionStrategy,
+    /// Used when no shipyard of the system has reported a price for a ship type yet.
+    /// Replaces the default guesses as a whole if present.
+    #[serde(serialize_with = "serialize_as_sorted_map")]
+    pub ship_price_guesses: HashMap<ShipType, u32>,
+    pub shopping_lists: ShoppingListStrategy,
+    pub cargo_transfer: CargoTransferStrategy,
+    pub mining: MiningStrategy,
+    pub contracts: ContractStrategy,
+    pub supply_chain_boosting: SupplyChainBoostingStrategy,
+    pub ship_purchases: ShipPurchaseStrategy,
+    pub market_observation: MarketObservationStrategy,
+    pub competitors: CompetitorStrategy,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct BudgetStrategy {
+    /// capital per ship with a cargo hold in the trading, construction and supply chain boosting fleets
+    pub budget_per_trader: Credits,
+    /// operating reserve for refueling per fuel-consuming ship
+    pub fuel_reserve_per_ship: Credits,
+    pub spawning_fleet_budget: Credits,
+    pub mining_fleet_budget: Credits,
+    pub siphoning_fleet_budget: Credits,
+}
+
+impl Default for BudgetStrategy {
+    fn default() -> Self {
+        Self {
+            budget_per_trader: 75_000.into(),
+            fuel_reserve_per_ship: 1_000.into(),
+            spawning_fleet_budget: 25_000.into(),
+            mining_fleet_budget: 5_000.into(),
+            siphoning_fleet_budget: 5_000.into(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConstructionStrategy {
+    /// lower values get delivered first - materials without an entry have priority 0
+    #[serde(serialize_with = "serialize_as_sorted_map")]
+    pub material_priorities: HashMap<TradeGoodSymbol, u32>,
+    /// a construction material is only bought if the fleet has more available capital than its limit
+    #[serde(serialize_with = "serialize_as_sorted_map")]
+    pub material_budget_limits: HashMap<TradeGoodSymbol, Credits>,
+    pub default_material_budget_limit: Credits,
+    /// added to the budget of the construction fleet once all ships have been purchased
+    pub budget_after_all_ship_purchases: Credits,
+}
+
+impl Default for ConstructionStrategy {
+    fn default() -> Self {
+        Self {
+            material_priorities: HashMap::from([(TradeGoodSymbol::FAB_MATS, 1), (TradeGoodSymbol::ADVANCED_CIRCUITRY, 2)]),
+            material_budget_limits: HashMap::from([
+                (TradeGoodSymbol::FAB_MATS, 125_000.into()),
+                (TradeGoodSymbol::ADVANCED_CIRCUITRY, 750_000.into()),
+            ]),
+            default_material_budget_limit: 100_000_000.into(),
+            budget_after_all_ship_purchases: 1_000_000.into(),
+        }
+    }
+}
+
+/// The ships we buy per fleet phase (in addition to the probes, which depend on the number of waypoints).
+///
+/// During the construction phase, ships are bought in this order:
+/// construction fleet, first mining squad, siphoning fleet, supply chain boosting fleet, remaining mining squads.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShoppingListStrategy {
+    pub construction_fleet: Vec<ShipType>,
+    pub mining_squad: Vec<ShipType>,
+    pub num_mining_squads: u32,
+    pub siphoning_fleet: Vec<ShipType>,
+    pub supply_chain_boosting_fleet: Vec<ShipType>,
+    pub trading_fleet: Vec<ShipType>,
+}
+
+impl Default for ShoppingListStrategy {
+    fn default() -> Self {
+        Self {
+            construction_fleet: [vec![ShipType::SHIP_COMMAND_FRIGATE], [ShipType::SHIP_LIGHT_HAULER].repeat(3)].concat(),
+            mining_squad: vec![
+                ShipType::SHIP_MINING_DRONE,
+                ShipType::SHIP_LIGHT_HAULER,
+                ShipType::SHIP_SURVEYOR,
+                ShipType::SHIP_MINING_DRONE,
+                ShipType::SHIP_MINING_DRONE,
+            ],
+            num_mining_squads: 2,
+            siphoning_fleet: vec![
+                ShipType::SHIP_SIPHON_DRONE,
+                ShipType::SHIP_LIGHT_HAULER,
+                ShipType::SHIP_SIPHON_DRONE,
+                ShipType::SHIP_SIPHON_DRONE,
+            ],
+            supply_chain_boosting_fleet: [ShipType::SHIP_LIGHT_HAULER].repeat(2),
+            trading_fleet: [ShipType::SHIP_LIGHT_HAULER].repeat(4),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct CargoTransferStrategy {
+    /// haulers waiting for cargo transfers leave once their cargo hold is filled above this ratio
+    pub hauler_fill_threshold: f64,
+}
+
+impl Default for CargoTransferStrategy {
+    fn default() -> Self {
+        Self { hauler_fill_threshold: 0.8 }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct MiningStrategy {
+    /// mining drones are spread across the best ranked asteroids - a site only gets more drones once all better sites are full
+    pub max_drones_per_site: u32,
+    /// hauling costs in credits per unit of cargo and distance unit to the delivery market - deducted from the sell price when valuing surveys
+    pub haul_cost_per_unit_and_distance: f64,
+}
+
+impl Default for MiningStrategy {
+    fn default() -> Self {
+        Self {
+            max_drones_per_site: 3,
+            haul_cost_per_unit_and_distance: 0.05,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ContractStrategy {
+    /// what a hauler earns per hour with regular trades - charged for the travel time of a contract
+    pub opportunity_cost_per_hour: Credits,
+    /// contracts with a lower expected profit are declined
+    pub min_profit: Credits,
+    /// idle haulers of the construction fleet that help the command ship with contracts that don't fit into its cargo hold
+    pub max_helper_haulers: u32,
+}
+
+impl Default for ContractStrategy {
+    fn default() -> Self {
+        Self {
+            opportunity_cost_per_hour: 10_000.into(),
+            min_profit: 1_000.into(),
+            max_helper_haulers: 2,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct SupplyChainBoostingStrategy {
+    /// the supply chain boosting fleet feeds the inputs of the producers of these goods - in order of priority
+    pub producing_trade_goods: Vec<TradeGoodSymbol>,
+    /// how long we feed a producer before we compare its export with the observation before our first delivery
+    pub evaluation_hours: i64,
+    /// feeding pays off if the export volume grew by at least this ratio...
+    pub min_volume_increase: f64,
+    /// ...or the purchase price of the export dropped by at least this ratio (or the supply level went up)
+    pub min_price_decrease: f64,
+}
+
+impl Default for SupplyChainBoostingStrategy {
+    fn default() -> Self {
+        Self {
+            producing_trade_goods: vec![TradeGoodSymbol::FAB_MATS, TradeGoodSymbol::ADVANCED_CIRCUITRY],
+            evaluation_hours: 2,
+            min_volume_increase: 0.1,
+            min_price_decrease: 0.05,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShipPurchaseStrategy {
+    /// added to the last known price of a ship - it might have risen since the shipyard was observed
+    pub price_markup: f64,
+    /// what a new ship earns per hour once it works for its fleet - charged for the time until it gets there
+    pub waiting_cost_per_hour: Credits,
+    /// what the command frigate earns per hour - charged for its detour if it has to purchase a ship
+    pub frigate_cost_per_hour: Credits,
+    /// credits per unit of ship fuel (a unit of FUEL on the market refuels 100)
+    pub fuel_cost_per_unit: f64,
+    /// more purchases at the same shipyard are done by the same purchaser in one visit
+    pub max_batch_size: u32,
+}
+
+impl Default for ShipPurchaseStrategy {
+    fn default() -> Self {
+        Self {
+            price_markup: 0.02,
+            waiting_cost_per_hour: 5_000.into(),
+            frigate_cost_per_hour: 10_000.into(),
+            fuel_cost_per_unit: 0.75,
+            max_batch_size: 3,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct MarketObservationStrategy {
+    /// markets with less than two observations in the history are observed at this interval
+    pub default_interval_minutes: i64,
+    pub min_interval_minutes: i64,
+    pub max_interval_minutes: i64,
+    /// a market gets observed again before its prices are expected to have moved by this ratio
+    pub max_price_change: f64,
+    /// one step of a supply level counts like a price change of this ratio
+    pub supply_change_weight: f64,
+    /// the interval of a market is divided by (1 + trade_weight * our active trade tickets at the market)
+    pub trade_weight: f64,
+    /// market observations of the probes and of docked ships together - the observations after our own trades don't count
+    pub max_requests_per_minute: u32,
+    /// how much market history we use to measure the volatility of a market
+    pub history_hours: i64,
+}
+
+impl Default for MarketObservationStrategy {
+    fn default() -> Self {
+        Self {
+            default_interval_minutes: 10,
+            min_interval_minutes: 2,
+            max_interval_minutes: 60,
+            max_price_change: 0.02,
+            supply_change_weight: 0.05,
+            trade_weight: 0.5,
+            max_requests_per_minute: 20,
+            history_hours: 6,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct CompetitorStrategy {
+    /// how much market history we search for price and supply changes we didn't cause ourselves
+    pub history_hours: i64,
+    /// markets with a higher share of unexplained changes are shown as contested
+    pub contested_threshold: f64,
+    /// the profit of a route between two fully contested markets gets reduced by this ratio
+    pub max_route_discount: f64,
+}
+
+impl Default for CompetitorStrategy {
+    fn default() -> Self {
+        Self {
+            history_hours: 12,
+            contested_threshold: 0.25,
+            max_route_discount: 0.5,
+        }
+    }
+}
+
+pub fn default_ship_price_guesses() -> HashMap<ShipType, u32> {
+    HashMap::from([
+        (ShipType::SHIP_PROBE, 25_000),
+        (ShipType::SHIP_LIGHT_HAULER, 277_000),
+        (ShipType::SHIP_LIGHT_SHUTTLE, 90_000),
+        (ShipType::SHIP_SIPHON_DRONE, 40_000),
+        (ShipType::SHIP_MINING_DRONE, 42_000),
+        (ShipType::SHIP_SURVEYOR, 30_000),
+    ])
+}
+
+impl Default for StrategyConfig {
+    fn default() -> Self {
+        Self {
+            budgets: Default::default(),
+            construction: Default::default(),
+            ship_price_guesses: default_ship_price_guesses(),
+            shopping_lists: Default::default(),
+            cargo_transfer: Default::default(),
+            mining: Default::default(),
+            contracts: Default::default(),
+            supply_chain_boosting: Default::default(),
+            ship_purchases: Default::default(),
+            market_observation: Default::default(),
+            competitors: Default::default(),
+        }
+    }
+}
+
+impl StrategyConfig {
+    /// Collects all validation errors, so that a broken strategy file can be fixed in one go.
+    pub fn validate(&self) -> Result<()> {
+        let mut errors = vec![];
+
+        let BudgetStrategy {
+            budget_per_trader,
+            fuel_reserve_per_ship,
+            spawning_fleet_budget,
+            mining_fleet_budget,
+            siphoning_fleet_budget,
+        } = &self.budgets;
+
+        for (name, credits) in [
+            ("budgets.budget_per_trader", budget_per_trader),
+            ("budgets.fuel_reserve_per_ship", fuel_reserve_per_ship),
+            ("budgets.spawning_fleet_budget", spawning_fleet_budget),
+            ("budgets.mining_fleet_budget", mining_fleet_budget),
+            ("budgets.siphoning_fleet_budget", siphoning_fleet_budget),
+            ("construction.default_material_budget_limit", &self.construction.default_material_budget_limit),
+            (
+                "construction.budget_after_all_ship_purchases",
+                &self.construction.budget_after_all_ship_purchases,
+            ),
+            ("contracts.opportunity_cost_per_hour", &self.contracts.opportunity_cost_per_hour),
+            ("ship_purchases.waiting_cost_per_hour", &self.ship_purchases.waiting_cost_per_hour),
+            ("ship_purchases.frigate_cost_per_hour", &self.ship_purchases.frigate_cost_per_hour),
+        ] {
+            if credits.0 < 0 {
+                errors.push(format!("{name} must not be negative, but is {credits}"));
+            }
+        }
+
+        for (trade_good, limit) in self.construction.material_budget_limits.iter().sorted() {
+            if limit.0 < 0 {
+                errors.push(format!("construction.material_budget_limits.{trade_good} must not be negative, but is {limit}"));
+            }
+        }
+
+        for (ship_type, price) in self.ship_price_guesses.iter().sorted() {
+            if *price == 0 {
+                errors.push(format!("ship_price_guesses.{ship_type} must be positive"));
+            }
+        }
+
+        let shopping_lists = &self.shopping_lists;
+        if shopping_lists.construction_fleet.is_empty() {
+            errors.push("shopping_lists.construction_fleet must not be empty".to_string());
+        }
+        if shopping_lists.trading_fleet.is_empty() {
+            errors.push("shopping_lists.trading_fleet must not be empty".to_string());
+        }
+        if shopping_lists.num_mining_squads > 0 && shopping_lists.mining_squad.is_empty() {
+            errors.push("shopping_lists.mining_squad must not be empty if num_mining_squads is greater than 0".to_string());
+        }
+
+        let fill_threshold = self.cargo_transfer.hauler_fill_threshold;
+        if !(fill_threshold > 0.0 && fill_threshold <= 1.0) {
+            errors.push(format!("cargo_transfer.hauler_fill_threshold must be within (0, 1], but is {fill_threshold}"));
+        }
+
+        if self.mining.max_drones_per_site == 0 {
+            errors.push("mining.max_drones_per_site must be positive".to_string());
+        }
+        let haul_cost = self.mining.haul_cost_per_unit_and_distance;
+        if !(haul_cost >= 0.0 && haul_cost.is_finite()) {
+            errors.push(format!("mining.haul_cost_per_unit_and_distance must not be negative, but is {haul_cost}"));
+        }
+
+        let boosting = &self.supply_chain_boosting;
+        if boosting.evaluation_hours <= 0 {
+            errors.push(format!(
+                "supply_chain_boosting.evaluation_hours must be positive, but is {}",
+                boosting.evaluation_hours
+            ));
+        }
+        for (name, ratio) in [
+            ("supply_chain_boosting.min_volume_increase", boosting.min_volume_increase),
+            ("supply_chain_boosting.min_price_decrease", boosting.min_price_decrease),
+        ] {
+            if !(ratio >= 0.0 && ratio.is_finite()) {
+                errors.push(format!("{name} must not be negative, but is {ratio}"));
+            }
+        }
+
+        let ship_purchases = &self.ship_purchases;
+        for (name, value) in [
+            ("ship_purchases.price_markup", ship_purchases.price_markup),
+            ("ship_purchases.fuel_cost_per_unit", ship_purchases.fuel_cost_per_unit),
+        ] {
+            if !(value >= 0.0 && value.is_finite()) {
+                errors.push(format!("{name} must not be negative, but is {value}"));
+            }
+        }
+        if ship_purchases.max_batch_size == 0 {
+            errors.push("ship_purchases.max_batch_size must be positive".to_string());
+        }
+
+        let observation = &self.market_observation;
+        if observation.min_interval_minutes <= 0 {
+            errors.push(format!(
+                "market_observation.min_interval_minutes must be positive, but is {}",
+                observation.min_interval_minutes
+            ));
+        }
+        if !(observation.min_interval_minutes..=observation.max_interval_minutes).contains(&observation.default_interval_minutes) {
+            errors.push(format!(
+                "market_observation.default_interval_minutes must be within [min_interval_minutes, max_interval_minutes], but is {}",
+                observation.default_interval_minutes
+            ));
+        }
+        for (name, value) in [
+            ("market_observation.max_price_change", observation.max_price_change),
+            ("market_observation.supply_change_weight", observation.supply_change_weight),
+            ("market_observation.trade_weight", observation.trade_weight),
+        ] {
+            if !(value >= 0.0 && value.is_finite()) {
+                errors.push(format!("{name} must not be negative, but is {value}"));
+            }
+        }
+        if observation.max_requests_per_minute == 0 {
+            errors.push("market_observation.max_requests_per_minute must be positive".to_string());
+        }
+        if observation.history_hours <= 0 {
+            errors.push(format!(
+                "market_observation.history_hours must be positive, but is {}",
+                observation.history_hours
+            ));
+        }
+
+        let competitors = &self.competitors;
+        if competitors.history_hours <= 0 {
+            errors.push(format!("competitors.history_hours must be positive, but is {}", competitors.history_hours));
+        }
+        for (name, ratio) in [
+            ("competitors.contested_threshold", competitors.contested_threshold),
+            ("competitors.max_route_discount", competitors.max_route_discount),
+        ] {
+            if !(0.0..=1.0).contains(&ratio) {
+                errors.push(format!("{name} must be within [0, 1], but is {ratio}"));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            bail!("invalid strategy config:\n{}", errors.join("\n"))
+        }
+    }
+}
@@ -0,0 +1,340 @@
+use crate::budgeting::credits::Credits;
+use crate::budgeting::ledger_audit::TimestampedLedgerEntry;
+use crate::budgeting::treasury_redesign::{FinanceTicketDetails, LedgerEntry, PurchaseCargoReason};
+use crate::market_history::MarketHourlyAggregate;
+use crate::strategy::SupplyChainBoostingStrategy;
+use crate::{HigherDeliveryRoute, MarketEntry, SupplyLevel, TicketId, TradeGoodSymbol, WaypointSymbol};
+use chrono::{DateTime, TimeDelta, Utc};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Feeding an import of a producer market to raise the volume of its export - e.g. IRON into the FAB_MATS producer.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BoostedRoute {
+    pub producer: WaypointSymbol,
+    pub input_good: TradeGoodSymbol,
+    pub export_good: TradeGoodSymbol,
+}
+
+impl From<&HigherDeliveryRoute> for BoostedRoute {
+    fn from(route: &HigherDeliveryRoute) -> Self {
+        Self {
+            producer: route.delivery_location.clone(),
+            input_good: route.trade_good.clone(),
+            export_good: route.producing_trade_good.clone(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BoostDeliveries {
+    pub units_delivered: u32,
+    pub purchase_costs: Credits,
+    pub sales_income: Credits,
+    pub first_delivery_at: DateTime<Utc>,
+    pub last_delivery_at: DateTime<Utc>,
+}
+
+impl BoostDeliveries {
+    /// what feeding the producer has cost us so far - the input goods are usually sold at a loss
+    pub fn net_costs(&self) -> Credits {
+        self.purchase_costs - self.sales_income
+    }
+}
+
+/// Collects the completed sales of cargo that has been bought with `PurchaseCargoReason::BoostSupplyChain`,
+/// grouped by the producer market and the delivered trade good.
+pub fn boost_deliveries_from_ledger(entries: &[TimestampedLedgerEntry]) -> HashMap<(WaypointSymbol, TradeGoodSymbol), BoostDeliveries> {
+    let mut boost_purchase_costs: HashMap<TicketId, Credits> = HashMap::new();
+    let mut deliveries: HashMap<(WaypointSymbol, TradeGoodSymbol), BoostDeliveries> = HashMap::new();
+
+    for TimestampedLedgerEntry { entry, created_at } in entries {
+        let LedgerEntry::TicketCompleted {
+            finance_ticket,
+            actual_units,
+            total,
+            ..
+        } = entry
+        else {
+            continue;
+        };
+
+        match &finance_ticket.details {
+            FinanceTicketDetails::PurchaseTradeGoods(d) => {
+                if d.purchase_cargo_reason == Some(PurchaseCargoReason::BoostSupplyChain) {
+                    boost_purchase_costs.insert(finance_ticket.ticket_id, total.abs());
+                }
+            }
+            FinanceTicketDetails::SellTradeGoods(d) => {
+                let Some(purchase_costs) = d
+                    .maybe_matching_purchase_ticket
+                    .and_then(|purchase_ticket_id| boost_purchase_costs.remove(&purchase_ticket_id))
+                else {
+                    continue;
+                };
+
+                deliveries
+                    .entry((d.waypoint_symbol.clone(), d.trade_good.clone()))
+                    .and_modify(|boost_deliveries| {
+                        boost_deliveries.units_delivered += actual_units;
+                        boost_deliveries.purchase_costs += purchase_costs;
+                        boost_deliveries.sales_income += total.abs();
+                        boost_deliveries.last_delivery_at = *created_at;
+                    })
+                    .or_insert_with(|| BoostDeliveries {
+                        units_delivered: *actual_units,
+                        purchase_costs,
+                        sales_income: total.abs(),
+                        first_delivery_at: *created_at,
+                        last_delivery_at: *created_at,
+                    });
+            }
+            _ => {}
+        }
+    }
+
+    deliveries
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ExportObservation {
+    pub observed_at: DateTime<Utc>,
+    pub trade_volume: i32,
+    pub supply: SupplyLevel,
+    pub purchase_price: i32,
+}
+
+/// The observations of the export of a producer market in chronological order.
+/// Observations older than the retention window only survive as hourly aggregates - each of them counts as one observation at the start of its hour.
+pub fn export_observations(
+    hourly_aggregates: &[MarketHourlyAggregate],
+    market_history: &[MarketEntry],
+    producer: &WaypointSymbol,
+    export_good: &TradeGoodSymbol,
+) -> Vec<ExportObservation> {
+    let downsampled = hourly_aggregates
+        .iter()
+        .filter(|agg| &agg.waypoint_symbol == producer && &agg.trade_good_symbol == export_good)
+        .map(|agg| ExportObservation {
+            observed_at: agg.hour,
+            trade_volume: agg.avg_trade_volume.round() as i32,
+            supply: agg.last_supply.clone(),
+            purchase_price: agg.avg_purchase_price.round() as i32,
+        });
+
+    let raw = market_history
+        .iter()
+        .filter(|me| &me.waypoint_symbol == producer)
+        .filter_map(|me| {
+            let trade_goods = me.market_data.trade_goods.as_ref()?;
+            let mtg = trade_goods.iter().find(|mtg| &mtg.symbol == export_good)?;
+            Some(ExportObservation {
+                observed_at: me.created_at,
+                trade_volume: mtg.trade_volume,
+                supply: mtg.supply.clone(),
+                purchase_price: mtg.purchase_price,
+            })
+        });
+
+    downsampled
+        .chain(raw)
+        .sorted_by_key(|obs| obs.observed_at)
+        .collect_vec()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum BoostVerdict {
+    NotStarted,
+    /// we keep feeding the producer until we have observed the export long enough after the first delivery
+    Evaluating,
+    PaysOff,
+    DoesNotPayOff,
+}
+
+impl BoostVerdict {
+    pub fn keep_feeding(&self) -> bool {
+        self != &BoostVerdict::DoesNotPayOff
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SupplyChainBoostEvaluation {
+    pub route: BoostedRoute,
+    pub deliveries: Option<BoostDeliveries>,
+    /// the last observation of the export before our first delivery
+    pub before: Option<ExportObservation>,
+    /// the latest observation of the export after the evaluation period
+    pub after: Option<ExportObservation>,
+    pub verdict: BoostVerdict,
+}
+
+impl SupplyChainBoostEvaluation {
+    pub fn volume_change_ratio(&self) -> Option<f64> {
+        let (before, after) = self.before.as_ref().zip(self.after.as_ref())?;
+        (before.trade_volume > 0).then(|| (after.trade_volume - before.trade_volume) as f64 / before.trade_volume as f64)
+    }
+
+    pub fn price_change_ratio(&self) -> Option<f64> {
+        let (before, after) = self.before.as_ref().zip(self.after.as_ref())?;
+        (before.purchase_price > 0).then(|| (after.purchase_price - before.purchase_price) as f64 / before.purchase_price as f64)
+    }
+}
+
+/// Compares the export of the producer before our first delivery with its latest observation.
+///
+/// Feeding the producer pays off if the export volume or its supply level went up, or its purchase price went down noticeably.
+/// Without an observation from before the first delivery we can't tell and keep feeding.
+pub fn evaluate_supply_chain_boost(
+    route: &BoostedRoute,
+    maybe_deliveries: Option<&BoostDeliveries>,
+    export_history: &[ExportObservation],
+    strategy: &SupplyChainBoostingStrategy,
+    now: DateTime<Utc>,
+) -> SupplyChainBoostEvaluation {
+    let Some(deliveries) = maybe_deliveries else {
+        return SupplyChainBoostEvaluation {
+            route: route.clone(),
+            deliveries: None,
+            before: None,
+            after: None,
+            verdict: BoostVerdict::NotStarted,
+        };
+    };
+
+    let evaluation_starts_at = deliveries.first_delivery_at + TimeDelta::hours(strategy.evaluation_hours);
+
+    let before = export_history
+        .iter()
+        .filter(|obs| obs.observed_at <= deliveries.first_delivery_at)
+        .max_by_key(|obs| obs.observed_at)
+        .cloned();
+
+    let after = export_history
+        .iter()
+        .filter(|obs| obs.observed_at >= evaluation_starts_at)
+        .max_by_key(|obs| obs.observed_at)
+        .cloned();
+
+    let verdict = match (&before, &after) {
+        _ if now < evaluation_starts_at => BoostVerdict::Evaluating,
+        (Some(before), Some(after)) => {
+            let has_raised_volume =
+                after.trade_volume > before.trade_volume && after.trade_volume as f64 >= before.trade_volume as f64 * (1.0 + strategy.min_volume_increase);
+            let has_raised_supply = after.supply > before.supply;
+            let has_lowered_price = (after.purchase_price as f64) <= before.purchase_price as f64 * (1.0 - strategy.min_price_decrease)
+                && after.purchase_price < before.purchase_price;
+
+            if has_raised_volume || has_raised_supply || has_lowered_price {
+                BoostVerdict::PaysOff
+            } else {
+                BoostVerdict::DoesNotPayOff
+            }
+        }
+        _ => BoostVerdict::Evaluating,
+    };
+
+    SupplyChainBoostEvaluation {
+        route: route.clone(),
+        deliveries: Some(deliveries.clone()),
+        before,
+        after,
+        verdict,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market_history::{aggregate_hourly, split_off_downsampled_observations};
+    use crate::test_objects::market_entry;
+    use chrono::TimeZone;
+
+    fn ts(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 6, 1, hour, 0, 0).unwrap()
+    }
+
+    fn observation(hour: u32, trade_volume: i32, supply: SupplyLevel, purchase_price: i32) -> ExportObservation {
+        ExportObservation {
+            observed_at: ts(hour),
+            trade_volume,
+            supply,
+            purchase_price,
+        }
+    }
+
+    #[test]
+    fn stops_feeding_producers_whose_export_did_not_react() {
+        let strategy = SupplyChainBoostingStrategy::default();
+        let route = BoostedRoute {
+            producer: WaypointSymbol("X1-FOO-C3".to_string()),
+            input_good: TradeGoodSymbol::IRON,
+            export_good: TradeGoodSymbol::FAB_MATS,
+        };
+        let deliveries = BoostDeliveries {
+            units_delivered: 120,
+            purchase_costs: 12_000.into(),
+            sales_income: 9_000.into(),
+            first_delivery_at: ts(10),
+            last_delivery_at: ts(13),
+        };
+        assert_eq!(deliveries.net_costs(), 3_000.into());
+
+        let not_started = evaluate_supply_chain_boost(&route, None, &[], &strategy, ts(14));
+        assert_eq!(not_started.verdict, BoostVerdict::NotStarted);
+
+        let raised_volume = vec![
+            observation(8, 60, SupplyLevel::Moderate, 2_000),
+            observation(9, 60, SupplyLevel::Moderate, 2_000),
+            observation(13, 80, SupplyLevel::Moderate, 1_980),
+        ];
+
+        let too_early = evaluate_supply_chain_boost(&route, Some(&deliveries), &raised_volume, &strategy, ts(11));
+        assert_eq!(too_early.verdict, BoostVerdict::Evaluating);
+
+        let pays_off = evaluate_supply_chain_boost(&route, Some(&deliveries), &raised_volume, &strategy, ts(14));
+        assert_eq!(pays_off.verdict, BoostVerdict::PaysOff);
+        assert_eq!(pays_off.before, Some(observation(9, 60, SupplyLevel::Moderate, 2_000)));
+        assert_eq!(pays_off.volume_change_ratio(), Some(20.0 / 60.0));
+
+        let unchanged = vec![
+            observation(9, 60, SupplyLevel::Moderate, 2_000),
+            observation(13, 60, SupplyLevel::Moderate, 1_990),
+        ];
+        let does_not_pay_off = evaluate_supply_chain_boost(&route, Some(&deliveries), &unchanged, &strategy, ts(14));
+        assert_eq!(does_not_pay_off.verdict, BoostVerdict::DoesNotPayOff);
+        assert!(!does_not_pay_off.verdict.keep_feeding());
+
+        let without_baseline = evaluate_supply_chain_boost(&route, Some(&deliveries), &unchanged[1..], &strategy, ts(14));
+        assert_eq!(without_baseline.verdict, BoostVerdict::Evaluating);
+    }
+
+    #[test]
+    fn export_observations_include_the_downsampled_history() {
+        let history = vec![
+            market_entry(10, 100, SupplyLevel::Moderate),
+            market_entry(20, 120, SupplyLevel::High),
+            market_entry(70, 130, SupplyLevel::High),
+            market_entry(130, 140, SupplyLevel::Abundant),
+        ];
+        let producer = history[0].waypoint_symbol.clone();
+        let (kept, downsampled) = split_off_downsampled_observations(history, DateTime::<Utc>::default() + TimeDelta::hours(2));
+        let aggregates = aggregate_hourly(&downsampled);
+
+        let observations = export_observations(&aggregates, &kept, &producer, &TradeGoodSymbol::FUEL);
+        let observed = observations
+            .iter()
+            .map(|obs| (obs.observed_at, obs.purchase_price, obs.supply.clone()))
+            .collect_vec();
+
+        assert_eq!(
+            observed,
+            vec![
+                (DateTime::<Utc>::default(), 110, SupplyLevel::High),
+                (DateTime::<Utc>::default() + TimeDelta::hours(1), 130, SupplyLevel::High),
+                (DateTime::<Utc>::default() + TimeDelta::minutes(130), 140, SupplyLevel::Abundant),
+            ]
+        );
+        assert!(export_observations(&aggregates, &kept, &producer, &TradeGoodSymbol::IRON).is_empty());
+    }
+}
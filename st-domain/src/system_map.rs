@@ -0,0 +1,258 @@
+use crate::budgeting::treasury_redesign::ActiveTradeRoute;
+use crate::{JumpGateEntry, NavStatus, Ship, ShipSymbol, SystemSymbol, Waypoint, WaypointSymbol, WaypointTraitSymbol, WaypointType};
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SystemMapWaypoint {
+    pub symbol: WaypointSymbol,
+    pub waypoint_type: WaypointType,
+    pub x: i64,
+    pub y: i64,
+    pub orbits: Option<WaypointSymbol>,
+    pub has_marketplace: bool,
+    pub has_shipyard: bool,
+    pub is_under_construction: bool,
+}
+
+impl From<&Waypoint> for SystemMapWaypoint {
+    fn from(wp: &Waypoint) -> Self {
+        Self {
+            symbol: wp.symbol.clone(),
+            waypoint_type: wp.r#type.clone(),
+            x: wp.x,
+            y: wp.y,
+            orbits: wp.orbits.clone(),
+            has_marketplace: wp
+                .traits
+                .iter()
+                .any(|t| t.symbol == WaypointTraitSymbol::MARKETPLACE),
+            has_shipyard: wp
+                .traits
+                .iter()
+                .any(|t| t.symbol == WaypointTraitSymbol::SHIPYARD),
+            is_under_construction: wp.is_under_construction,
+        }
+    }
+}
+
+/// The part of the ship's nav we need to place it on the map - the position in between is interpolated at render time.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SystemMapShip {
+    pub symbol: ShipSymbol,
+    pub nav_status: NavStatus,
+    pub origin: (i64, i64),
+    pub destination: (i64, i64),
+    pub departure_time: DateTime<Utc>,
+    pub arrival: DateTime<Utc>,
+}
+
+impl From<&Ship> for SystemMapShip {
+    fn from(ship: &Ship) -> Self {
+        let route = &ship.nav.route;
+        Self {
+            symbol: ship.symbol.clone(),
+            nav_status: ship.nav.status.clone(),
+            origin: (route.origin.x, route.origin.y),
+            destination: (route.destination.x, route.destination.y),
+            departure_time: route.departure_time,
+            arrival: route.arrival,
+        }
+    }
+}
+
+impl SystemMapShip {
+    /// Moves the ship linearly from the origin to the destination of its route. Ships that aren't traveling are at the destination of their last route.
+    pub fn position_at(&self, now: DateTime<Utc>) -> (f64, f64) {
+        let (dest_x, dest_y) = (self.destination.0 as f64, self.destination.1 as f64);
+        if self.nav_status != NavStatus::InTransit || now >= self.arrival {
+            return (dest_x, dest_y);
+        }
+
+        let (origin_x, origin_y) = (self.origin.0 as f64, self.origin.1 as f64);
+        let total_ms = (self.arrival - self.departure_time).num_milliseconds();
+        if total_ms <= 0 {
+            return (dest_x, dest_y);
+        }
+
+        let progress = ((now - self.departure_time).num_milliseconds() as f64 / total_ms as f64).clamp(0.0, 1.0);
+        (origin_x + (dest_x - origin_x) * progress, origin_y + (dest_y - origin_y) * progress)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SystemMap {
+    pub system_symbol: SystemSymbol,
+    pub waypoints: Vec<SystemMapWaypoint>,
+    pub ships: Vec<SystemMapShip>,
+    pub active_trade_routes: Vec<ActiveTradeRoute>,
+}
+
+impl SystemMap {
+    pub fn new(system_symbol: SystemSymbol, waypoints: &[Waypoint], ships: &[Ship], active_trade_routes: Vec<ActiveTradeRoute>) -> Self {
+        Self {
+            waypoints: waypoints
+                .iter()
+                .filter(|wp| wp.system_symbol == system_symbol)
+                .map(SystemMapWaypoint::from)
+                .collect_vec(),
+            ships: ships
+                .iter()
+                .filter(|s| s.nav.system_symbol == system_symbol)
+                .map(SystemMapShip::from)
+                .collect_vec(),
+            active_trade_routes,
+            system_symbol,
+        }
+    }
+
+    pub fn coordinates(&self) -> HashMap<WaypointSymbol, (i64, i64)> {
+        self.waypoints
+            .iter()
+            .map(|wp| (wp.symbol.clone(), (wp.x, wp.y)))
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GalaxyMapSystem {
+    pub symbol: SystemSymbol,
+    pub system_type: String,
+    pub x: i64,
+    pub y: i64,
+    pub num_waypoints: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GalaxyMap {
+    pub home_system: SystemSymbol,
+    pub systems: Vec<GalaxyMapSystem>,
+    /// every connection between two systems is only listed once
+    pub jump_gate_connections: Vec<(SystemSymbol, SystemSymbol)>,
+}
+
+/// The jump gates we know of, reduced to undirected connections between systems.
+pub fn jump_gate_connections(jump_gates: &[JumpGateEntry]) -> Vec<(SystemSymbol, SystemSymbol)> {
+    jump_gates
+        .iter()
+        .flat_map(|entry| {
+            entry
+                .jump_gate
+                .connections
+                .iter()
+                .map(|to| to.system_symbol())
+                .filter(|to_system| to_system != &entry.system_symbol)
+                .map(|to_system| {
+                    if entry.system_symbol < to_system {
+                        (entry.system_symbol.clone(), to_system)
+                    } else {
+                        (to_system, entry.system_symbol.clone())
+                    }
+                })
+        })
+        .unique()
+        .sorted()
+        .collect_vec()
+}
+
+/// The bounding box of the points and the origin, extended by the margin on every side.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MapBounds {
+    pub min_x: f64,
+    pub min_y: f64,
+    pub max_x: f64,
+    pub max_y: f64,
+}
+
+impl MapBounds {
+    pub fn from_points(points: impl Iterator<Item = (f64, f64)>, margin: f64) -> Self {
+        let (min_x, min_y, max_x, max_y) = points.fold((0.0_f64, 0.0_f64, 0.0_f64, 0.0_f64), |(min_x, min_y, max_x, max_y), (x, y)| {
+            (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+        });
+        Self {
+            min_x: min_x - margin,
+            min_y: min_y - margin,
+            max_x: max_x + margin,
+            max_y: max_y + margin,
+        }
+    }
+
+    pub fn width(&self) -> f64 {
+        self.max_x - self.min_x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max_y - self.min_y
+    }
+
+    /// Shrinks the bounds around the center - a zoom of 2.0 shows half of the width and height.
+    pub fn zoomed(&self, zoom: f64, (center_x, center_y): (f64, f64)) -> Self {
+        let zoom = zoom.max(1.0);
+        let (half_width, half_height) = (self.width() / zoom / 2.0, self.height() / zoom / 2.0);
+        Self {
+            min_x: center_x - half_width,
+            min_y: center_y - half_height,
+            max_x: center_x + half_width,
+            max_y: center_y + half_height,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::JumpGate;
+    use chrono::{TimeDelta, TimeZone};
+
+    #[test]
+    fn interpolates_ships_and_dedups_jump_gate_connections() {
+        let departure_time = Utc.with_ymd_and_hms(2025, 6, 1, 10, 0, 0).unwrap();
+        let ship = SystemMapShip {
+            symbol: ShipSymbol("FLWI-1".to_string()),
+            nav_status: NavStatus::InTransit,
+            origin: (0, 0),
+            destination: (100, -50),
+            departure_time,
+            arrival: departure_time + TimeDelta::seconds(100),
+        };
+
+        assert_eq!(ship.position_at(departure_time - TimeDelta::seconds(10)), (0.0, 0.0));
+        assert_eq!(ship.position_at(departure_time + TimeDelta::seconds(25)), (25.0, -12.5));
+        assert_eq!(ship.position_at(departure_time + TimeDelta::seconds(500)), (100.0, -50.0));
+
+        let docked = SystemMapShip {
+            nav_status: NavStatus::Docked,
+            ..ship.clone()
+        };
+        assert_eq!(docked.position_at(departure_time + TimeDelta::seconds(25)), (100.0, -50.0));
+
+        let jump_gate_entry = |system: &str, connections: &[&str]| JumpGateEntry {
+            system_symbol: SystemSymbol(system.to_string()),
+            waypoint_symbol: WaypointSymbol(format!("{system}-JG")),
+            jump_gate: JumpGate {
+                symbol: WaypointSymbol(format!("{system}-JG")),
+                connections: connections
+                    .iter()
+                    .map(|c| WaypointSymbol(format!("{c}-JG")))
+                    .collect_vec(),
+            },
+            created_at: departure_time,
+            updated_at: departure_time,
+        };
+
+        let connections = jump_gate_connections(&[
+            jump_gate_entry("X1-FOO", &["X1-BAR", "X1-BAZ"]),
+            jump_gate_entry("X1-BAR", &["X1-FOO"]),
+        ]);
+
+        assert_eq!(
+            connections,
+            vec![
+                (SystemSymbol("X1-BAR".to_string()), SystemSymbol("X1-FOO".to_string())),
+                (SystemSymbol("X1-BAZ".to_string()), SystemSymbol("X1-FOO".to_string())),
+            ]
+        );
+    }
+}
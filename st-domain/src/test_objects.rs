@@ -0,0 +1,27 @@
+use crate::{MarketData, MarketEntry, MarketTradeGood, SupplyLevel, TradeGoodSymbol, TradeGoodType, WaypointSymbol};
+use chrono::{DateTime, TimeDelta, Utc};
+
+/// An observation of a market that only trades FUEL - `minutes` after the epoch.
+pub(crate) fn market_entry(minutes: i64, purchase_price: i32, supply: SupplyLevel) -> MarketEntry {
+    let wps = WaypointSymbol("X1-FOO-A1".to_string());
+    MarketEntry {
+        waypoint_symbol: wps.clone(),
+        market_data: MarketData {
+            symbol: wps,
+            exports: vec![],
+            imports: vec![],
+            exchange: vec![],
+            transactions: None,
+            trade_goods: Some(vec![MarketTradeGood {
+                symbol: TradeGoodSymbol::FUEL,
+                trade_good_type: TradeGoodType::Exchange,
+                trade_volume: 100,
+                supply,
+                activity: None,
+                purchase_price,
+                sell_price: purchase_price - 10,
+            }]),
+        },
+        created_at: DateTime::<Utc>::default() + TimeDelta::minutes(minutes),
+    }
+}
@@ -1,10 +1,15 @@
 use crate::behavior_tree_page::BehaviorTreePage;
+use crate::components::agent_selector::AgentSelector;
 use crate::contract_overview_page::ContractOverviewPage;
 use crate::db_overview_page::*;
+use crate::finance_page::FinancePage;
 use crate::fleet_overview_page::*;
+use crate::leaderboard_page::LeaderboardPage;
+use crate::mining_page::MiningPage;
 use crate::petgraph_example_page::TechTreePetgraph;
 use crate::ship_overview_page::ShipOverviewPage;
 use crate::supply_chain_page::*;
+use crate::system_map_page::SystemMapPage;
 use leptos::prelude::*;
 use leptos_meta::{provide_meta_context, MetaTags, Stylesheet, Title};
 use leptos_router::{
@@ -44,6 +49,7 @@ pub fn App() -> impl IntoView {
         <Title text="Welcome to Leptos" />
 
         <div class="min-w-full p-4 bg-slate-900 text-slate-300">
+            <AgentSelector />
 
             <Router>
                 <main>
@@ -56,6 +62,10 @@ pub fn App() -> impl IntoView {
                         <Route path=StaticSegment("behavior-overview") view=BehaviorTreePage />
                         <Route path=StaticSegment("petgraph-example") view=TechTreePetgraph />
                         <Route path=StaticSegment("contract-overview") view=ContractOverviewPage />
+                        <Route path=StaticSegment("finance") view=FinancePage />
+                        <Route path=StaticSegment("mining") view=MiningPage />
+                        <Route path=StaticSegment("system-map") view=SystemMapPage />
+                        <Route path=StaticSegment("leaderboard") view=LeaderboardPage />
                     </Routes>
                 </main>
             </Router>
@@ -100,5 +110,55 @@ fn HomePage() -> impl IntoView {
 #[cfg(feature = "ssr")]
 #[derive(Clone)]
 pub struct AppState {
+    /// not scoped to an agent - only for the shared universe data, see `selected_agent_bmc` for the data of an agent
     pub bmc: std::sync::Arc<dyn st_store::bmc::Bmc>,
+    pub db_bmc: st_store::bmc::DbBmc,
+    pub strategy_store: st_core::strategy::StrategyStore,
+    pub ship_behavior_store: st_core::behavior_tree::behavior_definitions::ShipBehaviorStore,
+}
+
+/// The agent selected in the ui - the first agent of the db, if none (or an unknown one) has been selected.
+#[cfg(feature = "ssr")]
+pub async fn selected_agent_symbol(state: &AppState) -> anyhow::Result<Option<st_domain::AgentSymbol>> {
+    use crate::components::agent_selector::SELECTED_AGENT_COOKIE;
+    use axum::http::header::COOKIE;
+    use axum::http::HeaderMap;
+    use st_store::Ctx;
+
+    let headers: HeaderMap = leptos_axum::extract()
+        .await
+        .map_err(|err| anyhow::anyhow!("Failed to extract headers: {}", err))?;
+
+    let maybe_selected_agent = headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(name, _)| *name == SELECTED_AGENT_COOKIE)
+        .map(|(_, value)| value.to_string());
+
+    let agent_symbols = state
+        .bmc
+        .agent_bmc()
+        .list_agent_symbols(&Ctx::Anonymous)
+        .await?;
+
+    Ok(maybe_selected_agent
+        .and_then(|selected| {
+            agent_symbols
+                .iter()
+                .find(|agent_symbol| agent_symbol.0 == selected)
+                .cloned()
+        })
+        .or_else(|| agent_symbols.first().cloned()))
+}
+
+/// The bmc of the agent selected in the ui - anonymous calls on it are made on behalf of that agent.
+#[cfg(feature = "ssr")]
+pub async fn selected_agent_bmc(state: &AppState) -> anyhow::Result<std::sync::Arc<dyn st_store::bmc::Bmc>> {
+    Ok(match selected_agent_symbol(state).await? {
+        Some(agent_symbol) => std::sync::Arc::new(state.db_bmc.for_agent(agent_symbol)),
+        None => std::sync::Arc::clone(&state.bmc),
+    })
 }
@@ -17,7 +17,8 @@ async fn get_behavior_trees() -> Result<Vec<(String, MermaidString)>, ServerFnEr
     use st_core::behavior_tree::ship_behaviors::ShipAction;
     use std::collections::HashMap;
 
-    let behaviors = st_core::behavior_tree::ship_behaviors::ship_behaviors();
+    let state = expect_context::<crate::app::AppState>();
+    let behaviors = state.ship_behavior_store.get_behaviors();
     let labelled_behaviors: HashMap<String, Behavior<ShipAction>> = behaviors.to_labelled_sub_behaviors();
 
     let mermaid_strings = labelled_behaviors
@@ -35,8 +36,10 @@ async fn get_trading_behavior() -> Result<Vec<(String, MermaidString)>, ServerFn
     use st_core::behavior_tree::ship_behaviors::ShipAction;
     use std::collections::HashMap;
 
-    let behavior = st_core::behavior_tree::ship_behaviors::ship_behaviors().trading_behavior;
-    let all_behaviors = st_core::behavior_tree::ship_behaviors::ship_behaviors();
+    // the trees the ships run - including the overrides and changes of the definition files
+    let state = expect_context::<crate::app::AppState>();
+    let all_behaviors = state.ship_behavior_store.get_behaviors();
+    let behavior = all_behaviors.trading_behavior.clone();
     let labelled_behaviors: HashMap<String, Behavior<ShipAction>> = all_behaviors.to_labelled_sub_behaviors();
     let sub_behavior_hashes = st_core::behavior_tree::behavior_tree::compute_sub_behavior_hashes(&labelled_behaviors);
 
@@ -65,6 +68,7 @@ pub fn BehaviorTreePage() -> impl IntoView {
         <Title text="Leptos + Tailwindcss" />
         <main>
             <div class="flex flex-col min-h-screen">
+                <NodeTypeLegend />
                 <Suspense fallback=move || view! { <p>"Loading..."</p> }>
                     <ErrorBoundary fallback=|errors| {
                         view! { <p>"Error: " {format!("{errors:?}")}</p> }
@@ -101,6 +105,46 @@ pub fn BehaviorTreePage() -> impl IntoView {
     }
 }
 
+#[component]
+fn NodeTypeLegend() -> impl IntoView {
+    let node_types = [
+        ("Action", "runs a single ShipAction"),
+        ("Invert", "turns success into failure and vice versa"),
+        ("Select", "runs children in order until one succeeds"),
+        ("Sequence", "runs children in order until one fails"),
+        ("While", "runs the action as long as the condition succeeds"),
+        (
+            "Parallel",
+            "runs all children concurrently until the success or failure policy (RequireOne/RequireAll) is met",
+        ),
+        ("Retry", "re-runs a failed child up to n times with a backoff in between"),
+        ("Timeout", "fails if the child doesn't finish in time"),
+        ("Cooldown", "fails without running the child if it succeeded less than the duration ago"),
+        ("AlwaysSucceed", "runs the child and ignores its result"),
+    ];
+
+    view! {
+        <details class="mb-4">
+            <summary class="cursor-pointer">"Node types"</summary>
+            <table class="table-auto">
+                <tbody>
+                    {node_types
+                        .into_iter()
+                        .map(|(name, description)| {
+                            view! {
+                                <tr>
+                                    <td class="pr-4 font-bold">{name}</td>
+                                    <td>{description}</td>
+                                </tr>
+                            }
+                        })
+                        .collect_view()}
+                </tbody>
+            </table>
+        </details>
+    }
+}
+
 fn render_mermaid_trees(labelled_behaviors: Vec<(String, MermaidString)>) -> impl IntoView {
     use components::clipboard_button::*;
 
@@ -3,8 +3,13 @@ use clap;
 use clap::{Parser, Subcommand};
 use lazy_static::lazy_static;
 use st_core::agent_manager::AgentManager;
+use st_core::cassette::{record_read_only_endpoints, record_ship_endpoints, CassetteMiddleware};
 use st_core::configuration::AgentConfiguration;
+use st_core::reqwest_helpers::create_recording_client;
+use st_core::st_client::StClient;
+use st_domain::AgentSymbol;
 use st_server::cli_args::AppConfig;
+use std::path::PathBuf;
 use time::format_description;
 use tracing_appender::non_blocking;
 use tracing_appender::rolling::{RollingFileAppender, Rotation};
@@ -26,6 +31,18 @@ struct Args {
 #[derive(Subcommand, Debug, Clone)]
 enum MyCommand {
     RunServer,
+    /// Records the endpoints of the api into a cassette for the `StClient` regression tests
+    RecordCassette {
+        /// e.g. st-core/fixtures/cassettes/read_only_endpoints.json
+        #[arg(long)]
+        path: PathBuf,
+        /// token of the configured agent - the bearer token isn't written to the cassette
+        #[arg(long, env = "SPACETRADERS_AGENT_TOKEN")]
+        agent_token: String,
+        /// records the endpoints that change the state of the ships instead of the read-only ones - only for a freshly registered agent
+        #[arg(long)]
+        ship_endpoints: bool,
+    },
 }
 
 #[tokio::main]
@@ -45,6 +62,12 @@ async fn main() -> Result<()> {
         spacetraders_base_url,
         use_in_memory_agent,
         no_agent,
+        behavior_definition_dir,
+        strategy_file,
+        galaxy_crawler_requests_per_minute,
+        data_retention_hours,
+        competitor_tracking_requests_per_minute,
+        additional_agents,
     } = AppConfig::from_env().expect("cfg");
 
     //tracing_subscriber::registry().with(fmt::layer().with_span_events(fmt::format::FmtSpan::CLOSE)).with(EnvFilter::from_default_env()).init();
@@ -58,6 +81,11 @@ async fn main() -> Result<()> {
         spacetraders_base_url,
         use_in_memory_agent,
         no_agent,
+        behavior_definition_dir,
+        strategy_file,
+        galaxy_crawler_requests_per_minute,
+        data_retention_hours,
+        competitor_tracking_requests_per_minute,
     };
 
     let args = Args::parse();
@@ -65,9 +93,24 @@ async fn main() -> Result<()> {
     match args.command {
         MyCommand::RunServer => {
             // Create the agent manager and get the reset channel
-            let (mut agent_manager, _reset_tx) = AgentManager::new(cfg.clone());
+            let cfgs = cfg.with_additional_agents(&additional_agents)?;
+            let (mut agent_manager, _reset_tx) = AgentManager::new(cfgs);
             agent_manager.run().await?
         }
+        MyCommand::RecordCassette {
+            path,
+            agent_token,
+            ship_endpoints,
+        } => {
+            let client = create_recording_client(Some(agent_token), CassetteMiddleware::record(&path));
+            let client = StClient::try_with_base_url(client, &cfg.spacetraders_base_url)?;
+            if ship_endpoints {
+                record_ship_endpoints(&client).await?;
+            } else {
+                record_read_only_endpoints(&client, &AgentSymbol(cfg.spacetraders_agent_symbol.clone())).await?;
+            }
+            println!("Recorded cassette {}", path.display());
+        }
     }
 
     Ok(())
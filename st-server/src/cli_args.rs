@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use std::str::FromStr;
 
 #[derive(Debug)]
@@ -10,6 +11,12 @@ pub struct AppConfig {
     pub spacetraders_base_url: String,
     pub use_in_memory_agent: bool,
     pub no_agent: bool,
+    pub behavior_definition_dir: Option<PathBuf>,
+    pub strategy_file: Option<PathBuf>,
+    pub galaxy_crawler_requests_per_minute: u32,
+    pub data_retention_hours: u32,
+    pub competitor_tracking_requests_per_minute: u32,
+    pub additional_agents: String,
 }
 
 impl AppConfig {
@@ -38,6 +45,28 @@ impl AppConfig {
                     .as_str(),
             )
             .unwrap_or(false),
+            behavior_definition_dir: get_env_var("SPACETRADERS_BEHAVIOR_DEFINITION_DIR")
+                .ok()
+                .map(PathBuf::from),
+            strategy_file: get_env_var("SPACETRADERS_STRATEGY_FILE")
+                .ok()
+                .map(PathBuf::from),
+            // a quarter of the 2 requests per second of the client
+            galaxy_crawler_requests_per_minute: get_env_var("SPACETRADERS_GALAXY_CRAWLER_REQUESTS_PER_MINUTE")
+                .ok()
+                .and_then(|value| u32::from_str(&value).ok())
+                .unwrap_or(30),
+            data_retention_hours: get_env_var("SPACETRADERS_DATA_RETENTION_HOURS")
+                .ok()
+                .and_then(|value| u32::from_str(&value).ok())
+                .unwrap_or(48),
+            // the agents list changes slowly - a handful of pages per hour is plenty
+            competitor_tracking_requests_per_minute: get_env_var("SPACETRADERS_COMPETITOR_TRACKING_REQUESTS_PER_MINUTE")
+                .ok()
+                .and_then(|value| u32::from_str(&value).ok())
+                .unwrap_or(10),
+            // e.g. "RIVAL:COSMIC,MINER:VOID:miner-strategy.toml"
+            additional_agents: get_env_var("SPACETRADERS_ADDITIONAL_AGENTS").unwrap_or_default(),
         })
     }
 }
@@ -0,0 +1,113 @@
+use leptos::prelude::*;
+use leptos::{component, view, IntoView};
+
+/// Name of the cookie that remembers the agent the ui shows.
+pub const SELECTED_AGENT_COOKIE: &str = "selected_agent";
+
+#[server]
+async fn get_agent_selection() -> Result<(Vec<String>, Option<String>), ServerFnError> {
+    use st_store::Ctx;
+
+    async fn anyhow_fn() -> anyhow::Result<(Vec<String>, Option<String>)> {
+        let state = expect_context::<crate::app::AppState>();
+
+        let agent_symbols = state
+            .bmc
+            .agent_bmc()
+            .list_agent_symbols(&Ctx::Anonymous)
+            .await?;
+        let selected = crate::app::selected_agent_symbol(&state)
+            .await?
+            .map(|agent_symbol| agent_symbol.0);
+
+        Ok((
+            agent_symbols
+                .into_iter()
+                .map(|agent_symbol| agent_symbol.0)
+                .collect(),
+            selected,
+        ))
+    }
+
+    match anyhow_fn().await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(ServerFnError::ServerError(err.to_string())),
+    }
+}
+
+#[server]
+async fn select_agent(agent_symbol: String) -> Result<(), ServerFnError> {
+    use axum::http::header::SET_COOKIE;
+    use axum::http::HeaderValue;
+    use st_store::Ctx;
+
+    let state = expect_context::<crate::app::AppState>();
+    let agent_symbols = state
+        .bmc
+        .agent_bmc()
+        .list_agent_symbols(&Ctx::Anonymous)
+        .await
+        .map_err(|err| ServerFnError::new(err.to_string()))?;
+
+    if !agent_symbols.iter().any(|known| known.0 == agent_symbol) {
+        return Err(ServerFnError::new(format!("Unknown agent {agent_symbol}")));
+    }
+
+    let cookie =
+        HeaderValue::from_str(&format!("{SELECTED_AGENT_COOKIE}={agent_symbol}; Path=/; SameSite=Lax")).map_err(|err| ServerFnError::new(err.to_string()))?;
+    expect_context::<leptos_axum::ResponseOptions>().insert_header(SET_COOKIE, cookie);
+
+    Ok(())
+}
+
+/// Switches the agent all pages show. The pages load their data once, so the page gets reloaded after switching.
+#[component]
+pub fn AgentSelector() -> impl IntoView {
+    let agent_selection_resource = OnceResource::new(get_agent_selection());
+    let select_agent_action = ServerAction::<SelectAgent>::new();
+
+    Effect::new(move |_| {
+        if let Some(Ok(())) = select_agent_action.value().get() {
+            let _ = window().location().reload();
+        }
+    });
+
+    view! {
+        <Suspense fallback=move || ()>
+            {move || {
+                agent_selection_resource
+                    .get()
+                    .and_then(|result| result.ok())
+                    .filter(|(agent_symbols, _)| agent_symbols.len() > 1)
+                    .map(|(agent_symbols, selected)| {
+                        view! {
+                            <div class="flex flex-row gap-2 items-center mb-2">
+                                <span>"Agent"</span>
+                                <select
+                                    class="bg-slate-800 rounded px-2 py-1"
+                                    on:change=move |ev| {
+                                        select_agent_action
+                                            .dispatch(SelectAgent {
+                                                agent_symbol: event_target_value(&ev),
+                                            });
+                                    }
+                                >
+                                    {agent_symbols
+                                        .into_iter()
+                                        .map(|agent_symbol| {
+                                            let is_selected = selected.as_ref() == Some(&agent_symbol);
+                                            view! {
+                                                <option value=agent_symbol.clone() selected=is_selected>
+                                                    {agent_symbol.clone()}
+                                                </option>
+                                            }
+                                        })
+                                        .collect_view()}
+                                </select>
+                            </div>
+                        }
+                    })
+            }}
+        </Suspense>
+    }
+}
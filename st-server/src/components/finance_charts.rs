@@ -0,0 +1,202 @@
+use itertools::Itertools;
+use leptos::prelude::*;
+use leptos::{component, view, IntoView};
+use st_domain::budgeting::finance_report::{CashFlowBucket, FleetProfitBucket};
+use std::collections::HashMap;
+use thousands::Separable;
+
+const WIDTH: f64 = 900.0;
+const HEIGHT: f64 = 300.0;
+const MARGIN_LEFT: f64 = 90.0;
+const MARGIN_RIGHT: f64 = 20.0;
+const MARGIN_TOP: f64 = 20.0;
+const MARGIN_BOTTOM: f64 = 30.0;
+
+const LINE_COLORS: [&str; 8] = [
+    "#60a5fa", "#f472b6", "#34d399", "#fbbf24", "#a78bfa", "#f87171", "#2dd4bf", "#fb923c",
+];
+
+#[component]
+pub fn CashFlowChart(cash_flow: Vec<CashFlowBucket>) -> impl IntoView {
+    view! { <div inner_html=move || cash_flow_svg(&cash_flow) /> }
+}
+
+#[component]
+pub fn FleetProfitChart(fleet_profits: Vec<FleetProfitBucket>, fleet_names: HashMap<i32, String>) -> impl IntoView {
+    view! { <div inner_html=move || fleet_profit_svg(&fleet_profits, &fleet_names) /> }
+}
+
+/// maps values into the plot area - y grows downwards in svg
+struct Scale {
+    num_x: usize,
+    min_y: f64,
+    max_y: f64,
+}
+
+impl Scale {
+    fn new(num_x: usize, values: impl Iterator<Item = f64>) -> Self {
+        let (min_y, max_y) = values.fold((0.0_f64, 0.0_f64), |(min, max), v| (min.min(v), max.max(v)));
+        let max_y = if max_y == min_y { min_y + 1.0 } else { max_y };
+        Self { num_x, min_y, max_y }
+    }
+
+    fn x(&self, idx: usize) -> f64 {
+        let plot_width = WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+        MARGIN_LEFT + plot_width * (idx as f64 + 0.5) / self.num_x.max(1) as f64
+    }
+
+    fn bar_width(&self) -> f64 {
+        ((WIDTH - MARGIN_LEFT - MARGIN_RIGHT) / self.num_x.max(1) as f64 * 0.8).max(1.0)
+    }
+
+    fn y(&self, value: f64) -> f64 {
+        let plot_height = HEIGHT - MARGIN_TOP - MARGIN_BOTTOM;
+        MARGIN_TOP + plot_height * (1.0 - (value - self.min_y) / (self.max_y - self.min_y))
+    }
+
+    fn axes(&self, first_label: &str, last_label: &str) -> String {
+        let zero = self.y(0.0);
+        format!(
+            r##"<line x1="{MARGIN_LEFT}" y1="{zero}" x2="{x2}" y2="{zero}" stroke="#64748b" stroke-width="1"/>
+<text x="{label_x}" y="{top}" fill="#94a3b8" font-size="11" text-anchor="end">{max}c</text>
+<text x="{label_x}" y="{zero_label}" fill="#94a3b8" font-size="11" text-anchor="end">0c</text>
+{min_label}
+<text x="{MARGIN_LEFT}" y="{time_y}" fill="#94a3b8" font-size="11">{first_label}</text>
+<text x="{x2}" y="{time_y}" fill="#94a3b8" font-size="11" text-anchor="end">{last_label}</text>"##,
+            x2 = WIDTH - MARGIN_RIGHT,
+            label_x = MARGIN_LEFT - 5.0,
+            top = self.y(self.max_y) + 4.0,
+            zero_label = zero + 4.0,
+            max = (self.max_y as i64).separate_with_commas(),
+            min_label = if self.min_y < 0.0 {
+                format!(
+                    r##"<text x="{}" y="{}" fill="#94a3b8" font-size="11" text-anchor="end">{}c</text>"##,
+                    MARGIN_LEFT - 5.0,
+                    self.y(self.min_y) + 4.0,
+                    (self.min_y as i64).separate_with_commas()
+                )
+            } else {
+                String::new()
+            },
+            time_y = HEIGHT - 8.0,
+        )
+    }
+}
+
+fn svg(content: String) -> String {
+    format!(r#"<svg width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}" xmlns="http://www.w3.org/2000/svg">{content}</svg>"#)
+}
+
+fn cash_flow_svg(cash_flow: &[CashFlowBucket]) -> String {
+    if cash_flow.is_empty() {
+        return svg(String::new());
+    }
+
+    let scale = Scale::new(
+        cash_flow.len(),
+        cash_flow
+            .iter()
+            .flat_map(|b| [b.net.0 as f64, b.closing_balance.0 as f64]),
+    );
+
+    let bars = cash_flow
+        .iter()
+        .enumerate()
+        .map(|(idx, bucket)| {
+            let net = bucket.net.0 as f64;
+            let (top, bottom) = if net >= 0.0 { (scale.y(net), scale.y(0.0)) } else { (scale.y(0.0), scale.y(net)) };
+            let color = if net >= 0.0 { "#22c55e" } else { "#ef4444" };
+            format!(
+                r#"<rect x="{x}" y="{top}" width="{w}" height="{h}" fill="{color}"><title>{ts}: income {income}c, expenses {expenses}c, net {net}c</title></rect>"#,
+                x = scale.x(idx) - scale.bar_width() / 2.0,
+                w = scale.bar_width(),
+                h = (bottom - top).max(1.0),
+                ts = bucket.bucket_start.format("%Y-%m-%d %H:%M"),
+                income = bucket.income.0.separate_with_commas(),
+                expenses = bucket.expenses.0.separate_with_commas(),
+                net = bucket.net.0.separate_with_commas(),
+            )
+        })
+        .join("\n");
+
+    let balance_points = cash_flow
+        .iter()
+        .enumerate()
+        .map(|(idx, bucket)| format!("{},{}", scale.x(idx), scale.y(bucket.closing_balance.0 as f64)))
+        .join(" ");
+
+    let first_label = cash_flow
+        .first()
+        .unwrap()
+        .bucket_start
+        .format("%m-%d %H:%M")
+        .to_string();
+    let last_label = cash_flow
+        .last()
+        .unwrap()
+        .bucket_start
+        .format("%m-%d %H:%M")
+        .to_string();
+
+    svg(format!(
+        r##"{axes}
+{bars}
+<polyline points="{balance_points}" fill="none" stroke="#60a5fa" stroke-width="2"/>"##,
+        axes = scale.axes(&first_label, &last_label),
+    ))
+}
+
+fn fleet_profit_svg(fleet_profits: &[FleetProfitBucket], fleet_names: &HashMap<i32, String>) -> String {
+    let buckets = fleet_profits
+        .iter()
+        .map(|b| b.bucket_start)
+        .unique()
+        .sorted()
+        .collect_vec();
+
+    if buckets.is_empty() {
+        return svg(String::new());
+    }
+
+    let scale = Scale::new(
+        buckets.len(),
+        fleet_profits
+            .iter()
+            .map(|b| b.cumulative_realized_profit.0 as f64),
+    );
+
+    let lines = fleet_profits
+        .iter()
+        .into_group_map_by(|b| b.fleet_id.clone())
+        .into_iter()
+        .sorted_by_key(|(fleet_id, _)| fleet_id.clone())
+        .enumerate()
+        .map(|(line_idx, (fleet_id, fleet_buckets))| {
+            let color = LINE_COLORS[line_idx % LINE_COLORS.len()];
+            let name = fleet_names
+                .get(&fleet_id.0)
+                .cloned()
+                .unwrap_or_else(|| format!("Fleet #{fleet_id}"));
+
+            let points = fleet_buckets
+                .iter()
+                .filter_map(|b| {
+                    let idx = buckets.iter().position(|ts| ts == &b.bucket_start)?;
+                    Some(format!("{},{}", scale.x(idx), scale.y(b.cumulative_realized_profit.0 as f64)))
+                })
+                .join(" ");
+
+            format!(
+                r#"<polyline points="{points}" fill="none" stroke="{color}" stroke-width="2"><title>{name}</title></polyline>
+<text x="{legend_x}" y="{legend_y}" fill="{color}" font-size="11">{name}</text>"#,
+                legend_x = MARGIN_LEFT + 10.0,
+                legend_y = MARGIN_TOP + 14.0 * (line_idx as f64 + 1.0),
+            )
+        })
+        .join("\n");
+
+    let first_label = buckets.first().unwrap().format("%m-%d %H:%M").to_string();
+    let last_label = buckets.last().unwrap().format("%m-%d %H:%M").to_string();
+
+    svg(format!("{}\n{lines}", scale.axes(&first_label, &last_label)))
+}
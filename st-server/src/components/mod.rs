@@ -1,3 +1,6 @@
+pub mod agent_selector;
 pub mod clipboard_button;
+pub mod finance_charts;
 pub mod supply_chain_graph;
+pub mod system_map;
 pub mod treasury_overview;
@@ -0,0 +1,270 @@
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
+use leptos::prelude::*;
+use leptos::{component, view, IntoView};
+use st_domain::system_map::{GalaxyMap, MapBounds, SystemMap, SystemMapWaypoint};
+use st_domain::{NavStatus, WaypointType};
+use std::collections::HashMap;
+
+const SIZE: f64 = 900.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SystemMapLayers {
+    pub show_labels: bool,
+    pub show_ships: bool,
+    pub show_trade_routes: bool,
+}
+
+#[component]
+pub fn SystemMapSvg(
+    system_map: SystemMap,
+    #[prop(into)] layers: Signal<SystemMapLayers>,
+    #[prop(into)] zoom: Signal<f64>,
+    #[prop(into)] now: Signal<DateTime<Utc>>,
+) -> impl IntoView {
+    view! { <div inner_html=move || system_map_svg(&system_map, layers.get(), zoom.get(), now.get()) /> }
+}
+
+#[component]
+pub fn GalaxyMapSvg(galaxy_map: GalaxyMap, #[prop(into)] zoom: Signal<f64>) -> impl IntoView {
+    view! { <div inner_html=move || galaxy_map_svg(&galaxy_map, zoom.get()) /> }
+}
+
+fn svg(bounds: &MapBounds, content: String) -> String {
+    format!(
+        r##"<svg width="{SIZE}" height="{SIZE}" viewBox="{} {} {} {}" xmlns="http://www.w3.org/2000/svg" class="bg-slate-950 rounded-md">{content}</svg>"##,
+        bounds.min_x,
+        bounds.min_y,
+        bounds.width(),
+        bounds.height(),
+    )
+}
+
+/// in svg-land, everything scales with the viewBox - this keeps markers at the same size on screen, regardless of the zoom level
+fn pixel(bounds: &MapBounds) -> f64 {
+    bounds.width().max(bounds.height()) / SIZE
+}
+
+fn waypoint_color(waypoint_type: &WaypointType) -> &'static str {
+    match waypoint_type {
+        WaypointType::PLANET => "#60a5fa",
+        WaypointType::GAS_GIANT => "#f59e0b",
+        WaypointType::MOON => "#cbd5e1",
+        WaypointType::ORBITAL_STATION => "#a78bfa",
+        WaypointType::JUMP_GATE => "#f472b6",
+        WaypointType::ASTEROID_FIELD => "#a16207",
+        WaypointType::ASTEROID => "#78716c",
+        WaypointType::ENGINEERED_ASTEROID => "#fb923c",
+        WaypointType::ASTEROID_BASE => "#d97706",
+        WaypointType::NEBULA => "#2dd4bf",
+        WaypointType::DEBRIS_FIELD => "#64748b",
+        WaypointType::GRAVITY_WELL => "#e11d48",
+        WaypointType::ARTIFICIAL_GRAVITY_WELL => "#be123c",
+        WaypointType::FUEL_STATION => "#22c55e",
+    }
+}
+
+fn system_map_svg(system_map: &SystemMap, layers: SystemMapLayers, zoom: f64, now: DateTime<Utc>) -> String {
+    let full_bounds = MapBounds::from_points(
+        system_map
+            .waypoints
+            .iter()
+            .map(|wp| (wp.x as f64, wp.y as f64)),
+        20.0,
+    );
+    let bounds = full_bounds.zoomed(zoom, (0.0, 0.0));
+    let px = pixel(&bounds);
+
+    // orbitals share the coordinates of the waypoint they orbit - we render them as one body
+    let bodies: Vec<Vec<&SystemMapWaypoint>> = system_map
+        .waypoints
+        .iter()
+        .into_group_map_by(|wp| (wp.x, wp.y))
+        .into_values()
+        .map(|waypoints| {
+            waypoints
+                .into_iter()
+                .sorted_by_key(|wp| (wp.orbits.is_some(), wp.symbol.clone()))
+                .collect_vec()
+        })
+        .sorted_by_key(|waypoints| waypoints[0].symbol.clone())
+        .collect_vec();
+
+    let waypoints_svg = bodies
+        .iter()
+        .map(|waypoints| {
+            let main = waypoints[0];
+            let title = waypoints
+                .iter()
+                .map(|wp| {
+                    let mut facts = vec![wp.waypoint_type.to_string()];
+                    if wp.has_marketplace {
+                        facts.push("marketplace".to_string());
+                    }
+                    if wp.has_shipyard {
+                        facts.push("shipyard".to_string());
+                    }
+                    if wp.is_under_construction {
+                        facts.push("under construction".to_string());
+                    }
+                    format!("{} ({})", wp.symbol, facts.join(", "))
+                })
+                .join("\n");
+
+            let marketplace_marker = if waypoints.iter().any(|wp| wp.has_marketplace) {
+                format!(
+                    r##"<circle cx="{}" cy="{}" r="{}" fill="none" stroke="#22c55e" stroke-width="{}"/>"##,
+                    main.x,
+                    main.y,
+                    9.0 * px,
+                    1.5 * px
+                )
+            } else {
+                String::new()
+            };
+
+            let shipyard_marker = if waypoints.iter().any(|wp| wp.has_shipyard) {
+                format!(
+                    r##"<rect x="{}" y="{}" width="{size}" height="{size}" fill="none" stroke="#fbbf24" stroke-width="{}"/>"##,
+                    main.x as f64 - 12.0 * px,
+                    main.y as f64 - 12.0 * px,
+                    1.5 * px,
+                    size = 24.0 * px,
+                )
+            } else {
+                String::new()
+            };
+
+            let label = if layers.show_labels {
+                format!(
+                    r##"<text x="{}" y="{}" fill="#94a3b8" font-size="{}">{}</text>"##,
+                    main.x as f64 + 14.0 * px,
+                    main.y as f64 + 4.0 * px,
+                    11.0 * px,
+                    main.symbol.0.rsplit('-').next().unwrap_or_default()
+                )
+            } else {
+                String::new()
+            };
+
+            format!(
+                r##"<g><circle cx="{}" cy="{}" r="{}" fill="{}"><title>{title}</title></circle>{marketplace_marker}{shipyard_marker}{label}</g>"##,
+                main.x,
+                main.y,
+                (5.0 + waypoints.len() as f64) * px,
+                waypoint_color(&main.waypoint_type),
+            )
+        })
+        .join("\n");
+
+    let coordinates = system_map.coordinates();
+
+    let trade_routes_svg = if layers.show_trade_routes {
+        system_map
+            .active_trade_routes
+            .iter()
+            .filter_map(|route| {
+                let (from_x, from_y) = coordinates.get(&route.from)?;
+                let (to_x, to_y) = coordinates.get(&route.to)?;
+                Some(format!(
+                    r##"<line x1="{from_x}" y1="{from_y}" x2="{to_x}" y2="{to_y}" stroke="#38bdf8" stroke-opacity="0.6" stroke-width="{}" stroke-dasharray="{dash} {dash}"><title>{} -> {}: {} ({} ongoing)</title></line>"##,
+                    (1.0 + route.number_ongoing_trades as f64) * px,
+                    route.from,
+                    route.to,
+                    route.trade_good,
+                    route.number_ongoing_trades,
+                    dash = 6.0 * px,
+                ))
+            })
+            .join("\n")
+    } else {
+        String::new()
+    };
+
+    let ships_svg = if layers.show_ships {
+        system_map
+            .ships
+            .iter()
+            .map(|ship| {
+                let (x, y) = ship.position_at(now);
+                let is_traveling = ship.nav_status == NavStatus::InTransit && now < ship.arrival;
+                let course = if is_traveling {
+                    format!(
+                        r##"<line x1="{x}" y1="{y}" x2="{}" y2="{}" stroke="#f8fafc" stroke-opacity="0.3" stroke-width="{}"/>"##,
+                        ship.destination.0, ship.destination.1, px
+                    )
+                } else {
+                    String::new()
+                };
+                let size = 4.0 * px;
+                format!(
+                    r##"{course}<polygon points="{},{} {},{} {},{} {},{}" fill="{}"><title>{} ({:?})</title></polygon>"##,
+                    x,
+                    y - size,
+                    x + size,
+                    y,
+                    x,
+                    y + size,
+                    x - size,
+                    y,
+                    if is_traveling { "#f8fafc" } else { "#fde047" },
+                    ship.symbol.0,
+                    ship.nav_status,
+                )
+            })
+            .join("\n")
+    } else {
+        String::new()
+    };
+
+    svg(&bounds, format!("{trade_routes_svg}\n{waypoints_svg}\n{ships_svg}"))
+}
+
+fn galaxy_map_svg(galaxy_map: &GalaxyMap, zoom: f64) -> String {
+    let full_bounds = MapBounds::from_points(galaxy_map.systems.iter().map(|s| (s.x as f64, s.y as f64)), 100.0);
+
+    let system_coordinates: HashMap<_, _> = galaxy_map
+        .systems
+        .iter()
+        .map(|s| (s.symbol.clone(), (s.x, s.y)))
+        .collect();
+
+    let center = system_coordinates
+        .get(&galaxy_map.home_system)
+        .map(|(x, y)| (*x as f64, *y as f64))
+        .unwrap_or((0.0, 0.0));
+    let bounds = full_bounds.zoomed(zoom, center);
+    let px = pixel(&bounds);
+
+    let connections_svg = galaxy_map
+        .jump_gate_connections
+        .iter()
+        .filter_map(|(from, to)| {
+            let (from_x, from_y) = system_coordinates.get(from)?;
+            let (to_x, to_y) = system_coordinates.get(to)?;
+            Some(format!(
+                r##"<line x1="{from_x}" y1="{from_y}" x2="{to_x}" y2="{to_y}" stroke="#f472b6" stroke-opacity="0.5" stroke-width="{px}"><title>{from} - {to}</title></line>"##,
+            ))
+        })
+        .join("\n");
+
+    let systems_svg = galaxy_map
+        .systems
+        .iter()
+        .map(|system| {
+            let is_home = system.symbol == galaxy_map.home_system;
+            format!(
+                r##"<circle cx="{}" cy="{}" r="{}" fill="{}"><title>{} ({}, {} waypoints)</title></circle>"##,
+                system.x,
+                system.y,
+                if is_home { 6.0 * px } else { 2.0 * px },
+                if is_home { "#22c55e" } else { "#94a3b8" },
+                system.symbol,
+                system.system_type,
+                system.num_waypoints,
+            )
+        })
+        .join("\n");
+
+    svg(&bounds, format!("{connections_svg}\n{systems_svg}"))
+}
@@ -1,6 +1,11 @@
 use crate::ship_overview_page::ShipCard;
+use crate::tables::contract_tables::ContractOutcomeRow;
+use itertools::Itertools;
 use leptos::html::*;
 use leptos::prelude::*;
+use leptos_struct_table::TableContent;
+use st_domain::budgeting::contract_outcome::ContractOutcome;
+use st_domain::budgeting::credits::Credits;
 use st_domain::{ContractEvaluationResult, Ship};
 
 #[server]
@@ -12,14 +17,15 @@ async fn get_contract() -> Result<(Option<ContractEvaluationResult>, Ship), Serv
 
     async fn anyhow_fn() -> anyhow::Result<(Option<ContractEvaluationResult>, Ship)> {
         let state = expect_context::<crate::app::AppState>();
-        let bmc = state.bmc;
+        let bmc = crate::app::selected_agent_bmc(&state).await?;
 
         let agent_info = bmc.agent_bmc().get_initial_agent(&Ctx::Anonymous).await?;
 
         let maybe_contract = bmc
             .contract_bmc()
             .get_youngest_contract(&Ctx::Anonymous, &agent_info.headquarters.system_symbol())
-            .await?;
+            .await?
+            .map(|entry| entry.contract);
 
         let latest_market_entries = bmc
             .market_bmc()
@@ -59,9 +65,40 @@ async fn get_contract() -> Result<(Option<ContractEvaluationResult>, Ship), Serv
     }
 }
 
+#[server]
+async fn get_contract_outcomes() -> Result<Vec<ContractOutcome>, ServerFnError> {
+    use st_domain::budgeting::contract_outcome::compute_contract_outcomes;
+    use st_store::Ctx;
+
+    async fn anyhow_fn() -> anyhow::Result<Vec<ContractOutcome>> {
+        let state = expect_context::<crate::app::AppState>();
+        let bmc = crate::app::selected_agent_bmc(&state).await?;
+
+        let agent_info = bmc.agent_bmc().get_initial_agent(&Ctx::Anonymous).await?;
+
+        let contract_history = bmc
+            .contract_bmc()
+            .get_contract_history(&Ctx::Anonymous, &agent_info.headquarters.system_symbol())
+            .await?;
+
+        let ledger_entries = bmc
+            .ledger_bmc()
+            .get_timestamped_ledger_entries_in_order(&Ctx::Anonymous)
+            .await?;
+
+        Ok(compute_contract_outcomes(&contract_history, &ledger_entries))
+    }
+
+    match anyhow_fn().await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(ServerFnError::ServerError(err.to_string())),
+    }
+}
+
 #[component]
 pub fn ContractOverviewPage() -> impl IntoView {
     let resource = OnceResource::new(get_contract());
+    let contract_outcomes_resource = OnceResource::new(get_contract_outcomes());
 
     view! {
         // <Title text="Leptos + Tailwindcss" />
@@ -138,7 +175,71 @@ pub fn ContractOverviewPage() -> impl IntoView {
                         }}
                     </ErrorBoundary>
                 </Transition>
+                <Transition fallback=move || view! { <p>"Loading..."</p> }>
+                    <ErrorBoundary fallback=|errors| {
+                        view! { <p>"Error: " {format!("{errors:?}")}</p> }
+                    }>
+                        {move || {
+                            contract_outcomes_resource
+                                .get()
+                                .map(|result| {
+                                    result
+                                        .map(|contract_outcomes| {
+                                            view! { <ContractHistory contract_outcomes /> }
+                                        })
+                                })
+                        }}
+                    </ErrorBoundary>
+                </Transition>
             </div>
         </main>
     }
 }
+
+#[component]
+fn ContractHistory(contract_outcomes: Vec<ContractOutcome>) -> impl IntoView {
+    let fulfilled_contracts = contract_outcomes
+        .iter()
+        .filter(|outcome| outcome.fulfilled)
+        .collect_vec();
+    let total_realized_profit: Credits = fulfilled_contracts
+        .iter()
+        .map(|outcome| outcome.realized_profit.0)
+        .sum::<i64>()
+        .into();
+    let total_hours: f64 = fulfilled_contracts
+        .iter()
+        .filter_map(|outcome| outcome.duration())
+        .map(|duration| duration.num_seconds() as f64 / 3600.0)
+        .sum();
+    let profit_per_hour = if total_hours > 0.0 {
+        (total_realized_profit.0 as f64 / total_hours).round() as i64
+    } else {
+        0
+    };
+
+    let summary = format!(
+        "{} fulfilled contracts with a realized profit of {} in {:.1} hours ({}c per hour)",
+        fulfilled_contracts.len(),
+        total_realized_profit,
+        total_hours,
+        profit_per_hour,
+    );
+
+    let contract_outcome_rows = contract_outcomes
+        .into_iter()
+        .map(ContractOutcomeRow::from)
+        .collect_vec();
+
+    view! {
+        <div class="flex flex-col gap-2 p-4">
+            <h2 class="text-xl font-bold">"Contract History"</h2>
+            <p class="text-sm text-slate-400">{summary}</p>
+            <div class="rounded-md overflow-clip border dark:border-gray-700 w-fit">
+                <table class="text-sm text-left mb-[-1px]">
+                    <TableContent rows=contract_outcome_rows scroll_container="html" />
+                </table>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,199 @@
+use crate::components::finance_charts::{CashFlowChart, FleetProfitChart};
+use crate::tables::finance_tables::{FleetProfitRow, PriceSlippageRow, TradeGoodProfitRow, TradeRouteProfitRow};
+use itertools::Itertools;
+use leptos::prelude::*;
+use leptos::{component, view, IntoView};
+use leptos_struct_table::TableContent;
+use serde::{Deserialize, Serialize};
+use st_domain::budgeting::finance_report::FinanceReport;
+use st_domain::budgeting::ledger_audit::LedgerAuditReport;
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FinanceOverview {
+    report: FinanceReport,
+    fleet_names: HashMap<i32, String>,
+    maybe_latest_audit_report: Option<LedgerAuditReport>,
+}
+
+#[server]
+async fn get_finance_overview(bucket_size_minutes: i64) -> Result<FinanceOverview, ServerFnError> {
+    use st_domain::budgeting::finance_report::compute_finance_report;
+    use st_store::Ctx;
+
+    async fn anyhow_fn(bucket_size_minutes: i64) -> anyhow::Result<FinanceOverview> {
+        let state = expect_context::<crate::app::AppState>();
+        let bmc = crate::app::selected_agent_bmc(&state).await?;
+
+        let ledger_entries = bmc
+            .ledger_bmc()
+            .get_timestamped_ledger_entries_in_order(&Ctx::Anonymous)
+            .await?;
+        let fleets = bmc.fleet_bmc().load_fleets(&Ctx::Anonymous).await?;
+        let maybe_latest_audit_report = bmc
+            .ledger_bmc()
+            .get_latest_ledger_audit_report(&Ctx::Anonymous)
+            .await?;
+
+        let report = compute_finance_report(&ledger_entries, chrono::Duration::minutes(bucket_size_minutes.max(1)));
+
+        Ok(FinanceOverview {
+            report,
+            fleet_names: fleets
+                .into_iter()
+                .map(|fleet| (fleet.id.0, fleet.cfg.to_string()))
+                .collect(),
+            maybe_latest_audit_report,
+        })
+    }
+
+    match anyhow_fn(bucket_size_minutes).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(ServerFnError::ServerError(err.to_string())),
+    }
+}
+
+#[component]
+pub fn FinancePage() -> impl IntoView {
+    let (bucket_size_minutes, set_bucket_size_minutes) = signal(60_i64);
+    let finance_resource = Resource::new(move || bucket_size_minutes.get(), get_finance_overview);
+
+    view! {
+        <div class="bg-blue-950 text-white flex flex-col min-h-screen gap-4 p-4">
+            <div class="flex flex-row gap-4 items-center">
+                <h1 class="font-bold text-2xl">"Finance"</h1>
+                <select
+                    class="bg-slate-800 rounded px-2 py-1"
+                    on:change=move |ev| {
+                        if let Ok(minutes) = event_target_value(&ev).parse::<i64>() {
+                            set_bucket_size_minutes.set(minutes);
+                        }
+                    }
+                >
+                    <option value="15">"15 min"</option>
+                    <option value="60" selected>"1 hour"</option>
+                    <option value="360">"6 hours"</option>
+                    <option value="1440">"1 day"</option>
+                </select>
+            </div>
+            <Transition fallback=move || view! { <p>"Loading..."</p> }>
+                <ErrorBoundary fallback=|errors| {
+                    view! { <p>"Error: " {format!("{errors:?}")}</p> }
+                }>
+                    {move || {
+                        finance_resource
+                            .get()
+                            .map(|result| {
+                                result
+                                    .map(|overview| {
+                                        view! { <FinanceOverviewView overview /> }
+                                    })
+                            })
+                    }}
+                </ErrorBoundary>
+            </Transition>
+        </div>
+    }
+}
+
+#[component]
+fn FinanceOverviewView(overview: FinanceOverview) -> impl IntoView {
+    let FinanceOverview {
+        report,
+        fleet_names,
+        maybe_latest_audit_report,
+    } = overview;
+
+    let last_bucket = report.fleet_profits.iter().map(|b| b.bucket_start).max();
+    let fleet_profit_rows = report
+        .fleet_profits
+        .iter()
+        .into_group_map_by(|b| b.fleet_id.clone())
+        .into_iter()
+        .sorted_by_key(|(fleet_id, _)| fleet_id.clone())
+        .map(|(fleet_id, buckets)| FleetProfitRow {
+            fleet: fleet_names
+                .get(&fleet_id.0)
+                .cloned()
+                .unwrap_or_else(|| format!("Fleet #{fleet_id}")),
+            realized_profit: buckets
+                .last()
+                .map(|b| b.cumulative_realized_profit)
+                .unwrap_or_default(),
+            realized_profit_last_bucket: buckets
+                .iter()
+                .filter(|b| Some(b.bucket_start) == last_bucket)
+                .map(|b| b.realized_profit)
+                .next()
+                .unwrap_or_default(),
+        })
+        .collect_vec();
+
+    let trade_route_rows = report
+        .trade_route_profits
+        .into_iter()
+        .map(TradeRouteProfitRow::from)
+        .collect_vec();
+    let trade_good_rows = report
+        .trade_good_profits
+        .into_iter()
+        .map(TradeGoodProfitRow::from)
+        .collect_vec();
+    let slippage_rows = report
+        .price_slippages
+        .into_iter()
+        .map(PriceSlippageRow::from)
+        .collect_vec();
+
+    let audit_summary = match maybe_latest_audit_report {
+        None => "No ledger audit yet".to_string(),
+        Some(audit) => format!(
+            "Last ledger audit at {}: credit drift {}c ({:?}), {} unmatched ticket completions, {} unmatched transactions",
+            audit.audited_at.format("%Y-%m-%d %H:%M:%S"),
+            audit.credit_drift,
+            audit.drift_resolution,
+            audit.unmatched_ticket_completions.len(),
+            audit.unmatched_transactions.len(),
+        ),
+    };
+
+    view! {
+        <div class="flex flex-col gap-4">
+            <p class="text-sm text-slate-400">{audit_summary}</p>
+            <div class="flex flex-row flex-wrap gap-4">
+                <div class="flex flex-col gap-2">
+                    <h2 class="font-bold text-xl">"Cash Flow (net per bucket and agent credits)"</h2>
+                    <CashFlowChart cash_flow=report.cash_flow />
+                </div>
+                <div class="flex flex-col gap-2">
+                    <h2 class="font-bold text-xl">"Cumulative realized Profit per Fleet"</h2>
+                    <FleetProfitChart fleet_profits=report.fleet_profits fleet_names />
+                </div>
+            </div>
+            <h2 class="font-bold text-xl">"Profit per Fleet"</h2>
+            <div class="rounded-md overflow-clip border dark:border-gray-700 w-fit">
+                <table class="text-sm text-left mb-[-1px]">
+                    <TableContent rows=fleet_profit_rows scroll_container="html" />
+                </table>
+            </div>
+            <h2 class="font-bold text-xl">"Profit per Trade Route"</h2>
+            <div class="rounded-md overflow-clip border dark:border-gray-700 w-fit">
+                <table class="text-sm text-left mb-[-1px]">
+                    <TableContent rows=trade_route_rows scroll_container="html" />
+                </table>
+            </div>
+            <h2 class="font-bold text-xl">"Profit per Trade Good"</h2>
+            <div class="rounded-md overflow-clip border dark:border-gray-700 w-fit">
+                <table class="text-sm text-left mb-[-1px]">
+                    <TableContent rows=trade_good_rows scroll_container="html" />
+                </table>
+            </div>
+            <h2 class="font-bold text-xl">"Price Slippage (expected vs. actual prices)"</h2>
+            <div class="rounded-md overflow-clip border dark:border-gray-700 w-fit">
+                <table class="text-sm text-left mb-[-1px]">
+                    <TableContent rows=slippage_rows scroll_container="html" />
+                </table>
+            </div>
+        </div>
+    }
+}
@@ -1,9 +1,16 @@
 use crate::components::clipboard_button::ClipboardButton;
+use crate::tables::construction_forecast_table::{MaterialForecastRow, ScenarioForecastRow};
+use crate::tables::ship_type_evaluation_table::ShipTypeEvaluationRow;
 
 use chrono::{DateTime, Utc};
+use itertools::Itertools;
 use leptos::prelude::*;
 use leptos::{component, view, IntoView};
+use leptos_struct_table::TableContent;
 use serde::{Deserialize, Serialize};
+use st_domain::construction_forecast::ConstructionForecast;
+use st_domain::ship_type_evaluation::ShipTypeEvaluation;
+use st_domain::strategy::ShoppingListStrategy;
 use st_domain::{FleetDecisionFacts, FleetPhase, FleetsOverview, Ship};
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -12,6 +19,14 @@ pub struct ShipsOverview {
     last_update: DateTime<Utc>,
 }
 
+/// The simulated return per credit of the ship types we can buy and the shopping lists we'd get by buying the best one of each role.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ShipTypeAdvice {
+    evaluations: Vec<ShipTypeEvaluation>,
+    current_shopping_lists: ShoppingListStrategy,
+    suggested_shopping_lists: ShoppingListStrategy,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum GetShipsMode {
     AllShips,
@@ -19,13 +34,21 @@ pub enum GetShipsMode {
 }
 
 #[server]
-async fn get_fleet_decision_facts() -> Result<(FleetDecisionFacts, FleetsOverview, FleetPhase), ServerFnError> {
+async fn get_fleet_decision_facts() -> Result<(FleetDecisionFacts, FleetsOverview, FleetPhase, Option<ConstructionForecast>, ShipTypeAdvice), ServerFnError> {
     use st_core::fleet::fleet;
+    use st_core::ship_type_evaluator::evaluate_ship_types;
+    use st_domain::budgeting::credits::Credits;
+    use st_domain::construction_forecast::{best_export_markets, construction_deliveries_from_ledger, forecast_construction};
+    use st_domain::ship_type_evaluation::suggest_shopping_lists;
+    use st_domain::FleetConfig;
     use st_store::Ctx;
+    use std::collections::HashSet;
     use std::sync::Arc;
 
     let state = expect_context::<crate::app::AppState>();
-    let bmc = state.bmc;
+    let bmc = crate::app::selected_agent_bmc(&state)
+        .await
+        .expect("selected_agent_bmc");
 
     let home_waypoint_symbol = bmc
         .agent_bmc()
@@ -43,10 +66,75 @@ async fn get_fleet_decision_facts() -> Result<(FleetDecisionFacts, FleetsOvervie
         .await
         .expect("load_overview");
 
+    let latest_market_entries = bmc
+        .market_bmc()
+        .get_latest_market_data_for_system(&Ctx::Anonymous, &home_system_symbol)
+        .await
+        .expect("get_latest_market_data_for_system");
+
+    let maybe_construction_forecast = if let Some(construction_site) = decision_facts.construction_site.as_ref() {
+        let ledger_entries = bmc
+            .ledger_bmc()
+            .get_timestamped_ledger_entries_in_order(&Ctx::Anonymous)
+            .await
+            .expect("get_timestamped_ledger_entries_in_order");
+
+        let treasurer = fleet::replay_ledger(ledger_entries.iter().map(|entry| entry.entry.clone()));
+        let fleet_budgets = treasurer.get_fleet_budgets().unwrap_or_default();
+        let available_construction_budget: Credits = fleet_overview
+            .fleets
+            .values()
+            .filter(|fleet| matches!(fleet.cfg, FleetConfig::ConstructJumpGateCfg(_)))
+            .filter_map(|fleet| fleet_budgets.get(&fleet.id))
+            .map(|budget| budget.available_capital().0)
+            .sum::<i64>()
+            .into();
+
+        let construction_materials: HashSet<_> = construction_site
+            .materials
+            .iter()
+            .map(|cm| cm.trade_symbol.clone())
+            .collect();
+
+        Some(forecast_construction(
+            construction_site,
+            &construction_deliveries_from_ledger(&ledger_entries),
+            &best_export_markets(&latest_market_entries, &construction_materials),
+            available_construction_budget,
+            Utc::now(),
+        ))
+    } else {
+        None
+    };
+
+    let strategy = state.strategy_store.get_strategy();
+
+    let ship_prices = bmc
+        .shipyard_bmc()
+        .get_latest_ship_prices(&Ctx::Anonymous, &home_system_symbol)
+        .await
+        .expect("get_latest_ship_prices");
+    let waypoints = bmc
+        .system_bmc()
+        .get_waypoints_of_system(&Ctx::Anonymous, &home_system_symbol)
+        .await
+        .expect("get_waypoints_of_system");
+    let evaluations = evaluate_ship_types(&ship_prices, &latest_market_entries, &waypoints, strategy.ship_purchases.fuel_cost_per_unit);
+    let ship_type_advice = ShipTypeAdvice {
+        suggested_shopping_lists: suggest_shopping_lists(&strategy.shopping_lists, &evaluations),
+        current_shopping_lists: strategy.shopping_lists.clone(),
+        evaluations,
+    };
+
     // Create a construction fleet phase
-    let fleet_phase = fleet::compute_fleet_phase_with_tasks(home_system_symbol, &decision_facts, &fleet_overview.completed_fleet_tasks);
+    let fleet_phase = fleet::compute_fleet_phase_with_tasks(
+        home_system_symbol,
+        &decision_facts,
+        &fleet_overview.completed_fleet_tasks,
+        &strategy.shopping_lists,
+    );
 
-    Ok((decision_facts, fleet_overview, fleet_phase))
+    Ok((decision_facts, fleet_overview, fleet_phase, maybe_construction_forecast, ship_type_advice))
 }
 
 #[component]
@@ -63,10 +151,15 @@ pub fn FleetOverviewPage() -> impl IntoView {
                 <Transition>
                     {move || {
                         match fleet_decision_facts_resource.get() {
-                            Some(Ok((fleet_decision_facts, fleets_overview, fleet_phase))) => {
+                            Some(Ok((fleet_decision_facts, fleets_overview, fleet_phase, maybe_construction_forecast, ship_type_advice))) => {
 
                                 view! {
                                     <div class="flex flex-col gap-4 p-4">
+                                        {maybe_construction_forecast
+                                            .map(|forecast| {
+                                                view! { <ConstructionForecastView forecast /> }
+                                            })}
+                                        <ShipTypeAdviceView advice=ship_type_advice />
                                         <div class="flex flex-row gap-4 p-4">
                                             <div class="flex flex-col gap-2">
 
@@ -185,3 +278,84 @@ pub fn FleetOverviewPage() -> impl IntoView {
         </div>
     }
 }
+
+#[component]
+fn ConstructionForecastView(forecast: ConstructionForecast) -> impl IntoView {
+    let summary = format!(
+        "Jump gate ETA: {} - bottleneck: {} - remaining costs: {} (available budget: {}, shortfall: {})",
+        forecast
+            .eta
+            .map(|eta| eta.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or("unknown".to_string()),
+        forecast
+            .bottleneck_material
+            .map(|tg| tg.to_string())
+            .unwrap_or("---".to_string()),
+        forecast.remaining_costs,
+        forecast.available_budget,
+        forecast.budget_shortfall,
+    );
+
+    let material_rows = forecast
+        .materials
+        .into_iter()
+        .map(MaterialForecastRow::from)
+        .collect_vec();
+    let scenario_rows = forecast
+        .scenarios
+        .into_iter()
+        .map(ScenarioForecastRow::from)
+        .collect_vec();
+
+    view! {
+        <div class="flex flex-col gap-2">
+            <h2 class="font-bold text-xl">"Construction Forecast"</h2>
+            <p class="text-sm text-slate-400">{summary}</p>
+            <div class="flex flex-row flex-wrap gap-4">
+                <div class="rounded-md overflow-clip border dark:border-gray-700 w-fit">
+                    <table class="text-sm text-left mb-[-1px]">
+                        <TableContent rows=material_rows scroll_container="html" />
+                    </table>
+                </div>
+                <div class="flex flex-col gap-2">
+                    <h3 class="font-bold">"What if"</h3>
+                    <div class="rounded-md overflow-clip border dark:border-gray-700 w-fit">
+                        <table class="text-sm text-left mb-[-1px]">
+                            <TableContent rows=scenario_rows scroll_container="html" />
+                        </table>
+                    </div>
+                </div>
+            </div>
+        </div>
+    }
+}
+
+#[component]
+fn ShipTypeAdviceView(advice: ShipTypeAdvice) -> impl IntoView {
+    let rows = advice
+        .evaluations
+        .into_iter()
+        .map(ShipTypeEvaluationRow::from)
+        .collect_vec();
+
+    view! {
+        <div class="flex flex-col gap-2">
+            <h2 class="font-bold text-xl">"Ship Type Evaluation"</h2>
+            <div class="rounded-md overflow-clip border dark:border-gray-700 w-fit">
+                <table class="text-sm text-left mb-[-1px]">
+                    <TableContent rows scroll_container="html" />
+                </table>
+            </div>
+            <div class="flex flex-row gap-4">
+                <div class="flex flex-col gap-2">
+                    <h3 class="font-bold">"Current Shopping Lists"</h3>
+                    <pre>{serde_json::to_string_pretty(&advice.current_shopping_lists).unwrap_or("---".to_string())}</pre>
+                </div>
+                <div class="flex flex-col gap-2">
+                    <h3 class="font-bold">"Suggested Shopping Lists"</h3>
+                    <pre>{serde_json::to_string_pretty(&advice.suggested_shopping_lists).unwrap_or("---".to_string())}</pre>
+                </div>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,160 @@
+use crate::tables::competitor_tables::{ContestedMarketRow, LeaderboardRow};
+use itertools::Itertools;
+use leptos::prelude::*;
+use leptos::{component, view, IntoView};
+use leptos_struct_table::TableContent;
+use st_domain::competitor_intelligence::{LeaderboardEntry, MarketContestation};
+
+#[server]
+async fn get_leaderboard(comparison_hours: i64) -> Result<Vec<LeaderboardEntry>, ServerFnError> {
+    use chrono::{TimeDelta, Utc};
+    use st_domain::competitor_intelligence::compute_leaderboard;
+    use st_store::Ctx;
+
+    async fn anyhow_fn(comparison_hours: i64) -> anyhow::Result<Vec<LeaderboardEntry>> {
+        let state = expect_context::<crate::app::AppState>();
+
+        // the snapshots of the agents list are shared by all agents in the db
+        let now = Utc::now();
+        let latest = state
+            .bmc
+            .agent_bmc()
+            .get_latest_agent_snapshots_until(&Ctx::Anonymous, now)
+            .await?;
+        let earlier = state
+            .bmc
+            .agent_bmc()
+            .get_latest_agent_snapshots_until(&Ctx::Anonymous, now - TimeDelta::hours(comparison_hours.max(1)))
+            .await?;
+
+        Ok(compute_leaderboard(&latest, &earlier))
+    }
+
+    match anyhow_fn(comparison_hours).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(ServerFnError::ServerError(err.to_string())),
+    }
+}
+
+#[server]
+async fn get_market_contestation() -> Result<(Vec<MarketContestation>, f64), ServerFnError> {
+    use chrono::{TimeDelta, Utc};
+    use st_domain::competitor_intelligence::measure_market_contestations;
+    use st_store::Ctx;
+
+    async fn anyhow_fn() -> anyhow::Result<(Vec<MarketContestation>, f64)> {
+        let state = expect_context::<crate::app::AppState>();
+        let bmc = crate::app::selected_agent_bmc(&state).await?;
+        let strategy = state.strategy_store.get_strategy().competitors;
+
+        let agent_info = bmc.agent_bmc().get_initial_agent(&Ctx::Anonymous).await?;
+        let since = Utc::now() - TimeDelta::hours(strategy.history_hours);
+
+        let history = bmc
+            .market_bmc()
+            .get_market_history_of_system(&Ctx::Anonymous, &agent_info.headquarters.system_symbol(), since)
+            .await?;
+        let our_agent_symbols = bmc
+            .agent_bmc()
+            .list_agent_symbols(&Ctx::Anonymous)
+            .await?
+            .into_iter()
+            .collect();
+
+        let contestations = measure_market_contestations(&history, &our_agent_symbols)
+            .into_iter()
+            .sorted_by(|a, b| b.score.total_cmp(&a.score))
+            .collect_vec();
+
+        Ok((contestations, strategy.contested_threshold))
+    }
+
+    match anyhow_fn().await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(ServerFnError::ServerError(err.to_string())),
+    }
+}
+
+#[component]
+pub fn LeaderboardPage() -> impl IntoView {
+    let (comparison_hours, set_comparison_hours) = signal(24_i64);
+    let leaderboard_resource = Resource::new(move || comparison_hours.get(), get_leaderboard);
+    let contestation_resource = OnceResource::new(get_market_contestation());
+
+    view! {
+        <div class="bg-blue-950 text-white flex flex-col min-h-screen gap-4 p-4">
+            <div class="flex flex-row gap-4 items-center">
+                <h1 class="font-bold text-2xl">"Leaderboard"</h1>
+                <span>"compared to"</span>
+                <select
+                    class="bg-slate-800 rounded px-2 py-1"
+                    on:change=move |ev| {
+                        if let Ok(hours) = event_target_value(&ev).parse::<i64>() {
+                            set_comparison_hours.set(hours);
+                        }
+                    }
+                >
+                    <option value="1">"1 hour ago"</option>
+                    <option value="6">"6 hours ago"</option>
+                    <option value="24" selected>"1 day ago"</option>
+                </select>
+            </div>
+            <Transition fallback=move || view! { <p>"Loading..."</p> }>
+                <ErrorBoundary fallback=|errors| {
+                    view! { <p>"Error: " {format!("{errors:?}")}</p> }
+                }>
+                    {move || {
+                        leaderboard_resource
+                            .get()
+                            .map(|result| {
+                                result
+                                    .map(|leaderboard| {
+                                        let rows = leaderboard
+                                            .into_iter()
+                                            .map(LeaderboardRow::from)
+                                            .collect_vec();
+                                        view! {
+                                            <div class="rounded-md overflow-clip border dark:border-gray-700 w-fit">
+                                                <table class="text-sm text-left mb-[-1px]">
+                                                    <TableContent rows scroll_container="html" />
+                                                </table>
+                                            </div>
+                                        }
+                                    })
+                            })
+                    }}
+                </ErrorBoundary>
+            </Transition>
+            <Transition fallback=move || view! { <p>"Loading..."</p> }>
+                <ErrorBoundary fallback=|errors| {
+                    view! { <p>"Error: " {format!("{errors:?}")}</p> }
+                }>
+                    {move || {
+                        contestation_resource
+                            .get()
+                            .map(|result| {
+                                result
+                                    .map(|(contestations, contested_threshold)| {
+                                        let rows = contestations
+                                            .into_iter()
+                                            .map(|contestation| ContestedMarketRow::from_contestation(contestation, contested_threshold))
+                                            .collect_vec();
+                                        view! {
+                                            <h2 class="font-bold text-xl">"Contested Markets"</h2>
+                                            <p class="text-sm text-slate-400">
+                                                "Share of the price and supply changes at the markets of our home system we didn't cause ourselves"
+                                            </p>
+                                            <div class="rounded-md overflow-clip border dark:border-gray-700 w-fit">
+                                                <table class="text-sm text-left mb-[-1px]">
+                                                    <TableContent rows scroll_container="html" />
+                                                </table>
+                                            </div>
+                                        }
+                                    })
+                            })
+                    }}
+                </ErrorBoundary>
+            </Transition>
+        </div>
+    }
+}
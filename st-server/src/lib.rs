@@ -3,8 +3,12 @@ use chrono::Duration;
 pub mod app;
 pub mod behavior_tree_page;
 pub mod db_overview_page;
+pub mod finance_page;
 pub mod fleet_overview_page;
+pub mod leaderboard_page;
+pub mod mining_page;
 pub mod supply_chain_page;
+pub mod system_map_page;
 
 #[cfg(feature = "ssr")]
 pub mod cli_args;
@@ -7,7 +7,9 @@ async fn main() {
     use leptos_axum::generate_route_list;
     use leptos_axum::LeptosRoutes;
     use st_core::agent_manager::AgentManager;
+    use st_core::behavior_tree::behavior_definitions::ShipBehaviorStore;
     use st_core::configuration::AgentConfiguration;
+    use st_core::strategy::StrategyStore;
     use st_server::app::{shell, App};
     use st_server::cli_args::AppConfig;
     use st_store::bmc::{Bmc, DbBmc};
@@ -30,6 +32,12 @@ async fn main() {
         spacetraders_base_url,
         use_in_memory_agent,
         no_agent,
+        behavior_definition_dir,
+        strategy_file,
+        galaxy_crawler_requests_per_minute,
+        data_retention_hours,
+        competitor_tracking_requests_per_minute,
+        additional_agents,
     } = AppConfig::from_env().expect("cfg");
 
     let custom_filter = filter_fn(|metadata| {
@@ -61,6 +69,11 @@ async fn main() {
         spacetraders_base_url,
         use_in_memory_agent,
         no_agent,
+        behavior_definition_dir,
+        strategy_file,
+        galaxy_crawler_requests_per_minute,
+        data_retention_hours,
+        competitor_tracking_requests_per_minute,
     };
 
     // if !cfg.use_in_memory_agent {
@@ -68,7 +81,11 @@ async fn main() {
     // }
 
     // Create the agent manager and get the reset channel
-    let (mut agent_manager, _reset_tx) = AgentManager::new(cfg.clone());
+    let cfgs = cfg
+        .clone()
+        .with_additional_agents(&additional_agents)
+        .expect("additional agents");
+    let (mut agent_manager, _reset_tx) = AgentManager::new(cfgs);
 
     let pool = db::get_pg_connection_pool(cfg.pg_connection_string())
         .await
@@ -79,6 +96,9 @@ async fn main() {
 
     let app_state = st_server::app::AppState {
         bmc: Arc::clone(&db_bmc) as Arc<dyn Bmc>,
+        db_bmc: db_bmc.as_ref().clone(),
+        strategy_store: StrategyStore::new(cfg.strategy_file.clone()).expect("strategy_file"),
+        ship_behavior_store: ShipBehaviorStore::new(cfg.behavior_definition_dir.clone()).expect("behavior_definition_dir"),
     };
 
     // Generate the list of routes in your Leptos App
@@ -0,0 +1,85 @@
+use crate::tables::mining_tables::SiteThroughputRow;
+use itertools::Itertools;
+use leptos::prelude::*;
+use leptos::{component, view, IntoView};
+use leptos_struct_table::TableContent;
+use st_domain::cargo_flow::SiteThroughput;
+
+#[server]
+async fn get_site_throughput(window_hours: i64) -> Result<Vec<SiteThroughput>, ServerFnError> {
+    use chrono::{TimeDelta, Utc};
+    use st_domain::cargo_flow::summarize_site_throughput;
+    use st_store::Ctx;
+
+    async fn anyhow_fn(window_hours: i64) -> anyhow::Result<Vec<SiteThroughput>> {
+        let state = expect_context::<crate::app::AppState>();
+        let bmc = crate::app::selected_agent_bmc(&state).await?;
+
+        let now = Utc::now();
+        let since = now - TimeDelta::hours(window_hours.max(1));
+        let entries = bmc
+            .cargo_flow_bmc()
+            .get_cargo_flows_since(&Ctx::Anonymous, since)
+            .await?;
+
+        Ok(summarize_site_throughput(&entries, since, now))
+    }
+
+    match anyhow_fn(window_hours).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(ServerFnError::ServerError(err.to_string())),
+    }
+}
+
+#[component]
+pub fn MiningPage() -> impl IntoView {
+    let (window_hours, set_window_hours) = signal(1_i64);
+    let throughput_resource = Resource::new(move || window_hours.get(), get_site_throughput);
+
+    view! {
+        <div class="bg-blue-950 text-white flex flex-col min-h-screen gap-4 p-4">
+            <div class="flex flex-row gap-4 items-center">
+                <h1 class="font-bold text-2xl">"Mining Sites"</h1>
+                <select
+                    class="bg-slate-800 rounded px-2 py-1"
+                    on:change=move |ev| {
+                        if let Ok(hours) = event_target_value(&ev).parse::<i64>() {
+                            set_window_hours.set(hours);
+                        }
+                    }
+                >
+                    <option value="1" selected>"1 hour"</option>
+                    <option value="6">"6 hours"</option>
+                    <option value="24">"1 day"</option>
+                </select>
+            </div>
+            <Transition fallback=move || view! { <p>"Loading..."</p> }>
+                <ErrorBoundary fallback=|errors| {
+                    view! { <p>"Error: " {format!("{errors:?}")}</p> }
+                }>
+                    {move || {
+                        throughput_resource
+                            .get()
+                            .map(|result| {
+                                result
+                                    .map(|throughput| {
+                                        let rows = throughput
+                                            .into_iter()
+                                            .map(SiteThroughputRow::from)
+                                            .collect_vec();
+                                        view! {
+                                            <h2 class="font-bold text-xl">"Cargo Throughput per Site"</h2>
+                                            <div class="rounded-md overflow-clip border dark:border-gray-700 w-fit">
+                                                <table class="text-sm text-left mb-[-1px]">
+                                                    <TableContent rows scroll_container="html" />
+                                                </table>
+                                            </div>
+                                        }
+                                    })
+                            })
+                    }}
+                </ErrorBoundary>
+            </Transition>
+        </div>
+    }
+}
@@ -140,7 +140,9 @@ async fn get_materialized_supply_chain() -> Result<Option<(MaterializedSupplyCha
     use st_store::Ctx;
 
     let state = expect_context::<crate::app::AppState>();
-    let bmc = state.bmc;
+    let bmc = crate::app::selected_agent_bmc(&state)
+        .await
+        .expect("selected_agent_bmc");
 
     let home_waypoint_symbol = bmc
         .agent_bmc()
@@ -32,7 +32,9 @@ async fn get_ships_overview(get_ships_mode: GetShipsMode) -> Result<ShipsOvervie
     use st_store::Ctx;
 
     let state = expect_context::<crate::app::AppState>();
-    let bmc = state.bmc;
+    let bmc = crate::app::selected_agent_bmc(&state)
+        .await
+        .expect("selected_agent_bmc");
 
     let filter_timestamp_gte = match get_ships_mode {
         GetShipsMode::AllShips => None,
@@ -236,6 +238,7 @@ pub fn ShipCard<'a>(ship: &'a Ship, maybe_ship_task: Option<&'a ShipTask>, activ
             ShipTask::Trade => MONEY_WAVY,
             ShipTask::PrepositionShipForTrade { .. } => TRUCK,
             ShipTask::SiphonCarboHydratesAtWaypoint { .. } => ATOM,
+            ShipTask::HaulSiphonedGoods { .. } => TRUCK,
             ShipTask::ExecuteContracts => BRIEFCASE,
         }
     } else {
@@ -42,7 +42,7 @@ async fn get_supply_chain_data() -> Result<
         Vec<EvaluatedTradingOpportunity>,
     )> {
         let state = expect_context::<crate::app::AppState>();
-        let bmc = state.bmc;
+        let bmc = crate::app::selected_agent_bmc(&state).await?;
         let supply_chain = bmc
             .supply_chain_bmc()
             .get_supply_chain(&Ctx::Anonymous)
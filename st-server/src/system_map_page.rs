@@ -0,0 +1,225 @@
+use crate::components::system_map::{GalaxyMapSvg, SystemMapLayers, SystemMapSvg};
+use chrono::Utc;
+use leptos::prelude::*;
+use leptos::{component, view, IntoView};
+use st_domain::system_map::{GalaxyMap, SystemMap};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MapMode {
+    System,
+    Galaxy,
+}
+
+/// Loads the map of the system - without a system symbol the home system is shown.
+#[server]
+async fn get_system_map(maybe_system_symbol: Option<String>) -> Result<SystemMap, ServerFnError> {
+    use st_core::fleet::fleet::replay_ledger;
+    use st_domain::SystemSymbol;
+    use st_store::Ctx;
+
+    async fn anyhow_fn(maybe_system_symbol: Option<String>) -> anyhow::Result<SystemMap> {
+        let state = expect_context::<crate::app::AppState>();
+        let bmc = crate::app::selected_agent_bmc(&state).await?;
+
+        let system_symbol = match maybe_system_symbol.filter(|s| !s.trim().is_empty()) {
+            Some(system_symbol) => SystemSymbol(system_symbol.trim().to_uppercase()),
+            None => bmc
+                .agent_bmc()
+                .get_initial_agent(&Ctx::Anonymous)
+                .await?
+                .headquarters
+                .system_symbol(),
+        };
+
+        let waypoints = bmc
+            .system_bmc()
+            .get_waypoints_of_system(&Ctx::Anonymous, &system_symbol)
+            .await?;
+        let ships = bmc.ship_bmc().get_ships(&Ctx::Anonymous, None).await?;
+
+        let ledger_entries = bmc
+            .ledger_bmc()
+            .get_ledger_entries_in_order(&Ctx::Anonymous)
+            .await?;
+        let active_trade_routes = replay_ledger(ledger_entries)
+            .get_active_trade_routes()
+            .unwrap_or_default();
+
+        Ok(SystemMap::new(system_symbol, &waypoints, &ships, active_trade_routes))
+    }
+
+    match anyhow_fn(maybe_system_symbol).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(ServerFnError::ServerError(err.to_string())),
+    }
+}
+
+#[server]
+async fn get_galaxy_map() -> Result<GalaxyMap, ServerFnError> {
+    use st_domain::system_map::jump_gate_connections;
+    use st_store::Ctx;
+
+    async fn anyhow_fn() -> anyhow::Result<GalaxyMap> {
+        let state = expect_context::<crate::app::AppState>();
+        let bmc = crate::app::selected_agent_bmc(&state).await?;
+
+        let home_system = bmc
+            .agent_bmc()
+            .get_initial_agent(&Ctx::Anonymous)
+            .await?
+            .headquarters
+            .system_symbol();
+        let systems = bmc
+            .system_bmc()
+            .get_galaxy_map_systems(&Ctx::Anonymous)
+            .await?;
+        let jump_gates = bmc.jump_gate_bmc().get_jump_gates(&Ctx::Anonymous).await?;
+
+        Ok(GalaxyMap {
+            home_system,
+            systems,
+            jump_gate_connections: jump_gate_connections(&jump_gates),
+        })
+    }
+
+    match anyhow_fn().await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(ServerFnError::ServerError(err.to_string())),
+    }
+}
+
+#[component]
+pub fn SystemMapPage() -> impl IntoView {
+    let (mode, set_mode) = signal(MapMode::System);
+    let (maybe_system_symbol, set_maybe_system_symbol) = signal(None::<String>);
+    let (zoom, set_zoom) = signal(1.0_f64);
+    let (layers, set_layers) = signal(SystemMapLayers {
+        show_labels: true,
+        show_ships: true,
+        show_trade_routes: true,
+    });
+
+    #[allow(unused_variables)] // rustc gets confused, because the setter is only used in non-ssr mode
+    let (now, set_now) = signal(Utc::now());
+
+    let system_map_resource = Resource::new(move || maybe_system_symbol.get(), get_system_map);
+    // the galaxy is only loaded once it's requested - it's large and doesn't change much
+    let galaxy_map_resource = Resource::new(
+        move || mode.get() == MapMode::Galaxy,
+        |is_galaxy_mode| async move {
+            if is_galaxy_mode {
+                get_galaxy_map().await.map(Some)
+            } else {
+                Ok(None)
+            }
+        },
+    );
+
+    #[cfg(not(feature = "ssr"))]
+    let _refetch_handle = leptos_use::use_interval_fn(move || system_map_resource.refetch(), 10_000);
+
+    // ships move between the refetches
+    #[cfg(not(feature = "ssr"))]
+    let _animation_handle = leptos_use::use_interval_fn(move || set_now.set(Utc::now()), 1_000);
+
+    let layer_checkbox = move |label: &'static str, get: fn(&SystemMapLayers) -> bool, set: fn(&mut SystemMapLayers, bool)| {
+        view! {
+            <label class="flex flex-row gap-1 items-center">
+                <input
+                    type="checkbox"
+                    prop:checked=move || get(&layers.get())
+                    on:change=move |ev| set_layers.update(|l| set(l, event_target_checked(&ev)))
+                />
+                {label}
+            </label>
+        }
+    };
+
+    view! {
+        <div class="bg-blue-950 text-white flex flex-col min-h-screen gap-4 p-4">
+            <div class="flex flex-row gap-4 items-center">
+                <h1 class="font-bold text-2xl">
+                    {move || if mode.get() == MapMode::System { "System Map" } else { "Galaxy Map" }}
+                </h1>
+                <select
+                    class="bg-slate-800 rounded px-2 py-1"
+                    on:change=move |ev| {
+                        set_mode.set(if event_target_value(&ev) == "galaxy" { MapMode::Galaxy } else { MapMode::System });
+                        set_zoom.set(1.0);
+                    }
+                >
+                    <option value="system" selected>"System"</option>
+                    <option value="galaxy">"Galaxy"</option>
+                </select>
+                <select
+                    class="bg-slate-800 rounded px-2 py-1"
+                    prop:value=move || zoom.get().to_string()
+                    on:change=move |ev| {
+                        if let Ok(zoom) = event_target_value(&ev).parse::<f64>() {
+                            set_zoom.set(zoom);
+                        }
+                    }
+                >
+                    <option value="1">"1x"</option>
+                    <option value="2">"2x"</option>
+                    <option value="4">"4x"</option>
+                    <option value="8">"8x"</option>
+                    <option value="16">"16x"</option>
+                </select>
+                <Show when=move || mode.get() == MapMode::System>
+                    <input
+                        type="text"
+                        class="bg-slate-800 rounded px-2 py-1"
+                        placeholder="home system"
+                        on:change=move |ev| set_maybe_system_symbol.set(Some(event_target_value(&ev)))
+                    />
+                    {layer_checkbox("Labels", |l| l.show_labels, |l, v| l.show_labels = v)}
+                    {layer_checkbox("Ships", |l| l.show_ships, |l, v| l.show_ships = v)}
+                    {layer_checkbox("Trade Routes", |l| l.show_trade_routes, |l, v| l.show_trade_routes = v)}
+                </Show>
+            </div>
+            <Transition fallback=move || view! { <p>"Loading..."</p> }>
+                <ErrorBoundary fallback=|errors| {
+                    view! { <p>"Error: " {format!("{errors:?}")}</p> }
+                }>
+                    {move || match mode.get() {
+                        MapMode::System => {
+                            system_map_resource
+                                .get()
+                                .map(|result| {
+                                    result
+                                        .map(|system_map| {
+                                            view! {
+                                                <h2 class="font-bold text-xl">{system_map.system_symbol.to_string()}</h2>
+                                                <SystemMapSvg system_map layers zoom now />
+                                            }
+                                                .into_any()
+                                        })
+                                })
+                        }
+                        MapMode::Galaxy => {
+                            galaxy_map_resource
+                                .get()
+                                .map(|result| {
+                                    result
+                                        .map(|maybe_galaxy_map| {
+                                            maybe_galaxy_map
+                                                .map(|galaxy_map| {
+                                                    view! {
+                                                        <h2 class="font-bold text-xl">
+                                                            {format!("{} systems, {} jump gate connections", galaxy_map.systems.len(), galaxy_map.jump_gate_connections.len())}
+                                                        </h2>
+                                                        <GalaxyMapSvg galaxy_map zoom />
+                                                    }
+                                                        .into_any()
+                                                })
+                                        })
+                                })
+                                .and_then(|result| result.transpose())
+                        }
+                    }}
+                </ErrorBoundary>
+            </Transition>
+        </div>
+    }
+}
@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+use st_domain::budgeting::credits::Credits;
+use st_domain::competitor_intelligence::{LeaderboardEntry, MarketContestation};
+use st_domain::WaypointSymbol;
+use thousands::Separable;
+
+// IMPORTANT: all these imports are required, dear copy-and-paster
+use crate::tables::renderers::*;
+use crate::tailwind::TailwindClassesPreset;
+#[allow(unused_imports)]
+use leptos::prelude::*;
+use leptos_struct_table::*;
+
+#[derive(Serialize, Deserialize, Clone, Debug, TableRow)]
+#[table(impl_vec_data_provider, sortable, classes_provider = "TailwindClassesPreset")]
+pub struct LeaderboardRow {
+    #[table(class = "text-right")]
+    pub rank: usize,
+
+    pub agent: String,
+
+    pub faction: String,
+
+    #[table(renderer = "WaypointSymbolCellRenderer")]
+    pub headquarters: WaypointSymbol,
+
+    #[table(renderer = "CreditCellRenderer", class = "text-right")]
+    pub credits: Credits,
+
+    #[table(class = "text-right")]
+    pub credits_change: String,
+
+    #[table(class = "text-right")]
+    pub ships: i32,
+
+    #[table(class = "text-right")]
+    pub ships_change: String,
+}
+
+impl From<LeaderboardEntry> for LeaderboardRow {
+    fn from(entry: LeaderboardEntry) -> Self {
+        Self {
+            rank: entry.rank,
+            agent: entry.snapshot.agent_symbol.0.clone(),
+            faction: format!("{:?}", entry.snapshot.starting_faction),
+            headquarters: entry.snapshot.headquarters.clone(),
+            credits: entry.snapshot.credits.into(),
+            credits_change: entry
+                .credits_change
+                .map(|change| format!("{:+}c", change).separate_with_commas())
+                .unwrap_or_else(|| "-".to_string()),
+            ships: entry.snapshot.ship_count,
+            ships_change: entry
+                .ship_count_change
+                .map(|change| format!("{:+}", change))
+                .unwrap_or_else(|| "-".to_string()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TableRow)]
+#[table(impl_vec_data_provider, sortable, classes_provider = "TailwindClassesPreset")]
+pub struct ContestedMarketRow {
+    #[table(renderer = "WaypointSymbolCellRenderer")]
+    pub market: WaypointSymbol,
+
+    #[table(renderer = "PercentageCellRenderer", class = "text-right")]
+    pub score: Option<f64>,
+
+    #[table(class = "text-right")]
+    pub comparisons: u32,
+
+    #[table(class = "text-right")]
+    pub unexplained_changes: u32,
+
+    #[table(class = "text-right")]
+    pub foreign_transactions: u32,
+
+    pub contested: String,
+}
+
+impl ContestedMarketRow {
+    pub fn from_contestation(contestation: MarketContestation, contested_threshold: f64) -> Self {
+        Self {
+            market: contestation.waypoint_symbol,
+            score: Some(contestation.score),
+            comparisons: contestation.num_comparisons,
+            unexplained_changes: contestation.num_unexplained_changes,
+            foreign_transactions: contestation.num_foreign_transactions,
+            contested: if contestation.score >= contested_threshold {
+                "yes".to_string()
+            } else {
+                String::new()
+            },
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+use st_domain::budgeting::credits::Credits;
+use st_domain::construction_forecast::{MaterialForecast, ScenarioForecast};
+use st_domain::TradeGoodSymbol;
+
+// IMPORTANT: all these imports are required, dear copy-and-paster
+use crate::tables::renderers::*;
+use crate::tailwind::TailwindClassesPreset;
+#[allow(unused_imports)]
+use leptos::prelude::*;
+use leptos_struct_table::*;
+
+fn format_eta(maybe_eta: Option<chrono::DateTime<chrono::Utc>>) -> String {
+    maybe_eta
+        .map(|eta| eta.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or("unknown".to_string())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TableRow)]
+#[table(impl_vec_data_provider, sortable, classes_provider = "TailwindClassesPreset")]
+pub struct MaterialForecastRow {
+    #[table(renderer = "TradeGoodSymbolCellRenderer")]
+    pub trade_good: TradeGoodSymbol,
+
+    #[table(class = "text-right")]
+    pub required: u32,
+
+    #[table(class = "text-right")]
+    pub fulfilled: u32,
+
+    #[table(class = "text-right")]
+    pub remaining: u32,
+
+    #[table(class = "text-right")]
+    pub num_haulers: u32,
+
+    #[table(renderer = "UnitsPerHourCellRenderer", class = "text-right")]
+    pub hauler_units_per_hour: f64,
+
+    #[table(renderer = "UnitsPerHourCellRenderer", class = "text-right")]
+    pub market_units_per_hour: f64,
+
+    #[table(renderer = "UnitsPerHourCellRenderer", class = "text-right")]
+    pub projected_units_per_hour: f64,
+
+    pub bottleneck: String,
+
+    #[table(renderer = "CreditCellRenderer", class = "text-right")]
+    pub remaining_costs: Credits,
+
+    pub eta: String,
+}
+
+impl From<MaterialForecast> for MaterialForecastRow {
+    fn from(forecast: MaterialForecast) -> Self {
+        Self {
+            hauler_units_per_hour: forecast
+                .units_per_hour_per_hauler
+                .map(|rate| rate * forecast.num_haulers as f64)
+                .unwrap_or_default(),
+            market_units_per_hour: forecast.market_units_per_hour.unwrap_or_default(),
+            projected_units_per_hour: forecast.projected_units_per_hour.unwrap_or_default(),
+            bottleneck: format!("{:?}", forecast.bottleneck),
+            eta: format_eta(forecast.eta),
+            trade_good: forecast.trade_good,
+            required: forecast.required,
+            fulfilled: forecast.fulfilled,
+            remaining: forecast.remaining,
+            num_haulers: forecast.num_haulers,
+            remaining_costs: forecast.remaining_costs,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TableRow)]
+#[table(impl_vec_data_provider, sortable, classes_provider = "TailwindClassesPreset")]
+pub struct ScenarioForecastRow {
+    pub scenario: String,
+
+    pub eta: String,
+
+    pub bottleneck_material: String,
+}
+
+impl From<ScenarioForecast> for ScenarioForecastRow {
+    fn from(forecast: ScenarioForecast) -> Self {
+        Self {
+            scenario: forecast.label,
+            eta: format_eta(forecast.eta),
+            bottleneck_material: forecast
+                .bottleneck_material
+                .map(|tg| tg.to_string())
+                .unwrap_or_default(),
+        }
+    }
+}
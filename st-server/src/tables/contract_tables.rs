@@ -0,0 +1,79 @@
+use crate::format_duration;
+use serde::{Deserialize, Serialize};
+use st_domain::budgeting::contract_outcome::ContractOutcome;
+use st_domain::budgeting::credits::Credits;
+use st_domain::TradeGoodSymbol;
+
+// IMPORTANT: all these imports are required, dear copy-and-paster
+use crate::tables::renderers::*;
+use crate::tailwind::TailwindClassesPreset;
+#[allow(unused_imports)]
+use leptos::prelude::*;
+use leptos_struct_table::*;
+
+#[derive(Serialize, Deserialize, Clone, Debug, TableRow)]
+#[table(impl_vec_data_provider, sortable, classes_provider = "TailwindClassesPreset")]
+pub struct ContractOutcomeRow {
+    pub contract_id: String,
+
+    pub faction: String,
+
+    #[table(renderer = "TradeGoodSymbolListCellRenderer")]
+    pub trade_goods: Vec<TradeGoodSymbol>,
+
+    pub status: String,
+
+    pub started_at: String,
+
+    #[table(class = "text-right")]
+    pub duration: String,
+
+    #[table(class = "text-right")]
+    pub units_delivered: u32,
+
+    #[table(renderer = "CreditCellRenderer", class = "text-right")]
+    pub promised_payment: Credits,
+
+    #[table(renderer = "CreditCellRenderer", class = "text-right")]
+    pub payout: Credits,
+
+    #[table(renderer = "CreditCellRenderer", class = "text-right")]
+    pub spend: Credits,
+
+    #[table(renderer = "CreditCellRenderer", class = "text-right")]
+    pub realized_profit: Credits,
+}
+
+impl From<ContractOutcome> for ContractOutcomeRow {
+    fn from(outcome: ContractOutcome) -> Self {
+        let status = if outcome.fulfilled {
+            "fulfilled"
+        } else if outcome.accepted {
+            "in progress"
+        } else if outcome.declined {
+            "declined"
+        } else {
+            "not accepted"
+        };
+
+        Self {
+            contract_id: outcome.contract_id.0.clone(),
+            faction: outcome.faction_symbol.clone(),
+            trade_goods: outcome.trade_goods.clone(),
+            status: status.to_string(),
+            started_at: outcome
+                .started_at
+                .map(|started_at| started_at.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_default(),
+            duration: outcome
+                .duration()
+                .map(|duration| format_duration(&duration))
+                .unwrap_or_default(),
+            units_delivered: outcome.units_delivered,
+            promised_payment: outcome.promised_payment,
+            payout: outcome.payout,
+            spend: outcome.spend,
+            realized_profit: outcome.realized_profit,
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+use st_domain::budgeting::credits::Credits;
+use st_domain::budgeting::finance_report::{PriceSlippage, TradeGoodProfit, TradeRouteProfit};
+use st_domain::{TradeGoodSymbol, WaypointSymbol};
+
+// IMPORTANT: all these imports are required, dear copy-and-paster
+use crate::tables::renderers::*;
+use crate::tailwind::TailwindClassesPreset;
+#[allow(unused_imports)]
+use leptos::prelude::*;
+use leptos_struct_table::*;
+
+#[derive(Serialize, Deserialize, Clone, Debug, TableRow)]
+#[table(impl_vec_data_provider, sortable, classes_provider = "TailwindClassesPreset")]
+pub struct FleetProfitRow {
+    pub fleet: String,
+
+    #[table(renderer = "CreditCellRenderer", class = "text-right")]
+    pub realized_profit: Credits,
+
+    #[table(renderer = "CreditCellRenderer", class = "text-right")]
+    pub realized_profit_last_bucket: Credits,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TableRow)]
+#[table(impl_vec_data_provider, sortable, classes_provider = "TailwindClassesPreset")]
+pub struct TradeRouteProfitRow {
+    #[table(renderer = "TradeGoodSymbolCellRenderer")]
+    pub trade_good: TradeGoodSymbol,
+
+    #[table(renderer = "WaypointSymbolCellRenderer")]
+    pub purchase_waypoint_symbol: WaypointSymbol,
+
+    #[table(renderer = "WaypointSymbolCellRenderer")]
+    pub sell_waypoint_symbol: WaypointSymbol,
+
+    #[table(class = "text-right")]
+    pub num_trades: usize,
+
+    #[table(class = "text-right")]
+    pub units: u32,
+
+    #[table(renderer = "CreditCellRenderer", class = "text-right")]
+    pub purchase_total: Credits,
+
+    #[table(renderer = "CreditCellRenderer", class = "text-right")]
+    pub sell_total: Credits,
+
+    #[table(renderer = "CreditCellRenderer", class = "text-right")]
+    pub profit: Credits,
+
+    #[table(renderer = "CreditCellRenderer", class = "text-right")]
+    pub avg_profit_per_trade: Credits,
+}
+
+impl From<TradeRouteProfit> for TradeRouteProfitRow {
+    fn from(route: TradeRouteProfit) -> Self {
+        Self {
+            trade_good: route.trade_good,
+            purchase_waypoint_symbol: route.purchase_waypoint_symbol,
+            sell_waypoint_symbol: route.sell_waypoint_symbol,
+            num_trades: route.num_trades,
+            units: route.units,
+            purchase_total: route.purchase_total,
+            sell_total: route.sell_total,
+            profit: route.profit,
+            avg_profit_per_trade: route.avg_profit_per_trade,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TableRow)]
+#[table(impl_vec_data_provider, sortable, classes_provider = "TailwindClassesPreset")]
+pub struct TradeGoodProfitRow {
+    #[table(renderer = "TradeGoodSymbolCellRenderer")]
+    pub trade_good: TradeGoodSymbol,
+
+    #[table(class = "text-right")]
+    pub num_sells: usize,
+
+    #[table(class = "text-right")]
+    pub units_sold: u32,
+
+    #[table(renderer = "CreditCellRenderer", class = "text-right")]
+    pub revenue: Credits,
+
+    #[table(renderer = "CreditCellRenderer", class = "text-right")]
+    pub cost: Credits,
+
+    #[table(renderer = "CreditCellRenderer", class = "text-right")]
+    pub profit: Credits,
+}
+
+impl From<TradeGoodProfit> for TradeGoodProfitRow {
+    fn from(tg: TradeGoodProfit) -> Self {
+        Self {
+            trade_good: tg.trade_good,
+            num_sells: tg.num_sells,
+            units_sold: tg.units_sold,
+            revenue: tg.revenue,
+            cost: tg.cost,
+            profit: tg.profit,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TableRow)]
+#[table(impl_vec_data_provider, sortable, classes_provider = "TailwindClassesPreset")]
+pub struct PriceSlippageRow {
+    #[table(renderer = "TradeGoodSymbolCellRenderer")]
+    pub trade_good: TradeGoodSymbol,
+
+    #[table(renderer = "WaypointSymbolCellRenderer")]
+    pub waypoint_symbol: WaypointSymbol,
+
+    pub transaction_type: String,
+
+    #[table(class = "text-right")]
+    pub num_tickets: usize,
+
+    #[table(class = "text-right")]
+    pub units: u32,
+
+    #[table(renderer = "CreditCellRenderer", class = "text-right")]
+    pub expected_total: Credits,
+
+    #[table(renderer = "CreditCellRenderer", class = "text-right")]
+    pub actual_total: Credits,
+
+    #[table(renderer = "CreditCellRenderer", class = "text-right")]
+    pub slippage: Credits,
+
+    #[table(class = "text-right", format(precision = 2usize))]
+    pub slippage_percent: f64,
+}
+
+impl From<PriceSlippage> for PriceSlippageRow {
+    fn from(slippage: PriceSlippage) -> Self {
+        Self {
+            trade_good: slippage.trade_good,
+            waypoint_symbol: slippage.waypoint_symbol,
+            transaction_type: format!("{:?}", slippage.transaction_type),
+            num_tickets: slippage.num_tickets,
+            units: slippage.units,
+            expected_total: slippage.expected_total,
+            actual_total: slippage.actual_total,
+            slippage: slippage.slippage,
+            slippage_percent: slippage.slippage_percent,
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+use st_domain::cargo_flow::{CargoFlowKind, SiteThroughput};
+use st_domain::WaypointSymbol;
+
+// IMPORTANT: all these imports are required, dear copy-and-paster
+use crate::tables::renderers::*;
+use crate::tailwind::TailwindClassesPreset;
+#[allow(unused_imports)]
+use leptos::prelude::*;
+use leptos_struct_table::*;
+
+#[derive(Serialize, Deserialize, Clone, Debug, TableRow)]
+#[table(impl_vec_data_provider, sortable, classes_provider = "TailwindClassesPreset")]
+pub struct SiteThroughputRow {
+    #[table(renderer = "WaypointSymbolCellRenderer")]
+    pub site: WaypointSymbol,
+
+    #[table(renderer = "UnitsPerHourCellRenderer", class = "text-right")]
+    pub extracted: f64,
+
+    #[table(renderer = "UnitsPerHourCellRenderer", class = "text-right")]
+    pub transferred_to_haulers: f64,
+
+    #[table(renderer = "UnitsPerHourCellRenderer", class = "text-right")]
+    pub sold_by_drones: f64,
+
+    #[table(renderer = "UnitsPerHourCellRenderer", class = "text-right")]
+    pub jettisoned: f64,
+
+    #[table(renderer = "PercentageCellRenderer", class = "text-right")]
+    pub delivery_ratio: Option<f64>,
+}
+
+impl From<SiteThroughput> for SiteThroughputRow {
+    fn from(throughput: SiteThroughput) -> Self {
+        Self {
+            extracted: throughput.units_per_hour(&CargoFlowKind::Extracted),
+            transferred_to_haulers: throughput.units_per_hour(&CargoFlowKind::TransferredToHauler),
+            sold_by_drones: throughput.units_per_hour(&CargoFlowKind::SoldByDrone),
+            jettisoned: throughput.units_per_hour(&CargoFlowKind::Jettisoned),
+            delivery_ratio: throughput.delivery_ratio(),
+            site: throughput.site,
+        }
+    }
+}
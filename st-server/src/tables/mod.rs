@@ -1,6 +1,12 @@
 pub mod renderers;
 
+pub mod competitor_tables;
+pub mod construction_forecast_table;
+pub mod contract_tables;
+pub mod finance_tables;
 pub mod fleet_overview_table;
+pub mod mining_tables;
 pub mod scored_supply_chain_route_table;
+pub mod ship_type_evaluation_table;
 pub mod trade_good_overview_table;
 pub mod trading_opportunity_table;
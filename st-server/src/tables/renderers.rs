@@ -126,6 +126,41 @@ pub fn FloatCellRenderer<F: 'static>(
     }
 }
 
+#[component]
+pub fn UnitsPerHourCellRenderer<F: 'static>(
+    class: String,
+    value: Signal<f64>,
+    #[allow(unused_variables)] row: RwSignal<F>,
+    #[allow(unused_variables)] index: usize,
+) -> impl IntoView {
+    view! {
+        <td class=class>
+            <span>{move || format!("{} u/h", format_number(value.get_untracked()))}</span>
+        </td>
+    }
+}
+
+#[component]
+pub fn PercentageCellRenderer<F: 'static>(
+    class: String,
+    value: Signal<Option<f64>>,
+    #[allow(unused_variables)] row: RwSignal<F>,
+    #[allow(unused_variables)] index: usize,
+) -> impl IntoView {
+    view! {
+        <td class=class>
+            <span>
+                {move || {
+                    value
+                        .get_untracked()
+                        .map(|ratio| format!("{:.1}%", ratio * 100.0))
+                        .unwrap_or_else(|| "-".to_string())
+                }}
+            </span>
+        </td>
+    }
+}
+
 /// Print a number with 2 decimal places and comma-separated
 pub fn format_number(value: f64) -> String {
     // thousands will format floating point numbers just fine, but we can't
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+use st_domain::budgeting::credits::Credits;
+use st_domain::ship_type_evaluation::ShipTypeEvaluation;
+use st_domain::WaypointSymbol;
+
+// IMPORTANT: all these imports are required, dear copy-and-paster
+use crate::tables::renderers::*;
+use crate::tailwind::TailwindClassesPreset;
+#[allow(unused_imports)]
+use leptos::prelude::*;
+use leptos_struct_table::*;
+
+#[derive(Serialize, Deserialize, Clone, Debug, TableRow)]
+#[table(impl_vec_data_provider, sortable, classes_provider = "TailwindClassesPreset")]
+pub struct ShipTypeEvaluationRow {
+    pub role: String,
+
+    pub ship_type: String,
+
+    #[table(renderer = "WaypointSymbolCellRenderer")]
+    pub shipyard: WaypointSymbol,
+
+    #[table(renderer = "CreditCellRenderer", class = "text-right")]
+    pub price: Credits,
+
+    #[table(class = "text-right")]
+    pub cargo_capacity: u32,
+
+    #[table(class = "text-right")]
+    pub engine_speed: u32,
+
+    #[table(class = "text-right")]
+    pub fuel_capacity: u32,
+
+    #[table(renderer = "CreditCellRenderer", class = "text-right")]
+    pub profit_per_hour: Credits,
+
+    #[table(title = "return per credit / h", class = "text-right")]
+    pub return_per_credit: String,
+
+    #[table(class = "text-right")]
+    pub payback_hours: String,
+}
+
+impl From<ShipTypeEvaluation> for ShipTypeEvaluationRow {
+    fn from(evaluation: ShipTypeEvaluation) -> Self {
+        Self {
+            role: evaluation.role.to_string(),
+            ship_type: evaluation.ship_type.to_string(),
+            shipyard: evaluation.shipyard,
+            price: evaluation.price,
+            cargo_capacity: evaluation.cargo_capacity,
+            engine_speed: evaluation.engine_speed,
+            fuel_capacity: evaluation.fuel_capacity,
+            profit_per_hour: evaluation.profit_per_hour,
+            return_per_credit: format!("{:.2}%", evaluation.return_per_credit * 100.0),
+            payback_hours: evaluation
+                .payback_hours
+                .map(|hours| format!("{hours:.1}h"))
+                .unwrap_or("never".to_string()),
+        }
+    }
+}
@@ -0,0 +1,9 @@
+-- Add migration script here
+create table ledger_audit_reports
+(
+    id         bigserial primary key,
+    entry      jsonb       not null,
+    created_at timestamptz not null
+);
+
+create index ix_ledger_audit_reports_created_at on ledger_audit_reports (created_at);
@@ -0,0 +1,4 @@
+-- Add migration script here
+create index if not exists surveys_signature_idx on surveys (signature);
+
+create index if not exists survey_usage_log_created_at_idx on survey_usage_log (created_at);
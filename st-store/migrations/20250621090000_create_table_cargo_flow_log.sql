@@ -0,0 +1,10 @@
+-- Add migration script here
+create table cargo_flow_log
+(
+    site        text        not null,
+    ship_symbol text        not null,
+    entry       jsonb       not null,
+    created_at  timestamptz not null
+);
+
+create index cargo_flow_log_created_at_idx on cargo_flow_log (created_at);
@@ -0,0 +1,7 @@
+-- Add migration script here
+create table galaxy_crawl_progress
+(
+    reset_date text        not null primary key,
+    entry      jsonb       not null,
+    updated_at timestamptz not null
+);
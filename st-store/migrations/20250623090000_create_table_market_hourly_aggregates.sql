@@ -0,0 +1,29 @@
+-- old market observations get downsampled into one row per market, trade good and hour
+create table market_hourly_aggregates
+(
+    waypoint_symbol    text             not null,
+    trade_good_symbol  text             not null,
+    hour               timestamptz      not null,
+    num_observations   integer          not null,
+    min_purchase_price integer          not null,
+    avg_purchase_price double precision not null,
+    max_purchase_price integer          not null,
+    min_sell_price     integer          not null,
+    avg_sell_price     double precision not null,
+    max_sell_price     integer          not null,
+    avg_trade_volume   double precision not null,
+    last_supply        jsonb            not null,
+    last_activity      jsonb,
+    primary key (waypoint_symbol, trade_good_symbol, hour)
+);
+
+create index if not exists market_hourly_aggregates_waypoint_symbol_hour_idx on market_hourly_aggregates (waypoint_symbol, hour);
+
+-- the latest market data of a system is selected with a prefix match on the waypoint symbol
+create index if not exists markets_waypoint_symbol_pattern_created_at_idx on markets (waypoint_symbol text_pattern_ops, created_at desc);
+
+-- the retention job selects the old rows by their age
+create index if not exists markets_created_at_idx on markets (created_at);
+create index if not exists ships_updated_at_idx on ships (updated_at);
+create index if not exists surveys_created_at_idx on surveys (created_at);
+create index if not exists survey_usage_log_survey_signature_idx on survey_usage_log (survey_signature);
@@ -0,0 +1,45 @@
+-- the tables an agent owns are scoped by its symbol, so several agents can share one database.
+-- the universe data (systems, waypoints, markets, shipyards, ...) is the same for all agents of a reset and stays shared.
+-- existing rows belong to the single agent we ran so far.
+do
+$$
+    declare
+        agent_table text;
+    begin
+        foreach agent_table in array array ['registration', 'ships', 'ship_task_assignments', 'stationary_probe_locations', 'fleets',
+            'fleet_task_assignments', 'fleet_ship_assignment', 'completed_fleet_tasks', 'trade_tickets', 'transactions', 'surveys',
+            'survey_usage_log', 'ledger_entries', 'ledger_audit_reports', 'treasurer', 'contracts', 'cargo_flow_log']
+            loop
+                execute format('alter table %I add column agent_symbol text', agent_table);
+                execute format('update %I set agent_symbol = coalesce((select min(agent_symbol) from agent), '''')', agent_table);
+                execute format('alter table %I alter column agent_symbol set not null', agent_table);
+                execute format('create index %I on %I (agent_symbol)', agent_table || '_agent_symbol_idx', agent_table);
+            end loop;
+    end
+$$;
+
+-- fleet ids and probe locations are only unique per agent
+alter table fleet_task_assignments
+    drop constraint fleet_task_assignments_fleet_id_fkey;
+alter table fleet_ship_assignment
+    drop constraint fleet_ship_assignment_fleet_id_fkey;
+
+alter table fleets
+    drop constraint fleets_pkey;
+alter table fleets
+    add primary key (agent_symbol, id);
+
+alter table fleet_task_assignments
+    drop constraint fleet_task_assignments_pkey;
+alter table fleet_task_assignments
+    add primary key (agent_symbol, fleet_id);
+alter table fleet_task_assignments
+    add foreign key (agent_symbol, fleet_id) references fleets (agent_symbol, id);
+
+alter table fleet_ship_assignment
+    add foreign key (agent_symbol, fleet_id) references fleets (agent_symbol, id);
+
+alter table stationary_probe_locations
+    drop constraint stationary_probe_locations_pkey;
+alter table stationary_probe_locations
+    add primary key (agent_symbol, waypoint_symbol);
@@ -0,0 +1,12 @@
+-- the public info of all agents of the reset over time - shared by all our agents like the rest of the universe data
+create table agent_snapshots
+(
+    agent_symbol text        not null,
+    observed_at  timestamptz not null,
+    credits      bigint      not null,
+    ship_count   integer     not null,
+    entry        jsonb       not null,
+    primary key (agent_symbol, observed_at)
+);
+
+create index agent_snapshots_observed_at_idx on agent_snapshots (observed_at);
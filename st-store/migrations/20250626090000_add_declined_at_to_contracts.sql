@@ -0,0 +1,3 @@
+-- the api has no endpoint for declining a contract - we remember the offers we declined, so that we don't pick them up again
+alter table contracts
+    add column declined_at timestamptz;
@@ -2,9 +2,12 @@ use crate::ctx::Ctx;
 use crate::{db, DbModelManager};
 use anyhow::*;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
 use mockall::automock;
 use sqlx::types::Json;
-use st_domain::{Agent, Contract};
+use st_domain::competitor_intelligence::AgentSnapshot;
+use st_domain::{Agent, AgentSymbol, Contract};
 use std::fmt::Debug;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -25,31 +28,37 @@ pub trait AgentBmcTrait: Send + Sync + Debug {
     async fn get_initial_agent(&self, ctx: &Ctx) -> Result<Agent>;
     async fn load_agent(&self, ctx: &Ctx) -> Result<Agent>;
     async fn store_agent(&self, ctx: &Ctx, agent: &Agent) -> Result<()>;
+    /// All agents that have been run against this database, e.g. for picking one in the UI.
+    async fn list_agent_symbols(&self, ctx: &Ctx) -> Result<Vec<AgentSymbol>>;
+    /// The public info of all agents of the reset (not only ours) from the agents list.
+    async fn save_agent_snapshots(&self, ctx: &Ctx, snapshots: &[AgentSnapshot]) -> Result<()>;
+    async fn get_latest_agent_snapshots_until(&self, ctx: &Ctx, until: DateTime<Utc>) -> Result<Vec<AgentSnapshot>>;
 }
 
 #[async_trait]
 
 impl AgentBmcTrait for DbAgentBmc {
-    async fn get_initial_contract(&self, _ctx: &Ctx) -> Result<Option<Contract>> {
-        let registration_response = db::load_registration(self.mm.pool()).await?;
+    async fn get_initial_contract(&self, ctx: &Ctx) -> Result<Option<Contract>> {
+        let registration_response = db::load_registration(self.mm.pool(), &self.mm.agent_symbol(ctx)?).await?;
 
         Ok(registration_response.map(|r| r.entry.data.contract.clone()))
     }
 
-    async fn get_initial_agent(&self, __ctx: &Ctx) -> Result<Agent> {
-        let registration_response = db::load_registration(self.mm.pool()).await?;
+    async fn get_initial_agent(&self, ctx: &Ctx) -> Result<Agent> {
+        let registration_response = db::load_registration(self.mm.pool(), &self.mm.agent_symbol(ctx)?).await?;
 
         Ok(registration_response.unwrap().entry.data.agent.clone())
     }
 
-    async fn load_agent(&self, __ctx: &Ctx) -> Result<Agent> {
+    async fn load_agent(&self, ctx: &Ctx) -> Result<Agent> {
         let agent_entry: DbAgentEntry = sqlx::query_as!(
             DbAgentEntry,
             r#"
 SELECT entry as "entry: Json<Agent>"
   from agent
-
+ where agent_symbol = $1
         "#,
+            self.mm.agent_symbol(ctx)?.0
         )
         .fetch_one(self.mm.pool())
         .await?;
@@ -57,6 +66,20 @@ SELECT entry as "entry: Json<Agent>"
         Ok(agent_entry.entry.0)
     }
 
+    async fn list_agent_symbols(&self, _ctx: &Ctx) -> Result<Vec<AgentSymbol>> {
+        let agent_symbols = sqlx::query_scalar!(
+            r#"
+select agent_symbol
+  from agent
+ order by agent_symbol
+        "#
+        )
+        .fetch_all(self.mm.pool())
+        .await?;
+
+        Ok(agent_symbols.into_iter().map(AgentSymbol).collect())
+    }
+
     async fn store_agent(&self, _ctx: &Ctx, agent: &Agent) -> Result<()> {
         sqlx::query!(
             r#"
@@ -72,11 +95,20 @@ on conflict (agent_symbol) do update set entry = excluded.entry
 
         Ok(())
     }
+
+    async fn save_agent_snapshots(&self, _ctx: &Ctx, snapshots: &[AgentSnapshot]) -> Result<()> {
+        db::insert_agent_snapshots(self.mm.pool(), snapshots).await
+    }
+
+    async fn get_latest_agent_snapshots_until(&self, _ctx: &Ctx, until: DateTime<Utc>) -> Result<Vec<AgentSnapshot>> {
+        db::select_latest_agent_snapshots_until(self.mm.pool(), until).await
+    }
 }
 
 #[derive(Debug)]
 pub struct InMemoryAgentBmc {
     in_memory_agent: Arc<RwLock<Agent>>,
+    agent_snapshots: Arc<RwLock<Vec<AgentSnapshot>>>,
 }
 
 #[async_trait]
@@ -101,12 +133,41 @@ impl AgentBmcTrait for InMemoryAgentBmc {
         // println!("Stored agent");
         Ok(())
     }
+
+    async fn list_agent_symbols(&self, _ctx: &Ctx) -> Result<Vec<AgentSymbol>> {
+        Ok(vec![self.in_memory_agent.read().await.symbol.clone()])
+    }
+
+    async fn save_agent_snapshots(&self, _ctx: &Ctx, snapshots: &[AgentSnapshot]) -> Result<()> {
+        self.agent_snapshots
+            .write()
+            .await
+            .extend(snapshots.iter().cloned());
+        Ok(())
+    }
+
+    async fn get_latest_agent_snapshots_until(&self, _ctx: &Ctx, until: DateTime<Utc>) -> Result<Vec<AgentSnapshot>> {
+        let snapshots = self.agent_snapshots.read().await;
+        Ok(snapshots
+            .iter()
+            .filter(|snapshot| snapshot.observed_at <= until)
+            .into_group_map_by(|snapshot| snapshot.agent_symbol.clone())
+            .into_values()
+            .filter_map(|snapshots_of_agent| {
+                snapshots_of_agent
+                    .into_iter()
+                    .max_by_key(|snapshot| snapshot.observed_at)
+            })
+            .cloned()
+            .collect())
+    }
 }
 
 impl InMemoryAgentBmc {
     pub fn new(agent: Agent) -> Self {
         Self {
             in_memory_agent: Arc::new(RwLock::new(agent)),
+            agent_snapshots: Arc::new(RwLock::new(Vec::new())),
         }
     }
 }
@@ -2,7 +2,8 @@ use crate::{db, Ctx, DbModelManager};
 use anyhow::*;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use st_domain::{Contract, ContractId, SystemSymbol};
+use itertools::Itertools;
+use st_domain::{Contract, ContractEntry, ContractId, SystemSymbol};
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::sync::Arc;
@@ -11,7 +12,9 @@ use tokio::sync::RwLock;
 #[async_trait]
 pub trait ContractBmcTrait: Send + Sync + Debug {
     async fn upsert_contract(&self, ctx: &Ctx, system_symbol: &SystemSymbol, contract: Contract, now: DateTime<Utc>) -> Result<()>;
-    async fn get_youngest_contract(&self, ctx: &Ctx, system_symbol: &SystemSymbol) -> Result<Option<Contract>>;
+    async fn get_youngest_contract(&self, ctx: &Ctx, system_symbol: &SystemSymbol) -> Result<Option<ContractEntry>>;
+    async fn get_contract_history(&self, ctx: &Ctx, system_symbol: &SystemSymbol) -> Result<Vec<ContractEntry>>;
+    async fn mark_contract_as_declined(&self, ctx: &Ctx, contract_id: &ContractId, now: DateTime<Utc>) -> Result<()>;
 }
 
 #[derive(Debug)]
@@ -21,18 +24,26 @@ pub struct DbContractBmc {
 
 #[async_trait]
 impl ContractBmcTrait for DbContractBmc {
-    async fn upsert_contract(&self, _ctx: &Ctx, system_symbol: &SystemSymbol, contract: Contract, now: DateTime<Utc>) -> Result<()> {
-        db::upsert_contract(self.mm.pool(), system_symbol, &contract, now).await
+    async fn upsert_contract(&self, ctx: &Ctx, system_symbol: &SystemSymbol, contract: Contract, now: DateTime<Utc>) -> Result<()> {
+        db::upsert_contract(self.mm.pool(), &self.mm.agent_symbol(ctx)?, system_symbol, &contract, now).await
+    }
+
+    async fn get_youngest_contract(&self, ctx: &Ctx, system_symbol: &SystemSymbol) -> Result<Option<ContractEntry>> {
+        db::get_youngest_contract(self.mm.pool(), &self.mm.agent_symbol(ctx)?, system_symbol).await
+    }
+
+    async fn get_contract_history(&self, ctx: &Ctx, system_symbol: &SystemSymbol) -> Result<Vec<ContractEntry>> {
+        db::get_contract_history(self.mm.pool(), &self.mm.agent_symbol(ctx)?, system_symbol).await
     }
 
-    async fn get_youngest_contract(&self, _ctx: &Ctx, system_symbol: &SystemSymbol) -> Result<Option<Contract>> {
-        db::get_youngest_contract(self.mm.pool(), system_symbol).await
+    async fn mark_contract_as_declined(&self, ctx: &Ctx, contract_id: &ContractId, now: DateTime<Utc>) -> Result<()> {
+        db::mark_contract_as_declined(self.mm.pool(), &self.mm.agent_symbol(ctx)?, contract_id, now).await
     }
 }
 
 #[derive(Debug)]
 pub struct InMemoryContracts {
-    contracts: HashMap<SystemSymbol, HashMap<ContractId, (Contract, DateTime<Utc>)>>,
+    contracts: HashMap<SystemSymbol, HashMap<ContractId, ContractEntry>>,
 }
 
 impl InMemoryContracts {
@@ -65,16 +76,28 @@ impl ContractBmcTrait for InMemoryContractBmc {
     async fn upsert_contract(&self, _ctx: &Ctx, system_symbol: &SystemSymbol, contract: Contract, now: DateTime<Utc>) -> Result<()> {
         let mut guard = self.in_memory_contracts.write().await;
 
+        // keeps created_at, like the db upsert does
         guard
             .contracts
             .entry(system_symbol.clone())
             .or_default()
-            .insert(contract.id.clone(), (contract.clone(), now));
+            .entry(contract.id.clone())
+            .and_modify(|entry| {
+                entry.contract = contract.clone();
+                entry.updated_at = now;
+            })
+            .or_insert_with(|| ContractEntry {
+                system_symbol: system_symbol.clone(),
+                contract: contract.clone(),
+                created_at: now,
+                updated_at: now,
+                declined_at: None,
+            });
 
         Ok(())
     }
 
-    async fn get_youngest_contract(&self, _ctx: &Ctx, system_symbol: &SystemSymbol) -> Result<Option<Contract>> {
+    async fn get_youngest_contract(&self, _ctx: &Ctx, system_symbol: &SystemSymbol) -> Result<Option<ContractEntry>> {
         let guard = self.in_memory_contracts.read().await;
 
         let contracts_of_system = guard
@@ -85,8 +108,38 @@ impl ContractBmcTrait for InMemoryContractBmc {
 
         Ok(contracts_of_system
             .values()
-            .cloned()
-            .max_by_key(|(_contract, created_at)| *created_at)
-            .map(|(contract, _)| contract.clone()))
+            .max_by_key(|entry| entry.created_at)
+            .cloned())
+    }
+
+    async fn get_contract_history(&self, _ctx: &Ctx, system_symbol: &SystemSymbol) -> Result<Vec<ContractEntry>> {
+        let guard = self.in_memory_contracts.read().await;
+
+        Ok(guard
+            .contracts
+            .get(system_symbol)
+            .map(|contracts_of_system| {
+                contracts_of_system
+                    .values()
+                    .sorted_by_key(|entry| std::cmp::Reverse(entry.created_at))
+                    .cloned()
+                    .collect_vec()
+            })
+            .unwrap_or_default())
+    }
+
+    async fn mark_contract_as_declined(&self, _ctx: &Ctx, contract_id: &ContractId, now: DateTime<Utc>) -> Result<()> {
+        let mut guard = self.in_memory_contracts.write().await;
+
+        for entry in guard
+            .contracts
+            .values_mut()
+            .filter_map(|contracts_of_system| contracts_of_system.get_mut(contract_id))
+        {
+            entry.declined_at = Some(now);
+            entry.updated_at = now;
+        }
+
+        Ok(())
     }
 }
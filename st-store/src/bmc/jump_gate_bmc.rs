@@ -11,6 +11,7 @@ use tokio::sync::RwLock;
 #[async_trait]
 pub trait JumpGateBmcTrait: Send + Sync + Debug {
     async fn save_jump_gate_data(&self, ctx: &Ctx, jump_gate: JumpGate, now: DateTime<Utc>) -> Result<()>;
+    async fn get_jump_gates(&self, ctx: &Ctx) -> Result<Vec<JumpGateEntry>>;
 }
 
 #[derive(Debug)]
@@ -23,6 +24,10 @@ impl JumpGateBmcTrait for DbJumpGateBmc {
     async fn save_jump_gate_data(&self, _ctx: &Ctx, jump_gate: JumpGate, now: DateTime<Utc>) -> Result<()> {
         db::insert_jump_gates(self.mm.pool(), vec![jump_gate], now).await
     }
+
+    async fn get_jump_gates(&self, _ctx: &Ctx) -> Result<Vec<JumpGateEntry>> {
+        db::select_jump_gates(self.mm.pool()).await
+    }
 }
 
 #[derive(Debug)]
@@ -81,4 +86,15 @@ impl JumpGateBmcTrait for InMemoryJumpGateBmc {
 
         Ok(())
     }
+
+    async fn get_jump_gates(&self, _ctx: &Ctx) -> Result<Vec<JumpGateEntry>> {
+        Ok(self
+            .in_memory_jump_gates
+            .read()
+            .await
+            .jump_gates
+            .values()
+            .flat_map(|jump_gates_of_system| jump_gates_of_system.values().cloned())
+            .collect())
+    }
 }
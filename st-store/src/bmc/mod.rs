@@ -1,6 +1,7 @@
 use crate::bmc::contract_bmc::{ContractBmcTrait, DbContractBmc, InMemoryContractBmc};
 use crate::bmc::jump_gate_bmc::{DbJumpGateBmc, InMemoryJumpGateBmc, JumpGateBmcTrait};
 use crate::bmc::ship_bmc::{DbShipBmc, InMemoryShipsBmc, ShipBmcTrait};
+use crate::cargo_flow_bmc::{CargoFlowBmcTrait, DbCargoFlowBmc, InMemoryCargoFlowBmc};
 use crate::ledger_bmc::{DbLedgerBmc, InMemoryLedgerBmc, LedgerBmcTrait};
 use crate::shipyard_bmc::{DbShipyardBmc, InMemoryShipyardBmc, ShipyardBmcTrait};
 use crate::survey_bmc::{DbSurveyBmc, InMemorySurveyBmc, SurveyBmcTrait};
@@ -11,6 +12,7 @@ use crate::{
     InMemorySystemsBmc, MarketBmcTrait, StatusBmcTrait, SupplyChainBmcTrait, SystemBmcTrait,
 };
 use mockall::automock;
+use st_domain::AgentSymbol;
 use std::fmt::Debug;
 use std::sync::Arc;
 
@@ -34,6 +36,7 @@ pub trait Bmc: Send + Sync + Debug {
     fn status_bmc(&self) -> Arc<dyn StatusBmcTrait>;
     fn ledger_bmc(&self) -> Arc<dyn LedgerBmcTrait>;
     fn contract_bmc(&self) -> Arc<dyn ContractBmcTrait>;
+    fn cargo_flow_bmc(&self) -> Arc<dyn CargoFlowBmcTrait>;
 }
 
 #[derive(Debug, Clone)]
@@ -54,6 +57,7 @@ pub struct DbBmc {
     status_bmc: Arc<DbStatusBmc>,
     ledger_bmc: Arc<DbLedgerBmc>,
     contract_bmc: Arc<DbContractBmc>,
+    cargo_flow_bmc: Arc<DbCargoFlowBmc>,
 }
 
 impl DbBmc {
@@ -74,8 +78,14 @@ impl DbBmc {
             status_bmc: Arc::new(DbStatusBmc { mm: mm.clone() }),
             ledger_bmc: Arc::new(DbLedgerBmc { mm: mm.clone() }),
             contract_bmc: Arc::new(DbContractBmc { mm: mm.clone() }),
+            cargo_flow_bmc: Arc::new(DbCargoFlowBmc { mm: mm.clone() }),
         }
     }
+
+    /// A bmc on the same db whose anonymous calls are made on behalf of the given agent.
+    pub fn for_agent(&self, agent_symbol: AgentSymbol) -> Self {
+        Self::new(DbModelManager::for_agent(self.db_model_manager.pool().clone(), agent_symbol))
+    }
 }
 
 impl Bmc for DbBmc {
@@ -134,6 +144,10 @@ impl Bmc for DbBmc {
     fn contract_bmc(&self) -> Arc<dyn ContractBmcTrait> {
         self.contract_bmc.clone() as Arc<dyn ContractBmcTrait>
     }
+
+    fn cargo_flow_bmc(&self) -> Arc<dyn CargoFlowBmcTrait> {
+        self.cargo_flow_bmc.clone() as Arc<dyn CargoFlowBmcTrait>
+    }
 }
 
 #[derive(Debug)]
@@ -152,6 +166,7 @@ pub struct InMemoryBmc {
     pub in_mem_status_bmc: Arc<InMemoryStatusBmc>,
     pub in_mem_ledger_bmc: Arc<InMemoryLedgerBmc>,
     pub in_mem_contract_bmc: Arc<InMemoryContractBmc>,
+    pub in_mem_cargo_flow_bmc: Arc<InMemoryCargoFlowBmc>,
 }
 
 impl Bmc for InMemoryBmc {
@@ -210,4 +225,8 @@ impl Bmc for InMemoryBmc {
     fn contract_bmc(&self) -> Arc<dyn ContractBmcTrait> {
         Arc::clone(&self.in_mem_contract_bmc) as Arc<dyn ContractBmcTrait>
     }
+
+    fn cargo_flow_bmc(&self) -> Arc<dyn CargoFlowBmcTrait> {
+        Arc::clone(&self.in_mem_cargo_flow_bmc) as Arc<dyn CargoFlowBmcTrait>
+    }
 }
@@ -5,7 +5,7 @@ use chrono::{DateTime, Utc};
 use itertools::Itertools;
 use mockall::automock;
 use sqlx::types::Json;
-use st_domain::{ExplorationTask, Ship, ShipSymbol, ShipTask, StationaryProbeLocation, WaypointSymbol};
+use st_domain::{AgentSymbol, ExplorationTask, Ship, ShipSymbol, ShipTask, StationaryProbeLocation, WaypointSymbol};
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::sync::Arc;
@@ -21,6 +21,10 @@ pub trait ShipBmcTrait: Send + Sync + Debug {
     async fn get_stationary_probes(&self, ctx: &Ctx) -> Result<Vec<StationaryProbeLocation>>;
     async fn insert_stationary_probe(&self, ctx: &Ctx, location: StationaryProbeLocation) -> Result<()>;
     async fn upsert_ships(&self, ctx: &Ctx, ships: &[Ship], now: DateTime<Utc>) -> Result<()>;
+    /// removes the left-behind ships of agents that aren't active anymore (e.g. a previous registration under another symbol)
+    /// and haven't been updated since the given timestamp - returns the number of removed ships.
+    /// The pruning spans all agents in the db, so it takes no ctx.
+    async fn prune_superseded_ships(&self, active_agent_symbols: &[AgentSymbol], older_than: DateTime<Utc>) -> Result<u64>;
 }
 
 #[derive(Debug)]
@@ -30,7 +34,8 @@ pub struct DbShipBmc {
 
 #[async_trait]
 impl ShipBmcTrait for DbShipBmc {
-    async fn get_ships(&self, _ctx: &Ctx, timestamp_filter_gte: Option<DateTime<Utc>>) -> Result<Vec<Ship>> {
+    async fn get_ships(&self, ctx: &Ctx, timestamp_filter_gte: Option<DateTime<Utc>>) -> Result<Vec<Ship>> {
+        let agent_symbol = self.mm.agent_symbol(ctx)?;
         let fallback = DateTime::<Utc>::from_timestamp(0, 0).unwrap();
 
         let ship_entries: Vec<DbShipEntry> = sqlx::query_as!(
@@ -42,8 +47,10 @@ select ship_symbol
      , updated_at
   from ships
  where updated_at >= $1
+   and agent_symbol = $2
         "#,
-            timestamp_filter_gte.unwrap_or(fallback)
+            timestamp_filter_gte.unwrap_or(fallback),
+            agent_symbol.0
         )
         .fetch_all(self.mm.pool())
         .await?;
@@ -53,7 +60,8 @@ select ship_symbol
         anyhow::Ok(ships)
     }
 
-    async fn get_ship(&self, _ctx: &Ctx, ship_symbol: ShipSymbol) -> Result<Ship> {
+    async fn get_ship(&self, ctx: &Ctx, ship_symbol: ShipSymbol) -> Result<Ship> {
+        let agent_symbol = self.mm.agent_symbol(ctx)?;
         let ship_entry: DbShipEntry = sqlx::query_as!(
             DbShipEntry,
             r#"
@@ -63,8 +71,10 @@ select ship_symbol
      , updated_at
   from ships
  where ships.ship_symbol = $1
+   and ships.agent_symbol = $2
         "#,
-            ship_symbol.0
+            ship_symbol.0,
+            agent_symbol.0
         )
         .fetch_one(self.mm.pool())
         .await?;
@@ -72,14 +82,17 @@ select ship_symbol
         anyhow::Ok(ship_entry.entry.0)
     }
 
-    async fn load_ship_tasks(&self, _ctx: &Ctx) -> Result<HashMap<ShipSymbol, ShipTask>> {
+    async fn load_ship_tasks(&self, ctx: &Ctx) -> Result<HashMap<ShipSymbol, ShipTask>> {
+        let agent_symbol = self.mm.agent_symbol(ctx)?;
         let entries: Vec<DbShipTaskEntry> = sqlx::query_as!(
             DbShipTaskEntry,
             r#"
 select ship_symbol
      , task as "task: Json<ShipTask>"
   from ship_task_assignments
+ where agent_symbol = $1
         "#,
+            agent_symbol.0
         )
         .fetch_all(self.mm.pool())
         .await?;
@@ -92,16 +105,18 @@ select ship_symbol
         )
     }
 
-    async fn save_ship_tasks(&self, _ctx: &Ctx, ship_task_assignments: &HashMap<ShipSymbol, ShipTask>) -> Result<()> {
+    async fn save_ship_tasks(&self, ctx: &Ctx, ship_task_assignments: &HashMap<ShipSymbol, ShipTask>) -> Result<()> {
+        let agent_symbol = self.mm.agent_symbol(ctx)?;
         for (ship_symbol, task) in ship_task_assignments {
             sqlx::query!(
                 r#"
-insert into ship_task_assignments (ship_symbol, task)
-values ($1, $2)
+insert into ship_task_assignments (ship_symbol, task, agent_symbol)
+values ($1, $2, $3)
 on conflict (ship_symbol) do update set task = excluded.task
         "#,
                 ship_symbol.0,
-                Json(task.clone()) as _
+                Json(task.clone()) as _,
+                agent_symbol.0
             )
             .execute(self.mm.pool())
             .await?;
@@ -109,7 +124,8 @@ on conflict (ship_symbol) do update set task = excluded.task
         anyhow::Ok(())
     }
 
-    async fn get_stationary_probes(&self, _ctx: &Ctx) -> Result<Vec<StationaryProbeLocation>> {
+    async fn get_stationary_probes(&self, ctx: &Ctx) -> Result<Vec<StationaryProbeLocation>> {
+        let agent_symbol = self.mm.agent_symbol(ctx)?;
         let entries: Vec<DbStationaryProbeLocation> = sqlx::query_as!(
             DbStationaryProbeLocation,
             r#"
@@ -117,7 +133,9 @@ select waypoint_symbol
      , probe_ship_symbol
      , exploration_tasks as "exploration_tasks: Json<Vec<ExplorationTask>>"
   from stationary_probe_locations
+ where agent_symbol = $1
         "#,
+            agent_symbol.0
         )
         .fetch_all(self.mm.pool())
         .await?;
@@ -134,19 +152,21 @@ select waypoint_symbol
         )
     }
 
-    async fn insert_stationary_probe(&self, _ctx: &Ctx, location: StationaryProbeLocation) -> Result<()> {
+    async fn insert_stationary_probe(&self, ctx: &Ctx, location: StationaryProbeLocation) -> Result<()> {
+        let agent_symbol = self.mm.agent_symbol(ctx)?;
         sqlx::query!(
             r#"
-insert into stationary_probe_locations ( waypoint_symbol, probe_ship_symbol, exploration_tasks )
-values ($1, $2, $3)
-on conflict (waypoint_symbol) do update
+insert into stationary_probe_locations ( waypoint_symbol, probe_ship_symbol, exploration_tasks, agent_symbol )
+values ($1, $2, $3, $4)
+on conflict (agent_symbol, waypoint_symbol) do update
     set probe_ship_symbol = excluded.probe_ship_symbol
       , exploration_tasks = excluded.exploration_tasks
 
         "#,
             location.waypoint_symbol.0,
             location.probe_ship_symbol.0,
-            Json(location.exploration_tasks.clone()) as _
+            Json(location.exploration_tasks.clone()) as _,
+            agent_symbol.0
         )
         .execute(self.mm.pool())
         .await?;
@@ -154,11 +174,15 @@ on conflict (waypoint_symbol) do update
         anyhow::Ok(())
     }
 
-    async fn upsert_ships(&self, _ctx: &Ctx, ships: &[Ship], now: DateTime<Utc>) -> Result<()> {
-        db::upsert_ships(self.mm.pool(), ships, now).await?;
+    async fn upsert_ships(&self, ctx: &Ctx, ships: &[Ship], now: DateTime<Utc>) -> Result<()> {
+        db::upsert_ships(self.mm.pool(), &self.mm.agent_symbol(ctx)?, ships, now).await?;
 
         Ok(())
     }
+
+    async fn prune_superseded_ships(&self, active_agent_symbols: &[AgentSymbol], older_than: DateTime<Utc>) -> Result<u64> {
+        db::delete_superseded_ships(self.mm.pool(), active_agent_symbols, older_than).await
+    }
 }
 
 pub struct DbStationaryProbeLocation {
@@ -170,6 +194,8 @@ pub struct DbStationaryProbeLocation {
 #[derive(Debug)]
 pub struct InMemoryShips {
     ships: HashMap<ShipSymbol, Ship>,
+    /// the agent and the time of the last upsert - only known for ships upserted with an explicit agent ctx
+    ship_owners: HashMap<ShipSymbol, (AgentSymbol, DateTime<Utc>)>,
     ship_tasks: HashMap<ShipSymbol, ShipTask>,
     stationary_probe_locations: HashMap<WaypointSymbol, StationaryProbeLocation>,
 }
@@ -184,6 +210,7 @@ impl InMemoryShips {
     pub fn new() -> Self {
         Self {
             ships: Default::default(),
+            ship_owners: Default::default(),
             ship_tasks: Default::default(),
             stationary_probe_locations: Default::default(),
         }
@@ -257,12 +284,35 @@ impl ShipBmcTrait for InMemoryShipsBmc {
         Ok(())
     }
 
-    async fn upsert_ships(&self, _ctx: &Ctx, ships: &[Ship], _now: DateTime<Utc>) -> Result<()> {
+    async fn upsert_ships(&self, ctx: &Ctx, ships: &[Ship], now: DateTime<Utc>) -> Result<()> {
         let mut guard = self.in_memory_ships.write().await;
         for ship in ships {
             guard.ships.insert(ship.symbol.clone(), ship.clone());
+            if let Ctx::Agent(agent_symbol) = ctx {
+                guard
+                    .ship_owners
+                    .insert(ship.symbol.clone(), (agent_symbol.clone(), now));
+            }
         }
 
         Ok(())
     }
+
+    async fn prune_superseded_ships(&self, active_agent_symbols: &[AgentSymbol], older_than: DateTime<Utc>) -> Result<u64> {
+        let mut guard = self.in_memory_ships.write().await;
+        // ships without a known owner belong to the agent the bmc has been created for
+        let superseded = guard
+            .ship_owners
+            .iter()
+            .filter(|(_, (agent_symbol, updated_at))| !active_agent_symbols.contains(agent_symbol) && *updated_at < older_than)
+            .map(|(ship_symbol, _)| ship_symbol.clone())
+            .collect_vec();
+
+        for ship_symbol in superseded.iter() {
+            guard.ships.remove(ship_symbol);
+            guard.ship_owners.remove(ship_symbol);
+        }
+
+        Ok(superseded.len() as u64)
+    }
 }
@@ -0,0 +1,64 @@
+use crate::ctx::Ctx;
+use crate::{db, DbModelManager};
+use anyhow::*;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
+use mockall::automock;
+use st_domain::cargo_flow::CargoFlowEntry;
+use std::fmt::Debug;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+#[derive(Debug)]
+pub struct DbCargoFlowBmc {
+    pub(crate) mm: DbModelManager,
+}
+
+#[automock]
+#[async_trait]
+pub trait CargoFlowBmcTrait: Send + Sync + Debug {
+    async fn log_cargo_flows(&self, ctx: &Ctx, entries: &[CargoFlowEntry]) -> Result<()>;
+    async fn get_cargo_flows_since(&self, ctx: &Ctx, since: DateTime<Utc>) -> Result<Vec<CargoFlowEntry>>;
+}
+
+#[async_trait]
+impl CargoFlowBmcTrait for DbCargoFlowBmc {
+    async fn log_cargo_flows(&self, ctx: &Ctx, entries: &[CargoFlowEntry]) -> Result<()> {
+        db::insert_cargo_flows(self.mm.pool(), &self.mm.agent_symbol(ctx)?, entries).await
+    }
+
+    async fn get_cargo_flows_since(&self, ctx: &Ctx, since: DateTime<Utc>) -> Result<Vec<CargoFlowEntry>> {
+        db::select_cargo_flows_since(self.mm.pool(), &self.mm.agent_symbol(ctx)?, since).await
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct InMemoryCargoFlowBmc {
+    entries: Arc<RwLock<Vec<CargoFlowEntry>>>,
+}
+
+impl InMemoryCargoFlowBmc {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl CargoFlowBmcTrait for InMemoryCargoFlowBmc {
+    async fn log_cargo_flows(&self, _ctx: &Ctx, entries: &[CargoFlowEntry]) -> Result<()> {
+        self.entries.write().await.extend(entries.iter().cloned());
+        Ok(())
+    }
+
+    async fn get_cargo_flows_since(&self, _ctx: &Ctx, since: DateTime<Utc>) -> Result<Vec<CargoFlowEntry>> {
+        Ok(self
+            .entries
+            .read()
+            .await
+            .iter()
+            .filter(|entry| entry.created_at >= since)
+            .cloned()
+            .collect_vec())
+    }
+}
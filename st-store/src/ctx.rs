@@ -1,4 +1,12 @@
+use st_domain::AgentSymbol;
+
+/// The agent a bmc call is made for.
+///
+/// The tables an agent owns (ships, fleets, tickets, finances, ...) are scoped to it, so several agents can share one database.
+/// The universe data (systems, waypoints, markets, ...) is the same for all agents of a reset and isn't scoped.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Ctx {
+    /// the agent the bmc has been created for
     Anonymous,
-    // Add other context types as needed
+    Agent(AgentSymbol),
 }
@@ -10,10 +10,18 @@ use sqlx::{ConnectOptions, Pool, Postgres};
 use tracing::log::LevelFilter;
 use tracing::{event, Level};
 
+use st_domain::budgeting::ledger_audit::{LedgerAuditReport, TimestampedLedgerEntry};
 use st_domain::budgeting::treasury_redesign::LedgerEntry;
+use st_domain::cargo_flow::CargoFlowEntry;
+use st_domain::competitor_intelligence::AgentSnapshot;
+use st_domain::extraction_analytics::SurveyUsage;
+use st_domain::galaxy_crawl::GalaxyCrawlProgress;
+use st_domain::market_history::MarketHourlyAggregate;
+use st_domain::system_map::GalaxyMapSystem;
 use st_domain::{
-    distance_to, Construction, Contract, Data, Extraction, JumpGate, MarketData, MarketEntry, RegistrationResponse, Ship, ShipTask, Shipyard, ShipyardData,
-    StStatusResponse, SupplyChain, Survey, SurveySignature, SystemSymbol, SystemsPageData, Waypoint, WaypointSymbol, WaypointTraitSymbol,
+    distance_to, ActivityLevel, AgentSymbol, Construction, Contract, ContractEntry, ContractId, Data, Extraction, JumpGate, JumpGateEntry, MarketData,
+    MarketEntry, RegistrationResponse, Ship, ShipTask, Shipyard, ShipyardData, StStatusResponse, SupplyChain, SupplyLevel, Survey, SurveySignature,
+    SystemSymbol, SystemsPageData, TradeGoodSymbol, Waypoint, WaypointSymbol, WaypointTraitSymbol,
 };
 
 #[derive(Clone)]
@@ -162,15 +170,17 @@ pub struct DbRegistrationResponse {
     pub entry: Json<Data<RegistrationResponse>>,
 }
 
-pub async fn load_registration(pool: &Pool<Postgres>) -> Result<Option<DbRegistrationResponse>> {
+pub async fn load_registration(pool: &Pool<Postgres>, agent_symbol: &AgentSymbol) -> Result<Option<DbRegistrationResponse>> {
     let maybe_result = sqlx::query_as!(
         DbRegistrationResponse,
         r#"
 select token
      , entry as "entry: Json<Data<RegistrationResponse>>"
   from registration
-  limit 1
+ where agent_symbol = $1
+ limit 1
         "#,
+        agent_symbol.0
     )
     .fetch_optional(pool)
     .await?;
@@ -178,14 +188,15 @@ select token
     Ok(maybe_result)
 }
 
-pub async fn save_registration(pool: &Pool<Postgres>, api_registration_response: Data<RegistrationResponse>) -> Result<()> {
+pub async fn save_registration(pool: &Pool<Postgres>, agent_symbol: &AgentSymbol, api_registration_response: Data<RegistrationResponse>) -> Result<()> {
     sqlx::query!(
         r#"
-insert into registration (token, entry)
-values ($1, $2)
+insert into registration (token, entry, agent_symbol)
+values ($1, $2, $3)
         "#,
         api_registration_response.data.token,
-        Json(api_registration_response.clone()) as _
+        Json(api_registration_response.clone()) as _,
+        agent_symbol.0
     )
     .execute(pool)
     .await?;
@@ -258,9 +269,43 @@ pub struct DbLedgerEntry {
 }
 
 #[derive(Serialize, Clone, Debug, Deserialize)]
-pub struct DbContractEntry {
+pub struct DbLedgerAuditReport {
+    pub entry: Json<LedgerAuditReport>,
+}
+
+#[derive(Serialize, Clone, Debug, Deserialize)]
+pub struct DbGalaxyMapSystem {
+    pub system_symbol: String,
+    pub system_type: String,
+    pub x: i64,
+    pub y: i64,
+    pub num_waypoints: i64,
+}
+
+#[derive(Serialize, Clone, Debug, Deserialize)]
+pub struct DbGalaxyCrawlProgress {
+    pub entry: Json<GalaxyCrawlProgress>,
+}
+
+#[derive(Serialize, Clone, Debug, Deserialize)]
+pub struct DbContractHistoryEntry {
+    pub system_symbol: String,
     pub entry: Json<Contract>,
     pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub declined_at: Option<DateTime<Utc>>,
+}
+
+impl From<DbContractHistoryEntry> for ContractEntry {
+    fn from(db_entry: DbContractHistoryEntry) -> Self {
+        ContractEntry {
+            system_symbol: SystemSymbol(db_entry.system_symbol),
+            contract: db_entry.entry.0,
+            created_at: db_entry.created_at,
+            updated_at: db_entry.updated_at,
+            declined_at: db_entry.declined_at,
+        }
+    }
 }
 
 #[derive(Serialize, Clone, Debug, Deserialize)]
@@ -468,7 +513,7 @@ where system_symbol = $1
         .collect_vec())
 }
 
-pub async fn select_ships(pool: &Pool<Postgres>) -> Result<Vec<Ship>> {
+pub async fn select_ships(pool: &Pool<Postgres>, agent_symbol: &AgentSymbol) -> Result<Vec<Ship>> {
     let ship_entries: Vec<DbShipEntry> = sqlx::query_as!(
         DbShipEntry,
         r#"
@@ -477,7 +522,9 @@ select ship_symbol
      , created_at
      , updated_at
 from ships
+where agent_symbol = $1
     "#,
+        agent_symbol.0
     )
     .fetch_all(pool)
     .await?;
@@ -717,7 +764,7 @@ from systems s
     Ok(count.unwrap_or(0))
 }
 
-pub async fn upsert_ships(pool: &Pool<Postgres>, ships: &[Ship], now: DateTime<Utc>) -> Result<()> {
+pub async fn upsert_ships(pool: &Pool<Postgres>, agent_symbol: &AgentSymbol, ships: &[Ship], now: DateTime<Utc>) -> Result<()> {
     let db_entries: Vec<DbShipEntry> = ships
         .iter()
         .map(|ship| DbShipEntry {
@@ -735,14 +782,15 @@ pub async fn upsert_ships(pool: &Pool<Postgres>, ships: &[Ship], now: DateTime<U
 
         sqlx::query!(
             r#"
-insert into ships (ship_symbol, entry, created_at, updated_at)
-values ($1, $2, $3, $4)
+insert into ships (ship_symbol, entry, created_at, updated_at, agent_symbol)
+values ($1, $2, $3, $4, $5)
 on conflict (ship_symbol) do UPDATE set entry = excluded.entry, updated_at = excluded.updated_at
         "#,
             first.ship_symbol,
             first.entry as _,
             now,
             now,
+            agent_symbol.0,
         )
         .execute(pool)
         .await?;
@@ -751,12 +799,13 @@ on conflict (ship_symbol) do UPDATE set entry = excluded.entry, updated_at = exc
 
         sqlx::query!(
             r#"
-insert into ships
-select *
+insert into ships (ship_symbol, entry, created_at, updated_at, agent_symbol)
+select ship_symbol, entry, created_at, updated_at, $2
 from jsonb_populate_recordset(NULL::ships, $1)
 on conflict (ship_symbol) do UPDATE set entry = excluded.entry, updated_at = excluded.updated_at
             "#,
-            json_array
+            json_array,
+            agent_symbol.0
         )
         .execute(pool)
         .await?;
@@ -828,7 +877,7 @@ select entry as "entry: Json<SupplyChain>"
     Ok(maybe_supply_chain.map(|db_entry| db_entry.entry.0))
 }
 
-pub(crate) async fn upsert_surveys(pool: &Pool<Postgres>, surveys: Vec<Survey>, now: DateTime<Utc>) -> Result<()> {
+pub(crate) async fn upsert_surveys(pool: &Pool<Postgres>, agent_symbol: &AgentSymbol, surveys: Vec<Survey>, now: DateTime<Utc>) -> Result<()> {
     let db_entries: Vec<DbSurveyEntry> = surveys
         .iter()
         .map(|s| DbSurveyEntry {
@@ -844,8 +893,8 @@ pub(crate) async fn upsert_surveys(pool: &Pool<Postgres>, surveys: Vec<Survey>,
     for entry in db_entries {
         sqlx::query!(
             r#"
-insert into surveys (waypoint_symbol, signature, entry, created_at, expires_at, is_discarded)
-values ($1, $2, $3, $4, $5, $6)
+insert into surveys (waypoint_symbol, signature, entry, created_at, expires_at, is_discarded, agent_symbol)
+values ($1, $2, $3, $4, $5, $6, $7)
         "#,
             entry.waypoint_symbol,
             entry.signature,
@@ -853,6 +902,7 @@ values ($1, $2, $3, $4, $5, $6)
             now,
             entry.expires_at,
             entry.is_discarded,
+            agent_symbol.0,
         )
         .execute(pool)
         .await?;
@@ -860,7 +910,12 @@ values ($1, $2, $3, $4, $5, $6)
     Ok(())
 }
 
-pub(crate) async fn get_valid_surveys_for_waypoint(pool: &Pool<Postgres>, waypoint_symbol: WaypointSymbol, now: DateTime<Utc>) -> anyhow::Result<Vec<Survey>> {
+pub(crate) async fn get_valid_surveys_for_waypoint(
+    pool: &Pool<Postgres>,
+    agent_symbol: &AgentSymbol,
+    waypoint_symbol: WaypointSymbol,
+    now: DateTime<Utc>,
+) -> anyhow::Result<Vec<Survey>> {
     let entries: Vec<DbSurveyEntry> = sqlx::query_as!(
         DbSurveyEntry,
         r#"
@@ -874,9 +929,11 @@ from surveys
 where waypoint_symbol = $1
   and expires_at > $2
   and is_discarded = false
+  and agent_symbol = $3
     "#,
         waypoint_symbol.0,
-        now
+        now,
+        agent_symbol.0
     )
     .fetch_all(pool)
     .await?;
@@ -887,14 +944,16 @@ where waypoint_symbol = $1
         .collect_vec())
 }
 
-pub(crate) async fn mark_survey_as_exhausted(pool: &Pool<Postgres>, survey_signature: SurveySignature) -> anyhow::Result<()> {
+pub(crate) async fn mark_survey_as_exhausted(pool: &Pool<Postgres>, agent_symbol: &AgentSymbol, survey_signature: SurveySignature) -> anyhow::Result<()> {
     sqlx::query!(
         r#"
 update surveys
     set is_discarded = true
 where signature = $1
+  and agent_symbol = $2
         "#,
         survey_signature.0.clone(),
+        agent_symbol.0,
     )
     .execute(pool)
     .await?;
@@ -904,6 +963,7 @@ where signature = $1
 
 pub(crate) async fn insert_survey_usage(
     pool: &Pool<Postgres>,
+    agent_symbol: &AgentSymbol,
     survey_signature: SurveySignature,
     extraction: Extraction,
     now: DateTime<Utc>,
@@ -926,12 +986,13 @@ pub(crate) async fn insert_survey_usage(
 
     sqlx::query!(
         r#"
-insert into survey_usage_log (survey_signature, extraction, created_at)
-values ($1, $2, $3)
+insert into survey_usage_log (survey_signature, extraction, created_at, agent_symbol)
+values ($1, $2, $3, $4)
         "#,
         db_entry.signature,
         db_entry.extraction as _,
         db_entry.created_at,
+        agent_symbol.0,
     )
     .execute(pool)
     .await?;
@@ -939,7 +1000,140 @@ values ($1, $2, $3)
     Ok(())
 }
 
-pub(crate) async fn archive_ledger_entry(pool: &Pool<Postgres>, ledger_entry: &LedgerEntry, now: DateTime<Utc>) -> anyhow::Result<()> {
+#[derive(Serialize, Clone, Debug, Deserialize)]
+pub struct DbSurveyUsageWithSurveyEntry {
+    pub survey: Json<Survey>,
+    pub extraction: Json<Extraction>,
+    pub created_at: DateTime<Utc>,
+}
+
+pub(crate) async fn select_survey_usages_since(pool: &Pool<Postgres>, agent_symbol: &AgentSymbol, since: DateTime<Utc>) -> anyhow::Result<Vec<SurveyUsage>> {
+    // surveys can be stored multiple times (created_at is part of the pkey) - we only need one of them
+    let entries: Vec<DbSurveyUsageWithSurveyEntry> = sqlx::query_as!(
+        DbSurveyUsageWithSurveyEntry,
+        r#"
+select s.entry as "survey!: Json<Survey>"
+     , l.extraction as "extraction: Json<Extraction>"
+     , l.created_at
+from survey_usage_log l
+join lateral (select entry
+              from surveys
+              where signature = l.survey_signature
+                and agent_symbol = l.agent_symbol
+              order by created_at desc
+              limit 1) s on true
+where l.created_at >= $1
+  and l.agent_symbol = $2
+order by l.created_at
+    "#,
+        since,
+        agent_symbol.0
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(entries
+        .into_iter()
+        .map(|db_entry| SurveyUsage {
+            survey: db_entry.survey.0,
+            extraction: db_entry.extraction.0,
+            created_at: db_entry.created_at,
+        })
+        .collect_vec())
+}
+
+pub(crate) async fn insert_cargo_flows(pool: &Pool<Postgres>, agent_symbol: &AgentSymbol, entries: &[CargoFlowEntry]) -> anyhow::Result<()> {
+    /*
+        create table cargo_flow_log
+    (
+        site         text        not null,
+        ship_symbol  text        not null,
+        entry        jsonb       not null,
+        created_at   timestamptz not null
+    );
+         */
+
+    for entry in entries {
+        sqlx::query!(
+            r#"
+insert into cargo_flow_log (site, ship_symbol, entry, created_at, agent_symbol)
+values ($1, $2, $3, $4, $5)
+        "#,
+            entry.site.0.clone(),
+            entry.ship_symbol.0.clone(),
+            Json(entry.clone()) as _,
+            entry.created_at,
+            agent_symbol.0,
+        )
+        .execute(pool)
+        .await?;
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn select_cargo_flows_since(pool: &Pool<Postgres>, agent_symbol: &AgentSymbol, since: DateTime<Utc>) -> anyhow::Result<Vec<CargoFlowEntry>> {
+    let entries: Vec<Json<CargoFlowEntry>> = sqlx::query_scalar!(
+        r#"
+select entry as "entry: Json<CargoFlowEntry>"
+from cargo_flow_log
+where created_at >= $1
+  and agent_symbol = $2
+order by created_at
+    "#,
+        since,
+        agent_symbol.0
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(entries.into_iter().map(|entry| entry.0).collect_vec())
+}
+
+pub(crate) async fn insert_agent_snapshots(pool: &Pool<Postgres>, snapshots: &[AgentSnapshot]) -> anyhow::Result<()> {
+    for snapshot in snapshots {
+        sqlx::query!(
+            r#"
+insert into agent_snapshots (agent_symbol, observed_at, credits, ship_count, entry)
+values ($1, $2, $3, $4, $5)
+on conflict (agent_symbol, observed_at) do nothing
+        "#,
+            snapshot.agent_symbol.0.clone(),
+            snapshot.observed_at,
+            snapshot.credits,
+            snapshot.ship_count,
+            Json(snapshot.clone()) as _,
+        )
+        .execute(pool)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// The latest snapshot of every agent that had been observed at the given time.
+pub(crate) async fn select_latest_agent_snapshots_until(pool: &Pool<Postgres>, until: DateTime<Utc>) -> anyhow::Result<Vec<AgentSnapshot>> {
+    let entries: Vec<Json<AgentSnapshot>> = sqlx::query_scalar!(
+        r#"
+select distinct on (agent_symbol) entry as "entry: Json<AgentSnapshot>"
+  from agent_snapshots
+ where observed_at <= $1
+ order by agent_symbol, observed_at desc
+    "#,
+        until
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(entries.into_iter().map(|entry| entry.0).collect_vec())
+}
+
+pub(crate) async fn archive_ledger_entry(
+    pool: &Pool<Postgres>,
+    agent_symbol: &AgentSymbol,
+    ledger_entry: &LedgerEntry,
+    now: DateTime<Utc>,
+) -> anyhow::Result<()> {
     let entry = DbLedgerEntry {
         entry: Json(ledger_entry.clone()),
         created_at: now,
@@ -947,18 +1141,31 @@ pub(crate) async fn archive_ledger_entry(pool: &Pool<Postgres>, ledger_entry: &L
 
     sqlx::query!(
         r#"
-insert into ledger_entries (entry, created_at)
-values ($1, $2)
+insert into ledger_entries (entry, created_at, agent_symbol)
+values ($1, $2, $3)
         "#,
         entry.entry as _,
         now,
+        agent_symbol.0,
     )
     .execute(pool)
     .await?;
     Ok(())
 }
 
-pub(crate) async fn get_ledger_entries_in_order(pool: &Pool<Postgres>, _dt_gte: DateTime<Utc>) -> Result<Vec<LedgerEntry>> {
+pub(crate) async fn get_ledger_entries_in_order(pool: &Pool<Postgres>, agent_symbol: &AgentSymbol, dt_gte: DateTime<Utc>) -> Result<Vec<LedgerEntry>> {
+    Ok(get_timestamped_ledger_entries_in_order(pool, agent_symbol, dt_gte)
+        .await?
+        .into_iter()
+        .map(|e| e.entry)
+        .collect_vec())
+}
+
+pub(crate) async fn get_timestamped_ledger_entries_in_order(
+    pool: &Pool<Postgres>,
+    agent_symbol: &AgentSymbol,
+    _dt_gte: DateTime<Utc>,
+) -> Result<Vec<TimestampedLedgerEntry>> {
     let entries: Vec<DbLedgerEntry> = sqlx::query_as!(
         DbLedgerEntry,
         r#"
@@ -966,30 +1173,75 @@ select entry as "entry: Json<LedgerEntry>"
      , created_at
 
 from ledger_entries
+where agent_symbol = $1
 order by id
     "#,
+        agent_symbol.0
     )
     .fetch_all(pool)
     .await?;
 
     Ok(entries
-        .iter()
-        .map(|db_entry| db_entry.entry.0.clone())
+        .into_iter()
+        .map(|db_entry| TimestampedLedgerEntry {
+            entry: db_entry.entry.0,
+            created_at: db_entry.created_at,
+        })
         .collect_vec())
 }
 
-pub(crate) async fn upsert_contract(pool: &Pool<Postgres>, system_symbol: &SystemSymbol, contract: &Contract, now: DateTime<Utc>) -> Result<()> {
+pub(crate) async fn insert_ledger_audit_report(pool: &Pool<Postgres>, agent_symbol: &AgentSymbol, report: &LedgerAuditReport) -> Result<()> {
     sqlx::query!(
         r#"
-insert into contracts (id, system_symbol, entry, created_at, updated_at)
-values ($1, $2, $3, $4, $5)
+insert into ledger_audit_reports (entry, created_at, agent_symbol)
+values ($1, $2, $3)
+        "#,
+        Json(report.clone()) as _,
+        report.audited_at,
+        agent_symbol.0,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub(crate) async fn load_latest_ledger_audit_report(pool: &Pool<Postgres>, agent_symbol: &AgentSymbol) -> Result<Option<LedgerAuditReport>> {
+    let maybe_entry = sqlx::query_as!(
+        DbLedgerAuditReport,
+        r#"
+select entry as "entry: Json<LedgerAuditReport>"
+  from ledger_audit_reports
+ where agent_symbol = $1
+ order by id desc
+ limit 1
+        "#,
+        agent_symbol.0
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(maybe_entry.map(|db_entry| db_entry.entry.0))
+}
+
+pub(crate) async fn upsert_contract(
+    pool: &Pool<Postgres>,
+    agent_symbol: &AgentSymbol,
+    system_symbol: &SystemSymbol,
+    contract: &Contract,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+insert into contracts (id, system_symbol, entry, created_at, updated_at, agent_symbol)
+values ($1, $2, $3, $4, $5, $6)
 on conflict (id) do UPDATE set entry = excluded.entry, updated_at = excluded.updated_at
         "#,
         contract.id.0.clone(),
         system_symbol.0.clone(),
         Json(contract.clone()) as _,
         now,
-        now
+        now,
+        agent_symbol.0
     )
     .execute(pool)
     .await?;
@@ -997,19 +1249,330 @@ on conflict (id) do UPDATE set entry = excluded.entry, updated_at = excluded.upd
     Ok(())
 }
 
-pub(crate) async fn get_youngest_contract(pool: &Pool<Postgres>, _system_symbol: &SystemSymbol) -> Result<Option<Contract>> {
+pub(crate) async fn get_youngest_contract(pool: &Pool<Postgres>, agent_symbol: &AgentSymbol, _system_symbol: &SystemSymbol) -> Result<Option<ContractEntry>> {
     let maybe_result = sqlx::query_as!(
-        DbContractEntry,
+        DbContractHistoryEntry,
         r#"
-select entry as "entry: Json<Contract>",
-       created_at
+select system_symbol
+     , entry as "entry: Json<Contract>"
+     , created_at
+     , updated_at
+     , declined_at
   from contracts
-  order by created_at desc
-  limit 1
+ where agent_symbol = $1
+ order by created_at desc
+ limit 1
         "#,
+        agent_symbol.0
     )
     .fetch_optional(pool)
     .await?;
 
-    Ok(maybe_result.map(|db_entry| db_entry.entry.0))
+    Ok(maybe_result.map(ContractEntry::from))
+}
+
+pub(crate) async fn get_contract_history(pool: &Pool<Postgres>, agent_symbol: &AgentSymbol, system_symbol: &SystemSymbol) -> Result<Vec<ContractEntry>> {
+    let entries = sqlx::query_as!(
+        DbContractHistoryEntry,
+        r#"
+select system_symbol
+     , entry as "entry: Json<Contract>"
+     , created_at
+     , updated_at
+     , declined_at
+  from contracts
+ where system_symbol = $1
+   and agent_symbol = $2
+ order by created_at desc
+        "#,
+        system_symbol.0.clone(),
+        agent_symbol.0
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(entries.into_iter().map(ContractEntry::from).collect_vec())
+}
+
+pub(crate) async fn mark_contract_as_declined(pool: &Pool<Postgres>, agent_symbol: &AgentSymbol, contract_id: &ContractId, now: DateTime<Utc>) -> Result<()> {
+    sqlx::query!(
+        r#"
+update contracts
+   set declined_at = $3
+     , updated_at = $3
+ where id = $1
+   and agent_symbol = $2
+        "#,
+        contract_id.0.clone(),
+        agent_symbol.0,
+        now,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// The progress is stored for the reset of the status table - every reset gets its own schema.
+pub(crate) async fn upsert_galaxy_crawl_progress(pool: &Pool<Postgres>, progress: &GalaxyCrawlProgress) -> Result<()> {
+    sqlx::query!(
+        r#"
+insert into galaxy_crawl_progress (reset_date, entry, updated_at)
+select reset_date, $1, $2
+  from status
+on conflict (reset_date) do update set entry = excluded.entry, updated_at = excluded.updated_at
+        "#,
+        Json(progress.clone()) as _,
+        progress.updated_at,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub(crate) async fn load_galaxy_crawl_progress(pool: &Pool<Postgres>) -> Result<Option<GalaxyCrawlProgress>> {
+    let maybe_entry = sqlx::query_as!(
+        DbGalaxyCrawlProgress,
+        r#"
+select entry as "entry: Json<GalaxyCrawlProgress>"
+  from galaxy_crawl_progress
+ limit 1
+        "#,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(maybe_entry.map(|db_entry| db_entry.entry.0))
+}
+
+pub(crate) async fn select_jump_gates(pool: &Pool<Postgres>) -> Result<Vec<JumpGateEntry>> {
+    let entries: Vec<DbJumpGateData> = sqlx::query_as!(
+        DbJumpGateData,
+        r#"
+select system_symbol
+     , waypoint_symbol
+     , entry as "entry: Json<JumpGate>"
+     , created_at
+     , updated_at
+  from jump_gates
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(entries
+        .into_iter()
+        .map(|db_entry| JumpGateEntry {
+            system_symbol: SystemSymbol(db_entry.system_symbol),
+            waypoint_symbol: WaypointSymbol(db_entry.waypoint_symbol),
+            jump_gate: db_entry.entry.0,
+            created_at: db_entry.created_at,
+            updated_at: db_entry.updated_at,
+        })
+        .collect_vec())
+}
+
+/// Only the coordinates and a few facts of every system - the full entries are too large to render the whole galaxy.
+pub(crate) async fn select_galaxy_map_systems(pool: &Pool<Postgres>) -> Result<Vec<GalaxyMapSystem>> {
+    let entries: Vec<DbGalaxyMapSystem> = sqlx::query_as!(
+        DbGalaxyMapSystem,
+        r#"
+select system_symbol
+     , entry ->> 'type'                                   as "system_type!"
+     , (entry ->> 'x') :: bigint                          as "x!"
+     , (entry ->> 'y') :: bigint                          as "y!"
+     , jsonb_array_length(entry -> 'waypoints') :: bigint as "num_waypoints!"
+  from systems
+ order by system_symbol
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(entries
+        .into_iter()
+        .map(|db_entry| GalaxyMapSystem {
+            symbol: SystemSymbol(db_entry.system_symbol),
+            system_type: db_entry.system_type,
+            x: db_entry.x,
+            y: db_entry.y,
+            num_waypoints: db_entry.num_waypoints as usize,
+        })
+        .collect_vec())
+}
+
+/// Moves the market observations older than `older_than` into the hourly aggregates. The latest observation of every market stays,
+/// since it's still the current state of markets we haven't visited for a while. When such an observation gets downsampled later,
+/// it's merged into the aggregate of its hour.
+pub(crate) async fn downsample_market_observations(pool: &Pool<Postgres>, older_than: DateTime<Utc>) -> Result<u64> {
+    let count: Option<i64> = sqlx::query_scalar!(
+        r#"
+with downsampled as (delete
+                       from markets m
+                      where m.created_at < $1
+                        and m.created_at < (select max(latest.created_at)
+                                              from markets latest
+                                             where latest.waypoint_symbol = m.waypoint_symbol)
+                     returning m.waypoint_symbol, m.entry, m.created_at)
+   , aggregated as (insert into market_hourly_aggregates as a ( waypoint_symbol, trade_good_symbol, hour, num_observations
+                                                              , min_purchase_price, avg_purchase_price, max_purchase_price
+                                                              , min_sell_price, avg_sell_price, max_sell_price
+                                                              , avg_trade_volume, last_supply, last_activity)
+                        select d.waypoint_symbol
+                             , tg ->> 'symbol'
+                             , date_trunc('hour', d.created_at)
+                             , count(*)
+                             , min((tg ->> 'purchasePrice')::int)
+                             , avg((tg ->> 'purchasePrice')::int)
+                             , max((tg ->> 'purchasePrice')::int)
+                             , min((tg ->> 'sellPrice')::int)
+                             , avg((tg ->> 'sellPrice')::int)
+                             , max((tg ->> 'sellPrice')::int)
+                             , avg((tg ->> 'tradeVolume')::int)
+                             , (array_agg(tg -> 'supply' order by d.created_at desc))[1]
+                             , (array_agg(nullif(tg -> 'activity', 'null'::jsonb) order by d.created_at desc))[1]
+                          from downsampled d
+                         cross join lateral jsonb_array_elements(coalesce(d.entry -> 'tradeGoods', '[]'::jsonb)) tg
+                         group by d.waypoint_symbol, tg ->> 'symbol', date_trunc('hour', d.created_at)
+                    on conflict (waypoint_symbol, trade_good_symbol, hour) do update
+                        set num_observations   = a.num_observations + excluded.num_observations
+                          , min_purchase_price = least(a.min_purchase_price, excluded.min_purchase_price)
+                          , avg_purchase_price = (a.avg_purchase_price * a.num_observations + excluded.avg_purchase_price * excluded.num_observations)
+                                                 / (a.num_observations + excluded.num_observations)
+                          , max_purchase_price = greatest(a.max_purchase_price, excluded.max_purchase_price)
+                          , min_sell_price     = least(a.min_sell_price, excluded.min_sell_price)
+                          , avg_sell_price     = (a.avg_sell_price * a.num_observations + excluded.avg_sell_price * excluded.num_observations)
+                                                 / (a.num_observations + excluded.num_observations)
+                          , max_sell_price     = greatest(a.max_sell_price, excluded.max_sell_price)
+                          , avg_trade_volume   = (a.avg_trade_volume * a.num_observations + excluded.avg_trade_volume * excluded.num_observations)
+                                                 / (a.num_observations + excluded.num_observations)
+                          , last_supply        = excluded.last_supply
+                          , last_activity      = excluded.last_activity
+                    returning 1)
+select count(*)
+  from downsampled
+"#,
+        older_than
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(count.unwrap_or(0) as u64)
+}
+
+#[derive(Serialize, Clone, Debug, Deserialize)]
+pub struct DbMarketHourlyAggregate {
+    pub waypoint_symbol: String,
+    pub trade_good_symbol: Json<TradeGoodSymbol>,
+    pub hour: DateTime<Utc>,
+    pub num_observations: i32,
+    pub min_purchase_price: i32,
+    pub avg_purchase_price: f64,
+    pub max_purchase_price: i32,
+    pub min_sell_price: i32,
+    pub avg_sell_price: f64,
+    pub max_sell_price: i32,
+    pub avg_trade_volume: f64,
+    pub last_supply: Json<SupplyLevel>,
+    pub last_activity: Option<Json<ActivityLevel>>,
+}
+
+pub(crate) async fn select_market_hourly_aggregates_of_waypoint(
+    pool: &Pool<Postgres>,
+    waypoint_symbol: &WaypointSymbol,
+    since: DateTime<Utc>,
+) -> Result<Vec<MarketHourlyAggregate>> {
+    let entries: Vec<DbMarketHourlyAggregate> = sqlx::query_as!(
+        DbMarketHourlyAggregate,
+        r#"
+select waypoint_symbol
+     , to_jsonb(trade_good_symbol) as "trade_good_symbol!: Json<TradeGoodSymbol>"
+     , hour
+     , num_observations
+     , min_purchase_price
+     , avg_purchase_price
+     , max_purchase_price
+     , min_sell_price
+     , avg_sell_price
+     , max_sell_price
+     , avg_trade_volume
+     , last_supply as "last_supply: Json<SupplyLevel>"
+     , last_activity as "last_activity: Json<ActivityLevel>"
+  from market_hourly_aggregates
+ where waypoint_symbol = $1
+   and hour >= $2
+ order by hour, trade_good_symbol
+        "#,
+        waypoint_symbol.0,
+        since
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(entries
+        .into_iter()
+        .map(|db_entry| MarketHourlyAggregate {
+            waypoint_symbol: WaypointSymbol(db_entry.waypoint_symbol),
+            trade_good_symbol: db_entry.trade_good_symbol.0,
+            hour: db_entry.hour,
+            num_observations: db_entry.num_observations.max(0) as u32,
+            min_purchase_price: db_entry.min_purchase_price,
+            avg_purchase_price: db_entry.avg_purchase_price,
+            max_purchase_price: db_entry.max_purchase_price,
+            min_sell_price: db_entry.min_sell_price,
+            avg_sell_price: db_entry.avg_sell_price,
+            max_sell_price: db_entry.max_sell_price,
+            avg_trade_volume: db_entry.avg_trade_volume,
+            last_supply: db_entry.last_supply.0,
+            last_activity: db_entry.last_activity.map(|activity| activity.0),
+        })
+        .collect_vec())
+}
+
+/// Ships of agents that aren't active anymore (e.g. a previous registration under another symbol) are left behind in the table.
+/// The ships of the active agents stay, no matter how long they've been idle.
+pub(crate) async fn delete_superseded_ships(pool: &Pool<Postgres>, active_agent_symbols: &[AgentSymbol], older_than: DateTime<Utc>) -> Result<u64> {
+    let active_agent_symbols = active_agent_symbols
+        .iter()
+        .map(|agent_symbol| agent_symbol.0.clone())
+        .collect_vec();
+
+    let result = sqlx::query!(
+        r#"
+delete
+  from ships
+ where agent_symbol <> all($1::text[])
+   and updated_at < $2
+        "#,
+        &active_agent_symbols,
+        older_than
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Exhausted and expired surveys are kept as long as the yield analytics still need them for their usages.
+pub(crate) async fn delete_old_exhausted_surveys(pool: &Pool<Postgres>, agent_symbol: &AgentSymbol, older_than: DateTime<Utc>) -> Result<u64> {
+    let result = sqlx::query!(
+        r#"
+delete
+  from surveys s
+ where s.agent_symbol = $2
+   and s.created_at < $1
+   and (s.is_discarded or s.expires_at < $1)
+   and not exists (select 1
+                     from survey_usage_log l
+                    where l.survey_signature = s.signature
+                      and l.agent_symbol = s.agent_symbol
+                      and l.created_at >= $1)
+        "#,
+        older_than,
+        agent_symbol.0
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
 }
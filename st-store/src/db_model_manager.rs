@@ -1,17 +1,39 @@
 // In a new file: st_core/src/model_manager.rs or similar
+use crate::Ctx;
+use anyhow::anyhow;
 use sqlx::{Pool, Postgres};
+use st_domain::AgentSymbol;
 
 #[derive(Clone, Debug)]
 pub struct DbModelManager {
     pool: Pool<Postgres>,
+    agent_symbol: Option<AgentSymbol>,
 }
 
 impl DbModelManager {
     pub fn new(pool: Pool<Postgres>) -> Self {
-        Self { pool }
+        Self { pool, agent_symbol: None }
+    }
+
+    /// The model manager of a running agent - anonymous calls are made on behalf of this agent.
+    pub fn for_agent(pool: Pool<Postgres>, agent_symbol: AgentSymbol) -> Self {
+        Self {
+            pool,
+            agent_symbol: Some(agent_symbol),
+        }
     }
 
     pub fn pool(&self) -> &Pool<Postgres> {
         &self.pool
     }
+
+    pub fn agent_symbol(&self, ctx: &Ctx) -> anyhow::Result<AgentSymbol> {
+        match ctx {
+            Ctx::Agent(agent_symbol) => Ok(agent_symbol.clone()),
+            Ctx::Anonymous => self
+                .agent_symbol
+                .clone()
+                .ok_or_else(|| anyhow!("Anonymous ctx, but the bmc hasn't been created for an agent")),
+        }
+    }
 }
@@ -65,7 +65,8 @@ pub struct DbFleetBmc {
 
 #[async_trait]
 impl FleetBmcTrait for DbFleetBmc {
-    async fn load_fleet_tasks(&self, _ctx: &Ctx) -> Result<HashMap<FleetId, Vec<FleetTask>>> {
+    async fn load_fleet_tasks(&self, ctx: &Ctx) -> Result<HashMap<FleetId, Vec<FleetTask>>> {
+        let agent_symbol = self.mm.agent_symbol(ctx)?;
         /*
         fleet_id: Json<FleetId>
         fleet_task: Json<FleetTask>
@@ -76,7 +77,9 @@ impl FleetBmcTrait for DbFleetBmc {
 SELECT fleet_id
      , tasks as "tasks: Json<Vec<FleetTask>>"
   from fleet_task_assignments
+ where agent_symbol = $1
         "#,
+            agent_symbol.0
         )
         .fetch_all(self.mm.pool())
         .await?;
@@ -87,14 +90,17 @@ SELECT fleet_id
             .collect())
     }
 
-    async fn load_ship_fleet_assignment(&self, _ctx: &Ctx) -> Result<HashMap<ShipSymbol, FleetId>> {
+    async fn load_ship_fleet_assignment(&self, ctx: &Ctx) -> Result<HashMap<ShipSymbol, FleetId>> {
+        let agent_symbol = self.mm.agent_symbol(ctx)?;
         let assignment_entries: Vec<DbShipFleetAssignment> = sqlx::query_as!(
             DbShipFleetAssignment,
             r#"
 SELECT fleet_id
      , ship_symbol
   from fleet_ship_assignment
+ where agent_symbol = $1
         "#,
+            agent_symbol.0
         )
         .fetch_all(self.mm.pool())
         .await?;
@@ -105,14 +111,17 @@ SELECT fleet_id
             .collect())
     }
 
-    async fn load_fleets(&self, _ctx: &Ctx) -> Result<Vec<Fleet>> {
+    async fn load_fleets(&self, ctx: &Ctx) -> Result<Vec<Fleet>> {
+        let agent_symbol = self.mm.agent_symbol(ctx)?;
         let fleet_entries: Vec<DbFleetEntry> = sqlx::query_as!(
             DbFleetEntry,
             r#"
 SELECT id
      , cfg as "cfg: Json<FleetConfig>"
   from fleets
+ where agent_symbol = $1
   "#,
+            agent_symbol.0
         )
         .fetch_all(self.mm.pool())
         .await?;
@@ -126,14 +135,17 @@ SELECT id
             .collect_vec())
     }
 
-    async fn load_completed_fleet_tasks(&self, _ctx: &Ctx) -> Result<Vec<FleetTaskCompletion>> {
+    async fn load_completed_fleet_tasks(&self, ctx: &Ctx) -> Result<Vec<FleetTaskCompletion>> {
+        let agent_symbol = self.mm.agent_symbol(ctx)?;
         let completed_tasks: Vec<DbFleetTaskCompletion> = sqlx::query_as!(
             DbFleetTaskCompletion,
             r#"
 SELECT task as "task: Json<FleetTask>"
      , completed_at
   from completed_fleet_tasks
+ where agent_symbol = $1
         "#,
+            agent_symbol.0
         )
         .fetch_all(self.mm.pool())
         .await?;
@@ -147,14 +159,16 @@ SELECT task as "task: Json<FleetTask>"
             .collect_vec())
     }
 
-    async fn save_completed_fleet_task(&self, _ctx: &Ctx, task: &FleetTaskCompletion) -> Result<()> {
+    async fn save_completed_fleet_task(&self, ctx: &Ctx, task: &FleetTaskCompletion) -> Result<()> {
+        let agent_symbol = self.mm.agent_symbol(ctx)?;
         sqlx::query!(
             r#"
-insert into completed_fleet_tasks (task, completed_at)
-values ($1, $2)
+insert into completed_fleet_tasks (task, completed_at, agent_symbol)
+values ($1, $2, $3)
         "#,
             Json(task.task.clone()) as _,
-            task.completed_at
+            task.completed_at,
+            agent_symbol.0
         )
         .execute(self.mm.pool())
         .await?;
@@ -183,18 +197,20 @@ values ($1, $2)
     //             .collect_vec())
     //     }
 
-    async fn upsert_fleets(&self, _ctx: &Ctx, fleets: &HashMap<FleetId, Fleet>) -> Result<()> {
+    async fn upsert_fleets(&self, ctx: &Ctx, fleets: &HashMap<FleetId, Fleet>) -> Result<()> {
+        let agent_symbol = self.mm.agent_symbol(ctx)?;
         //TODO: upsert all at once (prob. json array magic)
 
         for (fleet_id, fleet) in fleets {
             sqlx::query!(
                 r#"
-insert into fleets(id, cfg)
-values ($1, $2)
-on conflict (id) do update SET cfg = excluded.cfg
+insert into fleets(id, cfg, agent_symbol)
+values ($1, $2, $3)
+on conflict (agent_symbol, id) do update SET cfg = excluded.cfg
 "#,
                 fleet_id.0,
                 Json(fleet.cfg.clone()) as _,
+                agent_symbol.0,
             )
             .execute(self.mm.pool())
             .await?;
@@ -203,7 +219,8 @@ on conflict (id) do update SET cfg = excluded.cfg
         Ok(())
     }
 
-    async fn upsert_fleet_tasks(&self, _ctx: &Ctx, fleet_tasks: &HashMap<FleetId, Vec<FleetTask>>) -> Result<()> {
+    async fn upsert_fleet_tasks(&self, ctx: &Ctx, fleet_tasks: &HashMap<FleetId, Vec<FleetTask>>) -> Result<()> {
+        let agent_symbol = self.mm.agent_symbol(ctx)?;
         //TODO: upsert all at once (prob. json array magic)
 
         //a fleet can (currently only have one task)
@@ -211,12 +228,13 @@ on conflict (id) do update SET cfg = excluded.cfg
         for (fleet_id, fleet_tasks) in fleet_tasks {
             sqlx::query!(
                 r#"
-insert into fleet_task_assignments(fleet_id, tasks)
-values ($1, $2)
-on conflict (fleet_id) do update SET tasks = excluded.tasks
+insert into fleet_task_assignments(fleet_id, tasks, agent_symbol)
+values ($1, $2, $3)
+on conflict (agent_symbol, fleet_id) do update SET tasks = excluded.tasks
 "#,
                 fleet_id.0,
                 Json(fleet_tasks.clone()) as _,
+                agent_symbol.0,
             )
             .execute(self.mm.pool())
             .await?;
@@ -225,18 +243,20 @@ on conflict (fleet_id) do update SET tasks = excluded.tasks
         Ok(())
     }
 
-    async fn upsert_ship_fleet_assignment(&self, _ctx: &Ctx, ship_fleet_assignment: &HashMap<ShipSymbol, FleetId>) -> Result<()> {
+    async fn upsert_ship_fleet_assignment(&self, ctx: &Ctx, ship_fleet_assignment: &HashMap<ShipSymbol, FleetId>) -> Result<()> {
+        let agent_symbol = self.mm.agent_symbol(ctx)?;
         //TODO: upsert all at once (prob. json array magic)
 
         for (ship_symbol, fleet_id) in ship_fleet_assignment {
             sqlx::query!(
                 r#"
-insert into fleet_ship_assignment(ship_symbol, fleet_id)
-values ($1, $2)
+insert into fleet_ship_assignment(ship_symbol, fleet_id, agent_symbol)
+values ($1, $2, $3)
 on conflict (ship_symbol) do update SET fleet_id = excluded.fleet_id
 "#,
                 ship_symbol.0,
                 fleet_id.0,
+                agent_symbol.0,
             )
             .execute(self.mm.pool())
             .await?;
@@ -245,13 +265,16 @@ on conflict (ship_symbol) do update SET fleet_id = excluded.fleet_id
         Ok(())
     }
 
-    async fn delete_fleet_ship_assignments_for_fleet(&self, _ctx: &Ctx, fleet_id: &FleetId) -> Result<()> {
+    async fn delete_fleet_ship_assignments_for_fleet(&self, ctx: &Ctx, fleet_id: &FleetId) -> Result<()> {
+        let agent_symbol = self.mm.agent_symbol(ctx)?;
         sqlx::query!(
             r#"
 delete from fleet_ship_assignment
 where fleet_id = $1
+  and agent_symbol = $2
 "#,
             fleet_id.0,
+            agent_symbol.0,
         )
         .execute(self.mm.pool())
         .await?;
@@ -259,13 +282,16 @@ where fleet_id = $1
         Ok(())
     }
 
-    async fn delete_fleet_task_assignments_for_fleet(&self, _ctx: &Ctx, fleet_id: &FleetId) -> Result<()> {
+    async fn delete_fleet_task_assignments_for_fleet(&self, ctx: &Ctx, fleet_id: &FleetId) -> Result<()> {
+        let agent_symbol = self.mm.agent_symbol(ctx)?;
         sqlx::query!(
             r#"
 delete from fleet_task_assignments
 where fleet_id = $1
+  and agent_symbol = $2
 "#,
             fleet_id.0,
+            agent_symbol.0,
         )
         .execute(self.mm.pool())
         .await?;
@@ -273,13 +299,16 @@ where fleet_id = $1
         Ok(())
     }
 
-    async fn delete_fleet(&self, _ctx: &Ctx, fleet_id: &FleetId) -> Result<()> {
+    async fn delete_fleet(&self, ctx: &Ctx, fleet_id: &FleetId) -> Result<()> {
+        let agent_symbol = self.mm.agent_symbol(ctx)?;
         sqlx::query!(
             r#"
 delete from fleets
 where id = $1
+  and agent_symbol = $2
 "#,
             fleet_id.0,
+            agent_symbol.0,
         )
         .execute(self.mm.pool())
         .await?;
@@ -3,6 +3,7 @@ use async_trait::async_trait;
 use chrono::Utc;
 use itertools::Itertools;
 use mockall::automock;
+use st_domain::budgeting::ledger_audit::{LedgerAuditReport, TimestampedLedgerEntry};
 use st_domain::budgeting::treasury_redesign::LedgerEntry;
 use std::collections::VecDeque;
 use std::fmt::Debug;
@@ -14,6 +15,9 @@ use tokio::sync::Mutex;
 pub trait LedgerBmcTrait: Send + Sync + Debug {
     async fn archive_ledger_entry(&self, _ctx: &Ctx, ledger_entry: &LedgerEntry) -> anyhow::Result<()>;
     async fn get_ledger_entries_in_order(&self, _ctx: &Ctx) -> anyhow::Result<Vec<LedgerEntry>>;
+    async fn get_timestamped_ledger_entries_in_order(&self, _ctx: &Ctx) -> anyhow::Result<Vec<TimestampedLedgerEntry>>;
+    async fn save_ledger_audit_report(&self, _ctx: &Ctx, report: &LedgerAuditReport) -> anyhow::Result<()>;
+    async fn get_latest_ledger_audit_report(&self, _ctx: &Ctx) -> anyhow::Result<Option<LedgerAuditReport>>;
 }
 
 #[derive(Debug)]
@@ -23,27 +27,45 @@ pub struct DbLedgerBmc {
 
 #[async_trait]
 impl LedgerBmcTrait for DbLedgerBmc {
-    async fn archive_ledger_entry(&self, _ctx: &Ctx, ledger_entry: &LedgerEntry) -> anyhow::Result<()> {
-        db::archive_ledger_entry(self.mm.pool(), ledger_entry, Utc::now()).await?;
+    async fn archive_ledger_entry(&self, ctx: &Ctx, ledger_entry: &LedgerEntry) -> anyhow::Result<()> {
+        db::archive_ledger_entry(self.mm.pool(), &self.mm.agent_symbol(ctx)?, ledger_entry, Utc::now()).await?;
 
         Ok(())
     }
 
-    async fn get_ledger_entries_in_order(&self, _ctx: &Ctx) -> anyhow::Result<Vec<LedgerEntry>> {
-        let entries = db::get_ledger_entries_in_order(self.mm.pool(), Utc::now()).await?;
+    async fn get_ledger_entries_in_order(&self, ctx: &Ctx) -> anyhow::Result<Vec<LedgerEntry>> {
+        let entries = db::get_ledger_entries_in_order(self.mm.pool(), &self.mm.agent_symbol(ctx)?, Utc::now()).await?;
+
+        Ok(entries)
+    }
+
+    async fn get_timestamped_ledger_entries_in_order(&self, ctx: &Ctx) -> anyhow::Result<Vec<TimestampedLedgerEntry>> {
+        let entries = db::get_timestamped_ledger_entries_in_order(self.mm.pool(), &self.mm.agent_symbol(ctx)?, Utc::now()).await?;
 
         Ok(entries)
     }
+
+    async fn save_ledger_audit_report(&self, ctx: &Ctx, report: &LedgerAuditReport) -> anyhow::Result<()> {
+        db::insert_ledger_audit_report(self.mm.pool(), &self.mm.agent_symbol(ctx)?, report).await
+    }
+
+    async fn get_latest_ledger_audit_report(&self, ctx: &Ctx) -> anyhow::Result<Option<LedgerAuditReport>> {
+        db::load_latest_ledger_audit_report(self.mm.pool(), &self.mm.agent_symbol(ctx)?).await
+    }
 }
 
 #[derive(Debug)]
 pub struct InMemoryLedger {
-    archived: VecDeque<LedgerEntry>,
+    archived: VecDeque<TimestampedLedgerEntry>,
+    audit_reports: Vec<LedgerAuditReport>,
 }
 
 impl InMemoryLedger {
     fn new() -> Self {
-        Self { archived: Default::default() }
+        Self {
+            archived: Default::default(),
+            audit_reports: Default::default(),
+        }
     }
 }
 
@@ -70,13 +92,33 @@ impl Default for InMemoryLedgerBmc {
 impl LedgerBmcTrait for InMemoryLedgerBmc {
     async fn archive_ledger_entry(&self, _ctx: &Ctx, ledger_entry: &LedgerEntry) -> anyhow::Result<()> {
         let mut guard = self.in_memory_ledger.lock().await;
-        guard.archived.push_back(ledger_entry.clone());
+        guard.archived.push_back(TimestampedLedgerEntry {
+            entry: ledger_entry.clone(),
+            created_at: Utc::now(),
+        });
 
         Ok(())
     }
 
     async fn get_ledger_entries_in_order(&self, _ctx: &Ctx) -> anyhow::Result<Vec<LedgerEntry>> {
+        let guard = self.in_memory_ledger.lock().await;
+        Ok(guard.archived.iter().map(|e| e.entry.clone()).collect_vec())
+    }
+
+    async fn get_timestamped_ledger_entries_in_order(&self, _ctx: &Ctx) -> anyhow::Result<Vec<TimestampedLedgerEntry>> {
         let guard = self.in_memory_ledger.lock().await;
         Ok(guard.archived.iter().cloned().collect_vec())
     }
+
+    async fn save_ledger_audit_report(&self, _ctx: &Ctx, report: &LedgerAuditReport) -> anyhow::Result<()> {
+        let mut guard = self.in_memory_ledger.lock().await;
+        guard.audit_reports.push(report.clone());
+
+        Ok(())
+    }
+
+    async fn get_latest_ledger_audit_report(&self, _ctx: &Ctx) -> anyhow::Result<Option<LedgerAuditReport>> {
+        let guard = self.in_memory_ledger.lock().await;
+        Ok(guard.audit_reports.last().cloned())
+    }
 }
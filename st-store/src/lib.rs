@@ -1,5 +1,6 @@
 pub mod agent_bmc;
 pub mod bmc;
+pub mod cargo_flow_bmc;
 pub mod construction_bmc;
 pub mod ctx;
 pub mod db;
@@ -6,7 +6,8 @@ use chrono::{DateTime, Utc};
 use itertools::Itertools;
 use mockall::automock;
 use sqlx::types::Json;
-use st_domain::{MarketData, MarketEntry, SystemSymbol, WaypointSymbol};
+use st_domain::market_history::{aggregate_hourly, split_off_downsampled_observations, MarketHourlyAggregate};
+use st_domain::{MarketData, MarketEntry, SystemSymbol, TradeGoodSymbol, WaypointSymbol};
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::sync::Arc;
@@ -22,6 +23,19 @@ pub struct DbMarketBmc {
 pub trait MarketBmcTrait: Send + Sync + Debug {
     async fn get_latest_market_data_for_system(&self, ctx: &Ctx, system_symbol: &SystemSymbol) -> Result<Vec<MarketEntry>>;
     async fn save_market_data(&self, ctx: &Ctx, market_entries: Vec<MarketData>, now: DateTime<Utc>) -> Result<()>;
+    /// all observations of the market since the given timestamp, oldest first
+    async fn get_market_history_of_waypoint(&self, ctx: &Ctx, waypoint_symbol: &WaypointSymbol, since: DateTime<Utc>) -> Result<Vec<MarketEntry>>;
+    /// all observations of the markets of the system since the given timestamp, oldest first
+    async fn get_market_history_of_system(&self, ctx: &Ctx, system_symbol: &SystemSymbol, since: DateTime<Utc>) -> Result<Vec<MarketEntry>>;
+    /// moves the observations older than the given timestamp into hourly aggregates - returns the number of downsampled observations
+    async fn downsample_market_history(&self, ctx: &Ctx, older_than: DateTime<Utc>) -> Result<u64>;
+    /// the hourly aggregates of the market since the given timestamp, oldest first
+    async fn get_hourly_market_aggregates_of_waypoint(
+        &self,
+        ctx: &Ctx,
+        waypoint_symbol: &WaypointSymbol,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<MarketHourlyAggregate>>;
 }
 
 #[async_trait]
@@ -61,11 +75,88 @@ ORDER BY waypoint_symbol, created_at DESC
     async fn save_market_data(&self, _ctx: &Ctx, market_entries: Vec<MarketData>, now: DateTime<Utc>) -> Result<()> {
         db::insert_market_data(self.mm.pool(), market_entries, now).await
     }
+
+    async fn get_market_history_of_waypoint(&self, _ctx: &Ctx, waypoint_symbol: &WaypointSymbol, since: DateTime<Utc>) -> Result<Vec<MarketEntry>> {
+        let market_entries: Vec<DbMarketEntry> = sqlx::query_as!(
+            DbMarketEntry,
+            r#"
+select waypoint_symbol
+     , entry as "entry: Json<MarketData>"
+     , created_at
+  from markets
+ where waypoint_symbol = $1
+   and created_at >= $2
+order by created_at
+        "#,
+            waypoint_symbol.0,
+            since
+        )
+        .fetch_all(self.mm.pool())
+        .await?;
+
+        let result = market_entries
+            .into_iter()
+            .map(|db_entry| MarketEntry {
+                waypoint_symbol: WaypointSymbol(db_entry.waypoint_symbol.clone()),
+                market_data: db_entry.entry.0,
+                created_at: db_entry.created_at,
+            })
+            .collect_vec();
+
+        Ok(result)
+    }
+
+    async fn get_market_history_of_system(&self, _ctx: &Ctx, system_symbol: &SystemSymbol, since: DateTime<Utc>) -> Result<Vec<MarketEntry>> {
+        let waypoint_symbol_pattern = format!("{}%", system_symbol.0);
+
+        let market_entries: Vec<DbMarketEntry> = sqlx::query_as!(
+            DbMarketEntry,
+            r#"
+select waypoint_symbol
+     , entry as "entry: Json<MarketData>"
+     , created_at
+  from markets
+ where waypoint_symbol like $1
+   and created_at >= $2
+order by created_at
+        "#,
+            waypoint_symbol_pattern,
+            since
+        )
+        .fetch_all(self.mm.pool())
+        .await?;
+
+        let result = market_entries
+            .into_iter()
+            .map(|db_entry| MarketEntry {
+                waypoint_symbol: WaypointSymbol(db_entry.waypoint_symbol.clone()),
+                market_data: db_entry.entry.0,
+                created_at: db_entry.created_at,
+            })
+            .collect_vec();
+
+        Ok(result)
+    }
+
+    async fn downsample_market_history(&self, _ctx: &Ctx, older_than: DateTime<Utc>) -> Result<u64> {
+        db::downsample_market_observations(self.mm.pool(), older_than).await
+    }
+
+    async fn get_hourly_market_aggregates_of_waypoint(
+        &self,
+        _ctx: &Ctx,
+        waypoint_symbol: &WaypointSymbol,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<MarketHourlyAggregate>> {
+        db::select_market_hourly_aggregates_of_waypoint(self.mm.pool(), waypoint_symbol, since).await
+    }
 }
 
 #[derive(Debug)]
 pub struct InMemoryMarket {
     latest_market_data: HashMap<SystemSymbol, HashMap<WaypointSymbol, MarketEntry>>,
+    market_history: HashMap<WaypointSymbol, Vec<MarketEntry>>,
+    hourly_aggregates: HashMap<(WaypointSymbol, TradeGoodSymbol, DateTime<Utc>), MarketHourlyAggregate>,
 }
 
 #[derive(Debug)]
@@ -93,21 +184,99 @@ impl MarketBmcTrait for InMemoryMarketBmc {
         let mut guard = self.in_memory_market.write().await;
 
         for me in market_entries {
+            let market_entry = MarketEntry {
+                waypoint_symbol: me.symbol.clone(),
+                market_data: me.clone(),
+                created_at: now,
+            };
+            guard
+                .market_history
+                .entry(me.symbol.clone())
+                .or_default()
+                .push(market_entry.clone());
             guard
                 .latest_market_data
                 .entry(me.symbol.system_symbol())
                 .or_default()
-                .insert(
-                    me.symbol.clone(),
-                    MarketEntry {
-                        waypoint_symbol: me.symbol.clone(),
-                        market_data: me.clone(),
-                        created_at: now,
-                    },
-                );
+                .insert(me.symbol.clone(), market_entry);
         }
         Ok(())
     }
+
+    async fn get_market_history_of_waypoint(&self, _ctx: &Ctx, waypoint_symbol: &WaypointSymbol, since: DateTime<Utc>) -> Result<Vec<MarketEntry>> {
+        Ok(self
+            .in_memory_market
+            .read()
+            .await
+            .market_history
+            .get(waypoint_symbol)
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|me| me.created_at >= since)
+            .sorted_by_key(|me| me.created_at)
+            .collect_vec())
+    }
+
+    async fn get_market_history_of_system(&self, _ctx: &Ctx, system_symbol: &SystemSymbol, since: DateTime<Utc>) -> Result<Vec<MarketEntry>> {
+        Ok(self
+            .in_memory_market
+            .read()
+            .await
+            .market_history
+            .iter()
+            .filter(|(wps, _)| &wps.system_symbol() == system_symbol)
+            .flat_map(|(_, history)| history.iter())
+            .filter(|me| me.created_at >= since)
+            .cloned()
+            .sorted_by_key(|me| me.created_at)
+            .collect_vec())
+    }
+
+    async fn downsample_market_history(&self, _ctx: &Ctx, older_than: DateTime<Utc>) -> Result<u64> {
+        let mut guard = self.in_memory_market.write().await;
+
+        let mut num_downsampled = 0;
+        for wps in guard.market_history.keys().cloned().collect_vec() {
+            let history = guard.market_history.remove(&wps).unwrap_or_default();
+            let (kept, downsampled) = split_off_downsampled_observations(history, older_than);
+            guard.market_history.insert(wps, kept);
+            num_downsampled += downsampled.len() as u64;
+
+            for aggregate in aggregate_hourly(&downsampled) {
+                let key = (aggregate.waypoint_symbol.clone(), aggregate.trade_good_symbol.clone(), aggregate.hour);
+                let merged = match guard.hourly_aggregates.remove(&key) {
+                    Some(existing) => existing.merge(aggregate),
+                    None => aggregate,
+                };
+                guard.hourly_aggregates.insert(key, merged);
+            }
+        }
+
+        Ok(num_downsampled)
+    }
+
+    async fn get_hourly_market_aggregates_of_waypoint(
+        &self,
+        _ctx: &Ctx,
+        waypoint_symbol: &WaypointSymbol,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<MarketHourlyAggregate>> {
+        Ok(self
+            .in_memory_market
+            .read()
+            .await
+            .hourly_aggregates
+            .values()
+            .filter(|aggregate| &aggregate.waypoint_symbol == waypoint_symbol && aggregate.hour >= since)
+            .cloned()
+            .sorted_by(|a, b| {
+                a.hour
+                    .cmp(&b.hour)
+                    .then(a.trade_good_symbol.cmp(&b.trade_good_symbol))
+            })
+            .collect_vec())
+    }
 }
 
 impl Default for InMemoryMarketBmc {
@@ -121,6 +290,8 @@ impl InMemoryMarketBmc {
         Self {
             in_memory_market: Arc::new(RwLock::new(InMemoryMarket {
                 latest_market_data: Default::default(),
+                market_history: Default::default(),
+                hourly_aggregates: Default::default(),
             })),
         }
     }
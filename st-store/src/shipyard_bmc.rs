@@ -5,6 +5,7 @@ use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use itertools::Itertools;
 use mockall::automock;
+use st_domain::strategy::default_ship_price_guesses;
 use st_domain::{ShipPriceInfo, Shipyard, ShipyardData, SystemSymbol, WaypointSymbol};
 use std::collections::HashMap;
 use std::fmt::Debug;
@@ -133,5 +134,6 @@ fn extract_ship_price_infos_from_shipyards(result: &[ShipyardData]) -> ShipPrice
     ShipPriceInfo {
         price_infos,
         latest_shipyard_infos: result.iter().cloned().collect_vec(),
+        ship_price_guesses: default_ship_price_guesses(),
     }
 }
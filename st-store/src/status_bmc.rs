@@ -2,6 +2,7 @@ use crate::ctx::Ctx;
 use crate::DbModelManager;
 use anyhow::*;
 use async_trait::async_trait;
+use st_domain::galaxy_crawl::GalaxyCrawlProgress;
 use st_domain::StStatusResponse;
 use std::fmt::Debug;
 use std::sync::Arc;
@@ -12,6 +13,8 @@ pub struct StatusBmc;
 #[async_trait]
 pub trait StatusBmcTrait: Send + Sync + Debug {
     async fn get_status(&self, ctx: &Ctx) -> Result<Option<StStatusResponse>>;
+    async fn get_galaxy_crawl_progress(&self, ctx: &Ctx) -> Result<Option<GalaxyCrawlProgress>>;
+    async fn save_galaxy_crawl_progress(&self, ctx: &Ctx, progress: &GalaxyCrawlProgress) -> Result<()>;
 }
 
 #[derive(Debug)]
@@ -26,11 +29,20 @@ impl StatusBmcTrait for DbStatusBmc {
             .await?
             .map(|db_status| db_status.entry.0))
     }
+
+    async fn get_galaxy_crawl_progress(&self, _ctx: &Ctx) -> Result<Option<GalaxyCrawlProgress>> {
+        crate::db::load_galaxy_crawl_progress(self.mm.pool()).await
+    }
+
+    async fn save_galaxy_crawl_progress(&self, _ctx: &Ctx, progress: &GalaxyCrawlProgress) -> Result<()> {
+        crate::db::upsert_galaxy_crawl_progress(self.mm.pool(), progress).await
+    }
 }
 
 #[derive(Debug)]
 pub struct InMemoryStatus {
     status_response: Option<StStatusResponse>,
+    galaxy_crawl_progress: Option<GalaxyCrawlProgress>,
 }
 
 #[derive(Debug)]
@@ -47,7 +59,10 @@ impl Default for InMemoryStatusBmc {
 impl InMemoryStatusBmc {
     pub fn new() -> Self {
         Self {
-            in_memory_status: Arc::new(RwLock::new(InMemoryStatus { status_response: None })),
+            in_memory_status: Arc::new(RwLock::new(InMemoryStatus {
+                status_response: None,
+                galaxy_crawl_progress: None,
+            })),
         }
     }
 }
@@ -57,4 +72,18 @@ impl StatusBmcTrait for InMemoryStatusBmc {
     async fn get_status(&self, _ctx: &Ctx) -> Result<Option<StStatusResponse>> {
         Ok(self.in_memory_status.read().await.status_response.clone())
     }
+
+    async fn get_galaxy_crawl_progress(&self, _ctx: &Ctx) -> Result<Option<GalaxyCrawlProgress>> {
+        Ok(self
+            .in_memory_status
+            .read()
+            .await
+            .galaxy_crawl_progress
+            .clone())
+    }
+
+    async fn save_galaxy_crawl_progress(&self, _ctx: &Ctx, progress: &GalaxyCrawlProgress) -> Result<()> {
+        self.in_memory_status.write().await.galaxy_crawl_progress = Some(progress.clone());
+        Ok(())
+    }
 }
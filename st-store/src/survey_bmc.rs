@@ -5,8 +5,9 @@ use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use itertools::Itertools;
 use mockall::automock;
+use st_domain::extraction_analytics::SurveyUsage;
 use st_domain::{Extraction, Survey, SurveySignature, WaypointSymbol};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -23,24 +24,42 @@ pub trait SurveyBmcTrait: Send + Sync + Debug {
     async fn get_all_valid_surveys_for_waypoint(&self, ctx: &Ctx, waypoint_symbol: &WaypointSymbol) -> Result<Vec<Survey>>;
     async fn mark_survey_as_exhausted(&self, ctx: &Ctx, waypoint_symbol: &WaypointSymbol, survey_signature: &SurveySignature) -> Result<()>;
     async fn log_survey_usage(&self, ctx: &Ctx, survey_signature: &SurveySignature, extraction: &Extraction) -> Result<()>;
+    async fn get_survey_usages_since(&self, ctx: &Ctx, since: DateTime<Utc>) -> Result<Vec<SurveyUsage>>;
+    /// removes the exhausted and expired surveys that haven't been used since the given timestamp - returns the number of removed surveys
+    async fn prune_exhausted_surveys(&self, ctx: &Ctx, older_than: DateTime<Utc>) -> Result<u64>;
 }
 
 #[async_trait]
 impl SurveyBmcTrait for DbSurveyBmc {
-    async fn save_surveys(&self, _ctx: &Ctx, surveys: Vec<Survey>) -> Result<()> {
-        db::upsert_surveys(self.mm.pool(), surveys, Utc::now()).await
+    async fn save_surveys(&self, ctx: &Ctx, surveys: Vec<Survey>) -> Result<()> {
+        db::upsert_surveys(self.mm.pool(), &self.mm.agent_symbol(ctx)?, surveys, Utc::now()).await
     }
 
-    async fn get_all_valid_surveys_for_waypoint(&self, _ctx: &Ctx, waypoint_symbol: &WaypointSymbol) -> Result<Vec<Survey>> {
-        db::get_valid_surveys_for_waypoint(self.mm.pool(), waypoint_symbol.clone(), Utc::now()).await
+    async fn get_all_valid_surveys_for_waypoint(&self, ctx: &Ctx, waypoint_symbol: &WaypointSymbol) -> Result<Vec<Survey>> {
+        db::get_valid_surveys_for_waypoint(self.mm.pool(), &self.mm.agent_symbol(ctx)?, waypoint_symbol.clone(), Utc::now()).await
     }
 
-    async fn mark_survey_as_exhausted(&self, _ctx: &Ctx, _waypoint_symbol: &WaypointSymbol, survey_signature: &SurveySignature) -> Result<()> {
-        db::mark_survey_as_exhausted(self.mm.pool(), survey_signature.clone()).await
+    async fn mark_survey_as_exhausted(&self, ctx: &Ctx, _waypoint_symbol: &WaypointSymbol, survey_signature: &SurveySignature) -> Result<()> {
+        db::mark_survey_as_exhausted(self.mm.pool(), &self.mm.agent_symbol(ctx)?, survey_signature.clone()).await
     }
 
-    async fn log_survey_usage(&self, _ctx: &Ctx, survey_signature: &SurveySignature, extraction: &Extraction) -> Result<()> {
-        db::insert_survey_usage(self.mm.pool(), survey_signature.clone(), extraction.clone(), Utc::now()).await
+    async fn log_survey_usage(&self, ctx: &Ctx, survey_signature: &SurveySignature, extraction: &Extraction) -> Result<()> {
+        db::insert_survey_usage(
+            self.mm.pool(),
+            &self.mm.agent_symbol(ctx)?,
+            survey_signature.clone(),
+            extraction.clone(),
+            Utc::now(),
+        )
+        .await
+    }
+
+    async fn get_survey_usages_since(&self, ctx: &Ctx, since: DateTime<Utc>) -> Result<Vec<SurveyUsage>> {
+        db::select_survey_usages_since(self.mm.pool(), &self.mm.agent_symbol(ctx)?, since).await
+    }
+
+    async fn prune_exhausted_surveys(&self, ctx: &Ctx, older_than: DateTime<Utc>) -> Result<u64> {
+        db::delete_old_exhausted_surveys(self.mm.pool(), &self.mm.agent_symbol(ctx)?, older_than).await
     }
 }
 
@@ -48,6 +67,8 @@ impl SurveyBmcTrait for DbSurveyBmc {
 pub struct InMemorySurveys {
     surveys: HashMap<WaypointSymbol, HashMap<SurveySignature, Survey>>,
     survey_usages: Vec<(SurveySignature, Extraction, DateTime<Utc>)>,
+    // surveys get cleaned up once they expire - we keep the used ones for the yield analytics
+    used_surveys: HashMap<SurveySignature, Survey>,
 }
 
 impl Default for InMemorySurveys {
@@ -61,6 +82,7 @@ impl InMemorySurveys {
         Self {
             surveys: Default::default(),
             survey_usages: Default::default(),
+            used_surveys: Default::default(),
         }
     }
 }
@@ -136,10 +158,65 @@ impl SurveyBmcTrait for InMemorySurveyBmc {
     async fn log_survey_usage(&self, _ctx: &Ctx, survey_signature: &SurveySignature, extraction: &Extraction) -> Result<()> {
         let mut in_memory_surveys = self.in_memory_surveys.write().await;
 
+        let maybe_survey = in_memory_surveys
+            .surveys
+            .values()
+            .find_map(|surveys_at_wp| surveys_at_wp.get(survey_signature))
+            .cloned();
+        if let Some(survey) = maybe_survey {
+            in_memory_surveys
+                .used_surveys
+                .insert(survey_signature.clone(), survey);
+        }
+
         in_memory_surveys
             .survey_usages
             .push((survey_signature.clone(), extraction.clone(), Utc::now()));
 
         Ok(())
     }
+
+    async fn get_survey_usages_since(&self, _ctx: &Ctx, since: DateTime<Utc>) -> Result<Vec<SurveyUsage>> {
+        let in_memory_surveys = self.in_memory_surveys.read().await;
+
+        Ok(in_memory_surveys
+            .survey_usages
+            .iter()
+            .filter(|(_, _, created_at)| *created_at >= since)
+            .filter_map(|(signature, extraction, created_at)| {
+                in_memory_surveys
+                    .used_surveys
+                    .get(signature)
+                    .map(|survey| SurveyUsage {
+                        survey: survey.clone(),
+                        extraction: extraction.clone(),
+                        created_at: *created_at,
+                    })
+            })
+            .collect_vec())
+    }
+    async fn prune_exhausted_surveys(&self, _ctx: &Ctx, older_than: DateTime<Utc>) -> Result<u64> {
+        let mut in_memory_surveys = self.in_memory_surveys.write().await;
+
+        let mut num_removed = 0;
+        for surveys_at_wp in in_memory_surveys.surveys.values_mut() {
+            let num_surveys = surveys_at_wp.len();
+            surveys_at_wp.retain(|_signature, survey| survey.expiration >= older_than);
+            num_removed += (num_surveys - surveys_at_wp.len()) as u64;
+        }
+
+        let recently_used_signatures = in_memory_surveys
+            .survey_usages
+            .iter()
+            .filter(|(_, _, created_at)| *created_at >= older_than)
+            .map(|(signature, _, _)| signature.clone())
+            .collect::<HashSet<_>>();
+        let num_used_surveys = in_memory_surveys.used_surveys.len();
+        in_memory_surveys
+            .used_surveys
+            .retain(|signature, _survey| recently_used_signatures.contains(signature));
+        num_removed += (num_used_surveys - in_memory_surveys.used_surveys.len()) as u64;
+
+        Ok(num_removed)
+    }
 }
@@ -6,6 +6,7 @@ use chrono::Utc;
 use itertools::Itertools;
 use mockall::automock;
 use sqlx::types::Json;
+use st_domain::system_map::GalaxyMapSystem;
 use st_domain::{SystemSymbol, Waypoint, WaypointSymbol};
 use std::collections::HashMap;
 use std::fmt::Debug;
@@ -27,6 +28,7 @@ pub trait SystemBmcTrait: Send + Sync + Debug {
 
     async fn get_num_systems(&self, ctx: &Ctx) -> Result<i64>;
     async fn get_num_waypoints(&self, ctx: &Ctx) -> Result<i64>;
+    async fn get_galaxy_map_systems(&self, ctx: &Ctx) -> Result<Vec<GalaxyMapSystem>>;
 }
 
 #[async_trait]
@@ -92,6 +94,10 @@ where waypoints.waypoint_symbol = $1
             .map(|wp_entry| wp_entry.entry.0.clone())
             .ok_or(anyhow!("Waypoint {} not found", waypoint_symbol.0.clone()))
     }
+
+    async fn get_galaxy_map_systems(&self, _ctx: &Ctx) -> Result<Vec<GalaxyMapSystem>> {
+        db::select_galaxy_map_systems(self.mm.pool()).await
+    }
 }
 
 #[derive(Debug)]
@@ -195,4 +201,9 @@ impl SystemBmcTrait for InMemorySystemsBmc {
             .sum();
         Ok(result)
     }
+
+    async fn get_galaxy_map_systems(&self, _ctx: &Ctx) -> Result<Vec<GalaxyMapSystem>> {
+        // the in-memory agent only knows the waypoints of the systems it visits - not the systems themselves
+        Ok(vec![])
+    }
 }
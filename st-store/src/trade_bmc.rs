@@ -27,7 +27,8 @@ pub struct DbTradeBmc {
 
 #[async_trait]
 impl TradeBmcTrait for DbTradeBmc {
-    async fn get_ticket_by_id(&self, _ctx: &Ctx, ticket_id: TicketId) -> Result<FinanceTicket> {
+    async fn get_ticket_by_id(&self, ctx: &Ctx, ticket_id: TicketId) -> Result<FinanceTicket> {
+        let agent_symbol = self.mm.agent_symbol(ctx)?;
         let db_entry: DbFinanceTicket = sqlx::query_as!(
             DbFinanceTicket,
             r#"
@@ -35,8 +36,10 @@ select ship_symbol
      , entry as "entry: Json<FinanceTicket>"
   from trade_tickets
  where ticket_id = $1
+   and agent_symbol = $2
         "#,
             ticket_id.0,
+            agent_symbol.0,
         )
         .fetch_one(self.mm.pool())
         .await?;
@@ -44,12 +47,13 @@ select ship_symbol
         Ok(db_entry.entry.0)
     }
 
-    async fn upsert_ticket(&self, _ctx: &Ctx, ship_symbol: &ShipSymbol, ticket_id: &TicketId, trade_ticket: &FinanceTicket, is_complete: bool) -> Result<()> {
+    async fn upsert_ticket(&self, ctx: &Ctx, ship_symbol: &ShipSymbol, ticket_id: &TicketId, trade_ticket: &FinanceTicket, is_complete: bool) -> Result<()> {
+        let agent_symbol = self.mm.agent_symbol(ctx)?;
         let now = Utc::now();
         sqlx::query!(
             r#"
-insert into trade_tickets (ticket_id, ship_symbol, entry, created_at, updated_at, completed_at)
-values ($1, $2, $3, $4, $5, $6)
+insert into trade_tickets (ticket_id, ship_symbol, entry, created_at, updated_at, completed_at, agent_symbol)
+values ($1, $2, $3, $4, $5, $6, $7)
 on conflict (ticket_id) do update set entry = excluded.entry
                                     , updated_at = excluded.updated_at
                                     , completed_at = excluded.completed_at
@@ -60,6 +64,7 @@ on conflict (ticket_id) do update set entry = excluded.entry
             now,
             now,
             is_complete.then_some(now),
+            agent_symbol.0,
         )
         .execute(self.mm.pool())
         .await?;
@@ -67,7 +72,8 @@ on conflict (ticket_id) do update set entry = excluded.entry
         Ok(())
     }
 
-    async fn load_uncompleted_tickets(&self, _ctx: &Ctx) -> Result<HashMap<ShipSymbol, FinanceTicket>> {
+    async fn load_uncompleted_tickets(&self, ctx: &Ctx) -> Result<HashMap<ShipSymbol, FinanceTicket>> {
+        let agent_symbol = self.mm.agent_symbol(ctx)?;
         let entries: Vec<DbFinanceTicket> = sqlx::query_as!(
             DbFinanceTicket,
             r#"
@@ -75,7 +81,9 @@ select ship_symbol
      , entry as "entry: Json<FinanceTicket>"
   from trade_tickets
  where completed_at is null
+   and agent_symbol = $1
         "#,
+            agent_symbol.0
         )
         .fetch_all(self.mm.pool())
         .await?;